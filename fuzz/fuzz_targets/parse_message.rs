@@ -0,0 +1,31 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Fuzz the commit message parser with arbitrary input, as the commit-msg
+//! hook does. Parsing must not panic, and a parsed message must format to
+//! text that parses back to the same text.
+
+#![no_main]
+
+use ck::commit::CommitMessage;
+use ck::rules::RuleEngine;
+use ck::CkConfig;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = match std::str::from_utf8(data) {
+        Ok(input) => input,
+        Err(_) => return,
+    };
+
+    let parsed = match CommitMessage::parse(input) {
+        Ok(parsed) => parsed,
+        Err(_) => return,
+    };
+    let formatted = parsed.format();
+    let reparsed = CommitMessage::parse(&formatted).expect("formatted message parses");
+    assert_eq!(reparsed.format(), formatted);
+
+    // The rules see the same untrusted input in the hook
+    let _ = RuleEngine::new(CkConfig::default()).validate(&parsed);
+});
@@ -6,22 +6,34 @@
 use crate::config::{CkConfig, CommitType};
 use crate::error::Result;
 use crate::git::{self, DiffInfo, DiffStats};
-use std::path::PathBuf;
+use crate::monorepo::BuildPackage;
+use crate::progress::Progress;
+use std::path::{Path, PathBuf};
 
+use super::hints::find_hints;
 use super::inference::{infer_scope, infer_type};
-use super::warnings::{generate_warnings, Warnings};
+use super::related::{related_files, related_pathspecs};
+use super::scope_map::ScopeMap;
+use super::warnings::{generate_warnings, unstaged_warning, WarningCode, Warnings};
+
+/// Subject suggested for the first commit in a repository.
+pub const INITIAL_SUBJECT: &str = "initial commit";
 
 /// Complete context about the repository state for commit assistance.
 #[derive(Debug, Clone)]
 pub struct RepositoryContext {
     /// Files that are staged for commit.
     pub staged_files: Vec<PathBuf>,
-    /// Files with unstaged changes.
+    /// Files with unstaged changes. Plugins see all of them; without
+    /// plugins, only the working tree around the staged files is read and
+    /// this lists the files there.
     pub unstaged_files: Vec<PathBuf>,
     /// Statistics about the staged diff.
     pub diff_stats: DiffStats,
     /// Current branch name.
     pub branch: String,
+    /// Whether this is the first commit in the repository.
+    pub initial_commit: bool,
     /// Detected packages (for monorepo support).
     pub packages: Vec<Package>,
     /// Suggested commit type based on diff analysis.
@@ -34,6 +46,11 @@ pub struct RepositoryContext {
     pub diff_info: DiffInfo,
 }
 
+/// Whether plugins run, and so see the whole working tree status.
+fn plugins_enabled(config: &CkConfig) -> bool {
+    config.plugins.enabled && !config.plugins.enabled_plugins.is_empty()
+}
+
 /// Package information for monorepo support.
 #[derive(Debug, Clone)]
 pub struct Package {
@@ -53,39 +70,135 @@ impl RepositoryContext {
 
     /// Build context with custom configuration.
     pub fn from_current_repo_with_config(config: &CkConfig) -> Result<Self> {
+        let progress = Progress::spinner("Reading staged changes");
+        let diff = git::get_staged_diff()?;
+        progress.set_message("Analyzing changes");
+        let mut context = Self::from_diff(diff, config);
+        if context.needs_working_tree(config) {
+            progress.set_message("Reading working tree status");
+            let result = git::open_repo().and_then(|repo| context.read_working_tree(&repo, config));
+            if let Err(e) = result {
+                tracing::debug!("Could not read working tree status: {}", e);
+            }
+        }
+        progress.finish();
+        Ok(context)
+    }
+
+    /// Whether anything looks at the working tree: plugins, which see the
+    /// unstaged files, or the warning about related files left out.
+    fn needs_working_tree(&self, config: &CkConfig) -> bool {
+        let warning = WarningCode::UnstagedChanges.to_string();
+        let warns = !self.staged_files.is_empty() && !config.warnings.disable.contains(&warning);
+        plugins_enabled(config) || warns
+    }
+
+    /// Fill in the unstaged files and warn when files related to the
+    /// staged ones are modified or untracked but left out. Only the files
+    /// that can be related are read, unless plugins need them all.
+    fn read_working_tree(&mut self, repo: &git::Repository, config: &CkConfig) -> Result<()> {
+        let pathspecs = if plugins_enabled(config) {
+            Vec::new()
+        } else {
+            related_pathspecs(&self.staged_files)
+        };
+        let status = git::status::WorkingTreeStatus::read_paths(repo, &pathspecs)?;
+        let mut candidates = status.untracked_files(repo)?;
+        candidates.extend(status.unstaged.iter().cloned());
+        self.unstaged_files = status.unstaged;
+
+        let related = related_files(&self.staged_files, &candidates);
+        if related.is_empty() {
+            return Ok(());
+        }
+        let diff = git::get_unstaged_diff(repo, &related)?;
+        if let Some(warning) = unstaged_warning(&diff, config) {
+            self.warnings.add(warning);
+            self.warnings.configure(&config.warnings);
+        }
+        Ok(())
+    }
+
+    /// Build context for an arbitrary diff against HEAD.
+    pub fn from_diff(diff_info: DiffInfo, config: &CkConfig) -> Self {
         // Get branch name
         let branch = git::get_branch_name().unwrap_or_else(|_| "HEAD".to_string());
 
-        // Get staged diff
-        let diff_info = git::get_staged_diff()?;
-
         // Extract file lists
         let staged_files: Vec<PathBuf> = diff_info.files.iter().map(|f| f.path.clone()).collect();
 
-        // TODO: Get unstaged files - for now return empty
+        // Only the working tree has unstaged files; callers reading it
+        // fill them in
         let unstaged_files = Vec::new();
 
         // Detect packages
         let packages = detect_packages(&staged_files, config);
 
+        // Hints pinned in the tree win over inference
+        let root = git::open_repo()
+            .ok()
+            .map(|repo| repo.workdir().to_path_buf());
+        let hint = root
+            .as_deref()
+            .map(|root| find_hints(root, &staged_files))
+            .unwrap_or_default();
+        let scope_map = root
+            .as_deref()
+            .map(ScopeMap::load)
+            .transpose()
+            .unwrap_or_else(|e| {
+                tracing::warn!("Ignoring scope map: {}", e);
+                None
+            })
+            .unwrap_or_default();
+
+        // The first commit imports the tree, so its content says little
+        // about its type or scope
+        let initial_commit = git::is_initial_commit();
+
         // Infer type and scope
-        let suggested_type = infer_type(&diff_info, &staged_files);
-        let suggested_scope = infer_scope(&staged_files, &packages, config);
+        let suggested_type = if initial_commit {
+            hint.commit_type.or(Some(CommitType::Chore))
+        } else {
+            hint.commit_type
+                .or_else(|| infer_type(&diff_info, &staged_files))
+        };
+        let suggested_scope = if initial_commit {
+            hint.scope
+        } else {
+            hint.scope
+                .or_else(|| infer_scope(&diff_info, &staged_files, &packages, config, &scope_map))
+        };
 
         // Generate warnings
-        let warnings = generate_warnings(&diff_info, &staged_files, &packages, config);
+        let mut warnings = generate_warnings(&diff_info, &staged_files, &packages, config);
+        if initial_commit {
+            // Size and mixed-content warnings are expected on the first commit
+            warnings.retain(|w| {
+                !matches!(
+                    w.code,
+                    WarningCode::OversizedCommit
+                        | WarningCode::LargeFile
+                        | WarningCode::MultiplePackages
+                        | WarningCode::MixedConcerns
+                        | WarningCode::MissingScope
+                )
+            });
+        }
+        warnings.configure(&config.warnings);
 
-        Ok(Self {
+        Self {
             staged_files,
             unstaged_files,
             diff_stats: diff_info.stats.clone(),
             branch,
+            initial_commit,
             packages,
             suggested_type,
             suggested_scope,
             warnings,
             diff_info,
-        })
+        }
     }
 
     /// Check if there are any staged changes.
@@ -93,11 +206,57 @@ impl RepositoryContext {
         !self.staged_files.is_empty()
     }
 
+    /// The context as a JSON document, as generating plugins see it.
+    pub fn to_json(&self) -> serde_json::Value {
+        let paths = |files: &[PathBuf]| -> Vec<String> {
+            files
+                .iter()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect()
+        };
+        let files: Vec<_> = self
+            .diff_info
+            .files
+            .iter()
+            .map(|file| {
+                serde_json::json!({
+                    "path": file.path.to_string_lossy(),
+                    "additions": file.lines_added,
+                    "deletions": file.lines_removed,
+                    "binary": file.is_binary,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "branch": self.branch,
+            "initial_commit": self.initial_commit,
+            "staged_files": paths(&self.staged_files),
+            "unstaged_files": paths(&self.unstaged_files),
+            "packages": self
+                .packages
+                .iter()
+                .filter(|package| package.has_changes)
+                .map(|package| &package.name)
+                .collect::<Vec<_>>(),
+            "suggested_type": self.suggested_type.as_ref().map(|t| t.as_str().to_string()),
+            "suggested_scope": self.suggested_scope,
+            "diff": {
+                "additions": self.diff_stats.lines_added,
+                "deletions": self.diff_stats.lines_removed,
+                "files": files,
+            },
+        })
+    }
+
     /// Get a human-readable summary of the context.
     pub fn summary(&self) -> String {
         let mut parts = Vec::new();
 
         parts.push(format!("Branch: {}", self.branch));
+        if self.initial_commit {
+            parts.push("Initial commit".to_string());
+        }
         parts.push(format!("Files: {}", self.staged_files.len()));
         parts.push(format!(
             "Changes: +{} -{}",
@@ -120,6 +279,15 @@ impl RepositoryContext {
     }
 }
 
+/// Names of the packages touched by `files`.
+pub fn changed_packages(files: &[PathBuf], config: &CkConfig) -> Vec<String> {
+    detect_packages(files, config)
+        .into_iter()
+        .filter(|package| package.has_changes)
+        .map(|package| package.name)
+        .collect()
+}
+
 /// Detect packages in the changed files.
 fn detect_packages(files: &[PathBuf], config: &CkConfig) -> Vec<Package> {
     let mut packages = Vec::new();
@@ -136,8 +304,32 @@ fn detect_packages(files: &[PathBuf], config: &CkConfig) -> Vec<Package> {
         seen_paths.insert(pkg_config.path.clone());
     }
 
-    // Then, auto-detect packages from markers
-    if config.monorepo.enabled {
+    // Then, auto-detect packages from build files or markers
+    if let (true, Some(system)) = (config.monorepo.enabled, config.monorepo.build_system) {
+        for file in files {
+            if seen_paths.iter().any(|path| file.starts_with(path)) {
+                continue;
+            }
+            let package = match BuildPackage::for_file(Path::new(""), file, system) {
+                Some(package) => package,
+                None => continue,
+            };
+            // A file owned by one target is scoped to it, others to the package
+            let name = match package.targets_for(file).as_slice() {
+                [target] => target.name.clone(),
+                _ => package
+                    .name()
+                    .unwrap_or_else(|| config.monorepo.root_scope.clone()),
+            };
+            if !packages.iter().any(|p: &Package| p.name == name) {
+                packages.push(Package {
+                    path: package.path,
+                    name,
+                    has_changes: true,
+                });
+            }
+        }
+    } else if config.monorepo.enabled {
         for file in files {
             // Walk up the directory tree looking for package markers
             let mut current = file.parent();
@@ -203,6 +395,7 @@ mod tests {
                 binary_files: 0,
             },
             branch: "main".to_string(),
+            initial_commit: false,
             packages: vec![],
             suggested_type: Some(CommitType::Feat),
             suggested_scope: Some("core".to_string()),
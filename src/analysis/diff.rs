@@ -4,14 +4,14 @@
 //! Diff analysis for semantic understanding.
 
 use crate::git::DiffInfo;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Semantic analysis of diff content.
 #[derive(Debug, Clone)]
 pub struct DiffAnalysis {
     /// Categorized changes by type.
-    pub categories: HashMap<ChangeCategory, Vec<PathBuf>>,
+    pub categories: BTreeMap<ChangeCategory, Vec<PathBuf>>,
     /// Key changes extracted from the diff.
     pub key_changes: Vec<String>,
     /// Whether this looks like a refactoring.
@@ -29,7 +29,7 @@ pub struct DiffAnalysis {
 }
 
 /// Categories of changes.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ChangeCategory {
     /// New files added.
     NewFiles,
@@ -52,7 +52,7 @@ pub enum ChangeCategory {
 impl DiffAnalysis {
     /// Analyze a diff and categorize the changes.
     pub fn from_diff(diff: &DiffInfo) -> Self {
-        let mut categories: HashMap<ChangeCategory, Vec<PathBuf>> = HashMap::new();
+        let mut categories: BTreeMap<ChangeCategory, Vec<PathBuf>> = BTreeMap::new();
         let mut key_changes = Vec::new();
 
         for file in &diff.files {
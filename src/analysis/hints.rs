@@ -0,0 +1,211 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Scope and type hints pinned in the source tree.
+//!
+//! A file can pin its own scope or type with a magic comment near the top,
+//! such as `// ck:scope=auth` or `# ck:type=fix`. A `.ck-scope` file pins
+//! them for everything below its directory, either as a bare scope name or
+//! as `scope=...` / `type=...` lines.
+
+use crate::config::CommitType;
+use std::path::{Path, PathBuf};
+
+/// Name of the directory-level hint file.
+pub const SCOPE_FILE: &str = ".ck-scope";
+
+/// Number of lines at the top of a file searched for magic comments.
+const MAGIC_COMMENT_LINES: usize = 20;
+
+/// Scope and type pinned for a set of files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathHint {
+    /// Pinned scope.
+    pub scope: Option<String>,
+    /// Pinned commit type.
+    pub commit_type: Option<CommitType>,
+}
+
+impl PathHint {
+    fn is_empty(&self) -> bool {
+        self.scope.is_none() && self.commit_type.is_none()
+    }
+}
+
+/// Find the hint shared by all of `files` (relative to `root`).
+///
+/// A magic comment in a file takes precedence over the nearest
+/// `.ck-scope`. A scope or type is only pinned when every hinted file
+/// agrees on it.
+pub fn find_hints(root: &Path, files: &[PathBuf]) -> PathHint {
+    let hints: Vec<PathHint> = files
+        .iter()
+        .map(|file| file_hint(root, file))
+        .filter(|hint| !hint.is_empty())
+        .collect();
+
+    PathHint {
+        scope: agreed(hints.iter().map(|h| h.scope.clone())),
+        commit_type: agreed(hints.iter().map(|h| h.commit_type.clone())),
+    }
+}
+
+/// Hint for a single file.
+fn file_hint(root: &Path, file: &Path) -> PathHint {
+    let mut hint = std::fs::read_to_string(root.join(file))
+        .map(|content| parse_magic_comments(&content))
+        .unwrap_or_default();
+
+    if hint.scope.is_none() || hint.commit_type.is_none() {
+        let dir_hint = directory_hint(root, file);
+        hint.scope = hint.scope.or(dir_hint.scope);
+        hint.commit_type = hint.commit_type.or(dir_hint.commit_type);
+    }
+
+    hint
+}
+
+/// Hint from the nearest `.ck-scope` at or above the file's directory.
+fn directory_hint(root: &Path, file: &Path) -> PathHint {
+    let mut dir = file.parent();
+    while let Some(current) = dir {
+        if let Ok(content) = std::fs::read_to_string(root.join(current).join(SCOPE_FILE)) {
+            return parse_scope_file(&content);
+        }
+        if current.as_os_str().is_empty() {
+            break;
+        }
+        dir = current.parent();
+    }
+    PathHint::default()
+}
+
+/// Parse `ck:scope=...` and `ck:type=...` from comments at the top of a file.
+pub fn parse_magic_comments(content: &str) -> PathHint {
+    let mut hint = PathHint::default();
+
+    for line in content.lines().take(MAGIC_COMMENT_LINES) {
+        let trimmed = line.trim_start();
+        let is_comment = ["//", "#", "/*", "*", "--", "<!--", ";"]
+            .iter()
+            .any(|marker| trimmed.starts_with(marker));
+        if !is_comment {
+            continue;
+        }
+
+        for token in trimmed.split_whitespace() {
+            if let Some(directive) = token.strip_prefix("ck:") {
+                apply_directive(&mut hint, directive);
+            }
+        }
+    }
+
+    hint
+}
+
+/// Parse a `.ck-scope` file.
+pub fn parse_scope_file(content: &str) -> PathHint {
+    let mut hint = PathHint::default();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.contains('=') {
+            apply_directive(&mut hint, line);
+        } else if hint.scope.is_none() {
+            hint.scope = Some(line.to_string());
+        }
+    }
+
+    hint
+}
+
+/// Apply a `key=value` directive.
+fn apply_directive(hint: &mut PathHint, directive: &str) {
+    let (key, value) = match directive.split_once('=') {
+        Some((key, value)) => (key.trim(), value.trim().trim_matches('"')),
+        None => return,
+    };
+    // Drop a closing comment marker glued to the value
+    let value = value.trim_end_matches("*/").trim_end_matches("-->");
+    if value.is_empty() {
+        return;
+    }
+
+    match key {
+        "scope" => hint.scope = Some(value.to_string()),
+        "type" => match value.parse::<CommitType>() {
+            Ok(commit_type) => hint.commit_type = Some(commit_type),
+            Err(_) => tracing::debug!("Ignoring unknown commit type hint '{}'", value),
+        },
+        _ => {}
+    }
+}
+
+/// The single value all hints agree on, ignoring files without one.
+fn agreed<T: PartialEq>(values: impl Iterator<Item = Option<T>>) -> Option<T> {
+    let mut result: Option<T> = None;
+    for value in values.flatten() {
+        match result {
+            Some(ref current) if *current != value => return None,
+            Some(_) => {}
+            None => result = Some(value),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_hints() {
+        let hint = parse_magic_comments("// ck:scope=auth ck:type=fix\nfn main() {}\n");
+        assert_eq!(hint.scope.as_deref(), Some("auth"));
+        assert_eq!(hint.commit_type, Some(CommitType::Fix));
+
+        // Only comments count
+        let hint = parse_magic_comments("let s = \"ck:scope=nope\";\n");
+        assert_eq!(hint, PathHint::default());
+
+        let hint = parse_scope_file("# billing service\nbilling\ntype=feat\n");
+        assert_eq!(hint.scope.as_deref(), Some("billing"));
+        assert_eq!(hint.commit_type, Some(CommitType::Feat));
+    }
+
+    #[test]
+    fn test_find_hints() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("services/auth/src")).unwrap();
+        std::fs::write(root.join("services/auth/.ck-scope"), "auth\n").unwrap();
+        std::fs::write(root.join("services/auth/src/lib.rs"), "pub fn f() {}\n").unwrap();
+        std::fs::write(root.join("services/auth/src/db.rs"), "/* ck:scope=db */\n").unwrap();
+
+        let lib = PathBuf::from("services/auth/src/lib.rs");
+        let db = PathBuf::from("services/auth/src/db.rs");
+        assert_eq!(
+            find_hints(root, std::slice::from_ref(&lib))
+                .scope
+                .as_deref(),
+            Some("auth")
+        );
+        assert_eq!(
+            find_hints(root, std::slice::from_ref(&db)).scope.as_deref(),
+            Some("db")
+        );
+
+        // Conflicting hints pin nothing; unhinted files don't count
+        assert_eq!(find_hints(root, &[lib.clone(), db]).scope, None);
+        assert_eq!(
+            find_hints(root, &[lib, PathBuf::from("README.md")])
+                .scope
+                .as_deref(),
+            Some("auth")
+        );
+    }
+}
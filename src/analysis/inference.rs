@@ -3,12 +3,14 @@
 
 //! Commit type and scope inference.
 
-use crate::config::{CkConfig, CommitType};
+use crate::config::{CkConfig, CommitType, ScopeConfig};
 use crate::git::DiffInfo;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use super::context::Package;
 use super::diff::{ChangeCategory, DiffAnalysis};
+use super::scope_map::ScopeMap;
 
 /// Score for a commit type inference.
 #[derive(Debug, Clone)]
@@ -23,7 +25,7 @@ pub struct CommitTypeScore {
 
 /// Infer the most likely commit type from the diff.
 pub fn infer_type(diff: &DiffInfo, files: &[PathBuf]) -> Option<CommitType> {
-    let scores = score_commit_types(diff, files);
+    let scores = score_commit_types(diff, files, false);
 
     // Return the highest scoring type if confidence is above threshold
     scores
@@ -38,7 +40,14 @@ pub fn infer_type(diff: &DiffInfo, files: &[PathBuf]) -> Option<CommitType> {
 }
 
 /// Score all possible commit types.
-pub fn score_commit_types(diff: &DiffInfo, files: &[PathBuf]) -> Vec<CommitTypeScore> {
+///
+/// With `redact_paths`, file paths in the reasons are redacted as in
+/// warnings.
+pub fn score_commit_types(
+    diff: &DiffInfo,
+    files: &[PathBuf],
+    redact_paths: bool,
+) -> Vec<CommitTypeScore> {
     let analysis = DiffAnalysis::from_diff(diff);
     let mut scores = Vec::new();
 
@@ -105,16 +114,26 @@ pub fn score_commit_types(diff: &DiffInfo, files: &[PathBuf]) -> Vec<CommitTypeS
         });
     }
 
-    // Check file patterns
+    // Check file patterns, weighted by each file's share of the changed lines
+    let total_changes: usize = diff.files.iter().map(|f| f.total_changes()).sum();
     for file in files {
         let path_str = file.to_string_lossy().to_lowercase();
+        let share = match diff.files.iter().find(|f| &f.path == file) {
+            Some(change) if total_changes > 0 => {
+                change.total_changes() as f64 / total_changes as f64
+            }
+            _ => 1.0,
+        };
 
         // Performance-related files
         if path_str.contains("perf") || path_str.contains("bench") || path_str.contains("optim") {
             scores.push(CommitTypeScore {
                 commit_type: CommitType::Perf,
-                score: 0.7,
-                reason: format!("Performance-related file: {}", file.display()),
+                score: 0.5 + 0.2 * share,
+                reason: format!(
+                    "Performance-related file: {}",
+                    crate::security::display_path(file, redact_paths)
+                ),
             });
         }
 
@@ -128,7 +147,7 @@ pub fn score_commit_types(diff: &DiffInfo, files: &[PathBuf]) -> Vec<CommitTypeS
         {
             scores.push(CommitTypeScore {
                 commit_type: CommitType::Style,
-                score: 0.8,
+                score: 0.6 + 0.2 * share,
                 reason: "Style/formatting files changed".to_string(),
             });
         }
@@ -151,13 +170,47 @@ pub fn score_commit_types(diff: &DiffInfo, files: &[PathBuf]) -> Vec<CommitTypeS
     scores
 }
 
-/// Infer the scope from file paths and packages.
-pub fn infer_scope(files: &[PathBuf], packages: &[Package], config: &CkConfig) -> Option<String> {
+/// Share of the changed lines a scope candidate needs to win over the
+/// others.
+const DOMINANT_SHARE: f64 = 0.75;
+
+/// Infer the scope from the scope map, packages and file paths.
+///
+/// When the files point at several scopes, one whose files hold most of
+/// the changed lines still wins, so a large change is not overruled by
+/// trivial edits elsewhere.
+pub fn infer_scope(
+    diff: &DiffInfo,
+    files: &[PathBuf],
+    packages: &[Package],
+    config: &CkConfig,
+    scope_map: &ScopeMap,
+) -> Option<String> {
+    // Explicit ownership mappings come first
+    if let Some(scope) = scope_map.infer(files) {
+        return Some(scope);
+    }
+    let mapped = files
+        .iter()
+        .filter_map(|file| Some((scope_map.scope_for(file)?, changed_lines(diff, file))));
+    if let Some(scope) = dominant(mapped) {
+        return Some(scope.to_string());
+    }
+
     // If only one package has changes, use its scope
     let changed_packages: Vec<_> = packages.iter().filter(|p| p.has_changes).collect();
     if changed_packages.len() == 1 {
         return Some(changed_packages[0].name.clone());
     }
+    let in_packages = files.iter().filter_map(|file| {
+        let package = changed_packages
+            .iter()
+            .find(|p| file.starts_with(&p.path))?;
+        Some((package.name.as_str(), changed_lines(diff, file)))
+    });
+    if let Some(scope) = dominant(in_packages) {
+        return Some(scope.to_string());
+    }
 
     // Try to find a common directory
     let common_dir = find_common_directory(files);
@@ -181,7 +234,22 @@ pub fn infer_scope(files: &[PathBuf], packages: &[Package], config: &CkConfig) -
         }
     }
 
-    // Check path-based rules
+    // Check path-based rules, preferring the rule covering most changes
+    let matches_rule = |file: &Path| {
+        config.rules.paths.iter().find_map(|(pattern, rule)| {
+            let scope = rule.scope.as_deref()?;
+            glob::Pattern::new(pattern)
+                .ok()?
+                .matches_path(file)
+                .then_some(scope)
+        })
+    };
+    let by_rule = files
+        .iter()
+        .filter_map(|file| Some((matches_rule(file)?, changed_lines(diff, file))));
+    if let Some(scope) = dominant(by_rule) {
+        return Some(scope.to_string());
+    }
     for (pattern, rule) in &config.rules.paths {
         if let Some(ref scope) = rule.scope {
             let glob_pattern = glob::Pattern::new(pattern).ok()?;
@@ -196,6 +264,164 @@ pub fn infer_scope(files: &[PathBuf], packages: &[Package], config: &CkConfig) -
     None
 }
 
+/// Lines changed in `file`, counting files outside the diff or without
+/// line changes (such as binaries) as a single line.
+fn changed_lines(diff: &DiffInfo, file: &Path) -> usize {
+    diff.files
+        .iter()
+        .find(|f| f.path == file)
+        .map(|f| f.total_changes())
+        .unwrap_or(0)
+        .max(1)
+}
+
+/// The candidate holding at least [`DOMINANT_SHARE`] of the changed
+/// lines, given each file's candidate and line count.
+fn dominant<'a>(candidates: impl Iterator<Item = (&'a str, usize)>) -> Option<&'a str> {
+    let mut weights: HashMap<&str, usize> = HashMap::new();
+    for (candidate, lines) in candidates {
+        *weights.entry(candidate).or_default() += lines;
+    }
+    let total: usize = weights.values().sum();
+    weights
+        .into_iter()
+        .find(|(_, lines)| *lines as f64 >= total as f64 * DOMINANT_SHARE)
+        .map(|(candidate, _)| candidate)
+}
+
+/// How an inferred scope was reconciled with the scope configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScopeReconciliation {
+    /// The scope is allowed as inferred.
+    Kept(String),
+    /// The scope was converted to `rules.scope.case`.
+    Normalized { from: String, to: String },
+    /// The scope was mapped through `rules.scope.aliases`.
+    Aliased { from: String, to: String },
+    /// The scope was replaced with the closest allowed scope.
+    Nearest { from: String, to: String },
+    /// No allowed scope was close enough, so the scope was dropped.
+    Omitted { from: String },
+}
+
+impl ScopeReconciliation {
+    /// The scope to use, if any.
+    pub fn scope(&self) -> Option<&str> {
+        match self {
+            ScopeReconciliation::Kept(scope) => Some(scope),
+            ScopeReconciliation::Normalized { to, .. }
+            | ScopeReconciliation::Aliased { to, .. }
+            | ScopeReconciliation::Nearest { to, .. } => Some(to),
+            ScopeReconciliation::Omitted { .. } => None,
+        }
+    }
+
+    /// Human-readable note when the inferred scope was changed.
+    pub fn describe(&self) -> Option<String> {
+        match self {
+            ScopeReconciliation::Kept(_) => None,
+            ScopeReconciliation::Normalized { from, to } => {
+                Some(format!("scope '{}' normalized to '{}'", from, to))
+            }
+            ScopeReconciliation::Aliased { from, to } => {
+                Some(format!("scope '{}' mapped to '{}' by alias", from, to))
+            }
+            ScopeReconciliation::Nearest { from, to } => Some(format!(
+                "scope '{}' is not allowed; using nearest allowed scope '{}'",
+                from, to
+            )),
+            ScopeReconciliation::Omitted { from } => Some(format!(
+                "scope '{}' is not allowed and no allowed scope is close; omitted",
+                from
+            )),
+        }
+    }
+}
+
+/// Reconcile an inferred scope with the case, allowed, forbidden and
+/// alias lists.
+pub fn reconcile_scope(scope: &str, config: &ScopeConfig) -> ScopeReconciliation {
+    let inferred = scope;
+    let normalized = config.normalize(scope);
+    let scope = normalized.as_str();
+    let usable = |s: &str| {
+        !config.forbidden.iter().any(|f| f == s)
+            && (config.allowed.is_empty() || config.allowed.iter().any(|a| a == s))
+    };
+
+    if let Some((_, to)) = config.aliases.iter().find(|(alias, _)| {
+        alias.eq_ignore_ascii_case(inferred) || alias.eq_ignore_ascii_case(scope)
+    }) {
+        if usable(to) {
+            return ScopeReconciliation::Aliased {
+                from: inferred.to_string(),
+                to: to.clone(),
+            };
+        }
+    }
+
+    if usable(scope) && scope != inferred {
+        return ScopeReconciliation::Normalized {
+            from: inferred.to_string(),
+            to: scope.to_string(),
+        };
+    }
+    if usable(scope) {
+        return ScopeReconciliation::Kept(scope.to_string());
+    }
+
+    // Closest allowed scope: a case or containment match, or a small edit
+    let lower = scope.to_lowercase();
+    let nearest = config
+        .allowed
+        .iter()
+        .filter(|allowed| usable(allowed))
+        .filter_map(|allowed| {
+            let candidate = allowed.to_lowercase();
+            let distance = if candidate == lower {
+                0
+            } else if lower.contains(&candidate) || candidate.contains(&lower) {
+                1
+            } else {
+                edit_distance(&lower, &candidate) + 1
+            };
+            let limit = 1 + lower.len().max(candidate.len()) / 3;
+            (distance <= limit).then_some((distance, allowed))
+        })
+        .min_by_key(|(distance, _)| *distance);
+
+    match nearest {
+        Some((_, to)) => ScopeReconciliation::Nearest {
+            from: inferred.to_string(),
+            to: to.clone(),
+        },
+        None => ScopeReconciliation::Omitted {
+            from: inferred.to_string(),
+        },
+    }
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current.push(
+                (previous[j] + cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
 /// Find the common directory for a set of files.
 fn find_common_directory(files: &[PathBuf]) -> Option<PathBuf> {
     if files.is_empty() {
@@ -272,6 +498,27 @@ mod tests {
         assert_eq!(inferred, Some(CommitType::Test));
     }
 
+    #[test]
+    fn test_path_signals_weighted_by_changed_lines() {
+        let mut diff = make_diff_with_files(&["benches/parse.rs", "src/parser.rs"]);
+        let files: Vec<PathBuf> = diff.files.iter().map(|f| f.path.clone()).collect();
+        let perf_score = |diff: &DiffInfo| {
+            score_commit_types(diff, &files, false)
+                .into_iter()
+                .find(|s| s.commit_type == CommitType::Perf)
+                .map(|s| s.score)
+                .unwrap()
+        };
+        let even = perf_score(&diff);
+
+        // A one-line benchmark tweak in a large change is a weak signal
+        diff.files[0].lines_added = 1;
+        diff.files[0].lines_removed = 0;
+        diff.files[1].lines_added = 99;
+        assert!(perf_score(&diff) < even);
+        assert!(perf_score(&diff) < 0.52);
+    }
+
     #[test]
     fn test_infer_scope_single_package() {
         let files = vec![PathBuf::from("crates/core/src/lib.rs")];
@@ -281,8 +528,117 @@ mod tests {
             has_changes: true,
         }];
 
-        let scope = infer_scope(&files, &packages, &CkConfig::default());
+        let scope = infer_scope(
+            &make_diff_with_files(&[]),
+            &files,
+            &packages,
+            &CkConfig::default(),
+            &ScopeMap::default(),
+        );
         assert_eq!(scope, Some("core".to_string()));
+
+        // The scope map takes priority over packages
+        let mut scope_map = ScopeMap::default();
+        scope_map.add("crates/core/**", "engine", false).unwrap();
+        let scope = infer_scope(
+            &make_diff_with_files(&[]),
+            &files,
+            &packages,
+            &CkConfig::default(),
+            &scope_map,
+        );
+        assert_eq!(scope, Some("engine".to_string()));
+    }
+
+    #[test]
+    fn test_infer_scope_weighted_by_changed_lines() {
+        let mut diff = make_diff_with_files(&[
+            "crates/core/src/engine.rs",
+            "crates/cli/src/main.rs",
+            "crates/lsp/src/lib.rs",
+            "crates/docs/src/lib.rs",
+        ]);
+        diff.files[0].lines_added = 400;
+        for file in &mut diff.files[1..] {
+            file.lines_added = 1;
+            file.lines_removed = 0;
+        }
+        let files: Vec<PathBuf> = diff.files.iter().map(|f| f.path.clone()).collect();
+        let packages: Vec<Package> = ["core", "cli", "lsp", "docs"]
+            .iter()
+            .map(|name| Package {
+                path: PathBuf::from("crates").join(name),
+                name: name.to_string(),
+                has_changes: true,
+            })
+            .collect();
+        let infer = |diff: &DiffInfo| {
+            infer_scope(
+                diff,
+                &files,
+                &packages,
+                &CkConfig::default(),
+                &ScopeMap::default(),
+            )
+        };
+
+        // One large change outweighs several one-line tweaks
+        assert_eq!(infer(&diff), Some("core".to_string()));
+
+        // Evenly spread changes have no dominant package
+        for file in &mut diff.files {
+            file.lines_added = 10;
+        }
+        assert_eq!(infer(&diff), Some("crates".to_string()));
+    }
+
+    #[test]
+    fn test_reconcile_scope() {
+        let mut config = ScopeConfig {
+            allowed: vec!["cli".to_string(), "config".to_string()],
+            ..Default::default()
+        };
+        config
+            .aliases
+            .insert("commands".to_string(), "cli".to_string());
+
+        assert_eq!(
+            reconcile_scope("cli", &config),
+            ScopeReconciliation::Kept("cli".to_string())
+        );
+        assert_eq!(reconcile_scope("commands", &config).scope(), Some("cli"));
+        assert_eq!(
+            reconcile_scope("configs", &config),
+            ScopeReconciliation::Nearest {
+                from: "configs".to_string(),
+                to: "config".to_string()
+            }
+        );
+        assert_eq!(reconcile_scope("database", &config).scope(), None);
+
+        // Anything not forbidden is kept when no allow-list is set
+        let open = ScopeConfig::default();
+        assert_eq!(reconcile_scope("database", &open).scope(), Some("database"));
+
+        // Inferred scopes take the configured case first
+        let kebab = ScopeConfig {
+            case: Some(crate::config::ScopeCase::Kebab),
+            ..config
+        };
+        assert_eq!(
+            reconcile_scope(
+                "UserProfile",
+                &ScopeConfig {
+                    allowed: Vec::new(),
+                    ..kebab.clone()
+                }
+            ),
+            ScopeReconciliation::Normalized {
+                from: "UserProfile".to_string(),
+                to: "user-profile".to_string()
+            }
+        );
+        assert_eq!(reconcile_scope("CLI", &kebab).scope(), Some("cli"));
     }
 
     #[test]
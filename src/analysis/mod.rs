@@ -7,10 +7,16 @@
 
 mod context;
 pub mod diff;
+pub mod hints;
 mod inference;
+mod related;
+pub mod scope_map;
 mod warnings;
 
-pub use context::RepositoryContext;
+pub use context::{changed_packages, RepositoryContext, INITIAL_SUBJECT};
 pub use diff::DiffAnalysis;
-pub use inference::{infer_scope, infer_type, CommitTypeScore};
-pub use warnings::{Warning, WarningLevel, Warnings};
+pub use inference::{
+    infer_scope, infer_type, reconcile_scope, score_commit_types, CommitTypeScore,
+    ScopeReconciliation,
+};
+pub use warnings::{Warning, WarningCode, WarningLevel, Warnings};
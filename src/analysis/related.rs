@@ -0,0 +1,207 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Files that belong with the staged changes.
+//!
+//! A file is related to a staged one when it is the same file with more
+//! changes, its test or source (`parser.rs` and `parser_test.rs`, or
+//! `Parser.java` and `ParserTest.java`), a header or sibling with the same
+//! name, or the lockfile of a staged manifest.
+
+use std::path::{Path, PathBuf};
+
+/// Manifests and the lockfiles generated from them.
+const LOCKFILES: &[(&str, &str)] = &[
+    ("Cargo.toml", "Cargo.lock"),
+    ("package.json", "package-lock.json"),
+    ("package.json", "yarn.lock"),
+    ("package.json", "pnpm-lock.yaml"),
+    ("go.mod", "go.sum"),
+    ("pyproject.toml", "poetry.lock"),
+    ("pyproject.toml", "uv.lock"),
+    ("Gemfile", "Gemfile.lock"),
+    ("composer.json", "composer.lock"),
+];
+
+/// Names too common to relate files in different directories.
+const GENERIC_STEMS: &[&str] = &[
+    "__init__",
+    "app",
+    "build",
+    "cargo",
+    "changelog",
+    "common",
+    "composer",
+    "config",
+    "conftest",
+    "constants",
+    "dockerfile",
+    "gemfile",
+    "go",
+    "helpers",
+    "index",
+    "lib",
+    "license",
+    "main",
+    "makefile",
+    "mod",
+    "package",
+    "pyproject",
+    "readme",
+    "setup",
+    "spec",
+    "test",
+    "tests",
+    "tsconfig",
+    "types",
+    "util",
+    "utils",
+];
+
+/// The `candidates` related to any of the `staged` files.
+pub fn related_files(staged: &[PathBuf], candidates: &[PathBuf]) -> Vec<PathBuf> {
+    candidates
+        .iter()
+        .filter(|candidate| staged.iter().any(|file| is_related(file, candidate)))
+        .cloned()
+        .collect()
+}
+
+/// Pathspecs covering every file that can be related to the `staged`
+/// ones, so the working tree status only has to look at those. They match
+/// a superset; [`related_files`] narrows it down.
+pub fn related_pathspecs(staged: &[PathBuf]) -> Vec<String> {
+    let mut pathspecs = Vec::new();
+    for name in staged.iter().filter_map(|path| file_name(path)) {
+        // Any file sharing the base name, in any case it is usually
+        // written in: parser, Parser, PARSER
+        if let Some(stem) = base_stem(name) {
+            let mut capitalized = stem.clone();
+            if let Some(first) = capitalized.get_mut(..1) {
+                first.make_ascii_uppercase();
+            }
+            for stem in [stem.clone(), capitalized, stem.to_uppercase()] {
+                pathspecs.push(format!("*{}*", stem));
+            }
+        }
+        for (_, lockfile) in LOCKFILES.iter().filter(|(m, _)| *m == name) {
+            pathspecs.push(format!("*{}", lockfile));
+        }
+        for (manifest, _) in LOCKFILES.iter().filter(|(_, l)| *l == name) {
+            pathspecs.push(format!("*{}", manifest));
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    pathspecs.retain(|pathspec| seen.insert(pathspec.clone()));
+    pathspecs
+}
+
+fn is_related(a: &Path, b: &Path) -> bool {
+    if a == b {
+        return true;
+    }
+
+    let (name_a, name_b) = match (file_name(a), file_name(b)) {
+        (Some(name_a), Some(name_b)) => (name_a, name_b),
+        _ => return false,
+    };
+    let same_dir = a.parent() == b.parent();
+    let is_lockfile_of = |manifest: &Path, lockfile: &Path, m: &str, l: &str| {
+        // Workspace members share the lockfile at the workspace root
+        LOCKFILES.contains(&(m, l))
+            && lockfile
+                .parent()
+                .is_none_or(|dir| manifest.parent().unwrap_or(dir).starts_with(dir))
+    };
+    if is_lockfile_of(a, b, name_a, name_b) || is_lockfile_of(b, a, name_b, name_a) {
+        return true;
+    }
+
+    match (base_stem(name_a), base_stem(name_b)) {
+        (Some(stem_a), Some(stem_b)) if stem_a == stem_b => {
+            same_dir || !GENERIC_STEMS.contains(&stem_a.as_str())
+        }
+        _ => false,
+    }
+}
+
+fn file_name(path: &Path) -> Option<&str> {
+    path.file_name().and_then(|name| name.to_str())
+}
+
+/// A file name without extensions or test markers, lowercased:
+/// `parser_test.rs`, `parser.spec.ts` and `ParserTest.java` all give `parser`.
+fn base_stem(name: &str) -> Option<String> {
+    let stem = name.split('.').next().filter(|stem| !stem.is_empty())?;
+    let stem = stem
+        .strip_suffix("Tests")
+        .or_else(|| stem.strip_suffix("Test"))
+        .unwrap_or(stem)
+        .to_lowercase();
+    let stem = ["_test", "_spec", "-test", "-spec", "_tests"]
+        .iter()
+        .find_map(|suffix| stem.strip_suffix(suffix))
+        .or_else(|| stem.strip_prefix("test_"))
+        .unwrap_or(&stem)
+        .to_string();
+    Some(stem).filter(|stem| !stem.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn related(staged: &str, candidate: &str) -> bool {
+        !related_files(&[PathBuf::from(staged)], &[PathBuf::from(candidate)]).is_empty()
+    }
+
+    #[test]
+    fn test_related_files() {
+        assert!(related("src/parser.rs", "src/parser.rs"));
+        assert!(related("src/parser.rs", "tests/parser_test.rs"));
+        assert!(related("src/parser.ts", "src/parser.spec.ts"));
+        assert!(related("tests/test_parser.py", "parser/parser.py"));
+        assert!(related("src/Parser.java", "test/ParserTest.java"));
+        assert!(related("include/parser.h", "src/parser.c"));
+        assert!(related("src/mod.rs", "src/mod_test.rs"));
+        assert!(!related("src/parser.rs", "src/lexer.rs"));
+    }
+
+    #[test]
+    fn test_related_pathspecs() {
+        let pathspecs = related_pathspecs(&[
+            PathBuf::from("src/ParserTest.java"),
+            PathBuf::from("crates/cli/Cargo.toml"),
+        ]);
+        assert_eq!(
+            pathspecs,
+            [
+                "*parser*",
+                "*Parser*",
+                "*PARSER*",
+                "*cargo*",
+                "*Cargo*",
+                "*CARGO*",
+                "*Cargo.lock"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generic_names_need_the_same_directory() {
+        assert!(!related("src/parser/mod.rs", "src/lexer/mod.rs"));
+        assert!(!related("web/index.ts", "docs/index.md"));
+        assert!(related("web/index.ts", "web/index.css"));
+        assert!(!related("crates/core/Cargo.toml", "crates/cli/Cargo.toml"));
+    }
+
+    #[test]
+    fn test_lockfiles() {
+        assert!(related("Cargo.toml", "Cargo.lock"));
+        assert!(related("Cargo.lock", "Cargo.toml"));
+        assert!(related("crates/core/Cargo.toml", "Cargo.lock"));
+        assert!(related("web/package.json", "web/yarn.lock"));
+        assert!(!related("web/package.json", "api/package-lock.json"));
+        assert!(!related("Cargo.toml", "go.sum"));
+    }
+}
@@ -0,0 +1,179 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Ownership-based scope mapping from `scope-map.toml`.
+//!
+//! The map lists glob patterns with the scope for matching files. Entries
+//! are tried in order and the first match wins, so earlier entries take
+//! priority:
+//!
+//! ```toml
+//! [[map]]
+//! pattern = "services/billing/**"
+//! scope = "billing"
+//! ```
+
+use crate::error::{CkError, ConfigError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Name of the scope map file in the repository root.
+pub const SCOPE_MAP_FILE: &str = "scope-map.toml";
+
+/// A glob pattern mapped to a scope.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScopeMapping {
+    /// Glob matched against paths relative to the repository root.
+    pub pattern: String,
+    /// Scope for matching files.
+    pub scope: String,
+}
+
+/// Ordered glob-to-scope mappings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScopeMap {
+    /// Mappings in priority order.
+    #[serde(default)]
+    pub map: Vec<ScopeMapping>,
+}
+
+impl ScopeMap {
+    /// Load the scope map from `root`, or an empty map if there is none.
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join(SCOPE_MAP_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path).map_err(|e| parse_error(&path, e))?;
+        let map: Self = toml::from_str(&content).map_err(|e| parse_error(&path, e))?;
+        for mapping in &map.map {
+            validate_pattern(&mapping.pattern)?;
+        }
+        Ok(map)
+    }
+
+    /// Write the scope map to `root`.
+    pub fn save(&self, root: &Path) -> Result<()> {
+        let content = toml::to_string_pretty(self).map_err(|e| {
+            CkError::Config(ConfigError::InvalidValue {
+                key: "map".to_string(),
+                message: e.to_string(),
+            })
+        })?;
+        std::fs::write(root.join(SCOPE_MAP_FILE), content)?;
+        Ok(())
+    }
+
+    /// Add a mapping, at the top when `first` is set and at the end
+    /// otherwise. An existing mapping for the same pattern is replaced.
+    pub fn add(&mut self, pattern: &str, scope: &str, first: bool) -> Result<()> {
+        validate_pattern(pattern)?;
+        self.map.retain(|m| m.pattern != pattern);
+
+        let mapping = ScopeMapping {
+            pattern: pattern.to_string(),
+            scope: scope.to_string(),
+        };
+        if first {
+            self.map.insert(0, mapping);
+        } else {
+            self.map.push(mapping);
+        }
+        Ok(())
+    }
+
+    /// Scope of the first mapping matching `path`.
+    pub fn scope_for(&self, path: &Path) -> Option<&str> {
+        self.map
+            .iter()
+            .find(|m| {
+                glob::Pattern::new(&m.pattern)
+                    .map(|p| p.matches_path(path))
+                    .unwrap_or(false)
+            })
+            .map(|m| m.scope.as_str())
+    }
+
+    /// Scope shared by every mapped file in `files`.
+    ///
+    /// Files without a mapping are ignored; files mapped to different
+    /// scopes yield no scope.
+    pub fn infer(&self, files: &[PathBuf]) -> Option<String> {
+        let mut scope: Option<&str> = None;
+        for found in files.iter().filter_map(|f| self.scope_for(f)) {
+            match scope {
+                Some(current) if current != found => return None,
+                _ => scope = Some(found),
+            }
+        }
+        scope.map(str::to_string)
+    }
+}
+
+fn validate_pattern(pattern: &str) -> Result<()> {
+    glob::Pattern::new(pattern).map(|_| ()).map_err(|e| {
+        CkError::Config(ConfigError::InvalidValue {
+            key: format!("scope map pattern '{}'", pattern),
+            message: e.to_string(),
+        })
+    })
+}
+
+fn parse_error(path: &Path, e: impl std::fmt::Display) -> CkError {
+    CkError::Config(ConfigError::ParseError {
+        message: format!("Failed to read {}: {}", path.display(), e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_first_match_wins() {
+        let mut map = ScopeMap::default();
+        map.add("services/billing/**", "billing", false).unwrap();
+        map.add("services/**", "services", false).unwrap();
+        map.add("services/billing/legacy/**", "legacy", true)
+            .unwrap();
+
+        assert_eq!(
+            map.scope_for(Path::new("services/billing/src/lib.rs")),
+            Some("billing")
+        );
+        assert_eq!(
+            map.scope_for(Path::new("services/billing/legacy/a.rs")),
+            Some("legacy")
+        );
+        assert_eq!(map.scope_for(Path::new("README.md")), None);
+
+        let files = vec![
+            PathBuf::from("services/billing/a.rs"),
+            PathBuf::from("README.md"),
+        ];
+        assert_eq!(map.infer(&files).as_deref(), Some("billing"));
+        let files = vec![
+            PathBuf::from("services/billing/a.rs"),
+            PathBuf::from("services/auth/a.rs"),
+        ];
+        assert_eq!(map.infer(&files), None);
+
+        assert!(map.add("[", "broken", false).is_err());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = TempDir::new().unwrap();
+        assert!(ScopeMap::load(dir.path()).unwrap().map.is_empty());
+
+        let mut map = ScopeMap::default();
+        map.add("web/**", "frontend", false).unwrap();
+        map.add("api/**", "backend", false).unwrap();
+        map.save(dir.path()).unwrap();
+
+        let loaded = ScopeMap::load(dir.path()).unwrap();
+        assert_eq!(loaded.map, map.map);
+    }
+}
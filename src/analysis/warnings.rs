@@ -3,7 +3,7 @@
 
 //! Warning generation for commit quality issues.
 
-use crate::config::CkConfig;
+use crate::config::{CkConfig, WarningsConfig};
 use crate::git::DiffInfo;
 use std::fmt;
 use std::path::PathBuf;
@@ -99,6 +99,24 @@ impl Warnings {
         self.warnings.push(warning);
     }
 
+    /// Keep only the warnings matching `keep`.
+    pub fn retain(&mut self, keep: impl FnMut(&Warning) -> bool) {
+        self.warnings.retain(keep);
+    }
+
+    /// Drop disabled warnings and raise promoted ones to errors.
+    pub fn configure(&mut self, config: &WarningsConfig) {
+        let listed = |codes: &[String], code: WarningCode| {
+            codes.iter().any(|c| c.as_str() == code.to_string())
+        };
+        self.warnings.retain(|w| !listed(&config.disable, w.code));
+        for warning in &mut self.warnings {
+            if listed(&config.promote, warning.code) {
+                warning.level = WarningLevel::Error;
+            }
+        }
+    }
+
     /// Check if there are any warnings.
     pub fn is_empty(&self) -> bool {
         self.warnings.is_empty()
@@ -155,21 +173,75 @@ pub fn generate_warnings(
     config: &CkConfig,
 ) -> Warnings {
     let mut warnings = Warnings::new();
+    let thresholds = &config.analysis;
 
-    // Check for oversized commit
-    const DEFAULT_SIZE_THRESHOLD: usize = 500;
-    if diff.stats.total_lines_changed() > DEFAULT_SIZE_THRESHOLD {
+    // Check for oversized commit, leaving out excluded files
+    let excluded: Vec<_> = diff
+        .files
+        .iter()
+        .filter(|f| {
+            thresholds
+                .path_override(&f.path)
+                .map(|o| o.exclude_from_totals)
+                .unwrap_or(false)
+        })
+        .collect();
+    let excluded_lines: usize = excluded.iter().map(|f| f.total_changes()).sum();
+    let total_lines = diff
+        .stats
+        .total_lines_changed()
+        .saturating_sub(excluded_lines);
+    if total_lines > thresholds.max_commit_lines {
         warnings.add(Warning {
             level: WarningLevel::Warning,
             code: WarningCode::OversizedCommit,
             message: format!(
-                "Commit is very large: {} lines changed",
-                diff.stats.total_lines_changed()
+                "Commit is very large: {} lines changed (limit {})",
+                total_lines, thresholds.max_commit_lines
+            ),
+            suggestion: Some("Consider splitting into smaller, focused commits".to_string()),
+        });
+    }
+    let total_files = diff.stats.files_changed.saturating_sub(excluded.len());
+    if total_files > thresholds.max_files {
+        warnings.add(Warning {
+            level: WarningLevel::Warning,
+            code: WarningCode::OversizedCommit,
+            message: format!(
+                "Commit touches {} file{} (limit {})",
+                total_files,
+                if total_files == 1 { "" } else { "s" },
+                thresholds.max_files
             ),
             suggestion: Some("Consider splitting into smaller, focused commits".to_string()),
         });
     }
 
+    // Check for large single-file changes
+    for file in diff.files.iter().filter(|f| !f.is_binary) {
+        let limit = thresholds
+            .path_override(&file.path)
+            .and_then(|o| o.max_file_lines)
+            .unwrap_or(thresholds.max_file_lines);
+        let lines = file.total_changes();
+        if lines > limit {
+            warnings.add(Warning {
+                level: WarningLevel::Warning,
+                code: WarningCode::LargeFile,
+                message: format!(
+                    "Large change in {}: {} lines (limit {})",
+                    crate::security::display_path(&file.path, config.ui.redact_paths),
+                    lines,
+                    limit
+                ),
+                suggestion: Some(
+                    "Consider splitting the change or raising analysis.max_file_lines for this path"
+                        .to_string(),
+                ),
+            });
+        }
+    }
+
     // Check for multiple packages
     let changed_packages: Vec<_> = packages.iter().filter(|p| p.has_changes).collect();
     if changed_packages.len() > 1 {
@@ -235,27 +307,20 @@ pub fn generate_warnings(
     }
 
     // Check for risky file changes
-    let risky_patterns = [
-        "secret",
-        "password",
-        "key",
-        "credential",
-        ".env",
-        "id_rsa",
-        "id_ed25519",
-    ];
     for file in files {
-        let path_str = file.to_string_lossy().to_lowercase();
-        for pattern in risky_patterns {
-            if path_str.contains(pattern) {
-                warnings.add(Warning {
-                    level: WarningLevel::Error,
-                    code: WarningCode::RiskyChanges,
-                    message: format!("Potentially sensitive file in commit: {}", file.display()),
-                    suggestion: Some("Make sure this file doesn't contain secrets".to_string()),
-                });
-                break;
-            }
+        if config.security.risky_paths.is_risky(file) {
+            warnings.add(Warning {
+                level: WarningLevel::Error,
+                code: WarningCode::RiskyChanges,
+                message: format!(
+                    "Potentially sensitive file in commit: {}",
+                    crate::security::display_path(file, config.ui.redact_paths)
+                ),
+                suggestion: Some(
+                    "Make sure this file doesn't contain secrets, or allow it in security.risky_paths"
+                        .to_string(),
+                ),
+            });
         }
     }
 
@@ -280,6 +345,40 @@ pub fn generate_warnings(
     warnings
 }
 
+/// Warn about related files whose changes are left out of the commit.
+///
+/// `related` is the unstaged diff of the files related to the staged ones.
+pub fn unstaged_warning(related: &DiffInfo, config: &CkConfig) -> Option<Warning> {
+    const SHOWN: usize = 5;
+
+    if related.files.is_empty() {
+        return None;
+    }
+    let mut files: Vec<String> = related
+        .files
+        .iter()
+        .take(SHOWN)
+        .map(|file| {
+            let path = crate::security::display_path(&file.path, config.ui.redact_paths);
+            match file.change_type {
+                crate::git::ChangeType::Added => format!("{} (untracked)", path),
+                _ if file.is_binary => path,
+                _ => format!("{} (+{} -{})", path, file.lines_added, file.lines_removed),
+            }
+        })
+        .collect();
+    if related.files.len() > SHOWN {
+        files.push(format!("{} more", related.files.len() - SHOWN));
+    }
+
+    Some(Warning {
+        level: WarningLevel::Warning,
+        code: WarningCode::UnstagedChanges,
+        message: format!("Related changes are not staged: {}", files.join(", ")),
+        suggestion: Some("Stage them with git add if they belong in this commit".to_string()),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -354,4 +453,152 @@ mod tests {
         assert!(warnings.has_errors());
         assert!(warnings.iter().any(|w| w.code == WarningCode::RiskyChanges));
     }
+
+    #[test]
+    fn test_generate_warnings_redacted() {
+        let diff = DiffInfo::empty();
+        let files = vec![PathBuf::from(".env.production")];
+        let mut config = CkConfig::default();
+        config.ui.redact_paths = true;
+
+        let warnings = generate_warnings(&diff, &files, &[], &config);
+        let risky = warnings
+            .iter()
+            .find(|w| w.code == WarningCode::RiskyChanges)
+            .unwrap();
+        assert!(!risky.message.contains(".env"));
+    }
+
+    #[test]
+    fn test_generate_warnings_thresholds() {
+        let file = |path: &str, lines: usize| crate::git::FileChange {
+            path: PathBuf::from(path),
+            change_type: crate::git::ChangeType::Modified,
+            lines_added: lines,
+            lines_removed: 0,
+            is_binary: false,
+            old_path: None,
+        };
+        let diff = DiffInfo {
+            files: vec![file("src/lib.rs", 120), file("Cargo.lock", 900)],
+            stats: DiffStats {
+                files_changed: 2,
+                lines_added: 1020,
+                lines_removed: 0,
+                binary_files: 0,
+            },
+            patches: HashMap::new(),
+        };
+
+        let mut config = CkConfig::default();
+        config.analysis.max_file_lines = 100;
+        let warnings = generate_warnings(&diff, &[], &[], &config);
+        let messages: Vec<_> = warnings.iter().map(|w| w.message.as_str()).collect();
+        assert!(messages.contains(&"Commit is very large: 1020 lines changed (limit 500)"));
+        assert!(messages.contains(&"Large change in src/lib.rs: 120 lines (limit 100)"));
+
+        config.analysis.paths.insert(
+            "*.lock".to_string(),
+            crate::config::AnalysisPathConfig {
+                max_file_lines: Some(usize::MAX),
+                exclude_from_totals: true,
+            },
+        );
+        config.analysis.paths.insert(
+            "src/**".to_string(),
+            crate::config::AnalysisPathConfig {
+                max_file_lines: Some(200),
+                exclude_from_totals: false,
+            },
+        );
+        config.analysis.max_files = 0;
+        let warnings = generate_warnings(&diff, &[], &[], &config);
+        let messages: Vec<_> = warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(messages, vec!["Commit touches 1 file (limit 0)"]);
+    }
+
+    #[test]
+    fn test_warnings_configure() {
+        let diff = DiffInfo {
+            files: vec![],
+            stats: DiffStats {
+                files_changed: 1,
+                lines_added: 0,
+                lines_removed: 0,
+                binary_files: 1,
+            },
+            patches: HashMap::new(),
+        };
+        let files = vec![
+            PathBuf::from("src/main.rs"),
+            PathBuf::from("tests/cli.rs"),
+            PathBuf::from("README.md"),
+        ];
+        let mut warnings = generate_warnings(&diff, &files, &[], &CkConfig::default());
+        assert!(warnings
+            .iter()
+            .any(|w| w.code == WarningCode::MixedConcerns));
+
+        warnings.configure(&WarningsConfig {
+            disable: vec!["mixed-concerns".to_string()],
+            promote: vec!["binary-files".to_string()],
+        });
+        assert!(!warnings
+            .iter()
+            .any(|w| w.code == WarningCode::MixedConcerns));
+        let binary = warnings
+            .iter()
+            .find(|w| w.code == WarningCode::BinaryFiles)
+            .unwrap();
+        assert_eq!(binary.level, WarningLevel::Error);
+    }
+
+    #[test]
+    fn test_unstaged_warning() {
+        let file = |path: &str, change_type, lines| crate::git::FileChange {
+            path: PathBuf::from(path),
+            change_type,
+            lines_added: lines,
+            lines_removed: 1,
+            is_binary: false,
+            old_path: None,
+        };
+        assert!(unstaged_warning(&DiffInfo::empty(), &CkConfig::default()).is_none());
+
+        let mut diff = DiffInfo::empty();
+        diff.files = vec![
+            file("src/parser.rs", crate::git::ChangeType::Modified, 3),
+            file("tests/parser_test.rs", crate::git::ChangeType::Added, 12),
+        ];
+        let warning = unstaged_warning(&diff, &CkConfig::default()).unwrap();
+        assert_eq!(warning.code, WarningCode::UnstagedChanges);
+        assert_eq!(
+            warning.message,
+            "Related changes are not staged: src/parser.rs (+3 -1), tests/parser_test.rs (untracked)"
+        );
+    }
+
+    #[test]
+    fn test_generate_warnings_risky_allow() {
+        let diff = DiffInfo::empty();
+        let files = vec![
+            PathBuf::from("tests/fixtures/.env"),
+            PathBuf::from("config/Secrets.yml"),
+            PathBuf::from(".env.example"),
+        ];
+        let mut config = CkConfig::default();
+        config.security.risky_paths.allow =
+            vec!["tests/fixtures/**".to_string(), "*.example".to_string()];
+
+        let warnings = generate_warnings(&diff, &files, &[], &config);
+        let risky: Vec<_> = warnings
+            .iter()
+            .filter(|w| w.code == WarningCode::RiskyChanges)
+            .map(|w| w.message.as_str())
+            .collect();
+        assert_eq!(
+            risky,
+            vec!["Potentially sensitive file in commit: config/Secrets.yml"]
+        );
+    }
 }
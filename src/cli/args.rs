@@ -9,7 +9,7 @@ use std::path::PathBuf;
 /// CK - Intelligent Git Commit Assistant
 ///
 /// A production-grade CLI tool for creating high-quality Git commits.
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(name = "ck")]
 #[command(author = "Eshan Roy")]
 #[command(version)]
@@ -20,6 +20,10 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
 
+    /// Commit options, for running commit without naming it
+    #[command(flatten)]
+    pub commit: CommitArgs,
+
     /// Stage modified and deleted files before committing
     #[arg(short, long, global = true)]
     pub all: bool,
@@ -36,6 +40,10 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub non_interactive: bool,
 
+    /// Accept suggested defaults at every prompt and skip confirmations
+    #[arg(short = 'y', long, global = true)]
+    pub yes: bool,
+
     /// Enable debug logging
     #[arg(short, long, global = true)]
     pub debug: bool,
@@ -47,6 +55,34 @@ pub struct Cli {
     /// Path to configuration file
     #[arg(short, long, global = true)]
     pub config: Option<PathBuf>,
+
+    /// Produce reproducible output: no colors, no confidence scores
+    #[arg(long, global = true)]
+    pub deterministic: bool,
+
+    /// Run against a linked worktree, by name, branch or path
+    #[arg(long, global = true, value_name = "NAME")]
+    pub worktree: Option<String>,
+
+    /// Stage and commit with the git binary instead of libgit2
+    #[arg(long, global = true)]
+    pub use_git_cli: bool,
+
+    /// When to prompt: only in a terminal (auto), always, or never
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = PromptMode::Auto)]
+    pub prompt: PromptMode,
+}
+
+/// When interactive prompts are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum PromptMode {
+    /// Prompt when stdin and stderr are terminals
+    #[default]
+    Auto,
+    /// Prompt even without a terminal
+    Always,
+    /// Never prompt; missing values must be passed as flags
+    Never,
 }
 
 /// Output format for CI and scripting.
@@ -56,13 +92,21 @@ pub enum OutputFormat {
     Text,
     /// JSON output for machine parsing
     Json,
+    /// GitHub Check Run payload (check command only)
+    CheckrunJson,
+    /// Azure Pipelines logging commands (check command only)
+    Azure,
+    /// Bitbucket Code Insights report (check command only)
+    BitbucketJson,
+    /// SARIF log for code scanning uploads (scan command only)
+    Sarif,
 }
 
 /// Available commands.
 #[derive(Subcommand, Debug, Clone)]
 pub enum Commands {
     /// Interactive commit creation (default command)
-    Commit(CommitArgs),
+    Commit(Box<CommitArgs>),
 
     /// Generate commit message from diff analysis
     Smart(SmartArgs),
@@ -73,9 +117,18 @@ pub enum Commands {
     /// Fix past commits interactively
     Fix(FixArgs),
 
+    /// Check the message a merge queue would land for a pull request
+    MergeCheck(MergeCheckArgs),
+
+    /// Run checks in CI with the detected platform's annotations
+    Ci(CiArgs),
+
     /// Manage git hooks
     Hooks(HooksArgs),
 
+    /// List, install and remove WASM plugins
+    Plugin(PluginArgs),
+
     /// Install ck as git-cz
     Install(InstallArgs),
 
@@ -84,38 +137,108 @@ pub enum Commands {
 
     /// Initialize ck configuration
     Init(InitArgs),
+
+    /// Run the language server for editor integration
+    Lsp,
+
+    /// Inspect and compare rule configurations
+    Rules(RulesArgs),
+
+    /// List worktrees with their branches and dirty state
+    Worktrees,
+
+    /// Work with stashed changes
+    Stash(StashArgs),
+
+    /// Cherry-pick a commit and adapt its message to this branch
+    CherryPick(CherryPickArgs),
+
+    /// Backport a commit to one or more release branches
+    Backport(BackportArgs),
+
+    /// Flag commits created while ck hooks were bypassed
+    Audit(AuditArgs),
+
+    /// Summarize the staged changes and what ck infers from them
+    Context(ContextArgs),
+
+    /// Manage how paths map to scopes
+    Scopes(ScopesArgs),
+
+    /// Show commit statistics
+    Stats(StatsArgs),
+
+    /// Suggest improvements for a range of commits as a markdown report
+    Review(ReviewArgs),
+
+    /// Verify a release range and emit an attestation
+    Verify(VerifyArgs),
+
+    /// Sign your unsigned commits in a range
+    Sign(SignArgs),
+
+    /// Scan staged changes, commits or the working tree for secrets
+    Scan(ScanArgs),
+
+    /// List the packages changed since a base revision and their dependents
+    Affected(AffectedArgs),
+
+    /// Compute the next version from the commits since the last tag
+    Bump(BumpArgs),
+
+    /// Render the commit conventions in ck.toml as a CONTRIBUTING.md section
+    StyleGuide(StyleGuideArgs),
+
+    /// Print example messages that pass the configured rules
+    Example(ExampleArgs),
 }
 
 /// Arguments for the commit command.
 #[derive(Parser, Debug, Default, Clone)]
 pub struct CommitArgs {
     /// Pre-fill the commit type
-    #[arg(short = 't', long)]
+    #[arg(short = 't', long, env = "CK_TYPE")]
     pub r#type: Option<String>,
 
     /// Pre-fill the scope
-    #[arg(short, long)]
+    #[arg(short, long, env = "CK_SCOPE")]
     pub scope: Option<String>,
 
     /// Pre-fill the subject
-    #[arg(short = 'm', long)]
+    #[arg(short = 'm', long, env = "CK_SUBJECT")]
     pub message: Option<String>,
 
     /// Pre-fill the body
-    #[arg(short, long)]
+    #[arg(short, long, env = "CK_BODY")]
     pub body: Option<String>,
 
     /// Mark as breaking change
-    #[arg(long)]
+    #[arg(long, env = "CK_BREAKING")]
     pub breaking: bool,
 
-    /// Add issue reference
+    /// Describe what breaks in a BREAKING CHANGE footer (implies --breaking)
+    #[arg(long, value_name = "TEXT")]
+    pub breaking_change: Option<String>,
+
+    /// Add a migration step to the breaking change (repeatable)
+    #[arg(long, value_name = "STEP", requires = "breaking_change")]
+    pub migration: Vec<String>,
+
+    /// Add a Deprecated footer, e.g. "old_fn, use new_fn" (repeatable)
+    #[arg(long, value_name = "TEXT")]
+    pub deprecated: Vec<String>,
+
+    /// Add an issue reference as a Refs trailer, e.g. 42 or PROJ-7
     #[arg(short, long)]
     pub issue: Option<String>,
 
-    /// Skip confirmation prompt
-    #[arg(short = 'y', long)]
-    pub yes: bool,
+    /// Add a trailer, e.g. Closes=#42 or "Co-authored-by=Ada <ada@example.com>" (repeatable)
+    #[arg(long, value_name = "TOKEN=VALUE")]
+    pub trailer: Vec<String>,
+
+    /// Pre-fill the message from a template in [templates]
+    #[arg(long, value_name = "NAME")]
+    pub template: Option<String>,
 
     /// Sign the commit with GPG
     #[arg(long)]
@@ -124,6 +247,101 @@ pub struct CommitArgs {
     /// Amend the previous commit
     #[arg(long)]
     pub amend: bool,
+
+    /// Record time spent, e.g. 2h30m or 1d 4h, as a Time-spent trailer
+    #[arg(long, value_name = "DURATION")]
+    pub spent: Option<String>,
+
+    /// Commit onto another branch without checking it out
+    #[arg(long, value_name = "BRANCH", conflicts_with = "amend")]
+    pub onto: Option<String>,
+
+    /// Interactively pick hunks to stage before writing the message
+    #[arg(short = 'p', long)]
+    pub patch: bool,
+
+    /// Stash unstaged and untracked changes while committing
+    #[arg(long, conflicts_with_all = ["only", "paths"])]
+    pub autostash: bool,
+
+    /// Commit only these paths, leaving other staged changes staged
+    #[arg(long, value_name = "PATH", conflicts_with = "amend")]
+    pub only: Vec<String>,
+
+    /// Paths to commit (same as --only)
+    #[arg(last = true, value_name = "PATHSPEC", conflicts_with = "amend")]
+    pub paths: Vec<String>,
+
+    /// Read pre-filled values as a JSON object from stdin
+    #[arg(long, conflicts_with = "patch")]
+    pub stdin_json: bool,
+
+    #[command(flatten)]
+    pub git: GitCommitArgs,
+}
+
+/// Options passed through to git when the commit is created.
+#[derive(Parser, Debug, Default, Clone)]
+pub struct GitCommitArgs {
+    /// Skip the pre-commit and commit-msg hooks
+    #[arg(short = 'n', long)]
+    pub no_verify: bool,
+
+    /// Override the commit author, as "Name <email>"
+    #[arg(long, value_name = "AUTHOR")]
+    pub author: Option<String>,
+
+    /// Override the author date
+    #[arg(long, value_name = "DATE")]
+    pub date: Option<String>,
+
+    /// How git cleans up the message (strip, whitespace, verbatim, ...)
+    #[arg(long, value_name = "MODE")]
+    pub cleanup: Option<String>,
+
+    /// Pass an extra argument to `git commit` (repeatable)
+    #[arg(long, value_name = "ARG", allow_hyphen_values = true)]
+    pub git_arg: Vec<String>,
+}
+
+impl GitCommitArgs {
+    /// Convert to the options used when creating the commit.
+    pub fn options(&self) -> crate::git::CommitOptions {
+        crate::git::CommitOptions {
+            no_verify: self.no_verify,
+            author: self.author.clone(),
+            date: self.date.clone(),
+            cleanup: self.cleanup.clone(),
+            git_args: self.git_arg.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl CommitArgs {
+    /// Fill in values not given as flags or environment variables.
+    pub fn fill_from(&mut self, prefill: crate::commit::Prefill) {
+        self.r#type = self.r#type.take().or(prefill.commit_type);
+        self.scope = self.scope.take().or(prefill.scope);
+        self.message = self.message.take().or(prefill.subject);
+        self.body = self.body.take().or(prefill.body);
+        self.breaking |= prefill.breaking;
+        if self.breaking_change.is_none() {
+            self.breaking_change = prefill.breaking_change;
+            self.migration = prefill.migration;
+        }
+        if self.deprecated.is_empty() {
+            self.deprecated = prefill.deprecated;
+        }
+        if self.trailer.is_empty() {
+            self.trailer = prefill.trailers;
+        }
+    }
+
+    /// Get the paths the commit is limited to, if any.
+    pub fn pathspec(&self) -> Vec<String> {
+        self.only.iter().chain(&self.paths).cloned().collect()
+    }
 }
 
 /// Arguments for the smart command.
@@ -140,6 +358,10 @@ pub struct SmartArgs {
     /// Include file names in the body
     #[arg(long)]
     pub include_files: bool,
+
+    /// Print the factors behind the suggestion
+    #[arg(long)]
+    pub explain: bool,
 }
 
 /// Arguments for the check command.
@@ -156,6 +378,75 @@ pub struct CheckArgs {
     /// Strict mode: treat warnings as errors
     #[arg(long)]
     pub strict: bool,
+
+    /// Show how outcomes would change under another configuration
+    #[arg(long, value_name = "CONFIG")]
+    pub with_config: Option<PathBuf>,
+
+    /// Reword failing commits in an assisted rebase
+    #[arg(long, conflicts_with = "with_config")]
+    pub fix: bool,
+
+    /// Let --fix rewrite commits that are already on a remote branch
+    #[arg(long, requires = "fix")]
+    pub force_rewrite: bool,
+
+    /// Let --fix sign your own signed commits again after rewording them
+    #[arg(long, requires = "fix")]
+    pub resign: bool,
+
+    /// Record the range's current findings in .ck-baseline.json
+    #[arg(long, conflicts_with_all = ["with_config", "fix"])]
+    pub update_baseline: bool,
+
+    /// Check a pull request title against header rules; without a value
+    /// it is read from CK_PR_TITLE or the GitHub Actions event payload
+    #[arg(
+        long,
+        value_name = "TITLE",
+        num_args = 0..=1,
+        conflicts_with_all = ["with_config", "fix", "update_baseline", "range"]
+    )]
+    pub pr_title: Option<Option<String>>,
+
+    /// In a shallow clone, fetch more history until the range base is reached
+    #[arg(long, conflicts_with = "pr_title")]
+    pub auto_deepen: bool,
+
+    /// Skip commits that passed before under the same rules, and record new
+    /// verdicts in git notes (refs/notes/ck-checks)
+    #[arg(long, conflicts_with = "pr_title")]
+    pub cache: bool,
+
+    /// Check the message in a file instead of a commit, e.g. in the
+    /// commit-msg hook
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["with_config", "fix", "update_baseline", "pr_title", "range"]
+    )]
+    pub message_file: Option<PathBuf>,
+
+    /// Check a message read from stdin instead of a commit
+    #[arg(
+        long,
+        conflicts_with_all = ["message_file", "with_config", "fix", "update_baseline", "pr_title", "range"]
+    )]
+    pub stdin: bool,
+
+    /// Check the message being committed, in .git/COMMIT_EDITMSG
+    #[arg(
+        long,
+        conflicts_with_all = ["message_file", "stdin", "with_config", "fix", "update_baseline", "pr_title", "range"]
+    )]
+    pub staged: bool,
+}
+
+impl CheckArgs {
+    /// Whether a message is checked instead of commits.
+    pub fn checks_message(&self) -> bool {
+        self.message_file.is_some() || self.stdin || self.staged
+    }
 }
 
 /// Arguments for the fix command.
@@ -172,6 +463,22 @@ pub struct FixArgs {
     /// Auto-fix without prompts (where possible)
     #[arg(long)]
     pub auto: bool,
+
+    /// Range to fix, e.g. main..HEAD, instead of the target
+    #[arg(long, value_name = "RANGE", conflicts_with = "count")]
+    pub range: Option<String>,
+
+    /// Write a JSON report of what was and wasn't fixed
+    #[arg(long, value_name = "FILE", requires = "auto")]
+    pub report: Option<PathBuf>,
+
+    /// Rewrite commits even if they are already on a remote branch
+    #[arg(long, requires = "auto")]
+    pub force_rewrite: bool,
+
+    /// Sign your own signed commits again after rewriting them
+    #[arg(long, requires = "auto")]
+    pub resign: bool,
 }
 
 /// Arguments for the hooks command.
@@ -194,6 +501,20 @@ pub enum HooksAction {
         /// Force overwrite existing hooks
         #[arg(short, long)]
         force: bool,
+
+        /// Keep existing hooks and run them before ck
+        #[arg(long, conflicts_with = "force")]
+        chain: bool,
+    },
+
+    /// Regenerate ck hooks installed from an older template
+    Upgrade,
+
+    /// Check installed hooks for problems
+    Doctor {
+        /// Regenerate hooks that have problems
+        #[arg(long)]
+        repair: bool,
     },
 
     /// Uninstall git hooks
@@ -217,6 +538,416 @@ pub enum HooksAction {
     },
 }
 
+/// Arguments for the rules command.
+#[derive(Parser, Debug, Clone)]
+pub struct RulesArgs {
+    /// Rules action to perform
+    #[command(subcommand)]
+    pub action: RulesAction,
+}
+
+/// Rules actions.
+#[derive(Subcommand, Debug, Clone)]
+pub enum RulesAction {
+    /// List active rules with their severity, settings and source
+    List,
+
+    /// Compare validation outcomes of two configurations over a range
+    Diff {
+        /// Base configuration
+        config_a: PathBuf,
+
+        /// Configuration to compare against the base
+        config_b: PathBuf,
+
+        /// Commit or range to validate
+        #[arg(long, default_value = "HEAD")]
+        range: String,
+    },
+}
+
+/// Arguments for the cherry-pick command.
+#[derive(Parser, Debug, Clone)]
+pub struct CherryPickArgs {
+    /// Commit to pick
+    pub reference: String,
+
+    /// Sign the commit with GPG
+    #[arg(long)]
+    pub sign: bool,
+}
+
+/// Arguments for the backport command.
+#[derive(Parser, Debug, Clone)]
+pub struct BackportArgs {
+    /// Commit to backport
+    pub reference: String,
+
+    /// Target branches
+    #[arg(long, required = true, value_delimiter = ',', value_name = "BRANCH")]
+    pub to: Vec<String>,
+
+    /// Sign the commits with GPG
+    #[arg(long)]
+    pub sign: bool,
+}
+
+/// Arguments for the scopes command.
+#[derive(Parser, Debug, Clone)]
+pub struct ScopesArgs {
+    /// Scopes action to perform
+    #[command(subcommand)]
+    pub action: ScopesAction,
+}
+
+/// Arguments for the plugin command.
+#[derive(Parser, Debug, Clone)]
+pub struct PluginArgs {
+    /// Plugin action to perform
+    #[command(subcommand)]
+    pub action: PluginAction,
+}
+
+/// Plugin actions.
+#[derive(Subcommand, Debug, Clone)]
+pub enum PluginAction {
+    /// List installed plugins with their versions and permissions
+    List,
+
+    /// Show a plugin's manifest, permissions and checksum
+    Info {
+        /// Plugin name
+        name: String,
+    },
+
+    /// Install a plugin from a directory or URL holding plugin.toml and plugin.wasm
+    Install {
+        /// Local directory or http(s) URL of the plugin
+        source: String,
+
+        /// Expected SHA-256 of plugin.wasm
+        #[arg(long)]
+        sha256: Option<String>,
+
+        /// Minisign public key plugin.wasm must be signed with (default: plugins.minisign_key)
+        #[arg(long)]
+        minisign_key: Option<String>,
+
+        /// Replace an installed plugin with the same name
+        #[arg(long)]
+        force: bool,
+
+        /// Add the plugin to plugins.enabled_plugins in ck.toml
+        #[arg(long)]
+        enable: bool,
+    },
+
+    /// Remove an installed plugin and disable it in ck.toml
+    Remove {
+        /// Plugin name
+        name: String,
+    },
+}
+
+/// Scopes actions.
+#[derive(Subcommand, Debug, Clone)]
+pub enum ScopesAction {
+    /// Edit the glob-to-scope mappings in scope-map.toml
+    Map {
+        #[command(subcommand)]
+        action: ScopeMapAction,
+    },
+}
+
+/// Arguments for the stats command.
+#[derive(Parser, Debug, Clone)]
+pub struct StatsArgs {
+    /// Commit range to summarize (defaults to all of HEAD's history)
+    pub range: Option<String>,
+
+    /// Aggregate Time-spent trailers by author, type and scope
+    #[arg(long)]
+    pub time: bool,
+}
+
+/// Arguments for the review command.
+#[derive(Parser, Debug, Clone)]
+pub struct ReviewArgs {
+    /// Range to review, e.g. main..HEAD
+    pub range: String,
+
+    /// Write the report to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Scope map actions.
+#[derive(Subcommand, Debug, Clone)]
+pub enum ScopeMapAction {
+    /// Map files matching a glob to a scope
+    Add {
+        /// Glob pattern relative to the repository root
+        glob: String,
+
+        /// Scope for matching files
+        scope: String,
+
+        /// Give the mapping the highest priority instead of the lowest
+        #[arg(long)]
+        first: bool,
+    },
+
+    /// List mappings in priority order
+    List,
+}
+
+/// Arguments for the context command.
+#[derive(Parser, Debug, Clone)]
+pub struct ContextArgs {
+    /// Print the factors behind the suggested type and scope
+    #[arg(long)]
+    pub explain: bool,
+}
+
+/// Arguments for the audit command.
+#[derive(Parser, Debug, Clone)]
+pub struct AuditArgs {
+    /// Commit or range to audit
+    #[arg(default_value = "HEAD")]
+    pub target: String,
+}
+
+/// Arguments for the bump command.
+#[derive(Parser, Debug, Clone)]
+pub struct BumpArgs {
+    /// Create an annotated tag for the next version on HEAD
+    #[arg(long)]
+    pub tag: bool,
+
+    /// Tag message (defaults to "Release <tag>")
+    #[arg(short, long, requires = "tag")]
+    pub message: Option<String>,
+}
+
+/// Arguments for the style-guide command.
+#[derive(Parser, Debug, Clone)]
+pub struct StyleGuideArgs {
+    /// Update the generated section of this file instead of printing it
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Fail if the section in --output is out of date instead of updating it
+    #[arg(long, requires = "output")]
+    pub check: bool,
+}
+
+/// Arguments for the example command.
+#[derive(Parser, Debug, Clone)]
+pub struct ExampleArgs {
+    /// Commit type of the examples (defaults to each allowed type in turn)
+    #[arg(short = 't', long = "type")]
+    pub commit_type: Option<String>,
+
+    /// Scope of the examples
+    #[arg(short, long)]
+    pub scope: Option<String>,
+
+    /// Make the examples breaking changes
+    #[arg(long)]
+    pub breaking: bool,
+
+    /// Number of examples
+    #[arg(short = 'n', long, default_value_t = 1)]
+    pub count: usize,
+}
+
+/// Arguments for the verify command.
+#[derive(Parser, Debug, Clone)]
+pub struct VerifyArgs {
+    /// Range to verify, e.g. v1.0.0..v1.1.0
+    pub range: String,
+
+    /// Require every commit to be signed
+    #[arg(long)]
+    pub require_signed: bool,
+
+    /// Sign the attestation with git's signing key
+    #[arg(long)]
+    pub sign: bool,
+
+    /// Emit an in-toto statement for provenance chains
+    #[arg(long)]
+    pub in_toto: bool,
+
+    /// Sign the written attestation with cosign, keyless unless --cosign-key is set
+    #[arg(long, requires = "output")]
+    pub cosign: bool,
+
+    /// Key for cosign, e.g. cosign.key or a KMS URI
+    #[arg(long, value_name = "KEY", requires = "cosign")]
+    pub cosign_key: Option<String>,
+
+    /// Write the attestation to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for the sign command.
+#[derive(Parser, Debug, Clone)]
+pub struct SignArgs {
+    /// Range to sign, ending at HEAD, e.g. origin/release/1.2..HEAD
+    #[arg(long, value_name = "RANGE")]
+    pub range: String,
+
+    /// Rewrite commits even if they are already on a remote branch
+    #[arg(long)]
+    pub force_rewrite: bool,
+}
+
+/// Arguments for the scan command.
+#[derive(Parser, Debug, Clone)]
+pub struct ScanArgs {
+    /// Scan the changes made by each commit in a range, e.g. main..HEAD
+    #[arg(long, value_name = "RANGE", conflicts_with = "all")]
+    pub range: Option<String>,
+
+    /// Scan every file in the working tree instead of the staged changes
+    #[arg(long)]
+    pub all: bool,
+
+    /// Write the JSON (or --format sarif) report to a file
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for the affected command.
+#[derive(Parser, Debug, Clone)]
+pub struct AffectedArgs {
+    /// Use the commits since the merge base with this revision instead of
+    /// the staged changes, e.g. origin/main
+    #[arg(long, value_name = "REV")]
+    pub base: Option<String>,
+
+    /// Only list packages with changed files, not their dependents
+    #[arg(long)]
+    pub direct: bool,
+}
+
+/// Arguments for the ci command.
+#[derive(Parser, Debug, Clone)]
+pub struct CiArgs {
+    /// CI action to perform
+    #[command(subcommand)]
+    pub action: CiAction,
+}
+
+/// CI actions.
+#[derive(Subcommand, Debug, Clone)]
+pub enum CiAction {
+    /// Check the build's commits, annotated for the detected platform
+    Validate {
+        /// Commit or range to check (default: the pull request's commits, or HEAD)
+        target: Option<String>,
+
+        /// Strict mode: treat warnings as errors
+        #[arg(long)]
+        strict: bool,
+
+        /// In a shallow clone, fetch more history until the range base is reached
+        #[arg(long)]
+        auto_deepen: bool,
+    },
+}
+
+/// Arguments for the merge-check command.
+#[derive(Parser, Debug, Clone)]
+pub struct MergeCheckArgs {
+    /// Branch the pull request lands on (default: the CI target branch)
+    #[arg(long)]
+    pub target: Option<String>,
+
+    /// Head of the pull request
+    #[arg(long, default_value = "HEAD")]
+    pub head: String,
+
+    /// How the queue lands the pull request (default: rules.queue.strategy)
+    #[arg(long)]
+    pub strategy: Option<QueueStrategyArg>,
+
+    /// Pull request title (default: CK_PR_TITLE or the GitHub event payload)
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Pull request description (default: the GitHub event payload)
+    #[arg(long)]
+    pub body: Option<String>,
+
+    /// Pull request number (default: the GitHub event payload)
+    #[arg(long)]
+    pub number: Option<u64>,
+
+    /// Source branch named in merge commit subjects (default: the GitHub event payload)
+    #[arg(long)]
+    pub branch: Option<String>,
+
+    /// Strict mode: treat warnings as errors
+    #[arg(long)]
+    pub strict: bool,
+}
+
+/// How a merge queue lands a pull request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum QueueStrategyArg {
+    /// One commit with a message composed by the platform
+    Squash,
+    /// A merge commit on top of the pull request's commits
+    Merge,
+    /// The pull request's commits, replayed onto the target
+    Rebase,
+}
+
+/// Arguments for the stash command.
+#[derive(Parser, Debug, Clone)]
+pub struct StashArgs {
+    /// Stash action to perform
+    #[command(subcommand)]
+    pub action: StashAction,
+}
+
+/// Stash actions.
+#[derive(Subcommand, Debug, Clone)]
+pub enum StashAction {
+    /// Turn a stash into a commit without touching the working tree
+    Commit {
+        /// Stash to commit (`stash@{N}` or `N`)
+        #[arg(default_value = "0")]
+        stash: String,
+
+        /// Pre-fill the commit type
+        #[arg(short = 't', long)]
+        r#type: Option<String>,
+
+        /// Pre-fill the scope
+        #[arg(short, long)]
+        scope: Option<String>,
+
+        /// Pre-fill the subject
+        #[arg(short = 'm', long)]
+        message: Option<String>,
+
+        /// Keep the stash after committing
+        #[arg(long)]
+        keep: bool,
+
+        /// Sign the commit with GPG
+        #[arg(long)]
+        sign: bool,
+
+        #[command(flatten)]
+        git: GitCommitArgs,
+    },
+}
+
 /// Arguments for the install command.
 #[derive(Parser, Debug, Clone)]
 pub struct InstallArgs {
@@ -239,6 +970,16 @@ pub struct InitArgs {
     /// Configuration preset
     #[arg(long)]
     pub preset: Option<ConfigPreset>,
+
+    /// Translate a commitlint configuration; without a value it is looked
+    /// up in the current directory
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        conflicts_with = "preset"
+    )]
+    pub from_commitlint: Option<Option<PathBuf>>,
 }
 
 /// Configuration presets for init.
@@ -260,6 +1001,47 @@ impl Cli {
         self.ci || self.non_interactive
     }
 
+    /// Check if prompts may be shown.
+    ///
+    /// With `--prompt=auto` this needs stdin and stderr to be terminals, so
+    /// piped runs and IDE terminals without a PTY fall back to flags.
+    pub fn is_interactive(&self) -> bool {
+        if self.is_ci_mode() {
+            return false;
+        }
+        match self.prompt {
+            PromptMode::Always => true,
+            PromptMode::Never => false,
+            PromptMode::Auto => {
+                use std::io::IsTerminal;
+                std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+            }
+        }
+    }
+
+    /// Check if a commit is previewed as a JSON document.
+    ///
+    /// The document is meant for other tools, so `commit` and `smart` don't
+    /// prompt in this mode.
+    pub fn is_json_dry_run(&self) -> bool {
+        self.dry_run && self.format == Some(OutputFormat::Json)
+    }
+
+    /// Why prompts are off, for error messages.
+    pub fn no_prompt_reason(&self) -> &'static str {
+        if self.ci {
+            "--ci is set"
+        } else if self.non_interactive {
+            "--non-interactive is set"
+        } else if self.prompt == PromptMode::Never {
+            "--prompt=never is set"
+        } else if self.is_json_dry_run() {
+            "--dry-run with --format json is set"
+        } else {
+            "not running in a terminal"
+        }
+    }
+
     /// Check if any output should be produced.
     pub fn should_output(&self) -> bool {
         !self.dry_run || self.debug
@@ -269,7 +1051,7 @@ impl Cli {
     pub fn effective_command(&self) -> Commands {
         self.command
             .clone()
-            .unwrap_or(Commands::Commit(CommitArgs::default()))
+            .unwrap_or_else(|| Commands::Commit(Box::new(self.commit.clone())))
     }
 }
 
@@ -279,6 +1061,17 @@ impl Default for CheckArgs {
             target: "HEAD".to_string(),
             range: false,
             strict: false,
+            with_config: None,
+            fix: false,
+            force_rewrite: false,
+            resign: false,
+            update_baseline: false,
+            pr_title: None,
+            auto_deepen: false,
+            cache: false,
+            message_file: None,
+            stdin: false,
+            staged: false,
         }
     }
 }
@@ -289,6 +1082,10 @@ impl Default for FixArgs {
             target: "HEAD".to_string(),
             count: None,
             auto: false,
+            range: None,
+            report: None,
+            force_rewrite: false,
+            resign: false,
         }
     }
 }
@@ -326,24 +1123,233 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_ci_validate() {
+        let args = Cli::parse_from(["ck", "ci", "validate"]);
+        match args.command {
+            Some(Commands::Ci(CiArgs {
+                action: CiAction::Validate { auto_deepen, .. },
+            })) => assert!(!auto_deepen),
+            _ => panic!("Expected Ci command"),
+        }
+
+        let args = Cli::parse_from(["ck", "ci", "validate", "--auto-deepen"]);
+        assert!(matches!(
+            args.command,
+            Some(Commands::Ci(CiArgs {
+                action: CiAction::Validate {
+                    auto_deepen: true,
+                    ..
+                },
+            }))
+        ));
+    }
+
     #[test]
     fn test_parse_hooks() {
         let args = Cli::parse_from(["ck", "hooks", "install", "--force"]);
         assert!(matches!(args.command, Some(Commands::Hooks(_))));
     }
 
+    #[test]
+    fn test_parse_commit_pathspec() {
+        let args = Cli::parse_from([
+            "ck",
+            "commit",
+            "--only",
+            "src/a.rs",
+            "--",
+            "docs",
+            "README.md",
+        ]);
+        if let Some(Commands::Commit(commit)) = args.command {
+            assert_eq!(commit.pathspec(), vec!["src/a.rs", "docs", "README.md"]);
+            assert!(!commit.patch);
+        } else {
+            panic!("Expected Commit command");
+        }
+    }
+
+    #[test]
+    fn test_parse_commit_patch() {
+        let args = Cli::parse_from(["ck", "commit", "--patch", "--", "src"]);
+        if let Some(Commands::Commit(commit)) = args.command {
+            assert!(commit.patch);
+            assert_eq!(commit.pathspec(), vec!["src"]);
+        } else {
+            panic!("Expected Commit command");
+        }
+
+        let args = Cli::parse_from(["ck", "commit", "-p"]);
+        assert!(matches!(args.command, Some(Commands::Commit(c)) if c.patch));
+        assert!(Cli::try_parse_from(["ck", "commit", "--patch", "--stdin-json"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rules_diff() {
+        let args = Cli::parse_from([
+            "ck",
+            "rules",
+            "diff",
+            "ck.toml",
+            "strict.toml",
+            "--range",
+            "HEAD~5..HEAD",
+        ]);
+        match args.command {
+            Some(Commands::Rules(RulesArgs {
+                action:
+                    RulesAction::Diff {
+                        config_b, range, ..
+                    },
+            })) => {
+                assert_eq!(config_b, PathBuf::from("strict.toml"));
+                assert_eq!(range, "HEAD~5..HEAD");
+            }
+            _ => panic!("Expected rules diff command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_lsp() {
+        let args = Cli::parse_from(["ck", "lsp"]);
+        assert!(matches!(args.command, Some(Commands::Lsp)));
+    }
+
+    #[test]
+    fn test_parse_worktree_flag() {
+        let args = Cli::parse_from(["ck", "check", "HEAD", "--worktree", "docs"]);
+        assert_eq!(args.worktree.as_deref(), Some("docs"));
+
+        let args = Cli::parse_from(["ck", "worktrees"]);
+        assert!(matches!(args.command, Some(Commands::Worktrees)));
+    }
+
+    #[test]
+    fn test_parse_stash_commit() {
+        let args = Cli::parse_from(["ck", "stash", "commit", "2", "-t", "fix"]);
+        match args.command {
+            Some(Commands::Stash(StashArgs {
+                action: StashAction::Commit { stash, r#type, .. },
+            })) => {
+                assert_eq!(stash, "2");
+                assert_eq!(r#type.as_deref(), Some("fix"));
+            }
+            _ => panic!("Expected stash commit"),
+        }
+    }
+
+    #[test]
+    fn test_parse_backport() {
+        let args = Cli::parse_from([
+            "ck",
+            "backport",
+            "abc123",
+            "--to",
+            "release/1.x,release/2.x",
+        ]);
+        match args.command {
+            Some(Commands::Backport(args)) => {
+                assert_eq!(args.reference, "abc123");
+                assert_eq!(args.to, vec!["release/1.x", "release/2.x"]);
+            }
+            _ => panic!("Expected backport"),
+        }
+    }
+
+    #[test]
+    fn test_deterministic_flag() {
+        let args = Cli::parse_from(["ck", "check", "HEAD", "--deterministic"]);
+        assert!(args.deterministic);
+    }
+
     #[test]
     fn test_global_flags() {
         let args = Cli::parse_from(["ck", "--ci", "--dry-run", "commit"]);
+        assert!(!args.deterministic);
         assert!(args.ci);
         assert!(args.dry_run);
         assert!(args.is_ci_mode());
     }
 
+    #[test]
+    fn test_fill_from_prefill() {
+        let args = Cli::parse_from(["ck", "commit", "--stdin-json", "-t", "fix"]);
+        let mut args = match args.command {
+            Some(Commands::Commit(args)) => args,
+            _ => panic!("Expected Commit command"),
+        };
+
+        args.fill_from(crate::commit::Prefill {
+            commit_type: Some("feat".to_string()),
+            subject: Some("add prefill".to_string()),
+            breaking: true,
+            ..Default::default()
+        });
+        assert_eq!(args.r#type.as_deref(), Some("fix"));
+        assert_eq!(args.message.as_deref(), Some("add prefill"));
+        assert!(args.breaking);
+    }
+
+    #[test]
+    fn test_global_yes() {
+        assert!(Cli::parse_from(["ck", "-a", "--yes", "commit", "-m", "subject"]).yes);
+        assert!(Cli::parse_from(["ck", "commit", "-y"]).yes);
+        assert!(Cli::parse_from(["ck", "backport", "abc", "--to", "main", "-y"]).yes);
+    }
+
+    #[test]
+    fn test_prompt_mode() {
+        let args = Cli::parse_from(["ck", "commit"]);
+        assert_eq!(args.prompt, PromptMode::Auto);
+
+        let args = Cli::parse_from(["ck", "commit", "--prompt", "always"]);
+        assert!(args.is_interactive());
+
+        let args = Cli::parse_from(["ck", "--prompt=never", "commit"]);
+        assert!(!args.is_interactive());
+        assert_eq!(args.no_prompt_reason(), "--prompt=never is set");
+
+        let args = Cli::parse_from(["ck", "--ci", "--prompt=always", "commit"]);
+        assert!(!args.is_interactive());
+    }
+
     #[test]
     fn test_default_command() {
         let args = Cli::parse_from(["ck"]);
         assert!(args.command.is_none());
         assert!(matches!(args.effective_command(), Commands::Commit(_)));
+
+        let args = Cli::parse_from(["ck", "-a", "--yes", "-m", "subject"]);
+        match args.effective_command() {
+            Commands::Commit(commit) => assert_eq!(commit.message.as_deref(), Some("subject")),
+            _ => panic!("Expected Commit command"),
+        }
+    }
+
+    #[test]
+    fn test_git_passthrough() {
+        let args = Cli::parse_from([
+            "ck",
+            "commit",
+            "--no-verify",
+            "--author",
+            "Ada <ada@example.com>",
+            "--git-arg",
+            "--trailer=Reviewed-by: Bob",
+            "--git-arg=--allow-empty",
+        ]);
+        match args.command {
+            Some(Commands::Commit(commit)) => {
+                let options = commit.git.options();
+                assert!(options.no_verify);
+                assert_eq!(options.author.as_deref(), Some("Ada <ada@example.com>"));
+                assert_eq!(
+                    options.git_args,
+                    vec!["--trailer=Reviewed-by: Bob", "--allow-empty"]
+                );
+            }
+            _ => panic!("Expected Commit command"),
+        }
     }
 }
@@ -6,34 +6,88 @@
 use crate::config::CkConfig;
 use crate::error::Result;
 
-use super::args::{Cli, Commands, HooksAction};
+use super::args::{
+    Cli, Commands, HooksAction, OutputFormat, PluginAction, RulesAction, ScopeMapAction,
+    ScopesAction, StashAction,
+};
 
 /// Run the CLI with the given arguments.
 pub fn run(cli: Cli) -> Result<()> {
+    // Switch to the requested worktree before anything reads the repository
+    if let Some(ref name) = cli.worktree {
+        let repo = crate::git::open_repo()?;
+        let path = crate::git::worktree::find_worktree(&repo, name)?;
+        tracing::debug!("Switching to worktree {}", path.display());
+        std::env::set_current_dir(&path)?;
+    }
+
     // Load configuration
-    let config = if let Some(config_path) = &cli.config {
+    let mut config = if let Some(config_path) = &cli.config {
         CkConfig::load_from(config_path)?
     } else {
         CkConfig::load()?
     };
 
+    // Writes go through the git binary when libgit2 would skip filters or hooks
+    config.git.use_cli |= cli.use_git_cli;
+
+    // Colors are off when disabled in config or for reproducible output
+    if cli.deterministic || !config.ui.color {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+
+    // Progress would end up in logs, JSON output or the LSP session
+    crate::progress::set_enabled(
+        !cli.is_ci_mode()
+            && !cli.deterministic
+            && matches!(cli.format, None | Some(OutputFormat::Text))
+            && !matches!(cli.command, Some(Commands::Lsp)),
+    );
+
     // Dispatch to the appropriate command handler
     match cli.effective_command() {
-        Commands::Commit(args) => run_commit(&cli, &config, args),
+        Commands::Commit(args) => run_commit(&cli, &config, *args),
         Commands::Smart(args) => run_smart(&cli, &config, args),
         Commands::Check(args) => run_check(&cli, &config, args),
         Commands::Fix(args) => run_fix(&cli, &config, args),
+        Commands::MergeCheck(args) => run_merge_check(&cli, &config, args),
+        Commands::Ci(args) => run_ci(&cli, &config, args),
         Commands::Hooks(args) => run_hooks(&cli, &config, args),
+        Commands::Plugin(args) => run_plugin(&cli, &config, args),
         Commands::Install(args) => run_install(&cli, args),
         Commands::Version => run_version(),
         Commands::Init(args) => run_init(&cli, args),
+        Commands::Lsp => run_lsp(&config),
+        Commands::Rules(args) => run_rules(&cli, &config, args),
+        Commands::Worktrees => run_worktrees(&cli),
+        Commands::Stash(args) => run_stash(&cli, &config, args),
+        Commands::CherryPick(args) => run_cherry_pick(&cli, &config, args),
+        Commands::Backport(args) => run_backport(&cli, &config, args),
+        Commands::Audit(args) => run_audit(&cli, args),
+        Commands::Context(args) => run_context(&config, args),
+        Commands::Scopes(args) => run_scopes(args),
+        Commands::Stats(args) => run_stats(&cli, args),
+        Commands::Review(args) => run_review(&cli, &config, args),
+        Commands::Verify(args) => run_verify(&cli, &config, args),
+        Commands::Sign(args) => run_sign(&cli, args),
+        Commands::Scan(args) => run_scan(&cli, &config, args),
+        Commands::Affected(args) => run_affected(&cli, &config, args),
+        Commands::Bump(args) => run_bump(&cli, args),
+        Commands::StyleGuide(args) => run_style_guide(&config, args),
+        Commands::Example(args) => run_example(&cli, &config, args),
     }
 }
 
 /// Run the commit command.
-fn run_commit(cli: &Cli, config: &CkConfig, args: super::args::CommitArgs) -> Result<()> {
+fn run_commit(cli: &Cli, config: &CkConfig, mut args: super::args::CommitArgs) -> Result<()> {
+    use crate::commit::trailers::Trailer;
     use crate::commit::CommitBuilder;
 
+    if args.stdin_json {
+        args.fill_from(crate::commit::Prefill::from_stdin()?);
+    }
+
     tracing::debug!("Running commit command with args: {:?}", args);
 
     let mut builder = CommitBuilder::new(config.clone());
@@ -54,40 +108,165 @@ fn run_commit(cli: &Cli, config: &CkConfig, args: super::args::CommitArgs) -> Re
     if args.breaking {
         builder = builder.with_breaking(true);
     }
+    if let Some(ref description) = args.breaking_change {
+        let change = args.migration.iter().fold(
+            crate::commit::breaking::BreakingChange::new(description),
+            |change, step| change.with_step(step),
+        );
+        builder = builder.with_breaking_change(change);
+    }
+    for item in &args.deprecated {
+        builder = builder.with_deprecation(item);
+    }
+    if let Some(ref issue) = args.issue {
+        let trailer = Trailer::issue(issue);
+        builder = builder.with_trailer(&trailer.token, &trailer.value);
+    }
+    for arg in &args.trailer {
+        let trailer = Trailer::from_arg(arg)?;
+        builder = builder.with_trailer(&trailer.token, &trailer.value);
+    }
+    if let Some(ref branch) = args.onto {
+        builder = builder.with_onto(branch);
+    }
+    builder = builder.with_git_options(args.git.options());
+    let template = match args.template {
+        Some(ref name) => Some(find_template(config, name)?),
+        None => None,
+    };
+    if let Some(ref template) = template {
+        builder = builder.with_template(template.clone())?;
+    }
+    if let Some(ref spent) = args.spent {
+        use crate::commit::time_spent::{format_duration, parse_duration, TIME_SPENT_TRAILER};
+        let minutes = parse_duration(spent)?;
+        builder = builder.with_trailer(TIME_SPENT_TRAILER, &format_duration(minutes));
+    }
 
     // Stage all if requested
     if cli.all {
-        crate::git::stage_all()?;
+        crate::git::stage_all(config.git.use_cli)?;
     }
 
+    // Pick hunks interactively, limited to the pathspec if one is given,
+    // before --autostash sets the unstaged ones aside
+    let pathspec = args.pathspec();
+    if args.patch {
+        if !cli.is_interactive() {
+            return Err(no_prompt(cli, "a terminal for --patch"));
+        }
+        if !cli.is_json_dry_run() {
+            CommitBuilder::stage_hunks(&pathspec, cli.dry_run)?;
+        }
+    }
+
+    // Narrow the index to the given paths for a partial commit
+    let repo = if pathspec.is_empty() || args.patch {
+        None
+    } else {
+        Some(crate::git::open_repo()?)
+    };
+    let partial = match repo {
+        Some(ref repo) => Some(crate::git::partial::PartialIndex::stage_only(
+            repo, &pathspec,
+        )?),
+        None => None,
+    };
+
+    // Set aside working tree noise so only the staged diff is seen
+    let autostash = if args.autostash {
+        let repo = crate::git::open_repo()?;
+        Some(crate::git::stash::AutoStash::push(repo.workdir())?)
+    } else {
+        None
+    };
+
     // Run the interactive builder or non-interactive commit
-    if cli.is_ci_mode() || cli.non_interactive {
-        builder.commit_non_interactive(cli.dry_run, args.sign)
+    let builder = builder.with_defaults_accepted(cli.yes);
+    let has_type =
+        args.r#type.is_some() || template.as_ref().is_some_and(|t| t.commit_type.is_some());
+    let has_subject =
+        args.message.is_some() || template.as_ref().is_some_and(|t| t.subject.is_some());
+    let result = if cli.is_json_dry_run() {
+        require_header_flags(cli, has_type || cli.yes, has_subject)
+            .and_then(|_| builder.preview_non_interactive())
+            .map(|(message, validation)| print_message_json(&message, None, &validation))
+    } else if cli.is_interactive() {
+        builder.run_interactive(cli.dry_run, args.sign, args.amend)
     } else {
-        builder.run_interactive(cli.dry_run, args.yes, args.sign, args.amend)
+        require_header_flags(cli, has_type || cli.yes, has_subject)
+            .and_then(|_| builder.commit_non_interactive(cli.dry_run, args.sign))
+    };
+
+    crate::git::partial::restore_after_commit(partial, autostash, result.is_ok() && !cli.dry_run)?;
+
+    result
+}
+
+/// The template `name` from `[templates]`.
+fn find_template(config: &CkConfig, name: &str) -> Result<crate::config::MessageTemplate> {
+    match config.templates.get(name) {
+        Some(template) => Ok(template.clone()),
+        None => {
+            let known: Vec<&str> = config.templates.keys().map(String::as_str).collect();
+            Err(crate::error::CkError::WithContext {
+                code: "template-unknown",
+                context: "commit".to_string(),
+                message: format!(
+                    "No template named '{}' in [templates] (known: {})",
+                    name,
+                    if known.is_empty() {
+                        "none".to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                ),
+            })
+        }
     }
 }
 
 /// Run the smart command.
 fn run_smart(cli: &Cli, config: &CkConfig, args: super::args::SmartArgs) -> Result<()> {
+    use crate::commit::CommitMessage;
     use crate::smart::SmartCommit;
 
     tracing::debug!("Running smart command with args: {:?}", args);
 
     // Stage all if requested
     if cli.all {
-        crate::git::stage_all()?;
+        crate::git::stage_all(config.git.use_cli)?;
     }
 
-    let smart = SmartCommit::new(config.clone());
+    let smart = SmartCommit::new(config.clone()).with_confidence(!cli.deterministic);
     let message = smart.generate(args.max_bullets, args.include_files)?;
 
-    if cli.is_ci_mode() || cli.non_interactive {
+    if args.explain {
+        let context = crate::analysis::RepositoryContext::from_current_repo_with_config(config)?;
+        eprintln!(
+            "{}\n",
+            crate::smart::Explanation::build(&context, config).render()
+        );
+    }
+
+    if cli.is_json_dry_run() {
+        let commit = CommitMessage::parse_with(&message.format(), config)?;
+        let validation = crate::rules::RuleEngine::new(config.clone()).validate(&commit);
+        let confidence = (!cli.deterministic).then_some(message.confidence);
+        print_message_json(&commit, confidence, &validation);
+        return Ok(());
+    }
+
+    if !cli.is_interactive() || cli.yes {
         if cli.dry_run {
             println!("{}", message.format());
             Ok(())
         } else {
-            crate::git::create_commit(&message.format(), false)?;
+            let options = crate::git::CommitOptions {
+                use_cli: config.git.use_cli,
+                ..Default::default()
+            };
+            crate::git::create_commit_with_options(&message.format(), false, &options)?;
             Ok(())
         }
     } else {
@@ -95,37 +274,220 @@ fn run_smart(cli: &Cli, config: &CkConfig, args: super::args::SmartArgs) -> Resu
     }
 }
 
+/// Print the message a dry run would commit as JSON, for tools wrapping ck.
+///
+/// `confidence` is only known for generated messages; the key is left out
+/// for other messages and under `--deterministic`.
+fn print_message_json(
+    message: &crate::commit::CommitMessage,
+    confidence: Option<f64>,
+    validation: &crate::rules::ValidationResult,
+) {
+    let mut json = serde_json::json!({
+        "type": message.commit_type,
+        "scope": message.scope,
+        "subject": message.subject,
+        "body": message.body,
+        "footer": message.footer,
+        "breaking": message.is_breaking,
+        "header": message.header(),
+        "message": message.format(),
+        "errors": validation.errors,
+        "warnings": validation.warnings,
+        "config_hash": validation.config_hash,
+    });
+    if let Some(confidence) = confidence {
+        json["confidence"] = confidence.into();
+    }
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json).unwrap_or_default()
+    );
+}
+
+/// Error for input that can only be given at a prompt while prompts are off.
+fn no_prompt(cli: &Cli, what: &str) -> crate::error::CkError {
+    crate::error::CkError::NoPrompt {
+        what: what.to_string(),
+        reason: cli.no_prompt_reason().to_string(),
+    }
+}
+
+/// Fail fast when the type or subject would have to be prompted for.
+///
+/// The first commit in a repository defaults to `chore: initial commit`.
+fn require_header_flags(cli: &Cli, has_type: bool, has_message: bool) -> Result<()> {
+    if (has_type && has_message) || crate::git::is_initial_commit() {
+        return Ok(());
+    }
+    let missing: Vec<&str> = [(has_type, "--type"), (has_message, "--message")]
+        .iter()
+        .filter(|(given, _)| !given)
+        .map(|(_, flag)| *flag)
+        .collect();
+    Err(no_prompt(cli, &missing.join(" and ")))
+}
+
 /// Run the check command.
 fn run_check(cli: &Cli, config: &CkConfig, args: super::args::CheckArgs) -> Result<()> {
+    use crate::rules::baseline::Baseline;
     use crate::rules::RuleEngine;
 
     tracing::debug!("Running check command with args: {:?}", args);
 
-    let engine = RuleEngine::new(config.clone());
-    let strict = args.strict || (cli.ci && config.rules.ci.strict);
+    // Cached passes carry no warnings, so comparisons check every commit
+    if let Some(ref other_path) = args.with_config {
+        let base = RuleEngine::new(config.clone());
+        let other = RuleEngine::new(CkConfig::load_from(other_path)?);
+        let repo = crate::git::open_repo()?;
+        let partial = crate::git::shallow::prepare_range(&repo, &args.target, args.auto_deepen)?;
+        let diff = crate::rules::RulesDiff::compute(&base, &other, &args.target)?;
+        diff.print(cli.format);
+        if let Some(partial) = partial {
+            partial.print(diff.total);
+        }
+        return Ok(());
+    }
+
+    let mut engine = RuleEngine::new(config.clone());
+    if args.cache {
+        engine = engine.with_cache();
+    }
 
-    let results = if args.range || args.target.contains("..") {
-        engine.check_range(&args.target)?
+    let mut results = if let Some(ref title) = args.pr_title {
+        let title = match title.clone().or_else(crate::rules::pull_request_title) {
+            Some(title) => title,
+            None => return Err(crate::error::CkError::WithContext {
+                code: "check-no-title",
+                context: "check".to_string(),
+                message:
+                    "No PR title given, and none found in CK_PR_TITLE or the GitHub event payload"
+                        .to_string(),
+            }),
+        };
+        vec![engine.check_title(&title)]
+    } else if args.checks_message() {
+        vec![engine.check_message(&read_message(&args)?)]
+    } else if args.range || args.target.contains("..") {
+        let repo = crate::git::open_repo()?;
+        let partial = crate::git::shallow::prepare_range(&repo, &args.target, args.auto_deepen)?;
+        let results = engine.check_range(&args.target)?;
+        if let Some(partial) = partial {
+            partial.print(results.len());
+        }
+        results
     } else {
         vec![engine.check_commit(&args.target)?]
     };
 
+    // Findings recorded in the baseline are accepted, unless it was taken
+    // under another configuration
+    if args.pr_title.is_none() && !args.checks_message() {
+        let root = crate::git::open_repo()?.workdir().to_path_buf();
+        let config_hash = config.hash();
+        if args.update_baseline {
+            let baseline = Baseline::from_results(&results, &config_hash);
+            baseline.save(&root)?;
+            baseline.print_saved();
+            return Ok(());
+        }
+        let baseline = Baseline::load(&root)?;
+        if baseline.is_stale(&config_hash) {
+            crate::rules::baseline::print_stale();
+        } else {
+            crate::rules::baseline::print_ignored(baseline.apply(&mut results));
+        }
+    }
+
+    let (failed, strict) = report_results(cli, config, &mut results, args.strict);
+
+    if failed && args.fix {
+        if !cli.is_interactive() {
+            return Err(no_prompt(cli, "a terminal for --fix"));
+        }
+        let fixer = crate::commit::fix::CommitFixer::new();
+        fixer.reword_range(
+            &args.target,
+            config,
+            strict,
+            args.force_rewrite,
+            args.resign,
+            cli.dry_run,
+        )?;
+        return Ok(());
+    }
+
+    check_status(&results, failed)
+}
+
+/// Promote CI severities, then print the results of a check.
+///
+/// Returns whether the check fails and whether warnings count as failures.
+fn report_results(
+    cli: &Cli,
+    config: &CkConfig,
+    results: &mut [crate::rules::ValidationResult],
+    strict: bool,
+) -> (bool, bool) {
+    use crate::rules::bitbucket::BitbucketReport;
+    use crate::rules::checkrun::{first_changed_path, CheckRun};
+
+    // Escalate severities in CI
+    if cli.ci {
+        for result in results.iter_mut() {
+            result.promote(&config.rules.ci.promote);
+        }
+    }
+    let warnings_fail = if strict {
+        Some("--strict")
+    } else if cli.ci && config.rules.ci.strict {
+        Some("rules.ci.strict")
+    } else if cli.ci && config.rules.ci.fail_on_warning {
+        Some("rules.ci.fail_on_warning")
+    } else {
+        None
+    };
+
     // Output results
     let mut has_errors = false;
     let mut has_warnings = false;
 
-    for result in &results {
+    for result in results.iter() {
         if !result.errors.is_empty() {
             has_errors = true;
         }
         if !result.warnings.is_empty() {
             has_warnings = true;
         }
-        result.print(cli.format);
     }
 
-    // Determine exit status
-    if has_errors || (strict && has_warnings) {
+    let strict = warnings_fail.is_some();
+    let failed = has_errors || (strict && has_warnings);
+
+    match cli.format {
+        Some(OutputFormat::CheckrunJson) => {
+            CheckRun::from_results(results, failed, first_changed_path).print()
+        }
+        Some(OutputFormat::Azure) => {
+            crate::rules::azure::print(results, failed, first_changed_path)
+        }
+        Some(OutputFormat::BitbucketJson) => {
+            BitbucketReport::from_results(results, failed, first_changed_path).print()
+        }
+        _ => {
+            for result in results.iter() {
+                result.print(cli.format);
+            }
+        }
+    }
+    crate::rules::print_escalation(results, warnings_fail);
+
+    (failed, strict)
+}
+
+/// The exit status of a check.
+fn check_status(results: &[crate::rules::ValidationResult], failed: bool) -> Result<()> {
+    if failed {
         Err(crate::error::CkError::Validation(
             crate::error::ValidationError::MultipleErrors {
                 count: results.iter().map(|r| r.errors.len()).sum(),
@@ -136,39 +498,544 @@ fn run_check(cli: &Cli, config: &CkConfig, args: super::args::CheckArgs) -> Resu
     }
 }
 
+/// Read the message `ck check` was asked to check instead of commits.
+fn read_message(args: &super::args::CheckArgs) -> Result<String> {
+    use std::io::Read;
+
+    let path = match (&args.message_file, args.staged) {
+        (Some(path), _) => path.clone(),
+        (None, true) => crate::git::open_repo()?.git_dir().join("COMMIT_EDITMSG"),
+        (None, false) => {
+            let mut message = String::new();
+            std::io::stdin().read_to_string(&mut message)?;
+            return Ok(message);
+        }
+    };
+    std::fs::read_to_string(&path).map_err(|e| crate::error::CkError::WithContext {
+        code: "check-read",
+        context: "check".to_string(),
+        message: format!("Could not read {}: {}", path.display(), e),
+    })
+}
+
+/// Run the ci command.
+fn run_ci(cli: &Cli, config: &CkConfig, args: super::args::CiArgs) -> Result<()> {
+    use super::args::{CheckArgs, CiAction};
+    use crate::rules::CiEnvironment;
+
+    match args.action {
+        CiAction::Validate {
+            target,
+            strict,
+            auto_deepen,
+        } => {
+            let ci = CiEnvironment::detect();
+            let provider = ci.as_ref().map_or("none", |ci| ci.provider.as_str());
+            tracing::debug!("Validating for CI provider {}", provider);
+
+            // A pull request build checks the commits it adds to the target
+            let target = target
+                .or_else(|| {
+                    let ci = ci.as_ref().filter(|ci| ci.pull_request)?;
+                    Some(format!("origin/{}..HEAD", ci.target_branch.as_ref()?))
+                })
+                .unwrap_or_else(|| "HEAD".to_string());
+            let format = match provider {
+                "azure" => Some(OutputFormat::Azure),
+                "bitbucket" => Some(OutputFormat::BitbucketJson),
+                _ => None,
+            };
+
+            let mut cli = cli.clone();
+            cli.ci = true;
+            cli.format = cli.format.or(format);
+            let args = CheckArgs {
+                target,
+                strict,
+                auto_deepen,
+                ..CheckArgs::default()
+            };
+            run_check(&cli, config, args)
+        }
+    }
+}
+
+/// Run the plugin command.
+fn run_plugin(cli: &Cli, config: &CkConfig, args: super::args::PluginArgs) -> Result<()> {
+    use crate::plugins::install::{self, InstallOptions};
+    use crate::plugins::PluginLoader;
+    use console::style;
+
+    tracing::debug!("Running plugin command with args: {:?}", args);
+
+    let loader = PluginLoader::from_config(config);
+    let settings = &config.plugins;
+    let enabled =
+        |name: &str| settings.enabled && settings.enabled_plugins.iter().any(|p| p == name);
+    let denied = |info: &crate::plugins::PluginInfo| -> Vec<crate::plugins::PluginCapability> {
+        info.manifest
+            .permissions
+            .iter()
+            .filter(|p| !settings.permissions.contains(p))
+            .copied()
+            .collect()
+    };
+    let names = |permissions: &[crate::plugins::PluginCapability]| -> Vec<&str> {
+        permissions.iter().map(|p| p.as_str()).collect()
+    };
+
+    match args.action {
+        PluginAction::List => {
+            let plugins = loader.discover()?;
+            if cli.format == Some(OutputFormat::Json) {
+                let entries: Vec<_> = plugins
+                    .iter()
+                    .map(|info| {
+                        serde_json::json!({
+                            "name": info.name,
+                            "version": info.manifest.version,
+                            "description": info.manifest.description,
+                            "permissions": names(&info.manifest.permissions),
+                            "denied_permissions": names(&denied(info)),
+                            "enabled": enabled(&info.name),
+                        })
+                    })
+                    .collect();
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&entries).unwrap_or_default()
+                );
+                return Ok(());
+            }
+
+            if plugins.is_empty() {
+                println!("No plugins in {}", loader.directory().display());
+            }
+            for info in &plugins {
+                let state = if !denied(info).is_empty() {
+                    style("blocked").red()
+                } else if enabled(&info.name) {
+                    style("enabled").green()
+                } else {
+                    style("disabled").dim()
+                };
+                let permissions = names(&info.manifest.permissions);
+                println!(
+                    "{:<20} {:<10} {:<8} {}",
+                    style(&info.name).cyan(),
+                    info.manifest.version,
+                    state,
+                    if permissions.is_empty() {
+                        "-".to_string()
+                    } else {
+                        permissions.join(", ")
+                    }
+                );
+            }
+        }
+        PluginAction::Info { name } => {
+            let info = loader
+                .discover()?
+                .into_iter()
+                .find(|info| info.name == name)
+                .ok_or(crate::error::CkError::Plugin(
+                    crate::error::PluginError::NotFound { name: name.clone() },
+                ))?;
+            let compatible = info.manifest.is_compatible(crate::version::VERSION);
+            let digest = install::module_digest(&info);
+            let signed = info.path.join(install::SIGNATURE_FILE).exists();
+            let denied = denied(&info);
+
+            if cli.format == Some(OutputFormat::Json) {
+                let entry = serde_json::json!({
+                    "name": info.name,
+                    "path": info.path,
+                    "manifest": info.manifest,
+                    "compatible": compatible,
+                    "sha256": digest,
+                    "signed": signed,
+                    "enabled": enabled(&info.name),
+                    "denied_permissions": names(&denied),
+                });
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&entry).unwrap_or_default()
+                );
+                return Ok(());
+            }
+
+            println!(
+                "{} {}",
+                style(&info.name).cyan().bold(),
+                info.manifest.version
+            );
+            if let Some(ref description) = info.manifest.description {
+                println!("  {}", description);
+            }
+            if let Some(ref author) = info.manifest.author {
+                println!("  Author:    {}", author);
+            }
+            println!(
+                "  Requires:  ck {}{}",
+                info.manifest.ck_version,
+                if compatible {
+                    String::new()
+                } else {
+                    format!(" (this is {})", crate::version::VERSION)
+                }
+            );
+            println!("  Path:      {}", info.path.display());
+            println!(
+                "  Module:    {}",
+                digest.as_deref().unwrap_or("missing plugin.wasm")
+            );
+            println!(
+                "  Signature: {}",
+                if signed {
+                    install::SIGNATURE_FILE
+                } else {
+                    "none"
+                }
+            );
+            println!(
+                "  Enabled:   {}",
+                if enabled(&info.name) { "yes" } else { "no" }
+            );
+            println!("  Permissions:");
+            if info.manifest.permissions.is_empty() {
+                println!("    none");
+            }
+            for permission in &info.manifest.permissions {
+                let granted = if denied.contains(permission) {
+                    style("not granted by plugins.permissions").red()
+                } else {
+                    style("granted").green()
+                };
+                println!(
+                    "    {:<12} {} ({})",
+                    permission.as_str(),
+                    permission.description(),
+                    granted
+                );
+            }
+        }
+        PluginAction::Install {
+            source,
+            sha256,
+            minisign_key,
+            force,
+            enable,
+        } => {
+            let options = InstallOptions {
+                sha256,
+                minisign_key: minisign_key.or_else(|| settings.minisign_key.clone()),
+                force,
+            };
+            let installed = install::install(&loader, &source, &options)?;
+            let info = &installed.info;
+            println!(
+                "✓ Installed {} {} into {}{}",
+                info.name,
+                info.manifest.version,
+                info.path.display(),
+                if installed.replaced {
+                    ", replacing the installed copy"
+                } else {
+                    ""
+                }
+            );
+            println!("  Module:    {}", installed.digest);
+            if installed.signed {
+                println!("  Signature: verified");
+            }
+
+            let denied = denied(info);
+            if !denied.is_empty() {
+                println!(
+                    "{} {} asks for {}, which plugins.permissions doesn't grant; it won't load until it does",
+                    style("!").yellow(),
+                    info.name,
+                    names(&denied).join(", ")
+                );
+            }
+            if enable {
+                let path = crate::config::edit::config_file_for_edit();
+                crate::config::edit::enable_plugin(&path, &info.name)?;
+                println!("✓ Enabled {} in {}", info.name, path.display());
+            } else if !enabled(&info.name) {
+                println!(
+                    "Enable it with --enable or by adding \"{}\" to plugins.enabled_plugins",
+                    info.name
+                );
+            }
+        }
+        PluginAction::Remove { name } => {
+            let path = install::remove(&loader, &name)?;
+            println!("✓ Removed {} from {}", name, path.display());
+            if let Some(file) = crate::config::find_config_file() {
+                if crate::config::edit::disable_plugin(&file, &name)? {
+                    println!("✓ Disabled {} in {}", name, file.display());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the merge-check command.
+fn run_merge_check(cli: &Cli, config: &CkConfig, args: super::args::MergeCheckArgs) -> Result<()> {
+    use super::args::QueueStrategyArg;
+    use crate::config::QueueStrategy;
+    use crate::rules::queue::PullRequest;
+    use crate::rules::{CiEnvironment, RuleEngine};
+
+    tracing::debug!("Running merge-check command with args: {:?}", args);
+
+    let target = match args
+        .target
+        .clone()
+        .or_else(|| CiEnvironment::detect().and_then(|ci| ci.target_branch))
+    {
+        Some(target) => target,
+        None => {
+            return Err(crate::error::CkError::WithContext {
+                code: "merge-check-no-target",
+                context: "merge-check".to_string(),
+                message: "No target branch given, and none found in the CI environment".to_string(),
+            })
+        }
+    };
+
+    // CI checkouts often only have the remote-tracking branch of the target,
+    // and branch rules are keyed by the name without the remote
+    let repo = crate::git::open_repo()?;
+    let remote = format!("origin/{}", target);
+    let (target, branch) = match target.split_once('/') {
+        Some((name, branch)) if repo.inner().find_remote(name).is_ok() => {
+            (target.clone(), branch.to_string())
+        }
+        _ if repo.get_commit(&target).is_err() && repo.get_commit(&remote).is_ok() => {
+            (remote, target)
+        }
+        _ => (target.clone(), target),
+    };
+
+    let strategy = match args.strategy {
+        Some(QueueStrategyArg::Squash) => QueueStrategy::Squash,
+        Some(QueueStrategyArg::Merge) => QueueStrategy::Merge,
+        Some(QueueStrategyArg::Rebase) => QueueStrategy::Rebase,
+        None => config.rules.queue.strategy,
+    };
+    let pr = PullRequest {
+        title: args.title.or_else(crate::rules::pull_request_title),
+        body: args.body,
+        number: args.number,
+        branch: args.branch,
+    }
+    .or_from_event();
+
+    let engine = RuleEngine::new(config.clone()).with_branch(&branch);
+    let mut results = engine.check_queue(&target, &args.head, &pr, strategy)?;
+
+    if strategy != QueueStrategy::Rebase && matches!(cli.format, None | Some(OutputFormat::Text)) {
+        println!("Lands on {} as:", branch);
+        for line in results[0].message.lines() {
+            println!("{}", format!("    {}", line).trim_end());
+        }
+        println!();
+    }
+
+    let (failed, _) = report_results(cli, config, &mut results, args.strict);
+    check_status(&results, failed)
+}
+
 /// Run the fix command.
-fn run_fix(cli: &Cli, _config: &CkConfig, args: super::args::FixArgs) -> Result<()> {
+fn run_fix(cli: &Cli, config: &CkConfig, args: super::args::FixArgs) -> Result<()> {
     use crate::commit::fix::CommitFixer;
 
     tracing::debug!("Running fix command with args: {:?}", args);
 
-    if cli.is_ci_mode() && !args.auto {
-        return Err(crate::error::CkError::WithContext {
-            context: "fix".to_string(),
-            message: "Cannot run fix in CI mode without --auto".to_string(),
-        });
+    if !cli.is_interactive() && !args.auto {
+        return Err(no_prompt(cli, "--auto"));
     }
 
     let fixer = CommitFixer::new();
-    fixer.fix(&args.target, args.count, cli.dry_run, args.auto)
+    let target = args.range.as_deref().unwrap_or(&args.target);
+    if !args.auto {
+        return fixer.fix(target, args.count, cli.dry_run);
+    }
+
+    let range = CommitFixer::range(target, args.count);
+    let report = fixer.fix_batch(&range, config, args.force_rewrite, args.resign, cli.dry_run)?;
+    if cli.format == Some(super::args::OutputFormat::Json) {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        );
+    } else {
+        report.print();
+    }
+    if let Some(ref path) = args.report {
+        report.write(path)?;
+    }
+
+    match report.failing() {
+        0 => Ok(()),
+        failing => Err(crate::error::CkError::WithContext {
+            code: "fix-incomplete",
+            context: "fix".to_string(),
+            message: format!("{} commit(s) still have errors and need rewording", failing),
+        }),
+    }
+}
+
+/// Run the sign command.
+fn run_sign(cli: &Cli, args: super::args::SignArgs) -> Result<()> {
+    tracing::debug!("Running sign command with args: {:?}", args);
+
+    let report = crate::commit::sign::sign_range(&args.range, args.force_rewrite, cli.dry_run)?;
+    if cli.format == Some(super::args::OutputFormat::Json) {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        );
+    } else {
+        report.print();
+    }
+    Ok(())
+}
+
+/// Run the scan command.
+fn run_scan(cli: &Cli, config: &CkConfig, args: super::args::ScanArgs) -> Result<()> {
+    use crate::security::scan::{scan, ScanTarget};
+
+    tracing::debug!("Running scan command with args: {:?}", args);
+
+    let target = match (args.range, args.all) {
+        (Some(range), _) => ScanTarget::Range(range),
+        (None, true) => ScanTarget::WorkingTree,
+        (None, false) => ScanTarget::Staged,
+    };
+    let repo = crate::git::open_repo()?;
+    let report = scan(&repo, config, &target)?;
+
+    // Files get the JSON report unless SARIF is asked for
+    let rendered = match cli.format {
+        Some(OutputFormat::Sarif) => {
+            serde_json::to_string_pretty(&crate::security::sarif::to_sarif(&report)).ok()
+        }
+        Some(OutputFormat::Json) => serde_json::to_string_pretty(&report).ok(),
+        _ if args.output.is_some() => serde_json::to_string_pretty(&report).ok(),
+        _ => None,
+    };
+    match (rendered, args.output) {
+        (Some(rendered), Some(path)) => {
+            std::fs::write(&path, rendered + "\n")?;
+            report.print();
+        }
+        (Some(rendered), None) => println!("{}", rendered),
+        (None, _) => report.print(),
+    }
+
+    report.status(config.security.block_on_secret)
+}
+
+/// Run the affected command.
+fn run_affected(cli: &Cli, config: &CkConfig, args: super::args::AffectedArgs) -> Result<()> {
+    tracing::debug!("Running affected command with args: {:?}", args);
+
+    let repo = crate::git::open_repo()?;
+    let report =
+        crate::monorepo::affected::affected(&repo, config, args.base.as_deref(), args.direct)?;
+    if cli.format == Some(OutputFormat::Json) {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        );
+    } else {
+        report.print();
+    }
+    Ok(())
 }
 
 /// Run the hooks command.
-fn run_hooks(_cli: &Cli, _config: &CkConfig, args: super::args::HooksArgs) -> Result<()> {
-    use crate::hooks::HookManager;
+fn run_hooks(cli: &Cli, _config: &CkConfig, args: super::args::HooksArgs) -> Result<()> {
+    use crate::hooks::{ConflictAction, HookManager, HookStatus, HookTemplate};
 
     tracing::debug!("Running hooks command");
 
     let manager = HookManager::new()?;
 
     match args.action {
-        HooksAction::Install { hook, force } => {
-            if let Some(hook_name) = hook {
-                manager.install_hook(&hook_name, force)?;
-                println!("✓ Installed {} hook", hook_name);
+        HooksAction::Install { hook, force, chain } => {
+            let on_conflict = if chain {
+                ConflictAction::Chain
+            } else if force {
+                ConflictAction::Backup
             } else {
-                manager.install_all(force)?;
-                println!("✓ Installed all hooks");
+                ConflictAction::Abort
+            };
+            let interactive = cli.is_interactive() && on_conflict == ConflictAction::Abort;
+
+            let names: Vec<String> = match hook {
+                Some(hook_name) => vec![hook_name],
+                None => HookTemplate::all()
+                    .iter()
+                    .map(|t| t.filename().to_string())
+                    .collect(),
+            };
+            for name in &names {
+                let action = match manager.conflicting_hook(name)? {
+                    Some(path) if interactive => match resolve_hook_conflict(name, &path)? {
+                        Some(action) => action,
+                        None => {
+                            println!("Skipped {} hook", name);
+                            continue;
+                        }
+                    },
+                    _ => on_conflict,
+                };
+                manager.install_hook(name, action)?;
+                match action {
+                    ConflictAction::Chain => {
+                        println!("✓ Installed {} hook (chained to existing hook)", name)
+                    }
+                    _ => println!("✓ Installed {} hook", name),
+                }
+            }
+        }
+        HooksAction::Doctor { repair } => {
+            let mut unhealthy = 0;
+            for health in manager.doctor()? {
+                if health.is_healthy() {
+                    println!("✓ {}", health.hook);
+                    continue;
+                }
+                println!("✗ {}", health.hook);
+                for issue in &health.issues {
+                    println!("    {}", issue);
+                }
+                if repair {
+                    manager.repair(&health.hook)?;
+                    let still_broken = manager
+                        .doctor()?
+                        .into_iter()
+                        .any(|h| h.hook == health.hook && !h.is_healthy());
+                    if still_broken {
+                        unhealthy += 1;
+                        println!("    Repaired, but problems remain");
+                    } else {
+                        println!("    ✓ Repaired");
+                    }
+                } else {
+                    unhealthy += 1;
+                }
+            }
+            if unhealthy > 0 {
+                return Err(crate::error::CkError::Hook(
+                    crate::error::HookError::Unhealthy { count: unhealthy },
+                ));
             }
         }
         HooksAction::Uninstall { hook } => {
@@ -182,9 +1049,28 @@ fn run_hooks(_cli: &Cli, _config: &CkConfig, args: super::args::HooksArgs) -> Re
         }
         HooksAction::Status => {
             let status = manager.status()?;
-            for (hook, installed) in status {
-                let icon = if installed { "✓" } else { "✗" };
-                println!("{} {}", icon, hook);
+            let mut outdated = false;
+            for (hook, state) in status {
+                match state {
+                    HookStatus::Installed => println!("✓ {}", hook),
+                    HookStatus::Outdated => {
+                        outdated = true;
+                        println!("! {} (outdated)", hook);
+                    }
+                    HookStatus::NotInstalled => println!("✗ {}", hook),
+                }
+            }
+            if outdated {
+                println!("\nRun `ck hooks upgrade` to regenerate outdated hooks");
+            }
+        }
+        HooksAction::Upgrade => {
+            let upgraded = manager.upgrade()?;
+            if upgraded.is_empty() {
+                println!("✓ All ck hooks are up to date");
+            }
+            for hook in upgraded {
+                println!("✓ Upgraded {} hook", hook);
             }
         }
         HooksAction::Run { hook, args } => {
@@ -195,6 +1081,467 @@ fn run_hooks(_cli: &Cli, _config: &CkConfig, args: super::args::HooksArgs) -> Re
     Ok(())
 }
 
+/// Ask what to do with an existing hook that was not installed by ck.
+///
+/// Returns `None` when the user chooses to skip this hook.
+fn resolve_hook_conflict(
+    name: &str,
+    path: &std::path::Path,
+) -> Result<Option<crate::hooks::ConflictAction>> {
+    use crate::error::{CkError, HookError};
+    use crate::hooks::ConflictAction;
+    use dialoguer::{theme::ColorfulTheme, Select};
+
+    let theme = ColorfulTheme::default();
+    let options = [
+        "View the existing hook",
+        "Chain it (run it before ck)",
+        "Back it up and replace it",
+        "Skip this hook",
+        "Abort",
+    ];
+
+    println!("A {} hook not installed by ck already exists.", name);
+    loop {
+        let selection = Select::with_theme(&theme)
+            .with_prompt("How should it be handled?")
+            .items(&options)
+            .default(1)
+            .interact()?;
+
+        match selection {
+            0 => match std::fs::read_to_string(path) {
+                Ok(content) => println!("\n{}\n", content.trim_end()),
+                Err(e) => eprintln!("Could not read {}: {}", path.display(), e),
+            },
+            1 => return Ok(Some(ConflictAction::Chain)),
+            2 => return Ok(Some(ConflictAction::Backup)),
+            3 => return Ok(None),
+            _ => {
+                return Err(CkError::Hook(HookError::AlreadyExists {
+                    hook: name.to_string(),
+                }))
+            }
+        }
+    }
+}
+
+/// Run the rules command.
+fn run_rules(cli: &Cli, config: &CkConfig, args: super::args::RulesArgs) -> Result<()> {
+    use crate::rules::{print_rules, RuleEngine, RulesDiff};
+
+    tracing::debug!("Running rules command with args: {:?}", args);
+
+    match args.action {
+        RulesAction::List => {
+            let sources = crate::config::load_config_sources(cli.config.as_deref())?;
+            let engine = RuleEngine::new(config.clone());
+            print_rules(&engine.list_rules(&sources), cli.format);
+        }
+        RulesAction::Diff {
+            config_a,
+            config_b,
+            range,
+        } => {
+            let base = RuleEngine::new(CkConfig::load_from(&config_a)?);
+            let other = RuleEngine::new(CkConfig::load_from(&config_b)?);
+            RulesDiff::compute(&base, &other, &range)?.print(cli.format);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the worktrees command.
+fn run_worktrees(cli: &Cli) -> Result<()> {
+    use crate::git::worktree::list_worktrees;
+    use console::style;
+
+    let repo = crate::git::open_repo()?;
+    let worktrees = list_worktrees(&repo)?;
+    if cli.format == Some(super::args::OutputFormat::Json) {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&worktrees).unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    for worktree in &worktrees {
+        let branch = worktree.branch.as_deref().unwrap_or("(detached)");
+        let state = if worktree.dirty {
+            style("dirty").yellow()
+        } else {
+            style("clean").green()
+        };
+        println!(
+            "{:<20} {:<24} {:<5} {}",
+            style(&worktree.name).cyan(),
+            branch,
+            state,
+            style(worktree.path.display()).dim()
+        );
+    }
+    Ok(())
+}
+
+/// Run the stash command.
+fn run_stash(cli: &Cli, config: &CkConfig, args: super::args::StashArgs) -> Result<()> {
+    use crate::analysis::RepositoryContext;
+    use crate::commit::CommitBuilder;
+    use crate::git::stash::StashCommit;
+
+    tracing::debug!("Running stash command with args: {:?}", args);
+
+    match args.action {
+        StashAction::Commit {
+            stash,
+            r#type,
+            scope,
+            message,
+            keep,
+            sign,
+            git,
+        } => {
+            let repo = crate::git::open_repo()?;
+            let stash = StashCommit::prepare(&repo, &stash)?;
+            let context = RepositoryContext::from_diff(stash.diff_info(&repo)?, config);
+
+            let mut builder = CommitBuilder::new(config.clone())
+                .with_context(context)
+                .with_stash(stash.clone())
+                .with_git_options(git.options());
+            if let Some(ref t) = r#type {
+                builder = builder.with_type_str(t)?;
+            }
+            if let Some(ref scope) = scope {
+                builder = builder.with_scope(scope);
+            }
+            if let Some(ref message) = message {
+                builder = builder.with_subject(message);
+            }
+
+            let builder = builder.with_defaults_accepted(cli.yes);
+            if cli.is_interactive() {
+                builder.run_interactive(cli.dry_run, sign, false)?;
+            } else {
+                require_header_flags(cli, r#type.is_some() || cli.yes, message.is_some())?;
+                builder.commit_non_interactive(cli.dry_run, sign)?;
+            }
+
+            if !keep && !cli.dry_run {
+                stash.drop_stash(&repo)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the cherry-pick command.
+fn run_cherry_pick(cli: &Cli, config: &CkConfig, args: super::args::CherryPickArgs) -> Result<()> {
+    use crate::commit::cherry_pick::{cherry_picked_trailer, pick_commit, PickOptions};
+
+    tracing::debug!("Running cherry-pick command with args: {:?}", args);
+
+    let sha = crate::git::open_repo()?
+        .get_commit(&args.reference)?
+        .id()
+        .to_string();
+    let options = PickOptions {
+        interactive: cli.is_interactive(),
+        dry_run: cli.dry_run,
+        yes: cli.yes,
+        sign: args.sign,
+    };
+
+    pick_commit(config, &sha, &cherry_picked_trailer(&sha), options)
+}
+
+/// Run the backport command.
+fn run_backport(cli: &Cli, config: &CkConfig, args: super::args::BackportArgs) -> Result<()> {
+    use crate::commit::cherry_pick::{backport, PickOptions};
+
+    tracing::debug!("Running backport command with args: {:?}", args);
+
+    let options = PickOptions {
+        interactive: cli.is_interactive(),
+        dry_run: cli.dry_run,
+        yes: cli.yes,
+        sign: args.sign,
+    };
+
+    let done = backport(config, &args.reference, &args.to, options)?;
+    if !cli.dry_run {
+        println!("Backported to {}", done.join(", "));
+    }
+    Ok(())
+}
+
+/// Run the context command.
+fn run_context(config: &CkConfig, args: super::args::ContextArgs) -> Result<()> {
+    use crate::analysis::RepositoryContext;
+
+    tracing::debug!("Running context command with args: {:?}", args);
+
+    let context = RepositoryContext::from_current_repo_with_config(config)?;
+    println!("{}", context.summary());
+    for warning in context.warnings.iter() {
+        println!("  {}", warning.message);
+    }
+
+    if args.explain {
+        println!(
+            "\n{}",
+            crate::smart::Explanation::build(&context, config).render()
+        );
+    }
+    Ok(())
+}
+
+/// Run the scopes command.
+fn run_scopes(args: super::args::ScopesArgs) -> Result<()> {
+    use crate::analysis::scope_map::{ScopeMap, SCOPE_MAP_FILE};
+
+    tracing::debug!("Running scopes command with args: {:?}", args);
+
+    let repo = crate::git::open_repo()?;
+    let root = repo.workdir();
+    let mut scope_map = ScopeMap::load(root)?;
+
+    match args.action {
+        ScopesAction::Map { action } => match action {
+            ScopeMapAction::Add { glob, scope, first } => {
+                scope_map.add(&glob, &scope, first)?;
+                scope_map.save(root)?;
+                println!(
+                    "✓ Mapped {} to scope '{}' in {}",
+                    glob, scope, SCOPE_MAP_FILE
+                );
+            }
+            ScopeMapAction::List => {
+                for (i, mapping) in scope_map.map.iter().enumerate() {
+                    println!("{:>3}. {} → {}", i + 1, mapping.pattern, mapping.scope);
+                }
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// Run the audit command.
+fn run_audit(cli: &Cli, args: super::args::AuditArgs) -> Result<()> {
+    use crate::hooks::audit::{audit, print_findings};
+
+    tracing::debug!("Running audit command with args: {:?}", args);
+
+    let repo = crate::git::open_repo()?;
+    let findings = audit(&repo, &args.target)?;
+    print_findings(&findings, cli.format);
+
+    if findings.is_empty() {
+        Ok(())
+    } else {
+        Err(crate::error::CkError::WithContext {
+            code: "audit-failed",
+            context: "audit".to_string(),
+            message: format!(
+                "{} commit(s) were created without ck validation",
+                findings.len()
+            ),
+        })
+    }
+}
+
+/// Run the stats command.
+fn run_stats(cli: &Cli, args: super::args::StatsArgs) -> Result<()> {
+    use crate::commit::stats::CommitStats;
+
+    tracing::debug!("Running stats command with args: {:?}", args);
+
+    let repo = crate::git::open_repo()?;
+    CommitStats::collect(&repo, args.range.as_deref(), args.time)?.print(cli.format);
+    Ok(())
+}
+
+/// Run the review command.
+fn run_review(cli: &Cli, config: &CkConfig, args: super::args::ReviewArgs) -> Result<()> {
+    use crate::commit::review::Review;
+
+    tracing::debug!("Running review command with args: {:?}", args);
+
+    let repo = crate::git::open_repo()?;
+    let review = Review::collect(&repo, config, &args.range)?;
+    match args.output {
+        Some(ref path) => {
+            std::fs::write(path, review.render(cli.format))?;
+            eprintln!("Wrote review to {}", path.display());
+        }
+        None => print!("{}", review.render(cli.format)),
+    }
+    Ok(())
+}
+
+/// Run the verify command.
+fn run_verify(cli: &Cli, config: &CkConfig, args: super::args::VerifyArgs) -> Result<()> {
+    use crate::rules::verify::{failure, print_summary, verify_range, ATTESTATION_TYPE};
+    use crate::rules::RuleEngine;
+    use crate::security::attestation::{cosign_sign_blob, Envelope, IN_TOTO_PAYLOAD_TYPE};
+
+    tracing::debug!("Running verify command with args: {:?}", args);
+
+    let repo = crate::git::open_repo()?;
+    let require_signed = args.require_signed || RuleEngine::new(config.clone()).requires_signing();
+
+    let attestation = verify_range(
+        &repo,
+        config,
+        &args.range,
+        require_signed,
+        cli.deterministic,
+    )?;
+    let mut envelope = if args.in_toto {
+        Envelope::new(IN_TOTO_PAYLOAD_TYPE, &attestation.to_statement())?
+    } else {
+        Envelope::new(ATTESTATION_TYPE, &attestation)?
+    };
+    if args.sign {
+        envelope.sign(&repo)?;
+    }
+
+    match args.output {
+        Some(ref path) => {
+            std::fs::write(path, envelope.to_json())?;
+            if args.cosign {
+                let bundle = cosign_sign_blob(path, args.cosign_key.as_deref())?;
+                eprintln!("Wrote cosign bundle to {}", bundle.display());
+            }
+        }
+        None => println!("{}", envelope.to_json()),
+    }
+
+    print_summary(&attestation);
+
+    match failure(&attestation) {
+        Some(error) => Err(error.into()),
+        None => Ok(()),
+    }
+}
+
+/// Run the bump command.
+fn run_bump(cli: &Cli, args: super::args::BumpArgs) -> Result<()> {
+    use crate::commit::bump::ReleasePlan;
+    use crate::git::tags::create_tag;
+
+    tracing::debug!("Running bump command with args: {:?}", args);
+
+    let repo = crate::git::open_repo()?;
+    let plan = ReleasePlan::compute(&repo)?;
+    plan.print(cli.format);
+
+    if args.tag && plan.needs_release() {
+        let message = args
+            .message
+            .unwrap_or_else(|| format!("Release {}", plan.next_tag));
+        if cli.dry_run {
+            eprintln!("Would create tag {}", plan.next_tag);
+        } else {
+            create_tag(&repo, &plan.next_tag, &message)?;
+            eprintln!("Created tag {}", plan.next_tag);
+        }
+    }
+    Ok(())
+}
+
+/// Run the style-guide command.
+fn run_style_guide(config: &CkConfig, args: super::args::StyleGuideArgs) -> Result<()> {
+    use crate::rules::style_guide::{render, update_section};
+
+    tracing::debug!("Running style-guide command with args: {:?}", args);
+
+    let section = render(config);
+    let path = match args.output {
+        Some(path) => path,
+        None => {
+            print!("{}", section);
+            return Ok(());
+        }
+    };
+
+    let current = if path.exists() {
+        std::fs::read_to_string(&path)?
+    } else {
+        String::new()
+    };
+    let updated = update_section(&current, &section);
+    if updated == current {
+        eprintln!("{} is up to date", path.display());
+        return Ok(());
+    }
+    if args.check {
+        return Err(crate::error::CkError::WithContext {
+            code: "style-guide-outdated",
+            context: "style-guide".to_string(),
+            message: format!(
+                "{} is out of date; run `ck style-guide -o {}`",
+                path.display(),
+                path.display()
+            ),
+        });
+    }
+    std::fs::write(&path, updated)?;
+    eprintln!("Updated the style guide in {}", path.display());
+    Ok(())
+}
+
+/// Run the example command.
+fn run_example(cli: &Cli, config: &CkConfig, args: super::args::ExampleArgs) -> Result<()> {
+    use crate::rules::examples::{generate, ExampleOptions};
+
+    tracing::debug!("Running example command with args: {:?}", args);
+
+    let commit_type = match args.commit_type {
+        Some(ref name) => Some(config.commit_type(name).ok_or_else(|| {
+            crate::error::CkError::Validation(crate::error::ValidationError::InvalidType {
+                commit_type: name.clone(),
+            })
+        })?),
+        None => None,
+    };
+    let options = ExampleOptions {
+        commit_type,
+        scope: args.scope,
+        breaking: args.breaking,
+        count: args.count,
+    };
+    let examples = generate(config, &options)?;
+
+    if cli.format == Some(super::args::OutputFormat::Json) {
+        let json: Vec<_> = examples
+            .iter()
+            .map(|message| {
+                serde_json::json!({
+                    "type": message.commit_type,
+                    "scope": message.scope,
+                    "breaking": message.is_breaking,
+                    "header": message.header(),
+                    "message": message.format(),
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json).unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    let formatted: Vec<String> = examples.iter().map(|m| m.format()).collect();
+    println!("{}", formatted.join("\n\n---\n\n"));
+    Ok(())
+}
+
 /// Run the install command.
 fn run_install(_cli: &Cli, args: super::args::InstallArgs) -> Result<()> {
     tracing::debug!("Running install command with args: {:?}", args);
@@ -205,6 +1552,7 @@ fn run_install(_cli: &Cli, args: super::args::InstallArgs) -> Result<()> {
             .args(["config", "--global", "alias.cz", "!ck"])
             .output()
             .map_err(|e| crate::error::CkError::WithContext {
+                code: "install-alias",
                 context: "install".to_string(),
                 message: format!("Failed to set git alias: {}", e),
             })?;
@@ -214,6 +1562,7 @@ fn run_install(_cli: &Cli, args: super::args::InstallArgs) -> Result<()> {
             println!("  You can now use: git cz");
         } else {
             return Err(crate::error::CkError::WithContext {
+                code: "install-alias",
                 context: "install".to_string(),
                 message: "Failed to set git alias".to_string(),
             });
@@ -224,12 +1573,14 @@ fn run_install(_cli: &Cli, args: super::args::InstallArgs) -> Result<()> {
         // Install the binary to the specified directory
         let current_exe =
             std::env::current_exe().map_err(|e| crate::error::CkError::WithContext {
+                code: "install-binary",
                 context: "install".to_string(),
                 message: format!("Failed to get current executable: {}", e),
             })?;
 
         let target = dir.join("ck");
         std::fs::copy(&current_exe, &target).map_err(|e| crate::error::CkError::WithContext {
+            code: "install-binary",
             context: "install".to_string(),
             message: format!("Failed to copy binary: {}", e),
         })?;
@@ -254,6 +1605,12 @@ fn run_version() -> Result<()> {
     Ok(())
 }
 
+/// Run the language server.
+fn run_lsp(config: &CkConfig) -> Result<()> {
+    tracing::debug!("Starting language server on stdio");
+    crate::lsp::run_stdio(config.clone())
+}
+
 /// Run the init command.
 fn run_init(_cli: &Cli, args: super::args::InitArgs) -> Result<()> {
     use crate::config::default::example_config;
@@ -270,6 +1627,10 @@ fn run_init(_cli: &Cli, args: super::args::InitArgs) -> Result<()> {
         ));
     }
 
+    if let Some(source) = args.from_commitlint {
+        return init_from_commitlint(config_path, source);
+    }
+
     let config_content = match args.preset {
         Some(super::args::ConfigPreset::Minimal) => {
             r#"# CK Configuration (Minimal)
@@ -321,6 +1682,7 @@ enabled = true
 
     std::fs::write(config_path, config_content).map_err(|e| {
         crate::error::CkError::WithContext {
+            code: "init-write",
             context: "init".to_string(),
             message: format!("Failed to write configuration: {}", e),
         }
@@ -330,3 +1692,38 @@ enabled = true
 
     Ok(())
 }
+
+/// Write `ck.toml` translated from a commitlint configuration.
+fn init_from_commitlint(
+    config_path: &std::path::Path,
+    source: Option<std::path::PathBuf>,
+) -> Result<()> {
+    use crate::config::importers::commitlint;
+
+    let source = match source {
+        Some(path) => path,
+        None => commitlint::find_config(std::path::Path::new(".")).ok_or_else(|| {
+            crate::error::CkError::WithContext {
+                code: "init-no-commitlint",
+                context: "init".to_string(),
+                message: "No commitlint configuration found in the current directory".to_string(),
+            }
+        })?,
+    };
+    let imported = commitlint::import_file(&source)?;
+
+    std::fs::write(config_path, &imported.toml).map_err(|e| {
+        crate::error::CkError::WithContext {
+            code: "init-write",
+            context: "init".to_string(),
+            message: format!("Failed to write configuration: {}", e),
+        }
+    })?;
+
+    let shown = source.strip_prefix(".").unwrap_or(&source);
+    println!("✓ Created ck.toml from {}", shown.display());
+    for note in &imported.notes {
+        eprintln!("  {} {}", console::style("⚠").yellow(), note);
+    }
+    Ok(())
+}
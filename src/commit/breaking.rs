@@ -0,0 +1,119 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Structured `BREAKING CHANGE:` footers.
+//!
+//! The footer states what breaks and lists migration steps as indented
+//! continuation lines, so it stays a single well-formed trailer:
+//!
+//! ```text
+//! BREAKING CHANGE: The `output` config key was removed
+//!   Migration:
+//!   - Rename `output` to `format` in ck.toml
+//! ```
+
+use serde::Serialize;
+
+/// Footer token for breaking changes.
+pub const BREAKING_CHANGE_TOKEN: &str = "BREAKING CHANGE";
+
+const MIGRATION_HEADER: &str = "Migration:";
+
+/// A breaking change with its migration steps.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BreakingChange {
+    /// What breaks.
+    pub description: String,
+    /// Steps users take to upgrade.
+    pub migration: Vec<String>,
+}
+
+impl BreakingChange {
+    /// Create a breaking change with the given description.
+    pub fn new(description: impl Into<String>) -> Self {
+        Self {
+            description: description.into(),
+            migration: Vec::new(),
+        }
+    }
+
+    /// Add a migration step.
+    pub fn with_step(mut self, step: impl Into<String>) -> Self {
+        let step = step.into();
+        if !step.trim().is_empty() {
+            self.migration.push(step.trim().to_string());
+        }
+        self
+    }
+
+    /// Render as a `BREAKING CHANGE:` footer.
+    pub fn render(&self) -> String {
+        let mut footer = format!("{}: {}", BREAKING_CHANGE_TOKEN, self.description.trim());
+        if !self.migration.is_empty() {
+            footer.push_str(&format!("\n  {}", MIGRATION_HEADER));
+            for step in &self.migration {
+                footer.push_str(&format!("\n  - {}", step));
+            }
+        }
+        footer
+    }
+
+    /// Parse the breaking change from a footer, accepting both
+    /// `BREAKING CHANGE:` and `BREAKING-CHANGE:`.
+    pub fn parse(footer: &str) -> Option<Self> {
+        let mut lines = footer.lines().skip_while(|line| {
+            !line.starts_with("BREAKING CHANGE:") && !line.starts_with("BREAKING-CHANGE:")
+        });
+        let first = lines.next()?;
+        let mut change = Self::new(first[BREAKING_CHANGE_TOKEN.len() + 1..].trim());
+
+        let mut in_migration = false;
+        for line in lines {
+            // The value continues on indented lines until the next trailer
+            if !line.starts_with(char::is_whitespace) && !line.is_empty() {
+                break;
+            }
+            let line = line.trim();
+            if line == MIGRATION_HEADER {
+                in_migration = true;
+            } else if in_migration {
+                change = change.with_step(line.trim_start_matches("- "));
+            } else if !line.is_empty() {
+                change.description.push(' ');
+                change.description.push_str(line);
+            }
+        }
+
+        Some(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_and_parse() {
+        let change = BreakingChange::new("The `output` key was removed")
+            .with_step("Rename `output` to `format`")
+            .with_step("  ")
+            .with_step("Run ck init --check");
+        let footer = change.render();
+        assert_eq!(
+            footer,
+            "BREAKING CHANGE: The `output` key was removed\n  Migration:\n  - Rename `output` to `format`\n  - Run ck init --check"
+        );
+
+        let with_trailers = format!("Refs: #12\n{}\nSigned-off-by: A <a@x>", footer);
+        assert_eq!(BreakingChange::parse(&with_trailers), Some(change));
+    }
+
+    #[test]
+    fn test_parse_plain_footer() {
+        let change =
+            BreakingChange::parse("BREAKING-CHANGE: drops Node 14\n  and Node 16").unwrap();
+        assert_eq!(change.description, "drops Node 14 and Node 16");
+        assert!(change.migration.is_empty());
+        assert_eq!(BreakingChange::parse("Refs: #1"), None);
+    }
+}
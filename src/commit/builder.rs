@@ -3,40 +3,133 @@
 
 //! Interactive commit builder.
 
-use crate::analysis::RepositoryContext;
-use crate::config::{CkConfig, CommitType};
+use crate::analysis::{RepositoryContext, INITIAL_SUBJECT};
+use crate::config::{CkConfig, CommitType, EmojiStyle, MessageTemplate};
 use crate::error::{CkError, CommitError, Result};
 use crate::git;
-use crate::rules::RuleEngine;
+use crate::interrupt;
+use crate::rules::{ticket, RuleEngine, ValidationIssue, ValidationResult};
 
 use console::{style, Term};
-use dialoguer::{theme::ColorfulTheme, Confirm, Editor, Input, Select};
+use dialoguer::{theme::ColorfulTheme, Confirm, FuzzySelect, Input, Select};
 
+use super::breaking::{BreakingChange, BREAKING_CHANGE_TOKEN};
+use super::editor::{MessageEditor, BODY_INSTRUCTIONS};
+use super::gitmoji::{self, GITMOJIS};
 use super::message::CommitMessage;
 use super::preview::CommitPreview;
+use super::recent::{order_by_recent, recency, unique, RecentUsage};
+use super::subject_input::SubjectInput;
+use super::template::{self, TemplateVars};
+use super::trailers::{self, Trailer, COMMON_TOKENS, SIGNED_OFF_BY};
+
+/// Rows shown at once by the type and scope pickers.
+const PICKER_ROWS: usize = 12;
+
+/// Scope picker entry for no scope.
+const NO_SCOPE: &str = "(none)";
+
+/// Scope picker entry for typing a scope that is not listed.
+const OTHER_SCOPE: &str = "(other...)";
+
+/// Trailer picker entry for finishing.
+const DONE_TRAILERS: &str = "(done)";
+
+/// Trailer picker entry for typing a token that is not listed.
+const OTHER_TRAILER: &str = "(other...)";
+
+/// A step of the interactive flow. Steps for values given up front are
+/// skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Type,
+    /// Pick the gitmoji, with `ui.emoji_style = "gitmoji"`.
+    Emoji,
+    Scope,
+    Subject,
+    Body,
+    /// Ask what breaks, and first whether anything does if `confirm`.
+    Breaking {
+        confirm: bool,
+    },
+    Deprecations,
+    /// Ask for missing required trailers, then offer to add more.
+    Trailers,
+}
+
+/// A prompt's answer, or a request to go back a step (Esc).
+enum Answer<T> {
+    Value(T),
+    Back,
+}
+
+impl<T> From<Option<T>> for Answer<T> {
+    fn from(answer: Option<T>) -> Self {
+        match answer {
+            Some(value) => Answer::Value(value),
+            None => Answer::Back,
+        }
+    }
+}
 
 /// Interactive commit builder.
 pub struct CommitBuilder {
     config: CkConfig,
     commit_type: Option<CommitType>,
+    emoji: Option<String>,
     scope: Option<String>,
     subject: Option<String>,
     body: Option<String>,
+    footer: Option<String>,
     is_breaking: bool,
+    breaking_change: Option<BreakingChange>,
+    deprecations: Vec<String>,
+    trailers: Vec<Trailer>,
+    target: git::CommitTarget,
     context: Option<RepositoryContext>,
+    accept_defaults: bool,
+    git_options: git::CommitOptions,
+    template: Option<MessageTemplate>,
 }
 
 impl CommitBuilder {
     /// Create a new commit builder.
     pub fn new(config: CkConfig) -> Self {
+        let use_cli = config.git.use_cli;
         Self {
             config,
             commit_type: None,
+            emoji: None,
             scope: None,
             subject: None,
             body: None,
+            footer: None,
             is_breaking: false,
+            breaking_change: None,
+            deprecations: Vec::new(),
+            trailers: Vec::new(),
+            target: git::CommitTarget::Head,
             context: None,
+            accept_defaults: false,
+            git_options: git::CommitOptions {
+                use_cli,
+                ..Default::default()
+            },
+            template: None,
+        }
+    }
+
+    /// Create a builder pre-filled from an existing message.
+    pub fn from_message(config: CkConfig, message: &CommitMessage) -> Self {
+        Self {
+            commit_type: Some(message.commit_type.clone()),
+            emoji: message.emoji.clone(),
+            scope: message.scope.clone(),
+            subject: Some(message.subject.clone()),
+            body: message.body.clone(),
+            footer: message.footer.clone(),
+            is_breaking: message.is_breaking,
+            ..Self::new(config)
         }
     }
 
@@ -57,6 +150,12 @@ impl CommitBuilder {
         self
     }
 
+    /// Set the gitmoji written before the subject.
+    pub fn with_emoji(mut self, emoji: &str) -> Self {
+        self.emoji = Some(emoji.to_string());
+        self
+    }
+
     /// Set the scope.
     pub fn with_scope(mut self, scope: &str) -> Self {
         if !scope.is_empty() {
@@ -87,21 +186,194 @@ impl CommitBuilder {
         self
     }
 
+    /// Describe the breaking change, which also marks the commit as breaking.
+    pub fn with_breaking_change(mut self, change: BreakingChange) -> Self {
+        self.is_breaking = true;
+        self.breaking_change = Some(change);
+        self
+    }
+
+    /// Add a `Deprecated:` footer for an item.
+    pub fn with_deprecation(mut self, item: &str) -> Self {
+        if !item.trim().is_empty() {
+            self.deprecations.push(item.trim().to_string());
+        }
+        self
+    }
+
+    /// Append a `token: value` trailer to the footer.
+    pub fn with_trailer(mut self, token: &str, value: &str) -> Self {
+        let trailer = format!("{}: {}", token, value);
+        self.footer = Some(match self.footer.take() {
+            Some(footer) => format!("{}\n{}", footer, trailer),
+            None => trailer,
+        });
+        self
+    }
+
+    /// Commit onto another branch instead of HEAD.
+    pub fn with_onto(mut self, branch: &str) -> Self {
+        self.target = git::CommitTarget::Branch(branch.to_string());
+        self
+    }
+
+    /// Commit an applied stash instead of the index.
+    pub fn with_stash(mut self, stash: git::stash::StashCommit) -> Self {
+        self.target = git::CommitTarget::Stash(stash);
+        self
+    }
+
+    /// Use an already analyzed context instead of the staged changes.
+    pub fn with_context(mut self, context: RepositoryContext) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Accept the suggested answer at every prompt that has one and skip
+    /// the confirmation, prompting only for what has no default.
+    pub fn with_defaults_accepted(mut self, yes: bool) -> Self {
+        self.accept_defaults = yes;
+        self
+    }
+
+    /// Forward options such as `--no-verify` or `--author` to git.
+    pub fn with_git_options(mut self, options: git::CommitOptions) -> Self {
+        self.git_options = git::CommitOptions {
+            use_cli: options.use_cli || self.git_options.use_cli,
+            ..options
+        };
+        self
+    }
+
+    /// Pre-fill the message from a template. Its type and scope are used
+    /// when none are given.
+    pub fn with_template(mut self, template: MessageTemplate) -> Result<Self> {
+        if self.commit_type.is_none() {
+            if let Some(ref commit_type) = template.commit_type {
+                self = self.with_type_str(commit_type)?;
+            }
+        }
+        if self.scope.is_none() {
+            if let Some(ref scope) = template.scope {
+                self = self.with_scope(scope);
+            }
+        }
+        self.template = Some(template);
+        Ok(self)
+    }
+
+    /// Walk the unstaged hunks under `paths`, asking which to stage. Esc
+    /// stops early and stages what was picked so far; with `dry_run` the
+    /// index is left alone.
+    ///
+    /// Runs before the message is written, and before `--autostash` sets
+    /// the unstaged changes aside.
+    pub fn stage_hunks(paths: &[String], dry_run: bool) -> Result<()> {
+        let term = Term::stderr();
+        let theme = &ColorfulTheme::default();
+        let repo = git::open_repo()?;
+        let hunks = git::staging::unstaged_hunks(&repo, paths)?;
+        if hunks.is_empty() {
+            term.write_line(&format!(
+                "{} No unstaged changes to pick from",
+                style("ℹ").blue()
+            ))?;
+            return Ok(());
+        }
+
+        let items = [
+            "Stage this hunk",
+            "Skip this hunk",
+            "Stage the rest of this file",
+            "Skip the rest of this file",
+            "Done",
+        ];
+        let mut selected = Vec::new();
+        // Decision for the remaining hunks of a file
+        let mut rest_of_file: Option<(&std::path::Path, bool)> = None;
+
+        for (position, hunk) in hunks.iter().enumerate() {
+            interrupt::checkpoint();
+            match rest_of_file {
+                Some((path, stage)) if path == hunk.path => {
+                    if stage {
+                        selected.push(hunk);
+                    }
+                    continue;
+                }
+                _ => rest_of_file = None,
+            }
+
+            term.write_line(&format!(
+                "\n{} {}",
+                style(hunk.path.display()).bold(),
+                style(format!("({}/{})", position + 1, hunks.len())).dim()
+            ))?;
+            term.write_line(&style(&hunk.header).cyan().to_string())?;
+            for line in &hunk.lines {
+                let line = match line.chars().next() {
+                    Some('+') => style(line).green(),
+                    Some('-') => style(line).red(),
+                    _ => style(line).dim(),
+                };
+                term.write_line(&line.to_string())?;
+            }
+
+            let selection = Select::with_theme(theme)
+                .with_prompt("Stage this hunk?")
+                .items(&items)
+                .default(0)
+                .interact_opt()?;
+            match selection {
+                Some(0) => selected.push(hunk),
+                Some(1) => {}
+                Some(2) => {
+                    selected.push(hunk);
+                    rest_of_file = Some((hunk.path.as_path(), true));
+                }
+                Some(3) => rest_of_file = Some((hunk.path.as_path(), false)),
+                _ => break,
+            }
+        }
+
+        if dry_run {
+            term.write_line(&format!(
+                "{} Would stage {} of {} hunk(s)",
+                style("ℹ").blue(),
+                selected.len(),
+                hunks.len()
+            ))?;
+            return Ok(());
+        }
+        git::staging::stage_hunks(&repo, &selected)?;
+        term.write_line(&format!(
+            "{} Staged {} of {} hunk(s)",
+            style("✓").green(),
+            selected.len(),
+            hunks.len()
+        ))?;
+        Ok(())
+    }
+
     /// Run the interactive commit flow.
-    pub fn run_interactive(
-        mut self,
-        dry_run: bool,
-        skip_confirm: bool,
-        sign: bool,
-        amend: bool,
-    ) -> Result<()> {
+    ///
+    /// The commit is signed when `sign` is set or its branch requires it.
+    pub fn run_interactive(mut self, dry_run: bool, sign: bool, amend: bool) -> Result<()> {
         let term = Term::stderr();
         let theme = ColorfulTheme::default();
+        let sign = sign || self.engine().requires_signing();
+
+        // Catch a broken signing setup before the message is written
+        if sign && !dry_run {
+            git::signing::check_signing_setup()?;
+        }
 
         // Load repository context
-        self.context = Some(RepositoryContext::from_current_repo_with_config(
-            &self.config,
-        )?);
+        if self.context.is_none() {
+            self.context = Some(RepositoryContext::from_current_repo_with_config(
+                &self.config,
+            )?);
+        }
         let context = self.context.as_ref().unwrap();
 
         // Check for staged changes
@@ -125,85 +397,75 @@ impl CommitBuilder {
             };
             term.write_line(&format!("  {} {}", icon, warning.message))?;
         }
-
-        // Prompt for commit type
-        if self.commit_type.is_none() {
-            self.commit_type = Some(self.prompt_type(&theme, context)?);
-        }
-
-        // Prompt for scope
-        if self.scope.is_none() {
-            self.scope = self.prompt_scope(&theme, context)?;
-        }
-
-        // Prompt for subject
-        if self.subject.is_none() {
-            self.subject = Some(self.prompt_subject(&theme)?);
+        if !self.accept_defaults {
+            Self::offer_to_hide_warnings(context, &theme, &term)?;
         }
 
-        // Prompt for body
-        if self.body.is_none() && self.config.rules.require_body {
-            self.body = self.prompt_body(&theme)?;
-        } else if self.body.is_none() {
-            // Optional body
-            let wants_body = Confirm::with_theme(&theme)
-                .with_prompt("Add a body?")
-                .default(false)
-                .interact()?;
-
-            if wants_body {
-                self.body = self.prompt_body(&theme)?;
+        // Show coaching hints if enabled
+        if self.config.ui.hints {
+            for hint in super::coaching::coaching_hints(&self.config.ui.coaching, context) {
+                term.write_line(&format!("  {} {}", style("💡").dim(), style(hint).dim()))?;
             }
         }
 
-        // Prompt for breaking change
-        if !self.is_breaking {
-            self.is_breaking = Confirm::with_theme(&theme)
-                .with_prompt("Is this a breaking change?")
-                .default(false)
-                .interact()?;
+        let initial_commit = context.initial_commit;
+        let has_deprecation_signals = !super::deprecation::detect(&context.diff_info).is_empty();
+        let steps = self.steps(initial_commit, has_deprecation_signals);
+        if initial_commit && self.subject.is_none() {
+            self.subject = Some(INITIAL_SUBJECT.to_string());
         }
 
-        // Build the message
-        let message = self.build_message()?;
-
-        // Validate
-        let engine = RuleEngine::new(self.config.clone());
-        let validation = engine.validate(&message);
-
-        // Show preview
-        let preview = CommitPreview::new(&message);
-        term.write_line("\n")?;
-        preview.print();
-
-        // Show validation results
-        if !validation.is_valid() {
-            term.write_line(&format!("\n{}", style("Validation errors:").red().bold()))?;
-            for error in &validation.errors {
-                term.write_line(&format!("  {}", error.format()))?;
+        // Going back from the confirmation resumes at the last step
+        let mut start = 0;
+        let message = loop {
+            self.run_steps(&steps, start, &theme)?;
+
+            // Build the message
+            let message = self.build_message()?;
+
+            // Validate
+            let engine = self.engine();
+            let diff = self.context.as_ref().map(|c| &c.diff_info);
+            let mut validation = engine.validate_with_diff(&message, diff);
+            validation.errors.extend(self.template_issue(&message));
+
+            // Show preview
+            let preview = CommitPreview::new(&message)
+                .with_max_subject_length(self.config.rules.max_subject_length);
+            term.write_line("\n")?;
+            preview.print();
+
+            // Show validation results
+            if !validation.is_valid() {
+                term.write_line(&format!("\n{}", style("Validation errors:").red().bold()))?;
+                for error in &validation.errors {
+                    term.write_line(&format!("  {}", error.format()))?;
+                }
+                return Err(CkError::Validation(
+                    crate::error::ValidationError::MultipleErrors {
+                        count: validation.errors.len(),
+                    },
+                ));
             }
-            return Err(CkError::Validation(
-                crate::error::ValidationError::MultipleErrors {
-                    count: validation.errors.len(),
-                },
-            ));
-        }
 
-        for warning in &validation.warnings {
-            term.write_line(&format!("  {}", warning.format()))?;
-        }
+            for warning in &validation.warnings {
+                term.write_line(&format!("  {}", warning.format()))?;
+            }
 
-        // Confirm
-        if !skip_confirm {
+            // Confirm
+            if self.accept_defaults {
+                break message;
+            }
             let confirmed = Confirm::with_theme(&theme)
                 .with_prompt("Commit?")
                 .default(true)
-                .interact()?;
-
-            if !confirmed {
-                return Err(CkError::Cancelled);
+                .interact_opt()?;
+            match confirmed {
+                Some(true) => break message,
+                None if !steps.is_empty() => start = steps.len() - 1,
+                _ => return Err(CkError::Cancelled),
             }
-        }
+        };
 
         // Commit
         if dry_run {
@@ -214,16 +476,17 @@ impl CommitBuilder {
             ))?;
         } else {
             let sha = if amend {
-                git::commands::amend_commit(&message.format(), sign)?
+                git::commands::amend_commit(&message.format(), sign, &self.git_options)?
             } else {
-                git::create_commit(&message.format(), sign)?
+                self.create_commit(&message, sign)?
             };
 
             let short_sha = &sha[..7.min(sha.len())];
             term.write_line(&format!(
-                "\n{} {} {}",
+                "\n{} {}{} {}",
                 style("✓").green().bold(),
                 style(format!("[{}]", short_sha)).cyan(),
+                self.onto_label(),
                 message.header()
             ))?;
         }
@@ -231,13 +494,278 @@ impl CommitBuilder {
         Ok(())
     }
 
+    /// Reword an existing message, prompting with its current values
+    /// pre-filled. Returns the new message without committing it.
+    pub fn run_reword(mut self) -> Result<CommitMessage> {
+        let theme = ColorfulTheme::default();
+        let steps = [Step::Type, Step::Scope, Step::Subject, Step::Body];
+        self.run_steps(&steps, 0, &theme)?;
+        self.build_message()
+    }
+
+    /// The steps to prompt for, skipping values that are already known.
+    fn steps(&self, initial_commit: bool, has_deprecation_signals: bool) -> Vec<Step> {
+        let mut steps = Vec::new();
+        if self.commit_type.is_none() {
+            steps.push(Step::Type);
+        }
+        if self.emoji.is_none() && self.config.ui.emoji_style == EmojiStyle::Gitmoji {
+            steps.push(Step::Emoji);
+        }
+        if self.scope.is_none() {
+            steps.push(Step::Scope);
+        }
+        if self.subject.is_none() {
+            steps.push(Step::Subject);
+        }
+        if self.body.is_none() {
+            steps.push(Step::Body);
+        }
+
+        // Nothing can break before the first commit
+        let confirm = !self.is_breaking && !initial_commit;
+        let describe = self.breaking_change.is_none() && !self.has_breaking_footer();
+        if confirm || (self.is_breaking && describe) {
+            steps.push(Step::Breaking { confirm });
+        }
+
+        // Deprecations are asked for when the diff deprecates or removes API
+        if has_deprecation_signals && self.deprecations.is_empty() && !self.has_deprecated_footer()
+        {
+            steps.push(Step::Deprecations);
+        }
+
+        steps.push(Step::Trailers);
+        steps
+    }
+
+    /// Prompt for `steps` from `start`, going back a step on Esc. Going
+    /// back from the first step cancels.
+    ///
+    /// When accepting defaults, steps with a default are answered without
+    /// a prompt, except when going back to them.
+    fn run_steps(&mut self, steps: &[Step], start: usize, theme: &ColorfulTheme) -> Result<()> {
+        let mut index = start;
+        let mut forward = true;
+        while let Some(step) = steps.get(index) {
+            if forward && self.accept_defaults && self.accept_default(*step) {
+                index += 1;
+                continue;
+            }
+            match self.prompt_step(*step, theme)? {
+                Answer::Value(()) => {
+                    index += 1;
+                    forward = true;
+                }
+                Answer::Back if index == 0 => return Err(CkError::Cancelled),
+                Answer::Back => {
+                    index -= 1;
+                    forward = false;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer a step with its default. Returns false if it has none.
+    fn accept_default(&mut self, step: Step) -> bool {
+        let context = self.context.as_ref();
+        match step {
+            Step::Type => match context.and_then(|c| c.suggested_type.clone()) {
+                Some(suggested) => self.commit_type = Some(suggested),
+                None => return false,
+            },
+            // The type's gitmoji is filled in when the message is built
+            Step::Emoji => {}
+            Step::Scope => {
+                self.scope = context.and_then(|c| c.suggested_scope.clone());
+                if self.scope.is_none() && self.config.rules.require_scope {
+                    return false;
+                }
+            }
+            // Only the first commit has a default subject
+            Step::Subject => return self.subject.is_some(),
+            Step::Body => {
+                return !(self.config.rules.require_body || self.has_body_sections());
+            }
+            // Not breaking, no description and no deprecations
+            Step::Breaking { .. } | Step::Deprecations => {}
+            Step::Trailers => {
+                self.trailers = self.default_trailers();
+                return self.missing_trailers().is_empty();
+            }
+        }
+        true
+    }
+
+    /// Fill in the type and scope suggested for the staged changes.
+    fn accept_suggestions(&mut self) -> Result<()> {
+        if self.context.is_none() {
+            self.context = Some(RepositoryContext::from_current_repo_with_config(
+                &self.config,
+            )?);
+        }
+        let context = self.context.as_ref();
+        if self.commit_type.is_none() {
+            self.commit_type = context.and_then(|c| c.suggested_type.clone());
+        }
+        if self.scope.is_none() {
+            self.scope = context.and_then(|c| c.suggested_scope.clone());
+        }
+        Ok(())
+    }
+
+    /// Prompt for one step, keeping earlier answers as defaults.
+    fn prompt_step(&mut self, step: Step, theme: &ColorfulTheme) -> Result<Answer<()>> {
+        match step {
+            Step::Type => {
+                let suggested = self
+                    .commit_type
+                    .clone()
+                    .or_else(|| self.context.as_ref().and_then(|c| c.suggested_type.clone()));
+                match self.prompt_type(theme, suggested)? {
+                    Answer::Value(commit_type) => self.commit_type = Some(commit_type),
+                    Answer::Back => return Ok(Answer::Back),
+                }
+            }
+            Step::Emoji => match self.prompt_emoji(theme)? {
+                Answer::Value(emoji) => self.emoji = Some(emoji),
+                Answer::Back => return Ok(Answer::Back),
+            },
+            Step::Scope => {
+                let suggested = self.scope.clone().or_else(|| {
+                    self.context
+                        .as_ref()
+                        .and_then(|c| c.suggested_scope.clone())
+                });
+                match self.prompt_scope(theme, suggested)? {
+                    Answer::Value(scope) => self.scope = scope,
+                    Answer::Back => return Ok(Answer::Back),
+                }
+            }
+            Step::Subject => match self.prompt_subject(theme)? {
+                Answer::Value(subject) => self.subject = Some(subject),
+                Answer::Back => return Ok(Answer::Back),
+            },
+            Step::Body => {
+                let required = self.body.is_none()
+                    && (self.config.rules.require_body || self.has_body_sections());
+                if !required {
+                    let prompt = if self.body.is_some() {
+                        "Edit the body?"
+                    } else {
+                        "Add a body?"
+                    };
+                    let wants_body = Confirm::with_theme(theme)
+                        .with_prompt(prompt)
+                        .default(false)
+                        .interact_opt()?;
+                    match wants_body {
+                        Some(true) => {}
+                        Some(false) => return Ok(Answer::Value(())),
+                        None => return Ok(Answer::Back),
+                    }
+                }
+                self.body = self.prompt_body(theme)?;
+            }
+            Step::Breaking { confirm } => {
+                if confirm {
+                    let breaking = Confirm::with_theme(theme)
+                        .with_prompt("Is this a breaking change?")
+                        .default(self.is_breaking)
+                        .interact_opt()?;
+                    match breaking {
+                        Some(breaking) => self.is_breaking = breaking,
+                        None => return Ok(Answer::Back),
+                    }
+                }
+                self.breaking_change = None;
+                if self.is_breaking && !self.has_breaking_footer() {
+                    self.breaking_change = self.prompt_breaking_change(theme)?;
+                }
+            }
+            Step::Deprecations => {
+                let signals = self
+                    .context
+                    .as_ref()
+                    .map(|c| super::deprecation::detect(&c.diff_info))
+                    .unwrap_or_default();
+                match self.prompt_deprecations(theme, &signals)? {
+                    Answer::Value(items) => self.deprecations = items,
+                    Answer::Back => return Ok(Answer::Back),
+                }
+            }
+            Step::Trailers => {
+                self.trailers.clear();
+                match self.prompt_trailers(theme)? {
+                    Answer::Value(trailers) => self.trailers = trailers,
+                    Answer::Back => return Ok(Answer::Back),
+                }
+            }
+        }
+        Ok(Answer::Value(()))
+    }
+
     /// Commit without interactive prompts.
-    pub fn commit_non_interactive(self, dry_run: bool, sign: bool) -> Result<()> {
+    ///
+    /// Without a type and subject, the first commit in a repository gets
+    /// `chore: initial commit`. When accepting defaults, a missing type and
+    /// scope are taken from the suggestion for the staged changes. The
+    /// commit is signed when `sign` is set or its branch requires it.
+    pub fn commit_non_interactive(mut self, dry_run: bool, sign: bool) -> Result<()> {
+        let sign = sign || self.engine().requires_signing();
+        if sign && !dry_run {
+            git::signing::check_signing_setup()?;
+        }
+
+        let (message, _) = self.validated_message()?;
+
+        if dry_run {
+            println!("{}", message.format());
+        } else {
+            let sha = self.create_commit(&message, sign)?;
+            let short_sha = &sha[..7.min(sha.len())];
+            println!("[{}]{} {}", short_sha, self.onto_label(), message.header());
+        }
+
+        Ok(())
+    }
+
+    /// Build and validate the message `commit_non_interactive` would
+    /// commit, without committing it.
+    ///
+    /// Returns the message with its validation result, which has no errors.
+    pub fn preview_non_interactive(mut self) -> Result<(CommitMessage, ValidationResult)> {
+        self.validated_message()
+    }
+
+    /// Fill in defaults, build the message and validate it against the
+    /// staged changes. Fails with the validation errors, after printing them.
+    fn validated_message(&mut self) -> Result<(CommitMessage, ValidationResult)> {
+        if self.commit_type.is_none() && self.subject.is_none() && git::is_initial_commit() {
+            self.commit_type = Some(CommitType::Chore);
+            self.subject = Some(INITIAL_SUBJECT.to_string());
+        }
+        if self.accept_defaults {
+            self.accept_suggestions()?;
+        }
+        if self.subject.is_none() {
+            self.subject = self.template_subject();
+        }
+        let defaults = self.default_trailers();
+        self.trailers.extend(defaults);
+
         let message = self.build_message()?;
 
         // Validate
-        let engine = RuleEngine::new(self.config);
-        let validation = engine.validate(&message);
+        let engine = self.engine();
+        let diff = match &self.context {
+            Some(context) => Some(context.diff_info.clone()),
+            None if engine.needs_diff() => Some(git::get_staged_diff()?),
+            None => None,
+        };
+        let mut validation = engine.validate_with_diff(&message, diff.as_ref());
+        validation.errors.extend(self.template_issue(&message));
 
         if !validation.is_valid() {
             for error in &validation.errors {
@@ -250,20 +778,103 @@ impl CommitBuilder {
             ));
         }
 
-        if dry_run {
-            println!("{}", message.format());
-        } else {
-            let sha = git::create_commit(&message.format(), sign)?;
-            let short_sha = &sha[..7.min(sha.len())];
-            println!("[{}] {}", short_sha, message.header());
+        Ok((message, validation))
+    }
+
+    /// Offer to stop showing the displayed warnings by adding their codes to
+    /// `warnings.disable`. Errors can't be hidden this way.
+    fn offer_to_hide_warnings(
+        context: &RepositoryContext,
+        theme: &ColorfulTheme,
+        term: &Term,
+    ) -> Result<()> {
+        let codes = unique(
+            context
+                .warnings
+                .iter()
+                .filter(|w| w.level != crate::analysis::WarningLevel::Error)
+                .map(|w| w.code.to_string()),
+        );
+        if codes.is_empty() {
+            return Ok(());
         }
 
+        let path = crate::config::edit::config_file_for_edit();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        let mut items = vec!["Continue".to_string()];
+        items.extend(
+            codes
+                .iter()
+                .map(|code| format!("Don't show {} again (save to {})", code, file_name)),
+        );
+
+        let selection = Select::with_theme(theme)
+            .with_prompt("Warnings")
+            .items(&items)
+            .default(0)
+            .interact_opt()?;
+        if let Some(index) = selection.filter(|&i| i > 0) {
+            let code = &codes[index - 1];
+            crate::config::edit::disable_warning(&path, code)?;
+            term.write_line(&format!(
+                "  {} {} hidden in {}",
+                style("✓").green(),
+                code,
+                path.display()
+            ))?;
+        }
         Ok(())
     }
 
+    /// Create the commit on the configured target.
+    ///
+    /// The commit was validated by ck, so it gets the validation note that
+    /// `ck audit` looks for, with the hash of the configuration.
+    fn create_commit(&self, message: &CommitMessage, sign: bool) -> Result<String> {
+        let sha = match self.target {
+            git::CommitTarget::Head => {
+                git::create_commit_with_options(&message.format(), sign, &self.git_options)
+            }
+            git::CommitTarget::Branch(ref branch) => {
+                git::commands::commit_onto(branch, &message.format(), sign, &self.git_options)
+            }
+            git::CommitTarget::Stash(ref stash) => {
+                let repo = git::open_repo()?;
+                stash.commit(&repo, &message.format(), sign, &self.git_options)
+            }
+        }?;
+
+        let config_hash = self.config.hash();
+        if let Err(e) = git::notes::mark_validated(&git::open_repo()?, &sha, &config_hash) {
+            tracing::debug!("Could not record validation note: {}", e);
+        }
+        Ok(sha)
+    }
+
+    /// Rule engine for the branch the commit lands on.
+    fn engine(&self) -> RuleEngine {
+        let engine = RuleEngine::new(self.config.clone());
+        match self.target {
+            git::CommitTarget::Branch(ref branch) => engine.with_branch(branch),
+            _ => engine,
+        }
+    }
+
+    /// Suffix shown after the SHA when not committing the index to HEAD.
+    fn onto_label(&self) -> String {
+        match self.target {
+            git::CommitTarget::Head => String::new(),
+            git::CommitTarget::Branch(ref branch) => format!(" ({})", branch),
+            git::CommitTarget::Stash(ref stash) => format!(" (from {})", stash.stash_ref),
+        }
+    }
+
     /// Build the commit message from collected data.
     fn build_message(&self) -> Result<CommitMessage> {
-        let commit_type = self.commit_type.ok_or_else(|| {
+        let commit_type = self.commit_type.clone().ok_or_else(|| {
             CkError::Commit(CommitError::ParseFailed {
                 message: "Commit type is required".to_string(),
             })
@@ -275,139 +886,534 @@ impl CommitBuilder {
             })
         })?;
 
+        let emoji = match self.config.ui.emoji_style {
+            EmojiStyle::Gitmoji => self
+                .emoji
+                .clone()
+                .or_else(|| gitmoji::for_type(&commit_type, &self.config).map(str::to_string)),
+            EmojiStyle::None => self.emoji.clone(),
+        };
         let mut message = CommitMessage::new(commit_type, subject);
+        if let Some(emoji) = emoji {
+            message = message.with_emoji(emoji);
+        }
 
+        // Inferred scopes carry the directory's casing
         if let Some(ref scope) = self.scope {
-            message = message.with_scope(scope);
+            message = message.with_scope(self.config.rules.scope.normalize(scope));
         }
 
         if let Some(ref body) = self.body {
             message = message.with_body(body);
         }
 
+        // Breaking changes go last since they span several lines
+        let footer: Vec<String> = self
+            .footer
+            .iter()
+            .cloned()
+            .chain(
+                self.deprecations
+                    .iter()
+                    .map(|d| super::deprecation::render(d)),
+            )
+            .chain(self.trailers.iter().map(Trailer::render))
+            .chain(self.template_footer())
+            .chain(self.breaking_change.as_ref().map(BreakingChange::render))
+            .collect();
+        if !footer.is_empty() {
+            message = message.with_footer(footer.join("\n"));
+        }
+
         message = message.with_breaking(self.is_breaking);
 
-        Ok(message)
+        Ok(ticket::with_branch_ticket(
+            message,
+            &self.config.rules.ticket,
+            self.branch().as_deref(),
+        ))
     }
 
     /// Prompt for commit type.
     fn prompt_type(
         &self,
         theme: &ColorfulTheme,
-        context: &RepositoryContext,
-    ) -> Result<CommitType> {
-        let types: Vec<CommitType> = self
+        suggested: Option<CommitType>,
+    ) -> Result<Answer<CommitType>> {
+        let mut types: Vec<CommitType> = self
             .config
-            .rules
-            .allowed_types
+            .allowed_type_names()
             .iter()
-            .filter_map(|t| t.parse().ok())
+            .filter_map(|t| self.config.commit_type(t))
             .collect();
 
+        // Recently used types first
+        let recent = RecentUsage::from_current_repo();
+        types.sort_by_key(|t| recency(&recent.types, t.as_str()));
+
         let items: Vec<String> = types
             .iter()
-            .map(|t| format!("{:10} {}", t.as_str(), style(t.description()).dim()))
+            .map(|t| {
+                let description = self.config.type_description(t);
+                match self.type_emoji(t).filter(|_| self.config.ui.emoji) {
+                    Some(emoji) => format!("{:10} {} {}", t.as_str(), emoji, description),
+                    None => format!("{:10} {}", t.as_str(), description),
+                }
+            })
             .collect();
 
         // Find default index based on suggestion
-        let default_idx = context
-            .suggested_type
-            .as_ref()
-            .and_then(|st| types.iter().position(|t| t == st))
+        let default_idx = suggested
+            .and_then(|st| types.iter().position(|t| *t == st))
             .unwrap_or(0);
 
-        let selection = Select::with_theme(theme)
+        let selection = FuzzySelect::with_theme(theme)
             .with_prompt("Select commit type")
             .items(&items)
             .default(default_idx)
-            .interact()?;
+            .max_length(PICKER_ROWS)
+            .interact_opt()?;
+
+        Ok(Answer::from(selection.map(|i| types[i].clone())))
+    }
+
+    /// Prompt for the gitmoji, defaulting to the one for the type.
+    fn prompt_emoji(&self, theme: &ColorfulTheme) -> Result<Answer<String>> {
+        let suggested = self.emoji.clone().or_else(|| {
+            self.commit_type
+                .as_ref()
+                .and_then(|t| gitmoji::for_type(t, &self.config))
+                .map(str::to_string)
+        });
+        let items: Vec<String> = GITMOJIS
+            .iter()
+            .map(|g| format!("{}  {:24} {}", g.emoji, g.code, g.description))
+            .collect();
+        let default_idx = suggested
+            .and_then(|emoji| GITMOJIS.iter().position(|g| g.emoji == emoji))
+            .unwrap_or(0);
+
+        let selection = FuzzySelect::with_theme(theme)
+            .with_prompt("Select gitmoji")
+            .items(&items)
+            .default(default_idx)
+            .max_length(PICKER_ROWS)
+            .interact_opt()?;
+
+        Ok(Answer::from(
+            selection.map(|i| GITMOJIS[i].emoji.to_string()),
+        ))
+    }
 
-        Ok(types[selection])
+    /// The emoji shown next to `commit_type` in the type picker.
+    fn type_emoji(&self, commit_type: &CommitType) -> Option<&str> {
+        match self.config.ui.emoji_style {
+            EmojiStyle::Gitmoji => gitmoji::for_type(commit_type, &self.config),
+            EmojiStyle::None => self.config.type_emoji(commit_type),
+        }
     }
 
     /// Prompt for scope.
+    ///
+    /// Scopes are picked with fuzzy search from the allowed list, or from
+    /// monorepo packages and scopes used before, recently used first.
+    /// Without any known scopes the scope is typed in.
     fn prompt_scope(
         &self,
         theme: &ColorfulTheme,
-        context: &RepositoryContext,
-    ) -> Result<Option<String>> {
-        let default = context.suggested_scope.clone().unwrap_or_default();
+        suggested: Option<String>,
+    ) -> Result<Answer<Option<String>>> {
+        let default = suggested.unwrap_or_default();
+        let rules = &self.config.rules;
+        let recent = RecentUsage::from_current_repo();
+
+        let restricted = !rules.scope.allowed.is_empty();
+        let candidates = if restricted {
+            rules.scope.allowed.clone()
+        } else {
+            let packages = self
+                .context
+                .iter()
+                .flat_map(|c| c.packages.iter().map(|p| p.name.clone()));
+            let mut known = unique(
+                std::iter::once(default.clone())
+                    .chain(packages)
+                    .chain(recent.scopes.iter().cloned()),
+            );
+            known.retain(|s| !rules.scope.forbidden.contains(s));
+            known
+        };
+
+        if candidates.is_empty() {
+            return Ok(Answer::Value(self.prompt_scope_text(theme, default)?));
+        }
 
-        let allowed = &self.config.rules.scope.allowed;
+        let mut items = order_by_recent(candidates, &recent.scopes);
+        let none = !rules.require_scope;
+        if none {
+            items.insert(0, NO_SCOPE.to_string());
+        }
+        if !restricted {
+            items.push(OTHER_SCOPE.to_string());
+        }
 
-        if !allowed.is_empty() {
-            // Use select for predefined scopes
-            let mut items: Vec<String> = allowed.clone();
-            items.insert(0, "(none)".to_string());
+        let default_idx = items.iter().position(|s| s == &default).unwrap_or(0);
 
-            let default_idx = if default.is_empty() {
-                0
-            } else {
-                items.iter().position(|s| s == &default).unwrap_or(0)
-            };
+        let selection = FuzzySelect::with_theme(theme)
+            .with_prompt("Select scope")
+            .items(&items)
+            .default(default_idx)
+            .max_length(PICKER_ROWS)
+            .interact_opt()?;
+
+        let scope = match selection.map(|i| items[i].as_str()) {
+            None => return Ok(Answer::Back),
+            Some(NO_SCOPE) if none => None,
+            Some(OTHER_SCOPE) if !restricted => self.prompt_scope_text(theme, String::new())?,
+            Some(scope) => Some(scope.to_string()),
+        };
+        Ok(Answer::Value(scope))
+    }
 
-            let selection = Select::with_theme(theme)
-                .with_prompt("Select scope")
-                .items(&items)
-                .default(default_idx)
-                .interact()?;
+    /// Prompt for a scope as free text.
+    fn prompt_scope_text(&self, theme: &ColorfulTheme, default: String) -> Result<Option<String>> {
+        let prompt = if self.config.rules.require_scope {
+            "Scope (required)"
+        } else {
+            "Scope (optional)"
+        };
 
-            if selection == 0 {
-                Ok(None)
-            } else {
-                Ok(Some(items[selection].clone()))
-            }
+        let scope: String = Input::with_theme(theme)
+            .with_prompt(prompt)
+            .default(default)
+            .allow_empty(!self.config.rules.require_scope)
+            .interact_text()?;
+
+        if scope.is_empty() {
+            Ok(None)
         } else {
-            // Free-form input
-            let prompt = if self.config.rules.require_scope {
-                "Scope (required)"
-            } else {
-                "Scope (optional)"
-            };
+            Ok(Some(scope))
+        }
+    }
 
-            let scope: String = Input::with_theme(theme)
-                .with_prompt(prompt)
-                .default(default)
-                .allow_empty(!self.config.rules.require_scope)
-                .interact_text()?;
+    /// Prompt for subject, showing the remaining length and subject rule
+    /// hints as it is typed.
+    fn prompt_subject(&self, _theme: &ColorfulTheme) -> Result<Answer<String>> {
+        let max_len = self.config.rules.max_subject_length;
+        let commit_type = self.commit_type.clone().unwrap_or(CommitType::Feat);
+        let prompt = format!("Subject (max {} chars)", max_len);
+
+        let input = SubjectInput::new(&prompt, max_len, |subject| {
+            let message = CommitMessage::new(commit_type.clone(), subject);
+            crate::rules::check_subject(&message, &self.config)
+        })
+        .with_initial_text(
+            self.subject
+                .clone()
+                .or_else(|| self.template_subject())
+                .unwrap_or_default(),
+        );
+        Ok(Answer::from(input.interact(&Term::stderr())?))
+    }
 
-            if scope.is_empty() {
-                Ok(None)
-            } else {
-                Ok(Some(scope))
+    /// Prompt for body.
+    fn prompt_body(&self, _theme: &ColorfulTheme) -> Result<Option<String>> {
+        let template = self.body_template();
+        let initial = self.body.clone().unwrap_or_else(|| template.clone());
+
+        let body = MessageEditor::from_config(&self.config)
+            .with_instructions(BODY_INSTRUCTIONS)
+            .edit(&initial)?;
+
+        // An untouched template counts as no body
+        Ok(body.filter(|s| !s.trim().is_empty() && s.trim() != template.trim()))
+    }
+
+    /// Prompt for what breaks and how to migrate.
+    fn prompt_breaking_change(&self, theme: &ColorfulTheme) -> Result<Option<BreakingChange>> {
+        let description: String = Input::with_theme(theme)
+            .with_prompt("What breaks? (empty to skip)")
+            .allow_empty(true)
+            .interact_text()?;
+        if description.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let mut change = BreakingChange::new(description);
+        loop {
+            let step: String = Input::with_theme(theme)
+                .with_prompt(format!(
+                    "Migration step {} (empty to finish)",
+                    change.migration.len() + 1
+                ))
+                .allow_empty(true)
+                .interact_text()?;
+            if step.trim().is_empty() {
+                break;
             }
+            change = change.with_step(step);
         }
+
+        Ok(Some(change))
     }
 
-    /// Prompt for subject.
-    fn prompt_subject(&self, theme: &ColorfulTheme) -> Result<String> {
-        let max_len = self.config.rules.max_subject_length;
+    /// Prompt for `Deprecated:` footers, showing why they are suggested.
+    fn prompt_deprecations(
+        &self,
+        theme: &ColorfulTheme,
+        signals: &[String],
+    ) -> Result<Answer<Vec<String>>> {
+        let term = Term::stderr();
+        term.write_line(&format!(
+            "\n{}",
+            style("This change may deprecate public API:").yellow()
+        ))?;
+        for signal in signals {
+            term.write_line(&format!("  - {}", signal))?;
+        }
+
+        let add = Confirm::with_theme(theme)
+            .with_prompt("Add Deprecated: footers?")
+            .default(true)
+            .interact_opt()?;
+        match add {
+            Some(true) => {}
+            Some(false) => return Ok(Answer::Value(Vec::new())),
+            None => return Ok(Answer::Back),
+        }
+
+        let mut items = Vec::new();
+        loop {
+            let item: String = Input::with_theme(theme)
+                .with_prompt("Deprecated item and replacement (empty to finish)")
+                .allow_empty(true)
+                .interact_text()?;
+            if item.trim().is_empty() {
+                break;
+            }
+            items.push(item.trim().to_string());
+        }
+        Ok(Answer::Value(items))
+    }
 
-        let subject: String = Input::with_theme(theme)
-            .with_prompt(format!("Subject (max {} chars)", max_len))
-            .validate_with(|input: &String| {
-                if input.is_empty() {
-                    Err("Subject is required")
-                } else if input.len() > max_len {
-                    Err("Subject is too long")
-                } else if input.len() < self.config.rules.min_subject_length {
-                    Err("Subject is too short")
-                } else {
-                    Ok(())
+    /// Prompt for missing required trailers, then offer to add others.
+    fn prompt_trailers(&self, theme: &ColorfulTheme) -> Result<Answer<Vec<Trailer>>> {
+        let missing = self.missing_trailers();
+        let mut trailers = Vec::new();
+        for token in &missing {
+            let mut input = Input::<String>::with_theme(theme).with_prompt(token.as_str());
+            if token.eq_ignore_ascii_case(SIGNED_OFF_BY) {
+                if let Some(identity) = git::get_identity() {
+                    input = input.default(identity);
                 }
+            }
+            let value = input.interact_text()?;
+            trailers.push(Trailer::new(token.as_str(), value.trim()));
+        }
+
+        if missing.is_empty() {
+            let add = Confirm::with_theme(theme)
+                .with_prompt("Add trailers (Closes, Refs, Co-authored-by, ...)?")
+                .default(false)
+                .interact_opt()?;
+            match add {
+                Some(true) => {}
+                Some(false) => return Ok(Answer::Value(trailers)),
+                None => return Ok(Answer::Back),
+            }
+        }
+
+        let mut items = vec![DONE_TRAILERS];
+        items.extend(COMMON_TOKENS);
+        items.push(OTHER_TRAILER);
+        loop {
+            let choice = Select::with_theme(theme)
+                .with_prompt("Trailer")
+                .items(&items)
+                .default(0)
+                .interact_opt()?;
+            let token = match choice.map(|index| items[index]) {
+                None | Some(DONE_TRAILERS) => break,
+                Some(OTHER_TRAILER) => Input::<String>::with_theme(theme)
+                    .with_prompt("Token")
+                    .validate_with(|token: &String| {
+                        if trailers::is_token(token.trim()) {
+                            Ok(())
+                        } else {
+                            Err("Use letters, digits and dashes, e.g. Reviewed-by")
+                        }
+                    })
+                    .interact_text()?
+                    .trim()
+                    .to_string(),
+                Some(token) => token.to_string(),
+            };
+            let value: String = Input::with_theme(theme)
+                .with_prompt(token.as_str())
+                .interact_text()?;
+            trailers.push(Trailer::new(token, value.trim()));
+        }
+        Ok(Answer::Value(trailers))
+    }
+
+    /// Trailers in the footer, including ones added so far.
+    fn current_trailers(&self) -> Vec<Trailer> {
+        let mut current = self
+            .footer
+            .as_deref()
+            .map(trailers::parse)
+            .unwrap_or_default();
+        current.extend(self.trailers.iter().cloned());
+        current
+    }
+
+    /// Required trailers the message doesn't have yet.
+    fn missing_trailers(&self) -> Vec<String> {
+        let current = self.current_trailers();
+        self.config
+            .rules
+            .required_trailers
+            .iter()
+            .filter(|token| !current.iter().any(|t| t.has_token(token)))
+            .cloned()
+            .collect()
+    }
+
+    /// Missing required trailers that have a default: a sign-off with the
+    /// committer identity, as `git commit --signoff` would add.
+    fn default_trailers(&self) -> Vec<Trailer> {
+        self.missing_trailers()
+            .iter()
+            .filter(|token| token.eq_ignore_ascii_case(SIGNED_OFF_BY))
+            .filter_map(|token| git::get_identity().map(|id| Trailer::new(token.as_str(), id)))
+            .collect()
+    }
+
+    /// The branch the commit lands on.
+    fn branch(&self) -> Option<String> {
+        match self.target {
+            git::CommitTarget::Branch(ref branch) => Some(branch.clone()),
+            _ => match self.context {
+                Some(ref context) => Some(context.branch.clone()),
+                None => git::get_branch_name().ok(),
+            },
+        }
+    }
+
+    /// Values for the template placeholders. The ticket is the trailer
+    /// `rules.ticket.token` names, or the ticket in the branch name.
+    fn template_vars(&self) -> TemplateVars {
+        let config = &self.config.rules.ticket;
+        let branch = self.branch();
+        let ticket = self
+            .current_trailers()
+            .into_iter()
+            .find(|t| t.has_token(&config.token))
+            .map(|t| t.value)
+            .or_else(|| {
+                branch
+                    .as_deref()
+                    .and_then(|b| ticket::branch_ticket(config, b))
+            });
+        TemplateVars {
+            commit_type: self.commit_type.as_ref().map(|t| t.as_str().to_string()),
+            scope: self.scope.clone(),
+            ticket,
+            branch,
+        }
+    }
+
+    /// The template's subject, filled in.
+    fn template_subject(&self) -> Option<String> {
+        let subject = self.template.as_ref()?.subject.as_deref()?;
+        Some(template::render(subject, &self.template_vars())).filter(|s| !s.trim().is_empty())
+    }
+
+    /// The template's footer lines, filled in, without the trailers the
+    /// message already has.
+    fn template_footer(&self) -> Option<String> {
+        let footer = self.template.as_ref()?.footer.as_deref()?;
+        let current = self.current_trailers();
+        let lines: Vec<String> = template::render_footer(footer, &self.template_vars())?
+            .lines()
+            .filter(|line| {
+                !trailers::parse(line)
+                    .iter()
+                    .any(|t| current.iter().any(|c| c.has_token(&t.token)))
             })
-            .interact_text()?;
+            .map(str::to_string)
+            .collect();
+        Some(lines.join("\n")).filter(|footer| !footer.is_empty())
+    }
 
-        Ok(subject)
+    /// An error for the sections of an enforced template that `message`
+    /// leaves empty.
+    fn template_issue(&self, message: &CommitMessage) -> Option<ValidationIssue> {
+        let message_template = self.template.as_ref().filter(|t| t.enforce)?;
+        let template_body = template::render(&message_template.body, &self.template_vars());
+        let empty: Vec<&str> =
+            template::empty_sections(&template_body, message.body.as_deref().unwrap_or(""))
+                .into_iter()
+                .map(|header| header.trim_end_matches(':'))
+                .collect();
+        if empty.is_empty() {
+            return None;
+        }
+        Some(ValidationIssue {
+            code: "template-section-empty".to_string(),
+            message: format!("Body leaves template sections empty: {}", empty.join(", ")),
+            suggestion: Some(format!("Fill in: {}", empty.join(", "))),
+            is_error: true,
+            line: Some(3),
+            span: None,
+        })
     }
 
-    /// Prompt for body.
-    fn prompt_body(&self, _theme: &ColorfulTheme) -> Result<Option<String>> {
-        let body = Editor::new()
-            .edit("Enter commit body (save and close to continue)")
-            .map_err(|e| CkError::Ui(e.to_string()))?;
+    /// Check whether the footer already lists deprecations.
+    fn has_deprecated_footer(&self) -> bool {
+        self.footer
+            .as_deref()
+            .is_some_and(|footer| !super::deprecation::parse(footer).is_empty())
+    }
+
+    /// Check whether the footer already describes a breaking change.
+    fn has_breaking_footer(&self) -> bool {
+        self.footer
+            .as_deref()
+            .is_some_and(|footer| footer.contains(BREAKING_CHANGE_TOKEN))
+    }
+
+    /// Check whether the selected type requires body sections.
+    fn has_body_sections(&self) -> bool {
+        let from_template = self
+            .template
+            .as_ref()
+            .is_some_and(|t| !template::sections(&t.body).is_empty());
+        from_template
+            || self
+                .commit_type
+                .as_ref()
+                .is_some_and(|t| !self.config.rules.body_sections_for(t.as_str()).is_empty())
+    }
+
+    /// Build the editor template: the message template's body, or the
+    /// section headers required for the selected type.
+    fn body_template(&self) -> String {
+        if let Some(ref message_template) = self.template {
+            return template::render(&message_template.body, &self.template_vars());
+        }
+        let commit_type = match &self.commit_type {
+            Some(t) => t,
+            None => return String::new(),
+        };
 
-        Ok(body.filter(|s| !s.trim().is_empty()))
+        self.config
+            .rules
+            .body_sections_for(commit_type.as_str())
+            .iter()
+            .map(|section| format!("{} \n", section.header))
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 }
 
@@ -429,6 +1435,153 @@ mod tests {
         assert_eq!(builder.commit_type, Some(CommitType::Feat));
     }
 
+    #[test]
+    fn test_breaking_change_footer() {
+        let builder = CommitBuilder::new(CkConfig::default())
+            .with_type(CommitType::Feat)
+            .with_subject("drop the legacy output flag")
+            .with_trailer("Refs", "#42")
+            .with_deprecation("--output, use --format")
+            .with_breaking_change(
+                BreakingChange::new("--output was removed").with_step("Use --format instead"),
+            );
+
+        let message = builder.build_message().unwrap();
+        assert!(message.is_breaking);
+        assert_eq!(
+            message.footer.as_deref(),
+            Some("Refs: #42\nDeprecated: --output, use --format\nBREAKING CHANGE: --output was removed\n  Migration:\n  - Use --format instead")
+        );
+        assert_eq!(message.deprecations(), vec!["--output, use --format"]);
+        assert_eq!(
+            message.breaking_change().unwrap().migration,
+            vec!["Use --format instead"]
+        );
+    }
+
+    #[test]
+    fn test_from_message_keeps_footer() {
+        let original =
+            CommitMessage::parse("Fix: Handle empty input.\n\nDetails.\n\nRefs: #12").unwrap();
+        let builder = CommitBuilder::from_message(CkConfig::default(), &original);
+
+        let message = builder.build_message().unwrap();
+        assert_eq!(message.commit_type, CommitType::Fix);
+        assert_eq!(message.body.as_deref(), Some("Details."));
+        assert_eq!(message.footer.as_deref(), Some("Refs: #12"));
+    }
+
+    #[test]
+    fn test_trailers() {
+        let mut config = CkConfig::default();
+        config.rules.required_trailers = vec!["Signed-off-by".to_string(), "Refs".to_string()];
+        let mut builder = CommitBuilder::new(config)
+            .with_type(CommitType::Fix)
+            .with_subject("handle empty input")
+            .with_trailer("refs", "#12")
+            .with_deprecation("old_fn, use new_fn");
+        assert_eq!(builder.missing_trailers(), vec!["Signed-off-by"]);
+
+        builder.trailers = vec![Trailer::new("Signed-off-by", "Ada <ada@example.com>")];
+        assert!(builder.missing_trailers().is_empty());
+        let message = builder.build_message().unwrap();
+        assert_eq!(
+            message.footer.as_deref(),
+            Some("refs: #12\nDeprecated: old_fn, use new_fn\nSigned-off-by: Ada <ada@example.com>")
+        );
+    }
+
+    #[test]
+    fn test_body_template() {
+        let mut config = CkConfig::default();
+        config.rules.body_sections.insert(
+            "fix".to_string(),
+            vec![
+                crate::config::BodySectionConfig {
+                    header: "Root cause:".to_string(),
+                    pattern: None,
+                },
+                crate::config::BodySectionConfig {
+                    header: "Testing:".to_string(),
+                    pattern: None,
+                },
+            ],
+        );
+
+        let builder = CommitBuilder::new(config.clone()).with_type(CommitType::Fix);
+        assert!(builder.has_body_sections());
+        assert_eq!(builder.body_template(), "Root cause: \n\nTesting: \n");
+
+        let builder = CommitBuilder::new(config).with_type(CommitType::Feat);
+        assert!(builder.body_template().is_empty());
+    }
+
+    #[test]
+    fn test_message_template() {
+        let message_template = MessageTemplate {
+            commit_type: Some("fix".to_string()),
+            scope: Some("auth".to_string()),
+            body: "Why ({scope}):\n\nTesting:\n".to_string(),
+            footer: Some("Refs: {ticket}\nReviewed-in: {scope}".to_string()),
+            enforce: true,
+            ..MessageTemplate::default()
+        };
+        let builder = CommitBuilder::new(CkConfig::default())
+            .with_scope("api")
+            .with_template(message_template)
+            .unwrap()
+            .with_trailer("Refs", "#42")
+            .with_subject("retry token refresh");
+        assert_eq!(builder.commit_type, Some(CommitType::Fix));
+        assert!(builder.has_body_sections());
+        assert_eq!(builder.body_template(), "Why (api):\n\nTesting:\n");
+
+        let message = builder.build_message().unwrap();
+        assert_eq!(
+            message.footer.as_deref(),
+            Some("Refs: #42\nReviewed-in: api")
+        );
+        let issue = builder.template_issue(&message).unwrap();
+        assert_eq!(issue.code, "template-section-empty");
+
+        let builder = builder.with_body("Why (api): tokens expire early\n\nTesting: unit tests");
+        assert!(builder
+            .template_issue(&builder.build_message().unwrap())
+            .is_none());
+    }
+
+    #[test]
+    fn test_template_ticket_from_branch() {
+        let message_template = MessageTemplate {
+            footer: Some("Refs: {ticket}".to_string()),
+            ..MessageTemplate::default()
+        };
+        let builder = CommitBuilder::new(CkConfig::default())
+            .with_type(CommitType::Fix)
+            .with_onto("feature/JIRA-1234-login")
+            .with_template(message_template)
+            .unwrap()
+            .with_subject("retry token refresh");
+        assert_eq!(builder.template_vars().ticket.as_deref(), Some("JIRA-1234"));
+
+        let builder = builder.with_onto("fix/login-timeout");
+        assert!(builder.template_vars().ticket.is_none());
+    }
+
+    #[test]
+    fn test_scope_normalized() {
+        let mut config = CkConfig::default();
+        config.rules.scope.case = Some(crate::config::ScopeCase::Kebab);
+        let builder = CommitBuilder::new(config)
+            .with_type(CommitType::Feat)
+            .with_scope("UserProfile")
+            .with_subject("add avatar upload");
+        assert_eq!(
+            builder.build_message().unwrap().header(),
+            "feat(user-profile): add avatar upload"
+        );
+    }
+
     #[test]
     fn test_commit_builder_build() {
         let config = CkConfig::default();
@@ -442,4 +1595,83 @@ mod tests {
         assert_eq!(message.scope, Some("core".to_string()));
         assert_eq!(message.subject, "add feature");
     }
+
+    #[test]
+    fn test_gitmoji() {
+        let mut config = CkConfig::default();
+        config.ui.emoji_style = EmojiStyle::Gitmoji;
+        let builder = CommitBuilder::new(config.clone())
+            .with_type(CommitType::Feat)
+            .with_subject("add export");
+        assert_eq!(builder.steps(true, false)[0], Step::Emoji);
+        assert_eq!(
+            builder.build_message().unwrap().header(),
+            "feat: ✨ add export"
+        );
+
+        let builder = builder.with_emoji("🚀");
+        assert!(!builder.steps(true, false).contains(&Step::Emoji));
+        assert_eq!(
+            builder.build_message().unwrap().header(),
+            "feat: 🚀 add export"
+        );
+
+        let builder = CommitBuilder::new(CkConfig::default())
+            .with_type(CommitType::Feat)
+            .with_subject("add export");
+        assert_eq!(
+            builder.build_message().unwrap().header(),
+            "feat: add export"
+        );
+    }
+
+    #[test]
+    fn test_steps_skip_known_values() {
+        let builder = CommitBuilder::new(CkConfig::default())
+            .with_type(CommitType::Feat)
+            .with_subject("add feature");
+        assert_eq!(
+            builder.steps(false, false),
+            vec![
+                Step::Scope,
+                Step::Body,
+                Step::Breaking { confirm: true },
+                Step::Trailers,
+            ]
+        );
+        assert_eq!(
+            builder.steps(true, false),
+            vec![Step::Scope, Step::Body, Step::Trailers]
+        );
+
+        let builder = CommitBuilder::new(CkConfig::default())
+            .with_breaking(true)
+            .with_body("why");
+        assert_eq!(
+            builder.steps(false, true),
+            vec![
+                Step::Type,
+                Step::Scope,
+                Step::Subject,
+                Step::Breaking { confirm: false },
+                Step::Deprecations,
+                Step::Trailers,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_accept_default() {
+        let mut builder = CommitBuilder::new(CkConfig::default()).with_defaults_accepted(true);
+
+        // Nothing to suggest a type or subject from
+        assert!(!builder.accept_default(Step::Type));
+        assert!(!builder.accept_default(Step::Subject));
+
+        assert!(builder.accept_default(Step::Scope));
+        assert!(builder.scope.is_none());
+        assert!(builder.accept_default(Step::Body));
+        assert!(builder.accept_default(Step::Breaking { confirm: true }));
+        assert!(!builder.is_breaking);
+    }
 }
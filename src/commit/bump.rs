@@ -0,0 +1,207 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Next release version from the commits since the last tag.
+//!
+//! Breaking changes bump the major version, `feat` commits the minor
+//! version and `fix` commits the patch version. Other commits don't call
+//! for a release.
+
+use crate::cli::args::OutputFormat;
+use crate::config::CommitType;
+use crate::error::{CkError, GitError, Result};
+use crate::git::tags::{latest_version_tag, Version};
+use crate::git::Repository;
+use console::style;
+use serde::Serialize;
+
+use super::message::CommitMessage;
+
+/// Version component a set of commits calls for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    /// The bump one commit message calls for.
+    pub fn for_message(message: &str) -> Self {
+        match CommitMessage::parse(message) {
+            Ok(parsed) if parsed.is_breaking || parsed.breaking_change().is_some() => Bump::Major,
+            Ok(parsed) => match parsed.commit_type {
+                CommitType::Feat => Bump::Minor,
+                CommitType::Fix => Bump::Patch,
+                _ => Bump::None,
+            },
+            Err(_) => Bump::None,
+        }
+    }
+
+    /// Apply the bump to `version`.
+    pub fn apply(self, version: Version) -> Version {
+        match self {
+            Bump::None => version,
+            Bump::Patch => Version {
+                patch: version.patch + 1,
+                ..version
+            },
+            Bump::Minor => Version {
+                minor: version.minor + 1,
+                patch: 0,
+                ..version
+            },
+            Bump::Major => Version {
+                major: version.major + 1,
+                minor: 0,
+                patch: 0,
+            },
+        }
+    }
+
+    /// Lowercase name of the bump.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Bump::None => "none",
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        }
+    }
+}
+
+/// The next release, computed from the history since the last version tag.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleasePlan {
+    /// Latest version tag reachable from HEAD.
+    pub previous_tag: Option<String>,
+    /// Version of that tag, or 0.0.0 without one.
+    pub current: Version,
+    /// Bump the commits call for.
+    pub bump: Bump,
+    /// Version after the bump.
+    pub next: Version,
+    /// Tag name for the next version, keeping the previous tag's prefix.
+    pub next_tag: String,
+    /// Number of commits since the previous tag.
+    pub commits: usize,
+}
+
+impl ReleasePlan {
+    /// Compute the next release for HEAD.
+    pub fn compute(repo: &Repository) -> Result<Self> {
+        let previous = latest_version_tag(repo)?;
+        let messages = messages_since(repo, previous.as_ref().map(|t| t.commit))?;
+        let bump = messages
+            .iter()
+            .map(|message| Bump::for_message(message))
+            .max()
+            .unwrap_or(Bump::None);
+
+        let current = previous.as_ref().map(|t| t.version).unwrap_or_default();
+        let prefix = previous.as_ref().map_or("v", |t| t.prefix());
+        let next = bump.apply(current);
+        Ok(Self {
+            next_tag: format!("{}{}", prefix, next),
+            previous_tag: previous.as_ref().map(|t| t.name.clone()),
+            current,
+            bump,
+            next,
+            commits: messages.len(),
+        })
+    }
+
+    /// Whether the commits since the previous tag call for a release.
+    pub fn needs_release(&self) -> bool {
+        self.bump != Bump::None
+    }
+
+    /// Print the plan: the next tag on stdout, the reasoning on stderr.
+    pub fn print(&self, format: Option<OutputFormat>) {
+        if let Some(OutputFormat::Json) = format {
+            println!("{}", serde_json::to_string_pretty(self).unwrap_or_default());
+            return;
+        }
+
+        let since = self
+            .previous_tag
+            .as_deref()
+            .unwrap_or("the start of history");
+        if !self.needs_release() {
+            eprintln!(
+                "{} No feat, fix or breaking commits in {} commit(s) since {}",
+                style("ℹ").blue(),
+                self.commits,
+                since
+            );
+            return;
+        }
+
+        eprintln!(
+            "{} {} → {} ({}, {} commit(s) since {})",
+            style("✓").green(),
+            self.current,
+            style(self.next).bold(),
+            self.bump.as_str(),
+            self.commits,
+            since
+        );
+        println!("{}", self.next_tag);
+    }
+}
+
+/// Messages of the commits reachable from HEAD but not from `since`.
+fn messages_since(repo: &Repository, since: Option<git2::Oid>) -> Result<Vec<String>> {
+    let git = repo.inner();
+    let walk_error = |e: git2::Error| {
+        CkError::Git(GitError::CommandFailed {
+            command: "revwalk".to_string(),
+            message: e.message().to_string(),
+        })
+    };
+
+    let mut revwalk = git.revwalk().map_err(walk_error)?;
+    revwalk.push(repo.head_commit()?.id()).map_err(walk_error)?;
+    if let Some(since) = since {
+        revwalk.hide(since).map_err(walk_error)?;
+    }
+
+    let mut messages = Vec::new();
+    for oid in revwalk {
+        let commit = git
+            .find_commit(oid.map_err(walk_error)?)
+            .map_err(walk_error)?;
+        messages.push(commit.message().unwrap_or("").to_string());
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bump_for_message() {
+        assert_eq!(Bump::for_message("feat(api): add export"), Bump::Minor);
+        assert_eq!(Bump::for_message("fix: handle timeouts"), Bump::Patch);
+        assert_eq!(Bump::for_message("feat!: drop the v1 API"), Bump::Major);
+        assert_eq!(
+            Bump::for_message("fix: rename flag\n\nBREAKING CHANGE: --out is now --output"),
+            Bump::Major
+        );
+        assert_eq!(Bump::for_message("docs: update readme"), Bump::None);
+        assert_eq!(Bump::for_message("Merge branch 'main'"), Bump::None);
+    }
+
+    #[test]
+    fn test_bump_apply() {
+        let version: Version = "1.4.2".parse().unwrap();
+        assert_eq!(Bump::None.apply(version).to_string(), "1.4.2");
+        assert_eq!(Bump::Patch.apply(version).to_string(), "1.4.3");
+        assert_eq!(Bump::Minor.apply(version).to_string(), "1.5.0");
+        assert_eq!(Bump::Major.apply(version).to_string(), "2.0.0");
+    }
+}
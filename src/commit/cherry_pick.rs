@@ -0,0 +1,220 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Message adaptation for commits carried over to another branch.
+
+use crate::analysis::RepositoryContext;
+use crate::config::CkConfig;
+use crate::error::{CkError, Result, ValidationError};
+use crate::git;
+use crate::rules::RuleEngine;
+
+use super::builder::CommitBuilder;
+use super::message::CommitMessage;
+
+/// How a picked commit is finalized.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PickOptions {
+    /// Review the adapted message in the builder.
+    pub interactive: bool,
+    /// Only print the adapted message.
+    pub dry_run: bool,
+    /// Skip the builder's confirmation prompt.
+    pub yes: bool,
+    /// Sign the resulting commit.
+    pub sign: bool,
+}
+
+/// Cherry-pick `reference` onto HEAD and rewrite its message for this
+/// branch, appending `trailer` to the footer.
+pub fn pick_commit(
+    config: &CkConfig,
+    reference: &str,
+    trailer: &str,
+    options: PickOptions,
+) -> Result<()> {
+    let repo = git::open_repo()?;
+    let commit = repo.get_commit(reference)?;
+    let sha = commit.id().to_string();
+    let original = commit.message().unwrap_or("").to_string();
+
+    // Analyze the change against this branch's layout
+    let context = RepositoryContext::from_diff(git::get_diff(&sha)?, config);
+    let message = CommitMessage::parse_with(&original, config)
+        .map(|m| adapt_message(&m, context.suggested_scope.as_deref(), trailer))?;
+
+    if options.dry_run {
+        println!("{}", message.format());
+        return Ok(());
+    }
+
+    // Catch a broken signing setup before anything is picked
+    if options.sign {
+        git::signing::check_signing_setup()?;
+    }
+
+    if options.interactive {
+        git::commands::cherry_pick(&sha)?;
+        return CommitBuilder::from_message(config.clone(), &message)
+            .with_context(context)
+            .with_defaults_accepted(options.yes)
+            .run_interactive(false, options.sign, true);
+    }
+
+    // Validate before picking so a failure leaves the branch untouched
+    let validation = RuleEngine::new(config.clone()).validate(&message);
+    if !validation.is_valid() {
+        for error in &validation.errors {
+            eprintln!("{}", error.format());
+        }
+        return Err(CkError::Validation(ValidationError::MultipleErrors {
+            count: validation.errors.len(),
+        }));
+    }
+
+    git::commands::cherry_pick(&sha)?;
+    let new_sha = git::commands::amend_commit(
+        &message.format(),
+        options.sign,
+        &git::CommitOptions::default(),
+    )?;
+    println!(
+        "[{}] {}",
+        &new_sha[..7.min(new_sha.len())],
+        message.header()
+    );
+    Ok(())
+}
+
+/// Pick `reference` onto each of `branches` with a `Backport-of:` trailer.
+///
+/// Each branch is validated with its own branch rules, and signing is
+/// forced where the branch requires signed commits. The original branch
+/// is checked out again afterwards, unless a pick fails and needs manual
+/// resolution.
+pub fn backport(
+    config: &CkConfig,
+    reference: &str,
+    branches: &[String],
+    options: PickOptions,
+) -> Result<Vec<String>> {
+    let repo = git::open_repo()?;
+    let sha = repo.get_commit(reference)?.id().to_string();
+    let original_branch = repo.branch_name()?;
+    let trailer = backport_trailer(&sha);
+    let mut done = Vec::new();
+
+    for branch in branches {
+        let mut branch_config = config.clone();
+        branch_config.rules = config.rules.for_branch(branch);
+        let require_signed = config
+            .rules
+            .branch_rules(branch)
+            .and_then(|rules| rules.require_signed)
+            .unwrap_or(false);
+        let branch_options = PickOptions {
+            sign: options.sign || require_signed,
+            ..options
+        };
+
+        if options.dry_run {
+            println!("# {}", branch);
+        } else {
+            git::commands::checkout(branch)?;
+        }
+        if let Err(e) = pick_commit(&branch_config, &sha, &trailer, branch_options) {
+            return Err(CkError::WithContext {
+                code: "backport-failed",
+                context: format!("backport to {}", branch),
+                message: format!(
+                    "{} (backported so far: {})",
+                    e,
+                    if done.is_empty() {
+                        "none".to_string()
+                    } else {
+                        done.join(", ")
+                    }
+                ),
+            });
+        }
+        done.push(branch.clone());
+    }
+
+    if !options.dry_run {
+        git::commands::checkout(&original_branch)?;
+    }
+    Ok(done)
+}
+
+/// Trailer recording the commit a backport was made from.
+pub fn backport_trailer(sha: &str) -> String {
+    format!("Backport-of: {}", sha)
+}
+
+/// Adapt the message of a picked commit to its new branch.
+///
+/// The scope is replaced when analysis of the change on this branch
+/// suggests a different one, and `trailer` is appended to the footer
+/// unless it is already there.
+pub fn adapt_message(
+    original: &CommitMessage,
+    inferred_scope: Option<&str>,
+    trailer: &str,
+) -> CommitMessage {
+    let mut message = original.clone();
+
+    if let (Some(old), Some(new)) = (original.scope.as_deref(), inferred_scope) {
+        if old != new {
+            tracing::debug!("Re-scoping picked commit from '{}' to '{}'", old, new);
+            message.scope = Some(new.to_string());
+        }
+    }
+
+    let already_present = message
+        .footer
+        .as_deref()
+        .is_some_and(|footer| footer.lines().any(|line| line.trim() == trailer));
+    if !already_present {
+        message.footer = Some(match message.footer {
+            Some(footer) => format!("{}\n{}", footer, trailer),
+            None => trailer.to_string(),
+        });
+    }
+
+    message
+}
+
+/// Trailer git adds for `cherry-pick -x`.
+pub fn cherry_picked_trailer(sha: &str) -> String {
+    format!("(cherry picked from commit {})", sha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adapt_rescopes_and_appends_trailer() {
+        let original = CommitMessage::parse("fix(web): handle empty input").unwrap();
+        let trailer = cherry_picked_trailer("abc123");
+
+        let adapted = adapt_message(&original, Some("frontend"), &trailer);
+        assert_eq!(adapted.scope.as_deref(), Some("frontend"));
+        assert_eq!(
+            adapted.format(),
+            "fix(frontend): handle empty input\n\n(cherry picked from commit abc123)"
+        );
+
+        // Adapting twice does not duplicate the trailer
+        let again = adapt_message(&adapted, None, &trailer);
+        assert_eq!(again.format(), adapted.format());
+    }
+
+    #[test]
+    fn test_adapt_keeps_missing_scope() {
+        let original = CommitMessage::parse("docs: fix typo").unwrap();
+        let adapted = adapt_message(&original, Some("core"), "Backport-of: abc123");
+        assert_eq!(adapted.scope, None);
+        assert_eq!(adapted.footer.as_deref(), Some("Backport-of: abc123"));
+    }
+}
@@ -0,0 +1,161 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Gentle hints about commit habits shown in the builder.
+
+use crate::analysis::RepositoryContext;
+use crate::config::{CoachingConfig, CommitType};
+use crate::git::Repository;
+
+use super::message::CommitMessage;
+
+/// Recent history relevant to coaching.
+#[derive(Debug, Clone, Default)]
+pub struct RecentHistory {
+    /// Hours since the last commit, if there is one.
+    pub hours_since_last: Option<f64>,
+    /// Type and changed line count of recent commits, newest first.
+    pub commits: Vec<(Option<CommitType>, usize)>,
+}
+
+impl RecentHistory {
+    /// Read recent history from the repository.
+    pub fn load(repo: &Repository, depth: usize) -> Self {
+        let git = repo.inner();
+        let mut revwalk = match git.revwalk() {
+            Ok(revwalk) => revwalk,
+            Err(_) => return Self::default(),
+        };
+        if revwalk.push_head().is_err() {
+            return Self::default();
+        }
+
+        let mut history = Self::default();
+        for commit in revwalk
+            .filter_map(|oid| oid.ok())
+            .filter_map(|oid| git.find_commit(oid).ok())
+            .take(depth)
+        {
+            if history.hours_since_last.is_none() {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or_default();
+                history.hours_since_last =
+                    Some((now - commit.time().seconds()).max(0) as f64 / 3600.0);
+            }
+
+            let commit_type = CommitMessage::parse(commit.message().unwrap_or(""))
+                .ok()
+                .map(|m| m.commit_type);
+            let lines = commit
+                .tree()
+                .ok()
+                .and_then(|tree| {
+                    let parent = commit.parent(0).ok().and_then(|p| p.tree().ok());
+                    git.diff_tree_to_tree(parent.as_ref(), Some(&tree), None)
+                        .ok()
+                })
+                .and_then(|diff| diff.stats().ok())
+                .map(|stats| stats.insertions() + stats.deletions())
+                .unwrap_or(0);
+            history.commits.push((commit_type, lines));
+        }
+
+        history
+    }
+}
+
+/// Coaching hints for the staged changes in `context`.
+pub fn coaching_hints(config: &CoachingConfig, context: &RepositoryContext) -> Vec<String> {
+    if !config.enabled {
+        return Vec::new();
+    }
+
+    let history = match Repository::open_current() {
+        Ok(repo) => RecentHistory::load(&repo, config.chore_streak.max(1)),
+        Err(_) => return Vec::new(),
+    };
+    hints_for(config, &history, context.diff_stats.total_lines_changed())
+}
+
+/// Hints for the given history and size of the staged diff.
+pub fn hints_for(
+    config: &CoachingConfig,
+    history: &RecentHistory,
+    staged_lines: usize,
+) -> Vec<String> {
+    let mut hints = Vec::new();
+
+    let stale = history
+        .hours_since_last
+        .is_some_and(|hours| hours > config.stale_hours as f64);
+    if stale && staged_lines > config.large_diff_lines {
+        hints.push(format!(
+            "This is {} changed lines since a commit over {}h ago; consider committing more often",
+            staged_lines, config.stale_hours
+        ));
+    }
+
+    let streak = history
+        .commits
+        .iter()
+        .take_while(|(commit_type, lines)| {
+            *commit_type == Some(CommitType::Chore) && *lines <= config.small_commit_lines
+        })
+        .count();
+    if config.chore_streak > 0 && streak >= config.chore_streak {
+        hints.push(format!(
+            "The last {} commits are small chores; these could be squashed",
+            streak
+        ));
+    }
+
+    hints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CoachingConfig {
+        CoachingConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stale_large_diff_hint() {
+        let history = RecentHistory {
+            hours_since_last: Some(30.0),
+            commits: vec![(Some(CommitType::Feat), 50)],
+        };
+        let hints = hints_for(&config(), &history, 2000);
+        assert_eq!(hints.len(), 1);
+        assert!(hints[0].contains("committing more often"));
+
+        // A big diff shortly after the last commit is fine
+        let recent = RecentHistory {
+            hours_since_last: Some(1.0),
+            ..history
+        };
+        assert!(hints_for(&config(), &recent, 2000).is_empty());
+    }
+
+    #[test]
+    fn test_chore_streak_hint() {
+        let chore = (Some(CommitType::Chore), 3);
+        let history = RecentHistory {
+            hours_since_last: Some(0.5),
+            commits: vec![chore; 5],
+        };
+        let hints = hints_for(&config(), &history, 10);
+        assert_eq!(hints.len(), 1);
+        assert!(hints[0].contains("squashed"));
+
+        let mut broken = history.clone();
+        broken.commits[2] = (Some(CommitType::Fix), 3);
+        assert!(hints_for(&config(), &broken, 10).is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `Deprecated:` footers and detection of deprecations in a diff.
+
+use crate::git::DiffInfo;
+
+/// Footer token for deprecations.
+pub const DEPRECATED_TOKEN: &str = "Deprecated";
+
+/// Misspellings of the token that are flagged by validation.
+const TOKEN_VARIANTS: &[&str] = &["deprecated", "deprecation", "deprecates"];
+
+/// Markers that deprecate an item in common languages.
+const DEPRECATION_MARKERS: &[&str] = &["#[deprecated", "@deprecated", "@Deprecated"];
+
+/// Prefixes of public API declarations.
+const PUBLIC_API_PREFIXES: &[&str] = &[
+    "pub fn ",
+    "pub struct ",
+    "pub enum ",
+    "pub trait ",
+    "pub const ",
+    "pub type ",
+    "pub mod ",
+    "export function ",
+    "export class ",
+    "export const ",
+    "export interface ",
+    "export type ",
+];
+
+/// Render a `Deprecated:` footer line.
+pub fn render(item: &str) -> String {
+    format!("{}: {}", DEPRECATED_TOKEN, item.trim())
+}
+
+/// Items listed in `Deprecated:` footer lines.
+pub fn parse(footer: &str) -> Vec<String> {
+    let prefix = format!("{}:", DEPRECATED_TOKEN);
+    footer
+        .lines()
+        .filter_map(|line| line.strip_prefix(&prefix))
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Problems with `Deprecated:` footer lines, one message per line.
+pub fn check_footer(footer: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    for line in footer.lines() {
+        let (token, value) = match line.split_once(':') {
+            Some((token, value)) => (token.trim(), value.trim()),
+            None => continue,
+        };
+
+        if token == DEPRECATED_TOKEN {
+            if value.is_empty() {
+                problems.push("Deprecated footer must name what is deprecated".to_string());
+            }
+        } else if TOKEN_VARIANTS.contains(&token.to_lowercase().as_str()) {
+            problems.push(format!(
+                "Use '{}:' instead of '{}:' for deprecations",
+                DEPRECATED_TOKEN, token
+            ));
+        }
+    }
+    problems
+}
+
+/// Reasons the staged diff looks like it deprecates or removes public API.
+pub fn detect(diff: &DiffInfo) -> Vec<String> {
+    let mut paths: Vec<_> = diff.patches.keys().collect();
+    paths.sort();
+
+    let mut signals = Vec::new();
+    for path in paths {
+        let patch = &diff.patches[path];
+        let added: Vec<&str> = changed_lines(patch, '+').collect();
+
+        if added
+            .iter()
+            .any(|line| DEPRECATION_MARKERS.iter().any(|m| line.contains(m)))
+        {
+            signals.push(format!("adds a deprecation marker in {}", path.display()));
+        }
+
+        for removed in changed_lines(patch, '-') {
+            // A changed signature is re-added in the same patch
+            let is_public = PUBLIC_API_PREFIXES.iter().any(|p| removed.starts_with(p));
+            if is_public && !added.contains(&removed) {
+                let declaration = removed.trim_end_matches(['{', ';']).trim_end();
+                signals.push(format!("removes `{}` from {}", declaration, path.display()));
+            }
+        }
+    }
+    signals
+}
+
+/// Trimmed added or removed lines of a patch.
+fn changed_lines(patch: &str, origin: char) -> impl Iterator<Item = &str> {
+    patch
+        .lines()
+        .filter_map(move |line| line.strip_prefix(origin))
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_and_check_footer() {
+        let footer = "Refs: #3\nDeprecated: Config::load_legacy, use Config::load\nDeprecated: ";
+        assert_eq!(parse(footer), vec!["Config::load_legacy, use Config::load"]);
+        assert_eq!(check_footer(footer).len(), 1);
+        assert_eq!(
+            check_footer("DEPRECATION: old flag"),
+            vec!["Use 'Deprecated:' instead of 'DEPRECATION:' for deprecations"]
+        );
+        assert!(check_footer("Refs: #3").is_empty());
+    }
+
+    #[test]
+    fn test_detect() {
+        let mut diff = DiffInfo::empty();
+        diff.patches.insert(
+            PathBuf::from("src/lib.rs"),
+            "-pub fn old_api() {\n+#[deprecated(note = \"use new_api\")]\n-pub fn changed(a: u8) {\n+pub fn changed(a: u8) {\n"
+                .to_string(),
+        );
+
+        let signals = detect(&diff);
+        assert_eq!(
+            signals,
+            vec![
+                "adds a deprecation marker in src/lib.rs",
+                "removes `pub fn old_api()` from src/lib.rs"
+            ]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Editing commit messages in the user's editor.
+//!
+//! The editor is picked like git picks it, with `ui.editor` taking
+//! precedence: `ui.editor`, `$GIT_EDITOR`, `$VISUAL`, `$EDITOR`,
+//! `core.editor`, then `vi`. The message is written to `COMMIT_EDITMSG`
+//! in the git directory, so editors apply their commit message syntax,
+//! and lines starting with `#` are dropped afterwards.
+
+use crate::config::CkConfig;
+use crate::error::{CkError, Result};
+use crate::git;
+use crate::interrupt;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Editor used when nothing is configured.
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Prefix of lines removed from the edited message.
+pub const COMMENT_PREFIX: &str = "#";
+
+/// Instructions shown when editing a body.
+pub const BODY_INSTRUCTIONS: &[&str] = &[
+    "Enter the commit body: what changed and why. Lines starting",
+    "with '#' are ignored; leave it empty for no body.",
+    "",
+    "The full message follows the conventional format:",
+    "",
+    "  <type>(<scope>): <subject>",
+    "",
+    "  <body>",
+    "",
+    "  BREAKING CHANGE: <what breaks>",
+    "  Refs: #123",
+];
+
+/// Instructions shown when editing a whole message.
+pub const MESSAGE_INSTRUCTIONS: &[&str] = &[
+    "Edit the commit message. Lines starting with '#' are ignored.",
+    "",
+    "  <type>(<scope>): <subject>",
+    "",
+    "  <body>",
+    "",
+    "  <footers, e.g. Refs: #123>",
+];
+
+/// Pick the editor command.
+///
+/// `var` looks up environment variables and `core_editor` is git's
+/// `core.editor` setting.
+pub fn resolve_editor(
+    configured: Option<&str>,
+    var: impl Fn(&str) -> Option<String>,
+    core_editor: Option<String>,
+) -> String {
+    configured
+        .map(str::to_string)
+        .or_else(|| var("GIT_EDITOR"))
+        .or_else(|| var("VISUAL"))
+        .or_else(|| var("EDITOR"))
+        .or(core_editor)
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+/// Drop comment lines and surrounding blank lines.
+///
+/// Everything below the scissors line `git commit --verbose` adds is
+/// dropped too, as git does.
+pub fn strip_comments(text: &str) -> String {
+    let lines: Vec<&str> = text
+        .lines()
+        .take_while(|line| !(line.starts_with(COMMENT_PREFIX) && line.contains(" >8 ")))
+        .filter(|line| !line.starts_with(COMMENT_PREFIX))
+        .collect();
+    lines.join("\n").trim().to_string()
+}
+
+/// Opens the configured editor on a message.
+#[derive(Debug, Clone)]
+pub struct MessageEditor {
+    command: String,
+    instructions: Vec<String>,
+}
+
+impl MessageEditor {
+    /// An editor resolved from the configuration and environment.
+    pub fn from_config(config: &CkConfig) -> Self {
+        let core_editor = git::open_repo()
+            .ok()
+            .and_then(|repo| repo.inner().config().ok())
+            .or_else(|| git2::Config::open_default().ok())
+            .and_then(|config| config.get_string("core.editor").ok());
+
+        Self {
+            command: resolve_editor(
+                config.ui.editor.as_deref(),
+                |name| std::env::var(name).ok(),
+                core_editor,
+            ),
+            instructions: Vec::new(),
+        }
+    }
+
+    /// Add commented instructions below the message.
+    pub fn with_instructions(mut self, lines: &[&str]) -> Self {
+        self.instructions = lines.iter().map(|line| line.to_string()).collect();
+        self
+    }
+
+    /// The file contents the editor opens with.
+    pub fn template(&self, initial: &str) -> String {
+        let mut content = format!("{}\n", initial.trim_end());
+        if !self.instructions.is_empty() {
+            content.push('\n');
+            for line in &self.instructions {
+                if line.is_empty() {
+                    content.push_str(&format!("{}\n", COMMENT_PREFIX));
+                } else {
+                    content.push_str(&format!("{} {}\n", COMMENT_PREFIX, line));
+                }
+            }
+        }
+        content
+    }
+
+    /// Edit `initial`, returning the text without comments.
+    ///
+    /// Returns `None` if the editor exits with an error, as git aborts
+    /// the commit in that case.
+    pub fn edit(&self, initial: &str) -> Result<Option<String>> {
+        let path = message_path();
+        std::fs::write(&path, self.template(initial))?;
+
+        // Ctrl-C belongs to the editor while it runs
+        let status = {
+            let _ignoring = interrupt::ignore_interrupts();
+            editor_command(&self.command, &path).status()
+        }
+        .map_err(|e| CkError::Ui(format!("Failed to run editor '{}': {}", self.command, e)))?;
+        interrupt::checkpoint();
+        if !status.success() {
+            return Ok(None);
+        }
+
+        let edited = std::fs::read_to_string(&path)?;
+        Ok(Some(strip_comments(&edited)))
+    }
+}
+
+/// Where the message is written for editing.
+fn message_path() -> PathBuf {
+    match git::open_repo() {
+        Ok(repo) => repo.git_dir().join("COMMIT_EDITMSG"),
+        Err(_) => std::env::temp_dir().join("CK_EDITMSG"),
+    }
+}
+
+/// Run the editor through the shell, as git does, so commands with
+/// arguments like `code --wait` work.
+#[cfg(unix)]
+fn editor_command(editor: &str, path: &std::path::Path) -> Command {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(editor)
+        .arg(path);
+    command
+}
+
+#[cfg(not(unix))]
+fn editor_command(editor: &str, path: &std::path::Path) -> Command {
+    let mut parts = editor.split_whitespace();
+    let mut command = Command::new(parts.next().unwrap_or(DEFAULT_EDITOR));
+    command.args(parts).arg(path);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_resolve_editor() {
+        let env: HashMap<&str, &str> = [("VISUAL", "code --wait"), ("EDITOR", "nano")].into();
+        let var = |name: &str| env.get(name).map(|v| v.to_string());
+
+        assert_eq!(resolve_editor(Some("hx"), var, None), "hx");
+        assert_eq!(resolve_editor(None, var, None), "code --wait");
+        assert_eq!(
+            resolve_editor(None, |_| None, Some("emacs".to_string())),
+            "emacs"
+        );
+        assert_eq!(resolve_editor(Some(""), |_| None, None), DEFAULT_EDITOR);
+    }
+
+    #[test]
+    fn test_template_round_trip() {
+        let editor = MessageEditor {
+            command: "true".to_string(),
+            instructions: Vec::new(),
+        }
+        .with_instructions(&["Enter the body", "", "  <type>: <subject>"]);
+
+        let template = editor.template("Explain why");
+        assert_eq!(
+            template,
+            "Explain why\n\n# Enter the body\n#\n#   <type>: <subject>\n"
+        );
+        assert_eq!(strip_comments(&template), "Explain why");
+        assert_eq!(strip_comments("# only comments\n#\n"), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_editor_with_arguments() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("COMMIT_EDITMSG");
+        std::fs::write(&path, "old\n# comment\n").unwrap();
+
+        // The command gets its own arguments followed by the file
+        let status = editor_command("sed -i s/old/new/", &path).status().unwrap();
+        assert!(status.success());
+        assert_eq!(
+            strip_comments(&std::fs::read_to_string(&path).unwrap()),
+            "new"
+        );
+    }
+}
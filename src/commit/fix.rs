@@ -3,7 +3,19 @@
 
 //! Commit fixing functionality.
 
-use crate::error::Result;
+use crate::config::CkConfig;
+use crate::error::{CkError, GitError, Result};
+use crate::git::rewrite::Published;
+use crate::rules::autofix::{apply_subject_fixes, fix_subject};
+use crate::rules::{RuleEngine, ValidationIssue};
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use super::builder::CommitBuilder;
+use super::message::CommitMessage;
+use super::word_diff;
 
 /// Commit fixer for interactive commit repair.
 pub struct CommitFixer;
@@ -15,15 +27,8 @@ impl CommitFixer {
     }
 
     /// Fix commits in a range.
-    pub fn fix(&self, target: &str, count: Option<usize>, dry_run: bool, auto: bool) -> Result<()> {
-        // Determine the actual range
-        let range = if let Some(n) = count {
-            format!("HEAD~{}..HEAD", n)
-        } else if target.contains("..") {
-            target.to_string()
-        } else {
-            format!("{}~1..{}", target, target)
-        };
+    pub fn fix(&self, target: &str, count: Option<usize>, dry_run: bool) -> Result<()> {
+        let range = Self::range(target, count);
 
         // Get commits in range
         let commits = crate::git::get_commit_range(&range)?;
@@ -58,10 +63,14 @@ impl CommitFixer {
                             }
                         }
 
-                        if !dry_run && auto {
-                            // Auto-fix logic would go here
-                            println!("  [auto-fix not yet implemented]");
-                        }
+                        let codes = result
+                            .errors
+                            .iter()
+                            .chain(&result.warnings)
+                            .map(|i| i.code.as_str());
+                        let mut proposed = parsed.clone();
+                        proposed.subject = apply_subject_fixes(codes, &parsed.subject);
+                        print_changes("Proposed", &parsed.header(), &proposed.header());
                     }
                 }
                 Err(e) => {
@@ -78,8 +87,608 @@ impl CommitFixer {
     }
 }
 
+impl CommitFixer {
+    /// The range of commits to fix: the last `count` commits, `target` if
+    /// it is a range, or the single commit `target`.
+    pub fn range(target: &str, count: Option<usize>) -> String {
+        if let Some(n) = count {
+            format!("HEAD~{}..HEAD", n)
+        } else if target.contains("..") {
+            target.to_string()
+        } else {
+            format!("{}~1..{}", target, target)
+        }
+    }
+
+    /// Assisted rebase: open the builder for each failing commit in
+    /// `range`, pre-filled with the old message and suggested corrections,
+    /// then rewrite history with the new messages.
+    ///
+    /// The range must end at HEAD, and commits already on a remote-tracking
+    /// branch are only rewritten with `force_rewrite`. Signed commits the
+    /// user authored are signed again with `resign`. Returns the number of
+    /// reworded commits.
+    pub fn reword_range(
+        &self,
+        range: &str,
+        config: &CkConfig,
+        strict: bool,
+        force_rewrite: bool,
+        resign: bool,
+        dry_run: bool,
+    ) -> Result<usize> {
+        let repo = crate::git::open_repo()?;
+        let base = rewrite_base(&repo, range, "fix")?;
+
+        let engine = RuleEngine::new(config.clone());
+        let mut commits = repo.get_commits_in_range(range)?;
+        commits.reverse(); // Oldest first, in rebase order
+
+        let mut messages = HashMap::new();
+        let mut published = None;
+        for (oid, message) in &commits {
+            let short_sha = &oid.to_string()[..7];
+            let first_line = message.lines().next().unwrap_or("");
+
+            let builder = match CommitMessage::parse(message) {
+                Ok(parsed) => {
+                    let result = engine.validate(&parsed);
+                    let failing = !result.is_valid() || (strict && !result.warnings.is_empty());
+                    if !failing {
+                        continue;
+                    }
+                    // Refuse before any prompts when shared history would change
+                    if messages.is_empty() && !dry_run {
+                        published = guard_published(&repo, &oid.to_string(), force_rewrite, "fix")?;
+                    }
+
+                    println!("\n✗ {} {}", short_sha, first_line);
+                    for issue in result.errors.iter().chain(&result.warnings) {
+                        println!("  {}", issue.format());
+                    }
+
+                    let codes = result
+                        .errors
+                        .iter()
+                        .chain(&result.warnings)
+                        .map(|i| i.code.as_str());
+                    let mut suggested = parsed.clone();
+                    suggested.subject = apply_subject_fixes(codes, &parsed.subject);
+                    CommitBuilder::from_message(config.clone(), &suggested)
+                }
+                Err(e) => {
+                    if messages.is_empty() && !dry_run {
+                        published = guard_published(&repo, &oid.to_string(), force_rewrite, "fix")?;
+                    }
+                    println!("\n✗ {} {} (parse error: {})", short_sha, first_line, e);
+                    let body = message.lines().skip(2).collect::<Vec<_>>().join("\n");
+                    CommitBuilder::new(config.clone())
+                        .with_subject(first_line)
+                        .with_body(body.trim())
+                }
+            };
+
+            let reworded = builder.run_reword()?.format();
+            print_changes("Reworded", message.trim_end(), &reworded);
+            messages.insert(oid.to_string(), reworded);
+        }
+
+        if messages.is_empty() {
+            println!("No commits need rewording");
+            return Ok(0);
+        }
+
+        let signed = crate::git::rewrite::signed_commits_replaced(&repo, base, &messages)?;
+        if !signed.is_empty() {
+            println!(
+                "\n! {} signed commit(s) will be rewritten; {}",
+                signed.len(),
+                if resign {
+                    "yours are signed again, other signatures are dropped"
+                } else {
+                    "their signatures are dropped (pass --resign to sign your own again)"
+                }
+            );
+        }
+
+        if dry_run {
+            println!("\n[dry-run] Would reword {} commit(s)", messages.len());
+            return Ok(messages.len());
+        }
+
+        let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Rewrite {} commit(s)?", messages.len()))
+            .default(true)
+            .interact()?;
+        if !confirmed {
+            return Err(CkError::Cancelled);
+        }
+
+        let reworded = crate::git::rewrite::reword_commits(&repo, base, &messages, resign)?;
+        println!(
+            "✓ Reworded {} commit(s), HEAD is now {}",
+            messages.len(),
+            &reworded.new_head[..7]
+        );
+        print_signatures(&reworded.resigned, &reworded.dropped_signatures);
+        if let Some(ref published) = published {
+            print_push_commands(&published.branches, &published.push_commands);
+        }
+
+        Ok(messages.len())
+    }
+}
+
+/// Report what a rewrite did with the signatures of the commits it
+/// replaced.
+fn print_signatures(resigned: &[String], dropped: &[String]) {
+    if !resigned.is_empty() {
+        println!("✓ Signed {} rewritten commit(s) again", resigned.len());
+    }
+    if !dropped.is_empty() {
+        let shas: Vec<&str> = dropped.iter().map(|sha| &sha[..7]).collect();
+        println!(
+            "! Dropped the signatures of {} rewritten commit(s): {}",
+            dropped.len(),
+            shas.join(", ")
+        );
+    }
+}
+
+/// Print a word diff of a changed message under `label`, if it changes
+/// anything.
+fn print_changes(label: &str, old: &str, new: &str) {
+    if !word_diff::has_changes(old, new) {
+        return;
+    }
+    let rendered = word_diff::render(old, new, console::colors_enabled());
+    println!("  {}:", label);
+    for line in rendered.lines() {
+        println!("    {}", line);
+    }
+}
+
+/// Refuse to rewrite history from `first` on for `command` when
+/// remote-tracking branches already have it, unless `force` is set.
+pub(crate) fn guard_published(
+    repo: &crate::git::Repository,
+    first: &str,
+    force: bool,
+    command: &str,
+) -> Result<Option<Published>> {
+    let published = match Published::find(repo, first)? {
+        Some(published) => published,
+        None => return Ok(None),
+    };
+    if force {
+        return Ok(Some(published));
+    }
+
+    let mut message = format!(
+        "{} is already on {}, so {} it rewrites shared history.\n\
+         Rerun with --force-rewrite to rewrite it anyway",
+        &first[..7],
+        published.branches.join(", "),
+        if command == "sign" {
+            "signing"
+        } else {
+            "rewording"
+        }
+    );
+    if !published.push_commands.is_empty() {
+        message.push_str(", then update the remote with:");
+        for command in &published.push_commands {
+            message.push_str(&format!("\n  {}", command));
+        }
+    }
+    Err(CkError::WithContext {
+        code: "rewrite-published",
+        context: command.to_string(),
+        message,
+    })
+}
+
+/// Tell how to publish a rewrite of commits already on `branches`.
+pub(crate) fn print_push_commands(branches: &[String], push_commands: &[String]) {
+    println!("! Rewrote commits already on {}", branches.join(", "));
+    if push_commands.is_empty() {
+        println!("  None of them is this branch's remote; they keep the old commits");
+        return;
+    }
+    println!("  Update the remote with:");
+    for command in push_commands {
+        println!("    {}", command);
+    }
+}
+
+/// The base of a `base..HEAD` range whose commits `command` can rewrite.
+pub(crate) fn rewrite_base<'a>(
+    repo: &crate::git::Repository,
+    range: &'a str,
+    command: &str,
+) -> Result<&'a str> {
+    let (base, tip) = match range.split_once("..") {
+        Some((base, tip)) => (base, if tip.is_empty() { "HEAD" } else { tip }),
+        None => {
+            return Err(CkError::WithContext {
+                code: "range-invalid",
+                context: command.to_string(),
+                message: format!("Expected a range like base..HEAD, got '{}'", range),
+            })
+        }
+    };
+    if repo.get_commit(tip)?.id() != repo.head_commit()?.id() {
+        return Err(CkError::Git(GitError::InvalidReference {
+            reference: format!("{} (the range must end at HEAD)", tip),
+        }));
+    }
+    Ok(base)
+}
+
+impl CommitFixer {
+    /// Fix every commit in `range` that can be fixed without prompts, then
+    /// rewrite history with the new messages.
+    ///
+    /// Only safe fixes are made: types are normalized through
+    /// `rules.type_aliases` and their canonical names, and subjects get
+    /// their case and trailing period fixed, and their mood when the first
+    /// word reads as a verb (see `rules::mood`). Bodies and footers are
+    /// kept as they are. The range must end at HEAD unless `dry_run` is set,
+    /// and commits already on a remote-tracking branch are only rewritten
+    /// with `force_rewrite`. Signed commits the user authored are signed
+    /// again with `resign`.
+    pub fn fix_batch(
+        &self,
+        range: &str,
+        config: &CkConfig,
+        force_rewrite: bool,
+        resign: bool,
+        dry_run: bool,
+    ) -> Result<FixReport> {
+        let repo = crate::git::open_repo()?;
+        let base = if dry_run {
+            None
+        } else {
+            Some(rewrite_base(&repo, range, "fix")?)
+        };
+
+        let engine = RuleEngine::new(config.clone());
+        let mut commits = repo.get_commits_in_range(range)?;
+        commits.reverse(); // Oldest first, in rebase order
+
+        let mut report = FixReport {
+            range: range.to_string(),
+            config_hash: config.hash(),
+            dry_run,
+            checked: commits.len(),
+            new_head: None,
+            resigned: Vec::new(),
+            dropped_signatures: Vec::new(),
+            published: Vec::new(),
+            push_commands: Vec::new(),
+            commits: Vec::new(),
+        };
+        let mut messages = HashMap::new();
+        let mut first = None;
+        for (oid, message) in &commits {
+            let fix = match fix_message(&engine, &config.rules.type_aliases, message) {
+                Some(fix) => fix,
+                None => continue,
+            };
+            if let Some(ref fixed) = fix.message {
+                first.get_or_insert_with(|| oid.to_string());
+                messages.insert(oid.to_string(), fixed.clone());
+            }
+            report.commits.push(FixedCommit {
+                sha: oid.to_string(),
+                header: message.lines().next().unwrap_or("").to_string(),
+                fixed_header: fix
+                    .message
+                    .as_deref()
+                    .map(|m| m.lines().next().unwrap_or("").to_string()),
+                status: fix.status,
+                applied: fix.applied,
+                remaining: fix.remaining,
+            });
+        }
+
+        if let (Some(base), Some(first)) = (base, first) {
+            if let Some(published) = guard_published(&repo, &first, force_rewrite, "fix")? {
+                report.published = published.branches;
+                report.push_commands = published.push_commands;
+            }
+            let reworded = crate::git::rewrite::reword_commits(&repo, base, &messages, resign)?;
+            report.new_head = Some(reworded.new_head);
+            report.resigned = reworded.resigned;
+            report.dropped_signatures = reworded.dropped_signatures;
+        }
+        Ok(report)
+    }
+}
+
+/// What batch fixing did about a commit with issues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixStatus {
+    /// The message was rewritten and no errors remain.
+    Fixed,
+    /// The message was rewritten, but errors remain.
+    Partial,
+    /// Nothing could be fixed.
+    Unfixable,
+}
+
+/// A commit batch fixing changed or couldn't fix.
+#[derive(Debug, Clone, Serialize)]
+pub struct FixedCommit {
+    /// Original commit SHA.
+    pub sha: String,
+    /// Original first line.
+    pub header: String,
+    /// New first line, when the message was rewritten.
+    pub fixed_header: Option<String>,
+    /// Outcome for this commit.
+    pub status: FixStatus,
+    /// Fixes made: rule codes, and `type` for a normalized type.
+    pub applied: Vec<String>,
+    /// Issues left after fixing.
+    pub remaining: Vec<ValidationIssue>,
+}
+
+/// Outcome of `ck fix --auto`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FixReport {
+    /// Range that was fixed.
+    pub range: String,
+    /// Hash of the configuration the commits were fixed under.
+    pub config_hash: String,
+    /// Whether history was left alone.
+    pub dry_run: bool,
+    /// Number of commits in the range.
+    pub checked: usize,
+    /// New HEAD after rewriting, when anything was rewritten.
+    pub new_head: Option<String>,
+    /// Original SHAs of rewritten commits that were signed again.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resigned: Vec<String>,
+    /// Original SHAs of rewritten commits whose signatures were dropped.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dropped_signatures: Vec<String>,
+    /// Remote-tracking branches that had rewritten commits.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub published: Vec<String>,
+    /// Commands that publish the rewrite.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub push_commands: Vec<String>,
+    /// Commits that were rewritten or still have issues.
+    pub commits: Vec<FixedCommit>,
+}
+
+impl FixReport {
+    /// Number of commits with the given outcome.
+    pub fn count(&self, status: FixStatus) -> usize {
+        self.commits.iter().filter(|c| c.status == status).count()
+    }
+
+    /// Number of commits that still have errors.
+    pub fn failing(&self) -> usize {
+        self.count(FixStatus::Partial) + self.count(FixStatus::Unfixable)
+    }
+
+    /// Print the outcome for each commit and a summary.
+    pub fn print(&self) {
+        for commit in &self.commits {
+            let symbol = match commit.status {
+                FixStatus::Fixed => "✓",
+                FixStatus::Partial => "~",
+                FixStatus::Unfixable => "✗",
+            };
+            println!("{} {} {}", symbol, &commit.sha[..7], commit.header);
+            if let Some(ref fixed) = commit.fixed_header {
+                print_changes("Fixed", &commit.header, fixed);
+            }
+            for issue in &commit.remaining {
+                println!("  {}", issue.format());
+            }
+        }
+
+        println!(
+            "\nFixed {}, partially fixed {}, could not fix {} of {} commit(s)",
+            self.count(FixStatus::Fixed),
+            self.count(FixStatus::Partial),
+            self.count(FixStatus::Unfixable),
+            self.checked
+        );
+        match self.new_head {
+            Some(ref head) => println!("✓ Rewrote history, HEAD is now {}", &head[..7]),
+            None if self.dry_run => println!("[dry-run] No changes made"),
+            None => {}
+        }
+        print_signatures(&self.resigned, &self.dropped_signatures);
+        if !self.published.is_empty() {
+            print_push_commands(&self.published, &self.push_commands);
+        }
+    }
+
+    /// Write the report as JSON to `path`.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| CkError::WithContext {
+            code: "fix-report-write",
+            context: "fix".to_string(),
+            message: format!("Failed to serialize report: {}", e),
+        })?;
+        std::fs::write(path, json + "\n")?;
+        Ok(())
+    }
+}
+
+/// The safe fixes for one message.
+#[derive(Debug)]
+struct MessageFix {
+    /// The rewritten message, if anything changed.
+    message: Option<String>,
+    status: FixStatus,
+    applied: Vec<String>,
+    remaining: Vec<ValidationIssue>,
+}
+
+/// Fix what can be fixed safely in `message`.
+///
+/// Only the first line is rebuilt; everything after it is kept byte for
+/// byte. Returns `None` for messages that need no change and have no
+/// errors, even if warnings that can't be fixed remain.
+fn fix_message(
+    engine: &RuleEngine,
+    type_aliases: &BTreeMap<String, String>,
+    message: &str,
+) -> Option<MessageFix> {
+    let header = message.lines().next().unwrap_or("");
+    let rest = &message[header.len()..];
+    let mut applied = Vec::new();
+
+    // Map the type before parsing, so unknown types can be rescued
+    let type_end = header.find(['(', '!', ':']).unwrap_or(header.len());
+    let written_type = header[..type_end].trim();
+    let aliased = type_aliases
+        .get(&written_type.to_lowercase())
+        .map(|alias| format!("{}{}", alias, &header[type_end..]));
+
+    let parsed = match CommitMessage::parse(aliased.as_deref().unwrap_or(header)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return Some(MessageFix {
+                message: None,
+                status: FixStatus::Unfixable,
+                applied,
+                remaining: vec![ValidationIssue {
+                    code: "parse-error".to_string(),
+                    message: e.to_string(),
+                    suggestion: Some(if header.contains(':') {
+                        "Map the type in rules.type_aliases".to_string()
+                    } else {
+                        "Reword it interactively with `ck check <range> --fix`".to_string()
+                    }),
+                    is_error: true,
+                    line: Some(1),
+                    span: None,
+                }],
+            })
+        }
+    };
+    if parsed.commit_type.as_str() != written_type {
+        applied.push("type".to_string());
+    }
+
+    // `!` comes from the header only; a BREAKING CHANGE footer doesn't add it
+    let mut fixed = parsed.clone();
+    fixed.is_breaking = header[..header.find(':').unwrap_or(0)].contains('!');
+    let typed = format!("{}{}", aliased.as_deref().unwrap_or(header), rest);
+    let result = engine.validate_string(&typed).ok();
+    let codes = result
+        .iter()
+        .flat_map(|r| r.errors.iter().chain(&r.warnings))
+        .map(|issue| issue.code.as_str());
+    for code in codes {
+        if let Some(subject) = fix_subject(code, &fixed.subject) {
+            fixed.subject = subject;
+            applied.push(code.to_string());
+        }
+    }
+
+    let new_message = format!("{}{}", fixed.header(), rest);
+    let changed = new_message != message;
+    let remaining = match engine.validate_string(&new_message) {
+        Ok(result) => result.errors.into_iter().chain(result.warnings).collect(),
+        Err(_) => Vec::new(),
+    };
+    let has_errors = remaining.iter().any(|issue| issue.is_error);
+    let status = match (changed, has_errors) {
+        (true, false) => FixStatus::Fixed,
+        (true, true) => FixStatus::Partial,
+        (false, true) => FixStatus::Unfixable,
+        (false, false) => return None,
+    };
+
+    Some(MessageFix {
+        message: changed.then_some(new_message),
+        status,
+        applied,
+        remaining,
+    })
+}
+
 impl Default for CommitFixer {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix_default(message: &str) -> Option<MessageFix> {
+        let engine = RuleEngine::new(CkConfig::default());
+        let aliases = BTreeMap::from([("bug".to_string(), "fix".to_string())]);
+        fix_message(&engine, &aliases, message)
+    }
+
+    #[test]
+    fn test_fix_message_subject() {
+        let result =
+            fix_default("feat(api): Added retries to uploads.\n\nBody  kept as is\n").unwrap();
+        assert_eq!(result.status, FixStatus::Fixed);
+        assert_eq!(
+            result.message.as_deref(),
+            Some("feat(api): add retries to uploads\n\nBody  kept as is\n")
+        );
+        assert!(result
+            .applied
+            .contains(&"subject-trailing-period".to_string()));
+        assert!(result.remaining.is_empty());
+    }
+
+    #[test]
+    fn test_fix_message_mood() {
+        let result = fix_default("ci: bumping the rust toolchain").unwrap();
+        assert_eq!(
+            result.message.as_deref(),
+            Some("ci: bump the rust toolchain")
+        );
+        assert_eq!(result.applied, vec!["subject-imperative"]);
+
+        // A plural noun, not a verb
+        assert!(fix_default("test: fixtures are regenerated").is_none());
+    }
+
+    #[test]
+    fn test_fix_message_type_alias() {
+        let result = fix_default("bug: handle empty responses").unwrap();
+        assert_eq!(result.status, FixStatus::Fixed);
+        assert_eq!(
+            result.message.as_deref(),
+            Some("fix: handle empty responses")
+        );
+        assert_eq!(result.applied, vec!["type"]);
+
+        let result = fix_default("Feature!: drop the legacy api\n").unwrap();
+        assert_eq!(
+            result.message.as_deref(),
+            Some("feat!: drop the legacy api\n")
+        );
+    }
+
+    #[test]
+    fn test_fix_message_unfixable() {
+        assert!(fix_default("fix: handle empty responses").is_none());
+
+        let result = fix_default("Update the readme").unwrap();
+        assert_eq!(result.status, FixStatus::Unfixable);
+        assert_eq!(result.remaining[0].code, "parse-error");
+
+        let result = fix_default("wip: Tmp.").unwrap();
+        assert_eq!(result.status, FixStatus::Partial);
+        assert_eq!(result.message.as_deref(), Some("wip: tmp"));
+        assert!(result.remaining.iter().any(|i| i.code == "type-forbidden"));
+    }
+}
@@ -0,0 +1,178 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Gitmoji in commit headers, for `ui.emoji_style = "gitmoji"`.
+//!
+//! The emoji goes between the type and the subject, `feat(api): ✨ add
+//! export`, so the header stays a conventional one. Parsing takes a leading
+//! gitmoji (or its `:shortcode:`) off the subject whatever the style, so
+//! rules only ever see the words.
+
+use crate::config::{CkConfig, CommitType};
+
+/// A gitmoji: the emoji, its shortcode and what it is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gitmoji {
+    /// The emoji itself.
+    pub emoji: &'static str,
+    /// Shortcode, e.g. `:sparkles:`.
+    pub code: &'static str,
+    /// What the emoji marks.
+    pub description: &'static str,
+}
+
+const fn gitmoji(emoji: &'static str, code: &'static str, description: &'static str) -> Gitmoji {
+    Gitmoji {
+        emoji,
+        code,
+        description,
+    }
+}
+
+/// The gitmojis offered by the interactive builder.
+pub const GITMOJIS: &[Gitmoji] = &[
+    gitmoji("✨", ":sparkles:", "Introduce new features"),
+    gitmoji("🐛", ":bug:", "Fix a bug"),
+    gitmoji("🚑️", ":ambulance:", "Critical hotfix"),
+    gitmoji("📝", ":memo:", "Add or update documentation"),
+    gitmoji("🎨", ":art:", "Improve structure or format of the code"),
+    gitmoji("♻️", ":recycle:", "Refactor code"),
+    gitmoji("⚡️", ":zap:", "Improve performance"),
+    gitmoji("✅", ":white_check_mark:", "Add, update or pass tests"),
+    gitmoji("🔧", ":wrench:", "Add or update configuration files"),
+    gitmoji("⏪️", ":rewind:", "Revert changes"),
+    gitmoji(
+        "📦️",
+        ":package:",
+        "Add or update compiled files or packages",
+    ),
+    gitmoji(
+        "👷",
+        ":construction_worker:",
+        "Add or update the CI build system",
+    ),
+    gitmoji("💚", ":green_heart:", "Fix the CI build"),
+    gitmoji("🚧", ":construction:", "Work in progress"),
+    gitmoji("🔥", ":fire:", "Remove code or files"),
+    gitmoji("💥", ":boom:", "Introduce breaking changes"),
+    gitmoji("🔒️", ":lock:", "Fix security or privacy issues"),
+    gitmoji("🚨", ":rotating_light:", "Fix compiler or linter warnings"),
+    gitmoji("✏️", ":pencil2:", "Fix typos"),
+    gitmoji("💄", ":lipstick:", "Add or update the UI and style files"),
+    gitmoji("♿️", ":wheelchair:", "Improve accessibility"),
+    gitmoji(
+        "🌐",
+        ":globe_with_meridians:",
+        "Internationalization and localization",
+    ),
+    gitmoji("🔊", ":loud_sound:", "Add or update logs"),
+    gitmoji(
+        "🗑️",
+        ":wastebasket:",
+        "Deprecate code that needs to be cleaned up",
+    ),
+    gitmoji("🚚", ":truck:", "Move or rename resources"),
+    gitmoji("➕", ":heavy_plus_sign:", "Add a dependency"),
+    gitmoji("➖", ":heavy_minus_sign:", "Remove a dependency"),
+    gitmoji("⬆️", ":arrow_up:", "Upgrade dependencies"),
+    gitmoji("⬇️", ":arrow_down:", "Downgrade dependencies"),
+    gitmoji("📌", ":pushpin:", "Pin dependencies to specific versions"),
+    gitmoji("🔖", ":bookmark:", "Release or version tags"),
+    gitmoji("🚀", ":rocket:", "Deploy stuff"),
+    gitmoji("🎉", ":tada:", "Begin a project"),
+    gitmoji("🔀", ":twisted_rightwards_arrows:", "Merge branches"),
+];
+
+/// Variation selector that asks for emoji presentation. Commit messages
+/// are written with and without it.
+const PRESENTATION: char = '\u{fe0f}';
+
+/// The gitmoji for `commit_type`: the emoji configured for it under
+/// `[[types]]`, or else the usual one for the built-in types.
+pub fn for_type<'a>(commit_type: &CommitType, config: &'a CkConfig) -> Option<&'a str> {
+    if let Some(emoji) = config.type_emoji(commit_type) {
+        return Some(emoji);
+    }
+    let emoji = match commit_type {
+        CommitType::Feat => "✨",
+        CommitType::Fix => "🐛",
+        CommitType::Docs => "📝",
+        CommitType::Style => "🎨",
+        CommitType::Refactor => "♻️",
+        CommitType::Perf => "⚡️",
+        CommitType::Test => "✅",
+        CommitType::Chore => "🔧",
+        CommitType::Revert => "⏪️",
+        CommitType::Build => "📦️",
+        CommitType::Ci => "👷",
+        CommitType::Wip => "🚧",
+        CommitType::Custom(_) => return None,
+    };
+    Some(emoji)
+}
+
+/// Split a leading gitmoji off `subject`, as written, from the rest of the
+/// subject. Emojis are recognized from [`GITMOJIS`], by shortcode, and as a
+/// leading word of pictographs, which covers the emojis configured under
+/// `[[types]]`; a subject that is only an emoji is kept.
+pub fn split(subject: &str) -> (Option<&str>, &str) {
+    let known = GITMOJIS.iter().flat_map(|g| [g.emoji, g.code]);
+    for candidate in known {
+        let candidate = candidate.trim_end_matches(PRESENTATION);
+        let rest = match subject.strip_prefix(candidate) {
+            Some(rest) => rest.strip_prefix(PRESENTATION).unwrap_or(rest),
+            None => continue,
+        };
+        let emoji = &subject[..subject.len() - rest.len()];
+        match rest.strip_prefix(char::is_whitespace).map(str::trim_start) {
+            Some(words) if !words.is_empty() => return (Some(emoji), words),
+            _ => {}
+        }
+    }
+    match subject.split_once(char::is_whitespace) {
+        Some((word, rest)) if is_pictographic(word) && !rest.trim_start().is_empty() => {
+            (Some(word), rest.trim_start())
+        }
+        _ => (None, subject),
+    }
+}
+
+/// Whether `word` is made only of symbols outside ASCII, as emojis are.
+fn is_pictographic(word: &str) -> bool {
+    !word.is_empty() && word.chars().all(|c| !c.is_ascii() && !c.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_type() {
+        let mut config = CkConfig::default();
+        assert_eq!(for_type(&CommitType::Feat, &config), Some("✨"));
+        assert_eq!(for_type(&CommitType::Perf, &config), Some("⚡️"));
+        let deps = CommitType::Custom("deps".to_string());
+        assert_eq!(for_type(&deps, &config), None);
+
+        config.types.push(crate::config::TypeDefinition {
+            name: "deps".to_string(),
+            description: String::new(),
+            emoji: Some("📦".to_string()),
+        });
+        assert_eq!(for_type(&deps, &config), Some("📦"));
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split("✨ add export"), (Some("✨"), "add export"));
+        assert_eq!(split("⚡ cache lookups"), (Some("⚡"), "cache lookups"));
+        assert_eq!(split("⚡️ cache lookups"), (Some("⚡️"), "cache lookups"));
+        assert_eq!(
+            split(":sparkles: add export"),
+            (Some(":sparkles:"), "add export")
+        );
+        assert_eq!(split("add ✨ export"), (None, "add ✨ export"));
+        assert_eq!(split("✨export"), (None, "✨export"));
+        assert_eq!(split("✨"), (None, "✨"));
+    }
+}
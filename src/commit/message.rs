@@ -3,15 +3,17 @@
 
 //! Commit message structure and parsing.
 
-use crate::config::CommitType;
+use crate::config::{CkConfig, CommitType};
 use crate::error::{CkError, CommitError, Result};
 use lazy_static::lazy_static;
 use regex::Regex;
 
+use super::breaking::BreakingChange;
+
 lazy_static! {
     /// Regex for parsing conventional commit messages.
     static ref CONVENTIONAL_REGEX: Regex = Regex::new(
-        r"^(?P<type>\w+)(?:\((?P<scope>[^)]+)\))?(?P<breaking>!)?: (?P<subject>.+?)(?:\n\n(?P<body>[\s\S]*?))?(?:\n\n(?P<footer>[\s\S]*))?$"
+        r"^(?P<type>\w+)(?:\((?P<scope>[^)]+)\))?(?P<breaking>!)?: (?P<subject>.+?)(?:\n\n+(?P<body>[\s\S]*))?$"
     ).unwrap();
 }
 
@@ -22,6 +24,8 @@ pub struct CommitMessage {
     pub commit_type: CommitType,
     /// Optional scope.
     pub scope: Option<String>,
+    /// Gitmoji written before the subject, e.g. `✨`.
+    pub emoji: Option<String>,
     /// Subject line.
     pub subject: String,
     /// Optional body.
@@ -38,6 +42,7 @@ impl CommitMessage {
         Self {
             commit_type,
             scope: None,
+            emoji: None,
             subject: subject.into(),
             body: None,
             footer: None,
@@ -51,6 +56,12 @@ impl CommitMessage {
         self
     }
 
+    /// Set the gitmoji written before the subject.
+    pub fn with_emoji(mut self, emoji: impl Into<String>) -> Self {
+        self.emoji = Some(emoji.into());
+        self
+    }
+
     /// Set the body.
     pub fn with_body(mut self, body: impl Into<String>) -> Self {
         let body_str = body.into();
@@ -76,7 +87,19 @@ impl CommitMessage {
     }
 
     /// Parse a commit message from a string.
+    ///
+    /// Only the built-in types are known; use [`CommitMessage::parse_with`]
+    /// to also accept the types defined in configuration.
     pub fn parse(message: &str) -> Result<Self> {
+        Self::parse_types(message, &|name| name.parse().ok())
+    }
+
+    /// Parse a commit message, resolving its type against `config`.
+    pub fn parse_with(message: &str, config: &CkConfig) -> Result<Self> {
+        Self::parse_types(message, &|name| config.commit_type(name))
+    }
+
+    fn parse_types(message: &str, resolve: &dyn Fn(&str) -> Option<CommitType>) -> Result<Self> {
         let message = message.trim();
 
         if message.is_empty() {
@@ -86,25 +109,20 @@ impl CommitMessage {
         // Try to parse as conventional commit
         if let Some(captures) = CONVENTIONAL_REGEX.captures(message) {
             let type_str = captures.name("type").map(|m| m.as_str()).unwrap_or("");
-            let commit_type = type_str.parse::<CommitType>().ok().ok_or_else(|| {
+            let commit_type = resolve(type_str).ok_or_else(|| {
                 CkError::Commit(CommitError::ParseFailed {
                     message: format!("Unknown commit type: {}", type_str),
                 })
             })?;
 
             let scope = captures.name("scope").map(|m| m.as_str().to_string());
-            let subject = captures
-                .name("subject")
-                .map(|m| m.as_str().to_string())
-                .unwrap_or_default();
-            let body = captures
-                .name("body")
-                .map(|m| m.as_str().trim().to_string())
-                .filter(|s| !s.is_empty());
-            let footer = captures
-                .name("footer")
-                .map(|m| m.as_str().trim().to_string())
-                .filter(|s| !s.is_empty());
+            let (emoji, subject) =
+                super::gitmoji::split(captures.name("subject").map(|m| m.as_str()).unwrap_or(""));
+            // The footer is the last paragraph, if it is made of trailers
+            let (body, footer) = match captures.name("body") {
+                Some(body) => super::trailers::split_footer(&message[body.start()..]),
+                None => (None, None),
+            };
             let is_breaking = captures.name("breaking").is_some()
                 || footer
                     .as_ref()
@@ -114,7 +132,8 @@ impl CommitMessage {
             Ok(Self {
                 commit_type,
                 scope,
-                subject,
+                emoji: emoji.map(str::to_string),
+                subject: subject.to_string(),
                 body,
                 footer,
                 is_breaking,
@@ -129,19 +148,21 @@ impl CommitMessage {
                 if parts.len() == 2 {
                     // Extract type from first part (might have scope)
                     let type_part = parts[0].trim();
-                    let subject = parts[1].trim().to_string();
+                    let subject = parts[1].trim();
 
                     // Check for scope in parentheses
-                    let (type_str, scope) = if type_part.contains('(') && type_part.contains(')') {
-                        let type_end = type_part.find('(').unwrap();
-                        let scope_start = type_end + 1;
-                        let scope_end = type_part.find(')').unwrap();
-                        (
-                            &type_part[..type_end],
-                            Some(type_part[scope_start..scope_end].to_string()),
-                        )
-                    } else {
-                        (type_part, None)
+                    // The scope is the text between `(` and the next `)`
+                    let parens = type_part.find('(').and_then(|open| {
+                        type_part[open..]
+                            .find(')')
+                            .map(|close| (open, open + close))
+                    });
+                    let (type_str, scope) = match parens {
+                        Some((open, close)) => (
+                            &type_part[..open],
+                            Some(type_part[open + 1..close].to_string()),
+                        ),
+                        None => (type_part, None),
                     };
 
                     // Check for breaking indicator
@@ -152,23 +173,33 @@ impl CommitMessage {
                         (type_str, false)
                     };
 
-                    if let Ok(commit_type) = type_str.parse::<CommitType>() {
+                    if let Some(commit_type) = resolve(type_str) {
                         // Extract body if present
                         let body = message
                             .lines()
                             .skip(2) // Skip first line and blank line
                             .collect::<Vec<_>>()
                             .join("\n");
-                        let body = if body.is_empty() { None } else { Some(body) };
+                        let body = Some(body.trim().to_string()).filter(|b| !b.is_empty());
 
-                        return Ok(Self {
+                        let (emoji, subject) = super::gitmoji::split(subject);
+                        let lenient = Self {
                             commit_type,
                             scope,
-                            subject,
+                            emoji: emoji.map(str::to_string),
+                            subject: subject.to_string(),
                             body,
                             footer: None,
                             is_breaking,
-                        });
+                        };
+
+                        // Split body and footer as for a well-formed message,
+                        // so formatting and parsing again gives the same text
+                        let canonical = lenient.format();
+                        if CONVENTIONAL_REGEX.is_match(&canonical) {
+                            return Self::parse_types(&canonical, resolve);
+                        }
+                        return Ok(lenient);
                     }
                 }
             }
@@ -198,6 +229,10 @@ impl CommitMessage {
 
         // Subject
         result.push_str(": ");
+        if let Some(ref emoji) = self.emoji {
+            result.push_str(emoji);
+            result.push(' ');
+        }
         result.push_str(&self.subject);
 
         // Body
@@ -215,6 +250,29 @@ impl CommitMessage {
         result
     }
 
+    /// The structured breaking change described in the footer, if any.
+    pub fn breaking_change(&self) -> Option<BreakingChange> {
+        BreakingChange::parse(&self.trailer_text())
+    }
+
+    /// Items listed in `Deprecated:` footers.
+    pub fn deprecations(&self) -> Vec<String> {
+        super::deprecation::parse(&self.trailer_text())
+    }
+
+    /// Body and footer together.
+    ///
+    /// Trailers that aren't in the last paragraph are parsed as body, so
+    /// they are looked up in both.
+    pub fn trailer_text(&self) -> String {
+        [self.body.as_deref(), self.footer.as_deref()]
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
     /// Get the first line (header) of the commit message.
     pub fn header(&self) -> String {
         let mut result = String::new();
@@ -231,11 +289,23 @@ impl CommitMessage {
         }
 
         result.push_str(": ");
+        if let Some(ref emoji) = self.emoji {
+            result.push_str(emoji);
+            result.push(' ');
+        }
         result.push_str(&self.subject);
 
         result
     }
 
+    /// Trailers in the footer.
+    pub fn trailers(&self) -> Vec<super::trailers::Trailer> {
+        self.footer
+            .as_deref()
+            .map(super::trailers::parse)
+            .unwrap_or_default()
+    }
+
     /// Get the header length.
     pub fn header_len(&self) -> usize {
         self.header().len()
@@ -312,6 +382,51 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_commit_message_parse_lenient() {
+        let msg = CommitMessage::parse("fix(api):handle it\n\nbody\n\nBREAKING CHANGE: x").unwrap();
+        assert_eq!(msg.scope.as_deref(), Some("api"));
+        assert_eq!(msg.subject, "handle it");
+        assert_eq!(msg.body.as_deref(), Some("body"));
+        assert!(msg.is_breaking);
+        assert!(CommitMessage::parse(")(: x").is_err());
+    }
+
+    #[test]
+    fn test_commit_message_parse_trailers() {
+        let msg = CommitMessage::parse(
+            "feat: add export\n\nFirst paragraph.\n\nSecond: not a trailer.\n\n\
+             Refs #42\nCo-authored-by: Ada <ada@example.com>",
+        )
+        .unwrap();
+        assert_eq!(
+            msg.body.as_deref(),
+            Some("First paragraph.\n\nSecond: not a trailer.")
+        );
+        let tokens: Vec<String> = msg.trailers().into_iter().map(|t| t.token).collect();
+        assert_eq!(tokens, vec!["Refs", "Co-authored-by"]);
+
+        let msg = CommitMessage::parse("fix: handle it\n\nSigned-off-by: Ada <a@b.c>").unwrap();
+        assert_eq!(msg.body, None);
+        assert_eq!(msg.trailers().len(), 1);
+    }
+
+    #[test]
+    fn test_commit_message_gitmoji() {
+        let msg = CommitMessage::parse("feat(cli): ✨ add export\n\nBody").unwrap();
+        assert_eq!(msg.emoji.as_deref(), Some("✨"));
+        assert_eq!(msg.subject, "add export");
+        assert_eq!(msg.header(), "feat(cli): ✨ add export");
+        assert_eq!(msg.format(), "feat(cli): ✨ add export\n\nBody");
+
+        let msg = CommitMessage::parse("fix: :bug: handle empty input").unwrap();
+        assert_eq!(msg.emoji.as_deref(), Some(":bug:"));
+        assert_eq!(msg.subject, "handle empty input");
+
+        let msg = CommitMessage::new(CommitType::Docs, "fix typo").with_emoji("📝");
+        assert_eq!(msg.format(), "docs: 📝 fix typo");
+    }
+
     #[test]
     fn test_commit_message_header() {
         let msg = CommitMessage::new(CommitType::Feat, "add feature").with_scope("cli");
@@ -319,4 +434,73 @@ mod tests {
         assert_eq!(msg.header(), "feat(cli): add feature");
         assert_eq!(msg.header_len(), 22);
     }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// Messages shaped like what people type: a type-ish prefix, an
+        /// optional scope, separators and newlines in odd places.
+        fn message_like() -> impl Strategy<Value = String> {
+            (
+                "(feat|fix|docs|chore|FEAT|wip|nope|)",
+                "(\\([a-z()]{0,6}\\)?)?",
+                "!?",
+                "(: ?|:|)",
+                "[^\\n]{0,20}",
+                "(\\n{0,3}[a-zA-Z :()!-]{0,15}){0,4}",
+            )
+                .prop_map(|(t, scope, bang, sep, subject, rest)| {
+                    format!("{}{}{}{}{}{}", t, scope, bang, sep, subject, rest)
+                })
+        }
+
+        proptest! {
+            #[test]
+            fn parse_never_panics(input in "\\PC*") {
+                let _ = CommitMessage::parse(&input);
+            }
+
+            #[test]
+            fn parse_never_panics_on_message_like(input in message_like()) {
+                let _ = CommitMessage::parse(&input);
+            }
+
+            #[test]
+            fn format_is_stable(input in message_like()) {
+                if let Ok(parsed) = CommitMessage::parse(&input) {
+                    let formatted = parsed.format();
+                    let reparsed = CommitMessage::parse(&formatted).unwrap();
+                    prop_assert_eq!(reparsed.format(), formatted);
+                }
+            }
+
+            #[test]
+            fn round_trip(
+                commit_type in prop::sample::select(vec![
+                    CommitType::Feat, CommitType::Fix, CommitType::Docs, CommitType::Chore,
+                ]),
+                scope in proptest::option::of("[a-z][a-z-]{0,10}"),
+                breaking in any::<bool>(),
+                subject in "[a-z][a-z ]{0,30}[a-z]",
+                body in proptest::option::of("[A-Za-z][A-Za-z .]{0,40}"),
+            ) {
+                let mut message = CommitMessage::new(commit_type.clone(), subject.clone())
+                    .with_breaking(breaking);
+                if let Some(ref scope) = scope {
+                    message = message.with_scope(scope.as_str());
+                }
+                if let Some(ref body) = body {
+                    message = message.with_body(body.trim());
+                }
+
+                let parsed = CommitMessage::parse(&message.format()).unwrap();
+                prop_assert_eq!(parsed.commit_type, commit_type);
+                prop_assert_eq!(parsed.scope, scope);
+                prop_assert_eq!(parsed.is_breaking, breaking);
+                prop_assert_eq!(parsed.subject, subject);
+                prop_assert_eq!(parsed.body, body.map(|b| b.trim().to_string()));
+            }
+        }
+    }
 }
@@ -3,11 +3,29 @@
 
 //! Commit module for message handling and interactive building.
 
+pub mod breaking;
 mod builder;
+pub mod bump;
+pub mod cherry_pick;
+pub mod coaching;
+pub mod deprecation;
+pub mod editor;
 pub mod fix;
+pub mod gitmoji;
 mod message;
+pub mod prefill;
 mod preview;
+mod recent;
+pub mod review;
+pub mod sign;
+pub mod stats;
+mod subject_input;
+pub mod template;
+pub mod time_spent;
+pub mod trailers;
+pub mod word_diff;
 
 pub use builder::CommitBuilder;
 pub use message::CommitMessage;
+pub use prefill::Prefill;
 pub use preview::CommitPreview;
@@ -0,0 +1,85 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Pre-filled commit values from a JSON document.
+//!
+//! `ck commit --stdin-json` reads an object like
+//! `{"type": "fix", "scope": "api", "subject": "handle timeouts"}` so wrapper
+//! scripts and task runners can drive ck without long command lines.
+//! Values given as flags or `CK_*` environment variables take precedence.
+
+use serde::Deserialize;
+use std::io::Read;
+
+use crate::error::{CkError, Result};
+
+/// Commit values to pre-fill. Every field is optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Prefill {
+    /// Commit type.
+    #[serde(rename = "type")]
+    pub commit_type: Option<String>,
+    /// Scope.
+    pub scope: Option<String>,
+    /// Subject.
+    pub subject: Option<String>,
+    /// Body.
+    pub body: Option<String>,
+    /// Whether the change is breaking.
+    #[serde(default)]
+    pub breaking: bool,
+    /// What breaks, for the BREAKING CHANGE footer.
+    pub breaking_change: Option<String>,
+    /// Migration steps for the breaking change.
+    #[serde(default)]
+    pub migration: Vec<String>,
+    /// Deprecated footers.
+    #[serde(default)]
+    pub deprecated: Vec<String>,
+    /// Trailers, as `Token=value` or `Token: value`.
+    #[serde(default)]
+    pub trailers: Vec<String>,
+}
+
+impl Prefill {
+    /// Parse a JSON object.
+    pub fn parse(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| CkError::WithContext {
+            code: "stdin-json-invalid",
+            context: "--stdin-json".to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Read and parse the JSON object on stdin.
+    pub fn from_stdin() -> Result<Self> {
+        let mut json = String::new();
+        std::io::stdin().read_to_string(&mut json)?;
+        Self::parse(&json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prefill() {
+        let prefill =
+            Prefill::parse(r#"{"type": "fix", "subject": "handle timeouts", "breaking": true}"#)
+                .unwrap();
+        assert_eq!(prefill.commit_type.as_deref(), Some("fix"));
+        assert_eq!(prefill.subject.as_deref(), Some("handle timeouts"));
+        assert!(prefill.breaking);
+        assert!(prefill.scope.is_none());
+
+        assert_eq!(Prefill::parse("{}").unwrap(), Prefill::default());
+    }
+
+    #[test]
+    fn test_parse_prefill_rejects_unknown_fields() {
+        let err = Prefill::parse(r#"{"subjet": "typo"}"#).unwrap_err();
+        assert!(err.to_string().contains("subjet"));
+    }
+}
@@ -2,111 +2,212 @@
 // SPDX-License-Identifier: MIT
 
 //! Commit message preview.
+//!
+//! The preview is boxed to the terminal width, wraps long lines, and marks
+//! the 50 and 72 column limits under the header. Subject characters past
+//! the configured maximum are shown in red.
 
-use console::{style, Term};
+use console::{measure_text_width, style, Style, Term};
+use std::ops::Range;
 
 use super::message::CommitMessage;
 
+/// Widest box drawn, even on wide terminals.
+const MAX_WIDTH: usize = 80;
+
+/// Narrowest box drawn, even on narrow terminals.
+const MIN_WIDTH: usize = 24;
+
+/// Columns marked on the ruler: the soft and hard subject limits.
+const RULER_MARKS: [usize; 2] = [50, 72];
+
 /// Commit preview renderer.
 pub struct CommitPreview<'a> {
     message: &'a CommitMessage,
+    max_subject_length: Option<usize>,
 }
 
 impl<'a> CommitPreview<'a> {
     /// Create a new preview for a commit message.
     pub fn new(message: &'a CommitMessage) -> Self {
-        Self { message }
+        Self {
+            message,
+            max_subject_length: None,
+        }
+    }
+
+    /// Highlight subject characters beyond `max`.
+    pub fn with_max_subject_length(mut self, max: usize) -> Self {
+        self.max_subject_length = Some(max);
+        self
     }
 
     /// Print the preview to stderr.
     pub fn print(&self) {
         let term = Term::stderr();
-        let _ = self.render(&term);
+        let (_, columns) = term.size();
+        for line in self.render(columns as usize) {
+            let _ = term.write_line(&line);
+        }
     }
 
-    /// Render the preview to a terminal.
-    fn render(&self, term: &Term) -> std::io::Result<()> {
-        // Box top
-        term.write_line(&format!(
-            "{}",
-            style("┌─ Commit Preview ─────────────────────────────────────────────┐").dim()
-        ))?;
-
-        // Header line
-        let header = self.format_header();
-        term.write_line(&format!(
-            "{} {}{}",
-            style("│").dim(),
-            header,
-            self.padding(header.len())
-        ))?;
+    /// Render the preview as lines fitting a terminal `columns` wide.
+    fn render(&self, columns: usize) -> Vec<String> {
+        let width = columns.clamp(MIN_WIDTH, MAX_WIDTH);
+        // Border and one space of margin on each side
+        let inner = width - 4;
+        let mut lines = Vec::new();
+
+        let title = "─ Commit Preview ";
+        lines.push(
+            style(format!(
+                "┌{}{}┐",
+                title,
+                "─".repeat(width - 2 - measure_text_width(title))
+            ))
+            .dim()
+            .to_string(),
+        );
+
+        // Header
+        let spans = self.header_spans();
+        let plain: String = spans.iter().map(|(text, _)| text.as_str()).collect();
+        for range in wrap(&plain, inner) {
+            lines.push(boxed(&styled_range(&spans, range), inner));
+        }
+        lines.push(boxed(&style(ruler(inner)).dim().to_string(), inner));
 
         // Body if present
         if let Some(ref body) = self.message.body {
-            term.write_line(&format!("{} {}", style("│").dim(), style("").dim()))?;
-
+            lines.push(boxed("", inner));
             for line in body.lines() {
-                let visible_len = line.len().min(60);
-                term.write_line(&format!(
-                    "{} {}{}",
-                    style("│").dim(),
-                    style(line).dim(),
-                    self.padding(visible_len)
-                ))?;
+                let chars: Vec<char> = line.chars().collect();
+                for range in wrap(line, inner) {
+                    let text: String = chars[range].iter().collect();
+                    lines.push(boxed(&style(text).dim().to_string(), inner));
+                }
             }
         }
 
-        // Box bottom
-        term.write_line(&format!(
-            "{}",
-            style("└──────────────────────────────────────────────────────────────┘").dim()
-        ))?;
-
-        Ok(())
+        lines.push(
+            style(format!("└{}┘", "─".repeat(width - 2)))
+                .dim()
+                .to_string(),
+        );
+        lines
     }
 
-    /// Format the header with syntax highlighting.
-    fn format_header(&self) -> String {
-        let mut result = String::new();
-
-        // Type (colored)
-        let type_style = match self.message.commit_type.as_str() {
-            "feat" => style(self.message.commit_type.as_str()).green().bold(),
-            "fix" => style(self.message.commit_type.as_str()).red().bold(),
-            "docs" => style(self.message.commit_type.as_str()).blue().bold(),
-            "style" => style(self.message.commit_type.as_str()).magenta().bold(),
-            "refactor" => style(self.message.commit_type.as_str()).yellow().bold(),
-            "perf" => style(self.message.commit_type.as_str()).cyan().bold(),
-            "test" => style(self.message.commit_type.as_str()).white().bold(),
-            _ => style(self.message.commit_type.as_str()).white().bold(),
+    /// The header split into styled pieces.
+    fn header_spans(&self) -> Vec<(String, Style)> {
+        let commit_type = self.message.commit_type.as_str();
+        let type_style = match commit_type {
+            "feat" => Style::new().green().bold(),
+            "fix" => Style::new().red().bold(),
+            "docs" => Style::new().blue().bold(),
+            "style" => Style::new().magenta().bold(),
+            "refactor" => Style::new().yellow().bold(),
+            "perf" => Style::new().cyan().bold(),
+            _ => Style::new().white().bold(),
         };
-        result.push_str(&type_style.to_string());
+        let mut spans = vec![(commit_type.to_string(), type_style)];
 
         // Scope
         if let Some(ref scope) = self.message.scope {
-            result.push_str(&format!("({})", style(scope).cyan()));
+            spans.push(("(".to_string(), Style::new()));
+            spans.push((scope.clone(), Style::new().cyan()));
+            spans.push((")".to_string(), Style::new()));
         }
 
         // Breaking indicator
         if self.message.is_breaking {
-            result.push_str(&style("!").red().bold().to_string());
+            spans.push(("!".to_string(), Style::new().red().bold()));
         }
 
-        // Separator
-        result.push_str(": ");
+        spans.push((": ".to_string(), Style::new()));
+
+        // Subject, with the part over the limit in red
+        let subject = &self.message.subject;
+        let max = self.max_subject_length.unwrap_or(usize::MAX);
+        let within: String = subject.chars().take(max).collect();
+        let over: String = subject.chars().skip(max).collect();
+        spans.push((within, Style::new()));
+        if !over.is_empty() {
+            spans.push((over, Style::new().red().underlined()));
+        }
+
+        spans
+    }
+}
 
-        // Subject
-        result.push_str(&self.message.subject);
+/// Put content between the box borders, padded to `inner` columns.
+fn boxed(content: &str, inner: usize) -> String {
+    let padding = inner.saturating_sub(measure_text_width(content));
+    format!(
+        "{} {}{} {}",
+        style("│").dim(),
+        content,
+        " ".repeat(padding),
+        style("│").dim()
+    )
+}
 
-        result
+/// Style the characters in `range` of the concatenated spans.
+fn styled_range(spans: &[(String, Style)], range: Range<usize>) -> String {
+    let mut result = String::new();
+    let mut offset = 0;
+    for (text, style) in spans {
+        let len = text.chars().count();
+        let start = range.start.max(offset);
+        let end = range.end.min(offset + len);
+        if start < end {
+            let piece: String = text
+                .chars()
+                .skip(start - offset)
+                .take(end - start)
+                .collect();
+            result.push_str(&style.apply_to(piece).to_string());
+        }
+        offset += len;
     }
+    result
+}
+
+/// A ruler `width` columns wide marking the subject limits that fit.
+fn ruler(width: usize) -> String {
+    (1..=width)
+        .map(|column| {
+            if RULER_MARKS.contains(&column) {
+                '|'
+            } else {
+                '·'
+            }
+        })
+        .collect()
+}
+
+/// Split `text` into character ranges at most `width` long, breaking at
+/// spaces where possible. The spaces at breaks are dropped.
+fn wrap(text: &str, width: usize) -> Vec<Range<usize>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut ranges = Vec::new();
+    let mut start = 0;
 
-    /// Create padding to align the right border.
-    fn padding(&self, content_len: usize) -> String {
-        let box_width: usize = 62;
-        let padding_needed = box_width.saturating_sub(content_len + 2);
-        format!("{}{}", " ".repeat(padding_needed), style("│").dim())
+    while chars.len() - start > width {
+        let limit = start + width;
+        match chars[start..=limit].iter().rposition(|c| *c == ' ') {
+            Some(space) if space > 0 => {
+                ranges.push(start..start + space);
+                start += space + 1;
+            }
+            _ => {
+                ranges.push(start..limit);
+                start = limit;
+            }
+        }
     }
+
+    ranges.push(start..chars.len());
+    ranges
 }
 
 impl<'a> std::fmt::Display for CommitPreview<'a> {
@@ -120,6 +221,14 @@ mod tests {
     use super::*;
     use crate::config::CommitType;
 
+    fn plain_header(preview: &CommitPreview<'_>) -> String {
+        preview
+            .header_spans()
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect()
+    }
+
     #[test]
     fn test_preview_new() {
         let message = CommitMessage::new(CommitType::Feat, "test");
@@ -131,7 +240,7 @@ mod tests {
     fn test_format_header() {
         let message = CommitMessage::new(CommitType::Feat, "add feature").with_scope("core");
         let preview = CommitPreview::new(&message);
-        let header = preview.format_header();
+        let header = plain_header(&preview);
         assert!(header.contains("feat"));
         assert!(header.contains("core"));
         assert!(header.contains("add feature"));
@@ -141,7 +250,45 @@ mod tests {
     fn test_format_header_breaking() {
         let message = CommitMessage::new(CommitType::Feat, "change").with_breaking(true);
         let preview = CommitPreview::new(&message);
-        let header = preview.format_header();
+        let header = plain_header(&preview);
         assert!(header.contains("!"));
     }
+
+    #[test]
+    fn test_subject_overflow_span() {
+        let message = CommitMessage::new(CommitType::Feat, "add a feature");
+        let preview = CommitPreview::new(&message).with_max_subject_length(6);
+        let spans = preview.header_spans();
+        assert_eq!(spans.last().unwrap().0, "feature");
+        assert_eq!(plain_header(&preview), "feat: add a feature");
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("short", 10), vec![0..5]);
+        assert_eq!(wrap("one two three", 7), vec![0..7, 8..13]);
+        assert_eq!(wrap("abcdefghij", 4), vec![0..4, 4..8, 8..10]);
+    }
+
+    #[test]
+    fn test_render_fits_width() {
+        let message = CommitMessage::new(CommitType::Fix, "word ".repeat(20))
+            .with_body("long body line ".repeat(10));
+        let preview = CommitPreview::new(&message).with_max_subject_length(50);
+
+        for columns in [30, 62, 200] {
+            let lines = preview.render(columns);
+            let width = columns.clamp(MIN_WIDTH, MAX_WIDTH);
+            assert!(lines.iter().all(|line| measure_text_width(line) == width));
+        }
+
+        // Only the marks that fit are drawn
+        let lines = preview.render(60);
+        let ruler = lines
+            .iter()
+            .map(|line| console::strip_ansi_codes(line).to_string())
+            .find(|line| line.contains('·'))
+            .unwrap();
+        assert_eq!(ruler.matches('|').count(), 1);
+    }
 }
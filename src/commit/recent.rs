@@ -0,0 +1,102 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Recently used types and scopes.
+//!
+//! The type and scope pickers list what was used in recent commits first,
+//! most recent at the top, so the usual choice is one keystroke away even
+//! with hundreds of scopes.
+
+use std::collections::HashSet;
+
+use super::message::CommitMessage;
+use crate::git;
+
+/// Number of commits read for recent types and scopes.
+const HISTORY_DEPTH: usize = 200;
+
+/// Types and scopes from recent commits, most recent first, without
+/// duplicates.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RecentUsage {
+    pub types: Vec<String>,
+    pub scopes: Vec<String>,
+}
+
+impl RecentUsage {
+    /// Read recent usage from the current repository.
+    pub(crate) fn from_current_repo() -> Self {
+        git::open_repo()
+            .and_then(|repo| repo.recent_commit_messages(HISTORY_DEPTH))
+            .map(|messages| Self::from_messages(&messages))
+            .unwrap_or_default()
+    }
+
+    /// Collect usage from commit messages, newest first.
+    pub(crate) fn from_messages(messages: &[String]) -> Self {
+        let parsed: Vec<CommitMessage> = messages
+            .iter()
+            .filter_map(|message| CommitMessage::parse(message).ok())
+            .collect();
+
+        Self {
+            types: unique(parsed.iter().map(|m| m.commit_type.as_str().to_string())),
+            scopes: unique(parsed.iter().filter_map(|m| m.scope.clone())),
+        }
+    }
+}
+
+/// Order `items` with the recently used ones first, in order of use,
+/// followed by the rest in their original order.
+pub(crate) fn order_by_recent(mut items: Vec<String>, recent: &[String]) -> Vec<String> {
+    items.sort_by_key(|item| recency(recent, item));
+    items
+}
+
+/// Sort key putting recently used items first. Unused items share the
+/// last rank, so a stable sort keeps their order.
+pub(crate) fn recency(recent: &[String], item: &str) -> usize {
+    recent
+        .iter()
+        .position(|used| used == item)
+        .unwrap_or(usize::MAX)
+}
+
+/// Drop empty and repeated entries, keeping the first of each.
+pub(crate) fn unique(items: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    items
+        .into_iter()
+        .filter(|item| !item.is_empty() && seen.insert(item.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_usage() {
+        let messages: Vec<String> = [
+            "fix(api): handle timeouts",
+            "Merge branch 'main'",
+            "feat(web): add login",
+            "fix(api): retry requests",
+            "docs: update readme",
+        ]
+        .iter()
+        .map(|m| m.to_string())
+        .collect();
+
+        let usage = RecentUsage::from_messages(&messages);
+        assert_eq!(usage.types, vec!["fix", "feat", "docs"]);
+        assert_eq!(usage.scopes, vec!["api", "web"]);
+    }
+
+    #[test]
+    fn test_order_by_recent() {
+        let items = vec!["core".to_string(), "api".to_string(), "web".to_string()];
+        let recent = vec!["web".to_string(), "old".to_string(), "api".to_string()];
+        assert_eq!(order_by_recent(items, &recent), vec!["web", "api", "core"]);
+    }
+}
@@ -0,0 +1,380 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Review suggestions for a range of commits.
+//!
+//! Unlike `ck check`, a review doesn't pass or fail. Each commit gets
+//! suggestions instead: rule findings with their fixes, a subject from the
+//! smart generator, a scope when one is missing, and a split when the diff
+//! mixes concerns. The report is markdown, ready to paste into a pull
+//! request review.
+
+use crate::analysis::{RepositoryContext, WarningCode};
+use crate::cli::args::OutputFormat;
+use crate::config::CkConfig;
+use crate::error::Result;
+use crate::git::{diff::diff_trees, Repository};
+use crate::progress::Progress;
+use crate::rules::RuleEngine;
+use crate::smart::{GeneratedMessage, SmartCommit};
+use serde::Serialize;
+
+use super::message::CommitMessage;
+
+/// What a suggestion is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SuggestionKind {
+    /// A rule finding and how to address it.
+    Rule,
+    /// A better subject line.
+    Subject,
+    /// A scope for an unscoped commit.
+    Scope,
+    /// Splitting the commit into focused ones.
+    Split,
+}
+
+impl SuggestionKind {
+    /// Label shown in the report.
+    pub fn label(&self) -> &'static str {
+        match self {
+            SuggestionKind::Rule => "Rule",
+            SuggestionKind::Subject => "Subject",
+            SuggestionKind::Scope => "Scope",
+            SuggestionKind::Split => "Split",
+        }
+    }
+}
+
+/// One suggestion for a commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Suggestion {
+    /// What the suggestion is about.
+    pub kind: SuggestionKind,
+    /// The suggestion, in markdown.
+    pub text: String,
+}
+
+impl Suggestion {
+    fn new(kind: SuggestionKind, text: impl Into<String>) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+        }
+    }
+}
+
+/// Suggestions for one commit.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitReview {
+    /// Commit SHA.
+    pub sha: String,
+    /// First line of the commit message.
+    pub subject: String,
+    /// Suggestions, empty when the commit looks good.
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Suggestions for a range of commits.
+#[derive(Debug, Clone, Serialize)]
+pub struct Review {
+    /// Reviewed range.
+    pub range: String,
+    /// Reviewed commits, newest first; merges are skipped.
+    pub commits: Vec<CommitReview>,
+}
+
+impl Review {
+    /// Review every non-merge commit in `range`.
+    pub fn collect(repo: &Repository, config: &CkConfig, range: &str) -> Result<Self> {
+        let engine = RuleEngine::new(config.clone());
+        let generator = SmartCommit::new(config.clone());
+        let commits = repo.get_commits_in_range(range)?;
+        let progress = Progress::bar(commits.len(), "Reviewing commits");
+        let mut reviews = Vec::new();
+
+        for (oid, message) in commits {
+            progress.inc();
+            let commit = repo.get_commit(&oid.to_string())?;
+            if commit.parent_count() > 1 {
+                continue;
+            }
+
+            // A root commit imports the tree, so only its message is reviewed
+            let context = match commit.parent(0) {
+                Ok(parent) => Some(RepositoryContext::from_diff(
+                    diff_trees(repo, Some(parent.tree_id()), commit.tree_id())?,
+                    config,
+                )),
+                Err(_) => None,
+            };
+
+            reviews.push(CommitReview {
+                sha: oid.to_string(),
+                subject: message.lines().next().unwrap_or("").to_string(),
+                suggestions: suggest(&engine, &generator, &message, context.as_ref()),
+            });
+        }
+        progress.finish();
+
+        Ok(Self {
+            range: range.to_string(),
+            commits: reviews,
+        })
+    }
+
+    /// Number of commits with at least one suggestion.
+    pub fn with_suggestions(&self) -> usize {
+        self.commits
+            .iter()
+            .filter(|c| !c.suggestions.is_empty())
+            .count()
+    }
+
+    /// Render the review as markdown, listing only commits with suggestions.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("## Commit review: `{}`\n\n", self.range);
+        if self.with_suggestions() == 0 {
+            out.push_str(&format!(
+                "No suggestions for {} commit(s).\n",
+                self.commits.len()
+            ));
+            return out;
+        }
+
+        out.push_str(&format!(
+            "{} of {} commit(s) could be improved.\n",
+            self.with_suggestions(),
+            self.commits.len()
+        ));
+        for commit in self.commits.iter().filter(|c| !c.suggestions.is_empty()) {
+            out.push_str(&format!(
+                "\n### `{}` {}\n\n",
+                &commit.sha[..7.min(commit.sha.len())],
+                commit.subject
+            ));
+            for suggestion in &commit.suggestions {
+                out.push_str(&format!(
+                    "- **{}:** {}\n",
+                    suggestion.kind.label(),
+                    suggestion.text
+                ));
+            }
+        }
+        out
+    }
+
+    /// Render the review as markdown, or as JSON when requested.
+    pub fn render(&self, format: Option<OutputFormat>) -> String {
+        match format {
+            Some(OutputFormat::Json) => {
+                serde_json::to_string_pretty(self).unwrap_or_default() + "\n"
+            }
+            _ => self.to_markdown(),
+        }
+    }
+}
+
+/// Suggestions for one commit message, given the context of its diff.
+fn suggest(
+    engine: &RuleEngine,
+    generator: &SmartCommit,
+    message: &str,
+    context: Option<&RepositoryContext>,
+) -> Vec<Suggestion> {
+    let generated = context.map(|c| generator.generate_for(c, 0, false));
+    let mut suggestions = match CommitMessage::parse(message) {
+        Ok(parsed) => message_suggestions(engine, &parsed, context, generated.as_ref()),
+        Err(_) => {
+            let proposal = match generated {
+                Some(ref generated) => format!("consider `{}`", generated.header()),
+                None => "use `type(scope): subject`".to_string(),
+            };
+            vec![Suggestion::new(
+                SuggestionKind::Subject,
+                format!("Not a conventional commit; {}", proposal),
+            )]
+        }
+    };
+    if let Some(context) = context {
+        suggestions.extend(split_suggestions(context));
+    }
+    suggestions
+}
+
+/// Rule findings, a better subject and a missing scope.
+fn message_suggestions(
+    engine: &RuleEngine,
+    parsed: &CommitMessage,
+    context: Option<&RepositoryContext>,
+    generated: Option<&GeneratedMessage>,
+) -> Vec<Suggestion> {
+    let mut suggestions = Vec::new();
+
+    let result = engine.validate_with_diff(parsed, context.map(|c| &c.diff_info));
+    for issue in result.errors.iter().chain(&result.warnings) {
+        let text = match issue.suggestion {
+            Some(ref fix) => format!("`{}`: {}. {}", issue.code, issue.message, fix),
+            None => format!("`{}`: {}", issue.code, issue.message),
+        };
+        suggestions.push(Suggestion::new(SuggestionKind::Rule, text));
+    }
+
+    let generated = match generated {
+        Some(generated) => generated,
+        None => return suggestions,
+    };
+
+    // Only propose a new subject when the current one has findings
+    let flagged = !result.errors.is_empty() || !result.warnings.is_empty();
+    if flagged && generated.header() != parsed.header() {
+        suggestions.push(Suggestion::new(
+            SuggestionKind::Subject,
+            format!("Consider `{}`", generated.header()),
+        ));
+    }
+
+    if let (None, Some(scope)) = (&parsed.scope, &generated.scope) {
+        let scoped = CommitMessage {
+            scope: Some(scope.clone()),
+            ..parsed.clone()
+        };
+        suggestions.push(Suggestion::new(
+            SuggestionKind::Scope,
+            format!("Add the `{}` scope: `{}`", scope, scoped.header()),
+        ));
+    }
+    suggestions
+}
+
+/// Splits recommended by the size and mixed-content warnings.
+fn split_suggestions(context: &RepositoryContext) -> Vec<Suggestion> {
+    context
+        .warnings
+        .iter()
+        .filter(|w| {
+            matches!(
+                w.code,
+                WarningCode::OversizedCommit
+                    | WarningCode::MultiplePackages
+                    | WarningCode::MixedConcerns
+            )
+        })
+        .map(|warning| {
+            let text = match warning.suggestion {
+                Some(ref fix) => format!("{}. {}", warning.message, fix),
+                None => warning.message.clone(),
+            };
+            Suggestion::new(SuggestionKind::Split, text)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{Warning, WarningLevel, Warnings};
+    use crate::git::{DiffInfo, DiffStats};
+
+    fn context(scope: Option<&str>, warnings: Warnings) -> RepositoryContext {
+        RepositoryContext {
+            staged_files: vec!["src/api/export.rs".into()],
+            unstaged_files: Vec::new(),
+            diff_stats: DiffStats::default(),
+            branch: "feature".to_string(),
+            initial_commit: false,
+            packages: Vec::new(),
+            suggested_type: None,
+            suggested_scope: scope.map(str::to_string),
+            warnings,
+            diff_info: DiffInfo::empty(),
+        }
+    }
+
+    #[test]
+    fn test_suggest_scope_and_split() {
+        let config = CkConfig::default();
+        let engine = RuleEngine::new(config.clone());
+        let generator = SmartCommit::new(config);
+        let mut warnings = Warnings::new();
+        warnings.add(Warning {
+            level: WarningLevel::Info,
+            code: WarningCode::MixedConcerns,
+            message: "Commit includes source, tests, and documentation".to_string(),
+            suggestion: None,
+        });
+
+        let suggestions = suggest(
+            &engine,
+            &generator,
+            "feat: add the export command",
+            Some(&context(Some("api"), warnings)),
+        );
+        assert_eq!(
+            suggestions,
+            vec![
+                Suggestion::new(
+                    SuggestionKind::Scope,
+                    "Add the `api` scope: `feat(api): add the export command`"
+                ),
+                Suggestion::new(
+                    SuggestionKind::Split,
+                    "Commit includes source, tests, and documentation"
+                ),
+            ]
+        );
+
+        let scoped = suggest(
+            &engine,
+            &generator,
+            "feat(api): add the export command",
+            Some(&context(Some("api"), Warnings::new())),
+        );
+        assert!(scoped.is_empty());
+    }
+
+    #[test]
+    fn test_suggest_for_bad_messages() {
+        let config = CkConfig::default();
+        let engine = RuleEngine::new(config.clone());
+        let generator = SmartCommit::new(config);
+
+        let unparsed = suggest(&engine, &generator, "updated stuff", None);
+        assert_eq!(unparsed.len(), 1);
+        assert_eq!(unparsed[0].kind, SuggestionKind::Subject);
+        assert_eq!(
+            unparsed[0].text,
+            "Not a conventional commit; use `type(scope): subject`"
+        );
+
+        let flagged = suggest(&engine, &generator, "feat: Added export.", None);
+        assert!(!flagged.is_empty());
+        assert!(flagged.iter().all(|s| s.kind == SuggestionKind::Rule));
+    }
+
+    #[test]
+    fn test_markdown_lists_commits_with_suggestions() {
+        let review = Review {
+            range: "main..HEAD".to_string(),
+            commits: vec![
+                CommitReview {
+                    sha: "0123456789abcdef".to_string(),
+                    subject: "feat: add export".to_string(),
+                    suggestions: vec![Suggestion::new(SuggestionKind::Scope, "Add a scope")],
+                },
+                CommitReview {
+                    sha: "fedcba9876543210".to_string(),
+                    subject: "fix(api): handle timeouts".to_string(),
+                    suggestions: Vec::new(),
+                },
+            ],
+        };
+
+        let markdown = review.to_markdown();
+        assert!(markdown.starts_with("## Commit review: `main..HEAD`"));
+        assert!(markdown.contains("1 of 2 commit(s) could be improved."));
+        assert!(markdown.contains("### `0123456` feat: add export\n\n- **Scope:** Add a scope\n"));
+        assert!(!markdown.contains("fedcba9"));
+    }
+}
@@ -0,0 +1,112 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Signing past commits, for branches that newly require signatures.
+
+use crate::error::Result;
+use crate::git;
+use serde::Serialize;
+
+use super::fix::{guard_published, print_push_commands, rewrite_base};
+
+/// Outcome of `ck sign`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SignReport {
+    /// Range that was signed.
+    pub range: String,
+    /// Whether history was left alone.
+    pub dry_run: bool,
+    /// Original SHAs of the unsigned commits by the current user.
+    pub unsigned: Vec<String>,
+    /// New HEAD after rewriting, when anything was signed.
+    pub new_head: Option<String>,
+    /// Original SHAs of signed commits that were signed again.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resigned: Vec<String>,
+    /// Original SHAs of other authors' commits whose signatures were dropped.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dropped_signatures: Vec<String>,
+    /// Remote-tracking branches that had rewritten commits.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub published: Vec<String>,
+    /// Commands that publish the rewrite.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub push_commands: Vec<String>,
+}
+
+/// Sign the unsigned commits the current user authored in `range`, which
+/// must end at HEAD, with the key git is configured to sign with.
+///
+/// Commits by other authors keep their content but get new SHAs after the
+/// first signed commit, so their signatures are dropped. Commits already on
+/// a remote-tracking branch are only rewritten with `force_rewrite`.
+pub fn sign_range(range: &str, force_rewrite: bool, dry_run: bool) -> Result<SignReport> {
+    let repo = git::open_repo()?;
+    let base = rewrite_base(&repo, range, "sign")?;
+    let unsigned = git::rewrite::unsigned_own_commits(&repo, base)?;
+
+    let mut report = SignReport {
+        range: range.to_string(),
+        dry_run,
+        unsigned,
+        new_head: None,
+        resigned: Vec::new(),
+        dropped_signatures: Vec::new(),
+        published: Vec::new(),
+        push_commands: Vec::new(),
+    };
+    let first = match report.unsigned.first() {
+        Some(first) if !dry_run => first.clone(),
+        _ => return Ok(report),
+    };
+
+    git::signing::check_signing_setup()?;
+    if let Some(published) = guard_published(&repo, &first, force_rewrite, "sign")? {
+        report.published = published.branches;
+        report.push_commands = published.push_commands;
+    }
+
+    let rewritten = git::rewrite::sign_commits(&repo, base)?;
+    report.new_head = Some(rewritten.new_head);
+    report.resigned = rewritten.resigned;
+    report.dropped_signatures = rewritten.dropped_signatures;
+    Ok(report)
+}
+
+impl SignReport {
+    /// Print the commits to sign and what rewriting did.
+    pub fn print(&self) {
+        if self.unsigned.is_empty() {
+            println!("No unsigned commits of yours in {}", self.range);
+            return;
+        }
+
+        let verb = if self.dry_run { "Would sign" } else { "Signed" };
+        println!("{} {} commit(s):", verb, self.unsigned.len());
+        for sha in &self.unsigned {
+            println!("  {}", &sha[..7]);
+        }
+        if !self.resigned.is_empty() {
+            println!(
+                "✓ Signed {} later commit(s) of yours again",
+                self.resigned.len()
+            );
+        }
+        if !self.dropped_signatures.is_empty() {
+            let shas: Vec<&str> = self.dropped_signatures.iter().map(|s| &s[..7]).collect();
+            println!(
+                "! Dropped the signatures of {} commit(s) by other authors: {}",
+                shas.len(),
+                shas.join(", ")
+            );
+        }
+        match self.new_head {
+            Some(ref head) => println!("✓ Rewrote history, HEAD is now {}", &head[..7]),
+            None if self.dry_run => println!("[dry-run] No changes made"),
+            None => {}
+        }
+        if !self.published.is_empty() {
+            print_push_commands(&self.published, &self.push_commands);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Commit statistics over a range of history.
+
+use crate::cli::args::OutputFormat;
+use crate::error::{CkError, GitError, Result};
+use crate::git::Repository;
+use crate::progress::Progress;
+use console::style;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::message::CommitMessage;
+use super::time_spent::{format_duration, time_spent};
+use crate::smart::provenance::generator;
+
+/// Commit counts and recorded time for a range of commits.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CommitStats {
+    /// Number of commits examined.
+    pub commits: usize,
+    /// Commits per type; unconventional commits are counted as `other`.
+    pub by_type: BTreeMap<String, usize>,
+    /// Commits per scope.
+    pub by_scope: BTreeMap<String, usize>,
+    /// Generated commits per `Generated-by:` value.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub generated_by: BTreeMap<String, usize>,
+    /// Recorded time, when requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<TimeStats>,
+}
+
+/// Minutes from `Time-spent:` trailers.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TimeStats {
+    /// Total minutes recorded.
+    pub total_minutes: u64,
+    /// Number of commits with a `Time-spent:` trailer.
+    pub tracked_commits: usize,
+    /// Minutes per author email.
+    pub by_author: BTreeMap<String, u64>,
+    /// Minutes per commit type.
+    pub by_type: BTreeMap<String, u64>,
+    /// Minutes per scope.
+    pub by_scope: BTreeMap<String, u64>,
+}
+
+impl CommitStats {
+    /// Collect statistics for `range`, or all of HEAD's history.
+    pub fn collect(repo: &Repository, range: Option<&str>, with_time: bool) -> Result<Self> {
+        let mut stats = Self {
+            time: with_time.then(TimeStats::default),
+            ..Default::default()
+        };
+
+        let progress = Progress::spinner("Scanning history");
+        for (author, message) in commits(repo, range)? {
+            stats.add(&author, &message);
+        }
+        progress.finish();
+        Ok(stats)
+    }
+
+    /// Count one commit.
+    fn add(&mut self, author: &str, message: &str) {
+        let parsed = CommitMessage::parse(message).ok();
+        let commit_type = parsed
+            .as_ref()
+            .map(|m| m.commit_type.to_string())
+            .unwrap_or_else(|| "other".to_string());
+        let scope = parsed.and_then(|m| m.scope);
+
+        self.commits += 1;
+        *self.by_type.entry(commit_type.clone()).or_default() += 1;
+        if let Some(ref scope) = scope {
+            *self.by_scope.entry(scope.clone()).or_default() += 1;
+        }
+        if let Some(generator) = generator(message) {
+            *self.generated_by.entry(generator).or_default() += 1;
+        }
+
+        if let (Some(time), Some(minutes)) = (self.time.as_mut(), time_spent(message)) {
+            // Trailers come from any author, so clamp instead of overflowing
+            let add = |total: &mut u64| *total = total.saturating_add(minutes);
+            add(&mut time.total_minutes);
+            time.tracked_commits += 1;
+            add(time.by_author.entry(author.to_string()).or_default());
+            add(time.by_type.entry(commit_type).or_default());
+            if let Some(scope) = scope {
+                add(time.by_scope.entry(scope).or_default());
+            }
+        }
+    }
+
+    /// Print the statistics to stdout.
+    pub fn print(&self, format: Option<OutputFormat>) {
+        if let Some(OutputFormat::Json) = format {
+            println!("{}", serde_json::to_string_pretty(self).unwrap_or_default());
+            return;
+        }
+
+        println!("{} {} commit(s)", style("Stats:").bold(), self.commits);
+        print_counts("Types", &self.by_type, |n| n.to_string());
+        print_counts("Scopes", &self.by_scope, |n| n.to_string());
+        print_counts("Generated by", &self.generated_by, |n| n.to_string());
+
+        if let Some(ref time) = self.time {
+            println!(
+                "\n{} {} over {} commit(s)",
+                style("Time spent:").bold(),
+                format_duration(time.total_minutes),
+                time.tracked_commits
+            );
+            print_counts("By author", &time.by_author, format_duration);
+            print_counts("By type", &time.by_type, format_duration);
+            print_counts("By scope", &time.by_scope, format_duration);
+        }
+    }
+}
+
+/// Print a labelled table, largest value first.
+fn print_counts<T: Copy + Ord>(
+    label: &str,
+    counts: &BTreeMap<String, T>,
+    show: impl Fn(T) -> String,
+) {
+    if counts.is_empty() {
+        return;
+    }
+
+    let mut rows: Vec<(&String, &T)> = counts.iter().collect();
+    rows.sort_by_key(|(_, value)| std::cmp::Reverse(**value));
+
+    println!("  {}", style(label).dim());
+    for (name, value) in rows {
+        println!("    {:<16} {}", name, show(*value));
+    }
+}
+
+/// Author email and message of each commit in `range`.
+fn commits(repo: &Repository, range: Option<&str>) -> Result<Vec<(String, String)>> {
+    let git = repo.inner();
+    let oids = match range {
+        Some(range) => repo
+            .get_commits_in_range(range)?
+            .into_iter()
+            .map(|(oid, _)| oid)
+            .collect(),
+        None => {
+            let mut revwalk = git.revwalk().map_err(|e| {
+                CkError::Git(GitError::CommandFailed {
+                    command: "revwalk".to_string(),
+                    message: e.message().to_string(),
+                })
+            })?;
+            if revwalk.push_head().is_err() {
+                return Ok(Vec::new());
+            }
+            revwalk.filter_map(|oid| oid.ok()).collect::<Vec<_>>()
+        }
+    };
+
+    Ok(oids
+        .into_iter()
+        .filter_map(|oid| git.find_commit(oid).ok())
+        .map(|commit| {
+            (
+                commit.author().email().unwrap_or("unknown").to_string(),
+                commit.message().unwrap_or("").to_string(),
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_aggregation() {
+        let mut stats = CommitStats {
+            time: Some(TimeStats::default()),
+            ..Default::default()
+        };
+        stats.add("a@example.com", "feat(api): add export\n\nTime-spent: 2h");
+        stats.add("b@example.com", "fix(api): handle empty\n\nTime-spent: 30m");
+        stats.add("a@example.com", "docs: update readme");
+        stats.add("a@example.com", "wip");
+        stats.add(
+            "c@example.com",
+            "chore: bump deps\n\nGenerated-by: ck-smart v1.0.0 (heuristic)",
+        );
+
+        assert_eq!(stats.commits, 5);
+        assert_eq!(stats.generated_by["ck-smart v1.0.0 (heuristic)"], 1);
+        assert_eq!(stats.by_type["other"], 1);
+        assert_eq!(stats.by_scope["api"], 2);
+
+        let time = stats.time.unwrap();
+        assert_eq!(time.total_minutes, 150);
+        assert_eq!(time.tracked_commits, 2);
+        assert_eq!(time.by_author["a@example.com"], 120);
+        assert_eq!(time.by_type["fix"], 30);
+        assert_eq!(time.by_scope["api"], 150);
+    }
+}
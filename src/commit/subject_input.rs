@@ -0,0 +1,256 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Subject prompt with live feedback.
+//!
+//! While the subject is typed, the line below it shows how many characters
+//! are left and the first problem found by the subject rules. Enter is
+//! refused only while an error remains; warnings are shown but allowed.
+//! Esc leaves the prompt without an answer.
+
+use console::{measure_text_width, style, Key, Term};
+
+use crate::error::Result;
+use crate::rules::ValidationIssue;
+
+/// Remaining count below which the counter turns yellow.
+const LOW_REMAINING: usize = 10;
+
+/// Text being edited and the cursor position, in characters.
+#[derive(Debug, Default)]
+struct LineBuffer {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl LineBuffer {
+    fn new(initial: &str) -> Self {
+        let chars: Vec<char> = initial.chars().collect();
+        let cursor = chars.len();
+        Self { chars, cursor }
+    }
+
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// Apply a key. Returns false for keys that are not editing keys.
+    fn apply(&mut self, key: &Key) -> bool {
+        match key {
+            Key::Char(c) if !c.is_control() => {
+                self.chars.insert(self.cursor, *c);
+                self.cursor += 1;
+            }
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.chars.remove(self.cursor);
+            }
+            Key::Del if self.cursor < self.chars.len() => {
+                self.chars.remove(self.cursor);
+            }
+            Key::ArrowLeft => self.cursor = self.cursor.saturating_sub(1),
+            Key::ArrowRight => self.cursor = (self.cursor + 1).min(self.chars.len()),
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = self.chars.len(),
+            Key::Backspace | Key::Del => {}
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Checks a subject as it is typed.
+type SubjectCheck<'a> = Box<dyn Fn(&str) -> Vec<ValidationIssue> + 'a>;
+
+/// Prompt for a subject, checking it with `check` as it is typed.
+pub(crate) struct SubjectInput<'a> {
+    prompt: &'a str,
+    initial: String,
+    max_len: usize,
+    check: SubjectCheck<'a>,
+}
+
+impl<'a> SubjectInput<'a> {
+    pub(crate) fn new(
+        prompt: &'a str,
+        max_len: usize,
+        check: impl Fn(&str) -> Vec<ValidationIssue> + 'a,
+    ) -> Self {
+        Self {
+            prompt,
+            initial: String::new(),
+            max_len,
+            check: Box::new(check),
+        }
+    }
+
+    pub(crate) fn with_initial_text(mut self, initial: impl Into<String>) -> Self {
+        self.initial = initial.into();
+        self
+    }
+
+    /// Read the subject from the terminal, or `None` on Esc.
+    pub(crate) fn interact(&self, term: &Term) -> Result<Option<String>> {
+        let mut buffer = LineBuffer::new(&self.initial);
+        let mut refused = false;
+        self.draw(term, &buffer, refused)?;
+
+        loop {
+            let key = term.read_key()?;
+            if key == Key::Enter {
+                let text = buffer.text();
+                let issues = (self.check)(&text);
+                if !text.trim().is_empty() && !issues.iter().any(|issue| issue.is_error) {
+                    self.finish(term, &text)?;
+                    return Ok(Some(text));
+                }
+                refused = true;
+            } else if key == Key::Escape {
+                self.clear(term)?;
+                return Ok(None);
+            } else if buffer.apply(&key) {
+                refused = false;
+            } else {
+                continue;
+            }
+            self.draw(term, &buffer, refused)?;
+        }
+    }
+
+    /// Redraw the input line and the feedback line, leaving the cursor
+    /// in the input.
+    fn draw(&self, term: &Term, buffer: &LineBuffer, refused: bool) -> Result<()> {
+        let text = buffer.text();
+        let prefix = format!("{} {} ", style("?").yellow(), style(self.prompt).bold());
+
+        term.clear_line()?;
+        term.write_str(&format!("{}{}", prefix, text))?;
+        term.write_str("\n")?;
+        term.clear_line()?;
+        term.write_str(&format!(
+            "  {}",
+            status_line(&text, &(self.check)(&text), self.max_len, refused)
+        ))?;
+        term.move_cursor_up(1)?;
+        term.write_str("\r")?;
+
+        let before: String = buffer.chars[..buffer.cursor].iter().collect();
+        term.move_cursor_right(measure_text_width(&prefix) + measure_text_width(&before))?;
+        Ok(())
+    }
+
+    /// Remove the prompt and feedback lines.
+    fn clear(&self, term: &Term) -> Result<()> {
+        term.write_str("\r")?;
+        term.clear_line()?;
+        term.move_cursor_down(1)?;
+        term.clear_line()?;
+        term.move_cursor_up(1)?;
+        Ok(())
+    }
+
+    /// Replace the prompt with the accepted subject.
+    fn finish(&self, term: &Term, text: &str) -> Result<()> {
+        term.write_str("\r")?;
+        term.clear_line()?;
+        term.write_line(&format!(
+            "{} {} {} {}",
+            style("✔").green(),
+            style(self.prompt).bold(),
+            style("·").dim(),
+            style(text).green()
+        ))?;
+        term.clear_line()?;
+        Ok(())
+    }
+}
+
+/// The feedback shown below the input: the remaining character count and
+/// the most important issue.
+fn status_line(text: &str, issues: &[ValidationIssue], max_len: usize, refused: bool) -> String {
+    let len = text.chars().count();
+    let counter = if len > max_len {
+        style(format!("{} over", len - max_len)).red().bold()
+    } else if max_len - len < LOW_REMAINING {
+        style(format!("{} left", max_len - len)).yellow()
+    } else {
+        style(format!("{} left", max_len - len)).dim()
+    };
+
+    let issue = issues
+        .iter()
+        .find(|issue| issue.is_error)
+        .or_else(|| issues.first());
+    let hint = match issue {
+        Some(issue) if issue.is_error && refused => style(issue.message.clone()).red().bold(),
+        Some(issue) if issue.is_error => style(issue.message.clone()).red(),
+        Some(issue) => style(issue.message.clone()).yellow(),
+        None if text.trim().is_empty() && refused => style("Subject is required".to_string()).red(),
+        None => style(String::new()),
+    };
+
+    let more = match issues.len() {
+        0 | 1 => String::new(),
+        n => style(format!(" (+{} more)", n - 1)).dim().to_string(),
+    };
+
+    if hint.to_string().is_empty() {
+        counter.to_string()
+    } else {
+        format!("{} {} {}{}", counter, style("·").dim(), hint, more)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_buffer_editing() {
+        let mut buffer = LineBuffer::new("ad");
+        buffer.apply(&Key::ArrowLeft);
+        buffer.apply(&Key::Char('d'));
+        buffer.apply(&Key::End);
+        buffer.apply(&Key::Char('s'));
+        assert_eq!(buffer.text(), "adds");
+
+        buffer.apply(&Key::Backspace);
+        buffer.apply(&Key::Home);
+        buffer.apply(&Key::Del);
+        assert_eq!(buffer.text(), "dd");
+        assert!(!buffer.apply(&Key::Tab));
+    }
+
+    #[test]
+    fn test_status_line() {
+        let warning = ValidationIssue {
+            code: "subject-case".to_string(),
+            message: "Subject should start with lowercase".to_string(),
+            suggestion: None,
+            is_error: false,
+            line: Some(1),
+            span: None,
+        };
+
+        let status = console::strip_ansi_codes(&status_line(
+            "Add",
+            std::slice::from_ref(&warning),
+            10,
+            false,
+        ))
+        .to_string();
+        assert_eq!(status, "7 left · Subject should start with lowercase");
+
+        let status =
+            console::strip_ansi_codes(&status_line("Add", &[warning.clone(), warning], 10, false))
+                .to_string();
+        assert!(status.ends_with("lowercase (+1 more)"));
+
+        let status =
+            console::strip_ansi_codes(&status_line("add things", &[], 8, false)).to_string();
+        assert_eq!(status, "2 over");
+
+        let status = console::strip_ansi_codes(&status_line("", &[], 8, true)).to_string();
+        assert_eq!(status, "8 left · Subject is required");
+    }
+}
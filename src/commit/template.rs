@@ -0,0 +1,139 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Message templates from `[templates]`, for `ck commit --template`.
+//!
+//! `{type}`, `{scope}`, `{ticket}` and `{branch}` are filled in from the
+//! commit being built. Lines of the body that end in `:` are sections, and
+//! an enforced template rejects messages that leave one empty.
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+lazy_static! {
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{(type|scope|ticket|branch)\}").unwrap();
+}
+
+/// Values for the placeholders of a template.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateVars {
+    /// Commit type.
+    pub commit_type: Option<String>,
+    /// Commit scope.
+    pub scope: Option<String>,
+    /// Issue the commit refers to.
+    pub ticket: Option<String>,
+    /// Current branch.
+    pub branch: Option<String>,
+}
+
+impl TemplateVars {
+    fn get(&self, name: &str) -> Option<&str> {
+        match name {
+            "type" => self.commit_type.as_deref(),
+            "scope" => self.scope.as_deref(),
+            "ticket" => self.ticket.as_deref(),
+            _ => self.branch.as_deref(),
+        }
+    }
+}
+
+/// Fill in `text`. Placeholders without a value become empty.
+pub fn render(text: &str, vars: &TemplateVars) -> String {
+    PLACEHOLDER
+        .replace_all(text, |caps: &Captures| {
+            vars.get(&caps[1]).unwrap_or_default().to_string()
+        })
+        .into_owned()
+}
+
+/// Fill in footer lines, leaving out those with a placeholder that has no
+/// value. `None` when no line is left.
+pub fn render_footer(text: &str, vars: &TemplateVars) -> Option<String> {
+    let lines: Vec<String> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter(|line| {
+            PLACEHOLDER
+                .captures_iter(line)
+                .all(|caps| vars.get(&caps[1]).is_some_and(|v| !v.is_empty()))
+        })
+        .map(|line| render(line, vars))
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// Section headers of a template body: lines ending in `:`.
+pub fn sections(body: &str) -> Vec<&str> {
+    body.lines()
+        .map(str::trim)
+        .filter(|line| line.len() > 1 && line.ends_with(':'))
+        .collect()
+}
+
+/// Sections of `template` that are missing from `body` or have nothing
+/// under them.
+pub fn empty_sections<'a>(template: &'a str, body: &str) -> Vec<&'a str> {
+    let headers = sections(template);
+    let lines: Vec<&str> = body.lines().map(str::trim).collect();
+    headers
+        .iter()
+        .copied()
+        .filter(|header| {
+            let start = match lines.iter().position(|line| line.starts_with(header)) {
+                Some(start) => start,
+                None => return true,
+            };
+            let inline = lines[start][header.len()..].trim();
+            let below = lines[start + 1..]
+                .iter()
+                .take_while(|line| !headers.contains(line))
+                .any(|line| !line.is_empty());
+            inline.is_empty() && !below
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = "Why:\n\nWhat:\n\nTesting:\n";
+
+    fn vars() -> TemplateVars {
+        TemplateVars {
+            commit_type: Some("fix".to_string()),
+            scope: None,
+            ticket: Some("JIRA-1234".to_string()),
+            branch: Some("feature/JIRA-1234-login".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("{type}({scope}) on {branch} {unknown}", &vars()),
+            "fix() on feature/JIRA-1234-login {unknown}"
+        );
+        assert_eq!(
+            render_footer("Refs: {ticket}\nScope: {scope}\n", &vars()).as_deref(),
+            Some("Refs: JIRA-1234")
+        );
+        assert!(render_footer("Scope: {scope}", &vars()).is_none());
+    }
+
+    #[test]
+    fn test_empty_sections() {
+        assert_eq!(sections(BODY), ["Why:", "What:", "Testing:"]);
+        assert_eq!(empty_sections(BODY, BODY), ["Why:", "What:", "Testing:"]);
+
+        let body = "Why: logins time out\n\nWhat:\nRetry once.\n\nTesting:\n";
+        assert_eq!(empty_sections(BODY, body), ["Testing:"]);
+        assert!(empty_sections(BODY, "Why: a\nWhat: b\nTesting: c").is_empty());
+        assert_eq!(empty_sections(BODY, "Why: a\nWhat: b"), ["Testing:"]);
+    }
+}
@@ -0,0 +1,135 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `Time-spent:` trailers for tracking effort per commit.
+//!
+//! Durations use GitLab's `/spend` units: `m`, `h`, `d` (8 hours) and
+//! `w` (5 days), e.g. `1d 2h30m`. They are normalized to hours and
+//! minutes, such as `10h30m`, when written as a trailer.
+
+use crate::error::{CkError, Result};
+
+/// Trailer token for time spent.
+pub const TIME_SPENT_TRAILER: &str = "Time-spent";
+
+const MINUTES_PER_HOUR: u64 = 60;
+const HOURS_PER_DAY: u64 = 8;
+const DAYS_PER_WEEK: u64 = 5;
+
+/// Parse a duration such as `2h30m` or `1d 4h` into minutes.
+pub fn parse_duration(input: &str) -> Result<u64> {
+    let invalid = |reason: &str| CkError::WithContext {
+        code: "duration-invalid",
+        context: "time spent".to_string(),
+        message: format!("Invalid duration '{}': {}", input, reason),
+    };
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in input.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        if c.is_whitespace() && digits.is_empty() {
+            continue;
+        }
+
+        let unit = match c.to_ascii_lowercase() {
+            'm' => 1,
+            'h' => MINUTES_PER_HOUR,
+            'd' => HOURS_PER_DAY * MINUTES_PER_HOUR,
+            'w' => DAYS_PER_WEEK * HOURS_PER_DAY * MINUTES_PER_HOUR,
+            _ => return Err(invalid("expected a number followed by w, d, h or m")),
+        };
+        if digits.is_empty() {
+            return Err(invalid("each unit needs a number before it"));
+        }
+        total = digits
+            .parse::<u64>()
+            .ok()
+            .and_then(|amount| amount.checked_mul(unit))
+            .and_then(|minutes| total.checked_add(minutes))
+            .ok_or_else(|| invalid("number is too large"))?;
+        digits.clear();
+    }
+
+    if !digits.is_empty() {
+        return Err(invalid("missing unit after the last number"));
+    }
+    if total == 0 {
+        return Err(invalid("duration must be greater than zero"));
+    }
+    Ok(total)
+}
+
+/// Format minutes as hours and minutes, e.g. `2h30m`.
+pub fn format_duration(minutes: u64) -> String {
+    let hours = minutes / MINUTES_PER_HOUR;
+    let rest = minutes % MINUTES_PER_HOUR;
+    match (hours, rest) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h{}m", h, m),
+    }
+}
+
+/// Total time recorded in `Time-spent:` trailers of a commit message.
+pub fn time_spent(message: &str) -> Option<u64> {
+    let prefix = format!("{}:", TIME_SPENT_TRAILER.to_lowercase());
+    let minutes: Vec<u64> = message
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.to_lowercase().starts_with(&prefix) {
+                parse_duration(&line[prefix.len()..]).ok()
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    if minutes.is_empty() {
+        None
+    } else {
+        Some(minutes.iter().fold(0, |total, m| total.saturating_add(*m)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format() {
+        assert_eq!(parse_duration("2h30m").unwrap(), 150);
+        assert_eq!(parse_duration("1d 2h").unwrap(), 600);
+        assert_eq!(parse_duration("1w").unwrap(), 2400);
+        assert_eq!(format_duration(parse_duration("90m").unwrap()), "1h30m");
+        assert_eq!(format_duration(45), "45m");
+        assert_eq!(format_duration(120), "2h");
+
+        for bad in [
+            "",
+            "2",
+            "h",
+            "2x",
+            "0m",
+            "2h30",
+            "9999999999999999w",
+            "18446744073709551615m 1m",
+        ] {
+            assert!(parse_duration(bad).is_err(), "{:?} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_time_spent_from_message() {
+        let message = "feat: add export\n\nBody\n\nRefs: #1\nTime-spent: 1h30m\ntime-spent: 15m";
+        assert_eq!(time_spent(message), Some(105));
+        assert_eq!(time_spent("fix: typo"), None);
+
+        let huge = "fix: typo\n\nTime-spent: 18446744073709551615m\nTime-spent: 1m";
+        assert_eq!(time_spent(huge), Some(u64::MAX));
+    }
+}
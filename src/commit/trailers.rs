@@ -0,0 +1,217 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Commit message trailers.
+//!
+//! Trailers are `Token: value` lines in the last paragraph of a message,
+//! as `git interpret-trailers` reads them. Conventional Commits also
+//! allows `Token #value`, e.g. `Refs #42`. A value continues on following
+//! lines that start with whitespace, and `BREAKING CHANGE` is the only
+//! token with a space in it.
+
+use crate::error::{CkError, Result};
+
+use super::breaking::BREAKING_CHANGE_TOKEN;
+
+/// Tokens offered by the interactive builder, in menu order.
+pub const COMMON_TOKENS: &[&str] = &["Closes", "Refs", "Co-authored-by", "Signed-off-by"];
+
+/// Token of the trailer added for `--issue`.
+pub const ISSUE_TOKEN: &str = "Refs";
+
+/// Token certifying the Developer Certificate of Origin.
+pub const SIGNED_OFF_BY: &str = "Signed-off-by";
+
+/// A `Token: value` line in the footer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trailer {
+    /// Token, e.g. `Refs`.
+    pub token: String,
+    /// Value, with continuation lines joined by newlines.
+    pub value: String,
+}
+
+impl Trailer {
+    /// Create a trailer.
+    pub fn new(token: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Parse a `--trailer` argument: `Token=value` or `Token: value`.
+    pub fn from_arg(arg: &str) -> Result<Self> {
+        let split = arg
+            .find(['=', ':'])
+            .map(|index| (arg[..index].trim(), arg[index + 1..].trim()));
+        match split {
+            Some((token, value)) if is_token(token) && !value.is_empty() => {
+                Ok(Self::new(token, value))
+            }
+            _ => Err(CkError::WithContext {
+                code: "trailer-invalid",
+                context: "trailer".to_string(),
+                message: format!(
+                    "Expected a trailer like Refs=#42 or \"Reviewed-by: Name\", got '{}'",
+                    arg
+                ),
+            }),
+        }
+    }
+
+    /// The `Refs:` trailer for an issue. Bare numbers get a `#`.
+    pub fn issue(issue: &str) -> Self {
+        let issue = issue.trim();
+        let value = if !issue.is_empty() && issue.chars().all(|c| c.is_ascii_digit()) {
+            format!("#{}", issue)
+        } else {
+            issue.to_string()
+        };
+        Self::new(ISSUE_TOKEN, value)
+    }
+
+    /// Whether this trailer has `token`, ignoring case as git does.
+    pub fn has_token(&self, token: &str) -> bool {
+        self.token.eq_ignore_ascii_case(token)
+    }
+
+    /// Render as footer lines.
+    pub fn render(&self) -> String {
+        format!("{}: {}", self.token, self.value)
+    }
+}
+
+/// Whether `paragraph` is a trailer block: its first line is a trailer and
+/// every other line is a trailer or continues one.
+pub fn is_block(paragraph: &str) -> bool {
+    let mut lines = paragraph.lines();
+    match lines.next() {
+        Some(first) if parse_line(first).is_some() => {}
+        _ => return false,
+    }
+    lines.all(|line| line.starts_with(char::is_whitespace) || parse_line(line).is_some())
+}
+
+/// Trailers in a block, with continuation lines joined to their value.
+///
+/// Lines that are neither a trailer nor a continuation are skipped.
+pub fn parse(block: &str) -> Vec<Trailer> {
+    let mut trailers: Vec<Trailer> = Vec::new();
+    for line in block.lines() {
+        if line.starts_with(char::is_whitespace) && !line.trim().is_empty() {
+            if let Some(last) = trailers.last_mut() {
+                last.value.push('\n');
+                last.value.push_str(line);
+            }
+        } else if let Some((token, value)) = parse_line(line) {
+            trailers.push(Trailer::new(token, value));
+        }
+    }
+    trailers
+}
+
+/// Split the text after the header into body and footer.
+///
+/// The last paragraph is the footer when it is a trailer block; anything
+/// else is body. Both are trimmed, and empty parts are `None`.
+pub fn split_footer(text: &str) -> (Option<String>, Option<String>) {
+    let text = text.trim();
+    let (body, last) = match text.rfind("\n\n") {
+        Some(index) => (&text[..index], &text[index..]),
+        None => ("", text),
+    };
+    let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+
+    if is_block(last.trim()) {
+        (non_empty(body), non_empty(last))
+    } else {
+        (non_empty(text), None)
+    }
+}
+
+/// Token and value of a single trailer line.
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let separator = line.find([':', '#'])?;
+    let (token, value) = if line[separator..].starts_with(':') {
+        (&line[..separator], &line[separator + 1..])
+    } else {
+        // `Token #value` keeps the `#` in the value
+        (line[..separator].strip_suffix(' ')?, &line[separator..])
+    };
+    let value = value.trim();
+    (is_token(token) && !value.is_empty()).then_some((token, value))
+}
+
+/// Whether `token` may start a trailer.
+pub fn is_token(token: &str) -> bool {
+    if token == BREAKING_CHANGE_TOKEN {
+        return true;
+    }
+    let mut chars = token.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_block() {
+        let block = "Refs #42\nCo-authored-by: Ada <ada@example.com>\n\
+                     BREAKING CHANGE: config moved\n  Migration:\n  - rename it";
+        assert!(is_block(block));
+        assert_eq!(
+            parse(block),
+            vec![
+                Trailer::new("Refs", "#42"),
+                Trailer::new("Co-authored-by", "Ada <ada@example.com>"),
+                Trailer::new(
+                    "BREAKING CHANGE",
+                    "config moved\n  Migration:\n  - rename it"
+                ),
+            ]
+        );
+        assert!(!is_block("Some prose: with a colon\nand more prose"));
+        assert!(!is_block("Not a token: value"));
+    }
+
+    #[test]
+    fn test_split_footer() {
+        assert_eq!(
+            split_footer("First paragraph.\n\nSecond one.\n\nRefs: #1\nCloses: #2"),
+            (
+                Some("First paragraph.\n\nSecond one.".to_string()),
+                Some("Refs: #1\nCloses: #2".to_string())
+            )
+        );
+        assert_eq!(
+            split_footer("Just a body.\n\nStill body"),
+            (Some("Just a body.\n\nStill body".to_string()), None)
+        );
+        assert_eq!(
+            split_footer("Signed-off-by: Ada <ada@example.com>"),
+            (
+                None,
+                Some("Signed-off-by: Ada <ada@example.com>".to_string())
+            )
+        );
+    }
+
+    #[test]
+    fn test_from_arg() {
+        assert_eq!(
+            Trailer::from_arg("Refs=#42").unwrap(),
+            Trailer::new("Refs", "#42")
+        );
+        assert_eq!(
+            Trailer::from_arg("Reviewed-by: Bob <bob@example.com>").unwrap(),
+            Trailer::new("Reviewed-by", "Bob <bob@example.com>")
+        );
+        assert!(Trailer::from_arg("no separator").is_err());
+        assert!(Trailer::from_arg("Bad token=x").is_err());
+        assert_eq!(Trailer::issue("42").render(), "Refs: #42");
+        assert_eq!(Trailer::issue("PROJ-7").render(), "Refs: PROJ-7");
+    }
+}
@@ -0,0 +1,197 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Word-level diff between two commit messages.
+//!
+//! Fix previews show what a proposed message changes inline instead of
+//! printing both messages in full. Messages are split into words,
+//! whitespace runs and single punctuation characters, so `fix(api):`
+//! becomes `fix`, `(`, `api`, `)` and `:`, and a changed scope doesn't
+//! mark the whole header as different.
+
+use console::style;
+
+/// A run of text that is kept, removed or added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change<'a> {
+    /// Text in both messages.
+    Equal(&'a str),
+    /// Text only in the old message.
+    Removed(&'a str),
+    /// Text only in the new message.
+    Added(&'a str),
+}
+
+/// Word-level changes turning `old` into `new`.
+///
+/// Adjacent tokens of the same kind are merged into a single change, and
+/// removals come before additions at the same position.
+pub fn diff<'a>(old: &'a str, new: &'a str) -> Vec<Change<'a>> {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let (n, m) = (old_tokens.len(), new_tokens.len());
+
+    // Longest common subsequence lengths of the token suffixes
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if old_tokens[i] == new_tokens[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes: Vec<Change<'a>> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        let change = if i < n && j < m && old_tokens[i] == new_tokens[j] {
+            i += 1;
+            j += 1;
+            Change::Equal(old_tokens[i - 1])
+        } else if i < n && (j == m || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            i += 1;
+            Change::Removed(old_tokens[i - 1])
+        } else {
+            j += 1;
+            Change::Added(new_tokens[j - 1])
+        };
+        push(&mut changes, change, old, new);
+    }
+    changes
+}
+
+/// Render the changes from `old` to `new` on one string.
+///
+/// With `color`, removed text is red and struck through and added text is
+/// green. Without it, git's plain word diff markers are used:
+/// `[-removed-]{+added+}`.
+pub fn render(old: &str, new: &str, color: bool) -> String {
+    diff(old, new)
+        .into_iter()
+        .map(|change| match change {
+            Change::Equal(text) => text.to_string(),
+            Change::Removed(text) if color => style(text).red().strikethrough().to_string(),
+            Change::Added(text) if color => style(text).green().underlined().to_string(),
+            Change::Removed(text) => format!("[-{}-]", text),
+            Change::Added(text) => format!("{{+{}+}}", text),
+        })
+        .collect()
+}
+
+/// Whether the two messages differ at all.
+pub fn has_changes(old: &str, new: &str) -> bool {
+    diff(old, new)
+        .iter()
+        .any(|change| !matches!(change, Change::Equal(_)))
+}
+
+/// Append `change`, merging it into the previous change of the same kind.
+///
+/// Tokens are consecutive slices of their message, so a merged change is
+/// the slice spanning both.
+fn push<'a>(changes: &mut Vec<Change<'a>>, change: Change<'a>, old: &'a str, new: &'a str) {
+    let merged = match (changes.last(), &change) {
+        (Some(Change::Equal(a)), Change::Equal(b)) => Some(Change::Equal(join(old, a, b))),
+        (Some(Change::Removed(a)), Change::Removed(b)) => Some(Change::Removed(join(old, a, b))),
+        (Some(Change::Added(a)), Change::Added(b)) => Some(Change::Added(join(new, a, b))),
+        _ => None,
+    };
+    match merged {
+        Some(merged) => *changes.last_mut().expect("checked above") = merged,
+        None => changes.push(change),
+    }
+}
+
+/// The slice of `text` from the start of `a` to the end of `b`, where `b`
+/// directly follows `a`. Equal text is always sliced from the old message.
+fn join<'a>(text: &'a str, a: &'a str, b: &'a str) -> &'a str {
+    let base = text.as_ptr() as usize;
+    let start = a.as_ptr() as usize - base;
+    let end = b.as_ptr() as usize - base + b.len();
+    &text[start..end]
+}
+
+/// Split `text` into words, whitespace runs and punctuation characters.
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut current: Option<Class> = None;
+
+    for (index, c) in text.char_indices() {
+        let class = Class::of(c);
+        if current != Some(class) || class == Class::Punctuation {
+            if index > start {
+                tokens.push(&text[start..index]);
+            }
+            start = index;
+            current = Some(class);
+        }
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Word,
+    Space,
+    Punctuation,
+}
+
+impl Class {
+    fn of(c: char) -> Self {
+        if c.is_alphanumeric() || c == '_' || c == '\'' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Punctuation
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("fix(api): Added  it."),
+            vec!["fix", "(", "api", ")", ":", " ", "Added", "  ", "it", "."]
+        );
+    }
+
+    #[test]
+    fn test_diff_subject_fix() {
+        assert_eq!(
+            diff("fix: Added retries.", "fix: add retries"),
+            vec![
+                Change::Equal("fix: "),
+                Change::Removed("Added"),
+                Change::Added("add"),
+                Change::Equal(" retries"),
+                Change::Removed("."),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_plain() {
+        assert_eq!(
+            render(
+                "feat: add export\n\nBody text",
+                "feat(cli): add export\n\nBody text",
+                false
+            ),
+            "feat{+(cli)+}: add export\n\nBody text"
+        );
+        assert_eq!(render("same", "same", false), "same");
+        assert!(!has_changes("same", "same"));
+        assert!(has_changes("a b", "a c"));
+    }
+}
@@ -0,0 +1,205 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Commit types: the conventional built-ins and types defined in config.
+//!
+//! Types listed under `[[types]]` in `ck.toml` belong to that configuration:
+//! messages using them parse against it (see
+//! [`CommitMessage::parse_with`](crate::commit::CommitMessage::parse_with)),
+//! so two configurations in one process never see each other's types. A
+//! definition named after a built-in only changes its description and emoji.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A commit type defined in configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct TypeDefinition {
+    /// Type name as written in the header, e.g. `deps`.
+    pub name: String,
+
+    /// Description shown in the type picker.
+    pub description: String,
+
+    /// Emoji shown next to the type when `ui.emoji` is on.
+    pub emoji: Option<String>,
+}
+
+impl TypeDefinition {
+    /// The type this definition describes, or `None` if its name is not a
+    /// valid type name.
+    pub fn commit_type(&self) -> Option<CommitType> {
+        CommitType::from_name(&self.name)
+    }
+}
+
+/// Commit type definition.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CommitType {
+    Feat,
+    Fix,
+    Docs,
+    Style,
+    Refactor,
+    Perf,
+    Test,
+    Chore,
+    Revert,
+    Build,
+    Ci,
+    Wip,
+    /// Any other type, such as one defined in configuration.
+    Custom(String),
+}
+
+impl CommitType {
+    /// The type named `name`: a built-in (or one of its aliases), or else
+    /// a custom type if `name` is a single word of letters, digits and
+    /// underscores.
+    pub fn from_name(name: &str) -> Option<CommitType> {
+        if let Ok(builtin) = name.parse() {
+            return Some(builtin);
+        }
+        let name = name.trim().to_lowercase();
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        valid.then_some(CommitType::Custom(name))
+    }
+
+    /// Get the string representation of the commit type.
+    pub fn as_str(&self) -> &str {
+        match self {
+            CommitType::Feat => "feat",
+            CommitType::Fix => "fix",
+            CommitType::Docs => "docs",
+            CommitType::Style => "style",
+            CommitType::Refactor => "refactor",
+            CommitType::Perf => "perf",
+            CommitType::Test => "test",
+            CommitType::Chore => "chore",
+            CommitType::Revert => "revert",
+            CommitType::Build => "build",
+            CommitType::Ci => "ci",
+            CommitType::Wip => "wip",
+            CommitType::Custom(name) => name,
+        }
+    }
+
+    /// Get a description of the built-in commit type.
+    ///
+    /// Descriptions of custom types, and overrides of the built-in ones,
+    /// come from [`CkConfig::type_description`](super::CkConfig::type_description).
+    pub fn description(&self) -> &'static str {
+        match self {
+            CommitType::Feat => "A new feature",
+            CommitType::Fix => "A bug fix",
+            CommitType::Docs => "Documentation only changes",
+            CommitType::Style => "Code style changes (formatting, whitespace)",
+            CommitType::Refactor => "Code refactoring (no feature/fix)",
+            CommitType::Perf => "Performance improvements",
+            CommitType::Test => "Adding or updating tests",
+            CommitType::Chore => "Build process or auxiliary tool changes",
+            CommitType::Revert => "Reverting a previous commit",
+            CommitType::Build => "Build system or dependency changes",
+            CommitType::Ci => "CI configuration changes",
+            CommitType::Wip => "Work in progress",
+            CommitType::Custom(_) => "",
+        }
+    }
+
+    /// Check if the type is defined in configuration rather than built in.
+    pub fn is_custom(&self) -> bool {
+        matches!(self, CommitType::Custom(_))
+    }
+
+    /// Get all built-in commit types.
+    pub fn all() -> &'static [CommitType] {
+        &[
+            CommitType::Feat,
+            CommitType::Fix,
+            CommitType::Docs,
+            CommitType::Style,
+            CommitType::Refactor,
+            CommitType::Perf,
+            CommitType::Test,
+            CommitType::Chore,
+            CommitType::Revert,
+            CommitType::Build,
+            CommitType::Ci,
+            CommitType::Wip,
+        ]
+    }
+}
+
+impl std::str::FromStr for CommitType {
+    type Err = ();
+
+    /// Parse a built-in type; see [`CommitType::from_name`] for custom ones.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        match s.as_str() {
+            "feat" | "feature" => Ok(CommitType::Feat),
+            "fix" | "bugfix" => Ok(CommitType::Fix),
+            "docs" | "doc" => Ok(CommitType::Docs),
+            "style" => Ok(CommitType::Style),
+            "refactor" => Ok(CommitType::Refactor),
+            "perf" | "performance" => Ok(CommitType::Perf),
+            "test" | "tests" => Ok(CommitType::Test),
+            "chore" => Ok(CommitType::Chore),
+            "revert" => Ok(CommitType::Revert),
+            "build" => Ok(CommitType::Build),
+            "ci" => Ok(CommitType::Ci),
+            "wip" => Ok(CommitType::Wip),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for CommitType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for CommitType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for CommitType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        CommitType::from_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid commit type '{}'", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_type_from_str() {
+        assert_eq!("feat".parse::<CommitType>(), Ok(CommitType::Feat));
+        assert_eq!("FIX".parse::<CommitType>(), Ok(CommitType::Fix));
+        assert!("unknown".parse::<CommitType>().is_err());
+    }
+
+    #[test]
+    fn test_commit_type_display() {
+        assert_eq!(CommitType::Feat.to_string(), "feat");
+        assert_eq!(CommitType::Refactor.to_string(), "refactor");
+    }
+
+    #[test]
+    fn test_custom_types() {
+        let infra = CommitType::from_name("Infra").unwrap();
+        assert_eq!(infra, CommitType::Custom("infra".to_string()));
+        assert!(infra.is_custom());
+        assert_eq!(infra.to_string(), "infra");
+        assert_eq!(infra.description(), "");
+        assert_eq!(CommitType::from_name("feature"), Some(CommitType::Feat));
+        assert_eq!(CommitType::from_name("not valid"), None);
+        assert!("infra".parse::<CommitType>().is_err());
+    }
+}
@@ -24,11 +24,24 @@ require_scope = true
 require_body = false
 allowed_types = ["feat", "fix", "docs", "style", "refactor", "perf", "test", "chore", "revert", "build", "ci"]
 forbidden_types = ["wip"]
+# Types in old messages that `ck fix --auto` rewrites
+type_aliases = { bug = "fix", misc = "chore" }
+# Trailers every commit must have; the builder prompts for missing ones
+# required_trailers = ["Signed-off-by"]
+# Rules a commit may override with a justified `Ck-Disable:` footer
+suppressible = ["subject-max-length"]
 
 # Scope configuration
 [rules.scope]
 require = true
 allowed = ["core", "cli", "config", "git", "rules", "hooks"]
+# Map inferred scopes onto allowed ones
+aliases = { commands = "cli", schema = "config" }
+# Naming convention for scopes; inferred scopes are converted to it
+# kebab-case, snake_case, camelCase, PascalCase or lowercase
+case = "kebab-case"
+# pattern = "^[a-z][a-z0-9-]*$"
+max_length = 20
 
 # Path-based rules
 [rules.paths]
@@ -43,16 +56,71 @@ allowed = ["core", "cli", "config", "git", "rules", "hooks"]
 "release/*" = { forbid = ["wip"], require_signed = true }
 "feature/*" = { allow = ["wip"] }
 
+# Body sections required for specific types
+[rules.body_sections]
+fix = [{ header = "Root cause:" }, { header = "Testing:" }]
+perf = [
+    { header = "Before:", pattern = "(?m)^Before:.*\\d" },
+    { header = "After:", pattern = "(?m)^After:.*\\d" },
+]
+
+# Tickets taken from branch names such as feature/JIRA-1234-login
+[rules.ticket]
+branch_patterns = ['(?:^|/)([A-Z][A-Z0-9]+-[0-9]+)\b']
+# Only count keys of these projects; any key when empty
+prefixes = []
+# Where a ticket from the branch goes: footer, subject or none
+placement = "footer"
+token = "Refs"
+subject_format = "{ticket} {subject}"
+# Fail messages that reference no ticket
+required = false
+
+# Language subjects are written in: an ISO 639-3 code such as "eng", or
+# "history" for the language of recent commits
+[rules.language]
+# require = "eng"
+# Shorter subjects, not counting identifiers, are not checked
+min_words = 4
+
+# Rules written as CEL expressions over the commit and its diff
+[rules.expressions.perf-needs-benchmark]
+expr = 'commit.type != "perf" || diff.files.exists(f, f.path.startsWith("benches/"))'
+message = "perf commits must update a benchmark"
+
 # CI-specific rules
 [rules.ci]
 strict = true
 fail_on_warning = true
+# Warnings that become errors only in CI
+promote = ["subject-imperative"]
+
+# Merge commits in range checks
+[rules.merge]
+# Accept git's generated "Merge branch ..." subjects
+allow_default_message = true
+# Check other merge subjects like any other commit
+require_conventional = true
+# Reject merges whose first parent is not the previous branch tip
+forbid_foxtrot = false
+
+# How the merge queue lands pull requests, checked by ck merge-check
+[rules.queue]
+# squash, merge or rebase
+strategy = "squash"
+# Squash subject and body: title, commits or first-commit
+squash_message = "title"
+# The platform appends " (#123)" to squashed subjects
+append_number = true
+merge_subject = "Merge pull request #{number} from {branch}"
 
 # Monorepo configuration
 [monorepo]
 enabled = true
 package_markers = ["Cargo.toml", "package.json", "go.mod"]
 root_scope = "root"
+# Use Bazel or Buck BUILD files instead of markers; target names become scopes
+# build_system = "bazel"
 
 [[monorepo.packages]]
 path = "crates/core"
@@ -79,6 +147,19 @@ pattern = "(?i)(api[_-]?key|apikey)\\s*[:=]\\s*['\"]?[a-zA-Z0-9]{16,}['\"]?"
 name = "Private Key"
 pattern = "-----BEGIN (RSA|DSA|EC|OPENSSH|PGP) PRIVATE KEY-----"
 
+# Files flagged as sensitive when staged (globs, case-insensitive)
+[security.risky_paths]
+deny = ["*secret*", "*password*", "*key*", "*credential*", "*.env*", "*id_rsa*", "*id_ed25519*"]
+allow = ["tests/fixtures/**", "*.env.example"]
+
+# Secret matches known to be safe: file globs, pattern names and the
+# fingerprints the scanner reports. A `ck:ignore-secret` comment on the
+# line suppresses a match too.
+[security.allowlist]
+paths = []
+patterns = []
+fingerprints = []
+
 # Hook configuration
 [hooks]
 enabled = true
@@ -97,13 +178,85 @@ enabled = true
 enabled = false
 directory = ".ck/plugins"
 enabled_plugins = []
+# Permissions plugins may declare: read_config, env, ...
+permissions = ["read_config"]
+# Require installed plugins to be signed with this minisign key
+# minisign_key = "RW..."
+
+# Commit size warnings
+[analysis]
+max_commit_lines = 500
+max_file_lines = 300
+max_files = 50
+
+# Per-path overrides; the first matching glob applies
+[analysis.paths]
+"**/*.lock" = { exclude_from_totals = true }
+"migrations/**" = { max_file_lines = 2000 }
+
+# Context warnings: hide some, raise others to errors
+[warnings]
+disable = []
+promote = ["binary-files"]
+
+# Generated message style
+[smart]
+# "concise" (subject only) or "detailed" (subject and bullet list)
+tone = "detailed"
+# max_subject_words = 8
+forbid_words = []
+# subject_template = "{verb} {target} in {scope}"
+# Mark generated messages with a Generated-by trailer for auditing
+provenance = false
 
 # UI configuration
 [ui]
 color = true
 emoji = true
+# "gitmoji" writes the type's gitmoji before the subject: feat: ✨ add export
+emoji_style = "none"
 hints = true
 theme = "default"
+# Avoid echoing file paths in previews, warnings and generated messages
+redact_paths = false
+# Editor for commit bodies; defaults to $GIT_EDITOR, $VISUAL, $EDITOR, core.editor
+# editor = "code --wait"
+
+[ui.coaching]
+# Gentle hints about commit habits in the interactive builder
+enabled = false
+# Hint when the last commit is older than this and the diff is large
+stale_hours = 8
+large_diff_lines = 500
+# Hint when this many small chore commits pile up
+chore_streak = 5
+small_commit_lines = 20
+
+# How ck talks to git
+[git]
+# Stage and commit with the git binary instead of libgit2, for setups that
+# rely on filters, hooks or fsmonitor; reads still use libgit2
+use_cli = false
+
+# Project-specific commit types, allowed alongside rules.allowed_types
+[[types]]
+name = "deps"
+description = "Dependency updates"
+emoji = "📦"
+
+# Message templates for `ck commit --template <name>`; {type}, {scope},
+# {ticket} and {branch} are filled in. Lines ending in ":" are sections,
+# which must be filled in when enforce is set
+[templates.change]
+body = """
+Why:
+
+What:
+
+Testing:
+"""
+footer = "Refs: {ticket}"
+enforce = true
 "#
 }
 
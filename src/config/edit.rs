@@ -0,0 +1,190 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! In-place edits to configuration files.
+//!
+//! Edits keep the file's comments, ordering and formatting, so a setting
+//! saved from a prompt doesn't reformat a hand-written `ck.toml`.
+
+use crate::error::{CkError, ConfigError, Result};
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, Item, Table};
+
+/// The configuration file edits are saved to: the one in use, or a new
+/// `ck.toml` at the repository root.
+pub fn config_file_for_edit() -> PathBuf {
+    super::find_config_file().unwrap_or_else(|| {
+        let root = crate::git::open_repo()
+            .map(|repo| repo.workdir().to_path_buf())
+            .unwrap_or_else(|_| PathBuf::from("."));
+        root.join("ck.toml")
+    })
+}
+
+/// Add `code` to `warnings.disable` in the file at `path`, creating the
+/// file or section if needed.
+pub fn disable_warning(path: &Path, code: &str) -> Result<()> {
+    let content = if path.exists() {
+        std::fs::read_to_string(path)?
+    } else {
+        String::new()
+    };
+    let updated = add_to_array(&content, "warnings", "disable", code)?;
+    std::fs::write(path, updated)?;
+    Ok(())
+}
+
+/// Add `name` to `plugins.enabled_plugins` and turn plugins on in the
+/// file at `path`, creating the file or section if needed.
+pub fn enable_plugin(path: &Path, name: &str) -> Result<()> {
+    let content = read(path)?;
+    let mut updated = set_bool(&content, "plugins", "enabled", true)?;
+    updated = add_to_array(&updated, "plugins", "enabled_plugins", name)?;
+    std::fs::write(path, updated)?;
+    Ok(())
+}
+
+/// Remove `name` from `plugins.enabled_plugins` in the file at `path`.
+///
+/// Returns whether the file listed it.
+pub fn disable_plugin(path: &Path, name: &str) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let content = read(path)?;
+    let updated = remove_from_array(&content, "plugins", "enabled_plugins", name)?;
+    let changed = updated != content;
+    if changed {
+        std::fs::write(path, updated)?;
+    }
+    Ok(changed)
+}
+
+fn read(path: &Path) -> Result<String> {
+    if path.exists() {
+        Ok(std::fs::read_to_string(path)?)
+    } else {
+        Ok(String::new())
+    }
+}
+
+fn parse(content: &str) -> Result<DocumentMut> {
+    content.parse().map_err(|e: toml_edit::TomlError| {
+        CkError::Config(ConfigError::ParseError {
+            message: e.to_string(),
+        })
+    })
+}
+
+/// Get the table `section`, creating it if needed.
+fn section_mut<'a>(
+    doc: &'a mut DocumentMut,
+    section: &str,
+) -> Result<&'a mut dyn toml_edit::TableLike> {
+    doc.entry(section)
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_like_mut()
+        .ok_or_else(|| not_a(section, "table"))
+}
+
+/// Append `value` to the string array `section.key` unless present.
+fn add_to_array(content: &str, section: &str, key: &str, value: &str) -> Result<String> {
+    let mut doc = parse(content)?;
+    let array = section_mut(&mut doc, section)?
+        .entry(key)
+        .or_insert(toml_edit::value(Array::new()))
+        .as_array_mut()
+        .ok_or_else(|| not_a(&format!("{}.{}", section, key), "array"))?;
+
+    if !array.iter().any(|item| item.as_str() == Some(value)) {
+        array.push(value);
+    }
+    Ok(doc.to_string())
+}
+
+/// Remove `value` from the string array `section.key`, if present.
+fn remove_from_array(content: &str, section: &str, key: &str, value: &str) -> Result<String> {
+    let mut doc = parse(content)?;
+    let array = doc
+        .get_mut(section)
+        .and_then(Item::as_table_like_mut)
+        .and_then(|table| table.get_mut(key))
+        .and_then(Item::as_array_mut);
+    match array {
+        Some(array) => {
+            array.retain(|item| item.as_str() != Some(value));
+            Ok(doc.to_string())
+        }
+        None => Ok(content.to_string()),
+    }
+}
+
+/// Set `section.key` to a boolean.
+fn set_bool(content: &str, section: &str, key: &str, value: bool) -> Result<String> {
+    let mut doc = parse(content)?;
+    let table = section_mut(&mut doc, section)?;
+    match table.get_mut(key).map(Item::as_value_mut) {
+        Some(Some(current)) => {
+            // Keep the comment after the old value
+            let decor = current.decor().clone();
+            *current = value.into();
+            *current.decor_mut() = decor;
+        }
+        Some(None) => return Err(not_a(&format!("{}.{}", section, key), "boolean")),
+        None => {
+            table.insert(key, toml_edit::value(value));
+        }
+    }
+    Ok(doc.to_string())
+}
+
+fn not_a(key: &str, what: &str) -> CkError {
+    CkError::Config(ConfigError::ParseError {
+        message: format!("`{}` is not a {}", key, what),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_to_array_keeps_comments() {
+        let content = "# Team settings\n[rules]\nmax_subject_length = 72 # hard limit\n";
+        let updated = add_to_array(content, "warnings", "disable", "mixed-concerns").unwrap();
+        assert!(updated.starts_with(content));
+        assert!(updated.contains("[warnings]\ndisable = [\"mixed-concerns\"]"));
+
+        let again = add_to_array(&updated, "warnings", "disable", "mixed-concerns").unwrap();
+        assert_eq!(again, updated);
+        let more = add_to_array(&updated, "warnings", "disable", "large-file").unwrap();
+        assert!(more.contains("disable = [\"mixed-concerns\", \"large-file\"]"));
+    }
+
+    #[test]
+    fn test_enable_and_disable_plugin() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("ck.toml");
+        std::fs::write(
+            &path,
+            "# Plugins\n[plugins]\nenabled = false # off for now\n",
+        )
+        .unwrap();
+
+        enable_plugin(&path, "jira").unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# Plugins\n[plugins]\nenabled = true # off for now\n"));
+        assert!(content.contains("enabled_plugins = [\"jira\"]"));
+
+        assert!(disable_plugin(&path, "jira").unwrap());
+        assert!(!disable_plugin(&path, "jira").unwrap());
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("enabled_plugins = []"));
+    }
+
+    #[test]
+    fn test_add_to_array_rejects_wrong_type() {
+        assert!(add_to_array("warnings = 1\n", "warnings", "disable", "x").is_err());
+        assert!(add_to_array("[warnings]\ndisable = 1\n", "warnings", "disable", "x").is_err());
+    }
+}
@@ -0,0 +1,321 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! commitlint configuration importer.
+//!
+//! Reads `.commitlintrc` in JSON or YAML, or the `commitlint` key of
+//! `package.json`, and translates its rules into `ck.toml`. A rule is
+//! `[level, "always" | "never", value]` with level 0 (off), 1 (warning)
+//! or 2 (error). JavaScript configs can't be evaluated; they have to be
+//! converted to JSON first.
+
+use crate::error::{CkError, ConfigError, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use toml_edit::{value, Array, DocumentMut, Item, Table};
+
+use super::Imported;
+
+/// commitlint configuration files, in commitlint's order of precedence.
+pub const CONFIG_FILES: &[&str] = &[
+    ".commitlintrc",
+    ".commitlintrc.json",
+    ".commitlintrc.yaml",
+    ".commitlintrc.yml",
+    ".commitlintrc.js",
+    ".commitlintrc.cjs",
+    ".commitlintrc.mjs",
+    ".commitlintrc.ts",
+    "commitlint.config.js",
+    "commitlint.config.cjs",
+    "commitlint.config.mjs",
+    "commitlint.config.ts",
+    "package.json",
+];
+
+/// Types allowed by `@commitlint/config-conventional`.
+const CONVENTIONAL_TYPES: &[&str] = &[
+    "build", "chore", "ci", "docs", "feat", "fix", "perf", "refactor", "revert", "style", "test",
+];
+
+/// Rules ck enforces with its built-in checks when configured as commitlint
+/// usually configures them.
+const BUILT_IN: &[&str] = &[
+    "type-empty",
+    "subject-empty",
+    "subject-full-stop",
+    "subject-case",
+    "type-case",
+];
+
+/// Find the commitlint configuration in `dir`.
+///
+/// `package.json` counts only when it has a `commitlint` key.
+pub fn find_config(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|path| path.is_file())
+        .find(|path| {
+            path.file_name() != Some("package.json".as_ref())
+                || std::fs::read_to_string(path)
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+                    .is_some_and(|json| json.get("commitlint").is_some())
+        })
+}
+
+/// Translate the commitlint configuration at `path`.
+pub fn import_file(path: &Path) -> Result<Imported> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if [".js", ".cjs", ".mjs", ".ts"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+    {
+        return Err(import_error(format!(
+            "{} is JavaScript and can't be read; move its settings to .commitlintrc.json",
+            name
+        )));
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    let config = if name == "package.json" {
+        let package: Value =
+            serde_json::from_str(&content).map_err(|e| import_error(format!("{}: {}", name, e)))?;
+        package
+            .get("commitlint")
+            .cloned()
+            .ok_or_else(|| import_error("package.json has no commitlint key".to_string()))?
+    } else {
+        parse(&content).map_err(|e| import_error(format!("{}: {}", name, e)))?
+    };
+    Ok(translate(&config, &name))
+}
+
+/// Parse JSON or YAML; JSON documents are valid YAML.
+fn parse(content: &str) -> std::result::Result<Value, serde_yaml::Error> {
+    serde_yaml::from_str(content)
+}
+
+/// Translate a parsed commitlint configuration read from `source`.
+pub fn translate(config: &Value, source: &str) -> Imported {
+    let mut doc = DocumentMut::new();
+    let mut rules = Table::new();
+    let mut scope = Table::new();
+    let mut notes = Vec::new();
+
+    let extends: Vec<&str> = match config.get("extends") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    for name in extends {
+        if name.ends_with("config-conventional") {
+            rules["allowed_types"] = value(string_array(CONVENTIONAL_TYPES.iter().copied()));
+        } else {
+            notes.push(format!(
+                "extends {}: shared configs other than config-conventional are not imported",
+                name
+            ));
+        }
+    }
+
+    let empty = serde_json::Map::new();
+    let entries = config
+        .get("rules")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    for (name, rule) in entries {
+        let rule = match Rule::parse(rule) {
+            Some(rule) => rule,
+            None => {
+                notes.push(format!("{}: not a [level, when, value] rule", name));
+                continue;
+            }
+        };
+        if rule.level == 0 {
+            continue;
+        }
+        if rule.level == 1 && !BUILT_IN.contains(&name.as_str()) {
+            notes.push(format!("{}: a warning in commitlint, an error in ck", name));
+        }
+
+        let always = rule.always;
+        match (name.as_str(), &rule.value) {
+            ("type-enum", Some(Value::Array(types))) => {
+                let key = if always {
+                    "allowed_types"
+                } else {
+                    "forbidden_types"
+                };
+                rules[key] = value(string_array(types.iter().filter_map(Value::as_str)));
+            }
+            ("scope-enum", Some(Value::Array(scopes))) if !scopes.is_empty() => {
+                let key = if always { "allowed" } else { "forbidden" };
+                scope[key] = value(string_array(scopes.iter().filter_map(Value::as_str)));
+            }
+            ("scope-empty", _) if !always => rules["require_scope"] = value(true),
+            ("body-empty", _) if !always => rules["require_body"] = value(true),
+            ("subject-max-length", Some(Value::Number(n))) if always => {
+                if let Some(n) = n.as_i64() {
+                    rules["max_subject_length"] = value(n);
+                }
+            }
+            ("subject-min-length", Some(Value::Number(n))) if always => {
+                if let Some(n) = n.as_i64() {
+                    rules["min_subject_length"] = value(n);
+                }
+            }
+            ("header-max-length", Some(Value::Number(n))) if always => {
+                // ck limits the subject, which is shorter than the header
+                if !entries.contains_key("subject-max-length") {
+                    if let Some(n) = n.as_i64() {
+                        rules["max_subject_length"] = value(n);
+                    }
+                }
+                notes.push(format!(
+                    "{}: ck limits the subject, not the whole header",
+                    name
+                ));
+            }
+            (name, _) if BUILT_IN.contains(&name) => {}
+            (name, _) => notes.push(format!("{}: no ck equivalent", name)),
+        }
+    }
+
+    if !scope.is_empty() {
+        rules["scope"] = Item::Table(scope);
+    }
+    doc["rules"] = Item::Table(rules);
+
+    let mut toml = format!(
+        "# CK Configuration\n# Imported from {} by `ck init --from-commitlint`\n\n{}",
+        source, doc
+    );
+    if !notes.is_empty() {
+        toml.push_str("\n# Not imported exactly:\n");
+        for note in &notes {
+            toml.push_str(&format!("# - {}\n", note));
+        }
+    }
+    Imported { toml, notes }
+}
+
+/// A commitlint rule: `[level, when, value]`.
+struct Rule {
+    level: u64,
+    always: bool,
+    value: Option<Value>,
+}
+
+impl Rule {
+    fn parse(rule: &Value) -> Option<Self> {
+        let parts = rule.as_array()?;
+        let level = parts.first()?.as_u64()?;
+        let always = match parts.get(1).map(Value::as_str) {
+            None | Some(Some("always")) => true,
+            Some(Some("never")) => false,
+            Some(_) => return None,
+        };
+        Some(Self {
+            level,
+            always,
+            value: parts.get(2).cloned(),
+        })
+    }
+}
+
+fn string_array<'a>(items: impl Iterator<Item = &'a str>) -> Array {
+    items.collect()
+}
+
+fn import_error(message: String) -> CkError {
+    CkError::Config(ConfigError::ParseError { message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CkConfig;
+
+    fn import(source: &str) -> (CkConfig, Vec<String>) {
+        let imported = translate(&parse(source).unwrap(), ".commitlintrc");
+        let config = toml::from_str(&imported.toml).unwrap();
+        (config, imported.notes)
+    }
+
+    #[test]
+    fn test_translate_json() {
+        let (config, notes) = import(
+            r#"{
+                "extends": ["@commitlint/config-conventional"],
+                "rules": {
+                    "type-enum": [2, "always", ["feat", "fix", "docs"]],
+                    "scope-enum": [2, "always", ["api", "cli"]],
+                    "scope-empty": [2, "never"],
+                    "subject-max-length": [2, "always", 60],
+                    "subject-case": [2, "never", ["upper-case"]],
+                    "body-max-line-length": [2, "always", 100],
+                    "footer-leading-blank": [0]
+                }
+            }"#,
+        );
+        assert_eq!(config.rules.allowed_types, vec!["feat", "fix", "docs"]);
+        assert_eq!(config.rules.scope.allowed, vec!["api", "cli"]);
+        assert!(config.rules.require_scope);
+        assert_eq!(config.rules.max_subject_length, 60);
+        assert_eq!(notes, vec!["body-max-line-length: no ck equivalent"]);
+    }
+
+    #[test]
+    fn test_translate_yaml() {
+        let (config, notes) = import(
+            "extends: '@commitlint/config-conventional'\n\
+             rules:\n  \
+               type-enum: [2, never, [wip]]\n  \
+               header-max-length: [1, always, 80]\n  \
+               body-empty: [2, never]\n",
+        );
+        assert_eq!(config.rules.allowed_types.len(), CONVENTIONAL_TYPES.len());
+        assert_eq!(config.rules.forbidden_types, vec!["wip"]);
+        assert_eq!(config.rules.max_subject_length, 80);
+        assert!(config.rules.require_body);
+        assert_eq!(
+            notes,
+            vec![
+                "header-max-length: a warning in commitlint, an error in ck",
+                "header-max-length: ck limits the subject, not the whole header",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("package.json"), r#"{"name": "app"}"#).unwrap();
+        assert_eq!(find_config(dir.path()), None);
+
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"commitlint": {"rules": {"subject-min-length": [2, "always", 5]}}}"#,
+        )
+        .unwrap();
+        let path = find_config(dir.path()).unwrap();
+        let imported = import_file(&path).unwrap();
+        assert!(imported.toml.contains("min_subject_length = 5"));
+
+        std::fs::write(
+            dir.path().join("commitlint.config.js"),
+            "module.exports = {}",
+        )
+        .unwrap();
+        let path = find_config(dir.path()).unwrap();
+        assert!(import_file(&path)
+            .unwrap_err()
+            .to_string()
+            .contains("move its settings to .commitlintrc.json"));
+    }
+}
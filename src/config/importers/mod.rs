@@ -0,0 +1,19 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Importers for other tools' commit conventions.
+//!
+//! Each importer reads another tool's configuration and writes the closest
+//! `ck.toml`, listing what it couldn't translate so nothing is dropped
+//! silently.
+
+pub mod commitlint;
+
+/// Configuration translated from another tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Imported {
+    /// Contents for `ck.toml`.
+    pub toml: String,
+    /// Rules that were not translated exactly, with the reason.
+    pub notes: Vec<String>,
+}
@@ -6,6 +6,7 @@
 use crate::error::{CkError, ConfigError, Result};
 use std::path::{Path, PathBuf};
 
+use super::remote::resolve_policy;
 use super::schema::CkConfig;
 
 /// Configuration file names to search for, in order of priority.
@@ -83,7 +84,120 @@ pub fn load_config_from(path: &Path) -> Result<CkConfig> {
         })
     })?;
 
-    parse_config(&content)
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let config = parse_config_with_extends(&content, base_dir)?;
+    for definition in &config.types {
+        if definition.commit_type().is_none() {
+            tracing::warn!(
+                "Ignoring commit type with invalid name '{}'",
+                definition.name
+            );
+        }
+    }
+    Ok(config)
+}
+
+/// Parse configuration, layering it over the policy it `extends`.
+///
+/// The extended policy acts as the base and local keys override it
+/// table by table. Only one level of `extends` is followed.
+pub fn parse_config_with_extends(content: &str, base_dir: &Path) -> Result<CkConfig> {
+    let local = parse_config(content)?;
+
+    let source = match local.extends {
+        Some(ref source) => source,
+        None => return Ok(local),
+    };
+
+    tracing::debug!("Configuration extends policy: {}", source);
+    let policy_content = resolve_policy(source, base_dir, &local.policy)?;
+
+    let mut base = parse_value(&policy_content)?;
+    if let toml::Value::Table(ref mut table) = base {
+        table.remove("extends");
+    }
+    let merged = merge_values(base, parse_value(content)?);
+
+    merged.try_into().map_err(|e: toml::de::Error| {
+        CkError::Config(ConfigError::MergeError {
+            message: format!("Failed to apply extended policy: {}", e),
+        })
+    })
+}
+
+/// The raw layers a configuration was built from, lowest precedence first.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    layers: Vec<(String, toml::Value)>,
+}
+
+impl ConfigSources {
+    /// Get the name of the layer that set a dotted key such as
+    /// `rules.scope.allowed`, or `"default"` if no layer set it.
+    pub fn source_of(&self, key: &str) -> &str {
+        self.layers
+            .iter()
+            .rev()
+            .find(|(_, value)| {
+                key.split('.')
+                    .try_fold(value, |v, part| v.get(part))
+                    .is_some()
+            })
+            .map(|(name, _)| name.as_str())
+            .unwrap_or("default")
+    }
+}
+
+/// Collect the layers of the configuration at `path`, or of the
+/// configuration found in the default locations.
+pub fn load_config_sources(path: Option<&Path>) -> Result<ConfigSources> {
+    let path = match path.map(Path::to_path_buf).or_else(find_config_file) {
+        Some(path) => path,
+        None => return Ok(ConfigSources::default()),
+    };
+
+    let content = std::fs::read_to_string(&path).map_err(|e| {
+        CkError::Config(ConfigError::ParseError {
+            message: format!("Failed to read config file: {}", e),
+        })
+    })?;
+    let local = parse_config(&content)?;
+
+    let mut layers = Vec::new();
+    if let Some(ref source) = local.extends {
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let policy_content = resolve_policy(source, base_dir, &local.policy)?;
+        layers.push((format!("extends:{}", source), parse_value(&policy_content)?));
+    }
+    layers.push((path.display().to_string(), parse_value(&content)?));
+
+    Ok(ConfigSources { layers })
+}
+
+/// Parse a TOML string into an untyped value.
+fn parse_value(content: &str) -> Result<toml::Value> {
+    toml::from_str(content).map_err(|e| {
+        CkError::Config(ConfigError::ParseError {
+            message: format!("Failed to parse TOML: {}", e),
+        })
+    })
+}
+
+/// Deep-merge two TOML values, with the overlay taking precedence.
+fn merge_values(base: toml::Value, overlay: toml::Value) -> toml::Value {
+    match (base, overlay) {
+        (toml::Value::Table(mut base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                let merged = match base.remove(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => value,
+                };
+                base.insert(key, merged);
+            }
+            toml::Value::Table(base)
+        }
+        (_, overlay) => overlay,
+    }
 }
 
 /// Parse configuration from a TOML string.
@@ -101,12 +215,28 @@ pub fn merge_configs(base: CkConfig, overlay: CkConfig) -> CkConfig {
     // take precedence. In a more complete implementation, we'd do field-by-field
     // merging with proper defaults detection.
     CkConfig {
+        extends: overlay.extends.or(base.extends),
+        policy: overlay.policy,
         rules: merge_rules_config(base.rules, overlay.rules),
         monorepo: overlay.monorepo,
         security: overlay.security,
         hooks: overlay.hooks,
         plugins: overlay.plugins,
+        smart: overlay.smart,
+        analysis: overlay.analysis,
+        warnings: overlay.warnings,
+        types: if overlay.types.is_empty() {
+            base.types
+        } else {
+            overlay.types
+        },
+        templates: {
+            let mut merged = base.templates;
+            merged.extend(overlay.templates);
+            merged
+        },
         ui: overlay.ui,
+        git: overlay.git,
     }
 }
 
@@ -137,6 +267,11 @@ fn merge_rules_config(
         } else {
             base.forbidden_types
         },
+        type_aliases: {
+            let mut merged = base.type_aliases;
+            merged.extend(overlay.type_aliases);
+            merged
+        },
         scope: overlay.scope,
         paths: {
             let mut merged = base.paths;
@@ -148,7 +283,31 @@ fn merge_rules_config(
             merged.extend(overlay.branch);
             merged
         },
+        body_sections: {
+            let mut merged = base.body_sections;
+            merged.extend(overlay.body_sections);
+            merged
+        },
+        required_trailers: if !overlay.required_trailers.is_empty() {
+            overlay.required_trailers
+        } else {
+            base.required_trailers
+        },
+        ticket: overlay.ticket,
+        language: overlay.language,
         ci: overlay.ci,
+        merge: overlay.merge,
+        queue: overlay.queue,
+        suppressible: if !overlay.suppressible.is_empty() {
+            overlay.suppressible
+        } else {
+            base.suppressible
+        },
+        expressions: {
+            let mut merged = base.expressions;
+            merged.extend(overlay.expressions);
+            merged
+        },
     }
 }
 
@@ -156,6 +315,33 @@ fn merge_rules_config(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_config_sources() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("base.toml"),
+            "[rules]\nrequire_scope = true\n",
+        )
+        .unwrap();
+        let path = dir.path().join("ck.toml");
+        std::fs::write(
+            &path,
+            "extends = \"base.toml\"\n[rules]\nmax_subject_length = 50\n",
+        )
+        .unwrap();
+
+        let sources = load_config_sources(Some(&path)).unwrap();
+        assert_eq!(
+            sources.source_of("rules.require_scope"),
+            "extends:base.toml"
+        );
+        assert_eq!(
+            sources.source_of("rules.max_subject_length"),
+            path.display().to_string()
+        );
+        assert_eq!(sources.source_of("rules.require_body"), "default");
+    }
+
     #[test]
     fn test_parse_minimal_config() {
         let config = parse_config("").unwrap();
@@ -242,4 +428,32 @@ require_scope = true
         assert_eq!(merged.rules.max_subject_length, 50);
         assert!(merged.rules.require_scope);
     }
+
+    #[test]
+    fn test_extends_local_policy() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("org.toml"),
+            r#"
+[rules]
+max_subject_length = 60
+require_scope = true
+
+[security]
+block_on_secret = false
+"#,
+        )
+        .unwrap();
+
+        let local = r#"
+extends = "org.toml"
+
+[rules]
+max_subject_length = 50
+"#;
+        let config = parse_config_with_extends(local, dir.path()).unwrap();
+        assert_eq!(config.rules.max_subject_length, 50);
+        assert!(config.rules.require_scope);
+        assert!(!config.security.block_on_secret);
+    }
 }
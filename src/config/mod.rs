@@ -6,10 +6,20 @@
 //! This module handles loading, parsing, and merging configuration from
 //! various sources (files, environment variables, defaults).
 
+mod commit_type;
 pub mod default;
+pub mod edit;
+pub mod importers;
 mod loader;
+mod remote;
 mod schema;
+mod scope_case;
 
+pub use commit_type::{CommitType, TypeDefinition};
 pub use default::default_config;
-pub use loader::{find_config_file, load_config, merge_configs};
+pub use loader::{
+    find_config_file, load_config, load_config_sources, merge_configs, ConfigSources,
+};
+pub use remote::is_remote;
 pub use schema::*;
+pub use scope_case::ScopeCase;
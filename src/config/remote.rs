@@ -0,0 +1,472 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Fetching and verifying shared policies referenced by `extends`.
+//!
+//! Remote policies are cached on disk together with their ETag and
+//! signature. Every load re-verifies the cached copy, so editing the
+//! cache by hand does not bypass signature checks.
+
+use crate::error::{CkError, ConfigError, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::schema::PolicyConfig;
+
+/// Resolve an `extends` reference to the policy's TOML content.
+///
+/// `source` is either an `http(s)://` URL or a path relative to the
+/// directory of the configuration file that references it.
+pub fn resolve_policy(source: &str, base_dir: &Path, policy: &PolicyConfig) -> Result<String> {
+    if is_remote(source) {
+        fetch_remote(source, policy)
+    } else {
+        load_local(source, base_dir, policy)
+    }
+}
+
+/// Check if a policy reference points at a remote URL.
+pub fn is_remote(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+/// Load a policy from a local path, verifying its signature if required.
+fn load_local(source: &str, base_dir: &Path, policy: &PolicyConfig) -> Result<String> {
+    let path = base_dir.join(source);
+    verify_files(source, &path, policy)?;
+    fs::read_to_string(&path).map_err(|e| fetch_error(source, format!("{}", e)))
+}
+
+/// Fetch a remote policy, falling back to the cache when offline.
+fn fetch_remote(source: &str, policy: &PolicyConfig) -> Result<String> {
+    let cache = PolicyCache::new(source, policy)?;
+
+    if policy.offline {
+        tracing::debug!("Offline mode, using cached policy for {}", source);
+        return cache.load(source, policy);
+    }
+
+    // A copy cached before a key was configured has no signatures to
+    // verify, so fetch the policy again instead of revalidating it.
+    let etag = if cache.has_signatures(policy) {
+        cache.etag()
+    } else {
+        None
+    };
+
+    match download(source, etag.as_deref()) {
+        Ok(Download::NotModified) => {
+            tracing::debug!("Policy {} not modified, using cache", source);
+            cache.load(source, policy)
+        }
+        Ok(Download::Body { content, etag }) => {
+            let signatures = download_signatures(source, policy)?;
+            cache.store(source, policy, &content, etag.as_deref(), &signatures)?;
+            Ok(content)
+        }
+        Err(message) => {
+            if cache.exists() {
+                tracing::warn!(
+                    "Failed to fetch policy {} ({}), using cached copy",
+                    source,
+                    message
+                );
+                cache.load(source, policy)
+            } else {
+                Err(fetch_error(source, message))
+            }
+        }
+    }
+}
+
+/// Result of an HTTP policy download.
+enum Download {
+    /// The cached copy is still current.
+    NotModified,
+    /// A fresh copy was downloaded.
+    Body {
+        content: String,
+        etag: Option<String>,
+    },
+}
+
+/// Download a URL, revalidating against a known ETag.
+fn download(url: &str, etag: Option<&str>) -> std::result::Result<Download, String> {
+    let mut request = ureq::get(url);
+    if let Some(etag) = etag {
+        request = request.set("If-None-Match", etag);
+    }
+
+    match request.call() {
+        Ok(response) if response.status() == 304 => Ok(Download::NotModified),
+        Ok(response) => {
+            let etag = response.header("ETag").map(|s| s.to_string());
+            let content = response.into_string().map_err(|e| e.to_string())?;
+            Ok(Download::Body { content, etag })
+        }
+        Err(ureq::Error::Status(304, _)) => Ok(Download::NotModified),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Download the detached signatures required by the policy settings.
+fn download_signatures(
+    source: &str,
+    policy: &PolicyConfig,
+) -> Result<Vec<(SignatureKind, String)>> {
+    let mut signatures = Vec::new();
+
+    for kind in SignatureKind::required(policy) {
+        let url = format!("{}{}", source, kind.extension());
+        match download(&url, None) {
+            Ok(Download::Body { content, .. }) => signatures.push((kind, content)),
+            Ok(Download::NotModified) => {}
+            Err(message) => {
+                return Err(CkError::Config(ConfigError::PolicySignature {
+                    source_ref: source.to_string(),
+                    message: format!("Failed to fetch signature {}: {}", url, message),
+                }))
+            }
+        }
+    }
+
+    Ok(signatures)
+}
+
+/// Supported detached signature formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SignatureKind {
+    Minisign,
+    Cosign,
+}
+
+impl SignatureKind {
+    /// Signature kinds required by the policy settings.
+    fn required(policy: &PolicyConfig) -> Vec<SignatureKind> {
+        let mut kinds = Vec::new();
+        if policy.minisign_key.is_some() {
+            kinds.push(SignatureKind::Minisign);
+        }
+        if policy.cosign_key.is_some() {
+            kinds.push(SignatureKind::Cosign);
+        }
+        kinds
+    }
+
+    /// Suffix appended to the policy location to find the signature.
+    fn extension(&self) -> &'static str {
+        match self {
+            SignatureKind::Minisign => ".minisig",
+            SignatureKind::Cosign => ".sig",
+        }
+    }
+}
+
+/// On-disk cache for a single remote policy.
+struct PolicyCache {
+    content_path: PathBuf,
+    etag_path: PathBuf,
+}
+
+impl PolicyCache {
+    /// Create the cache entry for a policy URL.
+    fn new(source: &str, policy: &PolicyConfig) -> Result<Self> {
+        let dir = policy
+            .cache_dir
+            .clone()
+            .or_else(|| dirs::cache_dir().map(|d| d.join("ck").join("policies")))
+            .unwrap_or_else(|| std::env::temp_dir().join("ck-policies"));
+
+        fs::create_dir_all(&dir)
+            .map_err(|e| fetch_error(source, format!("Failed to create policy cache: {}", e)))?;
+
+        let key = cache_key(source);
+        Ok(Self {
+            content_path: dir.join(format!("{}.toml", key)),
+            etag_path: dir.join(format!("{}.etag", key)),
+        })
+    }
+
+    /// Check if a cached copy exists.
+    fn exists(&self) -> bool {
+        self.content_path.exists()
+    }
+
+    /// Check if the cached copy has every signature the policy requires.
+    fn has_signatures(&self, policy: &PolicyConfig) -> bool {
+        SignatureKind::required(policy)
+            .into_iter()
+            .all(|kind| signature_path(&self.content_path, kind).exists())
+    }
+
+    /// Get the ETag of the cached copy, if any.
+    fn etag(&self) -> Option<String> {
+        if !self.exists() {
+            return None;
+        }
+        fs::read_to_string(&self.etag_path)
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    }
+
+    /// Load the cached policy after re-verifying its signatures.
+    fn load(&self, source: &str, policy: &PolicyConfig) -> Result<String> {
+        if !self.exists() {
+            return Err(fetch_error(source, "No cached copy available".to_string()));
+        }
+        verify_files(source, &self.content_path, policy)?;
+        fs::read_to_string(&self.content_path)
+            .map_err(|e| fetch_error(source, format!("Failed to read cache: {}", e)))
+    }
+
+    /// Verify and store a freshly downloaded policy.
+    fn store(
+        &self,
+        source: &str,
+        policy: &PolicyConfig,
+        content: &str,
+        etag: Option<&str>,
+        signatures: &[(SignatureKind, String)],
+    ) -> Result<()> {
+        // Stage the download next to the cache so the signature is checked
+        // before the previous, known-good copy is replaced.
+        let pending = self.content_path.with_extension("toml.pending");
+        let mut staged = vec![pending.clone()];
+        staged.extend(
+            signatures
+                .iter()
+                .map(|(kind, _)| signature_path(&pending, *kind)),
+        );
+        let _cleanup = crate::interrupt::remove_on_interrupt(staged);
+        write_file(source, &pending, content)?;
+        for (kind, signature) in signatures {
+            write_file(source, &signature_path(&pending, *kind), signature)?;
+        }
+
+        if let Err(e) = verify_files(source, &pending, policy) {
+            remove_with_signatures(&pending);
+            return Err(e);
+        }
+
+        for (kind, _) in signatures {
+            rename_file(
+                source,
+                &signature_path(&pending, *kind),
+                &signature_path(&self.content_path, *kind),
+            )?;
+        }
+        rename_file(source, &pending, &self.content_path)?;
+
+        match etag {
+            Some(etag) => write_file(source, &self.etag_path, etag)?,
+            None => {
+                let _ = fs::remove_file(&self.etag_path);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Verify every signature required by the policy settings.
+fn verify_files(source: &str, content_path: &Path, policy: &PolicyConfig) -> Result<()> {
+    for kind in SignatureKind::required(policy) {
+        let sig_path = signature_path(content_path, kind);
+        if !sig_path.exists() {
+            return Err(signature_error(
+                source,
+                format!("Missing {} signature", kind.extension()),
+            ));
+        }
+
+        match kind {
+            SignatureKind::Minisign => {
+                let key = policy.minisign_key.as_deref().unwrap_or_default();
+                verify_minisign(source, content_path, &sig_path, key)?
+            }
+            SignatureKind::Cosign => {
+                let key = policy.cosign_key.as_deref().unwrap_or(Path::new(""));
+                verify_cosign(source, content_path, &sig_path, key)?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Verify a minisign signature.
+fn verify_minisign(source: &str, content_path: &Path, sig_path: &Path, key: &str) -> Result<()> {
+    use minisign_verify::{PublicKey, Signature};
+
+    let public_key = PublicKey::from_base64(key.trim())
+        .map_err(|e| signature_error(source, format!("Invalid minisign key: {}", e)))?;
+    let sig_text = fs::read_to_string(sig_path)
+        .map_err(|e| signature_error(source, format!("Failed to read signature: {}", e)))?;
+    let signature = Signature::decode(&sig_text)
+        .map_err(|e| signature_error(source, format!("Invalid minisign signature: {}", e)))?;
+    let content = fs::read(content_path)
+        .map_err(|e| signature_error(source, format!("Failed to read policy: {}", e)))?;
+
+    public_key
+        .verify(&content, &signature, false)
+        .map_err(|e| signature_error(source, e.to_string()))
+}
+
+/// Verify a cosign signature using the cosign CLI.
+fn verify_cosign(source: &str, content_path: &Path, sig_path: &Path, key: &Path) -> Result<()> {
+    let output = std::process::Command::new("cosign")
+        .arg("verify-blob")
+        .arg("--key")
+        .arg(key)
+        .arg("--signature")
+        .arg(sig_path)
+        .arg(content_path)
+        .output()
+        .map_err(|e| signature_error(source, format!("Failed to run cosign: {}", e)))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(signature_error(
+            source,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// Stable cache file name for a policy URL.
+fn cache_key(source: &str) -> String {
+    let digest = Sha256::digest(source.as_bytes());
+    digest
+        .iter()
+        .take(12)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Path of the detached signature for a policy file.
+fn signature_path(content_path: &Path, kind: SignatureKind) -> PathBuf {
+    let mut path = content_path.as_os_str().to_owned();
+    path.push(kind.extension());
+    PathBuf::from(path)
+}
+
+fn remove_with_signatures(path: &Path) {
+    let _ = fs::remove_file(path);
+    for kind in [SignatureKind::Minisign, SignatureKind::Cosign] {
+        let _ = fs::remove_file(signature_path(path, kind));
+    }
+}
+
+fn write_file(source: &str, path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content)
+        .map_err(|e| fetch_error(source, format!("Failed to write cache: {}", e)))
+}
+
+fn rename_file(source: &str, from: &Path, to: &Path) -> Result<()> {
+    fs::rename(from, to).map_err(|e| fetch_error(source, format!("Failed to update cache: {}", e)))
+}
+
+fn fetch_error(source: &str, message: String) -> CkError {
+    CkError::Config(ConfigError::PolicyFetch {
+        source_ref: source.to_string(),
+        message,
+    })
+}
+
+fn signature_error(source: &str, message: String) -> CkError {
+    CkError::Config(ConfigError::PolicySignature {
+        source_ref: source.to_string(),
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_remote() {
+        assert!(is_remote("https://ck.example.com/policy.toml"));
+        assert!(!is_remote("../policy.toml"));
+    }
+
+    #[test]
+    fn test_cache_key_stable() {
+        let a = cache_key("https://ck.example.com/policy.toml");
+        let b = cache_key("https://ck.example.com/policy.toml");
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 24);
+        assert_ne!(a, cache_key("https://ck.example.com/other.toml"));
+    }
+
+    #[test]
+    fn test_load_local_policy() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("policy.toml"),
+            "[rules]\nrequire_scope = true\n",
+        )
+        .unwrap();
+
+        let content = resolve_policy("policy.toml", dir.path(), &PolicyConfig::default()).unwrap();
+        assert!(content.contains("require_scope"));
+    }
+
+    #[test]
+    fn test_missing_signature_rejected() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("policy.toml"), "[rules]\n").unwrap();
+
+        let policy = PolicyConfig {
+            minisign_key: Some("RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3".into()),
+            ..Default::default()
+        };
+        let result = resolve_policy("policy.toml", dir.path(), &policy);
+        assert!(matches!(
+            result,
+            Err(CkError::Config(ConfigError::PolicySignature { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_cache_without_signatures_not_revalidated() {
+        let dir = TempDir::new().unwrap();
+        let mut policy = PolicyConfig {
+            cache_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let cache = PolicyCache::new("https://ck.example.com/policy.toml", &policy).unwrap();
+        fs::write(&cache.content_path, "[rules]\n").unwrap();
+        assert!(cache.has_signatures(&policy));
+
+        // A key configured after the policy was cached
+        policy.minisign_key =
+            Some("RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3".into());
+        assert!(!cache.has_signatures(&policy));
+
+        fs::write(
+            signature_path(&cache.content_path, SignatureKind::Minisign),
+            "",
+        )
+        .unwrap();
+        assert!(cache.has_signatures(&policy));
+    }
+
+    #[test]
+    fn test_offline_without_cache_fails() {
+        let dir = TempDir::new().unwrap();
+        let policy = PolicyConfig {
+            offline: true,
+            cache_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let result = resolve_policy("https://ck.example.com/policy.toml", dir.path(), &policy);
+        assert!(matches!(
+            result,
+            Err(CkError::Config(ConfigError::PolicyFetch { .. }))
+        ));
+    }
+}
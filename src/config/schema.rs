@@ -5,14 +5,22 @@
 //!
 //! Defines all configuration structures that can be loaded from ck.toml.
 
+use super::{CommitType, TypeDefinition};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 /// The main configuration structure for ck.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
 pub struct CkConfig {
+    /// Shared policy this configuration extends (URL or path).
+    pub extends: Option<String>,
+
+    /// Policy fetch and verification settings.
+    pub policy: PolicyConfig,
+
     /// Rule configuration.
     pub rules: RulesConfig,
 
@@ -28,8 +36,26 @@ pub struct CkConfig {
     /// Plugin configuration.
     pub plugins: PluginsConfig,
 
+    /// Generated message style.
+    pub smart: SmartConfig,
+
+    /// Commit size thresholds.
+    pub analysis: AnalysisConfig,
+
+    /// Which context warnings are shown, and at what level.
+    pub warnings: WarningsConfig,
+
+    /// Commit types beyond the built-ins.
+    pub types: Vec<TypeDefinition>,
+
+    /// Named message templates for `ck commit --template`.
+    pub templates: BTreeMap<String, MessageTemplate>,
+
     /// UI/UX configuration.
     pub ui: UiConfig,
+
+    /// How ck talks to git.
+    pub git: GitConfig,
 }
 
 impl CkConfig {
@@ -42,6 +68,89 @@ impl CkConfig {
     pub fn load_from(path: &std::path::Path) -> crate::error::Result<Self> {
         super::loader::load_config_from(path)
     }
+
+    /// Stable hash of the effective configuration, as `sha256:<hex>`.
+    ///
+    /// Outputs, notes and baselines record it so results from another
+    /// configuration can be told apart. `[ui]` is left out, so personal
+    /// display settings don't change it.
+    pub fn hash(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("ui");
+        }
+        format!("sha256:{:x}", Sha256::digest(value.to_string().as_bytes()))
+    }
+
+    /// Names of the commit types that may be used: `rules.allowed_types`
+    /// followed by the types defined under `[[types]]`.
+    pub fn allowed_type_names(&self) -> Vec<String> {
+        let mut names = self.rules.allowed_types.clone();
+        for definition in &self.types {
+            let name = definition.name.trim().to_lowercase();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// The commit type named `name`, if it is a built-in or defined under
+    /// `[[types]]`.
+    pub fn commit_type(&self, name: &str) -> Option<CommitType> {
+        name.parse().ok().or_else(|| {
+            self.types
+                .iter()
+                .filter_map(TypeDefinition::commit_type)
+                .find(|t| t.as_str() == name.trim().to_lowercase())
+        })
+    }
+
+    /// Description of `commit_type`, preferring the one under `[[types]]`.
+    pub fn type_description<'a>(&'a self, commit_type: &CommitType) -> &'a str {
+        self.type_definition(commit_type)
+            .map(|d| d.description.as_str())
+            .filter(|d| !d.is_empty())
+            .unwrap_or_else(|| commit_type.description())
+    }
+
+    /// Emoji configured for `commit_type` under `[[types]]`, if any.
+    pub fn type_emoji(&self, commit_type: &CommitType) -> Option<&str> {
+        self.type_definition(commit_type)
+            .and_then(|d| d.emoji.as_deref())
+    }
+
+    /// The last definition of `commit_type` under `[[types]]`.
+    fn type_definition(&self, commit_type: &CommitType) -> Option<&TypeDefinition> {
+        self.types
+            .iter()
+            .rev()
+            .find(|d| d.commit_type().as_ref() == Some(commit_type))
+    }
+}
+
+/// Settings for fetching the policy referenced by `extends`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PolicyConfig {
+    /// Never hit the network; use the cached policy only.
+    pub offline: bool,
+
+    /// Minisign public key (base64) used to verify `<extends>.minisig`.
+    pub minisign_key: Option<String>,
+
+    /// Cosign public key file used to verify `<extends>.sig`.
+    pub cosign_key: Option<PathBuf>,
+
+    /// Directory for cached policies (defaults to the user cache dir).
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl PolicyConfig {
+    /// Check if the fetched policy must carry a signature.
+    pub fn requires_signature(&self) -> bool {
+        self.minisign_key.is_some() || self.cosign_key.is_some()
+    }
 }
 
 /// Rule configuration.
@@ -66,19 +175,131 @@ pub struct RulesConfig {
     /// Forbidden commit types.
     pub forbidden_types: Vec<String>,
 
+    /// Types found in existing messages mapped to the type `ck fix --auto`
+    /// rewrites them to.
+    pub type_aliases: BTreeMap<String, String>,
+
     /// Scope configuration.
     pub scope: ScopeConfig,
 
     /// Path-based rules.
     #[serde(default)]
-    pub paths: HashMap<String, PathRuleConfig>,
+    pub paths: BTreeMap<String, PathRuleConfig>,
 
     /// Branch-based rules.
     #[serde(default)]
-    pub branch: HashMap<String, BranchRuleConfig>,
+    pub branch: BTreeMap<String, BranchRuleConfig>,
+
+    /// Body sections required for specific commit types.
+    #[serde(default)]
+    pub body_sections: BTreeMap<String, Vec<BodySectionConfig>>,
+
+    /// Trailer tokens every commit must have, e.g. `Signed-off-by`.
+    pub required_trailers: Vec<String>,
+
+    /// Ticket references taken from branch names and required in messages.
+    pub ticket: TicketConfig,
+
+    /// Language subjects must be written in.
+    pub language: LanguageConfig,
 
     /// CI-specific rules.
     pub ci: CiRulesConfig,
+
+    /// Rules for merge commits in range checks.
+    pub merge: MergeRulesConfig,
+
+    /// How the merge queue lands pull requests, for `ck merge-check`.
+    pub queue: QueueConfig,
+
+    /// Rule codes commits may suppress with a `Ck-Disable:` footer.
+    pub suppressible: Vec<String>,
+
+    /// Rules written as CEL expressions, keyed by rule name.
+    #[serde(default)]
+    pub expressions: BTreeMap<String, ExpressionRuleConfig>,
+}
+
+impl RulesConfig {
+    /// Get the body sections required for a commit type.
+    pub fn body_sections_for(&self, commit_type: &str) -> &[BodySectionConfig] {
+        self.body_sections
+            .get(commit_type)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Get the rules configured for a branch, by exact name or glob.
+    pub fn branch_rules(&self, branch: &str) -> Option<&BranchRuleConfig> {
+        if let Some(rules) = self.branch.get(branch) {
+            return Some(rules);
+        }
+
+        self.branch.iter().find_map(|(pattern, rules)| {
+            let matches = pattern.contains('*')
+                && glob::Pattern::new(pattern).is_ok_and(|glob| glob.matches(branch));
+            matches.then_some(rules)
+        })
+    }
+
+    /// Get the effective rules on a branch, with its branch rules applied.
+    pub fn for_branch(&self, branch: &str) -> RulesConfig {
+        let mut rules = self.clone();
+        let overrides = match self.branch_rules(branch) {
+            Some(overrides) => overrides,
+            None => return rules,
+        };
+
+        for forbidden in &overrides.forbid {
+            if !rules.forbidden_types.contains(forbidden) {
+                rules.forbidden_types.push(forbidden.clone());
+            }
+        }
+        rules
+            .forbidden_types
+            .retain(|t| !overrides.allow.contains(t));
+        if let Some(require_body) = overrides.require_body {
+            rules.require_body = require_body;
+        }
+
+        rules
+    }
+
+    /// Get the path rules whose globs match `file`, with their patterns.
+    pub fn path_rules<'a>(
+        &'a self,
+        file: &'a Path,
+    ) -> impl Iterator<Item = (&'a str, &'a PathRuleConfig)> + 'a {
+        self.paths.iter().filter_map(move |(pattern, rules)| {
+            glob::Pattern::new(pattern)
+                .is_ok_and(|glob| glob.matches_path(file))
+                .then_some((pattern.as_str(), rules))
+        })
+    }
+
+    /// Get the effective rules for a commit changing `files`.
+    ///
+    /// `require_scope = false` and `require_body = false` on paths lift the
+    /// global requirement when every file is under such a path. Paths that
+    /// add requirements are checked by the path rules instead.
+    pub fn for_paths(&self, files: &[PathBuf]) -> RulesConfig {
+        let mut rules = self.clone();
+        let lifted = |setting: fn(&PathRuleConfig) -> Option<bool>| {
+            !files.is_empty()
+                && files.iter().all(|file| {
+                    self.path_rules(file)
+                        .any(|(_, rules)| setting(rules) == Some(false))
+                })
+        };
+
+        if lifted(|rules| rules.require_scope) {
+            rules.require_scope = false;
+        }
+        if lifted(|rules| rules.require_body) {
+            rules.require_body = false;
+        }
+        rules
+    }
 }
 
 impl Default for RulesConfig {
@@ -102,10 +323,19 @@ impl Default for RulesConfig {
                 "ci".to_string(),
             ],
             forbidden_types: vec!["wip".to_string()],
+            type_aliases: BTreeMap::new(),
             scope: ScopeConfig::default(),
-            paths: HashMap::new(),
-            branch: HashMap::new(),
+            paths: BTreeMap::new(),
+            branch: BTreeMap::new(),
+            body_sections: BTreeMap::new(),
+            required_trailers: Vec::new(),
+            ticket: TicketConfig::default(),
+            language: LanguageConfig::default(),
             ci: CiRulesConfig::default(),
+            merge: MergeRulesConfig::default(),
+            queue: QueueConfig::default(),
+            suppressible: Vec::new(),
+            expressions: BTreeMap::new(),
         }
     }
 }
@@ -122,6 +352,28 @@ pub struct ScopeConfig {
 
     /// Forbidden scopes.
     pub forbidden: Vec<String>,
+
+    /// Inferred scope names mapped to the allowed scope to use instead.
+    pub aliases: BTreeMap<String, String>,
+
+    /// Naming convention scopes must follow, e.g. `kebab-case`.
+    pub case: Option<super::ScopeCase>,
+
+    /// Regex scopes must match.
+    pub pattern: Option<String>,
+
+    /// Maximum length of a scope.
+    pub max_length: Option<usize>,
+}
+
+impl ScopeConfig {
+    /// `scope` in the configured case, or as given without one.
+    pub fn normalize(&self, scope: &str) -> String {
+        match self.case {
+            Some(case) => case.apply(scope),
+            None => scope.to_string(),
+        }
+    }
 }
 
 /// Path-based rule configuration.
@@ -159,6 +411,57 @@ pub struct BranchRuleConfig {
     pub require_signed: Option<bool>,
 }
 
+/// A section the commit body must contain.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct BodySectionConfig {
+    /// Section header, e.g. `Root cause:`. Pre-seeded into the body editor.
+    pub header: String,
+
+    /// Regex the body must match. Defaults to a line starting with the header.
+    pub pattern: Option<String>,
+}
+
+/// A message template. `{type}`, `{scope}`, `{ticket}` and `{branch}` are
+/// replaced when it is used.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct MessageTemplate {
+    /// Commit type used when none is given.
+    #[serde(rename = "type")]
+    pub commit_type: Option<String>,
+
+    /// Scope used when none is given.
+    pub scope: Option<String>,
+
+    /// Subject pre-filled in the prompt, or used when none is given.
+    pub subject: Option<String>,
+
+    /// Body pre-filled in the editor. Lines ending in `:` are sections.
+    pub body: String,
+
+    /// Footer lines appended to the message. Lines with a placeholder that
+    /// has no value are left out.
+    pub footer: Option<String>,
+
+    /// Reject messages that leave a section of the body empty.
+    pub enforce: bool,
+}
+
+/// A rule written as a CEL expression.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ExpressionRuleConfig {
+    /// Expression that must evaluate to `true` for the commit to pass.
+    pub expr: String,
+
+    /// Message reported when the expression is `false`.
+    pub message: Option<String>,
+
+    /// Report violations as warnings instead of errors.
+    pub warning: bool,
+}
+
 /// CI-specific rules.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -168,6 +471,9 @@ pub struct CiRulesConfig {
 
     /// Whether to fail on warnings in CI.
     pub fail_on_warning: bool,
+
+    /// Warning codes promoted to errors in CI.
+    pub promote: Vec<String>,
 }
 
 impl Default for CiRulesConfig {
@@ -175,6 +481,210 @@ impl Default for CiRulesConfig {
         Self {
             strict: true,
             fail_on_warning: false,
+            promote: Vec::new(),
+        }
+    }
+}
+
+/// Rules for merge commits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MergeRulesConfig {
+    /// Whether git's generated `Merge branch ...` subjects are accepted.
+    pub allow_default_message: bool,
+
+    /// Whether other merge subjects must follow the commit rules.
+    pub require_conventional: bool,
+
+    /// Whether to reject merges that swap the first-parent history.
+    pub forbid_foxtrot: bool,
+}
+
+impl Default for MergeRulesConfig {
+    fn default() -> Self {
+        Self {
+            allow_default_message: true,
+            require_conventional: true,
+            forbid_foxtrot: false,
+        }
+    }
+}
+
+/// How the merge queue lands a pull request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueStrategy {
+    /// One commit with a message composed by the platform.
+    #[default]
+    Squash,
+    /// A merge commit on top of the pull request's commits.
+    Merge,
+    /// The pull request's commits, replayed onto the target.
+    Rebase,
+}
+
+/// How the platform composes the message of a squash merge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SquashMessage {
+    /// The pull request title, with its description as the body.
+    #[default]
+    Title,
+    /// The pull request title, with the commit messages as a bullet list.
+    /// A single commit keeps its own message.
+    Commits,
+    /// The message of the first commit.
+    FirstCommit,
+}
+
+/// Ticket references such as `JIRA-1234`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketConfig {
+    /// Regexes for the ticket in branch names, e.g.
+    /// `^feature/([A-Z]+-[0-9]+)`. The first group, or else the whole
+    /// match, is the ticket. By default an issue key at the start of the
+    /// branch or after a `/` is taken.
+    pub branch_patterns: Vec<String>,
+
+    /// Regex a ticket reference in a message matches.
+    pub pattern: String,
+
+    /// Project keys tickets must start with, e.g. `JIRA`. Keys of other
+    /// projects are ignored in messages and branch names; any key counts
+    /// when empty.
+    pub prefixes: Vec<String>,
+
+    /// Where the ticket from the branch goes when the message has none.
+    pub placement: TicketPlacement,
+
+    /// Trailer token for tickets placed in the footer.
+    pub token: String,
+
+    /// Subject for tickets placed in the subject; `{ticket}` and
+    /// `{subject}` are replaced.
+    pub subject_format: String,
+
+    /// Whether every message must reference a ticket.
+    pub required: bool,
+}
+
+impl Default for TicketConfig {
+    fn default() -> Self {
+        Self {
+            branch_patterns: vec![r"(?:^|/)([A-Z][A-Z0-9]+-[0-9]+)\b".to_string()],
+            pattern: r"\b[A-Z][A-Z0-9]+-[0-9]+\b|\B#[0-9]+\b".to_string(),
+            prefixes: Vec::new(),
+            placement: TicketPlacement::Footer,
+            token: "Refs".to_string(),
+            subject_format: "{ticket} {subject}".to_string(),
+            required: false,
+        }
+    }
+}
+
+/// The language of subjects, for teams that write commits in one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguageConfig {
+    /// Language subjects must be written in: an ISO 639-3 code such as
+    /// `eng`, or `history` for the language of most recent commits. The
+    /// check is off when unset.
+    pub require: Option<RequiredLanguage>,
+
+    /// Subjects with fewer words, not counting identifiers, are too short
+    /// to tell and are not checked.
+    pub min_words: usize,
+}
+
+impl Default for LanguageConfig {
+    fn default() -> Self {
+        Self {
+            require: None,
+            min_words: 4,
+        }
+    }
+}
+
+/// The language `rules.language.require` asks for. Unknown codes are
+/// rejected when the configuration is loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequiredLanguage {
+    /// The language most recent commits are written in.
+    History,
+    /// A fixed language.
+    Code(whatlang::Lang),
+}
+
+impl std::str::FromStr for RequiredLanguage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        if s == "history" {
+            return Ok(RequiredLanguage::History);
+        }
+        whatlang::Lang::from_code(s.as_str())
+            .map(RequiredLanguage::Code)
+            .ok_or_else(|| format!("'{}' is not an ISO 639-3 code or \"history\"", s))
+    }
+}
+
+impl Serialize for RequiredLanguage {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            RequiredLanguage::History => serializer.serialize_str("history"),
+            RequiredLanguage::Code(lang) => serializer.serialize_str(lang.code()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RequiredLanguage {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Where a ticket taken from the branch name goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketPlacement {
+    /// A trailer, `Refs: JIRA-1234` by default.
+    #[default]
+    Footer,
+    /// The subject, as `subject_format` lays it out.
+    Subject,
+    /// Nowhere; the ticket is only used for `{ticket}` in templates.
+    None,
+}
+
+/// Merge queue behavior, so `ck merge-check` checks the message that
+/// lands on the target branch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueueConfig {
+    /// How pull requests are landed.
+    pub strategy: QueueStrategy,
+
+    /// How squash messages are composed.
+    pub squash_message: SquashMessage,
+
+    /// Whether the platform appends ` (#123)` to the squashed subject.
+    pub append_number: bool,
+
+    /// Subject of merge commits; `{number}` and `{branch}` are replaced.
+    pub merge_subject: String,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            strategy: QueueStrategy::Squash,
+            squash_message: SquashMessage::Title,
+            append_number: true,
+            merge_subject: "Merge pull request #{number} from {branch}".to_string(),
         }
     }
 }
@@ -194,6 +704,30 @@ pub struct MonorepoConfig {
 
     /// Explicit package definitions.
     pub packages: Vec<PackageConfig>,
+
+    /// Build system whose BUILD files define the packages and targets,
+    /// instead of `package_markers`.
+    pub build_system: Option<BuildSystem>,
+}
+
+/// Build systems whose build files map files to targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildSystem {
+    /// Bazel `BUILD` and `BUILD.bazel` files.
+    Bazel,
+    /// Buck `BUCK` and `TARGETS` files.
+    Buck,
+}
+
+impl BuildSystem {
+    /// Names of the files that define a package, in lookup order.
+    pub fn build_files(self) -> &'static [&'static str] {
+        match self {
+            BuildSystem::Bazel => &["BUILD.bazel", "BUILD"],
+            BuildSystem::Buck => &["BUCK", "BUCK.v2", "TARGETS"],
+        }
+    }
 }
 
 impl Default for MonorepoConfig {
@@ -209,6 +743,7 @@ impl Default for MonorepoConfig {
             ],
             root_scope: "root".to_string(),
             packages: Vec::new(),
+            build_system: None,
         }
     }
 }
@@ -238,6 +773,12 @@ pub struct SecurityConfig {
 
     /// Custom secret patterns.
     pub patterns: Vec<SecretPattern>,
+
+    /// Paths that get a sensitive-file warning when staged.
+    pub risky_paths: RiskyPathsConfig,
+
+    /// Secret matches known to be safe.
+    pub allowlist: SecretAllowlist,
 }
 
 impl Default for SecurityConfig {
@@ -246,10 +787,77 @@ impl Default for SecurityConfig {
             enabled: true,
             block_on_secret: true,
             patterns: Vec::new(),
+            risky_paths: RiskyPathsConfig::default(),
+            allowlist: SecretAllowlist::default(),
+        }
+    }
+}
+
+/// Secret matches that are reported as suppressed instead of found, such
+/// as example keys in documentation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretAllowlist {
+    /// Globs for files whose matches are suppressed.
+    pub paths: Vec<String>,
+
+    /// Names of secret patterns whose matches are suppressed.
+    pub patterns: Vec<String>,
+
+    /// Fingerprints of specific matches, as reported by the scanner.
+    pub fingerprints: Vec<String>,
+}
+
+/// Globs for files that likely hold credentials. Matching ignores case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskyPathsConfig {
+    /// Paths flagged as sensitive.
+    pub deny: Vec<String>,
+
+    /// Paths never flagged, even if a `deny` glob matches, such as
+    /// test fixtures.
+    pub allow: Vec<String>,
+}
+
+impl Default for RiskyPathsConfig {
+    fn default() -> Self {
+        Self {
+            deny: [
+                "*secret*",
+                "*password*",
+                "*key*",
+                "*credential*",
+                "*.env*",
+                "*id_rsa*",
+                "*id_ed25519*",
+            ]
+            .iter()
+            .map(|glob| glob.to_string())
+            .collect(),
+            allow: Vec::new(),
         }
     }
 }
 
+impl RiskyPathsConfig {
+    /// Check if `path` matches a `deny` glob and no `allow` glob.
+    pub fn is_risky(&self, path: &std::path::Path) -> bool {
+        let options = glob::MatchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+        let matches = |globs: &[String]| {
+            globs.iter().any(|glob| {
+                glob::Pattern::new(glob)
+                    .map(|pattern| pattern.matches_path_with(path, options))
+                    .unwrap_or(false)
+            })
+        };
+        matches(&self.deny) && !matches(&self.allow)
+    }
+}
+
 /// Secret pattern definition.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecretPattern {
@@ -292,17 +900,139 @@ pub struct HookSettings {
 }
 
 /// Plugin configuration.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct PluginsConfig {
     /// Whether plugins are enabled.
     pub enabled: bool,
 
-    /// Directory containing plugins.
+    /// Directory containing plugins, relative to the repository root.
     pub directory: Option<PathBuf>,
 
     /// List of enabled plugins.
     pub enabled_plugins: Vec<String>,
+
+    /// Permissions plugins may declare; a plugin asking for more is not loaded.
+    pub permissions: Vec<crate::plugins::PluginCapability>,
+
+    /// Minisign public key `ck plugin install` requires a signature from.
+    pub minisign_key: Option<String>,
+}
+
+impl Default for PluginsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: None,
+            enabled_plugins: Vec::new(),
+            permissions: vec![crate::plugins::PluginCapability::ReadConfig],
+            minisign_key: None,
+        }
+    }
+}
+
+/// Thresholds for commit size warnings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalysisConfig {
+    /// Changed lines above which a commit is oversized.
+    pub max_commit_lines: usize,
+
+    /// Changed lines above which a single file gets a warning.
+    pub max_file_lines: usize,
+
+    /// Changed files above which a commit is oversized.
+    pub max_files: usize,
+
+    /// Overrides for files matching a glob, tried in order.
+    pub paths: BTreeMap<String, AnalysisPathConfig>,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            max_commit_lines: 500,
+            max_file_lines: 300,
+            max_files: 50,
+            paths: BTreeMap::new(),
+        }
+    }
+}
+
+impl AnalysisConfig {
+    /// Get the override for `path`, if a glob matches it.
+    pub fn path_override(&self, path: &std::path::Path) -> Option<&AnalysisPathConfig> {
+        self.paths.iter().find_map(|(pattern, config)| {
+            glob::Pattern::new(pattern)
+                .ok()
+                .filter(|glob| glob.matches_path(path))
+                .map(|_| config)
+        })
+    }
+}
+
+/// Size thresholds for files matching a glob.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct AnalysisPathConfig {
+    /// Changed lines above which a matching file gets a warning.
+    pub max_file_lines: Option<usize>,
+
+    /// Leave matching files out of the commit's line and file counts,
+    /// e.g. for lockfiles and generated code.
+    pub exclude_from_totals: bool,
+}
+
+/// Tuning for the warnings shown about staged changes.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WarningsConfig {
+    /// Warning codes never shown, e.g. `mixed-concerns`.
+    pub disable: Vec<String>,
+
+    /// Warning codes raised to errors, e.g. `binary-files`.
+    pub promote: Vec<String>,
+}
+
+/// Style of generated commit messages.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SmartConfig {
+    /// How much detail generated messages include.
+    pub tone: SmartTone,
+
+    /// Maximum number of words in a generated subject.
+    pub max_subject_words: Option<usize>,
+
+    /// Words never used in generated messages.
+    pub forbid_words: Vec<String>,
+
+    /// Subject layout using `{verb}`, `{target}` and `{scope}`.
+    pub subject_template: Option<String>,
+
+    /// Append a `Generated-by:` trailer to generated messages.
+    pub provenance: bool,
+}
+
+/// Level of detail in generated messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmartTone {
+    /// Subject line only.
+    Concise,
+    /// Subject with a bullet list of changes.
+    #[default]
+    Detailed,
+}
+
+/// How ck talks to git.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Stage and commit with the git binary instead of libgit2, so
+    /// filters, hooks and fsmonitor behave as with plain git. Partial
+    /// commits and hunk staging still write the index with libgit2.
+    pub use_cli: bool,
 }
 
 /// UI/UX configuration.
@@ -315,123 +1045,81 @@ pub struct UiConfig {
     /// Whether to use emoji.
     pub emoji: bool,
 
+    /// Emoji written into commit headers.
+    pub emoji_style: EmojiStyle,
+
     /// Whether to show hints.
     pub hints: bool,
 
     /// Theme name.
     pub theme: String,
-}
 
-impl Default for UiConfig {
-    fn default() -> Self {
-        Self {
-            color: true,
-            emoji: true,
-            hints: true,
-            theme: "default".to_string(),
-        }
-    }
+    /// Replace file paths with hashes and counts in terminal output.
+    pub redact_paths: bool,
+
+    /// Editor for commit bodies, before `$GIT_EDITOR`, `$VISUAL`,
+    /// `$EDITOR` and `core.editor`.
+    pub editor: Option<String>,
+
+    /// Hints about commit habits shown in the builder.
+    pub coaching: CoachingConfig,
 }
 
-/// Commit type definition.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Emoji written into commit headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
-pub enum CommitType {
-    Feat,
-    Fix,
-    Docs,
-    Style,
-    Refactor,
-    Perf,
-    Test,
-    Chore,
-    Revert,
-    Build,
-    Ci,
-    Wip,
-}
-
-impl CommitType {
-    /// Get the string representation of the commit type.
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            CommitType::Feat => "feat",
-            CommitType::Fix => "fix",
-            CommitType::Docs => "docs",
-            CommitType::Style => "style",
-            CommitType::Refactor => "refactor",
-            CommitType::Perf => "perf",
-            CommitType::Test => "test",
-            CommitType::Chore => "chore",
-            CommitType::Revert => "revert",
-            CommitType::Build => "build",
-            CommitType::Ci => "ci",
-            CommitType::Wip => "wip",
-        }
-    }
+pub enum EmojiStyle {
+    /// Headers carry no emoji.
+    #[default]
+    None,
+    /// A gitmoji for the type goes before the subject: `feat: ✨ add export`.
+    Gitmoji,
+}
 
-    /// Get a description of the commit type.
-    pub fn description(&self) -> &'static str {
-        match self {
-            CommitType::Feat => "A new feature",
-            CommitType::Fix => "A bug fix",
-            CommitType::Docs => "Documentation only changes",
-            CommitType::Style => "Code style changes (formatting, whitespace)",
-            CommitType::Refactor => "Code refactoring (no feature/fix)",
-            CommitType::Perf => "Performance improvements",
-            CommitType::Test => "Adding or updating tests",
-            CommitType::Chore => "Build process or auxiliary tool changes",
-            CommitType::Revert => "Reverting a previous commit",
-            CommitType::Build => "Build system or dependency changes",
-            CommitType::Ci => "CI configuration changes",
-            CommitType::Wip => "Work in progress",
-        }
-    }
+/// Thresholds for commit habit hints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoachingConfig {
+    /// Whether coaching hints are shown.
+    pub enabled: bool,
 
-    /// Get all commit types.
-    pub fn all() -> &'static [CommitType] {
-        &[
-            CommitType::Feat,
-            CommitType::Fix,
-            CommitType::Docs,
-            CommitType::Style,
-            CommitType::Refactor,
-            CommitType::Perf,
-            CommitType::Test,
-            CommitType::Chore,
-            CommitType::Revert,
-            CommitType::Build,
-            CommitType::Ci,
-            CommitType::Wip,
-        ]
-    }
-}
+    /// Hours since the last commit after which a large diff gets a hint.
+    pub stale_hours: u64,
 
-impl std::str::FromStr for CommitType {
-    type Err = ();
+    /// Changed lines that count as a large diff.
+    pub large_diff_lines: usize,
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "feat" | "feature" => Ok(CommitType::Feat),
-            "fix" | "bugfix" => Ok(CommitType::Fix),
-            "docs" | "doc" => Ok(CommitType::Docs),
-            "style" => Ok(CommitType::Style),
-            "refactor" => Ok(CommitType::Refactor),
-            "perf" | "performance" => Ok(CommitType::Perf),
-            "test" | "tests" => Ok(CommitType::Test),
-            "chore" => Ok(CommitType::Chore),
-            "revert" => Ok(CommitType::Revert),
-            "build" => Ok(CommitType::Build),
-            "ci" => Ok(CommitType::Ci),
-            "wip" => Ok(CommitType::Wip),
-            _ => Err(()),
+    /// Number of consecutive small chore commits that gets a squash hint.
+    pub chore_streak: usize,
+
+    /// Changed lines up to which a commit counts as small.
+    pub small_commit_lines: usize,
+}
+
+impl Default for CoachingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            stale_hours: 8,
+            large_diff_lines: 500,
+            chore_streak: 5,
+            small_commit_lines: 20,
         }
     }
 }
 
-impl std::fmt::Display for CommitType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.as_str())
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            color: true,
+            emoji: true,
+            emoji_style: EmojiStyle::None,
+            hints: true,
+            theme: "default".to_string(),
+            redact_paths: false,
+            editor: None,
+            coaching: CoachingConfig::default(),
+        }
     }
 }
 
@@ -448,22 +1136,66 @@ mod tests {
     }
 
     #[test]
-    fn test_commit_type_from_str() {
-        assert_eq!("feat".parse::<CommitType>(), Ok(CommitType::Feat));
-        assert_eq!("FIX".parse::<CommitType>(), Ok(CommitType::Fix));
-        assert!("unknown".parse::<CommitType>().is_err());
+    fn test_config_serialization() {
+        let config = CkConfig::default();
+        let toml_str = toml::to_string(&config).unwrap();
+        assert!(toml_str.contains("max_subject_length"));
+    }
+
+    #[test]
+    fn test_config_hash() {
+        let config = CkConfig::default();
+        assert_eq!(config.hash(), CkConfig::default().hash());
+        assert!(config.hash().starts_with("sha256:"));
+
+        let mut ui = config.clone();
+        ui.ui.color = !ui.ui.color;
+        assert_eq!(ui.hash(), config.hash());
+
+        let mut stricter = config.clone();
+        stricter.rules.require_body = true;
+        assert_ne!(stricter.hash(), config.hash());
     }
 
     #[test]
-    fn test_commit_type_display() {
-        assert_eq!(CommitType::Feat.to_string(), "feat");
-        assert_eq!(CommitType::Refactor.to_string(), "refactor");
+    fn test_rules_for_branch() {
+        let mut rules = RulesConfig::default();
+        rules.branch.insert(
+            "release/*".to_string(),
+            BranchRuleConfig {
+                forbid: vec!["wip".to_string()],
+                require_body: Some(true),
+                ..Default::default()
+            },
+        );
+
+        let release = rules.for_branch("release/1.x");
+        assert!(release.require_body);
+        assert!(release.forbidden_types.contains(&"wip".to_string()));
+
+        let feature = rules.for_branch("feature/x");
+        assert!(!feature.require_body);
+        assert!(rules.branch_rules("feature/x").is_none());
     }
 
     #[test]
-    fn test_config_serialization() {
-        let config = CkConfig::default();
-        let toml_str = toml::to_string(&config).unwrap();
-        assert!(toml_str.contains("max_subject_length"));
+    fn test_rules_for_paths() {
+        let mut rules = RulesConfig {
+            require_body: true,
+            ..Default::default()
+        };
+        rules.paths.insert(
+            "docs/**".to_string(),
+            PathRuleConfig {
+                require_body: Some(false),
+                ..Default::default()
+            },
+        );
+
+        let docs = [PathBuf::from("docs/guide.md"), PathBuf::from("docs/a/b.md")];
+        assert!(!rules.for_paths(&docs).require_body);
+        let mixed = [PathBuf::from("docs/guide.md"), PathBuf::from("src/lib.rs")];
+        assert!(rules.for_paths(&mixed).require_body);
+        assert!(rules.for_paths(&[]).require_body);
     }
 }
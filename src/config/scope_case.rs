@@ -0,0 +1,161 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Naming conventions for scopes, set with `rules.scope.case`.
+//!
+//! Scopes are split into words at punctuation and case changes, so
+//! `UserProfile`, `user_profile` and `user-profile` convert to each other.
+//! Parts of multi-scopes (`api,cli`) and nested scopes (`auth/oauth`) are
+//! converted one by one.
+
+use serde::{Deserialize, Serialize};
+
+/// A naming convention for scopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScopeCase {
+    /// `user-profile`
+    #[serde(rename = "kebab-case")]
+    Kebab,
+    /// `user_profile`
+    #[serde(rename = "snake_case")]
+    Snake,
+    /// `userProfile`
+    #[serde(rename = "camelCase")]
+    Camel,
+    /// `UserProfile`
+    #[serde(rename = "PascalCase")]
+    Pascal,
+    /// `userprofile`
+    #[serde(rename = "lowercase")]
+    Lower,
+}
+
+impl ScopeCase {
+    /// Name as written in the configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScopeCase::Kebab => "kebab-case",
+            ScopeCase::Snake => "snake_case",
+            ScopeCase::Camel => "camelCase",
+            ScopeCase::Pascal => "PascalCase",
+            ScopeCase::Lower => "lowercase",
+        }
+    }
+
+    /// `scope` in this case.
+    pub fn apply(&self, scope: &str) -> String {
+        scope
+            .split(',')
+            .map(|part| {
+                part.trim()
+                    .split('/')
+                    .map(|segment| self.convert(segment))
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Whether `scope` is already in this case.
+    pub fn matches(&self, scope: &str) -> bool {
+        self.apply(scope) == scope
+    }
+
+    fn convert(&self, segment: &str) -> String {
+        let words = words(segment);
+        match self {
+            ScopeCase::Kebab => join_lower(&words, "-"),
+            ScopeCase::Snake => join_lower(&words, "_"),
+            ScopeCase::Lower => join_lower(&words, ""),
+            ScopeCase::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| match i {
+                    0 => word.to_lowercase(),
+                    _ => capitalize(word),
+                })
+                .collect(),
+            ScopeCase::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+        }
+    }
+}
+
+/// The words of a scope segment, split at punctuation, at lower-to-upper
+/// changes and before the last capital of an acronym (`HTTPServer`).
+fn words(segment: &str) -> Vec<String> {
+    let chars: Vec<char> = segment.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        let previous = i.checked_sub(1).map(|j| chars[j]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_uppercase()
+            && previous.is_some_and(|p| {
+                p.is_lowercase()
+                    || p.is_ascii_digit()
+                    || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+            });
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn join_lower(words: &[String], separator: &str) -> String {
+    words
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(ScopeCase::Kebab.apply("UserProfile"), "user-profile");
+        assert_eq!(ScopeCase::Kebab.apply("HTTPServer"), "http-server");
+        assert_eq!(ScopeCase::Snake.apply("user-profile"), "user_profile");
+        assert_eq!(ScopeCase::Camel.apply("user_profile"), "userProfile");
+        assert_eq!(ScopeCase::Pascal.apply("user profile"), "UserProfile");
+        assert_eq!(ScopeCase::Lower.apply("User-Profile"), "userprofile");
+        assert_eq!(
+            ScopeCase::Kebab.apply("Auth/TokenStore, CLI"),
+            "auth/token-store,cli"
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(ScopeCase::Kebab.matches("user-profile"));
+        assert!(ScopeCase::Kebab.matches("api,cli"));
+        assert!(!ScopeCase::Kebab.matches("userProfile"));
+        assert!(ScopeCase::Camel.matches("userProfile"));
+        assert!(!ScopeCase::Pascal.matches("userProfile"));
+    }
+}
@@ -52,9 +52,158 @@ pub enum CkError {
     #[error("Operation cancelled by user")]
     Cancelled,
 
-    // Generic error with context
+    // Input is needed but prompts are off
+    #[error("Missing {what}, and prompts are unavailable ({reason})")]
+    NoPrompt { what: String, reason: String },
+
+    // Error from a command, with its own code. The part of the code before
+    // the first `-` names the area and decides the category
     #[error("{context}: {message}")]
-    WithContext { context: String, message: String },
+    WithContext {
+        code: &'static str,
+        context: String,
+        message: String,
+    },
+}
+
+impl CkError {
+    /// Stable machine-readable code for the error.
+    ///
+    /// Codes never change once released, so wrappers can branch on them
+    /// instead of parsing messages.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CkError::Config(e) => match e {
+                ConfigError::NotFound { .. } => "config-not-found",
+                ConfigError::ParseError { .. } => "config-parse",
+                ConfigError::InvalidValue { .. } => "config-invalid-value",
+                ConfigError::MissingRequired { .. } => "config-missing-required",
+                ConfigError::MergeError { .. } => "config-merge",
+                ConfigError::PolicyFetch { .. } => "policy-fetch",
+                ConfigError::PolicySignature { .. } => "policy-signature",
+            },
+            CkError::Git(e) => match e {
+                GitError::NotARepository => "not-a-repository",
+                GitError::OpenFailed { .. } => "repository-open",
+                GitError::NoStagedChanges => "no-staged-changes",
+                GitError::DiffFailed { .. } => "diff-failed",
+                GitError::CommitFailed { .. } => "commit-failed",
+                GitError::BranchFailed { .. } => "branch-failed",
+                GitError::InvalidReference { .. } => "invalid-reference",
+                GitError::CommandFailed { .. } => "git-command-failed",
+                GitError::DetachedHead => "detached-head",
+                GitError::NoCommits => "no-commits",
+            },
+            CkError::Validation(e) => match e {
+                ValidationError::SubjectTooLong { .. } => "subject-max-length",
+                ValidationError::SubjectTooShort { .. } => "subject-min-length",
+                ValidationError::InvalidType { .. } => "type-not-allowed",
+                ValidationError::ForbiddenType { .. } => "type-forbidden",
+                ValidationError::MissingScope => "scope-required",
+                ValidationError::InvalidScope { .. } => "scope-not-allowed",
+                ValidationError::MissingBody => "body-required",
+                ValidationError::InvalidFormat { .. } => "invalid-format",
+                ValidationError::RuleViolation { .. } => "rule-violation",
+                ValidationError::MultipleErrors { .. } => "validation-failed",
+                ValidationError::VerificationFailed { .. } => "verify-failed",
+            },
+            CkError::Plugin(e) => match e {
+                PluginError::NotFound { .. } => "plugin-not-found",
+                PluginError::LoadFailed { .. } => "plugin-load",
+                PluginError::VersionMismatch { .. } => "plugin-version-mismatch",
+                PluginError::PermissionDenied { .. } => "plugin-permission-denied",
+                PluginError::ExecutionFailed { .. } => "plugin-execution",
+                PluginError::InvalidManifest { .. } => "plugin-invalid-manifest",
+                PluginError::VerificationFailed { .. } => "plugin-verification",
+            },
+            CkError::Security(e) => match e {
+                SecurityError::SecretDetected { .. } => "secret-detected",
+                SecurityError::MultipleSecrets { .. } => "secrets-detected",
+                SecurityError::SigningRequired => "signing-required",
+                SecurityError::InvalidSignature { .. } => "invalid-signature",
+                SecurityError::CheckFailed { .. } => "security-check",
+            },
+            CkError::Commit(e) => match e {
+                CommitError::ParseFailed { .. } => "parse-error",
+                CommitError::EmptyMessage => "empty-message",
+                CommitError::InvalidConventionalFormat => "invalid-conventional-format",
+                CommitError::Aborted => "commit-aborted",
+            },
+            CkError::Hook(e) => match e {
+                HookError::InstallFailed { .. } => "hook-install",
+                HookError::AlreadyExists { .. } => "hook-exists",
+                HookError::NotFound { .. } => "hook-not-found",
+                HookError::RemoveFailed { .. } => "hook-remove",
+                HookError::ExecutionFailed { .. } => "hook-execution",
+                HookError::Unhealthy { .. } => "hook-unhealthy",
+            },
+            CkError::Io(_) => "io",
+            CkError::Ui(_) => "ui",
+            CkError::Cancelled => "cancelled",
+            CkError::NoPrompt { .. } => "no-prompt",
+            CkError::WithContext { code, .. } => code,
+        }
+    }
+
+    /// Broad category of the error.
+    pub fn category(&self) -> &'static str {
+        match self {
+            CkError::Config(_) => "config",
+            CkError::Git(_) => "git",
+            CkError::Validation(_) => "validation",
+            CkError::Plugin(_) => "plugin",
+            CkError::Security(_) => "security",
+            CkError::Commit(_) => "commit",
+            CkError::Hook(_) => "hook",
+            CkError::Io(_) => "io",
+            CkError::Ui(_) | CkError::Cancelled | CkError::NoPrompt { .. } => "ui",
+            CkError::WithContext { code, .. } => context_category(code),
+        }
+    }
+
+    /// Suggested next step, if there is a common one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            CkError::Git(GitError::NotARepository) => Some("Run ck inside a git repository"),
+            CkError::Git(GitError::NoStagedChanges) => {
+                Some("Stage changes with `git add` or pass --all")
+            }
+            CkError::Git(GitError::DetachedHead) => Some("Check out a branch first"),
+            CkError::Git(GitError::NoCommits) => {
+                Some("Create the first commit with `ck commit`, e.g. 'chore: initial commit'")
+            }
+            CkError::Validation(_) => Some("Run `ck check` for details on each issue"),
+            CkError::Security(SecurityError::SecretDetected { .. })
+            | CkError::Security(SecurityError::MultipleSecrets { .. }) => {
+                Some("Remove the secret from the staged changes")
+            }
+            CkError::Config(ConfigError::ParseError { .. }) => {
+                Some("Check the configuration file syntax")
+            }
+            CkError::Hook(HookError::Unhealthy { .. }) => {
+                Some("Refresh the hooks with `ck hooks doctor --repair`")
+            }
+            CkError::NoPrompt { .. } => {
+                Some("Pass the missing flags, or use --prompt=always to prompt anyway")
+            }
+            CkError::Hook(HookError::AlreadyExists { .. }) => {
+                Some("Keep the existing hook with --chain, or replace it with --force")
+            }
+            _ => None,
+        }
+    }
+
+    /// JSON envelope describing the error, for `--format json`.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": {
+                "code": self.code(),
+                "category": self.category(),
+                "message": self.to_string(),
+                "hint": self.hint(),
+            }
+        })
+    }
 }
 
 impl From<dialoguer::Error> for CkError {
@@ -80,6 +229,12 @@ pub enum ConfigError {
 
     #[error("Configuration merge error: {message}")]
     MergeError { message: String },
+
+    #[error("Failed to fetch policy '{source_ref}': {message}")]
+    PolicyFetch { source_ref: String, message: String },
+
+    #[error("Policy signature verification failed for '{source_ref}': {message}")]
+    PolicySignature { source_ref: String, message: String },
 }
 
 /// Git-related errors.
@@ -111,6 +266,9 @@ pub enum GitError {
 
     #[error("Detached HEAD state")]
     DetachedHead,
+
+    #[error("Repository has no commits yet")]
+    NoCommits,
 }
 
 impl From<git2::Error> for GitError {
@@ -153,6 +311,9 @@ pub enum ValidationError {
 
     #[error("Multiple validation errors: {count} issues found")]
     MultipleErrors { count: usize },
+
+    #[error("{failed} of {total} commit(s) failed verification")]
+    VerificationFailed { failed: usize, total: usize },
 }
 
 /// Plugin-related errors.
@@ -179,6 +340,9 @@ pub enum PluginError {
 
     #[error("Invalid plugin manifest: {message}")]
     InvalidManifest { message: String },
+
+    #[error("Plugin verification failed for '{source_ref}': {message}")]
+    VerificationFailed { source_ref: String, message: String },
 }
 
 /// Security-related errors.
@@ -233,6 +397,23 @@ pub enum HookError {
 
     #[error("Hook execution failed: {hook} - {message}")]
     ExecutionFailed { hook: String, message: String },
+
+    #[error("{count} hook(s) need attention")]
+    Unhealthy { count: usize },
+}
+
+/// Category of a [`CkError::WithContext`] code, from its area prefix.
+fn context_category(code: &str) -> &'static str {
+    let area = code.split('-').next().unwrap_or(code);
+    match area {
+        "stash" | "autostash" | "worktree" | "rewrite" | "range" | "backport" => "git",
+        "signing" | "attestation" => "security",
+        "verify" | "fix" | "audit" | "example" | "merge" | "check" | "style" => "validation",
+        "trailer" | "duration" | "stdin" | "template" => "commit",
+        "install" | "init" => "io",
+        "plugin" => "plugin",
+        _ => "other",
+    }
 }
 
 /// Result type alias for ck operations.
@@ -240,13 +421,14 @@ pub type Result<T> = std::result::Result<T, CkError>;
 
 /// Extension trait for adding context to errors.
 pub trait ResultExt<T> {
-    /// Add context to an error.
-    fn context(self, context: impl Into<String>) -> Result<T>;
+    /// Add context, and the code the error is reported with.
+    fn context(self, code: &'static str, context: impl Into<String>) -> Result<T>;
 }
 
 impl<T, E: std::error::Error + 'static> ResultExt<T> for std::result::Result<T, E> {
-    fn context(self, context: impl Into<String>) -> Result<T> {
+    fn context(self, code: &'static str, context: impl Into<String>) -> Result<T> {
         self.map_err(|e| CkError::WithContext {
+            code,
             context: context.into(),
             message: e.to_string(),
         })
@@ -292,4 +474,46 @@ mod tests {
         let ck_err: CkError = config_err.into();
         assert!(ck_err.to_string().contains("scope"));
     }
+
+    #[test]
+    fn test_error_codes_and_envelope() {
+        let err = CkError::Git(GitError::NoStagedChanges);
+        assert_eq!(err.code(), "no-staged-changes");
+        assert_eq!(err.category(), "git");
+
+        let json = err.to_json();
+        assert_eq!(json["error"]["code"], "no-staged-changes");
+        assert_eq!(
+            json["error"]["message"],
+            "Git error: No staged changes found"
+        );
+        assert!(json["error"]["hint"].is_string());
+
+        let err = CkError::Validation(ValidationError::MultipleErrors { count: 2 });
+        assert_eq!(err.code(), "validation-failed");
+        assert!(CkError::Cancelled.to_json()["error"]["hint"].is_null());
+    }
+
+    #[test]
+    fn test_context_error_codes() {
+        let err = CkError::WithContext {
+            code: "autostash-restore",
+            context: "autostash".to_string(),
+            message: "Failed to restore stashed changes".to_string(),
+        };
+        assert_eq!(err.code(), "autostash-restore");
+        assert_eq!(err.category(), "git");
+        assert_eq!(
+            err.to_string(),
+            "autostash: Failed to restore stashed changes"
+        );
+
+        let err = std::fs::read_to_string("/nonexistent/ck.toml")
+            .context("init-write", "init")
+            .unwrap_err();
+        assert_eq!(err.code(), "init-write");
+        assert_eq!(err.category(), "io");
+        assert_eq!(context_category("merge-check-no-target"), "validation");
+        assert_eq!(context_category("lsp-protocol"), "other");
+    }
 }
@@ -2,16 +2,123 @@
 // SPDX-License-Identifier: MIT
 
 //! Git command wrappers for staging and committing.
+//!
+//! Writes use libgit2 unless the git binary is needed: for signing, for
+//! passthrough options, or when `git.use_cli` asks for git's own filters,
+//! hooks and fsmonitor handling. Reads always use libgit2, and so do the
+//! index writes of partial commits (`ck commit <paths>`) and `--patch` hunk
+//! staging, which git has no non-interactive command for.
 
 use crate::error::{CkError, GitError, Result};
 use std::path::Path;
 use std::process::Command;
 
 use super::repo::Repository;
+use super::stash::StashCommit;
+
+/// Where a new commit is written.
+#[derive(Debug, Clone, Default)]
+pub enum CommitTarget {
+    /// On top of HEAD, from the index.
+    #[default]
+    Head,
+    /// On top of another branch, from the staged changes.
+    Branch(String),
+    /// On top of HEAD, from a stash applied in a temporary index.
+    Stash(StashCommit),
+}
+
+/// Options forwarded to git when creating a commit.
+///
+/// Commits that would be written with git2 go through `git commit` or
+/// `git commit-tree` instead when any option is set, so git applies them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitOptions {
+    /// Skip the pre-commit and commit-msg hooks.
+    pub no_verify: bool,
+    /// Override the author, as `Name <email>`.
+    pub author: Option<String>,
+    /// Override the author date.
+    pub date: Option<String>,
+    /// How git cleans up the message (`--cleanup`).
+    pub cleanup: Option<String>,
+    /// Extra arguments for `git commit`.
+    pub git_args: Vec<String>,
+    /// Write with the git binary even when nothing else needs it, as
+    /// `git.use_cli` asks.
+    pub use_cli: bool,
+}
+
+impl CommitOptions {
+    /// Check if no option is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Arguments for `git commit`.
+    fn commit_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.no_verify {
+            args.push("--no-verify".to_string());
+        }
+        if let Some(ref author) = self.author {
+            args.push(format!("--author={}", author));
+        }
+        if let Some(ref date) = self.date {
+            args.push(format!("--date={}", date));
+        }
+        if let Some(ref cleanup) = self.cleanup {
+            args.push(format!("--cleanup={}", cleanup));
+        }
+        args.extend(self.git_args.iter().cloned());
+        args
+    }
+
+    /// Environment for `git commit-tree`, which has no author options.
+    /// Hooks and cleanup do not apply there.
+    fn commit_tree_env(&self) -> Result<Vec<(&'static str, String)>> {
+        if !self.git_args.is_empty() {
+            return Err(CkError::Git(GitError::CommitFailed {
+                message: "--git-arg only applies to commits on HEAD".to_string(),
+            }));
+        }
+
+        let mut env = Vec::new();
+        if let Some(ref author) = self.author {
+            let (name, email) = parse_author(author).ok_or_else(|| {
+                CkError::Git(GitError::CommitFailed {
+                    message: format!("Author '{}' is not in 'Name <email>' form", author),
+                })
+            })?;
+            env.push(("GIT_AUTHOR_NAME", name));
+            env.push(("GIT_AUTHOR_EMAIL", email));
+        }
+        if let Some(ref date) = self.date {
+            env.push(("GIT_AUTHOR_DATE", date.clone()));
+        }
+        Ok(env)
+    }
+}
+
+/// Split `Name <email>` into its parts.
+fn parse_author(author: &str) -> Option<(String, String)> {
+    let (name, rest) = author.split_once('<')?;
+    let email = rest.strip_suffix('>')?;
+    let name = name.trim();
+    if name.is_empty() || email.is_empty() {
+        return None;
+    }
+    Some((name.to_string(), email.to_string()))
+}
 
-/// Stage all modified and deleted files.
-pub fn stage_all() -> Result<()> {
+/// Stage all modified and deleted files, with `git add` when `use_cli` is
+/// set.
+pub fn stage_all(use_cli: bool) -> Result<()> {
     let repo = Repository::open_current()?;
+    if use_cli {
+        return run_git(repo.workdir(), &["add", "--all"]);
+    }
+
     let mut index = repo.inner().index().map_err(|e| {
         CkError::Git(GitError::CommandFailed {
             command: "index".to_string(),
@@ -39,13 +146,21 @@ pub fn stage_all() -> Result<()> {
 }
 
 /// Stage specific files.
-pub fn stage_files(paths: &[&Path]) -> Result<()> {
+pub fn stage_files(paths: &[&Path], use_cli: bool) -> Result<()> {
     let repo = Repository::open_current()?;
-    stage_files_in_repo(&repo, paths)
+    stage_files_in_repo(&repo, paths, use_cli)
 }
 
-/// Stage specific files in a given repository.
-pub fn stage_files_in_repo(repo: &Repository, paths: &[&Path]) -> Result<()> {
+/// Stage specific files in a given repository, with `git add` when
+/// `use_cli` is set.
+pub fn stage_files_in_repo(repo: &Repository, paths: &[&Path], use_cli: bool) -> Result<()> {
+    if use_cli {
+        let mut args = vec!["add".to_string(), "--".to_string()];
+        args.extend(paths.iter().map(|path| path.display().to_string()));
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        return run_git(repo.workdir(), &args);
+    }
+
     let mut index = repo.inner().index().map_err(|e| {
         CkError::Git(GitError::CommandFailed {
             command: "index".to_string(),
@@ -92,8 +207,64 @@ pub fn stage_files_in_repo(repo: &Repository, paths: &[&Path]) -> Result<()> {
     Ok(())
 }
 
+/// Check out a branch.
+pub fn checkout(branch: &str) -> Result<()> {
+    let output = Command::new("git")
+        .args(["checkout", "--quiet", branch])
+        .output()
+        .map_err(|e| {
+            CkError::Git(GitError::CommandFailed {
+                command: "git checkout".to_string(),
+                message: e.to_string(),
+            })
+        })?;
+
+    if !output.status.success() {
+        return Err(CkError::Git(GitError::CommandFailed {
+            command: format!("git checkout {}", branch),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }));
+    }
+
+    Ok(())
+}
+
+/// Cherry-pick a commit onto HEAD, keeping its author.
+pub fn cherry_pick(reference: &str) -> Result<()> {
+    let output = Command::new("git")
+        .args(["cherry-pick", "--allow-empty", reference])
+        .output()
+        .map_err(|e| {
+            CkError::Git(GitError::CommandFailed {
+                command: "git cherry-pick".to_string(),
+                message: e.to_string(),
+            })
+        })?;
+
+    if !output.status.success() {
+        return Err(CkError::Git(GitError::CommandFailed {
+            command: "git cherry-pick".to_string(),
+            message: format!(
+                "{}\nResolve the conflicts and run `git cherry-pick --continue`, or `git cherry-pick --abort`",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
 /// Create a commit with the given message.
 pub fn create_commit(message: &str, sign: bool) -> Result<String> {
+    create_commit_with_options(message, sign, &CommitOptions::default())
+}
+
+/// Create a commit from the staged changes, forwarding `options` to git.
+pub fn create_commit_with_options(
+    message: &str,
+    sign: bool,
+    options: &CommitOptions,
+) -> Result<String> {
     let repo = Repository::open_current()?;
 
     // Check for staged changes
@@ -134,9 +305,9 @@ pub fn create_commit(message: &str, sign: bool) -> Result<String> {
     let parent_refs: Vec<&git2::Commit<'_>> = parents.iter().collect();
 
     // Create the commit
-    if sign {
+    if sign || !options.is_empty() {
         // Use git command for signed commits as git2 signing is complex
-        create_commit_with_git(message, sign)?;
+        create_commit_with_git(message, sign, options)?;
         let new_head = repo.head_commit()?;
         Ok(new_head.id().to_string())
     } else {
@@ -153,8 +324,157 @@ pub fn create_commit(message: &str, sign: bool) -> Result<String> {
     }
 }
 
-/// Create a commit using the git command (for signing support).
-fn create_commit_with_git(message: &str, sign: bool) -> Result<()> {
+/// Commit the staged changes onto another branch without checking it out.
+///
+/// The staged diff against HEAD is applied to the tip of `branch`, and only
+/// that branch ref moves. The index and working tree are left untouched.
+pub fn commit_onto(
+    branch: &str,
+    message: &str,
+    sign: bool,
+    options: &CommitOptions,
+) -> Result<String> {
+    let repo = Repository::open_current()?;
+    commit_onto_in_repo(&repo, branch, message, sign, options)
+}
+
+/// Commit the staged changes onto another branch in a specific repository.
+pub fn commit_onto_in_repo(
+    repo: &Repository,
+    branch: &str,
+    message: &str,
+    sign: bool,
+    options: &CommitOptions,
+) -> Result<String> {
+    let git = repo.inner();
+
+    if !repo.has_staged_changes()? {
+        return Err(CkError::Git(GitError::NoStagedChanges));
+    }
+
+    let ref_name = format!("refs/heads/{}", branch);
+    let target = git
+        .find_reference(&ref_name)
+        .and_then(|r| r.peel_to_commit())
+        .map_err(|_| {
+            CkError::Git(GitError::BranchFailed {
+                message: format!("Branch '{}' not found", branch),
+            })
+        })?;
+
+    // Committing onto the checked out branch is a normal commit
+    if git.head().ok().and_then(|h| h.name().map(String::from)) == Some(ref_name.clone()) {
+        return create_commit_with_options(message, sign, options);
+    }
+
+    let onto_error = |what: &str, e: git2::Error| {
+        CkError::Git(GitError::CommitFailed {
+            message: format!("{}: {}", what, e.message()),
+        })
+    };
+
+    // Diff of the staged changes, applied to the other branch's tree
+    let head_tree = repo.head_commit().ok().and_then(|c| c.tree().ok());
+    let index = git
+        .index()
+        .map_err(|e| onto_error("Failed to get index", e))?;
+    let mut opts = git2::DiffOptions::new();
+    opts.show_binary(true);
+    let diff = git
+        .diff_tree_to_index(head_tree.as_ref(), Some(&index), Some(&mut opts))
+        .map_err(|e| onto_error("Failed to diff index", e))?;
+
+    let target_tree = target
+        .tree()
+        .map_err(|e| onto_error("Failed to read branch tree", e))?;
+    let mut applied = git.apply_to_tree(&target_tree, &diff, None).map_err(|e| {
+        CkError::Git(GitError::CommitFailed {
+            message: format!(
+                "Staged changes do not apply cleanly to '{}': {}",
+                branch,
+                e.message()
+            ),
+        })
+    })?;
+    let tree_id = applied
+        .write_tree_to(git)
+        .map_err(|e| onto_error("Failed to write tree", e))?;
+
+    let commit_id = if sign || !options.is_empty() {
+        commit_tree_with_git(
+            repo,
+            &tree_id.to_string(),
+            &target.id().to_string(),
+            message,
+            sign,
+            options,
+        )?
+    } else {
+        let tree = git
+            .find_tree(tree_id)
+            .map_err(|e| onto_error("Failed to find tree", e))?;
+        let sig = git
+            .signature()
+            .map_err(|e| onto_error("Failed to get signature", e))?;
+        git.commit(None, &sig, &sig, message, &tree, &[&target])
+            .map_err(|e| onto_error("Failed to create commit", e))?
+            .to_string()
+    };
+
+    // Only move the branch if nobody else did in the meantime
+    let oid = git2::Oid::from_str(&commit_id).map_err(|e| onto_error("Invalid commit id", e))?;
+    git.reference_matching(
+        &ref_name,
+        oid,
+        true,
+        target.id(),
+        &format!("ck: commit onto {}", branch),
+    )
+    .map_err(|e| onto_error("Failed to update branch", e))?;
+
+    Ok(commit_id)
+}
+
+/// Create a commit object with `git commit-tree`, for signing or
+/// author overrides.
+pub(super) fn commit_tree_with_git(
+    repo: &Repository,
+    tree: &str,
+    parent: &str,
+    message: &str,
+    sign: bool,
+    options: &CommitOptions,
+) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(["commit-tree", tree, "-p", parent]);
+    if sign {
+        cmd.arg("-S");
+    }
+    let output = cmd
+        .arg("-m")
+        .arg(message)
+        .envs(options.commit_tree_env()?)
+        .current_dir(repo.workdir())
+        .output()
+        .map_err(|e| {
+            CkError::Git(GitError::CommitFailed {
+                message: format!("Failed to run git commit-tree: {}", e),
+            })
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CkError::Git(GitError::CommitFailed {
+            message: stderr.to_string(),
+        }));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Create a commit using the git command (for signing and passthrough
+/// options).
+fn create_commit_with_git(message: &str, sign: bool, options: &CommitOptions) -> Result<()> {
     let mut cmd = Command::new("git");
     cmd.arg("commit");
     cmd.arg("-m").arg(message);
@@ -162,6 +482,7 @@ fn create_commit_with_git(message: &str, sign: bool) -> Result<()> {
     if sign {
         cmd.arg("-S");
     }
+    cmd.args(options.commit_args());
 
     let output = cmd.output().map_err(|e| {
         CkError::Git(GitError::CommitFailed {
@@ -180,7 +501,7 @@ fn create_commit_with_git(message: &str, sign: bool) -> Result<()> {
 }
 
 /// Amend the last commit with a new message.
-pub fn amend_commit(message: &str, sign: bool) -> Result<String> {
+pub fn amend_commit(message: &str, sign: bool, options: &CommitOptions) -> Result<String> {
     let mut cmd = Command::new("git");
     cmd.arg("commit");
     cmd.arg("--amend");
@@ -189,6 +510,7 @@ pub fn amend_commit(message: &str, sign: bool) -> Result<String> {
     if sign {
         cmd.arg("-S");
     }
+    cmd.args(options.commit_args());
 
     let output = cmd.output().map_err(|e| {
         CkError::Git(GitError::CommitFailed {
@@ -209,6 +531,23 @@ pub fn amend_commit(message: &str, sign: bool) -> Result<String> {
     Ok(new_head.id().to_string())
 }
 
+/// Run a git command, failing with its stderr.
+pub(super) fn run_git(workdir: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(workdir)
+        .output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(CkError::Git(GitError::CommandFailed {
+            command: format!("git {}", args.join(" ")),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }))
+    }
+}
+
 /// Check if a commit is signed.
 pub fn is_commit_signed(reference: &str) -> Result<bool> {
     let repo = Repository::open_current()?;
@@ -222,6 +561,7 @@ pub fn is_commit_signed(reference: &str) -> Result<bool> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::FixtureRepo;
     use std::fs;
     use tempfile::TempDir;
 
@@ -254,13 +594,161 @@ mod tests {
         (dir, repo)
     }
 
+    /// A fixture repository with an uncommitted `test.txt`.
+    fn fixture_with_file() -> (FixtureRepo, Repository) {
+        let fixture = FixtureRepo::new();
+        fixture.write("test.txt", "hello");
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        (fixture, repo)
+    }
+
     #[test]
     fn test_stage_files() {
         let (dir, repo) = create_test_repo_with_file();
         let test_file = dir.path().join("test.txt");
 
-        stage_files_in_repo(&repo, &[test_file.as_path()]).unwrap();
+        stage_files_in_repo(&repo, &[test_file.as_path()], false).unwrap();
 
         assert!(repo.has_staged_changes().unwrap());
     }
+
+    #[test]
+    fn test_stage_files_with_git_cli_runs_filters() {
+        let (fixture, repo) = fixture_with_file();
+        fixture.git(&["config", "filter.upper.clean", "tr a-z A-Z"]);
+        fixture.write(".gitattributes", "*.txt filter=upper\n");
+        let test_file = fixture.join("test.txt");
+        let staged = || {
+            let mut index = repo.inner().index().unwrap();
+            index.read(true).unwrap();
+            let entry = index.get_path(Path::new("test.txt"), 0).unwrap();
+            repo.inner().find_blob(entry.id).unwrap().content().to_vec()
+        };
+
+        // libgit2 skips external filter drivers; git runs them
+        stage_files_in_repo(&repo, &[test_file.as_path()], false).unwrap();
+        assert_eq!(staged(), b"hello");
+        fs::write(&test_file, "hello again").unwrap();
+        stage_files_in_repo(&repo, &[test_file.as_path()], true).unwrap();
+        assert_eq!(staged(), b"HELLO AGAIN");
+    }
+
+    #[test]
+    fn test_commit_onto_other_branch() {
+        let (fixture, repo) = fixture_with_file();
+        fixture.write("docs.md", "docs");
+        fixture.commit("feat: initial commit");
+        fixture.git(&["branch", "main-line"]);
+        fixture.git(&["checkout", "-b", "feature"]);
+        fixture.write("test.txt", "feature");
+        fixture.git(&["commit", "-am", "feat: feature work"]);
+
+        fixture.write("docs.md", "fixed docs");
+        fixture.stage(&["docs.md"]);
+        let head = repo.head_commit().unwrap().id();
+
+        let sha = commit_onto_in_repo(
+            &repo,
+            "main-line",
+            "docs: fix typo",
+            false,
+            &CommitOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(repo.head_commit().unwrap().id(), head);
+        let commit = repo.get_commit("main-line").unwrap();
+        assert_eq!(commit.id().to_string(), sha);
+        assert_eq!(commit.message(), Some("docs: fix typo"));
+        let tree = commit.tree().unwrap();
+        let blob = tree.get_name("test.txt").unwrap().id();
+        let content = repo.inner().find_blob(blob).unwrap().content().to_vec();
+        assert_eq!(content, b"hello");
+        let blob = tree.get_name("docs.md").unwrap().id();
+        let content = repo.inner().find_blob(blob).unwrap().content().to_vec();
+        assert_eq!(content, b"fixed docs");
+    }
+
+    #[test]
+    fn test_commit_options() {
+        let options = CommitOptions {
+            no_verify: true,
+            author: Some("Ada Lovelace <ada@example.com>".to_string()),
+            git_args: vec!["--trailer=Reviewed-by: Bob".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            options.commit_args(),
+            vec![
+                "--no-verify",
+                "--author=Ada Lovelace <ada@example.com>",
+                "--trailer=Reviewed-by: Bob",
+            ]
+        );
+        assert!(options.commit_tree_env().is_err());
+        assert!(CommitOptions::default().is_empty());
+
+        assert_eq!(
+            parse_author("Ada Lovelace <ada@example.com>"),
+            Some(("Ada Lovelace".to_string(), "ada@example.com".to_string()))
+        );
+        assert_eq!(parse_author("ada"), None);
+    }
+
+    #[test]
+    fn test_commit_onto_with_author() {
+        let (fixture, repo) = fixture_with_file();
+        fixture.commit("feat: initial commit");
+        fixture.git(&["branch", "other"]);
+        fixture.write("test.txt", "changed");
+        fixture.stage(&[]);
+
+        let options = CommitOptions {
+            author: Some("Ada Lovelace <ada@example.com>".to_string()),
+            date: Some("2020-01-02T03:04:05Z".to_string()),
+            ..Default::default()
+        };
+        commit_onto_in_repo(&repo, "other", "fix: change", false, &options).unwrap();
+
+        let commit = repo.get_commit("other").unwrap();
+        assert_eq!(commit.author().name(), Some("Ada Lovelace"));
+        assert_eq!(commit.author().email(), Some("ada@example.com"));
+        assert_eq!(commit.author().when().seconds(), 1577934245);
+    }
+
+    #[test]
+    fn test_commit_round_trip_through_git2_and_git_cli() {
+        use crate::commit::CommitMessage;
+
+        let (fixture, repo) = fixture_with_file();
+        fixture.commit("feat: initial commit");
+        fixture.git(&["branch", "via-git2"]);
+        fixture.git(&["branch", "via-cli"]);
+        fixture.git(&["checkout", "-b", "work"]);
+        fixture.write("test.txt", "changed");
+        fixture.stage(&[]);
+
+        let message = "fix(core): handle empty input\n\nEmpty input no longer panics.\n\nRefs: #12";
+        commit_onto_in_repo(&repo, "via-git2", message, false, &CommitOptions::default()).unwrap();
+        // `git.use_cli` sends the commit through `git commit-tree`
+        let options = CommitOptions {
+            use_cli: true,
+            ..Default::default()
+        };
+        assert!(!options.is_empty());
+        commit_onto_in_repo(&repo, "via-cli", message, false, &options).unwrap();
+
+        let via_git2 = repo.get_commit("via-git2").unwrap();
+        let via_cli = repo.get_commit("via-cli").unwrap();
+        assert_eq!(via_git2.tree_id(), via_cli.tree_id());
+        assert_eq!(via_git2.author().name(), via_cli.author().name());
+        assert_eq!(via_git2.author().email(), via_cli.author().email());
+
+        let expected = CommitMessage::parse(message).unwrap().format();
+        for commit in [via_git2, via_cli] {
+            let parsed = CommitMessage::parse(commit.message().unwrap()).unwrap();
+            assert_eq!(parsed.format(), expected);
+        }
+    }
 }
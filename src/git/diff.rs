@@ -4,6 +4,7 @@
 //! Diff operations for analyzing changes.
 
 use crate::error::{CkError, GitError, Result};
+use git2::Oid;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -62,6 +63,13 @@ pub struct FileChange {
     pub old_path: Option<PathBuf>,
 }
 
+impl FileChange {
+    /// Lines added plus lines removed.
+    pub fn total_changes(&self) -> usize {
+        self.lines_added + self.lines_removed
+    }
+}
+
 /// Complete diff information.
 #[derive(Debug, Clone)]
 pub struct DiffInfo {
@@ -153,6 +161,35 @@ fn get_staged_diff_for_repo(repo: &Repository) -> Result<DiffInfo> {
     parse_diff(&diff)
 }
 
+/// Get the diff between the index and the working tree for `paths`.
+///
+/// Paths are matched literally; untracked files among them show as added.
+/// With no paths, every tracked file is diffed.
+pub fn get_unstaged_diff(repo: &Repository, paths: &[PathBuf]) -> Result<DiffInfo> {
+    let mut opts = git2::DiffOptions::new();
+    opts.ignore_submodules(true);
+    if !paths.is_empty() {
+        opts.disable_pathspec_match(true)
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+        for path in paths {
+            opts.pathspec(path);
+        }
+    }
+
+    let diff = repo
+        .inner()
+        .diff_index_to_workdir(None, Some(&mut opts))
+        .map_err(|e| {
+            CkError::Git(GitError::DiffFailed {
+                message: e.message().to_string(),
+            })
+        })?;
+
+    parse_diff(&diff)
+}
+
 /// Get the diff for a specific commit.
 pub fn get_diff(reference: &str) -> Result<DiffInfo> {
     let repo = Repository::open_current()?;
@@ -179,11 +216,34 @@ pub fn get_diff(reference: &str) -> Result<DiffInfo> {
     parse_diff(&diff)
 }
 
+/// Get the diff between two trees.
+pub fn diff_trees(repo: &Repository, old_tree: Option<Oid>, new_tree: Oid) -> Result<DiffInfo> {
+    let git = repo.inner();
+    let diff_error = |e: git2::Error| {
+        CkError::Git(GitError::DiffFailed {
+            message: e.message().to_string(),
+        })
+    };
+
+    let old_tree = old_tree
+        .map(|id| git.find_tree(id))
+        .transpose()
+        .map_err(diff_error)?;
+    let new_tree = git.find_tree(new_tree).map_err(diff_error)?;
+
+    let diff = git
+        .diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)
+        .map_err(diff_error)?;
+
+    parse_diff(&diff)
+}
+
 /// Parse a git2 diff into our DiffInfo structure.
 fn parse_diff(diff: &git2::Diff<'_>) -> Result<DiffInfo> {
     let mut files = Vec::new();
     let mut patches = HashMap::new();
     let mut stats = DiffStats::default();
+    let mut line_counts: HashMap<PathBuf, (usize, usize)> = HashMap::new();
 
     diff.foreach(
         &mut |delta, _| {
@@ -201,7 +261,7 @@ fn parse_diff(diff: &git2::Diff<'_>) -> Result<DiffInfo> {
             };
 
             let change_type = match delta.status() {
-                git2::Delta::Added => ChangeType::Added,
+                git2::Delta::Added | git2::Delta::Untracked => ChangeType::Added,
                 git2::Delta::Deleted => ChangeType::Deleted,
                 git2::Delta::Modified => ChangeType::Modified,
                 git2::Delta::Renamed => ChangeType::Renamed,
@@ -229,10 +289,21 @@ fn parse_diff(diff: &git2::Diff<'_>) -> Result<DiffInfo> {
         },
         None,
         None,
-        Some(&mut |_delta, _hunk, line| {
+        Some(&mut |delta, _hunk, line| {
+            let path = delta.new_file().path().or_else(|| delta.old_file().path());
+            let counts = match path {
+                Some(path) => line_counts.entry(path.to_path_buf()).or_default(),
+                None => return true,
+            };
             match line.origin() {
-                '+' => stats.lines_added += 1,
-                '-' => stats.lines_removed += 1,
+                '+' => {
+                    stats.lines_added += 1;
+                    counts.0 += 1;
+                }
+                '-' => {
+                    stats.lines_removed += 1;
+                    counts.1 += 1;
+                }
                 _ => {}
             }
             true
@@ -245,6 +316,12 @@ fn parse_diff(diff: &git2::Diff<'_>) -> Result<DiffInfo> {
     })?;
 
     stats.files_changed = files.len();
+    for file in &mut files {
+        if let Some(&(added, removed)) = line_counts.get(&file.path) {
+            file.lines_added = added;
+            file.lines_removed = removed;
+        }
+    }
 
     // Get patch content for semantic analysis
     diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
@@ -317,6 +394,8 @@ pub fn diff_summary(info: &DiffInfo) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::FixtureRepo;
+    use std::path::Path;
 
     #[test]
     fn test_diff_stats_total() {
@@ -373,4 +452,61 @@ mod tests {
         assert!(summary.contains("10 insertion"));
         assert!(summary.contains("5 deletion"));
     }
+
+    #[test]
+    fn test_diff_trees_counts_lines_per_file() {
+        let fixture = FixtureRepo::new();
+        fixture
+            .write("a.txt", "one\ntwo\n")
+            .commit("chore: init")
+            .write("a.txt", "one\n2\nthree\n")
+            .write("b.txt", "b\n")
+            .commit("feat: more");
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        let head = repo.head_commit().unwrap();
+        let parent = head.parent(0).unwrap();
+        let info = diff_trees(&repo, Some(parent.tree_id()), head.tree_id()).unwrap();
+
+        let a = info
+            .files
+            .iter()
+            .find(|f| f.path == Path::new("a.txt"))
+            .unwrap();
+        assert_eq!((a.lines_added, a.lines_removed), (2, 1));
+        assert_eq!(a.total_changes(), 3);
+        let b = info
+            .files
+            .iter()
+            .find(|f| f.path == Path::new("b.txt"))
+            .unwrap();
+        assert_eq!(b.total_changes(), 1);
+        assert_eq!(info.stats.total_lines_changed(), 4);
+    }
+
+    #[test]
+    fn test_unstaged_diff() {
+        let fixture = FixtureRepo::new();
+        fixture
+            .write("a.txt", "one\n")
+            .write("b.txt", "b\n")
+            .commit("chore: init")
+            .write("a.txt", "one\ntwo\n")
+            .stage(&["a.txt"])
+            .write("a.txt", "one\ntwo\nthree\n")
+            .write("b.txt", "c\n")
+            .write("new/c.txt", "x\ny\n");
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        let info = get_unstaged_diff(&repo, &[]).unwrap();
+        let paths: Vec<_> = info.files.iter().map(|f| f.path.as_path()).collect();
+        assert_eq!(paths, vec![Path::new("a.txt"), Path::new("b.txt")]);
+        // Only the change the index doesn't have yet
+        assert_eq!(info.files[0].total_changes(), 1);
+
+        let info = get_unstaged_diff(&repo, &[PathBuf::from("new/c.txt")]).unwrap();
+        assert_eq!(info.files.len(), 1);
+        assert_eq!(info.files[0].change_type, ChangeType::Added);
+        assert_eq!(info.files[0].lines_added, 2);
+    }
 }
@@ -7,11 +7,25 @@
 
 pub mod commands;
 pub mod diff;
+pub mod notes;
+pub mod partial;
 mod repo;
+pub mod rewrite;
+pub mod shallow;
+pub mod signing;
+pub mod staging;
+pub mod stash;
+pub mod status;
+pub mod tags;
+pub mod worktree;
 
-pub use commands::{create_commit, stage_all, stage_files};
-pub use diff::{get_diff, get_staged_diff, ChangeType, DiffInfo, DiffStats, FileChange};
+pub use commands::{
+    create_commit, create_commit_with_options, stage_all, stage_files, CommitOptions, CommitTarget,
+};
+pub use diff::{
+    get_diff, get_staged_diff, get_unstaged_diff, ChangeType, DiffInfo, DiffStats, FileChange,
+};
 pub use repo::{
-    get_branch_name, get_commit_message, get_commit_range, get_head_commit, is_git_repo, open_repo,
-    Repository,
+    get_branch_name, get_commit_message, get_commit_range, get_head_commit, get_identity,
+    is_git_repo, is_initial_commit, open_repo, Repository,
 };
@@ -0,0 +1,57 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Git notes recording which commits passed ck validation.
+//!
+//! `refs/notes/ck` marks commits that went through the commit-msg hook or
+//! the builder. Other notes refs hold caches, e.g. range check verdicts.
+
+use crate::error::{CkError, GitError, Result};
+use git2::Oid;
+
+use super::repo::Repository;
+
+/// Notes ref holding ck validation records.
+pub const NOTES_REF: &str = "refs/notes/ck";
+
+/// First line of the note attached to commits that passed validation.
+pub const VALIDATED_NOTE: &str = "ck: validated";
+
+/// Record that `sha` passed ck validation under the configuration with
+/// hash `config_hash`.
+pub fn mark_validated(repo: &Repository, sha: &str, config_hash: &str) -> Result<()> {
+    let oid = Oid::from_str(sha).map_err(|e| notes_error(e.message()))?;
+    let note = format!("{}\nconfig: {}\n", VALIDATED_NOTE, config_hash);
+    write_note(repo, NOTES_REF, oid, &note)
+}
+
+/// Whether `oid` carries the ck validation note.
+pub fn is_validated(repo: &Repository, oid: Oid) -> bool {
+    read_note(repo, NOTES_REF, oid)
+        .is_some_and(|note| note.lines().next().map(str::trim) == Some(VALIDATED_NOTE))
+}
+
+/// The note on `oid` under `notes_ref`, if any.
+pub fn read_note(repo: &Repository, notes_ref: &str, oid: Oid) -> Option<String> {
+    repo.inner()
+        .find_note(Some(notes_ref), oid)
+        .ok()
+        .and_then(|note| note.message().map(str::to_string))
+}
+
+/// Attach `message` to `oid` under `notes_ref`, replacing any note there.
+pub fn write_note(repo: &Repository, notes_ref: &str, oid: Oid, message: &str) -> Result<()> {
+    let git = repo.inner();
+    let signature = git.signature().map_err(|e| notes_error(e.message()))?;
+
+    git.note(&signature, &signature, Some(notes_ref), oid, message, true)
+        .map_err(|e| notes_error(e.message()))?;
+    Ok(())
+}
+
+fn notes_error(message: &str) -> CkError {
+    CkError::Git(GitError::CommandFailed {
+        command: "notes".to_string(),
+        message: message.to_string(),
+    })
+}
@@ -0,0 +1,232 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Partial commits limited to a set of paths.
+
+use crate::error::{CkError, GitError, Result};
+use crate::interrupt::{self, Cleanup};
+use git2::{IndexAddOption, Oid};
+
+use super::repo::Repository;
+use super::stash::AutoStash;
+
+/// The index narrowed to a set of paths for a partial commit.
+///
+/// While alive, the index holds HEAD plus the working tree state of the
+/// given paths only, so analysis and the commit see just those paths.
+/// Other staged changes are set aside and restored by [`finish`] or
+/// [`abort`]; dropping the guard aborts.
+///
+/// [`finish`]: PartialIndex::finish
+/// [`abort`]: PartialIndex::abort
+pub struct PartialIndex<'a> {
+    repo: &'a Repository,
+    original_tree: Oid,
+    paths: Vec<String>,
+    done: bool,
+    _cleanup: Cleanup,
+}
+
+impl<'a> PartialIndex<'a> {
+    /// Stage only `paths`, setting other staged changes aside.
+    ///
+    /// Paths are relative to the current directory, as with `git commit`.
+    pub fn stage_only(repo: &'a Repository, paths: &[String]) -> Result<Self> {
+        let mut index = repo.inner().index().map_err(staging_error)?;
+        let original_tree = index.write_tree().map_err(staging_error)?;
+
+        // Start from HEAD (or an empty index for the initial commit)
+        match repo.head_commit() {
+            Ok(head) => {
+                let tree = head.tree().map_err(staging_error)?;
+                index.read_tree(&tree).map_err(staging_error)?;
+            }
+            Err(_) => index.clear().map_err(staging_error)?,
+        }
+
+        let workdir = repo.workdir().to_path_buf();
+        let cleanup = interrupt::on_interrupt("restore the index", move || {
+            if let Ok(repo) = Repository::open(&workdir) {
+                let _ = restore_index(repo.inner(), original_tree);
+            }
+        });
+
+        let guard = Self {
+            repo,
+            original_tree,
+            paths: paths.iter().map(|p| repo_relative(repo, p)).collect(),
+            done: false,
+            _cleanup: cleanup,
+        };
+        guard.stage_paths(&mut index)?;
+
+        Ok(guard)
+    }
+
+    /// Restore the other staged changes after the partial commit.
+    ///
+    /// The committed paths stay staged at their committed state.
+    pub fn finish(mut self) -> Result<()> {
+        self.done = true;
+        let mut index = self.restore_original()?;
+        self.stage_paths(&mut index)
+    }
+
+    /// Restore the index exactly as it was before.
+    pub fn abort(mut self) -> Result<()> {
+        self.done = true;
+        self.restore_original().map(|_| ())
+    }
+
+    /// Stage the working tree state of the selected paths.
+    fn stage_paths(&self, index: &mut git2::Index) -> Result<()> {
+        index
+            .add_all(self.paths.iter(), IndexAddOption::DEFAULT, None)
+            .map_err(staging_error)?;
+        index
+            .update_all(self.paths.iter(), None)
+            .map_err(staging_error)?;
+        index.write().map_err(staging_error)
+    }
+
+    /// Reset the index to the tree captured before narrowing.
+    fn restore_original(&self) -> Result<git2::Index> {
+        restore_index(self.repo.inner(), self.original_tree)
+    }
+}
+
+/// Put back what a commit set aside: the other staged changes of a partial
+/// commit, then the autostashed changes.
+///
+/// The autostash is restored even when restoring the index fails, so
+/// unstaged work is never left behind in the stash. The first failure is
+/// returned.
+pub fn restore_after_commit(
+    partial: Option<PartialIndex<'_>>,
+    autostash: Option<AutoStash>,
+    committed: bool,
+) -> Result<()> {
+    let index = match partial {
+        Some(partial) if committed => partial.finish(),
+        Some(partial) => partial.abort(),
+        None => Ok(()),
+    };
+    let stash = autostash.map_or(Ok(()), AutoStash::restore);
+    index.and(stash)
+}
+
+/// Reset the index of `git` to `tree`.
+fn restore_index(git: &git2::Repository, tree: Oid) -> Result<git2::Index> {
+    let mut index = git.index().map_err(staging_error)?;
+    let tree = git.find_tree(tree).map_err(staging_error)?;
+    index.read_tree(&tree).map_err(staging_error)?;
+    index.write().map_err(staging_error)?;
+    Ok(index)
+}
+
+impl Drop for PartialIndex<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            if let Err(e) = self.restore_original() {
+                tracing::warn!("Failed to restore staged changes: {}", e);
+            }
+        }
+    }
+}
+
+/// Make a pathspec relative to the repository root.
+pub(super) fn repo_relative(repo: &Repository, path: &str) -> String {
+    let prefix = std::env::current_dir()
+        .and_then(|cwd| cwd.canonicalize())
+        .ok()
+        .zip(repo.workdir().canonicalize().ok())
+        .and_then(|(cwd, root)| cwd.strip_prefix(&root).ok().map(|p| p.to_path_buf()));
+
+    match prefix {
+        Some(prefix) if !prefix.as_os_str().is_empty() => {
+            prefix.join(path).to_string_lossy().replace('\\', "/")
+        }
+        _ => path.to_string(),
+    }
+}
+
+fn staging_error(e: git2::Error) -> CkError {
+    CkError::Git(GitError::CommandFailed {
+        command: "index".to_string(),
+        message: e.message().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixtureRepo;
+
+    fn create_repo() -> (FixtureRepo, Repository) {
+        let fixture = FixtureRepo::new();
+        fixture
+            .write("a.txt", "a")
+            .write("b.txt", "b")
+            .commit("feat: initial commit")
+            .write("a.txt", "a2")
+            .write("b.txt", "b2")
+            .stage(&["b.txt"]);
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        (fixture, repo)
+    }
+
+    #[test]
+    fn test_partial_commit_keeps_other_staged() {
+        let (fixture, repo) = create_repo();
+
+        let partial = PartialIndex::stage_only(&repo, &["a.txt".to_string()]).unwrap();
+        assert_eq!(fixture.git(&["diff", "--cached", "--name-only"]), "a.txt\n");
+
+        fixture.git(&["commit", "-m", "fix: update a"]);
+        partial.finish().unwrap();
+
+        assert_eq!(fixture.git(&["diff", "--cached", "--name-only"]), "b.txt\n");
+        assert_eq!(
+            fixture.git(&["show", "--name-only", "--format="]),
+            "a.txt\n"
+        );
+    }
+
+    #[test]
+    fn test_abort_restores_index() {
+        let (fixture, repo) = create_repo();
+
+        let partial = PartialIndex::stage_only(&repo, &["a.txt".to_string()]).unwrap();
+        drop(partial);
+
+        assert_eq!(fixture.git(&["diff", "--cached", "--name-only"]), "b.txt\n");
+    }
+
+    #[test]
+    fn test_autostash_restored_when_index_restore_fails() {
+        // Lookups must reach the object database for the tree to be lost
+        git2::opts::enable_caching(false);
+        let (fixture, repo) = create_repo();
+        fixture.write("c.txt", "untracked");
+
+        let partial = PartialIndex::stage_only(&repo, &["a.txt".to_string()]).unwrap();
+        let autostash = AutoStash::push(fixture.path()).unwrap();
+        assert!(!fixture.join("c.txt").exists());
+
+        // Lose the tree the staged changes are restored from
+        let tree = partial.original_tree.to_string();
+        std::fs::remove_file(fixture.join(&format!(".git/objects/{}/{}", &tree[..2], &tree[2..])))
+            .unwrap();
+
+        assert!(restore_after_commit(Some(partial), Some(autostash), true).is_err());
+        assert_eq!(
+            std::fs::read_to_string(fixture.join("c.txt")).unwrap(),
+            "untracked"
+        );
+        assert_eq!(
+            std::fs::read_to_string(fixture.join("b.txt")).unwrap(),
+            "b2"
+        );
+    }
+}
@@ -61,8 +61,33 @@ impl Repository {
         &self.workdir
     }
 
+    /// Check if HEAD points to a branch with no commits yet.
+    pub fn is_unborn(&self) -> bool {
+        matches!(
+            self.inner.head(),
+            Err(ref e) if e.code() == git2::ErrorCode::UnbornBranch
+        )
+    }
+
     /// Get the current branch name.
+    ///
+    /// Before the first commit this is the branch HEAD points to.
     pub fn branch_name(&self) -> Result<String> {
+        if self.is_unborn() {
+            let target = self
+                .inner
+                .find_reference("HEAD")
+                .ok()
+                .and_then(|head| head.symbolic_target().map(str::to_string));
+            return match target {
+                Some(target) => Ok(target
+                    .strip_prefix("refs/heads/")
+                    .unwrap_or(&target)
+                    .to_string()),
+                None => Err(CkError::Git(GitError::DetachedHead)),
+            };
+        }
+
         let head = self.inner.head().map_err(|e| {
             if e.code() == git2::ErrorCode::UnbornBranch {
                 CkError::Git(GitError::DetachedHead)
@@ -88,9 +113,13 @@ impl Repository {
     /// Get the HEAD commit.
     pub fn head_commit(&self) -> Result<git2::Commit<'_>> {
         let head = self.inner.head().map_err(|e| {
-            CkError::Git(GitError::BranchFailed {
-                message: e.message().to_string(),
-            })
+            if e.code() == git2::ErrorCode::UnbornBranch {
+                CkError::Git(GitError::NoCommits)
+            } else {
+                CkError::Git(GitError::BranchFailed {
+                    message: e.message().to_string(),
+                })
+            }
         })?;
 
         let commit = head.peel_to_commit().map_err(|e| {
@@ -105,9 +134,13 @@ impl Repository {
     /// Get a commit by reference (SHA, branch name, etc.).
     pub fn get_commit(&self, reference: &str) -> Result<git2::Commit<'_>> {
         let obj = self.inner.revparse_single(reference).map_err(|e| {
-            CkError::Git(GitError::InvalidReference {
-                reference: format!("{}: {}", reference, e.message()),
-            })
+            if self.is_unborn() {
+                CkError::Git(GitError::NoCommits)
+            } else {
+                CkError::Git(GitError::InvalidReference {
+                    reference: format!("{}: {}", reference, e.message()),
+                })
+            }
         })?;
 
         let commit = obj.peel_to_commit().map_err(|e| {
@@ -143,7 +176,13 @@ impl Repository {
         if range.contains("..") {
             let parts: Vec<&str> = range.split("..").collect();
             if parts.len() == 2 {
-                let from = self.get_commit(parts[0])?;
+                // In a shallow clone the base may not have been fetched;
+                // the walk then ends at the shallow boundary
+                let from = match self.get_commit(parts[0]) {
+                    Ok(from) => Some(from),
+                    Err(_) if self.inner.is_shallow() => None,
+                    Err(e) => return Err(e),
+                };
                 let to = self.get_commit(parts[1])?;
 
                 revwalk.push(to.id()).map_err(|e| {
@@ -152,12 +191,14 @@ impl Repository {
                         message: e.message().to_string(),
                     })
                 })?;
-                revwalk.hide(from.id()).map_err(|e| {
-                    CkError::Git(GitError::CommandFailed {
-                        command: "revwalk.hide".to_string(),
-                        message: e.message().to_string(),
-                    })
-                })?;
+                if let Some(from) = from {
+                    revwalk.hide(from.id()).map_err(|e| {
+                        CkError::Git(GitError::CommandFailed {
+                            command: "revwalk.hide".to_string(),
+                            message: e.message().to_string(),
+                        })
+                    })?;
+                }
             }
         } else {
             // Single reference, get that commit only
@@ -188,6 +229,30 @@ impl Repository {
         Ok(commits)
     }
 
+    /// Get the messages of the most recent commits reachable from HEAD.
+    ///
+    /// Returns an empty list for a repository without commits.
+    pub fn recent_commit_messages(&self, limit: usize) -> Result<Vec<String>> {
+        let mut revwalk = self.inner.revwalk().map_err(|e| {
+            CkError::Git(GitError::CommandFailed {
+                command: "revwalk".to_string(),
+                message: e.message().to_string(),
+            })
+        })?;
+        if revwalk.push_head().is_err() {
+            return Ok(Vec::new());
+        }
+
+        let messages = revwalk
+            .filter_map(|oid| oid.ok())
+            .filter_map(|oid| self.inner.find_commit(oid).ok())
+            .take(limit)
+            .map(|commit| commit.message().unwrap_or("").to_string())
+            .collect();
+
+        Ok(messages)
+    }
+
     /// Check if there are staged changes.
     pub fn has_staged_changes(&self) -> Result<bool> {
         let head = self.inner.head().ok();
@@ -209,6 +274,21 @@ impl Repository {
     pub fn git_dir(&self) -> &Path {
         self.inner.path()
     }
+
+    /// Whether a merge is in progress, so the next commit is a merge.
+    pub fn is_merging(&self) -> bool {
+        self.inner.state() == git2::RepositoryState::Merge
+    }
+
+    /// Get the git directory shared by all worktrees.
+    pub fn common_dir(&self) -> PathBuf {
+        let git_dir = self.git_dir();
+        // Linked worktrees point at the shared git dir via `commondir`
+        std::fs::read_to_string(git_dir.join("commondir"))
+            .ok()
+            .map(|common| git_dir.join(common.trim()))
+            .unwrap_or_else(|| git_dir.to_path_buf())
+    }
 }
 
 /// Open the repository from the current directory.
@@ -221,12 +301,26 @@ pub fn is_git_repo() -> bool {
     Repository::open_current().is_ok()
 }
 
+/// Check if the next commit in the current repository is its first.
+pub fn is_initial_commit() -> bool {
+    Repository::open_current()
+        .map(|repo| repo.is_unborn())
+        .unwrap_or(false)
+}
+
 /// Get the current branch name.
 pub fn get_branch_name() -> Result<String> {
     let repo = Repository::open_current()?;
     repo.branch_name()
 }
 
+/// Get the committer identity from git config as `Name <email>`.
+pub fn get_identity() -> Option<String> {
+    let repo = Repository::open_current().ok()?;
+    let signature = repo.inner().signature().ok()?;
+    Some(format!("{} <{}>", signature.name()?, signature.email()?))
+}
+
 /// Get the HEAD commit OID.
 pub fn get_head_commit() -> Result<String> {
     let repo = Repository::open_current()?;
@@ -253,6 +347,7 @@ pub fn get_commit_range(range: &str) -> Result<Vec<(String, String)>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::FixtureRepo;
     use tempfile::TempDir;
 
     fn create_test_repo() -> (TempDir, Repository) {
@@ -305,4 +400,21 @@ mod tests {
         let branch = repo.branch_name().unwrap();
         assert!(!branch.is_empty());
     }
+
+    #[test]
+    fn test_unborn_branch() {
+        let fixture = FixtureRepo::new();
+        fixture.git(&["symbolic-ref", "HEAD", "refs/heads/trunk"]);
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        assert!(repo.is_unborn());
+        assert_eq!(repo.branch_name().unwrap(), "trunk");
+        assert!(matches!(
+            repo.get_commit("HEAD"),
+            Err(CkError::Git(GitError::NoCommits))
+        ));
+
+        let (_dir, repo) = create_test_repo();
+        assert!(!repo.is_unborn());
+    }
 }
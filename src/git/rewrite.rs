@@ -0,0 +1,496 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! History rewriting for rewording and signing past commits.
+
+use crate::error::{CkError, GitError, Result};
+use crate::interrupt;
+use crate::progress::Progress;
+use git2::{BranchType, ObjectType, Oid, Sort};
+use std::collections::HashMap;
+
+use super::repo::Repository;
+
+/// Headers that hold a signature over the rest of the commit object.
+const SIGNATURE_HEADERS: &[&str] = &["gpgsig", "gpgsig-sha256"];
+
+/// Outcome of rewriting commits.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Rewritten {
+    /// SHA of the new HEAD.
+    pub new_head: String,
+    /// Original SHAs of unsigned commits that were signed.
+    pub signed: Vec<String>,
+    /// Original SHAs of rewritten commits whose signatures were dropped.
+    pub dropped_signatures: Vec<String>,
+    /// Original SHAs of rewritten commits that were signed again.
+    pub resigned: Vec<String>,
+}
+
+/// Replay the commits between `base` (exclusive) and HEAD, replacing the
+/// messages of the commits listed in `messages` (keyed by full SHA).
+///
+/// Trees, authors, committers, the message encoding and any other headers
+/// are kept, so the working tree and index are untouched. Commits after
+/// the first reworded one get new SHAs, which invalidates their
+/// signatures. With `resign`, signed commits authored by the current user
+/// are signed again with their configured key; other signatures are
+/// dropped. Merge commits in the range are rejected.
+pub fn reword_commits(
+    repo: &Repository,
+    base: &str,
+    messages: &HashMap<String, String>,
+    resign: bool,
+) -> Result<Rewritten> {
+    replay(repo, base, messages, false, resign, "reword")
+}
+
+/// Sign the unsigned commits authored by the current user between `base`
+/// (exclusive) and HEAD with their configured key.
+///
+/// Signed commits after the first newly signed one get new SHAs; the
+/// user's own are signed again, other signatures are dropped. Merge
+/// commits in the range are rejected.
+pub fn sign_commits(repo: &Repository, base: &str) -> Result<Rewritten> {
+    replay(repo, base, &HashMap::new(), true, true, "sign")
+}
+
+/// Original SHAs of the unsigned commits authored by the current user
+/// between `base` and HEAD.
+pub fn unsigned_own_commits(repo: &Repository, base: &str) -> Result<Vec<String>> {
+    let git = repo.inner();
+    let rewriter = rewriter_email(repo);
+    let mut unsigned = Vec::new();
+    for oid in replayed_commits(repo, base)? {
+        let commit = git
+            .find_commit(oid)
+            .map_err(|e| rewrite_error("find_commit", e))?;
+        if is_own(&commit, rewriter.as_deref()) && git.extract_signature(&oid, None).is_err() {
+            unsigned.push(oid.to_string());
+        }
+    }
+    Ok(unsigned)
+}
+
+/// Replay the commits between `base` and HEAD with the new `messages`,
+/// signing the user's unsigned commits with `sign_unsigned` and their
+/// signed ones that change with `resign`.
+fn replay(
+    repo: &Repository,
+    base: &str,
+    messages: &HashMap<String, String>,
+    sign_unsigned: bool,
+    resign: bool,
+    command: &str,
+) -> Result<Rewritten> {
+    let git = repo.inner();
+    let head = repo.head_commit()?;
+    let oids = replayed_commits(repo, base)?;
+    let rewriter = rewriter_email(repo);
+
+    // Put the branch back if interrupted once it has moved
+    let workdir = repo.workdir().to_path_buf();
+    let original_head = head.id();
+    let _cleanup = interrupt::on_interrupt("abort the history rewrite", move || {
+        if let Ok(repo) = Repository::open(&workdir) {
+            let _ = move_head(&repo, original_head);
+        }
+    });
+
+    // Map of original commit to its replacement
+    let mut rewritten: HashMap<Oid, Oid> = HashMap::new();
+    let mut outcome = Rewritten::default();
+    let mut new_head = head.id();
+    let progress = Progress::bar(oids.len(), "Rewriting commits");
+
+    for oid in oids {
+        interrupt::checkpoint();
+        progress.inc();
+        let commit = git
+            .find_commit(oid)
+            .map_err(|e| rewrite_error("find_commit", e))?;
+
+        if commit.parent_count() > 1 {
+            return Err(CkError::Git(GitError::CommandFailed {
+                command: command.to_string(),
+                message: format!(
+                    "Cannot {} across merge commit {}; rebase it manually",
+                    command,
+                    &oid.to_string()[..7]
+                ),
+            }));
+        }
+
+        let new_message = messages.get(&oid.to_string());
+        let parents: Vec<Oid> = commit
+            .parent_ids()
+            .map(|p| rewritten.get(&p).copied().unwrap_or(p))
+            .collect();
+        let parents_changed = commit.parent_ids().ne(parents.iter().copied());
+        let own = is_own(&commit, rewriter.as_deref());
+        let to_sign = sign_unsigned && own && git.extract_signature(&oid, None).is_err();
+
+        if new_message.is_none() && !parents_changed && !to_sign {
+            new_head = oid;
+            continue;
+        }
+
+        let (mut buffer, signed) =
+            commit_buffer(&commit, &parents, new_message.map(String::as_str));
+        if to_sign || (signed && resign && own) {
+            let signature = super::signing::sign_buffer(repo, &buffer)?;
+            buffer = add_signature(&buffer, &signature);
+            if signed {
+                outcome.resigned.push(oid.to_string());
+            } else {
+                outcome.signed.push(oid.to_string());
+            }
+        } else if signed {
+            outcome.dropped_signatures.push(oid.to_string());
+        }
+
+        let new_oid = git
+            .odb()
+            .and_then(|odb| odb.write(ObjectType::Commit, &buffer))
+            .map_err(|e| {
+                CkError::Git(GitError::CommitFailed {
+                    message: e.message().to_string(),
+                })
+            })?;
+
+        rewritten.insert(oid, new_oid);
+        new_head = new_oid;
+    }
+
+    if new_head != head.id() {
+        move_head(repo, new_head)?;
+    }
+
+    outcome.new_head = new_head.to_string();
+    Ok(outcome)
+}
+
+/// Email of the user running the rewrite, from git's configuration.
+fn rewriter_email(repo: &Repository) -> Option<String> {
+    let signature = repo.inner().signature().ok()?;
+    signature.email().map(str::to_string)
+}
+
+/// Whether `commit` was authored by the user with `email`.
+fn is_own(commit: &git2::Commit<'_>, email: Option<&str>) -> bool {
+    email.is_some() && commit.author().email() == email
+}
+
+/// Original SHAs of the signed commits that rewording `messages` between
+/// `base` and HEAD replaces: the first reworded commit and all after it.
+pub fn signed_commits_replaced(
+    repo: &Repository,
+    base: &str,
+    messages: &HashMap<String, String>,
+) -> Result<Vec<String>> {
+    let git = repo.inner();
+    let mut signed = Vec::new();
+    let mut replaced = false;
+    for oid in replayed_commits(repo, base)? {
+        replaced = replaced || messages.contains_key(&oid.to_string());
+        if replaced && git.extract_signature(&oid, None).is_ok() {
+            signed.push(oid.to_string());
+        }
+    }
+    Ok(signed)
+}
+
+/// The commits between `base` (exclusive) and HEAD, oldest first.
+fn replayed_commits(repo: &Repository, base: &str) -> Result<Vec<Oid>> {
+    let git = repo.inner();
+    let base_commit = repo.get_commit(base)?;
+    let head = repo.head_commit()?;
+
+    let mut revwalk = git.revwalk().map_err(|e| rewrite_error("revwalk", e))?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .map_err(|e| rewrite_error("revwalk.sort", e))?;
+    revwalk
+        .push(head.id())
+        .map_err(|e| rewrite_error("revwalk.push", e))?;
+    revwalk
+        .hide(base_commit.id())
+        .map_err(|e| rewrite_error("revwalk.hide", e))?;
+
+    revwalk
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|e| rewrite_error("revwalk", e))
+}
+
+/// The raw object for `commit` with new parents and, optionally, a new
+/// message, and whether the original was signed.
+///
+/// Every header other than the tree, parents and signature is copied as
+/// it is. A new message is UTF-8, so a legacy `encoding` header is only
+/// kept with the original message.
+fn commit_buffer(
+    commit: &git2::Commit<'_>,
+    parents: &[Oid],
+    message: Option<&str>,
+) -> (Vec<u8>, bool) {
+    let mut buffer = format!("tree {}\n", commit.tree_id()).into_bytes();
+    for parent in parents {
+        buffer.extend_from_slice(format!("parent {}\n", parent).as_bytes());
+    }
+
+    let mut signed = false;
+    let mut skipping = false;
+    for line in commit.raw_header_bytes().split(|&b| b == b'\n') {
+        // Continuation lines belong to the header before them
+        if line.first() == Some(&b' ') {
+            if !skipping {
+                buffer.extend_from_slice(line);
+                buffer.push(b'\n');
+            }
+            continue;
+        }
+        let name = line.split(|&b| b == b' ').next().unwrap_or_default();
+        let is_signature = SIGNATURE_HEADERS.iter().any(|h| h.as_bytes() == name);
+        signed |= is_signature;
+        skipping = line.is_empty()
+            || is_signature
+            || name == b"tree"
+            || name == b"parent"
+            || (name == b"encoding" && message.is_some() && !is_utf8(&line[name.len()..]));
+        if !skipping {
+            buffer.extend_from_slice(line);
+            buffer.push(b'\n');
+        }
+    }
+
+    buffer.push(b'\n');
+    match message {
+        Some(message) => buffer.extend_from_slice(message.as_bytes()),
+        None => buffer.extend_from_slice(commit.message_raw_bytes()),
+    }
+    (buffer, signed)
+}
+
+/// Add a `gpgsig` header with `signature` to an unsigned commit object.
+fn add_signature(buffer: &[u8], signature: &str) -> Vec<u8> {
+    let header_end = buffer
+        .windows(2)
+        .position(|w| w == b"\n\n")
+        .map(|i| i + 1)
+        .unwrap_or(buffer.len());
+    let mut signed = buffer[..header_end].to_vec();
+    signed.extend_from_slice(b"gpgsig ");
+    signed.extend_from_slice(signature.trim_end().replace('\n', "\n ").as_bytes());
+    signed.push(b'\n');
+    signed.extend_from_slice(&buffer[header_end..]);
+    signed
+}
+
+fn is_utf8(encoding: &[u8]) -> bool {
+    let encoding = String::from_utf8_lossy(encoding).trim().to_lowercase();
+    encoding == "utf-8" || encoding == "utf8"
+}
+
+/// Remote-tracking branches that already contain commits a rewrite replaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Published {
+    /// Remote-tracking branches containing the commits, e.g. `origin/main`.
+    pub branches: Vec<String>,
+    /// Commands that force-push the rewritten branch to those of the
+    /// branches it tracks or shares a name with.
+    pub push_commands: Vec<String>,
+}
+
+impl Published {
+    /// Find the remote-tracking branches containing `first`, the oldest
+    /// commit a rewrite replaces. Every other replaced commit descends from
+    /// it, so no branch can have one without it. `None` when no remote
+    /// branch has it.
+    pub fn find(repo: &Repository, first: &str) -> Result<Option<Self>> {
+        let git = repo.inner();
+        let first = repo.get_commit(first)?.id();
+        let local = repo.branch_name().ok();
+        let upstream = local
+            .as_deref()
+            .and_then(|name| git.find_branch(name, BranchType::Local).ok())
+            .and_then(|branch| branch.upstream().ok())
+            .and_then(|upstream| upstream.name().ok().flatten().map(str::to_string));
+
+        let mut published = Self {
+            branches: Vec::new(),
+            push_commands: Vec::new(),
+        };
+        let remote_branches = git
+            .branches(Some(BranchType::Remote))
+            .map_err(|e| rewrite_error("branch", e))?;
+        for entry in remote_branches {
+            let (branch, _) = entry.map_err(|e| rewrite_error("branch", e))?;
+            // Symbolic refs such as origin/HEAD have no target of their own
+            let (name, tip) = match (branch.name(), branch.get().target()) {
+                (Ok(Some(name)), Some(tip)) => (name.to_string(), tip),
+                _ => continue,
+            };
+            if tip != first && !git.graph_descendant_of(tip, first).unwrap_or(false) {
+                continue;
+            }
+
+            if let (Some(local), Some((remote, remote_branch))) = (&local, name.split_once('/')) {
+                if upstream.as_deref() == Some(name.as_str()) || remote_branch == local {
+                    published.push_commands.push(format!(
+                        "git push --force-with-lease {} {}:{}",
+                        remote, local, remote_branch
+                    ));
+                }
+            }
+            published.branches.push(name);
+        }
+
+        Ok((!published.branches.is_empty()).then_some(published))
+    }
+}
+
+/// Point HEAD (or the branch it refers to) at a new commit.
+fn move_head(repo: &Repository, target: Oid) -> Result<()> {
+    let git = repo.inner();
+    let head = git.head().map_err(|e| rewrite_error("head", e))?;
+
+    if head.is_branch() {
+        let name = head.name().unwrap_or("HEAD").to_string();
+        git.reference(&name, target, true, "ck: reword commits")
+            .map_err(|e| rewrite_error("update-ref", e))?;
+    } else {
+        git.set_head_detached(target)
+            .map_err(|e| rewrite_error("update-ref", e))?;
+    }
+
+    Ok(())
+}
+
+fn rewrite_error(command: &str, e: git2::Error) -> CkError {
+    CkError::Git(GitError::CommandFailed {
+        command: command.to_string(),
+        message: e.message().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixtureRepo;
+
+    fn create_repo_with_commits(subjects: &[&str]) -> (FixtureRepo, Repository) {
+        let fixture = FixtureRepo::new();
+        for (i, subject) in subjects.iter().enumerate() {
+            fixture.write("file.txt", &i.to_string()).commit(subject);
+        }
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        (fixture, repo)
+    }
+
+    #[test]
+    fn test_published_commits() {
+        let (fixture, repo) =
+            create_repo_with_commits(&["feat: initial commit", "fix: the bug", "docs: readme"]);
+        let branch = repo.branch_name().unwrap();
+        fixture.git(&[
+            "update-ref",
+            &format!("refs/remotes/origin/{}", branch),
+            "HEAD~1",
+        ]);
+        fixture.git(&["update-ref", "refs/remotes/origin/old", "HEAD~2"]);
+
+        let published = Published::find(&repo, "HEAD~1").unwrap().unwrap();
+        assert_eq!(published.branches, [format!("origin/{}", branch)]);
+        assert_eq!(
+            published.push_commands,
+            [format!(
+                "git push --force-with-lease origin {0}:{0}",
+                branch
+            )]
+        );
+        assert!(Published::find(&repo, "HEAD").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_reword_middle_commit() {
+        let (_fixture, repo) = create_repo_with_commits(&[
+            "feat: initial commit",
+            "Fixed the bug.",
+            "docs: update readme",
+        ]);
+        let target = repo.get_commit("HEAD~1").unwrap().id().to_string();
+        let old_tree = repo.head_commit().unwrap().tree_id();
+
+        let mut messages = HashMap::new();
+        messages.insert(target, "fix: handle the bug".to_string());
+        reword_commits(&repo, "HEAD~2", &messages, false).unwrap();
+
+        assert_eq!(
+            repo.get_commit_message("HEAD~1").unwrap(),
+            "fix: handle the bug"
+        );
+        assert_eq!(
+            repo.get_commit_message("HEAD").unwrap().trim(),
+            "docs: update readme"
+        );
+        assert_eq!(repo.head_commit().unwrap().tree_id(), old_tree);
+    }
+
+    #[test]
+    fn test_reword_nothing() {
+        let (_fixture, repo) = create_repo_with_commits(&["feat: one", "feat: two"]);
+        let head = repo.head_commit().unwrap().id().to_string();
+        let reworded = reword_commits(&repo, "HEAD~1", &HashMap::new(), false).unwrap();
+        assert_eq!(reworded.new_head, head);
+    }
+
+    #[test]
+    fn test_reword_keeps_headers_and_drops_signatures() {
+        let (fixture, repo) =
+            create_repo_with_commits(&["feat: initial commit", "Fixed the bug.", "docs: readme"]);
+        let git_repo = repo.inner();
+        let head = repo.head_commit().unwrap();
+
+        // Replace HEAD with a signed commit carrying extra headers
+        let mut raw = format!(
+            "tree {}\nparent {}\nauthor {ident}\ncommitter {ident}\nencoding ISO-8859-1\n\
+             x-review approved\n line two\ngpgsig -----BEGIN SSH SIGNATURE-----\n \
+             c2lnbmF0dXJl\n -----END SSH SIGNATURE-----\n\n",
+            head.tree_id(),
+            head.parent_id(0).unwrap(),
+            ident = "Test User <test@example.com> 1700000000 +0000",
+        )
+        .into_bytes();
+        let message = b"docs: r\xe9sum\xe9\n";
+        raw.extend_from_slice(message);
+        let signed = git_repo
+            .odb()
+            .unwrap()
+            .write(ObjectType::Commit, &raw)
+            .unwrap();
+        fixture.git(&["reset", "-q", "--hard", &signed.to_string()]);
+
+        let target = repo.get_commit("HEAD~1").unwrap().id().to_string();
+        let mut messages = HashMap::new();
+        messages.insert(target, "fix: handle the bug".to_string());
+        assert_eq!(
+            signed_commits_replaced(&repo, "HEAD~2", &messages).unwrap(),
+            [signed.to_string()]
+        );
+
+        let reworded = reword_commits(&repo, "HEAD~2", &messages, false).unwrap();
+        assert_eq!(reworded.dropped_signatures, [signed.to_string()]);
+        assert!(reworded.resigned.is_empty());
+
+        let new_head = repo.head_commit().unwrap();
+        let header = String::from_utf8_lossy(new_head.raw_header_bytes()).to_string();
+        assert!(header.contains("encoding ISO-8859-1\nx-review approved\n line two"));
+        assert!(!header.contains("gpgsig"));
+        assert_eq!(new_head.message_raw_bytes(), message);
+        assert_eq!(
+            repo.get_commit_message("HEAD~1").unwrap(),
+            "fix: handle the bug"
+        );
+    }
+}
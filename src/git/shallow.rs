@@ -0,0 +1,175 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Range checks in shallow clones.
+//!
+//! CI systems often clone with `--depth`, so the base of a range may be
+//! missing or its history cut off. The range is then walked only down to
+//! the shallow boundary; it can be deepened with `git fetch --deepen` until
+//! the base is reached.
+
+use crate::error::{CkError, GitError, Result};
+use console::style;
+use std::process::Command;
+
+use super::repo::Repository;
+
+/// Commits fetched per deepening step.
+pub const DEEPEN_STEP: u32 = 100;
+
+/// Deepening steps tried before giving up.
+pub const MAX_DEEPEN_STEPS: usize = 10;
+
+/// A range whose base is beyond the shallow boundary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialRange {
+    /// The requested range.
+    pub range: String,
+    /// Commits where the fetched history ends.
+    pub boundary: Vec<String>,
+}
+
+impl PartialRange {
+    /// Report how much of the range was validated.
+    pub fn print(&self, validated: usize) {
+        let boundary: Vec<_> = self
+            .boundary
+            .iter()
+            .map(|sha| &sha[..7.min(sha.len())])
+            .collect();
+        eprintln!(
+            "{} Shallow clone: validated {} commit(s) of {}; history ends at {}",
+            style("⚠").yellow(),
+            validated,
+            self.range,
+            boundary.join(", ")
+        );
+        eprintln!(
+            "  {} Pass --auto-deepen or fetch more history (git fetch --deepen=<n>)",
+            style("→").dim()
+        );
+    }
+}
+
+/// Commits at the shallow boundary, if the repository is shallow.
+pub fn boundary(repo: &Repository) -> Vec<String> {
+    if !repo.inner().is_shallow() {
+        return Vec::new();
+    }
+    std::fs::read_to_string(repo.common_dir().join("shallow"))
+        .map(|content| content.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Check if the base of `from..to` is present and connected to `to`.
+pub fn is_complete(repo: &Repository, range: &str) -> bool {
+    let (from, to) = match range.split_once("..") {
+        Some(parts) => parts,
+        None => return true,
+    };
+    let (from, to) = match (repo.get_commit(from), repo.get_commit(to)) {
+        (Ok(from), Ok(to)) => (from.id(), to.id()),
+        _ => return false,
+    };
+    repo.inner().merge_base(from, to).is_ok()
+}
+
+/// Fetch `depth` more commits of history from the default remote.
+pub fn deepen(repo: &Repository, depth: u32) -> Result<()> {
+    let output = Command::new("git")
+        .args(["fetch", "--quiet", &format!("--deepen={}", depth)])
+        .current_dir(repo.workdir())
+        .output()
+        .map_err(|e| {
+            CkError::Git(GitError::CommandFailed {
+                command: "git fetch --deepen".to_string(),
+                message: e.to_string(),
+            })
+        })?;
+
+    if !output.status.success() {
+        return Err(CkError::Git(GitError::CommandFailed {
+            command: "git fetch --deepen".to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }));
+    }
+
+    Ok(())
+}
+
+/// Make sure a range can be walked in full.
+///
+/// In a shallow clone with `auto_deepen`, history is fetched until the
+/// range base is reached. Returns the partial range if the base is still
+/// beyond the boundary.
+pub fn prepare_range(
+    repo: &Repository,
+    range: &str,
+    auto_deepen: bool,
+) -> Result<Option<PartialRange>> {
+    if !repo.inner().is_shallow() || is_complete(repo, range) {
+        return Ok(None);
+    }
+
+    let mut boundary_now = boundary(repo);
+    if auto_deepen {
+        for _ in 0..MAX_DEEPEN_STEPS {
+            deepen(repo, DEEPEN_STEP)?;
+            // Reopen so the fetched objects and new boundary are seen
+            let deepened = Repository::open(repo.workdir())?;
+            if !deepened.inner().is_shallow() || is_complete(&deepened, range) {
+                return Ok(None);
+            }
+            let before = std::mem::replace(&mut boundary_now, boundary(&deepened));
+            if boundary_now == before {
+                break;
+            }
+        }
+    }
+
+    Ok(Some(PartialRange {
+        range: range.to_string(),
+        boundary: boundary_now,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixtureRepo;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_shallow_range() {
+        let origin = FixtureRepo::new();
+        for i in 0..5 {
+            origin.commit(&format!("feat: add {}", i));
+        }
+        let base = origin.git(&["rev-parse", "HEAD~4"]).trim().to_string();
+
+        let dir = TempDir::new().unwrap();
+        let url = format!("file://{}", origin.path().display());
+        let target = dir.path().join("clone");
+        origin.git(&[
+            "clone",
+            "--quiet",
+            "--depth",
+            "2",
+            &url,
+            target.to_str().unwrap(),
+        ]);
+        let clone = Repository::open(&dir.path().join("clone")).unwrap();
+        let range = format!("{}..HEAD", base);
+
+        // The walk stops at the boundary instead of failing
+        assert_eq!(boundary(&clone).len(), 1);
+        assert!(!is_complete(&clone, &range));
+        assert_eq!(clone.get_commits_in_range(&range).unwrap().len(), 2);
+        let partial = prepare_range(&clone, &range, false).unwrap().unwrap();
+        assert_eq!(partial.boundary, boundary(&clone));
+
+        assert_eq!(prepare_range(&clone, &range, true).unwrap(), None);
+        let clone = Repository::open(&dir.path().join("clone")).unwrap();
+        assert_eq!(clone.get_commits_in_range(&range).unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,325 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Signing key health checks run before signed commits, and signing of
+//! commit objects ck writes itself.
+
+use crate::error::{CkError, Result};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use super::repo::Repository;
+
+/// State of an OpenPGP secret key as reported by `gpg --with-colons`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyStatus {
+    /// The key can be used for signing.
+    Usable,
+    /// No secret key matched.
+    Missing,
+    /// The key expired at the given Unix time.
+    Expired(i64),
+    /// The key was revoked.
+    Revoked,
+}
+
+/// Check that a signed commit can be created with the configured key.
+///
+/// Verifies the key exists, is not expired or revoked, and that the agent
+/// holding it is reachable. Errors carry a remediation hint.
+pub fn check_signing_setup() -> Result<()> {
+    let repo = Repository::open_current()?;
+    let config = repo
+        .inner()
+        .config()
+        .map_err(|e| signing_error(e.message()))?;
+
+    let format = config
+        .get_string("gpg.format")
+        .unwrap_or_else(|_| "openpgp".to_string());
+    let key = config.get_string("user.signingkey").ok();
+
+    match format.as_str() {
+        "ssh" => check_ssh_key(key.as_deref(), repo.workdir()),
+        "x509" => Ok(()),
+        _ => {
+            let program = config
+                .get_string("gpg.openpgp.program")
+                .or_else(|_| config.get_string("gpg.program"))
+                .unwrap_or_else(|_| "gpg".to_string());
+            let key = match key.or_else(|| config.get_string("user.email").ok()) {
+                Some(key) => key,
+                None => return Err(signing_error(
+                    "No signing key configured. Set one with `git config user.signingkey <key-id>`",
+                )),
+            };
+            check_gpg_key(&program, &key)
+        }
+    }
+}
+
+/// Check an OpenPGP key and its agent.
+fn check_gpg_key(program: &str, key: &str) -> Result<()> {
+    let output = Command::new(program)
+        .args(["--batch", "--with-colons", "--list-secret-keys", key])
+        .output()
+        .map_err(|e| {
+            signing_error(&format!(
+                "Could not run '{}' ({}). Install GnuPG or set `git config gpg.program`",
+                program, e
+            ))
+        })?;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+
+    match parse_key_status(&String::from_utf8_lossy(&output.stdout), now) {
+        KeyStatus::Usable => {}
+        KeyStatus::Missing => {
+            return Err(signing_error(&format!(
+                "No secret key for '{}'. List keys with `gpg --list-secret-keys` and set `git config user.signingkey <key-id>`",
+                key
+            )))
+        }
+        KeyStatus::Expired(at) => {
+            return Err(signing_error(&format!(
+                "Signing key '{}' expired (at Unix time {}). Extend it with `gpg --quick-set-expire <fingerprint> 1y`",
+                key, at
+            )))
+        }
+        KeyStatus::Revoked => {
+            return Err(signing_error(&format!(
+                "Signing key '{}' is revoked. Configure another key with `git config user.signingkey <key-id>`",
+                key
+            )))
+        }
+    }
+
+    // The agent must be reachable to unlock the key
+    let agent_ok = Command::new("gpg-connect-agent")
+        .arg("/bye")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    if !agent_ok {
+        return Err(signing_error(
+            "gpg-agent is not reachable. Start it with `gpgconf --launch gpg-agent` and make sure GPG_TTY is set (`export GPG_TTY=$(tty)`)",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check an SSH signing key and, for public keys, that the agent holds it.
+fn check_ssh_key(key: Option<&str>, workdir: &Path) -> Result<()> {
+    let key = match key {
+        Some(key) => key,
+        None => {
+            return Err(signing_error(
+                "No SSH signing key configured. Set one with `git config user.signingkey ~/.ssh/id_ed25519.pub`",
+            ))
+        }
+    };
+
+    // Literal keys ("key::ssh-ed25519 ...") and public key files need the agent
+    let public_key = if let Some(literal) = key.strip_prefix("key::") {
+        Some(literal.to_string())
+    } else if key.starts_with("ssh-") {
+        Some(key.to_string())
+    } else {
+        let path = expand_home(key, workdir);
+        if !path.exists() {
+            return Err(signing_error(&format!(
+                "SSH signing key '{}' does not exist. Point `git config user.signingkey` at an existing key",
+                path.display()
+            )));
+        }
+        if path.extension().is_some_and(|ext| ext == "pub") && !path.with_extension("").exists() {
+            std::fs::read_to_string(&path).ok()
+        } else {
+            None
+        }
+    };
+
+    if let Some(public_key) = public_key {
+        let listed = Command::new("ssh-add")
+            .arg("-L")
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+            .unwrap_or_default();
+        let key_body = public_key.split_whitespace().nth(1).unwrap_or("");
+        if key_body.is_empty() || !listed.contains(key_body) {
+            return Err(signing_error(
+                "The SSH signing key is not loaded in ssh-agent. Add it with `ssh-add <private-key>`",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Sign a commit object the way `git commit -S` does, with the key and
+/// program configured by `gpg.format`, `user.signingkey` and `gpg.*.program`.
+///
+/// Returns the detached signature for the object's `gpgsig` header.
+pub fn sign_buffer(repo: &Repository, content: &[u8]) -> Result<String> {
+    let config = repo
+        .inner()
+        .config()
+        .map_err(|e| signing_error(e.message()))?;
+    let format = config
+        .get_string("gpg.format")
+        .unwrap_or_else(|_| "openpgp".to_string());
+    let key = config.get_string("user.signingkey").ok();
+    let program = |name: &str, default: &str| {
+        config
+            .get_string(&format!("gpg.{}.program", name))
+            .or_else(|_| match name {
+                "openpgp" => config.get_string("gpg.program"),
+                _ => Err(git2::Error::from_str("unset")),
+            })
+            .unwrap_or_else(|_| default.to_string())
+    };
+
+    match format.as_str() {
+        "ssh" => {
+            let key = key.ok_or_else(|| {
+                signing_error("No SSH signing key configured. Set one with `git config user.signingkey ~/.ssh/id_ed25519.pub`")
+            })?;
+            // Literal keys are passed to ssh-keygen through a file
+            let literal = key
+                .strip_prefix("key::")
+                .or_else(|| key.starts_with("ssh-").then_some(key.as_str()));
+            let key_file = match literal {
+                Some(literal) => {
+                    let path = std::env::temp_dir()
+                        .join(format!("ck-signing-key-{}.pub", std::process::id()));
+                    std::fs::write(&path, literal)?;
+                    path
+                }
+                None => expand_home(&key, repo.workdir()),
+            };
+            let mut command = Command::new(program("ssh", "ssh-keygen"));
+            command
+                .args(["-Y", "sign", "-n", "git", "-f"])
+                .arg(&key_file);
+            let signature = run_signer(command, content);
+            if literal.is_some() {
+                let _ = std::fs::remove_file(&key_file);
+            }
+            signature
+        }
+        _ => {
+            let program = if format == "x509" {
+                program("x509", "gpgsm")
+            } else {
+                program("openpgp", "gpg")
+            };
+            let key = match key.or_else(|| config.get_string("user.email").ok()) {
+                Some(key) => key,
+                None => return Err(signing_error(
+                    "No signing key configured. Set one with `git config user.signingkey <key-id>`",
+                )),
+            };
+            let mut command = Command::new(program);
+            command.args(["--status-fd=2", "-bsau", &key]);
+            run_signer(command, content)
+        }
+    }
+}
+
+/// Run a signing program with `content` on stdin and return its output.
+fn run_signer(mut command: Command, content: &[u8]) -> Result<String> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| signing_error(&format!("Could not run the signing program: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(content)?;
+    }
+    let output = child.wait_with_output()?;
+
+    let signature = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() || signature.trim().is_empty() {
+        return Err(signing_error(&format!(
+            "Signing failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(signature)
+}
+
+/// Determine the status of the first secret key in `--with-colons` output.
+pub fn parse_key_status(output: &str, now: i64) -> KeyStatus {
+    let sec = match output.lines().find(|line| line.starts_with("sec:")) {
+        Some(line) => line,
+        None => return KeyStatus::Missing,
+    };
+    let fields: Vec<&str> = sec.split(':').collect();
+
+    match fields.get(1).copied() {
+        Some("r") => return KeyStatus::Revoked,
+        Some("e") => {
+            let at = fields.get(6).and_then(|f| f.parse().ok()).unwrap_or(0);
+            return KeyStatus::Expired(at);
+        }
+        _ => {}
+    }
+
+    match fields.get(6).and_then(|f| f.parse::<i64>().ok()) {
+        Some(expires) if expires <= now => KeyStatus::Expired(expires),
+        _ => KeyStatus::Usable,
+    }
+}
+
+/// Expand a leading `~/` and resolve relative paths against the repository.
+pub(crate) fn expand_home(path: &str, workdir: &Path) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(rest),
+        None => workdir.join(path),
+    }
+}
+
+fn signing_error(message: &str) -> CkError {
+    CkError::WithContext {
+        code: "signing-setup",
+        context: "signing".to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn test_parse_usable_and_missing() {
+        let output =
+            "sec:u:255:22:ABCDEF1234567890:1600000000:1800000000::u:::scESC:::+:::23::0:\n\
+                      fpr:::::::::0123456789ABCDEF:\n";
+        assert_eq!(parse_key_status(output, NOW), KeyStatus::Usable);
+        assert_eq!(parse_key_status("", NOW), KeyStatus::Missing);
+    }
+
+    #[test]
+    fn test_parse_expired_and_revoked() {
+        let expired = "sec:u:255:22:ABCDEF1234567890:1600000000:1650000000::u:::scESC:::+:::23::0:";
+        assert_eq!(
+            parse_key_status(expired, NOW),
+            KeyStatus::Expired(1_650_000_000)
+        );
+
+        let revoked = "sec:r:255:22:ABCDEF1234567890:1600000000:::u:::sc:::+:::23::0:";
+        assert_eq!(parse_key_status(revoked, NOW), KeyStatus::Revoked);
+    }
+}
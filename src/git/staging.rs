@@ -0,0 +1,252 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Hunk-by-hunk staging, like `git add --patch`.
+//!
+//! Hunks are read from the diff between the index and the working tree.
+//! Staging re-creates that diff and applies it to the index, keeping only
+//! the chosen hunks, so the working tree must not change in between.
+
+use crate::error::{CkError, GitError, Result};
+use git2::{ApplyLocation, ApplyOptions, DiffOptions, Patch};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::partial::repo_relative;
+use super::repo::Repository;
+
+/// One unstaged hunk of a tracked file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// File the hunk belongs to, relative to the repository root.
+    pub path: PathBuf,
+    /// Position of the hunk within its file, starting at 0.
+    pub index: usize,
+    /// Hunk header, e.g. `@@ -1,3 +1,4 @@ fn main()`.
+    pub header: String,
+    /// Lines prefixed with `+`, `-` or a space, without line endings.
+    pub lines: Vec<String>,
+    /// Lines added.
+    pub added: usize,
+    /// Lines removed.
+    pub removed: usize,
+}
+
+/// Unstaged hunks of tracked files, limited to `paths` when given.
+///
+/// Paths are relative to the current directory, as with `git add`.
+/// Binary files and mode changes have no hunks and are left out.
+pub fn unstaged_hunks(repo: &Repository, paths: &[String]) -> Result<Vec<Hunk>> {
+    let diff = unstaged_diff(repo, paths)?;
+    let mut hunks = Vec::new();
+
+    for delta_index in 0..diff.deltas().len() {
+        let patch = match Patch::from_diff(&diff, delta_index).map_err(apply_error)? {
+            Some(patch) => patch,
+            None => continue,
+        };
+        let path = match delta_path(&patch.delta()) {
+            Some(path) => path,
+            None => continue,
+        };
+
+        for index in 0..patch.num_hunks() {
+            let (hunk, line_count) = patch.hunk(index).map_err(apply_error)?;
+            let mut lines = Vec::with_capacity(line_count);
+            let (mut added, mut removed) = (0, 0);
+            for line_index in 0..line_count {
+                let line = patch.line_in_hunk(index, line_index).map_err(apply_error)?;
+                let origin = line.origin();
+                match origin {
+                    '+' => added += 1,
+                    '-' => removed += 1,
+                    ' ' => {}
+                    // "\ No newline at end of file" markers
+                    _ => continue,
+                }
+                let content = String::from_utf8_lossy(line.content());
+                lines.push(format!(
+                    "{}{}",
+                    origin,
+                    content.trim_end_matches(['\n', '\r'])
+                ));
+            }
+
+            hunks.push(Hunk {
+                path: path.clone(),
+                index,
+                header: String::from_utf8_lossy(hunk.header())
+                    .trim_end()
+                    .to_string(),
+                lines,
+                added,
+                removed,
+            });
+        }
+    }
+    Ok(hunks)
+}
+
+/// Stage the given hunks, leaving the rest of the working tree unstaged.
+pub fn stage_hunks(repo: &Repository, hunks: &[&Hunk]) -> Result<()> {
+    if hunks.is_empty() {
+        return Ok(());
+    }
+
+    let mut selected: HashMap<&Path, HashSet<usize>> = HashMap::new();
+    for hunk in hunks {
+        selected
+            .entry(hunk.path.as_path())
+            .or_default()
+            .insert(hunk.index);
+    }
+
+    // Hunks are re-read from the whole diff so their positions match
+    let diff = unstaged_diff(repo, &[])?;
+
+    // The hunk callback doesn't say which file it is in, so the delta
+    // callback records it along with a running hunk count
+    let current: RefCell<Option<(PathBuf, usize)>> = RefCell::new(None);
+    let mut options = ApplyOptions::new();
+    options.delta_callback(|delta| {
+        let path = delta.as_ref().and_then(delta_path);
+        let wanted = path
+            .as_deref()
+            .is_some_and(|path| selected.contains_key(path));
+        *current.borrow_mut() = path.filter(|_| wanted).map(|path| (path, 0));
+        wanted
+    });
+    options.hunk_callback(|_| match current.borrow_mut().as_mut() {
+        Some((path, next)) => {
+            let index = *next;
+            *next += 1;
+            selected
+                .get(path.as_path())
+                .is_some_and(|indexes| indexes.contains(&index))
+        }
+        None => false,
+    });
+
+    repo.inner()
+        .apply(&diff, ApplyLocation::Index, Some(&mut options))
+        .map_err(apply_error)
+}
+
+/// Diff from the index to the working tree.
+fn unstaged_diff<'r>(repo: &'r Repository, paths: &[String]) -> Result<git2::Diff<'r>> {
+    let mut options = DiffOptions::new();
+    for path in paths {
+        options.pathspec(repo_relative(repo, path));
+    }
+    repo.inner()
+        .diff_index_to_workdir(None, Some(&mut options))
+        .map_err(apply_error)
+}
+
+fn delta_path(delta: &git2::DiffDelta<'_>) -> Option<PathBuf> {
+    delta
+        .new_file()
+        .path()
+        .or_else(|| delta.old_file().path())
+        .map(Path::to_path_buf)
+}
+
+fn apply_error(e: git2::Error) -> CkError {
+    CkError::Git(GitError::CommandFailed {
+        command: "apply".to_string(),
+        message: e.message().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixtureRepo;
+
+    fn create_repo() -> (FixtureRepo, Repository) {
+        let fixture = FixtureRepo::new();
+        let lines: Vec<String> = (1..=20).map(|n| format!("line {}", n)).collect();
+        fixture
+            .write("a.txt", &(lines.join("\n") + "\n"))
+            .write("b.txt", "b\n")
+            .commit("feat: initial commit");
+
+        // Two hunks in a.txt, far enough apart not to merge, and one in b.txt
+        let mut changed = lines.clone();
+        changed[1] = "line 2 changed".to_string();
+        changed[17] = "line 18 changed".to_string();
+        fixture
+            .write("a.txt", &(changed.join("\n") + "\n"))
+            .write("b.txt", "b2\n");
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        (fixture, repo)
+    }
+
+    #[test]
+    fn test_unstaged_hunks() {
+        let (_fixture, repo) = create_repo();
+
+        let hunks = unstaged_hunks(&repo, &[]).unwrap();
+        let positions: Vec<(&Path, usize)> =
+            hunks.iter().map(|h| (h.path.as_path(), h.index)).collect();
+        assert_eq!(
+            positions,
+            vec![
+                (Path::new("a.txt"), 0),
+                (Path::new("a.txt"), 1),
+                (Path::new("b.txt"), 0),
+            ]
+        );
+        assert!(hunks[0].header.starts_with("@@ -1,5 +1,5 @@"));
+        assert!(hunks[0].lines.contains(&"+line 2 changed".to_string()));
+        assert_eq!((hunks[0].added, hunks[0].removed), (1, 1));
+
+        let limited = unstaged_hunks(&repo, &["b.txt".to_string()]).unwrap();
+        assert_eq!(limited.len(), 1);
+    }
+
+    #[test]
+    fn test_stage_selected_hunks() {
+        let (fixture, repo) = create_repo();
+
+        let hunks = unstaged_hunks(&repo, &[]).unwrap();
+        stage_hunks(&repo, &[&hunks[1], &hunks[2]]).unwrap();
+
+        let staged = fixture.git(&["diff", "--cached"]);
+        assert!(staged.contains("+line 18 changed"));
+        assert!(staged.contains("+b2"));
+        assert!(!staged.contains("line 2 changed"));
+
+        let unstaged = fixture.git(&["diff"]);
+        assert!(unstaged.contains("+line 2 changed"));
+        assert!(!unstaged.contains("line 18 changed"));
+        assert!(!unstaged.contains("b2"));
+    }
+
+    #[test]
+    fn test_stage_hunk_over_staged_changes() {
+        let (fixture, repo) = create_repo();
+        fixture.git(&["add", "b.txt"]);
+
+        // Only the hunks not yet in the index are offered
+        let hunks = unstaged_hunks(&repo, &[]).unwrap();
+        assert!(hunks.iter().all(|h| h.path == Path::new("a.txt")));
+        stage_hunks(&repo, &[&hunks[0]]).unwrap();
+
+        let staged = fixture.git(&["diff", "--cached"]);
+        assert!(staged.contains("+b2"));
+        assert!(staged.contains("+line 2 changed"));
+        assert!(!staged.contains("line 18 changed"));
+
+        let remaining = unstaged_hunks(&repo, &[]).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert!(remaining[0].lines.contains(&"+line 18 changed".to_string()));
+
+        // Nothing is left to stage once every hunk is staged
+        stage_hunks(&repo, &remaining.iter().collect::<Vec<_>>()).unwrap();
+        assert!(unstaged_hunks(&repo, &[]).unwrap().is_empty());
+        assert!(fixture.git(&["diff"]).is_empty());
+    }
+}
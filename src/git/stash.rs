@@ -0,0 +1,450 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Temporary stashing of changes that are not part of a commit, and
+//! turning existing stashes into commits.
+
+use crate::error::{CkError, GitError, Result};
+use crate::interrupt::{self, Cleanup};
+use git2::Oid;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::commands::run_git;
+use super::diff::{diff_trees, DiffInfo};
+use super::repo::Repository;
+
+/// Message used for stashes created by ck.
+const AUTOSTASH_MESSAGE: &str = "ck autostash";
+
+/// Unstaged and untracked changes set aside while committing.
+///
+/// The index is kept, so analysis, hooks and the commit only see staged
+/// changes. The stash is popped by [`restore`] or when the guard is dropped.
+///
+/// [`restore`]: AutoStash::restore
+pub struct AutoStash {
+    workdir: PathBuf,
+    stashed: bool,
+    _cleanup: Option<Cleanup>,
+}
+
+impl AutoStash {
+    /// Stash unstaged and untracked changes in the repository at `workdir`.
+    pub fn push(workdir: &Path) -> Result<Self> {
+        let before = stash_head(workdir)?;
+        run_git(
+            workdir,
+            &[
+                "stash",
+                "push",
+                "--keep-index",
+                "--include-untracked",
+                "--message",
+                AUTOSTASH_MESSAGE,
+            ],
+        )?;
+        let stashed = stash_head(workdir)? != before;
+
+        let cleanup = stashed.then(|| {
+            tracing::debug!("Stashed unstaged and untracked changes");
+            let workdir = workdir.to_path_buf();
+            interrupt::on_interrupt("restore autostashed changes", move || {
+                let _ = apply_stash(&workdir)
+                    .and_then(|_| run_git(&workdir, &["stash", "drop", "--quiet"]));
+            })
+        });
+
+        Ok(Self {
+            workdir: workdir.to_path_buf(),
+            stashed,
+            _cleanup: cleanup,
+        })
+    }
+
+    /// Whether anything was stashed.
+    pub fn is_active(&self) -> bool {
+        self.stashed
+    }
+
+    /// Restore the stashed changes.
+    pub fn restore(mut self) -> Result<()> {
+        self.pop()
+    }
+
+    fn pop(&mut self) -> Result<()> {
+        if !self.stashed {
+            return Ok(());
+        }
+        self.stashed = false;
+
+        apply_stash(&self.workdir)
+            .and_then(|_| run_git(&self.workdir, &["stash", "drop", "--quiet"]))
+            .map_err(|e| CkError::WithContext {
+                code: "autostash-restore",
+                context: "autostash".to_string(),
+                message: format!(
+                    "Failed to restore stashed changes ({}). They are kept in the stash; run `git stash pop` to restore them",
+                    e
+                ),
+            })
+    }
+}
+
+impl Drop for AutoStash {
+    fn drop(&mut self) {
+        if let Err(e) = self.pop() {
+            tracing::warn!("{}", e);
+        }
+    }
+}
+
+/// A stash applied to HEAD in a temporary index, ready to be committed.
+///
+/// Neither the working tree nor the index are touched until [`commit`] is
+/// called, and even then the working tree is left as it is.
+///
+/// [`commit`]: StashCommit::commit
+#[derive(Debug, Clone)]
+pub struct StashCommit {
+    /// The stash reference, e.g. `stash@{0}`.
+    pub stash_ref: String,
+    /// HEAD the stash was applied to.
+    head: Oid,
+    /// Tree of that HEAD.
+    head_tree: Oid,
+    /// Tree of HEAD with the stash applied.
+    tree: Oid,
+    /// Tree for the index after committing: the new tree plus any changes
+    /// that were staged before.
+    index_tree: Oid,
+}
+
+impl StashCommit {
+    /// Apply a stash (`stash@{N}` or just `N`) to HEAD in memory.
+    pub fn prepare(repo: &Repository, stash: &str) -> Result<Self> {
+        let git = repo.inner();
+        let stash_ref = if stash.chars().all(|c| c.is_ascii_digit()) {
+            format!("stash@{{{}}}", stash)
+        } else {
+            stash.to_string()
+        };
+
+        let stash_commit = git
+            .revparse_single(&stash_ref)
+            .and_then(|o| o.peel_to_commit())
+            .map_err(|_| {
+                CkError::Git(GitError::InvalidReference {
+                    reference: stash_ref.clone(),
+                })
+            })?;
+        let head = repo.head_commit()?;
+        let head_tree = head.tree().map_err(|e| stash_error("tree", e))?;
+
+        let mut opts = git2::DiffOptions::new();
+        opts.show_binary(true);
+
+        // Changes to tracked files, relative to the commit the stash was made on
+        let base_tree = stash_commit
+            .parent(0)
+            .and_then(|c| c.tree())
+            .map_err(|e| stash_error("stash^1", e))?;
+        let worktree_tree = stash_commit.tree().map_err(|e| stash_error("stash", e))?;
+        let tracked = git
+            .diff_tree_to_tree(Some(&base_tree), Some(&worktree_tree), Some(&mut opts))
+            .map_err(|e| stash_error("diff", e))?;
+        let mut index = git
+            .apply_to_tree(&head_tree, &tracked, None)
+            .map_err(|e| not_applicable(&stash_ref, e))?;
+        let mut tree = index
+            .write_tree_to(git)
+            .map_err(|e| stash_error("tree", e))?;
+
+        // Untracked files are stored in a third parent
+        if let Ok(untracked) = stash_commit.parent(2) {
+            let untracked_tree = untracked.tree().map_err(|e| stash_error("stash^3", e))?;
+            let added = git
+                .diff_tree_to_tree(None, Some(&untracked_tree), Some(&mut opts))
+                .map_err(|e| stash_error("diff", e))?;
+            let current = git.find_tree(tree).map_err(|e| stash_error("tree", e))?;
+            let mut index = git
+                .apply_to_tree(&current, &added, None)
+                .map_err(|e| not_applicable(&stash_ref, e))?;
+            tree = index
+                .write_tree_to(git)
+                .map_err(|e| stash_error("tree", e))?;
+        }
+
+        if tree == head_tree.id() {
+            return Err(CkError::Git(GitError::NoStagedChanges));
+        }
+
+        // Carry the currently staged changes over to the new index
+        let repo_index = git.index().map_err(|e| stash_error("index", e))?;
+        let staged = git
+            .diff_tree_to_index(Some(&head_tree), Some(&repo_index), Some(&mut opts))
+            .map_err(|e| stash_error("diff", e))?;
+        let new_tree = git.find_tree(tree).map_err(|e| stash_error("tree", e))?;
+        let index_tree = git
+            .apply_to_tree(&new_tree, &staged, None)
+            .and_then(|mut i| i.write_tree_to(git))
+            .map_err(|e| CkError::WithContext {
+                code: "stash-conflict",
+                context: "stash".to_string(),
+                message: format!(
+                    "Staged changes conflict with {} ({}); commit or unstage them first",
+                    stash_ref,
+                    e.message()
+                ),
+            })?;
+
+        Ok(Self {
+            stash_ref,
+            head: head.id(),
+            head_tree: head_tree.id(),
+            tree,
+            index_tree,
+        })
+    }
+
+    /// Diff of the stash against HEAD, for analysis.
+    pub fn diff_info(&self, repo: &Repository) -> Result<DiffInfo> {
+        diff_trees(repo, Some(self.head_tree), self.tree)
+    }
+
+    /// Commit the applied stash on HEAD and update the index to match.
+    ///
+    /// The working tree is not touched. Returns the new commit SHA.
+    pub fn commit(
+        &self,
+        repo: &Repository,
+        message: &str,
+        sign: bool,
+        options: &super::CommitOptions,
+    ) -> Result<String> {
+        let git = repo.inner();
+        let head = repo.head_commit()?;
+        if head.id() != self.head {
+            return Err(CkError::Git(GitError::CommitFailed {
+                message: "HEAD moved while preparing the commit".to_string(),
+            }));
+        }
+
+        let commit_id = if sign || !options.is_empty() {
+            super::commands::commit_tree_with_git(
+                repo,
+                &self.tree.to_string(),
+                &head.id().to_string(),
+                message,
+                sign,
+                options,
+            )?
+        } else {
+            let tree = git
+                .find_tree(self.tree)
+                .map_err(|e| stash_error("tree", e))?;
+            let sig = git.signature().map_err(|e| stash_error("signature", e))?;
+            git.commit(None, &sig, &sig, message, &tree, &[&head])
+                .map_err(|e| {
+                    CkError::Git(GitError::CommitFailed {
+                        message: e.message().to_string(),
+                    })
+                })?
+                .to_string()
+        };
+        let oid = Oid::from_str(&commit_id).map_err(|e| stash_error("commit", e))?;
+
+        let head_ref = git.head().map_err(|e| stash_error("head", e))?;
+        if head_ref.is_branch() {
+            let name = head_ref.name().unwrap_or("HEAD").to_string();
+            git.reference_matching(&name, oid, true, head.id(), "ck: commit from stash")
+                .map_err(|e| stash_error("update-ref", e))?;
+        } else {
+            git.set_head_detached(oid)
+                .map_err(|e| stash_error("update-ref", e))?;
+        }
+
+        let mut index = git.index().map_err(|e| stash_error("index", e))?;
+        let index_tree = git
+            .find_tree(self.index_tree)
+            .map_err(|e| stash_error("tree", e))?;
+        index
+            .read_tree(&index_tree)
+            .and_then(|_| index.write())
+            .map_err(|e| stash_error("index", e))?;
+
+        Ok(commit_id)
+    }
+
+    /// Drop the stash once it has been committed.
+    pub fn drop_stash(&self, repo: &Repository) -> Result<()> {
+        run_git(
+            repo.workdir(),
+            &["stash", "drop", "--quiet", &self.stash_ref],
+        )
+    }
+}
+
+fn not_applicable(stash_ref: &str, e: git2::Error) -> CkError {
+    CkError::WithContext {
+        code: "stash-not-applicable",
+        context: "stash".to_string(),
+        message: format!(
+            "{} does not apply cleanly to HEAD: {}",
+            stash_ref,
+            e.message()
+        ),
+    }
+}
+
+/// Apply the latest stash to the working tree only.
+///
+/// `git stash pop` merges against the stash's HEAD, which conflicts once the
+/// kept index has been committed. The changes are replayed relative to the
+/// stashed index instead, and untracked files are written back as new files.
+fn apply_stash(workdir: &Path) -> Result<()> {
+    let repo = git2::Repository::open(workdir).map_err(|e| stash_error("open", e))?;
+    let stash = repo
+        .revparse_single("refs/stash")
+        .and_then(|o| o.peel_to_commit())
+        .map_err(|e| stash_error("stash", e))?;
+
+    let mut opts = git2::DiffOptions::new();
+    opts.show_binary(true);
+
+    let index_tree = stash
+        .parent(1)
+        .and_then(|c| c.tree())
+        .map_err(|e| stash_error("stash^2", e))?;
+    let worktree_tree = stash.tree().map_err(|e| stash_error("stash", e))?;
+    let tracked = repo
+        .diff_tree_to_tree(Some(&index_tree), Some(&worktree_tree), Some(&mut opts))
+        .map_err(|e| stash_error("diff", e))?;
+    repo.apply(&tracked, git2::ApplyLocation::WorkDir, None)
+        .map_err(|e| stash_error("apply", e))?;
+
+    // Untracked files are stored in a third parent
+    if let Ok(untracked) = stash.parent(2) {
+        let tree = untracked.tree().map_err(|e| stash_error("stash^3", e))?;
+        let diff = repo
+            .diff_tree_to_tree(None, Some(&tree), Some(&mut opts))
+            .map_err(|e| stash_error("diff", e))?;
+        repo.apply(&diff, git2::ApplyLocation::WorkDir, None)
+            .map_err(|e| stash_error("apply", e))?;
+    }
+
+    Ok(())
+}
+
+/// Get the current stash commit, if any.
+fn stash_head(workdir: &Path) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--quiet", "--verify", "refs/stash"])
+        .current_dir(workdir)
+        .output()?;
+
+    if output.status.success() {
+        Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ))
+    } else {
+        Ok(None)
+    }
+}
+
+fn stash_error(command: &str, e: git2::Error) -> CkError {
+    CkError::Git(GitError::CommandFailed {
+        command: command.to_string(),
+        message: e.message().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixtureRepo;
+
+    fn create_repo() -> FixtureRepo {
+        let fixture = FixtureRepo::new();
+        fixture.write("a.txt", "a").commit("feat: initial commit");
+        fixture
+    }
+
+    #[test]
+    fn test_autostash_round_trip() {
+        let fixture = create_repo();
+        std::fs::write(fixture.path().join("a.txt"), "staged").unwrap();
+        fixture.git(&["add", "a.txt"]);
+        std::fs::write(fixture.path().join("a.txt"), "unstaged").unwrap();
+        std::fs::write(fixture.path().join("new.txt"), "untracked").unwrap();
+
+        let stash = AutoStash::push(fixture.path()).unwrap();
+        assert!(stash.is_active());
+        assert_eq!(
+            std::fs::read_to_string(fixture.path().join("a.txt")).unwrap(),
+            "staged"
+        );
+        assert!(!fixture.path().join("new.txt").exists());
+
+        fixture.git(&["commit", "-m", "fix: update a"]);
+        stash.restore().unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(fixture.path().join("a.txt")).unwrap(),
+            "unstaged"
+        );
+        assert!(fixture.path().join("new.txt").exists());
+    }
+
+    #[test]
+    fn test_autostash_restores_without_commit() {
+        let fixture = create_repo();
+        std::fs::write(fixture.path().join("a.txt"), "unstaged").unwrap();
+
+        drop(AutoStash::push(fixture.path()).unwrap());
+
+        assert_eq!(
+            std::fs::read_to_string(fixture.path().join("a.txt")).unwrap(),
+            "unstaged"
+        );
+        assert_eq!(fixture.git(&["stash", "list"]), "");
+    }
+
+    #[test]
+    fn test_autostash_clean_tree() {
+        let fixture = create_repo();
+        let stash = AutoStash::push(fixture.path()).unwrap();
+        assert!(!stash.is_active());
+    }
+
+    #[test]
+    fn test_commit_from_stash() {
+        let fixture = create_repo();
+        std::fs::write(fixture.path().join("a.txt"), "stashed").unwrap();
+        std::fs::write(fixture.path().join("new.txt"), "untracked").unwrap();
+        fixture.git(&["stash", "push", "--include-untracked"]);
+        std::fs::write(fixture.path().join("b.txt"), "staged").unwrap();
+        fixture.git(&["add", "b.txt"]);
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        let stash = StashCommit::prepare(&repo, "0").unwrap();
+        assert_eq!(stash.stash_ref, "stash@{0}");
+        assert_eq!(stash.diff_info(&repo).unwrap().files.len(), 2);
+
+        stash
+            .commit(&repo, "feat: recover stash", false, &Default::default())
+            .unwrap();
+        stash.drop_stash(&repo).unwrap();
+
+        assert_eq!(
+            fixture.git(&["show", "--name-only", "--format="]),
+            "a.txt\nnew.txt\n"
+        );
+        assert_eq!(fixture.git(&["diff", "--cached", "--name-only"]), "b.txt\n");
+        assert_eq!(fixture.git(&["stash", "list"]), "");
+        assert_eq!(
+            std::fs::read_to_string(fixture.path().join("a.txt")).unwrap(),
+            "a"
+        );
+    }
+}
@@ -0,0 +1,316 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Working tree status.
+//!
+//! libgit2 finds unstaged and untracked files by scanning the whole working
+//! tree, which takes seconds on large monorepos. When the repository enables
+//! `core.fsmonitor` or `core.untrackedCache`, git answers from those caches
+//! instead, so status is read from `git status --porcelain=v2 -z`. Without
+//! them, or if git fails, libgit2 is used.
+
+use crate::error::{CkError, GitError, Result};
+use std::path::PathBuf;
+use std::process::Command;
+
+use super::repo::Repository;
+
+/// Paths that differ between HEAD, the index and the working tree.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkingTreeStatus {
+    /// Paths with staged changes.
+    pub staged: Vec<PathBuf>,
+    /// Tracked paths with changes that are not staged, including conflicts.
+    pub unstaged: Vec<PathBuf>,
+    /// Untracked paths; untracked directories are listed once.
+    pub untracked: Vec<PathBuf>,
+}
+
+impl WorkingTreeStatus {
+    /// Read the status of `repo`, using git's caches when enabled.
+    pub fn read(repo: &Repository) -> Result<Self> {
+        Self::read_paths(repo, &[])
+    }
+
+    /// Read the status of the paths matching `pathspecs`, or of the whole
+    /// repository when there are none. Patterns are matched as git does by
+    /// default, so `*` also matches `/`.
+    pub fn read_paths(repo: &Repository, pathspecs: &[String]) -> Result<Self> {
+        if uses_git_caches(repo) {
+            match Self::from_git(repo, pathspecs) {
+                Ok(status) => return Ok(status),
+                Err(e) => tracing::debug!("Falling back to libgit2 status: {}", e),
+            }
+        }
+        Self::from_libgit2(repo, pathspecs)
+    }
+
+    /// Check if nothing is staged, modified or untracked.
+    pub fn is_clean(&self) -> bool {
+        self.staged.is_empty() && self.unstaged.is_empty() && self.untracked.is_empty()
+    }
+
+    /// Untracked files, with untracked directories expanded to the files
+    /// in them.
+    pub fn untracked_files(&self, repo: &Repository) -> Result<Vec<PathBuf>> {
+        let (dirs, mut files): (Vec<_>, Vec<_>) = self
+            .untracked
+            .iter()
+            .cloned()
+            .partition(|path| path.to_string_lossy().ends_with('/'));
+        if dirs.is_empty() {
+            return Ok(files);
+        }
+
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(false)
+            .exclude_submodules(true);
+        for dir in &dirs {
+            opts.pathspec(dir);
+        }
+        let statuses = repo.inner().statuses(Some(&mut opts)).map_err(|e| {
+            CkError::Git(GitError::CommandFailed {
+                command: "status".to_string(),
+                message: e.message().to_string(),
+            })
+        })?;
+        files.extend(
+            statuses
+                .iter()
+                .filter(|entry| entry.status().is_wt_new())
+                .filter_map(|entry| entry.path().map(PathBuf::from)),
+        );
+        Ok(files)
+    }
+
+    /// Read status with `git status`, which honors fsmonitor and the
+    /// untracked cache.
+    fn from_git(repo: &Repository, pathspecs: &[String]) -> Result<Self> {
+        let output = Command::new("git")
+            .args([
+                "status",
+                "--porcelain=v2",
+                "-z",
+                "--untracked-files=normal",
+                "--ignore-submodules=dirty",
+                "--",
+            ])
+            .args(pathspecs)
+            .current_dir(repo.workdir())
+            .output()?;
+
+        if !output.status.success() {
+            return Err(CkError::Git(GitError::CommandFailed {
+                command: "git status".to_string(),
+                message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            }));
+        }
+        Ok(parse_porcelain_v2(&output.stdout))
+    }
+
+    /// Read status by scanning the working tree with libgit2.
+    fn from_libgit2(repo: &Repository, pathspecs: &[String]) -> Result<Self> {
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(true)
+            .recurse_untracked_dirs(false)
+            .include_ignored(false)
+            .exclude_submodules(true);
+        for pathspec in pathspecs {
+            opts.pathspec(pathspec);
+        }
+        let statuses = repo.inner().statuses(Some(&mut opts)).map_err(|e| {
+            CkError::Git(GitError::CommandFailed {
+                command: "status".to_string(),
+                message: e.message().to_string(),
+            })
+        })?;
+
+        let mut status = Self::default();
+        for entry in statuses.iter() {
+            let path = match entry.path() {
+                Some(path) => PathBuf::from(path),
+                None => continue,
+            };
+            let flags = entry.status();
+            if flags.is_wt_new() {
+                status.untracked.push(path);
+                continue;
+            }
+            if flags.intersects(
+                git2::Status::INDEX_NEW
+                    | git2::Status::INDEX_MODIFIED
+                    | git2::Status::INDEX_DELETED
+                    | git2::Status::INDEX_RENAMED
+                    | git2::Status::INDEX_TYPECHANGE,
+            ) {
+                status.staged.push(path.clone());
+            }
+            if flags.intersects(
+                git2::Status::WT_MODIFIED
+                    | git2::Status::WT_DELETED
+                    | git2::Status::WT_RENAMED
+                    | git2::Status::WT_TYPECHANGE
+                    | git2::Status::CONFLICTED,
+            ) {
+                status.unstaged.push(path);
+            }
+        }
+        Ok(status)
+    }
+}
+
+/// Check if git can answer status from fsmonitor or the untracked cache.
+fn uses_git_caches(repo: &Repository) -> bool {
+    let config = match repo.inner().config() {
+        Ok(config) => config,
+        Err(_) => return false,
+    };
+    // `core.fsmonitor` is either a boolean or the path of a hook
+    let fsmonitor = match config.get_bool("core.fsmonitor") {
+        Ok(enabled) => enabled,
+        Err(_) => config
+            .get_string("core.fsmonitor")
+            .map(|hook| !hook.is_empty())
+            .unwrap_or(false),
+    };
+    fsmonitor || config.get_bool("core.untrackedCache").unwrap_or(false)
+}
+
+/// Parse `git status --porcelain=v2 -z` output.
+fn parse_porcelain_v2(output: &[u8]) -> WorkingTreeStatus {
+    let mut status = WorkingTreeStatus::default();
+    let mut records = output
+        .split(|&b| b == 0)
+        .map(|record| String::from_utf8_lossy(record).into_owned());
+
+    while let Some(record) = records.next() {
+        // The path is the last field and may contain spaces
+        let (xy, path) = match record.chars().next() {
+            Some('1') => field_and_path(&record, 9),
+            Some('2') => {
+                // Renames are followed by the original path
+                records.next();
+                field_and_path(&record, 10)
+            }
+            Some('u') => field_and_path(&record, 11),
+            Some('?') => {
+                if let Some(path) = record.get(2..) {
+                    status.untracked.push(PathBuf::from(path));
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let (xy, path) = match (xy, path) {
+            (Some(xy), Some(path)) => (xy, PathBuf::from(path)),
+            _ => continue,
+        };
+
+        let mut codes = xy.chars();
+        let staged = codes.next().unwrap_or('.');
+        let unstaged = codes.next().unwrap_or('.');
+        if record.starts_with('u') {
+            status.unstaged.push(path);
+            continue;
+        }
+        if staged != '.' {
+            status.staged.push(path.clone());
+        }
+        if unstaged != '.' {
+            status.unstaged.push(path);
+        }
+    }
+    status
+}
+
+/// Split a porcelain v2 record into its `XY` field and its path, the
+/// `fields`-th field.
+fn field_and_path(record: &str, fields: usize) -> (Option<&str>, Option<&str>) {
+    let mut parts = record.splitn(fields, ' ');
+    let xy = parts.nth(1);
+    (xy, parts.last())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixtureRepo;
+
+    #[test]
+    fn test_parse_porcelain_v2() {
+        let output = [
+            "1 M. N... 100644 100644 100644 abc abc src/lib.rs",
+            "1 .M N... 100644 100644 100644 abc abc docs/read me.md",
+            "1 MM N... 100644 100644 100644 abc abc both.rs",
+            "2 R. N... 100644 100644 100644 abc abc R100 new.rs",
+            "old.rs",
+            "u UU N... 100644 100644 100644 100644 a b c conflict.rs",
+            "? scratch/",
+            "",
+        ]
+        .join("\0");
+
+        let status = parse_porcelain_v2(output.as_bytes());
+        assert_eq!(
+            status.staged,
+            vec![
+                PathBuf::from("src/lib.rs"),
+                PathBuf::from("both.rs"),
+                PathBuf::from("new.rs"),
+            ]
+        );
+        assert_eq!(
+            status.unstaged,
+            vec![
+                PathBuf::from("docs/read me.md"),
+                PathBuf::from("both.rs"),
+                PathBuf::from("conflict.rs"),
+            ]
+        );
+        assert_eq!(status.untracked, vec![PathBuf::from("scratch/")]);
+    }
+
+    #[test]
+    fn test_git_and_libgit2_status_agree() {
+        let fixture = FixtureRepo::new();
+        fixture
+            .write("a.txt", "a")
+            .write("b.txt", "b")
+            .commit("chore: init")
+            .write("a.txt", "staged")
+            .stage(&["a.txt"])
+            .write("b.txt", "unstaged")
+            .write("new/c.txt", "c");
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        let from_git = WorkingTreeStatus::from_git(&repo, &[]).unwrap();
+        assert_eq!(
+            from_git,
+            WorkingTreeStatus::from_libgit2(&repo, &[]).unwrap()
+        );
+        assert_eq!(from_git.staged, vec![PathBuf::from("a.txt")]);
+        assert_eq!(from_git.unstaged, vec![PathBuf::from("b.txt")]);
+        assert_eq!(from_git.untracked, vec![PathBuf::from("new/")]);
+        assert!(!from_git.is_clean());
+        assert_eq!(
+            from_git.untracked_files(&repo).unwrap(),
+            vec![PathBuf::from("new/c.txt")]
+        );
+
+        let pathspecs = ["*b*".to_string()];
+        let from_git = WorkingTreeStatus::from_git(&repo, &pathspecs).unwrap();
+        assert_eq!(
+            from_git,
+            WorkingTreeStatus::from_libgit2(&repo, &pathspecs).unwrap()
+        );
+        assert_eq!(
+            from_git,
+            WorkingTreeStatus {
+                unstaged: vec![PathBuf::from("b.txt")],
+                ..WorkingTreeStatus::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,172 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Version tags.
+//!
+//! Tags named like `v1.2.3` or `1.2.3` mark releases. Pre-release and
+//! build suffixes (`v1.3.0-rc.1`) are not releases and are ignored.
+
+use crate::error::{CkError, GitError, Result};
+use git2::Oid;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+use super::repo::Repository;
+
+/// A release version, `major.minor.patch`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl std::str::FromStr for Version {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.strip_prefix('v').unwrap_or(s);
+        let mut parts = s.split('.').map(|part| {
+            // Reject signs, leading `+` and suffixes like `3-rc.1`
+            if !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()) {
+                part.parse::<u64>().map_err(|_| ())
+            } else {
+                Err(())
+            }
+        });
+        let version = Version {
+            major: parts.next().ok_or(())??,
+            minor: parts.next().ok_or(())??,
+            patch: parts.next().ok_or(())??,
+        };
+        match parts.next() {
+            Some(_) => Err(()),
+            None => Ok(version),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// A tag naming a release version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionTag {
+    /// Tag name, e.g. `v1.2.3`.
+    pub name: String,
+    /// Parsed version.
+    pub version: Version,
+    /// Commit the tag points to.
+    pub commit: Oid,
+}
+
+impl VersionTag {
+    /// Prefix before the version number, e.g. `v`.
+    pub fn prefix(&self) -> &str {
+        if self.name.starts_with('v') {
+            "v"
+        } else {
+            ""
+        }
+    }
+}
+
+/// The highest version tag reachable from HEAD, if any.
+pub fn latest_version_tag(repo: &Repository) -> Result<Option<VersionTag>> {
+    let git = repo.inner();
+    let head = repo.head_commit()?.id();
+    let names = git.tag_names(None).map_err(|e| tag_error(e.message()))?;
+
+    let mut latest: Option<VersionTag> = None;
+    for name in names.iter().flatten() {
+        let version: Version = match name.parse() {
+            Ok(version) => version,
+            Err(_) => continue,
+        };
+        let commit = match git
+            .revparse_single(&format!("refs/tags/{}", name))
+            .and_then(|object| object.peel_to_commit())
+        {
+            Ok(commit) => commit.id(),
+            Err(_) => continue,
+        };
+        // Tags on other branches don't describe this history
+        let reachable = commit == head || git.graph_descendant_of(head, commit).unwrap_or(false);
+        if reachable && latest.as_ref().is_none_or(|l| version > l.version) {
+            latest = Some(VersionTag {
+                name: name.to_string(),
+                version,
+                commit,
+            });
+        }
+    }
+    Ok(latest)
+}
+
+/// Create an annotated tag `name` on HEAD.
+pub fn create_tag(repo: &Repository, name: &str, message: &str) -> Result<Oid> {
+    let git = repo.inner();
+    let head = repo.head_commit()?;
+    let signature = git.signature().map_err(|e| tag_error(e.message()))?;
+    git.tag(name, head.as_object(), &signature, message, false)
+        .map_err(|e| tag_error(e.message()))
+}
+
+fn tag_error(message: &str) -> CkError {
+    CkError::Git(GitError::CommandFailed {
+        command: "tag".to_string(),
+        message: message.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixtureRepo;
+
+    #[test]
+    fn test_parse_version() {
+        let version = Version {
+            major: 1,
+            minor: 12,
+            patch: 0,
+        };
+        assert_eq!("v1.12.0".parse(), Ok(version));
+        assert_eq!("1.12.0".parse(), Ok(version));
+        assert_eq!(version.to_string(), "1.12.0");
+        assert!("v1.3.0-rc.1".parse::<Version>().is_err());
+        assert!("1.2".parse::<Version>().is_err());
+        assert!("1.2.3.4".parse::<Version>().is_err());
+        assert!("release-1".parse::<Version>().is_err());
+    }
+
+    #[test]
+    fn test_latest_version_tag_ignores_other_branches() {
+        let fixture = FixtureRepo::new();
+        fixture.commit("chore: start");
+        fixture.git(&["tag", "v1.9.0"]);
+        fixture.git(&["tag", "v1.10.0"]);
+        fixture.git(&["tag", "v2.0.0-rc.1"]);
+        fixture.git(&["checkout", "-q", "-b", "next"]);
+        fixture.git(&["commit", "-q", "--allow-empty", "-m", "feat: next"]);
+        fixture.git(&["tag", "v3.0.0"]);
+        fixture.git(&["checkout", "-q", "main"]);
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        let latest = latest_version_tag(&repo).unwrap().unwrap();
+        assert_eq!(latest.name, "v1.10.0");
+        assert_eq!(latest.prefix(), "v");
+
+        create_tag(&repo, "v1.10.1", "Release v1.10.1").unwrap();
+        assert_eq!(latest_version_tag(&repo).unwrap().unwrap().name, "v1.10.1");
+    }
+}
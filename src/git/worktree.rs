@@ -0,0 +1,181 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Linked worktree discovery.
+
+use crate::error::{CkError, GitError, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use super::repo::Repository;
+use super::status::WorkingTreeStatus;
+
+/// A working tree of the repository.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeInfo {
+    /// Worktree name (the directory name for the main worktree).
+    pub name: String,
+    /// Path to the working directory.
+    pub path: PathBuf,
+    /// Checked out branch, if HEAD is not detached.
+    pub branch: Option<String>,
+    /// Whether there are uncommitted or untracked changes.
+    pub dirty: bool,
+    /// Whether this is the main worktree.
+    pub main: bool,
+}
+
+/// List the main worktree followed by all linked worktrees.
+pub fn list_worktrees(repo: &Repository) -> Result<Vec<WorktreeInfo>> {
+    worktrees(repo)?
+        .into_iter()
+        .map(|mut worktree| {
+            let repo = Repository::open(&worktree.path)?;
+            worktree.dirty = !WorkingTreeStatus::read(&repo)?.is_clean();
+            Ok(worktree)
+        })
+        .collect()
+}
+
+/// Find a worktree by name, branch or path. Paths may be relative or go
+/// through symlinks. The working trees are not scanned for changes.
+pub fn find_worktree(repo: &Repository, name: &str) -> Result<PathBuf> {
+    let worktrees = worktrees(repo)?;
+    let path = Path::new(name).canonicalize().ok();
+
+    worktrees
+        .iter()
+        .find(|w| w.name == name)
+        .or_else(|| worktrees.iter().find(|w| w.branch.as_deref() == Some(name)))
+        .or_else(|| {
+            let path = path.as_deref()?;
+            worktrees
+                .iter()
+                .find(|w| w.path.canonicalize().is_ok_and(|p| p == path))
+        })
+        .map(|w| w.path.clone())
+        .ok_or_else(|| {
+            let known: Vec<&str> = worktrees.iter().map(|w| w.name.as_str()).collect();
+            CkError::WithContext {
+                code: "worktree-unknown",
+                context: "worktree".to_string(),
+                message: format!(
+                    "Unknown worktree '{}' (available: {})",
+                    name,
+                    known.join(", ")
+                ),
+            }
+        })
+}
+
+/// The worktrees of the repository, without their dirty state.
+fn worktrees(repo: &Repository) -> Result<Vec<WorktreeInfo>> {
+    let git = repo.inner();
+    let mut worktrees = Vec::new();
+
+    if let Some(main_dir) = main_workdir(git) {
+        let name = main_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| main_dir.display().to_string());
+        worktrees.push(describe(name, &main_dir, true)?);
+    }
+
+    let names = git
+        .worktrees()
+        .map_err(|e| worktree_error("worktree list", e))?;
+    for name in names.iter().flatten() {
+        let worktree = git
+            .find_worktree(name)
+            .map_err(|e| worktree_error("worktree", e))?;
+        // Skip worktrees whose directory has been removed
+        if worktree.validate().is_err() {
+            continue;
+        }
+        worktrees.push(describe(name.to_string(), worktree.path(), false)?);
+    }
+
+    Ok(worktrees)
+}
+
+/// Get the main working directory from any worktree of the repository.
+fn main_workdir(git: &git2::Repository) -> Option<PathBuf> {
+    if git.is_bare() {
+        return None;
+    }
+    if !git.is_worktree() {
+        return git.workdir().map(Path::to_path_buf);
+    }
+    // A linked worktree's git dir points at the shared one via `commondir`
+    let common = std::fs::read_to_string(git.path().join("commondir")).ok()?;
+    let common = git.path().join(common.trim()).canonicalize().ok()?;
+    common.parent().map(Path::to_path_buf)
+}
+
+/// Collect the branch of the worktree at `path`. Its dirty state is left
+/// for [`list_worktrees`] to read.
+fn describe(name: String, path: &Path, main: bool) -> Result<WorktreeInfo> {
+    let repo = Repository::open(path)?;
+    let branch = repo.branch_name().ok();
+
+    Ok(WorktreeInfo {
+        name,
+        path: repo.workdir().to_path_buf(),
+        branch,
+        dirty: false,
+        main,
+    })
+}
+
+fn worktree_error(command: &str, e: git2::Error) -> CkError {
+    CkError::Git(GitError::CommandFailed {
+        command: command.to_string(),
+        message: e.message().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixtureRepo;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_list_and_find_worktrees() {
+        let fixture = FixtureRepo::new();
+        fixture.write("a.txt", "a").commit("feat: initial commit");
+        let dir = TempDir::new().unwrap();
+        let docs = dir.path().join("docs-tree");
+        fixture.git(&["worktree", "add", "-b", "docs", docs.to_str().unwrap()]);
+        std::fs::write(docs.join("b.txt"), "b").unwrap();
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        let worktrees = list_worktrees(&repo).unwrap();
+        assert_eq!(worktrees.len(), 2);
+        assert!(worktrees[0].main);
+        assert!(!worktrees[0].dirty);
+        assert_eq!(worktrees[1].name, "docs-tree");
+        assert_eq!(worktrees[1].branch.as_deref(), Some("docs"));
+        assert!(worktrees[1].dirty);
+
+        // Lookup works by name or branch, from any worktree
+        let linked = Repository::open(&docs).unwrap();
+        let by_branch = find_worktree(&linked, "docs").unwrap();
+        assert_eq!(by_branch, worktrees[1].path);
+        assert_eq!(find_worktree(&linked, "main").unwrap(), worktrees[0].path);
+        assert!(find_worktree(&repo, "missing").is_err());
+
+        // Paths may be relative or go through a symlink
+        let by_path = find_worktree(&repo, docs.join("../docs-tree").to_str().unwrap());
+        assert_eq!(by_path.unwrap(), worktrees[1].path);
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("link");
+            std::os::unix::fs::symlink(&docs, &link).unwrap();
+            assert_eq!(
+                find_worktree(&repo, link.to_str().unwrap()).unwrap(),
+                worktrees[1].path
+            );
+        }
+    }
+}
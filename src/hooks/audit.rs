@@ -0,0 +1,192 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Audit trail for commits created while hooks were bypassed or that
+//! suppress rules.
+
+use crate::cli::args::OutputFormat;
+use crate::error::Result;
+use crate::git::{notes, Repository};
+use crate::progress::Progress;
+use crate::rules::suppress::{self, Suppression};
+use console::style;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Why a commit was flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditReason {
+    /// The post-commit hook logged it to `bypass.log`.
+    Logged,
+    /// It has no ck validation note.
+    Unvalidated,
+    /// It suppresses rules with `Ck-Disable:` footers.
+    Suppressed,
+}
+
+/// A commit created without ck validation.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditFinding {
+    /// Commit SHA.
+    pub sha: String,
+    /// First line of the commit message.
+    pub subject: String,
+    /// Why the commit was flagged.
+    pub reasons: Vec<AuditReason>,
+    /// Rules the commit suppresses.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<Suppression>,
+}
+
+/// Directory holding ck state, shared by all worktrees.
+pub fn ck_dir(repo: &Repository) -> PathBuf {
+    repo.common_dir().join("ck")
+}
+
+/// SHAs recorded in `bypass.log` by the post-commit hook.
+pub fn read_bypass_log(ck_dir: &Path) -> Vec<String> {
+    std::fs::read_to_string(ck_dir.join("bypass.log"))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Flag commits in `target` (a commit or range) that were not validated.
+///
+/// Merge commits are skipped since no commit-msg hook ran for them.
+pub fn audit(repo: &Repository, target: &str) -> Result<Vec<AuditFinding>> {
+    let logged = read_bypass_log(&ck_dir(repo));
+    let mut findings = Vec::new();
+
+    let commits = repo.get_commits_in_range(target)?;
+    let progress = Progress::bar(commits.len(), "Auditing commits");
+    for (oid, message) in commits {
+        progress.inc();
+        let is_merge = repo
+            .inner()
+            .find_commit(oid)
+            .map(|c| c.parent_count() > 1)
+            .unwrap_or(false);
+        if is_merge {
+            continue;
+        }
+
+        let sha = oid.to_string();
+        let mut reasons = Vec::new();
+        if logged.contains(&sha) {
+            reasons.push(AuditReason::Logged);
+        }
+        if !notes::is_validated(repo, oid) {
+            reasons.push(AuditReason::Unvalidated);
+        }
+        let suppressions = suppress::parse(&message);
+        if !suppressions.is_empty() {
+            reasons.push(AuditReason::Suppressed);
+        }
+
+        if !reasons.is_empty() {
+            findings.push(AuditFinding {
+                sha,
+                subject: message.lines().next().unwrap_or("").to_string(),
+                reasons,
+                suppressions,
+            });
+        }
+    }
+
+    Ok(findings)
+}
+
+/// Print audit findings to stdout.
+pub fn print_findings(findings: &[AuditFinding], format: Option<OutputFormat>) {
+    match format {
+        Some(OutputFormat::Json) => println!(
+            "{}",
+            serde_json::to_string_pretty(findings).unwrap_or_default()
+        ),
+        _ => {
+            if findings.is_empty() {
+                println!("{} All commits were validated by ck", style("✓").green());
+            }
+            for finding in findings {
+                let reason = if finding.reasons.contains(&AuditReason::Logged) {
+                    "hooks bypassed"
+                } else if finding.reasons.contains(&AuditReason::Unvalidated) {
+                    "no validation record"
+                } else {
+                    "rules suppressed"
+                };
+                println!(
+                    "{} {} {} {}",
+                    style("✗").red(),
+                    style(&finding.sha[..7.min(finding.sha.len())]).cyan(),
+                    finding.subject,
+                    style(format!("({})", reason)).dim()
+                );
+                for suppression in &finding.suppressions {
+                    println!(
+                        "    {} {}: {}",
+                        style("⊘").dim(),
+                        suppression.rules.join(", "),
+                        suppression
+                            .justification
+                            .as_deref()
+                            .unwrap_or("no justification")
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixtureRepo;
+
+    #[test]
+    fn test_audit_flags_unvalidated_commits() {
+        let fixture = FixtureRepo::new();
+        let validated = fixture.commit("feat: validated").head();
+        let bypassed = fixture.commit("wip").head();
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        notes::mark_validated(&repo, &validated, "sha256:test").unwrap();
+        let ck = ck_dir(&repo);
+        std::fs::create_dir_all(&ck).unwrap();
+        std::fs::write(
+            ck.join("bypass.log"),
+            format!("{} 2026-01-01T00:00:00Z test@example.com\n", bypassed),
+        )
+        .unwrap();
+
+        let findings = audit(&repo, &format!("{}..HEAD", validated)).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].sha, bypassed);
+        assert_eq!(
+            findings[0].reasons,
+            vec![AuditReason::Logged, AuditReason::Unvalidated]
+        );
+
+        assert!(audit(&repo, &validated).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_audit_records_suppressions() {
+        let fixture = FixtureRepo::new();
+        let base = fixture.commit("chore: start").head();
+        let head = fixture
+            .commit("feat: add the export command\n\nCk-Disable: subject-case\n  product name")
+            .head();
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        notes::mark_validated(&repo, &head, "sha256:test").unwrap();
+
+        let findings = audit(&repo, &format!("{}..HEAD", base)).unwrap();
+        assert_eq!(findings[0].reasons, vec![AuditReason::Suppressed]);
+        assert_eq!(findings[0].suppressions[0].rules, vec!["subject-case"]);
+    }
+}
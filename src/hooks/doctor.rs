@@ -0,0 +1,194 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Health checks for installed hooks.
+
+use std::fmt;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use super::templates::{HookTemplate, TEMPLATE_VERSION};
+
+/// A problem found in an installed hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookIssue {
+    /// The ck binary the hook runs cannot be found.
+    MissingBinary(String),
+    /// The hook file is not executable.
+    NotExecutable,
+    /// The hook does not start with a usable shebang.
+    BadShebang,
+    /// The hook predates the current template, with the template version
+    /// it was generated from when known.
+    Outdated(Option<u32>),
+    /// The hook was edited after it was generated.
+    Modified,
+}
+
+impl fmt::Display for HookIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookIssue::MissingBinary(binary) => {
+                write!(f, "ck binary '{}' not found", binary)
+            }
+            HookIssue::NotExecutable => write!(f, "hook is not executable"),
+            HookIssue::BadShebang => write!(f, "missing or invalid shebang"),
+            HookIssue::Outdated(Some(version)) => write!(
+                f,
+                "template version {} is older than {}",
+                version, TEMPLATE_VERSION
+            ),
+            HookIssue::Outdated(None) => write!(f, "generated before template versioning"),
+            HookIssue::Modified => write!(f, "edited since it was generated"),
+        }
+    }
+}
+
+/// Health of one installed hook.
+#[derive(Debug, Clone)]
+pub struct HookHealth {
+    /// Hook filename.
+    pub hook: String,
+    /// Problems found; empty when the hook is healthy.
+    pub issues: Vec<HookIssue>,
+}
+
+impl HookHealth {
+    /// Whether no problems were found.
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Check the hook at `path` against the script it should contain.
+pub fn diagnose(path: &Path, content: &str, expected: &str) -> Vec<HookIssue> {
+    let mut issues = Vec::new();
+
+    for binary in ck_binaries(content) {
+        if resolve_binary(&binary).is_none() {
+            issues.push(HookIssue::MissingBinary(binary));
+        }
+    }
+
+    if !is_executable(path) {
+        issues.push(HookIssue::NotExecutable);
+    }
+
+    let shebang_ok = content
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("#!"))
+        .and_then(|interpreter| interpreter.split_whitespace().next())
+        .is_some_and(|interpreter| Path::new(interpreter).exists());
+    if !shebang_ok {
+        issues.push(HookIssue::BadShebang);
+    }
+
+    match HookTemplate::installed_version(content) {
+        Some(version) if version >= TEMPLATE_VERSION => {
+            // The ck version in the header changes with every release
+            if strip_generated_by(content) != strip_generated_by(expected) {
+                issues.push(HookIssue::Modified);
+            }
+        }
+        version => issues.push(HookIssue::Outdated(version)),
+    }
+
+    issues
+}
+
+/// Commands the hook uses to invoke ck, e.g. `ck` or `/usr/local/bin/ck`.
+fn ck_binaries(content: &str) -> Vec<String> {
+    let mut binaries: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim_start();
+        if line.starts_with('#') {
+            continue;
+        }
+        // Look inside command substitutions like `$(ck smart ...)` too
+        let line = line.rsplit("$(").next().unwrap_or(line);
+        let mut words = line.split_whitespace();
+        if let (Some(binary), Some(subcommand)) = (words.next(), words.next()) {
+            let is_ck = Path::new(binary)
+                .file_name()
+                .is_some_and(|name| name == "ck");
+            if is_ck
+                && matches!(subcommand, "check" | "smart")
+                && !binaries.iter().any(|b| b == binary)
+            {
+                binaries.push(binary.to_string());
+            }
+        }
+    }
+
+    binaries
+}
+
+/// Resolve a command to an executable file, searching `PATH` for bare names.
+pub fn resolve_binary(binary: &str) -> Option<PathBuf> {
+    let path = Path::new(binary);
+    if path.components().count() > 1 {
+        return is_executable(path).then(|| path.to_path_buf());
+    }
+
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(binary))
+            .find(|candidate| is_executable(candidate))
+    })
+}
+
+fn strip_generated_by(content: &str) -> Vec<&str> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with("# Generated by ck v"))
+        .collect()
+}
+
+fn is_executable(path: &Path) -> bool {
+    match fs::metadata(path) {
+        #[cfg(unix)]
+        Ok(metadata) => metadata.is_file() && metadata.permissions().mode() & 0o111 != 0,
+        #[cfg(not(unix))]
+        Ok(metadata) => metadata.is_file(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ck_binaries() {
+        let content = "#!/bin/sh\n# ck check is run below\nck check --ci \"$1\"\n\
+                       GENERATED=$(/opt/ck/bin/ck smart --ci 2>/dev/null)\n";
+        assert_eq!(ck_binaries(content), vec!["ck", "/opt/ck/bin/ck"]);
+    }
+
+    #[test]
+    fn test_diagnose_stale_hook() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("commit-msg");
+        let content = "# Template version: 1\n/nonexistent/ck check --ci \"$1\"\n";
+        fs::write(&path, content).unwrap();
+
+        let issues = diagnose(&path, content, "expected");
+        assert!(issues.contains(&HookIssue::MissingBinary("/nonexistent/ck".to_string())));
+        assert!(issues.contains(&HookIssue::BadShebang));
+        assert!(issues.contains(&HookIssue::Outdated(Some(1))));
+        #[cfg(unix)]
+        assert!(issues.contains(&HookIssue::NotExecutable));
+
+        // A release bump alone does not make a hook stale
+        let expected = HookTemplate::CommitMsg.generate();
+        let older_release =
+            expected.replacen(&format!("ck v{}", crate::version::VERSION), "ck v0.0.1", 1);
+        assert!(!diagnose(&path, &older_release, &expected)
+            .iter()
+            .any(|issue| matches!(issue, HookIssue::Outdated(_) | HookIssue::Modified)));
+    }
+}
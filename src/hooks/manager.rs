@@ -10,7 +10,30 @@ use std::fs;
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 
-use super::templates::HookTemplate;
+use super::doctor::{diagnose, HookHealth};
+use super::templates::{HookTemplate, TEMPLATE_VERSION};
+
+/// What to do with an existing hook that was not installed by ck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictAction {
+    /// Fail with `AlreadyExists`.
+    Abort,
+    /// Move the hook to `<hook>.backup` and replace it.
+    Backup,
+    /// Move the hook to `<hook>.local` and run it before ck.
+    Chain,
+}
+
+/// Installation state of a hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStatus {
+    /// Missing, or installed by something other than ck.
+    NotInstalled,
+    /// Installed by ck from the current template.
+    Installed,
+    /// Installed by ck from an older template.
+    Outdated,
+}
 
 /// Manager for git hooks.
 pub struct HookManager {
@@ -33,57 +56,80 @@ impl HookManager {
             })?;
         }
 
-        Ok(Self { hooks_dir })
+        Ok(Self::with_hooks_dir(hooks_dir))
     }
 
-    /// Install a specific hook.
-    pub fn install_hook(&self, hook_name: &str, force: bool) -> Result<()> {
-        let template = hook_name.parse::<HookTemplate>().ok().ok_or_else(|| {
-            CkError::Hook(HookError::NotFound {
-                hook: hook_name.to_string(),
-            })
-        })?;
+    /// Create a hook manager for an existing hooks directory.
+    pub fn with_hooks_dir(hooks_dir: PathBuf) -> Self {
+        Self { hooks_dir }
+    }
 
-        self.install_template(&template, force)
+    /// Install a specific hook.
+    pub fn install_hook(&self, hook_name: &str, on_conflict: ConflictAction) -> Result<()> {
+        let template = Self::template(hook_name)?;
+        self.install_template(&template, on_conflict)
     }
 
     /// Install all hooks.
-    pub fn install_all(&self, force: bool) -> Result<()> {
+    pub fn install_all(&self, on_conflict: ConflictAction) -> Result<()> {
         for template in HookTemplate::all() {
-            self.install_template(template, force)?;
+            self.install_template(template, on_conflict)?;
         }
         Ok(())
     }
 
+    /// Get the path of an existing hook that was not installed by ck.
+    pub fn conflicting_hook(&self, hook_name: &str) -> Result<Option<PathBuf>> {
+        let template = Self::template(hook_name)?;
+        let hook_path = self.hooks_dir.join(template.filename());
+
+        if hook_path.exists() && !self.is_ck_hook(&hook_path)? {
+            Ok(Some(hook_path))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Install a hook from a template.
-    fn install_template(&self, template: &HookTemplate, force: bool) -> Result<()> {
+    fn install_template(&self, template: &HookTemplate, on_conflict: ConflictAction) -> Result<()> {
         let hook_path = self.hooks_dir.join(template.filename());
         let backup_path = self
             .hooks_dir
             .join(format!("{}.backup", template.filename()));
+        let chained_name = format!("{}.local", template.filename());
+        let chained_path = self.hooks_dir.join(&chained_name);
 
-        // Check if hook already exists
-        if hook_path.exists() && !force {
-            // Check if it's our hook
-            if !self.is_ck_hook(&hook_path)? {
-                return Err(CkError::Hook(HookError::AlreadyExists {
-                    hook: template.filename().to_string(),
-                }));
-            }
-        }
-
-        // Backup existing hook if it's not ours
+        // Move an existing hook that is not ours out of the way
         if hook_path.exists() && !self.is_ck_hook(&hook_path)? {
-            fs::rename(&hook_path, &backup_path).map_err(|e| {
+            let target = match on_conflict {
+                ConflictAction::Abort => {
+                    return Err(CkError::Hook(HookError::AlreadyExists {
+                        hook: template.filename().to_string(),
+                    }))
+                }
+                ConflictAction::Backup => &backup_path,
+                ConflictAction::Chain => {
+                    if chained_path.exists() {
+                        return Err(CkError::Hook(HookError::InstallFailed {
+                            hook: template.filename().to_string(),
+                            message: format!("{} already exists", chained_name),
+                        }));
+                    }
+                    &chained_path
+                }
+            };
+
+            fs::rename(&hook_path, target).map_err(|e| {
                 CkError::Hook(HookError::InstallFailed {
                     hook: template.filename().to_string(),
-                    message: format!("Failed to backup existing hook: {}", e),
+                    message: format!("Failed to move existing hook: {}", e),
                 })
             })?;
         }
 
-        // Generate and write hook
-        let script = template.generate();
+        let script = self.expected_script(template);
+
+        // Write hook
         fs::write(&hook_path, &script).map_err(|e| {
             CkError::Hook(HookError::InstallFailed {
                 hook: template.filename().to_string(),
@@ -117,16 +163,15 @@ impl HookManager {
 
     /// Uninstall a specific hook.
     pub fn uninstall_hook(&self, hook_name: &str) -> Result<()> {
-        let template = hook_name.parse::<HookTemplate>().ok().ok_or_else(|| {
-            CkError::Hook(HookError::NotFound {
-                hook: hook_name.to_string(),
-            })
-        })?;
+        let template = Self::template(hook_name)?;
 
         let hook_path = self.hooks_dir.join(template.filename());
         let backup_path = self
             .hooks_dir
             .join(format!("{}.backup", template.filename()));
+        let chained_path = self
+            .hooks_dir
+            .join(format!("{}.local", template.filename()));
 
         if !hook_path.exists() {
             return Ok(()); // Nothing to uninstall
@@ -147,8 +192,10 @@ impl HookManager {
             })
         })?;
 
-        // Restore backup if exists
-        if backup_path.exists() {
+        // Restore the chained hook or backup if one exists
+        if chained_path.exists() {
+            fs::rename(&chained_path, &hook_path).ok();
+        } else if backup_path.exists() {
             fs::rename(&backup_path, &hook_path).ok();
         }
 
@@ -157,32 +204,52 @@ impl HookManager {
 
     /// Uninstall all hooks.
     pub fn uninstall_all(&self) -> Result<()> {
-        for template in HookTemplate::all() {
+        for template in HookTemplate::known() {
             self.uninstall_hook(template.filename())?;
         }
         Ok(())
     }
 
     /// Get the status of all hooks.
-    pub fn status(&self) -> Result<Vec<(String, bool)>> {
+    pub fn status(&self) -> Result<Vec<(String, HookStatus)>> {
         let mut status = Vec::new();
 
-        for template in HookTemplate::all() {
+        for template in HookTemplate::known() {
             let hook_path = self.hooks_dir.join(template.filename());
-            let installed = hook_path.exists() && self.is_ck_hook(&hook_path).unwrap_or(false);
-            status.push((template.filename().to_string(), installed));
+            let state = if !hook_path.exists() || !self.is_ck_hook(&hook_path).unwrap_or(false) {
+                HookStatus::NotInstalled
+            } else {
+                let content = fs::read_to_string(&hook_path).unwrap_or_default();
+                match HookTemplate::installed_version(&content) {
+                    Some(version) if version >= TEMPLATE_VERSION => HookStatus::Installed,
+                    _ => HookStatus::Outdated,
+                }
+            };
+            status.push((template.filename().to_string(), state));
         }
 
         Ok(status)
     }
 
+    /// Regenerate outdated ck hooks, leaving other hooks untouched.
+    ///
+    /// Returns the names of the upgraded hooks.
+    pub fn upgrade(&self) -> Result<Vec<String>> {
+        let mut upgraded = Vec::new();
+
+        for (hook, state) in self.status()? {
+            if state == HookStatus::Outdated {
+                self.repair(&hook)?;
+                upgraded.push(hook);
+            }
+        }
+
+        Ok(upgraded)
+    }
+
     /// Run a hook manually.
     pub fn run_hook(&self, hook_name: &str, args: &[String]) -> Result<()> {
-        let template = hook_name.parse::<HookTemplate>().ok().ok_or_else(|| {
-            CkError::Hook(HookError::NotFound {
-                hook: hook_name.to_string(),
-            })
-        })?;
+        let template = Self::template(hook_name)?;
 
         let hook_path = self.hooks_dir.join(template.filename());
 
@@ -213,6 +280,58 @@ impl HookManager {
         Ok(())
     }
 
+    /// Check every hook installed by ck for problems.
+    ///
+    /// Hooks that are missing or were not installed by ck are skipped.
+    pub fn doctor(&self) -> Result<Vec<HookHealth>> {
+        let mut report = Vec::new();
+
+        for template in HookTemplate::known() {
+            let hook_path = self.hooks_dir.join(template.filename());
+            if !hook_path.exists() || !self.is_ck_hook(&hook_path)? {
+                continue;
+            }
+
+            let content = fs::read_to_string(&hook_path).map_err(|e| {
+                CkError::Hook(HookError::ExecutionFailed {
+                    hook: template.filename().to_string(),
+                    message: format!("Failed to read hook: {}", e),
+                })
+            })?;
+            report.push(HookHealth {
+                hook: template.filename().to_string(),
+                issues: diagnose(&hook_path, &content, &self.expected_script(template)),
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// Regenerate an installed ck hook in place from the current template.
+    pub fn repair(&self, hook_name: &str) -> Result<()> {
+        let template = Self::template(hook_name)?;
+        self.install_template(&template, ConflictAction::Abort)
+    }
+
+    /// The script a hook should contain, chaining a hook that was chained by
+    /// an earlier install.
+    fn expected_script(&self, template: &HookTemplate) -> String {
+        let chained_name = format!("{}.local", template.filename());
+        if self.hooks_dir.join(&chained_name).exists() {
+            template.generate_chained(&chained_name)
+        } else {
+            template.generate()
+        }
+    }
+
+    fn template(hook_name: &str) -> Result<HookTemplate> {
+        hook_name.parse::<HookTemplate>().map_err(|_| {
+            CkError::Hook(HookError::NotFound {
+                hook: hook_name.to_string(),
+            })
+        })
+    }
+
     /// Check if a hook was installed by ck.
     fn is_ck_hook(&self, path: &Path) -> Result<bool> {
         let content = fs::read_to_string(path).map_err(|e| {
@@ -228,10 +347,89 @@ impl HookManager {
 
 #[cfg(test)]
 mod tests {
+    use super::super::doctor::HookIssue;
+    use super::*;
+    use crate::testing::FixtureRepo;
+
+    const CUSTOM_HOOK: &str = "#!/bin/sh\necho custom\n";
 
     #[test]
     fn test_is_ck_hook_detection() {
         let content = "#!/bin/sh\n# CK Git Hook\n# Generated by ck v0.1.0\n";
         assert!(content.contains("CK Git Hook"));
     }
+
+    #[test]
+    fn test_install_conflict_abort_and_backup() {
+        let fixture = FixtureRepo::new();
+        let hooks_dir = fixture.join(".git/hooks");
+        let manager = HookManager::with_hooks_dir(hooks_dir.clone());
+        fs::write(hooks_dir.join("commit-msg"), CUSTOM_HOOK).unwrap();
+
+        assert!(manager.conflicting_hook("commit-msg").unwrap().is_some());
+        assert!(matches!(
+            manager.install_hook("commit-msg", ConflictAction::Abort),
+            Err(CkError::Hook(HookError::AlreadyExists { .. }))
+        ));
+
+        manager
+            .install_hook("commit-msg", ConflictAction::Backup)
+            .unwrap();
+        assert!(manager.conflicting_hook("commit-msg").unwrap().is_none());
+        assert_eq!(
+            fs::read_to_string(hooks_dir.join("commit-msg.backup")).unwrap(),
+            CUSTOM_HOOK
+        );
+    }
+
+    #[test]
+    fn test_install_chain_survives_reinstall() {
+        let fixture = FixtureRepo::new();
+        let hooks_dir = fixture.join(".git/hooks");
+        let manager = HookManager::with_hooks_dir(hooks_dir.clone());
+        let hook_path = hooks_dir.join("pre-push");
+        fs::write(&hook_path, CUSTOM_HOOK).unwrap();
+
+        manager
+            .install_hook("pre-push", ConflictAction::Chain)
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(hooks_dir.join("pre-push.local")).unwrap(),
+            CUSTOM_HOOK
+        );
+
+        // Reinstalling without --chain keeps the chained hook running
+        manager
+            .install_hook("pre-push", ConflictAction::Abort)
+            .unwrap();
+        assert!(fs::read_to_string(&hook_path)
+            .unwrap()
+            .contains("pre-push.local"));
+
+        // A freshly installed hook is healthy apart from the ck binary lookup
+        let report = manager.doctor().unwrap();
+        assert_eq!(report.len(), 1);
+        assert!(report[0]
+            .issues
+            .iter()
+            .all(|issue| matches!(issue, HookIssue::MissingBinary(_))));
+
+        // An old template is reported and upgraded in place
+        let old = fs::read_to_string(&hook_path)
+            .unwrap()
+            .replace(&format!("# Template version: {}", TEMPLATE_VERSION), "");
+        fs::write(&hook_path, old).unwrap();
+        let status = manager.status().unwrap();
+        assert!(status.contains(&("pre-push".to_string(), HookStatus::Outdated)));
+        assert_eq!(manager.upgrade().unwrap(), vec!["pre-push"]);
+        assert!(fs::read_to_string(&hook_path)
+            .unwrap()
+            .contains("pre-push.local"));
+        assert!(manager.upgrade().unwrap().is_empty());
+
+        // Uninstalling puts the original hook back
+        manager.uninstall_hook("pre-push").unwrap();
+        assert_eq!(fs::read_to_string(&hook_path).unwrap(), CUSTOM_HOOK);
+        assert!(!hooks_dir.join("pre-push.local").exists());
+    }
 }
@@ -3,8 +3,11 @@
 
 //! Git hooks management.
 
+pub mod audit;
+mod doctor;
 mod manager;
 mod templates;
 
-pub use manager::HookManager;
-pub use templates::HookTemplate;
+pub use doctor::{HookHealth, HookIssue};
+pub use manager::{ConflictAction, HookManager, HookStatus};
+pub use templates::{HookTemplate, TEMPLATE_VERSION};
@@ -5,12 +5,16 @@
 
 use crate::version;
 
+/// Version of the hook scripts; bump whenever a generated script changes.
+pub const TEMPLATE_VERSION: u32 = 4;
+
 /// Hook template type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HookTemplate {
     CommitMsg,
     PrepareCommitMsg,
     PrePush,
+    PostCommit,
 }
 
 impl HookTemplate {
@@ -20,10 +24,11 @@ impl HookTemplate {
             HookTemplate::CommitMsg => "commit-msg",
             HookTemplate::PrepareCommitMsg => "prepare-commit-msg",
             HookTemplate::PrePush => "pre-push",
+            HookTemplate::PostCommit => "post-commit",
         }
     }
 
-    /// Get all available templates.
+    /// Get the templates installed by default.
     pub fn all() -> &'static [HookTemplate] {
         &[
             HookTemplate::CommitMsg,
@@ -32,26 +37,92 @@ impl HookTemplate {
         ]
     }
 
+    /// Get every template, including opt-in ones.
+    pub fn known() -> &'static [HookTemplate] {
+        &[
+            HookTemplate::CommitMsg,
+            HookTemplate::PrepareCommitMsg,
+            HookTemplate::PrePush,
+            HookTemplate::PostCommit,
+        ]
+    }
+
     /// Generate the hook script.
     pub fn generate(&self) -> String {
-        let version_str = version::VERSION;
-        let header = format!(
+        format!("{}{}", self.header(), self.body())
+    }
+
+    /// Generate a hook that runs the pre-existing hook at `chained` (a path
+    /// relative to the hooks directory) before the ck checks.
+    ///
+    /// The old hook's exit status is kept: if it fails, ck does not run.
+    pub fn generate_chained(&self, chained: &str) -> String {
+        let run_chained = match self {
+            // pre-push gets the pushed refs on stdin, so both hooks need a copy
+            HookTemplate::PrePush => format!(
+                r#"# Run the pre-existing hook first
+CK_CHAINED="$(dirname "$0")/{0}"
+CK_PUSH_REFS=$(mktemp)
+cat > "$CK_PUSH_REFS"
+if [ -x "$CK_CHAINED" ]; then
+    "$CK_CHAINED" "$@" < "$CK_PUSH_REFS"
+    CK_CHAINED_STATUS=$?
+    if [ $CK_CHAINED_STATUS -ne 0 ]; then
+        rm -f "$CK_PUSH_REFS"
+        exit $CK_CHAINED_STATUS
+    fi
+fi
+exec < "$CK_PUSH_REFS"
+rm -f "$CK_PUSH_REFS"
+
+"#,
+                chained
+            ),
+            _ => format!(
+                r#"# Run the pre-existing hook first
+CK_CHAINED="$(dirname "$0")/{0}"
+if [ -x "$CK_CHAINED" ]; then
+    "$CK_CHAINED" "$@" || exit $?
+fi
+
+"#,
+                chained
+            ),
+        };
+
+        format!("{}{}{}", self.header(), run_chained, self.body())
+    }
+
+    fn header(&self) -> String {
+        format!(
             r#"#!/bin/sh
 # CK Git Hook
 # Generated by ck v{}
+# Template version: {}
 # Do not edit manually - regenerate with: ck hooks install
 
 "#,
-            version_str
-        );
+            version::VERSION,
+            TEMPLATE_VERSION
+        )
+    }
 
-        let body = match self {
+    /// Template version of an installed script, if it records one.
+    pub fn installed_version(content: &str) -> Option<u32> {
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix("# Template version:"))
+            .and_then(|version| version.trim().parse().ok())
+    }
+
+    fn body(&self) -> &'static str {
+        match self {
             HookTemplate::CommitMsg => {
                 r#"# Validate commit message
 COMMIT_MSG_FILE="$1"
 
 # Run ck check on the commit message
-ck check --ci "$COMMIT_MSG_FILE" 2>&1
+ck check --ci --message-file "$COMMIT_MSG_FILE" 2>&1
 
 EXIT_CODE=$?
 if [ $EXIT_CODE -ne 0 ]; then
@@ -60,6 +131,10 @@ if [ $EXIT_CODE -ne 0 ]; then
     echo "Fix the issues above or use --no-verify to skip."
     exit 1
 fi
+
+# Record the validated tree so post-commit can tell this commit was checked
+CK_DIR="$(git rev-parse --git-common-dir)/ck"
+mkdir -p "$CK_DIR" && git write-tree > "$CK_DIR/validated" 2>/dev/null
 "#
             }
             HookTemplate::PrepareCommitMsg => {
@@ -122,9 +197,23 @@ done
 exit 0
 "#
             }
-        };
+            HookTemplate::PostCommit => {
+                r#"# Audit commits created with --no-verify
+CK_DIR="$(git rev-parse --git-common-dir)/ck"
+mkdir -p "$CK_DIR"
 
-        format!("{}{}", header, body)
+TREE=$(git rev-parse "HEAD^{tree}")
+if [ -f "$CK_DIR/validated" ] && [ "$(cat "$CK_DIR/validated")" = "$TREE" ]; then
+    git notes --ref=ck add -f -m "ck: validated" HEAD >/dev/null 2>&1
+else
+    echo "$(git rev-parse HEAD) $(date -u +%Y-%m-%dT%H:%M:%SZ) $(git config user.email)" >> "$CK_DIR/bypass.log"
+fi
+rm -f "$CK_DIR/validated"
+
+exit 0
+"#
+            }
+        }
     }
 }
 
@@ -136,6 +225,7 @@ impl std::str::FromStr for HookTemplate {
             "commit-msg" => Ok(HookTemplate::CommitMsg),
             "prepare-commit-msg" => Ok(HookTemplate::PrepareCommitMsg),
             "pre-push" => Ok(HookTemplate::PrePush),
+            "post-commit" => Ok(HookTemplate::PostCommit),
             _ => Err(()),
         }
     }
@@ -158,6 +248,33 @@ mod tests {
         assert!(script.contains("ck"));
     }
 
+    #[test]
+    fn test_hook_generate_chained() {
+        let script = HookTemplate::CommitMsg.generate_chained("commit-msg.local");
+        assert!(script.contains("# CK Git Hook"));
+        let chained = script.find("commit-msg.local").unwrap();
+        let check = script.find("ck check").unwrap();
+        assert!(chained < check);
+
+        // pre-push replays the refs for its own loop
+        let script = HookTemplate::PrePush.generate_chained("pre-push.local");
+        assert!(script.contains("exec < \"$CK_PUSH_REFS\""));
+    }
+
+    #[test]
+    fn test_installed_version() {
+        let script = HookTemplate::PrePush.generate();
+        assert_eq!(
+            HookTemplate::installed_version(&script),
+            Some(TEMPLATE_VERSION)
+        );
+        // Scripts from before template versioning have none
+        assert_eq!(
+            HookTemplate::installed_version("#!/bin/sh\n# CK Git Hook\n"),
+            None
+        );
+    }
+
     #[test]
     fn test_hook_all() {
         let all = HookTemplate::all();
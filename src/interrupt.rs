@@ -0,0 +1,366 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Cleanup when ck is interrupted.
+//!
+//! Operations that leave the repository in a temporary state (a narrowed
+//! index, stashed changes, a history rewrite in progress, a staged
+//! download) register a cleanup action while that state exists. On
+//! SIGINT or SIGTERM the actions run, newest first, the cursor is shown
+//! again, and ck exits with the conventional status.
+//!
+//! The signal handler only records the signal. The actions run on the
+//! main thread when it reaches a [`checkpoint`], and nowhere else, so they
+//! never race the operation they undo; blocking system calls are
+//! interrupted so the main thread gets there promptly. If it has not
+//! within a grace period, a watcher thread says what ck is waiting for. A
+//! second signal exits at once and names the cleanups left undone.
+//!
+//! While a child such as the message editor owns the terminal, Ctrl-C is
+//! left to the child; see [`ignore_interrupts`].
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, Once};
+
+type Action = Box<dyn FnOnce() + Send>;
+
+/// Cleanup actions in registration order.
+#[derive(Default)]
+struct Registry {
+    actions: Vec<(u64, &'static str, Action)>,
+}
+
+impl Registry {
+    const fn new() -> Self {
+        Self {
+            actions: Vec::new(),
+        }
+    }
+
+    fn register(&mut self, id: u64, description: &'static str, action: Action) {
+        self.actions.push((id, description, action));
+    }
+
+    fn unregister(&mut self, id: u64) {
+        self.actions.retain(|(other, _, _)| *other != id);
+    }
+
+    /// Descriptions of the pending actions, newest first.
+    fn descriptions(&self) -> Vec<&'static str> {
+        self.actions
+            .iter()
+            .rev()
+            .map(|(_, description, _)| *description)
+            .collect()
+    }
+
+    /// Take every action, newest first.
+    fn drain(&mut self) -> Vec<(&'static str, Action)> {
+        std::mem::take(&mut self.actions)
+            .into_iter()
+            .rev()
+            .map(|(_, description, action)| (description, action))
+            .collect()
+    }
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry::new());
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+static INSTALL: Once = Once::new();
+
+/// Exit status to use once interrupted, or 0 if not interrupted.
+static INTERRUPTED: AtomicI32 = AtomicI32::new(0);
+
+/// Set by whichever thread runs the cleanups.
+static FINISHING: AtomicBool = AtomicBool::new(false);
+
+/// Number of live [`Ignoring`] guards.
+static IGNORING: AtomicUsize = AtomicUsize::new(0);
+
+/// Exit status after SIGINT, as a shell reports it.
+pub const INTERRUPTED_STATUS: i32 = 130;
+
+/// How long the main thread has to reach a checkpoint after a signal
+/// before ck says it is waiting.
+const GRACE_PERIOD_MS: i32 = 3000;
+
+/// A registered cleanup action, unregistered when dropped.
+#[must_use = "the action is unregistered when the guard is dropped"]
+#[derive(Debug)]
+pub struct Cleanup {
+    id: u64,
+}
+
+impl Drop for Cleanup {
+    fn drop(&mut self) {
+        if let Ok(mut registry) = REGISTRY.lock() {
+            registry.unregister(self.id);
+        }
+    }
+}
+
+/// Run `action` if ck is interrupted while the returned guard is alive.
+pub fn on_interrupt(description: &'static str, action: impl FnOnce() + Send + 'static) -> Cleanup {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    if let Ok(mut registry) = REGISTRY.lock() {
+        registry.register(id, description, Box::new(action));
+    }
+    Cleanup { id }
+}
+
+/// Remove `paths` if ck is interrupted while the returned guard is alive.
+pub fn remove_on_interrupt(paths: Vec<PathBuf>) -> Cleanup {
+    on_interrupt("remove temporary files", move || {
+        for path in paths {
+            let _ = std::fs::remove_file(path);
+        }
+    })
+}
+
+/// SIGINT is ignored while the returned guard is alive.
+#[must_use = "interrupts are handled again when the guard is dropped"]
+#[derive(Debug)]
+pub struct Ignoring {
+    _private: (),
+}
+
+impl Drop for Ignoring {
+    fn drop(&mut self) {
+        IGNORING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Leave SIGINT to a child process, such as an editor, that shares the
+/// terminal until the returned guard is dropped. The child still sees
+/// Ctrl-C and decides what it means; ck keeps running.
+pub fn ignore_interrupts() -> Ignoring {
+    IGNORING.fetch_add(1, Ordering::SeqCst);
+    Ignoring { _private: () }
+}
+
+fn is_ignoring() -> bool {
+    IGNORING.load(Ordering::SeqCst) > 0
+}
+
+/// Whether a signal has been received and not yet handled.
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst) != 0
+}
+
+/// Stop here if ck has been interrupted: run the cleanups on this
+/// thread and exit. Call this where the main flow can safely stop, such
+/// as between the steps of a long operation.
+pub fn checkpoint() {
+    let status = INTERRUPTED.load(Ordering::SeqCst);
+    if status != 0 {
+        finish(status);
+    }
+}
+
+/// Run the cleanups and exit, unless another thread already is, in
+/// which case wait for it to exit the process.
+fn finish(status: i32) -> ! {
+    claim_exit();
+    run_cleanups();
+    restore_terminal();
+    std::process::exit(status);
+}
+
+/// Exit without running the cleanups, naming the ones left undone. Used
+/// from the watcher thread, which must not touch the repository while the
+/// main thread may still be working in it.
+fn abandon(status: i32) -> ! {
+    claim_exit();
+    restore_terminal();
+    let pending = match REGISTRY.lock() {
+        Ok(registry) => registry.descriptions(),
+        Err(_) => Vec::new(),
+    };
+    if !pending.is_empty() {
+        eprintln!(
+            "{} Stopped without cleaning up; still to do: {}",
+            console::style("⚠").yellow(),
+            pending.join(", ")
+        );
+    }
+    std::process::exit(status);
+}
+
+/// Make this thread the one that exits, or wait for the one that is.
+fn claim_exit() {
+    if FINISHING.swap(true, Ordering::SeqCst) {
+        loop {
+            std::thread::park();
+        }
+    }
+}
+
+/// Run and unregister every action, newest first.
+fn run_cleanups() {
+    let actions = match REGISTRY.lock() {
+        Ok(mut registry) => registry.drain(),
+        Err(_) => return,
+    };
+    for (description, action) in actions {
+        tracing::debug!("Interrupted: {}", description);
+        action();
+    }
+}
+
+/// Restore the terminal after an interrupted prompt or progress bar.
+fn restore_terminal() {
+    for term in [console::Term::stderr(), console::Term::stdout()] {
+        if term.is_term() {
+            let _ = term.show_cursor();
+        }
+    }
+    eprintln!();
+}
+
+/// Install the SIGINT and SIGTERM handlers. Calling this again is a no-op.
+pub fn install() {
+    INSTALL.call_once(imp::install);
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::os::raw::c_int;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    /// Write end of the pipe the handler wakes the watcher through.
+    static PIPE: AtomicI32 = AtomicI32::new(-1);
+
+    extern "C" fn handle(signal: c_int) {
+        if signal == libc::SIGINT && super::is_ignoring() {
+            return;
+        }
+        let status = if signal == libc::SIGTERM {
+            128 + libc::SIGTERM
+        } else {
+            super::INTERRUPTED_STATUS
+        };
+        // Only async-signal-safe calls here
+        let _ = super::INTERRUPTED.compare_exchange(0, status, Ordering::SeqCst, Ordering::SeqCst);
+        let fd = PIPE.load(Ordering::Relaxed);
+        let byte = signal as u8;
+        unsafe {
+            libc::write(fd, &byte as *const u8 as *const libc::c_void, 1);
+        }
+    }
+
+    /// Wait up to `timeout_ms` for the pipe to become readable.
+    fn wait_readable(fd: c_int, timeout_ms: c_int) -> bool {
+        let mut poll = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        unsafe { libc::poll(&mut poll, 1, timeout_ms) > 0 }
+    }
+
+    fn read_byte(fd: c_int) -> bool {
+        let mut byte = 0u8;
+        unsafe { libc::read(fd, &mut byte as *mut u8 as *mut libc::c_void, 1) == 1 }
+    }
+
+    /// Keep the signals off the watcher thread so they interrupt the
+    /// main thread's blocking calls instead.
+    fn block_signals() {
+        unsafe {
+            let mut set: libc::sigset_t = std::mem::zeroed();
+            libc::sigemptyset(&mut set);
+            libc::sigaddset(&mut set, libc::SIGINT);
+            libc::sigaddset(&mut set, libc::SIGTERM);
+            libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut());
+        }
+    }
+
+    /// Wait for the main thread to reach a checkpoint, saying so after a
+    /// grace period. A second signal exits without cleaning up.
+    fn watch(read: c_int) {
+        block_signals();
+        if !read_byte(read) {
+            return;
+        }
+        if !wait_readable(read, super::GRACE_PERIOD_MS) {
+            eprintln!("\nFinishing the current step; interrupt again to stop without cleaning up");
+            wait_readable(read, -1);
+        }
+        read_byte(read);
+        super::abandon(super::INTERRUPTED.load(Ordering::SeqCst));
+    }
+
+    pub(super) fn install() {
+        let mut fds = [0 as c_int; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            tracing::warn!("Could not install interrupt handler");
+            return;
+        }
+        let [read, write] = fds;
+        PIPE.store(write, Ordering::Relaxed);
+
+        let spawned = std::thread::Builder::new()
+            .name("ck-interrupt".to_string())
+            .spawn(move || watch(read));
+        if spawned.is_err() {
+            tracing::warn!("Could not start interrupt handler");
+            return;
+        }
+
+        // No SA_RESTART, so blocking reads and waits on the main thread
+        // return early and it reaches a checkpoint
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handle as extern "C" fn(c_int) as libc::sighandler_t;
+            action.sa_flags = 0;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(libc::SIGINT, &action, std::ptr::null_mut());
+            libc::sigaction(libc::SIGTERM, &action, std::ptr::null_mut());
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    pub(super) fn install() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_registry_runs_newest_first() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = Registry::new();
+        for (id, name) in [(0, "first"), (1, "dropped"), (2, "second")] {
+            let log = Arc::clone(&log);
+            registry.register(id, name, Box::new(move || log.lock().unwrap().push(name)));
+        }
+        registry.unregister(1);
+
+        assert_eq!(registry.descriptions(), vec!["second", "first"]);
+        for (_, action) in registry.drain() {
+            action();
+        }
+        assert_eq!(*log.lock().unwrap(), vec!["second", "first"]);
+        assert!(registry.drain().is_empty());
+    }
+
+    #[test]
+    fn test_ignore_interrupts_nests() {
+        assert!(!is_interrupted());
+        checkpoint();
+
+        let outer = ignore_interrupts();
+        let inner = ignore_interrupts();
+        drop(inner);
+        assert!(is_ignoring());
+        drop(outer);
+        assert!(!is_ignoring());
+    }
+}
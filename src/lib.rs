@@ -41,11 +41,17 @@ pub mod config;
 pub mod error;
 pub mod git;
 pub mod hooks;
+pub mod interrupt;
+pub mod lsp;
 pub mod monorepo;
 pub mod plugins;
+pub mod progress;
 pub mod rules;
 pub mod security;
 pub mod smart;
+#[cfg(any(test, feature = "testing"))]
+#[path = "../tests/fixtures/mod.rs"]
+pub mod testing;
 
 // Re-exports for convenience
 pub use config::CkConfig;
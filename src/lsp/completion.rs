@@ -0,0 +1,238 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Completion of commit types, scopes and trailers.
+
+use crate::commit::CommitMessage;
+use crate::config::CkConfig;
+use crate::git::Repository;
+use crate::monorepo::detect_packages;
+use std::path::Path;
+
+/// Number of past commits scanned for scopes.
+const HISTORY_DEPTH: usize = 200;
+
+/// Trailer tokens offered in the footer.
+const TRAILERS: &[(&str, &str)] = &[
+    ("BREAKING CHANGE", "Describe a breaking change"),
+    (
+        "Signed-off-by",
+        "Certify the Developer Certificate of Origin",
+    ),
+    ("Co-authored-by", "Credit a co-author"),
+    ("Reviewed-by", "Credit a reviewer"),
+    ("Refs", "Reference related issues"),
+    ("Closes", "Close an issue when merged"),
+];
+
+/// What a completion item inserts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// A commit type in the header.
+    Type,
+    /// A scope inside the header parentheses.
+    Scope,
+    /// A trailer token at the start of a footer line.
+    Trailer,
+}
+
+/// A single completion candidate.
+#[derive(Debug, Clone)]
+pub struct CompletionItem {
+    /// Label shown in the completion menu.
+    pub label: String,
+    /// Short description shown next to the label.
+    pub detail: Option<String>,
+    /// What the item completes.
+    pub kind: CompletionKind,
+    /// Text replacing the partial word before the cursor.
+    pub insert_text: String,
+    /// Column (in characters) where the partial word starts.
+    pub start: usize,
+}
+
+/// Known commit types and scopes for a repository.
+#[derive(Debug, Clone, Default)]
+pub struct CompletionSource {
+    types: Vec<(String, Option<String>)>,
+    scopes: Vec<String>,
+}
+
+impl CompletionSource {
+    /// Collect types from the config and scopes from the config,
+    /// monorepo packages and commit history under `root`.
+    pub fn new(config: &CkConfig, root: Option<&Path>) -> Self {
+        let types = config
+            .allowed_type_names()
+            .iter()
+            .filter(|t| !config.rules.forbidden_types.contains(t))
+            .map(|t| {
+                let description = config
+                    .commit_type(t)
+                    .map(|ct| config.type_description(&ct).to_string())
+                    .filter(|description| !description.is_empty());
+                (t.clone(), description)
+            })
+            .collect();
+
+        let mut scopes: Vec<String> = config.rules.scope.allowed.clone();
+        if let Some(root) = root {
+            scopes.extend(detect_packages(root, config).into_iter().map(|p| p.scope));
+            if let Ok(repo) = Repository::open(root) {
+                let history = repo
+                    .recent_commit_messages(HISTORY_DEPTH)
+                    .unwrap_or_default();
+                scopes.extend(
+                    history
+                        .iter()
+                        .filter_map(|m| CommitMessage::parse(m).ok())
+                        .filter_map(|m| m.scope),
+                );
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        scopes.retain(|s| {
+            !s.is_empty() && !config.rules.scope.forbidden.contains(s) && seen.insert(s.clone())
+        });
+
+        Self { types, scopes }
+    }
+
+    /// Complete at a position in a commit message buffer.
+    ///
+    /// `character` is a column in characters on the zero-based `line`.
+    pub fn complete(&self, text: &str, line: usize, character: usize) -> Vec<CompletionItem> {
+        let lines: Vec<&str> = text.lines().collect();
+        let current = lines.get(line).copied().unwrap_or("");
+        if current.starts_with('#') {
+            return Vec::new();
+        }
+        let prefix: String = current.chars().take(character).collect();
+
+        // The header is the first line that is neither blank nor a comment
+        let header_line = lines
+            .iter()
+            .position(|l| !l.trim().is_empty() && !l.starts_with('#'))
+            .unwrap_or(line);
+
+        if line <= header_line {
+            self.complete_header(&prefix)
+        } else if in_footer(&lines, header_line, line) {
+            complete_trailer(&prefix)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Complete a type or scope within the header.
+    fn complete_header(&self, prefix: &str) -> Vec<CompletionItem> {
+        if prefix.contains(':') || prefix.contains(')') {
+            return Vec::new();
+        }
+
+        if let Some(open) = prefix.find('(') {
+            let partial = &prefix[open + 1..];
+            let start = prefix[..open].chars().count() + 1;
+            return self
+                .scopes
+                .iter()
+                .filter(|s| s.starts_with(partial))
+                .map(|s| CompletionItem {
+                    label: s.clone(),
+                    detail: Some("scope".to_string()),
+                    kind: CompletionKind::Scope,
+                    insert_text: s.clone(),
+                    start,
+                })
+                .collect();
+        }
+
+        if prefix.contains(['!', ' ']) {
+            return Vec::new();
+        }
+
+        self.types
+            .iter()
+            .filter(|(t, _)| t.starts_with(prefix))
+            .map(|(t, description)| CompletionItem {
+                label: t.clone(),
+                detail: description.clone(),
+                kind: CompletionKind::Type,
+                insert_text: t.clone(),
+                start: 0,
+            })
+            .collect()
+    }
+}
+
+/// Check whether a line lies past the blank line separating the header,
+/// where trailers may start.
+fn in_footer(lines: &[&str], header_line: usize, line: usize) -> bool {
+    lines[header_line + 1..line.min(lines.len())]
+        .iter()
+        .filter(|l| !l.starts_with('#'))
+        .any(|l| l.trim().is_empty())
+}
+
+/// Complete a trailer token at the start of a line.
+fn complete_trailer(prefix: &str) -> Vec<CompletionItem> {
+    if prefix.contains(':') {
+        return Vec::new();
+    }
+    let lower = prefix.to_lowercase();
+
+    TRAILERS
+        .iter()
+        .filter(|(token, _)| token.to_lowercase().starts_with(&lower))
+        .map(|(token, description)| CompletionItem {
+            label: token.to_string(),
+            detail: Some(description.to_string()),
+            kind: CompletionKind::Trailer,
+            insert_text: format!("{}: ", token),
+            start: 0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> CompletionSource {
+        let mut config = CkConfig::default();
+        config.rules.scope.allowed = vec!["core".to_string(), "cli".to_string()];
+        CompletionSource::new(&config, None)
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|i| i.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_complete_type() {
+        let items = source().complete("fe\n", 0, 2);
+        assert_eq!(labels(&items), vec!["feat"]);
+        assert_eq!(items[0].detail.as_deref(), Some("A new feature"));
+        assert!(source().complete("feat: add\n", 0, 9).is_empty());
+    }
+
+    #[test]
+    fn test_complete_scope() {
+        let items = source().complete("feat(c\n", 0, 6);
+        assert_eq!(labels(&items), vec!["core", "cli"]);
+        assert_eq!(items[0].start, 5);
+    }
+
+    #[test]
+    fn test_complete_trailer() {
+        let text = "feat: add parser\n\nBody text.\n\nSig\n";
+        let items = source().complete(text, 4, 3);
+        assert_eq!(labels(&items), vec!["Signed-off-by"]);
+        assert_eq!(items[0].insert_text, "Signed-off-by: ");
+
+        // Nothing is offered mid-sentence or directly below the header
+        assert!(source().complete(text, 2, 6).is_empty());
+        assert!(source().complete("feat: add\nSig\n", 1, 3).is_empty());
+    }
+}
@@ -0,0 +1,326 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Analysis of commit message and rebase todo buffers.
+
+use crate::commit::CommitMessage;
+use crate::rules::autofix::fix_subject;
+use crate::rules::{RuleEngine, ValidationIssue};
+
+/// Kind of buffer being edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    /// COMMIT_EDITMSG, MERGE_MSG and similar message buffers.
+    CommitMessage,
+    /// An interactive rebase todo list.
+    RebaseTodo,
+}
+
+impl DocumentKind {
+    /// Detect the document kind from its URI and language id.
+    pub fn detect(uri: &str, language_id: Option<&str>) -> Option<Self> {
+        match language_id {
+            Some("gitcommit" | "git-commit") => return Some(DocumentKind::CommitMessage),
+            Some("gitrebase" | "git-rebase") => return Some(DocumentKind::RebaseTodo),
+            _ => {}
+        }
+
+        let name = uri.rsplit('/').next().unwrap_or(uri);
+        match name {
+            "COMMIT_EDITMSG" | "MERGE_MSG" | "SQUASH_MSG" => Some(DocumentKind::CommitMessage),
+            "git-rebase-todo" => Some(DocumentKind::RebaseTodo),
+            _ => None,
+        }
+    }
+}
+
+/// A problem found in a document, positioned on a single line.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    /// Zero-based line in the document.
+    pub line: usize,
+    /// The underlying validation issue.
+    pub issue: ValidationIssue,
+    /// Zero-based character columns of the offending text, when known.
+    /// The whole line is meant otherwise.
+    pub columns: Option<(usize, usize)>,
+    /// Replacement text for the whole line, if an automatic fix exists.
+    pub fix: Option<LineFix>,
+}
+
+/// A whole-line replacement offered as a quick fix.
+#[derive(Debug, Clone)]
+pub struct LineFix {
+    /// Title shown in the editor.
+    pub title: String,
+    /// New content of the line.
+    pub replacement: String,
+}
+
+/// Analyze a document and return its findings.
+pub fn analyze(kind: DocumentKind, text: &str, engine: &RuleEngine) -> Vec<Finding> {
+    match kind {
+        DocumentKind::CommitMessage => analyze_message(text, engine),
+        DocumentKind::RebaseTodo => analyze_todo(text, engine),
+    }
+}
+
+/// Analyze a commit message buffer, ignoring git's comment lines.
+fn analyze_message(text: &str, engine: &RuleEngine) -> Vec<Finding> {
+    // Map message lines (as seen by the parser) back to document lines.
+    let mut mapping = Vec::new();
+    let mut lines = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        if line.starts_with("# ") && line.contains(">8") {
+            break; // Scissors line: everything below is ignored by git
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        if lines.is_empty() && line.trim().is_empty() {
+            continue;
+        }
+        mapping.push(idx);
+        lines.push(line);
+    }
+
+    let message = lines.join("\n");
+    if message.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let header_line = mapping.first().copied().unwrap_or(0);
+    let result = match engine.validate_string(&message) {
+        Ok(result) => result,
+        Err(e) => {
+            return vec![Finding {
+                line: header_line,
+                issue: ValidationIssue {
+                    code: "parse-error".to_string(),
+                    message: e.to_string(),
+                    suggestion: Some("Use the format: type(scope): subject".to_string()),
+                    is_error: true,
+                    line: Some(1),
+                    span: None,
+                },
+                columns: None,
+                fix: None,
+            }]
+        }
+    };
+
+    let parsed = engine.parse(&message).ok();
+    // Spans point into the message as ck formats it, which matches the
+    // buffer unless a line was written loosely, e.g. `feat:add`
+    let formatted: Vec<String> = result.message.lines().map(str::to_string).collect();
+    result
+        .errors
+        .into_iter()
+        .chain(result.warnings)
+        .map(|issue| {
+            let line = issue
+                .line
+                .and_then(|l| mapping.get(l.saturating_sub(1)).copied())
+                .unwrap_or(header_line);
+            let columns = issue
+                .span
+                .filter(|span| {
+                    formatted.get(span.line - 1).map(String::as_str)
+                        == lines.get(span.line - 1).copied()
+                })
+                .map(|span| (span.column - 1, span.end_column - 1));
+            let fix = if line == header_line {
+                parsed
+                    .as_ref()
+                    .and_then(|p| header_fix(&issue.code, p, lines[0]))
+            } else {
+                None
+            };
+            Finding {
+                line,
+                issue,
+                columns,
+                fix,
+            }
+        })
+        .collect()
+}
+
+/// Build a header replacement for a fixable issue.
+fn header_fix(code: &str, message: &CommitMessage, header: &str) -> Option<LineFix> {
+    let fixed_subject = fix_subject(code, &message.subject)?;
+    let prefix_len = header.rfind(&message.subject)?;
+    Some(LineFix {
+        title: format!("Fix {}", code),
+        replacement: format!("{}{}", &header[..prefix_len], fixed_subject),
+    })
+}
+
+/// Analyze a rebase todo list, validating the subject of each picked commit.
+fn analyze_todo(text: &str, engine: &RuleEngine) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        let entry = match TodoEntry::parse(line) {
+            Some(entry) => entry,
+            None => continue,
+        };
+
+        let (issues, formatted) = match engine.validate_header(entry.subject) {
+            Ok(result) => (
+                result.errors.into_iter().chain(result.warnings).collect(),
+                result.message,
+            ),
+            Err(e) => (
+                vec![ValidationIssue {
+                    code: "parse-error".to_string(),
+                    message: e.to_string(),
+                    suggestion: Some("Reword this commit".to_string()),
+                    is_error: true,
+                    line: Some(1),
+                    span: None,
+                }],
+                String::new(),
+            ),
+        };
+
+        for issue in issues {
+            let columns = issue
+                .span
+                .filter(|_| formatted == entry.subject)
+                .map(|span| {
+                    (
+                        entry.subject_column + span.column - 1,
+                        entry.subject_column + span.end_column - 1,
+                    )
+                });
+            let fix = (entry.command != "reword").then(|| LineFix {
+                title: format!("Reword {}", entry.sha),
+                replacement: format!("reword{}", &line[entry.command_len..]),
+            });
+            findings.push(Finding {
+                line: idx,
+                issue,
+                columns,
+                fix,
+            });
+        }
+    }
+
+    findings
+}
+
+/// A todo line that carries a commit subject.
+struct TodoEntry<'a> {
+    command: &'static str,
+    command_len: usize,
+    sha: &'a str,
+    subject: &'a str,
+    /// Character column where the subject starts.
+    subject_column: usize,
+}
+
+impl<'a> TodoEntry<'a> {
+    /// Parse a `pick <sha> <subject>` style line.
+    fn parse(line: &'a str) -> Option<Self> {
+        let mut parts = line.splitn(3, ' ');
+        let word = parts.next()?;
+        let command = match word {
+            "pick" | "p" => "pick",
+            "reword" | "r" => "reword",
+            "edit" | "e" => "edit",
+            _ => return None,
+        };
+        let sha = parts.next()?;
+        let rest = parts.next()?;
+        let subject = rest.trim();
+        if subject.is_empty() {
+            return None;
+        }
+        let subject_start = line.len() - rest.trim_start().len();
+        Some(Self {
+            command,
+            command_len: word.len(),
+            sha,
+            subject,
+            subject_column: line[..subject_start].chars().count(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CkConfig;
+
+    fn engine() -> RuleEngine {
+        RuleEngine::new(CkConfig::default())
+    }
+
+    #[test]
+    fn test_detect_kind() {
+        assert_eq!(
+            DocumentKind::detect("file:///repo/.git/COMMIT_EDITMSG", None),
+            Some(DocumentKind::CommitMessage)
+        );
+        assert_eq!(
+            DocumentKind::detect("file:///repo/.git/rebase-merge/git-rebase-todo", None),
+            Some(DocumentKind::RebaseTodo)
+        );
+        assert_eq!(DocumentKind::detect("file:///repo/src/main.rs", None), None);
+    }
+
+    #[test]
+    fn test_message_findings_skip_comments() {
+        let text = "# Please enter the commit message\n\nfeat: Add new parser.\n# comment\n";
+        let findings = analyze(DocumentKind::CommitMessage, text, &engine());
+
+        assert!(findings.iter().all(|f| f.line == 2));
+        let case = findings
+            .iter()
+            .find(|f| f.issue.code == "subject-case")
+            .unwrap();
+        assert_eq!(
+            case.fix.as_ref().unwrap().replacement,
+            "feat: add new parser."
+        );
+        assert_eq!(case.columns, Some((6, 7)));
+        let period = findings
+            .iter()
+            .find(|f| f.issue.code == "subject-trailing-period")
+            .unwrap();
+        assert_eq!(period.columns, Some((20, 21)));
+
+        // A loosely written header can't be underlined precisely
+        let findings = analyze(DocumentKind::CommitMessage, "feat:Add parser.", &engine());
+        assert!(findings.iter().all(|f| f.columns.is_none()));
+    }
+
+    #[test]
+    fn test_message_parse_error() {
+        let findings = analyze(DocumentKind::CommitMessage, "just some text", &engine());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].issue.code, "parse-error");
+    }
+
+    #[test]
+    fn test_todo_findings() {
+        let text = "pick abc1234 feat: add parser\npick def5678 Fixed the thing\n# comment\n";
+        let findings = analyze(DocumentKind::RebaseTodo, text, &engine());
+
+        assert!(!findings.is_empty());
+        assert!(findings.iter().all(|f| f.line == 1));
+        assert_eq!(
+            findings[0].fix.as_ref().unwrap().replacement,
+            "reword def5678 Fixed the thing"
+        );
+
+        let text = "pick abc1234 fix: Handle input\n";
+        let findings = analyze(DocumentKind::RebaseTodo, text, &engine());
+        let case = findings
+            .iter()
+            .find(|f| f.issue.code == "subject-case")
+            .unwrap();
+        assert_eq!(case.columns, Some((18, 19)));
+    }
+}
@@ -0,0 +1,17 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Language server for commit message buffers.
+//!
+//! `ck lsp` speaks a minimal subset of the Language Server Protocol over
+//! stdio, publishing rule violations as diagnostics for COMMIT_EDITMSG and
+//! git-rebase-todo buffers, offering quick fixes as code actions, and
+//! completing commit types, scopes and trailers.
+
+mod completion;
+mod document;
+mod server;
+
+pub use completion::{CompletionItem, CompletionKind, CompletionSource};
+pub use document::{analyze, DocumentKind, Finding, LineFix};
+pub use server::{run_stdio, LspServer};
@@ -0,0 +1,510 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Minimal JSON-RPC language server over stdio.
+
+use crate::config::CkConfig;
+use crate::error::{CkError, Result};
+use crate::rules::RuleEngine;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use super::completion::{CompletionKind, CompletionSource};
+use super::document::{analyze, DocumentKind, Finding};
+
+/// An open document tracked by the server.
+struct OpenDocument {
+    kind: DocumentKind,
+    text: String,
+}
+
+/// Language server providing diagnostics and quick fixes.
+pub struct LspServer<R, W> {
+    reader: R,
+    writer: W,
+    config: CkConfig,
+    engine: RuleEngine,
+    documents: HashMap<String, OpenDocument>,
+    root: Option<PathBuf>,
+    completion: Option<CompletionSource>,
+    shutdown_requested: bool,
+}
+
+impl<R: BufRead, W: Write> LspServer<R, W> {
+    /// Create a new server reading requests from `reader`.
+    pub fn new(config: CkConfig, reader: R, writer: W) -> Self {
+        Self {
+            reader,
+            writer,
+            engine: RuleEngine::new(config.clone()),
+            config,
+            documents: HashMap::new(),
+            root: None,
+            completion: None,
+            shutdown_requested: false,
+        }
+    }
+
+    /// Serve requests until the client sends `exit`.
+    ///
+    /// A message that is not valid JSON gets a parse error response; only
+    /// the end of input or broken framing stops the server.
+    pub fn run(&mut self) -> Result<()> {
+        while let Some(body) = self.read_message()? {
+            let message = match serde_json::from_slice(&body) {
+                Ok(message) => message,
+                Err(e) => {
+                    self.send(json!({
+                        "jsonrpc": "2.0",
+                        "id": null,
+                        "error": { "code": -32700, "message": format!("Parse error: {}", e) },
+                    }))?;
+                    continue;
+                }
+            };
+            if !self.handle(message)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the body of one framed message. Returns `None` at end of input.
+    fn read_message(&mut self) -> Result<Option<Vec<u8>>> {
+        let mut content_length = None;
+
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Content-Length:") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+
+        let length = content_length.ok_or_else(|| protocol_error("Missing Content-Length"))?;
+        let mut body = vec![0u8; length];
+        self.reader.read_exact(&mut body)?;
+        Ok(Some(body))
+    }
+
+    /// Write one framed message.
+    fn send(&mut self, message: Value) -> Result<()> {
+        let body = message.to_string();
+        write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Handle a message. Returns `false` when the server should exit.
+    fn handle(&mut self, message: Value) -> Result<bool> {
+        let method = message["method"].as_str().unwrap_or("").to_string();
+        let id = message.get("id").cloned();
+        let params = &message["params"];
+
+        match method.as_str() {
+            "initialize" => {
+                self.root = params["rootUri"]
+                    .as_str()
+                    .and_then(uri_to_path)
+                    .or_else(|| params["rootPath"].as_str().map(PathBuf::from));
+                let result = json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "codeActionProvider": { "codeActionKinds": ["quickfix"] },
+                        "completionProvider": { "triggerCharacters": ["("] },
+                    },
+                    "serverInfo": { "name": "ck", "version": crate::version::VERSION },
+                });
+                self.respond(id, result)?;
+            }
+            "shutdown" => {
+                self.shutdown_requested = true;
+                self.respond(id, Value::Null)?;
+            }
+            "exit" => {
+                if !self.shutdown_requested {
+                    tracing::warn!("Client exited without requesting shutdown");
+                }
+                return Ok(false);
+            }
+            "textDocument/didOpen" => {
+                let doc = &params["textDocument"];
+                let uri = doc["uri"].as_str().unwrap_or_default().to_string();
+                if let Some(kind) = DocumentKind::detect(&uri, doc["languageId"].as_str()) {
+                    let text = doc["text"].as_str().unwrap_or_default().to_string();
+                    self.documents
+                        .insert(uri.clone(), OpenDocument { kind, text });
+                    self.publish_diagnostics(&uri)?;
+                }
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                // Full sync: the last change carries the whole document
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                if let (Some(doc), Some(text)) = (self.documents.get_mut(uri), text) {
+                    doc.text = text.to_string();
+                    let uri = uri.to_string();
+                    self.publish_diagnostics(&uri)?;
+                }
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                if self.documents.remove(uri).is_some() {
+                    let clear = json!({ "uri": uri, "diagnostics": [] });
+                    self.notify("textDocument/publishDiagnostics", clear)?;
+                }
+            }
+            "textDocument/codeAction" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let start = params["range"]["start"]["line"].as_u64().unwrap_or(0) as usize;
+                let end = params["range"]["end"]["line"].as_u64().unwrap_or(0) as usize;
+                let actions = self.code_actions(uri, start, end);
+                self.respond(id, Value::Array(actions))?;
+            }
+            "textDocument/completion" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let line = params["position"]["line"].as_u64().unwrap_or(0) as usize;
+                let character = params["position"]["character"].as_u64().unwrap_or(0) as usize;
+                let items = self.completions(uri, line, character);
+                self.respond(id, Value::Array(items))?;
+            }
+            _ => {
+                // Unknown requests get an error; unknown notifications are ignored
+                if let Some(id) = id {
+                    self.send(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": -32601, "message": format!("Method not found: {}", method) },
+                    }))?;
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Send a response to a request.
+    fn respond(&mut self, id: Option<Value>, result: Value) -> Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+
+    /// Send a notification to the client.
+    fn notify(&mut self, method: &str, params: Value) -> Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    /// Analyze a document and publish its diagnostics.
+    fn publish_diagnostics(&mut self, uri: &str) -> Result<()> {
+        let diagnostics: Vec<Value> = match self.documents.get(uri) {
+            Some(doc) => analyze(doc.kind, &doc.text, &self.engine)
+                .iter()
+                .map(|f| diagnostic(f, &doc.text))
+                .collect(),
+            None => return Ok(()),
+        };
+
+        self.notify(
+            "textDocument/publishDiagnostics",
+            json!({ "uri": uri, "diagnostics": diagnostics }),
+        )
+    }
+
+    /// Build quick-fix code actions for findings within a line range.
+    fn code_actions(&self, uri: &str, start: usize, end: usize) -> Vec<Value> {
+        let doc = match self.documents.get(uri) {
+            Some(doc) => doc,
+            None => return Vec::new(),
+        };
+
+        analyze(doc.kind, &doc.text, &self.engine)
+            .iter()
+            .filter(|f| f.line >= start && f.line <= end)
+            .filter_map(|f| {
+                let fix = f.fix.as_ref()?;
+                Some(json!({
+                    "title": fix.title,
+                    "kind": "quickfix",
+                    "diagnostics": [diagnostic(f, &doc.text)],
+                    "edit": {
+                        "changes": {
+                            uri: [{ "range": line_range(&doc.text, f.line), "newText": fix.replacement }],
+                        },
+                    },
+                }))
+            })
+            .collect()
+    }
+
+    /// Build completion items at a position in a commit message buffer.
+    fn completions(&mut self, uri: &str, line: usize, character: usize) -> Vec<Value> {
+        let text = match self.documents.get(uri) {
+            Some(doc) if doc.kind == DocumentKind::CommitMessage => doc.text.clone(),
+            _ => return Vec::new(),
+        };
+
+        // Scopes come from packages and history, so collect them once
+        if self.completion.is_none() {
+            let root = self.root.clone().or_else(|| std::env::current_dir().ok());
+            self.completion = Some(CompletionSource::new(&self.config, root.as_deref()));
+        }
+        let source = match self.completion.as_ref() {
+            Some(source) => source,
+            None => return Vec::new(),
+        };
+
+        let current = text.lines().nth(line).unwrap_or("");
+        let column = utf16_to_chars(current, character);
+
+        source
+            .complete(&text, line, column)
+            .into_iter()
+            .map(|item| {
+                let start = chars_to_utf16(current, item.start);
+                let kind = match item.kind {
+                    CompletionKind::Type => 14,
+                    CompletionKind::Scope => 9,
+                    CompletionKind::Trailer => 10,
+                };
+                json!({
+                    "label": item.label,
+                    "detail": item.detail,
+                    "kind": kind,
+                    "textEdit": {
+                        "range": {
+                            "start": { "line": line, "character": start },
+                            "end": { "line": line, "character": character },
+                        },
+                        "newText": item.insert_text,
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+/// Convert a UTF-16 column to a character count.
+fn utf16_to_chars(line: &str, column: usize) -> usize {
+    let mut units = 0;
+    for (idx, c) in line.chars().enumerate() {
+        if units >= column {
+            return idx;
+        }
+        units += c.len_utf16();
+    }
+    line.chars().count()
+}
+
+/// Convert a character count to a UTF-16 column.
+fn chars_to_utf16(line: &str, chars: usize) -> usize {
+    line.chars().take(chars).map(char::len_utf16).sum()
+}
+
+/// Convert a `file://` URI to a path.
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut iter = path.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex: Vec<u8> = iter.by_ref().take(2).collect();
+            let decoded = std::str::from_utf8(&hex)
+                .ok()
+                .and_then(|h| u8::from_str_radix(h, 16).ok())?;
+            bytes.push(decoded);
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+/// Convert a finding to an LSP diagnostic.
+fn diagnostic(finding: &Finding, text: &str) -> Value {
+    let mut message = finding.issue.message.clone();
+    if let Some(ref suggestion) = finding.issue.suggestion {
+        message.push_str(&format!("\n{}", suggestion));
+    }
+
+    json!({
+        "range": finding_range(text, finding),
+        "severity": if finding.issue.is_error { 1 } else { 2 },
+        "code": finding.issue.code,
+        "source": "ck",
+        "message": message,
+    })
+}
+
+/// Range of the offending text of a finding, or of its whole line.
+fn finding_range(text: &str, finding: &Finding) -> Value {
+    let (start, end) = match finding.columns {
+        Some(columns) => columns,
+        None => return line_range(text, finding.line),
+    };
+    let line_text = text.lines().nth(finding.line).unwrap_or("");
+    json!({
+        "start": { "line": finding.line, "character": chars_to_utf16(line_text, start) },
+        "end": { "line": finding.line, "character": chars_to_utf16(line_text, end) },
+    })
+}
+
+/// Range covering a whole line, in UTF-16 code units.
+fn line_range(text: &str, line: usize) -> Value {
+    let width = text
+        .lines()
+        .nth(line)
+        .map(|l| l.encode_utf16().count())
+        .unwrap_or(0);
+    json!({
+        "start": { "line": line, "character": 0 },
+        "end": { "line": line, "character": width },
+    })
+}
+
+fn protocol_error(message: &str) -> CkError {
+    CkError::WithContext {
+        code: "lsp-protocol",
+        context: "lsp".to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Run the language server on stdin/stdout.
+pub fn run_stdio(config: CkConfig) -> Result<()> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let mut server = LspServer::new(config, stdin.lock(), stdout.lock());
+    server.run()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn frame(message: Value) -> String {
+        let body = message.to_string();
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+    }
+
+    fn run_session(messages: Vec<Value>) -> String {
+        let input: String = messages.into_iter().map(frame).collect();
+        let mut output = Vec::new();
+        {
+            let mut server = LspServer::new(
+                CkConfig::default(),
+                Cursor::new(input.into_bytes()),
+                &mut output,
+            );
+            server.run().unwrap();
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_initialize_and_diagnostics() {
+        let output = run_session(vec![
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+                "textDocument": {
+                    "uri": "file:///repo/.git/COMMIT_EDITMSG",
+                    "languageId": "gitcommit",
+                    "version": 1,
+                    "text": "feat: Add parser.\n",
+                },
+            }}),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/codeAction", "params": {
+                "textDocument": { "uri": "file:///repo/.git/COMMIT_EDITMSG" },
+                "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } },
+                "context": { "diagnostics": [] },
+            }}),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ]);
+
+        assert!(output.contains("codeActionProvider"));
+        assert!(output.contains("publishDiagnostics"));
+        assert!(output.contains("subject-case"));
+        assert!(output.contains("feat: add parser."));
+    }
+
+    #[test]
+    fn test_completion() {
+        let output = run_session(vec![
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "rootUri": null } }),
+            json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+                "textDocument": {
+                    "uri": "file:///repo/.git/COMMIT_EDITMSG",
+                    "languageId": "gitcommit",
+                    "version": 1,
+                    "text": "fi\n",
+                },
+            }}),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/completion", "params": {
+                "textDocument": { "uri": "file:///repo/.git/COMMIT_EDITMSG" },
+                "position": { "line": 0, "character": 2 },
+            }}),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ]);
+
+        assert!(output.contains("completionProvider"));
+        assert!(output.contains("\"label\":\"fix\""));
+        assert!(output.contains("A bug fix"));
+    }
+
+    #[test]
+    fn test_uri_to_path() {
+        assert_eq!(
+            uri_to_path("file:///home/me/my%20repo"),
+            Some(PathBuf::from("/home/me/my repo"))
+        );
+        assert_eq!(uri_to_path("untitled:1"), None);
+    }
+
+    #[test]
+    fn test_unknown_request() {
+        let output = run_session(vec![
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "workspace/unknown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ]);
+        assert!(output.contains("-32601"));
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let garbage = "{not json";
+        let input = format!(
+            "Content-Length: {}\r\n\r\n{}{}",
+            garbage.len(),
+            garbage,
+            frame(json!({ "jsonrpc": "2.0", "id": 1, "method": "shutdown" })),
+        );
+        let mut output = Vec::new();
+        LspServer::new(
+            CkConfig::default(),
+            Cursor::new(input.into_bytes()),
+            &mut output,
+        )
+        .run()
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("-32700"));
+        assert!(output.contains("\"result\":null"));
+    }
+}
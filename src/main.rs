@@ -5,6 +5,7 @@
 //!
 //! A production-grade CLI tool for creating high-quality Git commits.
 
+use ck::cli::args::OutputFormat;
 use ck::cli::{run, Cli};
 use clap::Parser;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
@@ -16,9 +17,22 @@ fn main() {
     // Set up logging
     setup_logging(cli.debug);
 
+    // Undo temporary repository changes on Ctrl-C
+    ck::interrupt::install();
+
     // Run the CLI
-    if let Err(e) = run(cli) {
-        eprintln!("Error: {}", e);
+    let format = cli.format;
+    let result = run(cli);
+    // An interrupted operation usually fails; report the interrupt instead
+    ck::interrupt::checkpoint();
+    if let Err(e) = result {
+        match format {
+            Some(OutputFormat::Json) => eprintln!(
+                "{}",
+                serde_json::to_string_pretty(&e.to_json()).unwrap_or_default()
+            ),
+            _ => eprintln!("Error: {}", e),
+        }
         std::process::exit(1);
     }
 }
@@ -0,0 +1,148 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! The packages a change affects, for `ck affected`.
+//!
+//! A package is affected when files in it changed, or when it depends on
+//! an affected package. CI pipelines can build and test just those.
+
+use crate::config::CkConfig;
+use crate::error::Result;
+use crate::git::{self, DiffInfo, Repository};
+use console::style;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use super::graph::{PackageGraph, Reason};
+
+/// A package affected by a change.
+#[derive(Debug, Clone, Serialize)]
+pub struct AffectedPackage {
+    /// Package or target name.
+    pub name: String,
+    /// Scope commits to the package use.
+    pub scope: String,
+    /// Package directory, relative to the repository root.
+    pub path: String,
+    /// Whether files in the package changed.
+    pub changed: bool,
+    /// The affected package this one depends on, when none of its own
+    /// files changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub via: Option<String>,
+}
+
+/// Outcome of `ck affected`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AffectedReport {
+    /// Revision the commits were compared with, or `None` for the staged
+    /// changes.
+    pub base: Option<String>,
+    /// Files that changed.
+    pub files: Vec<String>,
+    /// Changed files outside every package.
+    pub unowned: Vec<String>,
+    /// Affected packages, changed ones first.
+    pub packages: Vec<AffectedPackage>,
+}
+
+/// The packages affected by the commits since the merge base of `base` and
+/// HEAD or, without a base, by the staged changes. Dependents are left out
+/// with `direct_only`.
+pub fn affected(
+    repo: &Repository,
+    config: &CkConfig,
+    base: Option<&str>,
+    direct_only: bool,
+) -> Result<AffectedReport> {
+    let diff = match base {
+        Some(base) => crate::rules::queue::landed_diff(repo, base, "HEAD")?,
+        None => git::get_staged_diff()?,
+    };
+    let files = changed_files(&diff);
+
+    let graph = PackageGraph::build(repo.workdir(), config);
+    let mut packages: Vec<AffectedPackage> = graph
+        .affected(&files, direct_only)
+        .into_iter()
+        .map(|(package, reason)| AffectedPackage {
+            name: package.name.clone(),
+            scope: package.scope.clone(),
+            path: package
+                .path
+                .strip_prefix(repo.workdir())
+                .unwrap_or(&package.path)
+                .to_string_lossy()
+                .replace('\\', "/"),
+            changed: reason == Reason::Changed,
+            via: match reason {
+                Reason::Changed => None,
+                Reason::DependsOn(name) => Some(name),
+            },
+        })
+        .collect();
+    packages.sort_by(|a, b| b.changed.cmp(&a.changed).then(a.path.cmp(&b.path)));
+
+    let display = |file: &PathBuf| file.to_string_lossy().replace('\\', "/");
+    Ok(AffectedReport {
+        base: base.map(str::to_string),
+        unowned: files
+            .iter()
+            .filter(|file| !graph.contains(file))
+            .map(display)
+            .collect(),
+        files: files.iter().map(display).collect(),
+        packages,
+    })
+}
+
+/// Paths a diff touches, counting both sides of renames.
+fn changed_files(diff: &DiffInfo) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = diff
+        .files
+        .iter()
+        .flat_map(|file| std::iter::once(&file.path).chain(&file.old_path))
+        .cloned()
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+impl AffectedReport {
+    /// Print the affected packages and why.
+    pub fn print(&self) {
+        let changes = match self.base {
+            Some(ref base) => format!("the commits since {}", base),
+            None => "the staged changes".to_string(),
+        };
+        if self.packages.is_empty() {
+            println!("No packages affected by {}", changes);
+        } else {
+            println!(
+                "{} package(s) affected by {}:",
+                self.packages.len(),
+                changes
+            );
+        }
+        for package in &self.packages {
+            let reason = match package.via {
+                Some(ref via) => format!("depends on {}", via),
+                None => "changed".to_string(),
+            };
+            println!(
+                "  {:<20} {} {}",
+                package.name,
+                style(&package.path).dim(),
+                style(format!("({})", reason)).dim()
+            );
+        }
+        if !self.unowned.is_empty() {
+            println!(
+                "! {} changed file(s) outside any package: {}",
+                self.unowned.len(),
+                self.unowned.join(", ")
+            );
+        }
+    }
+}
@@ -0,0 +1,285 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Packages and targets from Bazel and Buck build files.
+//!
+//! A package is a directory with a build file, and owns the files below it
+//! up to the next package. Its targets come from the rule calls in the
+//! build file: a file listed in (or globbed by) a target's `srcs`, `hdrs`
+//! or `data` belongs to that target, and its `deps` name the targets it
+//! depends on.
+
+use crate::config::BuildSystem;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref NAME_ATTR: Regex = Regex::new(r#"\bname\s*=\s*["']([^"']+)["']"#).unwrap();
+    static ref FILE_ATTR: Regex = Regex::new(r"\b(srcs|hdrs|data)\s*=").unwrap();
+    static ref DEPS_ATTR: Regex = Regex::new(r"\b(deps|runtime_deps|exports|data)\s*=").unwrap();
+    static ref STRING: Regex = Regex::new(r#""([^"]*)"|'([^']*)'"#).unwrap();
+}
+
+/// A target declared in a build file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildTarget {
+    /// Rule that declares the target, e.g. `go_library`.
+    pub kind: String,
+    /// Target name.
+    pub name: String,
+    /// Files listed by name, relative to the package.
+    pub files: Vec<String>,
+    /// Glob patterns, relative to the package.
+    pub globs: Vec<String>,
+    /// Labels of the targets this one depends on, as written.
+    pub deps: Vec<String>,
+}
+
+impl BuildTarget {
+    /// Whether the target lists `file`, given relative to its package.
+    pub fn owns(&self, file: &Path) -> bool {
+        let file = file.to_string_lossy();
+        self.files.iter().any(|f| *f == file)
+            || self.globs.iter().any(|g| {
+                glob::Pattern::new(g)
+                    .map(|pattern| pattern.matches(&file))
+                    .unwrap_or(false)
+            })
+    }
+}
+
+/// A build package and its targets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildPackage {
+    /// Package directory, relative to the repository root.
+    pub path: PathBuf,
+    /// Targets declared in the package's build file.
+    pub targets: Vec<BuildTarget>,
+}
+
+impl BuildPackage {
+    /// Load the package that owns `file`, relative to `root`: the nearest
+    /// directory above it with a build file.
+    pub fn for_file(root: &Path, file: &Path, system: BuildSystem) -> Option<Self> {
+        let mut current = file.parent();
+        while let Some(dir) = current {
+            for name in system.build_files() {
+                let build_file = root.join(dir).join(name);
+                if build_file.is_file() {
+                    let content = std::fs::read_to_string(&build_file).unwrap_or_default();
+                    return Some(Self {
+                        path: dir.to_path_buf(),
+                        targets: parse_build_file(&content),
+                    });
+                }
+            }
+            current = dir.parent();
+        }
+        None
+    }
+
+    /// The package's own name: its directory name, if it has one.
+    pub fn name(&self) -> Option<String> {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }
+
+    /// Targets that list `file`, given relative to the repository root.
+    pub fn targets_for(&self, file: &Path) -> Vec<&BuildTarget> {
+        let relative = file.strip_prefix(&self.path).unwrap_or(file);
+        self.targets.iter().filter(|t| t.owns(relative)).collect()
+    }
+}
+
+/// The targets declared by the rule calls in a build file.
+pub fn parse_build_file(content: &str) -> Vec<BuildTarget> {
+    rule_calls(content)
+        .into_iter()
+        .filter_map(|(kind, args)| {
+            let name = NAME_ATTR.captures(args)?[1].to_string();
+            let (files, globs) = file_attrs(args);
+            let deps = DEPS_ATTR
+                .find_iter(args)
+                .flat_map(|found| strings(attr_value(&args[found.end()..])))
+                .filter(|dep| is_label(dep))
+                .map(str::to_string)
+                .collect();
+            Some(BuildTarget {
+                kind: kind.to_string(),
+                name,
+                files,
+                globs,
+                deps,
+            })
+        })
+        .collect()
+}
+
+/// Top-level calls in Starlark source, as the callee and its arguments.
+fn rule_calls(content: &str) -> Vec<(&str, &str)> {
+    let mut calls = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut callee = "";
+    let mut quote = None;
+    let mut chars = content.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) if c == '\\' => {
+                chars.next();
+            }
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '#' => while chars.next_if(|&(_, c)| c != '\n').is_some() {},
+                '(' | '[' | '{' => {
+                    if depth == 0 && c == '(' {
+                        callee = identifier_before(&content[..i]);
+                        start = i + 1;
+                    }
+                    depth += 1;
+                }
+                ')' | ']' | '}' => {
+                    depth -= 1;
+                    if depth == 0 && c == ')' && !callee.is_empty() {
+                        calls.push((callee, &content[start..i]));
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+    calls
+}
+
+/// The identifier at the end of `source`, e.g. `java_library`.
+fn identifier_before(source: &str) -> &str {
+    let source = source.trim_end();
+    let start = source
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    &source[start..]
+}
+
+/// Files and globs listed in the `srcs`, `hdrs` and `data` attributes.
+fn file_attrs(args: &str) -> (Vec<String>, Vec<String>) {
+    let mut files = Vec::new();
+    let mut globs = Vec::new();
+    for found in FILE_ATTR.find_iter(args) {
+        let value = attr_value(&args[found.end()..]);
+        // Excluded files still belong to the package, just not the target
+        let (value, is_glob) = match value.trim_start().strip_prefix("glob(") {
+            Some(glob) => (glob.split("exclude").next().unwrap_or(glob), true),
+            None => (value, false),
+        };
+        for entry in strings(value) {
+            // Labels name other targets, not files
+            if is_label(entry) {
+                continue;
+            }
+            if is_glob || entry.contains('*') {
+                globs.push(entry.to_string());
+            } else {
+                files.push(entry.to_string());
+            }
+        }
+    }
+    (files, globs)
+}
+
+/// The string literals in `source`.
+fn strings(source: &str) -> impl Iterator<Item = &str> {
+    STRING
+        .captures_iter(source)
+        .filter_map(|string| string.get(1).or_else(|| string.get(2)))
+        .map(|string| string.as_str())
+}
+
+/// Whether `entry` is a label rather than a file name.
+fn is_label(entry: &str) -> bool {
+    entry.starts_with(':') || entry.starts_with("//") || entry.starts_with('@')
+}
+
+/// The text of an attribute value, up to the comma that ends it.
+fn attr_value(rest: &str) -> &str {
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in rest.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' if depth == 0 => return &rest[..i],
+                ')' | ']' | '}' => depth -= 1,
+                ',' if depth == 0 => return &rest[..i],
+                _ => {}
+            },
+        }
+    }
+    rest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUILD: &str = r#"
+load("@rules_go//go:def.bzl", "go_library", "go_test")
+
+# The server binary
+go_library(
+    name = "server",
+    srcs = [
+        "handler.go",
+        "server.go",  # entry point
+    ],
+    deps = ["//lib/log:log"],
+)
+
+go_test(
+    name = "server_test",
+    srcs = glob(["*_test.go"], exclude = ["slow_test.go"]),
+    data = [":fixtures"],
+)
+
+filegroup(name = 'fixtures', srcs = glob(['testdata/**']))
+"#;
+
+    #[test]
+    fn test_parse_build_file() {
+        let targets = parse_build_file(BUILD);
+        let names: Vec<_> = targets.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["server", "server_test", "fixtures"]);
+        assert_eq!(targets[0].kind, "go_library");
+        assert_eq!(targets[0].files, ["handler.go", "server.go"]);
+        assert_eq!(targets[0].deps, ["//lib/log:log"]);
+        assert_eq!(targets[1].deps, [":fixtures"]);
+        assert_eq!(targets[1].globs, ["*_test.go"]);
+        assert!(targets[2].owns(Path::new("testdata/a/b.json")));
+        assert!(!targets[0].owns(Path::new("main.go")));
+    }
+
+    #[test]
+    fn test_package_for_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("svc/auth/internal")).unwrap();
+        std::fs::write(dir.path().join("svc/auth/BUILD.bazel"), BUILD).unwrap();
+
+        let file = Path::new("svc/auth/internal/server.go");
+        let package = BuildPackage::for_file(dir.path(), file, BuildSystem::Bazel).unwrap();
+        assert_eq!(package.path, PathBuf::from("svc/auth"));
+        assert_eq!(package.name().as_deref(), Some("auth"));
+        assert!(package.targets_for(file).is_empty());
+
+        let targets = package.targets_for(Path::new("svc/auth/server.go"));
+        assert_eq!(targets[0].name, "server");
+        assert!(BuildPackage::for_file(dir.path(), file, BuildSystem::Buck).is_none());
+    }
+}
@@ -3,11 +3,14 @@
 
 //! Package detection for monorepos.
 
-use crate::config::CkConfig;
+use crate::config::{BuildSystem, CkConfig};
+use crate::progress::Progress;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+use super::build_files::parse_build_file;
+
 /// Information about a detected package.
 #[derive(Debug, Clone)]
 pub struct PackageInfo {
@@ -27,6 +30,7 @@ pub fn detect_packages(root: &Path, config: &CkConfig) -> Vec<PackageInfo> {
         return Vec::new();
     }
 
+    let progress = Progress::spinner("Detecting packages");
     let mut packages = Vec::new();
     let mut seen_paths = HashSet::new();
 
@@ -44,6 +48,13 @@ pub fn detect_packages(root: &Path, config: &CkConfig) -> Vec<PackageInfo> {
         }
     }
 
+    // Build files declare the targets; markers are only a fallback
+    if let Some(system) = config.monorepo.build_system {
+        packages.extend(detect_build_targets(root, system, &seen_paths));
+        progress.finish();
+        return packages;
+    }
+
     // Then auto-detect packages
     for marker in &config.monorepo.package_markers {
         for entry in WalkDir::new(root)
@@ -52,12 +63,13 @@ pub fn detect_packages(root: &Path, config: &CkConfig) -> Vec<PackageInfo> {
             .filter_entry(|e| {
                 // Skip hidden directories and common non-package directories
                 let name = e.file_name().to_string_lossy();
-                !name.starts_with('.')
-                    && name != "node_modules"
-                    && name != "target"
-                    && name != "vendor"
-                    && name != "dist"
-                    && name != "build"
+                e.depth() == 0
+                    || !name.starts_with('.')
+                        && name != "node_modules"
+                        && name != "target"
+                        && name != "vendor"
+                        && name != "dist"
+                        && name != "build"
             })
             .flatten()
         {
@@ -89,6 +101,44 @@ pub fn detect_packages(root: &Path, config: &CkConfig) -> Vec<PackageInfo> {
         }
     }
 
+    progress.finish();
+    packages
+}
+
+/// One package per target declared in the build files under `root`.
+fn detect_build_targets(
+    root: &Path,
+    system: BuildSystem,
+    seen_paths: &HashSet<PathBuf>,
+) -> Vec<PackageInfo> {
+    let mut packages = Vec::new();
+    for entry in WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            e.depth() == 0
+                || !name.starts_with('.') && !name.starts_with("bazel-") && name != "buck-out"
+        })
+        .flatten()
+    {
+        let name = entry.file_name().to_string_lossy();
+        let dir = match entry.path().parent() {
+            Some(dir) if system.build_files().contains(&name.as_ref()) => dir,
+            _ => continue,
+        };
+        if seen_paths.contains(dir) {
+            continue;
+        }
+        let content = std::fs::read_to_string(entry.path()).unwrap_or_default();
+        for target in parse_build_file(&content) {
+            packages.push(PackageInfo {
+                path: dir.to_path_buf(),
+                name: target.name.clone(),
+                scope: target.name,
+                marker: name.to_string(),
+            });
+        }
+    }
     packages
 }
 
@@ -0,0 +1,411 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Dependencies between the packages of a monorepo.
+//!
+//! Edges come from the manifests `detect_packages` found: Cargo, npm and
+//! Python dependencies are matched by package name and Go requirements by
+//! module path. With a build system configured, targets are the packages
+//! and their `deps` labels the edges.
+
+use crate::config::CkConfig;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+use super::build_files::{parse_build_file, BuildTarget};
+use super::detector::{detect_packages, PackageInfo};
+
+/// A package and the packages it depends on.
+#[derive(Debug, Clone)]
+struct Node {
+    info: PackageInfo,
+    /// Name dependencies refer to the package by.
+    id: Option<String>,
+    /// Names of the dependencies.
+    refs: Vec<String>,
+    /// The build target, with a build system.
+    target: Option<BuildTarget>,
+}
+
+/// Why a package is affected by a change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// Files in the package changed.
+    Changed,
+    /// The package depends on an affected package, named here.
+    DependsOn(String),
+}
+
+/// The packages of a repository and the dependencies between them.
+#[derive(Debug, Clone)]
+pub struct PackageGraph {
+    root: PathBuf,
+    nodes: Vec<Node>,
+    /// For each package, the packages that depend on it.
+    dependents: Vec<Vec<usize>>,
+}
+
+impl PackageGraph {
+    /// Detect the packages under `root` and read their dependencies.
+    pub fn build(root: &Path, config: &CkConfig) -> Self {
+        let mut build_files: HashMap<PathBuf, Vec<BuildTarget>> = HashMap::new();
+        let nodes: Vec<Node> = detect_packages(root, config)
+            .into_iter()
+            .map(|info| match config.monorepo.build_system {
+                Some(_) => {
+                    let targets = build_files
+                        .entry(info.path.join(&info.marker))
+                        .or_insert_with_key(|file| {
+                            parse_build_file(&std::fs::read_to_string(file).unwrap_or_default())
+                        });
+                    let target = targets.iter().find(|t| t.name == info.name).cloned();
+                    target_node(root, info, target)
+                }
+                None => manifest_node(info, &config.monorepo.package_markers),
+            })
+            .collect();
+
+        let ids: HashMap<&str, usize> = nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, node)| node.id.as_deref().map(|id| (id, i)))
+            .collect();
+        let mut dependents = vec![Vec::new(); nodes.len()];
+        for (i, node) in nodes.iter().enumerate() {
+            for dependency in node.refs.iter().filter_map(|r| ids.get(r.as_str())) {
+                if *dependency != i && !dependents[*dependency].contains(&i) {
+                    dependents[*dependency].push(i);
+                }
+            }
+        }
+
+        Self {
+            root: root.to_path_buf(),
+            nodes,
+            dependents,
+        }
+    }
+
+    /// The packages in detection order.
+    pub fn packages(&self) -> impl Iterator<Item = &PackageInfo> {
+        self.nodes.iter().map(|node| &node.info)
+    }
+
+    /// The packages affected by changes to `files`, relative to the root:
+    /// the packages containing them and, unless `direct_only`, everything
+    /// that depends on those.
+    pub fn affected(&self, files: &[PathBuf], direct_only: bool) -> Vec<(&PackageInfo, Reason)> {
+        let mut reasons: Vec<Option<Reason>> = vec![None; self.nodes.len()];
+        let mut queue = VecDeque::new();
+        for file in files {
+            for i in self.owners(file) {
+                if reasons[i].is_none() {
+                    reasons[i] = Some(Reason::Changed);
+                    queue.push_back(i);
+                }
+            }
+        }
+
+        while let Some(i) = queue.pop_front().filter(|_| !direct_only) {
+            for &dependent in &self.dependents[i] {
+                if reasons[dependent].is_none() {
+                    reasons[dependent] = Some(Reason::DependsOn(self.nodes[i].info.name.clone()));
+                    queue.push_back(dependent);
+                }
+            }
+        }
+
+        self.nodes
+            .iter()
+            .zip(reasons)
+            .filter_map(|(node, reason)| Some((&node.info, reason?)))
+            .collect()
+    }
+
+    /// Whether `file`, relative to the root, is in any package.
+    pub fn contains(&self, file: &Path) -> bool {
+        !self.owners(file).is_empty()
+    }
+
+    /// The packages a file belongs to: the deepest package containing it
+    /// or, with a build system, the targets in the deepest package that
+    /// list it (all of them when none do).
+    fn owners(&self, file: &Path) -> Vec<usize> {
+        let path = self.root.join(file);
+        let depth = match self
+            .nodes
+            .iter()
+            .filter(|node| path.starts_with(&node.info.path))
+            .map(|node| node.info.path.components().count())
+            .max()
+        {
+            Some(depth) => depth,
+            None => return Vec::new(),
+        };
+        let deepest: Vec<usize> = (0..self.nodes.len())
+            .filter(|&i| {
+                let package = &self.nodes[i].info.path;
+                path.starts_with(package) && package.components().count() == depth
+            })
+            .collect();
+
+        let relative = |i: usize| path.strip_prefix(&self.nodes[i].info.path).ok();
+        let listing: Vec<usize> = deepest
+            .iter()
+            .copied()
+            .filter(|&i| match (&self.nodes[i].target, relative(i)) {
+                (Some(target), Some(relative)) => target.owns(relative),
+                _ => false,
+            })
+            .collect();
+        if listing.is_empty() {
+            deepest
+        } else {
+            listing
+        }
+    }
+}
+
+/// A node for a build target, identified by its label.
+fn target_node(root: &Path, info: PackageInfo, target: Option<BuildTarget>) -> Node {
+    let package = info
+        .path
+        .strip_prefix(root)
+        .unwrap_or(&info.path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let refs = target
+        .iter()
+        .flat_map(|t| &t.deps)
+        .filter_map(|dep| normalize_label(&package, dep))
+        .collect();
+    Node {
+        id: Some(format!("//{}:{}", package, info.name)),
+        refs,
+        target,
+        info,
+    }
+}
+
+/// A label in `//package:name` form, resolved against `package`. Labels
+/// of external repositories are skipped.
+fn normalize_label(package: &str, label: &str) -> Option<String> {
+    if let Some(name) = label.strip_prefix(':') {
+        return Some(format!("//{}:{}", package, name));
+    }
+    let path = label.strip_prefix("//")?;
+    match path.split_once(':') {
+        Some(_) => Some(label.to_string()),
+        None => {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            Some(format!("//{}:{}", path, name))
+        }
+    }
+}
+
+/// A node for a package with a manifest, identified by its manifest name.
+fn manifest_node(info: PackageInfo, markers: &[String]) -> Node {
+    let manifest = markers
+        .iter()
+        .map(|marker| info.path.join(marker))
+        .find(|path| path.is_file());
+    let (id, refs) = match manifest {
+        Some(manifest) => read_manifest(&manifest),
+        None => (None, Vec::new()),
+    };
+    Node {
+        info,
+        id,
+        refs,
+        target: None,
+    }
+}
+
+/// The name a manifest declares and the names of its dependencies.
+fn read_manifest(path: &Path) -> (Option<String>, Vec<String>) {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return (None, Vec::new()),
+    };
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    match file_name {
+        "Cargo.toml" => {
+            let toml = content
+                .parse::<toml::Value>()
+                .unwrap_or(toml::Value::Integer(0));
+            let id = toml
+                .get("package")
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+                .map(str::to_string);
+            let refs = ["dependencies", "dev-dependencies", "build-dependencies"]
+                .iter()
+                .filter_map(|table| toml.get(table).and_then(|t| t.as_table()))
+                .flat_map(|table| table.keys().cloned())
+                .collect();
+            (id, refs)
+        }
+        "package.json" => {
+            let json: serde_json::Value = serde_json::from_str(&content).unwrap_or_default();
+            let id = json
+                .get("name")
+                .and_then(|n| n.as_str())
+                .map(str::to_string);
+            let refs = [
+                "dependencies",
+                "devDependencies",
+                "peerDependencies",
+                "optionalDependencies",
+            ]
+            .iter()
+            .filter_map(|key| json.get(key).and_then(|d| d.as_object()))
+            .flat_map(|deps| deps.keys().cloned())
+            .collect();
+            (id, refs)
+        }
+        "go.mod" => {
+            let mut id = None;
+            let mut refs = Vec::new();
+            let mut in_require = false;
+            for line in content.lines().map(str::trim) {
+                if let Some(module) = line.strip_prefix("module ") {
+                    id = Some(module.trim().to_string());
+                } else if line.starts_with("require (") {
+                    in_require = true;
+                } else if in_require && line == ")" {
+                    in_require = false;
+                } else if let Some(require) =
+                    line.strip_prefix("require ").or(in_require.then_some(line))
+                {
+                    if let Some(module) = require.split_whitespace().next() {
+                        refs.push(module.to_string());
+                    }
+                }
+            }
+            (id, refs)
+        }
+        "pyproject.toml" => {
+            let toml = content
+                .parse::<toml::Value>()
+                .unwrap_or(toml::Value::Integer(0));
+            let id = toml
+                .get("project")
+                .and_then(|p| p.get("name"))
+                .or_else(|| toml.get("tool")?.get("poetry")?.get("name"))
+                .and_then(|n| n.as_str())
+                .map(python_name);
+            let mut refs: Vec<String> = toml
+                .get("project")
+                .and_then(|p| p.get("dependencies"))
+                .and_then(|d| d.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|dep| dep.as_str())
+                .map(|dep| {
+                    let end = dep
+                        .find(|c: char| !(c.is_alphanumeric() || "-_.".contains(c)))
+                        .unwrap_or(dep.len());
+                    python_name(&dep[..end])
+                })
+                .collect();
+            if let Some(poetry) = toml
+                .get("tool")
+                .and_then(|t| t.get("poetry"))
+                .and_then(|p| p.get("dependencies"))
+                .and_then(|d| d.as_table())
+            {
+                refs.extend(poetry.keys().map(|name| python_name(name)));
+            }
+            (id, refs)
+        }
+        _ => (None, Vec::new()),
+    }
+}
+
+/// A Python distribution name in normalized form.
+fn python_name(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn names(affected: &[(&PackageInfo, Reason)]) -> Vec<String> {
+        affected
+            .iter()
+            .map(|(package, reason)| match reason {
+                Reason::Changed => package.name.clone(),
+                Reason::DependsOn(on) => format!("{} <- {}", package.name, on),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cargo_dependents() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        for (path, manifest) in [
+            ("crates/core", "[package]\nname = \"app-core\"\n"),
+            (
+                "crates/cli",
+                "[package]\nname = \"app-cli\"\n[dependencies]\napp-core = { path = \"../core\" }\n",
+            ),
+            (
+                "crates/web",
+                "[package]\nname = \"app-web\"\n[dev-dependencies]\napp-cli = \"1\"\n",
+            ),
+            ("crates/docs", "[package]\nname = \"app-docs\"\n"),
+        ] {
+            fs::create_dir_all(root.join(path)).unwrap();
+            fs::write(root.join(path).join("Cargo.toml"), manifest).unwrap();
+        }
+
+        let graph = PackageGraph::build(root, &CkConfig::default());
+        let files = [PathBuf::from("crates/core/src/lib.rs")];
+        let mut affected = names(&graph.affected(&files, false));
+        affected.sort();
+        assert_eq!(
+            affected,
+            ["app-cli <- app-core", "app-core", "app-web <- app-cli"]
+        );
+        assert_eq!(names(&graph.affected(&files, true)), ["app-core"]);
+        assert!(graph
+            .affected(&[PathBuf::from("README.md")], false)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_bazel_dependents() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("lib/log")).unwrap();
+        fs::create_dir_all(root.join("svc/auth")).unwrap();
+        fs::write(
+            root.join("lib/log/BUILD"),
+            "go_library(name = \"log\", srcs = [\"log.go\"])\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("svc/auth/BUILD.bazel"),
+            "go_library(name = \"auth\", srcs = [\"auth.go\"], deps = [\"//lib/log\"])\n\
+             go_test(name = \"auth_test\", srcs = [\"auth_test.go\"], deps = [\":auth\"])\n\
+             go_binary(name = \"tool\", srcs = [\"tool.go\"])\n",
+        )
+        .unwrap();
+
+        let mut config = CkConfig::default();
+        config.monorepo.build_system = Some(crate::config::BuildSystem::Bazel);
+        let graph = PackageGraph::build(root, &config);
+
+        let mut affected = names(&graph.affected(&[PathBuf::from("lib/log/log.go")], false));
+        affected.sort();
+        assert_eq!(affected, ["auth <- log", "auth_test <- auth", "log"]);
+        assert_eq!(
+            names(&graph.affected(&[PathBuf::from("svc/auth/tool.go")], false)),
+            ["tool"]
+        );
+    }
+}
@@ -3,8 +3,13 @@
 
 //! Monorepo support module.
 
+pub mod affected;
+pub mod build_files;
 mod detector;
+mod graph;
 mod scope;
 
+pub use build_files::BuildPackage;
 pub use detector::{detect_packages, PackageInfo};
+pub use graph::{PackageGraph, Reason};
 pub use scope::{resolve_scope, ScopeResolver};
@@ -2,6 +2,22 @@
 // SPDX-License-Identifier: MIT
 
 //! Plugin ABI definitions.
+//!
+//! Plugins exchange JSON with ck through their linear memory. A plugin
+//! exports `memory` and `ck_alloc(len) -> ptr`, which ck calls to get a
+//! buffer for its input. `ck_plugin_validate` and `ck_plugin_generate`
+//! take the input as `(ptr, len)` and return their output packed into an
+//! `i64` as `ptr << 32 | len`, or 0 for no output.
+//!
+//! `ck_plugin_validate` gets the rule context (see
+//! [`RuleContext`](crate::rules::RuleContext)) with the full `message`
+//! added, and returns `{"valid": bool, "errors": [..], "warnings": [..]}`.
+//! `ck_plugin_generate` gets the repository context and returns the
+//! message as a JSON string.
+//!
+//! Imports live in the `ck` module: `log(ptr, len)` always, and with the
+//! matching permission `config() -> i64` and `env(ptr, len) -> i64`,
+//! which return JSON in a buffer from `ck_alloc`.
 
 use serde::{Deserialize, Serialize};
 
@@ -76,6 +92,18 @@ impl PluginCapability {
         ]
     }
 
+    /// Name of the capability in manifests and `plugins.permissions`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginCapability::ReadConfig => "read_config",
+            PluginCapability::Network => "network",
+            PluginCapability::FsRead => "fs_read",
+            PluginCapability::FsWrite => "fs_write",
+            PluginCapability::GitRead => "git_read",
+            PluginCapability::Env => "env",
+        }
+    }
+
     /// Get a human-readable description.
     pub fn description(&self) -> &'static str {
         match self {
@@ -113,28 +141,54 @@ fn parse_version_req(req: &str) -> Option<(u32, u32)> {
 }
 
 /// Plugin ABI version.
-#[allow(dead_code)]
 pub const ABI_VERSION: u32 = 1;
 
 /// Function signatures for plugin exports.
 pub mod exports {
-    /// Initialize the plugin.
-    #[allow(dead_code)]
+    /// Linear memory shared with ck.
+    pub const MEMORY: &str = "memory";
+    /// Allocate a buffer for ck's input: `(len) -> ptr`.
+    pub const ALLOC: &str = "ck_alloc";
+    /// Free a buffer returned to ck: `(ptr, len)`. Optional.
+    pub const DEALLOC: &str = "ck_dealloc";
+    /// Initialize the plugin: `() -> status`, non-zero on failure. Optional.
     pub const INIT: &str = "ck_plugin_init";
     /// Get plugin metadata.
     #[allow(dead_code)]
     pub const METADATA: &str = "ck_plugin_metadata";
-    /// Validate a commit message.
-    #[allow(dead_code)]
+    /// Validate a commit message: `(ptr, len) -> packed`.
     pub const VALIDATE: &str = "ck_plugin_validate";
-    /// Generate a commit message.
-    #[allow(dead_code)]
+    /// Generate a commit message: `(ptr, len) -> packed`.
     pub const GENERATE: &str = "ck_plugin_generate";
     /// Clean up the plugin.
     #[allow(dead_code)]
     pub const CLEANUP: &str = "ck_plugin_cleanup";
 }
 
+/// Host functions plugins import.
+pub mod imports {
+    /// Module the host functions are imported from.
+    pub const MODULE: &str = "ck";
+    /// Write a debug log line: `(ptr, len)`.
+    pub const LOG: &str = "log";
+    /// The configuration as JSON: `() -> packed`. Needs `read_config`.
+    pub const CONFIG: &str = "config";
+    /// An environment variable as a JSON string or null: `(ptr, len) -> packed`.
+    /// Needs `env`.
+    pub const ENV: &str = "env";
+}
+
+/// Pack a guest buffer into the `i64` the ABI returns.
+pub fn pack(ptr: u32, len: u32) -> i64 {
+    ((ptr as u64) << 32 | len as u64) as i64
+}
+
+/// Split a packed `i64` into a guest pointer and length.
+pub fn unpack(packed: i64) -> (u32, u32) {
+    let packed = packed as u64;
+    ((packed >> 32) as u32, packed as u32)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,6 +217,13 @@ mod tests {
         assert!(!manifest.is_compatible("0.0.9"));
     }
 
+    #[test]
+    fn test_pack() {
+        assert_eq!(unpack(pack(1024, 17)), (1024, 17));
+        assert_eq!(unpack(pack(u32::MAX, u32::MAX)), (u32::MAX, u32::MAX));
+        assert_eq!(unpack(0), (0, 0));
+    }
+
     #[test]
     fn test_manifest_from_toml() {
         let toml = r#"
@@ -0,0 +1,218 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Plugins enabled in the configuration.
+//!
+//! The rule engine runs every enabled plugin after the built-in rules.
+//! A plugin that can't be loaded or fails while checking reports an error
+//! on the commit instead of being skipped, so a broken policy plugin
+//! doesn't quietly let commits through.
+
+use std::sync::Mutex;
+
+use crate::analysis::RepositoryContext;
+use crate::config::CkConfig;
+use crate::error::{CkError, PluginError, Result};
+use crate::rules::{RuleContext, ValidationIssue};
+
+use super::abi::ABI_VERSION;
+use super::loader::PluginLoader;
+use super::runtime::{PluginInstance, PluginRuntime};
+
+/// Prefix of the codes of issues reported by plugins.
+pub const CODE_PREFIX: &str = "plugin:";
+
+/// The plugins a configuration enables, ready to run.
+pub struct EnabledPlugins {
+    plugins: Vec<(String, std::result::Result<Mutex<PluginInstance>, String>)>,
+}
+
+impl EnabledPlugins {
+    /// Load the plugins `config` enables, or `None` when it enables none.
+    pub fn load(config: &CkConfig) -> Option<Self> {
+        let settings = &config.plugins;
+        if !settings.enabled || settings.enabled_plugins.is_empty() {
+            return None;
+        }
+
+        let loader = PluginLoader::from_config(config);
+        let runtime = PluginRuntime::new().map(|runtime| {
+            runtime
+                .with_capabilities(settings.permissions.clone())
+                .with_config(serde_json::to_value(config).unwrap_or_default())
+        });
+
+        let plugins = settings
+            .enabled_plugins
+            .iter()
+            .map(|name| {
+                let instance = match runtime {
+                    Ok(ref runtime) => Self::start(&loader, runtime, name),
+                    Err(ref e) => Err(e.to_string()),
+                };
+                (name.clone(), instance.map(Mutex::new))
+            })
+            .collect();
+        Some(Self { plugins })
+    }
+
+    /// Load and instantiate the plugin `name`.
+    fn start(
+        loader: &PluginLoader,
+        runtime: &PluginRuntime,
+        name: &str,
+    ) -> std::result::Result<PluginInstance, String> {
+        let start = || -> Result<PluginInstance> {
+            let plugin = loader.load(name)?;
+            let manifest = &plugin.info.manifest;
+            if !manifest.is_compatible(crate::version::VERSION) {
+                return Err(CkError::Plugin(PluginError::VersionMismatch {
+                    name: name.to_string(),
+                    required: manifest.ck_version.clone(),
+                    current: crate::version::VERSION.to_string(),
+                }));
+            }
+            runtime.execute(&plugin)
+        };
+        start().map_err(|e| {
+            tracing::debug!("Could not load plugin {}: {}", name, e);
+            e.to_string()
+        })
+    }
+
+    /// Names of the enabled plugins.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.plugins.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Run every plugin's validate function on a commit.
+    pub fn check(&self, context: &RuleContext) -> Vec<ValidationIssue> {
+        let mut input = context.to_json();
+        input["message"] = context.message.format().into();
+        input["abi"] = ABI_VERSION.into();
+
+        let mut issues = Vec::new();
+        for (name, plugin) in &self.plugins {
+            let code = format!("{}{}", CODE_PREFIX, name);
+            let issue = |message: String, is_error: bool| ValidationIssue {
+                code: code.clone(),
+                message,
+                suggestion: None,
+                is_error,
+                line: None,
+                span: None,
+            };
+
+            let result = match plugin {
+                Ok(plugin) => lock(plugin).validate(&input),
+                Err(e) => {
+                    issues.push(issue(e.clone(), true));
+                    continue;
+                }
+            };
+            match result {
+                Ok(result) => {
+                    if !result.valid && result.errors.is_empty() {
+                        issues.push(issue(format!("Rejected by the '{}' plugin", name), true));
+                    }
+                    issues.extend(result.errors.into_iter().map(|e| issue(e, true)));
+                    issues.extend(result.warnings.into_iter().map(|w| issue(w, false)));
+                }
+                Err(e) => issues.push(issue(e.to_string(), true)),
+            }
+        }
+        issues
+    }
+
+    /// The first message a plugin generates for the staged changes, with
+    /// the name of that plugin.
+    pub fn generate(&self, context: &RepositoryContext) -> Option<(String, String)> {
+        let mut input = context.to_json();
+        input["abi"] = ABI_VERSION.into();
+
+        self.plugins.iter().find_map(|(name, plugin)| {
+            let plugin = plugin.as_ref().ok()?;
+            match lock(plugin).generate(&input) {
+                Ok(message) => message.map(|message| (name.clone(), message)),
+                Err(e) => {
+                    tracing::debug!("{}", e);
+                    None
+                }
+            }
+        })
+    }
+}
+
+impl std::fmt::Debug for EnabledPlugins {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// Lock a plugin, recovering it from a panic in an earlier call.
+fn lock(plugin: &Mutex<PluginInstance>) -> std::sync::MutexGuard<'_, PluginInstance> {
+    plugin
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commit::CommitMessage;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// A plugin that warns about every message.
+    const WARN_PLUGIN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (data (i32.const 16) "{\"warnings\":[\"looks odd\"]}")
+          (func (export "ck_alloc") (param i32) (result i32) (i32.const 1024))
+          (func (export "ck_plugin_validate") (param i32 i32) (result i64)
+            (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 26))))
+    "#;
+
+    fn config(dir: &TempDir, plugins: &[&str]) -> CkConfig {
+        let mut config = CkConfig::default();
+        config.plugins.enabled = true;
+        config.plugins.directory = Some(dir.path().to_path_buf());
+        config.plugins.enabled_plugins = plugins.iter().map(|p| p.to_string()).collect();
+        config
+    }
+
+    #[test]
+    fn test_disabled() {
+        let dir = TempDir::new().unwrap();
+        let mut config = config(&dir, &["warn"]);
+        config.plugins.enabled = false;
+        assert!(EnabledPlugins::load(&config).is_none());
+        assert!(EnabledPlugins::load(&CkConfig::default()).is_none());
+    }
+
+    #[test]
+    fn test_check() {
+        let dir = TempDir::new().unwrap();
+        let plugin_dir = dir.path().join("warn");
+        fs::create_dir(&plugin_dir).unwrap();
+        fs::write(
+            plugin_dir.join("plugin.toml"),
+            "name = \"warn\"\nversion = \"1.0.0\"\nck_version = \">=0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(plugin_dir.join("plugin.wasm"), WARN_PLUGIN).unwrap();
+
+        let config = config(&dir, &["warn", "missing"]);
+        let plugins = EnabledPlugins::load(&config).unwrap();
+        let message = CommitMessage::parse("feat: add export").unwrap();
+        let issues = plugins.check(&RuleContext::new(&message, &config));
+
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].code, "plugin:warn");
+        assert_eq!(issues[0].message, "looks odd");
+        assert!(!issues[0].is_error);
+        // A plugin that can't be loaded fails the check
+        assert_eq!(issues[1].code, "plugin:missing");
+        assert!(issues[1].is_error);
+    }
+}
@@ -0,0 +1,377 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Installing and removing plugins.
+//!
+//! A plugin source is a directory holding `plugin.toml` and `plugin.wasm`,
+//! either on disk or at an `http(s)://` URL. The module is checked against
+//! the expected checksum and, when a minisign key is configured, against
+//! `plugin.wasm.minisig` before anything is written to the plugins
+//! directory.
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::error::{CkError, PluginError, Result};
+
+use super::abi::PluginManifest;
+use super::loader::{PluginInfo, PluginLoader};
+
+/// Manifest file of a plugin.
+pub const MANIFEST_FILE: &str = "plugin.toml";
+
+/// Module file of a plugin.
+pub const WASM_FILE: &str = "plugin.wasm";
+
+/// Detached minisign signature of the module.
+pub const SIGNATURE_FILE: &str = "plugin.wasm.minisig";
+
+/// How a plugin is checked and installed.
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Expected SHA-256 of `plugin.wasm`, as hex with or without `sha256:`.
+    pub sha256: Option<String>,
+    /// Minisign public key the module must be signed with.
+    pub minisign_key: Option<String>,
+    /// Replace an installed plugin of the same name.
+    pub force: bool,
+}
+
+/// A plugin that was installed.
+#[derive(Debug, Clone)]
+pub struct Installed {
+    /// The installed plugin.
+    pub info: PluginInfo,
+    /// Digest of the module, as `sha256:<hex>`.
+    pub digest: String,
+    /// Whether the module's signature was verified.
+    pub signed: bool,
+    /// Whether an installed plugin was replaced.
+    pub replaced: bool,
+}
+
+/// Install the plugin at `source` into the loader's directory.
+pub fn install(loader: &PluginLoader, source: &str, options: &InstallOptions) -> Result<Installed> {
+    let manifest_bytes =
+        fetch(source, MANIFEST_FILE)?.ok_or_else(|| missing(source, MANIFEST_FILE))?;
+    let manifest = String::from_utf8(manifest_bytes.clone())
+        .map_err(|e| e.to_string())
+        .and_then(|content| PluginManifest::from_toml(&content).map_err(|e| e.to_string()))
+        .map_err(|e| {
+            CkError::Plugin(PluginError::InvalidManifest {
+                message: format!("Failed to parse manifest: {}", e),
+            })
+        })?;
+    check_name(&manifest.name)?;
+
+    let wasm = fetch(source, WASM_FILE)?.ok_or_else(|| missing(source, WASM_FILE))?;
+    let digest = digest(&wasm);
+    if let Some(ref expected) = options.sha256 {
+        let expected = expected.trim().trim_start_matches("sha256:").to_lowercase();
+        if digest.trim_start_matches("sha256:") != expected {
+            return Err(verification_error(
+                source,
+                format!("{} has {}, expected sha256:{}", WASM_FILE, digest, expected),
+            ));
+        }
+    }
+
+    let signature = match options.minisign_key {
+        Some(ref key) => {
+            let signature = fetch(source, SIGNATURE_FILE)?
+                .ok_or_else(|| verification_error(source, format!("Missing {}", SIGNATURE_FILE)))?;
+            verify_minisign(source, &wasm, &signature, key)?;
+            Some(signature)
+        }
+        None => None,
+    };
+
+    if !manifest.is_compatible(crate::version::VERSION) {
+        return Err(CkError::Plugin(PluginError::VersionMismatch {
+            name: manifest.name,
+            required: manifest.ck_version,
+            current: crate::version::VERSION.to_string(),
+        }));
+    }
+
+    let destination = loader.directory().join(&manifest.name);
+    let replaced = destination.exists();
+    if replaced && !options.force {
+        return Err(CkError::WithContext {
+            code: "plugin-exists",
+            context: "plugin install".to_string(),
+            message: format!(
+                "'{}' is already installed at {}; pass --force to replace it",
+                manifest.name,
+                destination.display()
+            ),
+        });
+    }
+
+    // Stage the plugin next to its destination so a failed or interrupted
+    // install leaves any installed version alone.
+    let pending = loader
+        .directory()
+        .join(format!(".{}.pending", manifest.name));
+    let cleanup_dir = pending.clone();
+    let _cleanup = crate::interrupt::on_interrupt("remove the staged plugin", move || {
+        let _ = fs::remove_dir_all(cleanup_dir);
+    });
+    let staged = stage(&pending, &manifest_bytes, &wasm, signature.as_deref());
+    let moved = staged.and_then(|()| {
+        if replaced {
+            fs::remove_dir_all(&destination)?;
+        }
+        fs::rename(&pending, &destination)
+    });
+    if let Err(e) = moved {
+        let _ = fs::remove_dir_all(&pending);
+        return Err(CkError::Plugin(PluginError::LoadFailed {
+            name: manifest.name,
+            message: format!("Failed to install into {}: {}", destination.display(), e),
+        }));
+    }
+
+    let info = loader
+        .load_plugin_info(&destination)?
+        .ok_or_else(|| missing(source, MANIFEST_FILE))?;
+    Ok(Installed {
+        info,
+        digest,
+        signed: signature.is_some(),
+        replaced,
+    })
+}
+
+/// Remove the installed plugin `name`, returning the directory removed.
+pub fn remove(loader: &PluginLoader, name: &str) -> Result<PathBuf> {
+    check_name(name)?;
+    let path = loader.directory().join(name);
+    if !path.join(MANIFEST_FILE).exists() {
+        return Err(CkError::Plugin(PluginError::NotFound {
+            name: name.to_string(),
+        }));
+    }
+    fs::remove_dir_all(&path).map_err(|e| {
+        CkError::Plugin(PluginError::LoadFailed {
+            name: name.to_string(),
+            message: format!("Failed to remove {}: {}", path.display(), e),
+        })
+    })?;
+    Ok(path)
+}
+
+/// Digest of an installed plugin's module, as `sha256:<hex>`.
+pub fn module_digest(info: &PluginInfo) -> Option<String> {
+    fs::read(info.path.join(WASM_FILE))
+        .ok()
+        .map(|wasm| digest(&wasm))
+}
+
+fn digest(bytes: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(bytes))
+}
+
+/// Write a plugin's files into `dir`, replacing any earlier staging.
+fn stage(
+    dir: &Path,
+    manifest: &[u8],
+    wasm: &[u8],
+    signature: Option<&[u8]>,
+) -> std::io::Result<()> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(MANIFEST_FILE), manifest)?;
+    fs::write(dir.join(WASM_FILE), wasm)?;
+    if let Some(signature) = signature {
+        fs::write(dir.join(SIGNATURE_FILE), signature)?;
+    }
+    Ok(())
+}
+
+/// Read `file` from a plugin source, or `None` when the source lacks it.
+fn fetch(source: &str, file: &str) -> Result<Option<Vec<u8>>> {
+    let fetch_error = |message: String| {
+        CkError::Plugin(PluginError::LoadFailed {
+            name: source.to_string(),
+            message,
+        })
+    };
+
+    if crate::config::is_remote(source) {
+        let url = format!("{}/{}", source.trim_end_matches('/'), file);
+        match ureq::get(&url).call() {
+            Ok(response) => {
+                let mut bytes = Vec::new();
+                response
+                    .into_reader()
+                    .read_to_end(&mut bytes)
+                    .map_err(|e| fetch_error(format!("Failed to download {}: {}", url, e)))?;
+                Ok(Some(bytes))
+            }
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(fetch_error(format!("Failed to download {}: {}", url, e))),
+        }
+    } else {
+        let path = Path::new(source).join(file);
+        if !path.exists() {
+            return Ok(None);
+        }
+        fs::read(&path)
+            .map(Some)
+            .map_err(|e| fetch_error(format!("Failed to read {}: {}", path.display(), e)))
+    }
+}
+
+/// Verify a minisign signature of the module.
+fn verify_minisign(source: &str, wasm: &[u8], signature: &[u8], key: &str) -> Result<()> {
+    use minisign_verify::{PublicKey, Signature};
+
+    let public_key = PublicKey::from_base64(key.trim())
+        .map_err(|e| verification_error(source, format!("Invalid minisign key: {}", e)))?;
+    let signature = Signature::decode(&String::from_utf8_lossy(signature))
+        .map_err(|e| verification_error(source, format!("Invalid minisign signature: {}", e)))?;
+    public_key
+        .verify(wasm, &signature, false)
+        .map_err(|e| verification_error(source, e.to_string()))
+}
+
+/// Reject names that would leave the plugins directory.
+fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(CkError::Plugin(PluginError::InvalidManifest {
+            message: format!("Invalid plugin name '{}'", name),
+        }))
+    }
+}
+
+fn missing(source: &str, file: &str) -> CkError {
+    CkError::Plugin(PluginError::LoadFailed {
+        name: source.to_string(),
+        message: format!("No {} found", file),
+    })
+}
+
+fn verification_error(source: &str, message: String) -> CkError {
+    CkError::Plugin(PluginError::VerificationFailed {
+        source_ref: source.to_string(),
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn source(name: &str) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(MANIFEST_FILE),
+            format!(
+                "name = \"{}\"\nversion = \"1.0.0\"\nck_version = \">=0.1.0\"\n",
+                name
+            ),
+        )
+        .unwrap();
+        fs::write(dir.path().join(WASM_FILE), "(module)").unwrap();
+        dir
+    }
+
+    fn source_ref(dir: &TempDir) -> String {
+        dir.path().to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_install_and_remove() {
+        let plugins = TempDir::new().unwrap();
+        let loader = PluginLoader::new(plugins.path());
+        let from = source("lint");
+
+        let options = InstallOptions {
+            sha256: Some(digest(b"(module)").to_uppercase().replace("SHA256:", "")),
+            ..Default::default()
+        };
+        let installed = install(&loader, &source_ref(&from), &options).unwrap();
+        assert_eq!(installed.info.name, "lint");
+        assert!(installed.info.has_wasm);
+        assert!(!installed.replaced);
+        assert_eq!(module_digest(&installed.info), Some(installed.digest));
+
+        // Installing again needs --force
+        assert!(install(&loader, &source_ref(&from), &options).is_err());
+        let options = InstallOptions {
+            force: true,
+            ..options
+        };
+        assert!(
+            install(&loader, &source_ref(&from), &options)
+                .unwrap()
+                .replaced
+        );
+        assert!(!plugins.path().join(".lint.pending").exists());
+
+        assert_eq!(
+            remove(&loader, "lint").unwrap(),
+            plugins.path().join("lint")
+        );
+        assert!(loader.discover().unwrap().is_empty());
+        assert!(matches!(
+            remove(&loader, "lint"),
+            Err(CkError::Plugin(PluginError::NotFound { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_install_verifies_module() {
+        let plugins = TempDir::new().unwrap();
+        let loader = PluginLoader::new(plugins.path());
+        let from = source("lint");
+
+        let options = InstallOptions {
+            sha256: Some("sha256:0000".to_string()),
+            ..Default::default()
+        };
+        let result = install(&loader, &source_ref(&from), &options);
+        assert!(matches!(
+            result,
+            Err(CkError::Plugin(PluginError::VerificationFailed { .. }))
+        ));
+
+        let options = InstallOptions {
+            minisign_key: Some("RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3".into()),
+            ..Default::default()
+        };
+        let result = install(&loader, &source_ref(&from), &options);
+        assert!(matches!(
+            result,
+            Err(CkError::Plugin(PluginError::VerificationFailed { .. }))
+        ));
+        assert!(loader.discover().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_install_rejects_unsafe_name() {
+        let plugins = TempDir::new().unwrap();
+        let loader = PluginLoader::new(plugins.path());
+        let from = source("../escape");
+
+        let result = install(&loader, &source_ref(&from), &InstallOptions::default());
+        assert!(matches!(
+            result,
+            Err(CkError::Plugin(PluginError::InvalidManifest { .. }))
+        ));
+        assert!(remove(&loader, "..").is_err());
+    }
+}
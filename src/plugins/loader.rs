@@ -3,11 +3,15 @@
 
 //! Plugin loader for WASM plugins.
 
+use crate::config::CkConfig;
 use crate::error::{CkError, PluginError, Result};
 use std::path::{Path, PathBuf};
 
 use super::abi::PluginManifest;
 
+/// Directory plugins are loaded from without `plugins.directory`.
+const DEFAULT_DIRECTORY: &str = ".ck/plugins";
+
 /// Plugin loader.
 pub struct PluginLoader {
     plugins_dir: PathBuf,
@@ -21,6 +25,25 @@ impl PluginLoader {
         }
     }
 
+    /// Create a loader for the plugins directory `config` sets, relative
+    /// to the repository root.
+    pub fn from_config(config: &CkConfig) -> Self {
+        let directory = config
+            .plugins
+            .directory
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_DIRECTORY));
+        match crate::git::open_repo() {
+            Ok(repo) if directory.is_relative() => Self::new(repo.workdir().join(directory)),
+            _ => Self::new(directory),
+        }
+    }
+
+    /// The directory plugins are loaded from.
+    pub fn directory(&self) -> &Path {
+        &self.plugins_dir
+    }
+
     /// Discover available plugins.
     pub fn discover(&self) -> Result<Vec<PluginInfo>> {
         let mut plugins = Vec::new();
@@ -50,11 +73,12 @@ impl PluginLoader {
             }
         }
 
+        plugins.sort_by(|a, b| a.name.cmp(&b.name));
         Ok(plugins)
     }
 
     /// Load plugin info from a directory.
-    fn load_plugin_info(&self, plugin_dir: &Path) -> Result<Option<PluginInfo>> {
+    pub(crate) fn load_plugin_info(&self, plugin_dir: &Path) -> Result<Option<PluginInfo>> {
         let manifest_path = plugin_dir.join("plugin.toml");
         let wasm_path = plugin_dir.join("plugin.wasm");
 
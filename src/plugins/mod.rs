@@ -4,9 +4,12 @@
 //! WASM plugin system.
 
 mod abi;
+mod enabled;
+pub mod install;
 mod loader;
 mod runtime;
 
 pub use abi::{PluginCapability, PluginManifest};
-pub use loader::PluginLoader;
-pub use runtime::PluginRuntime;
+pub use enabled::{EnabledPlugins, CODE_PREFIX};
+pub use loader::{PluginInfo, PluginLoader};
+pub use runtime::{PluginInstance, PluginRuntime, ValidateResult};
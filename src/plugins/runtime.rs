@@ -2,27 +2,43 @@
 // SPDX-License-Identifier: MIT
 
 //! Plugin runtime using Wasmtime.
+//!
+//! Each call into a plugin gets a fixed amount of fuel, so a plugin stuck
+//! in a loop fails the call instead of hanging the commit.
 
 use crate::error::{CkError, PluginError, Result};
-use wasmtime::{Engine, Instance, Linker, Module, Store};
+use serde::Deserialize;
+use wasmtime::{Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store};
 
-use super::abi::PluginCapability;
+use super::abi::{exports, imports, pack, unpack, PluginCapability};
 use super::loader::LoadedPlugin;
 
+/// Fuel for a single call into a plugin, roughly its instruction budget.
+const FUEL_PER_CALL: u64 = 200_000_000;
+
 /// Plugin runtime for executing WASM plugins.
 pub struct PluginRuntime {
     engine: Engine,
     allowed_capabilities: Vec<PluginCapability>,
+    config: Option<serde_json::Value>,
 }
 
 impl PluginRuntime {
     /// Create a new plugin runtime.
     pub fn new() -> Result<Self> {
-        let engine = Engine::default();
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| {
+            CkError::Plugin(PluginError::LoadFailed {
+                name: "runtime".to_string(),
+                message: format!("Failed to create the WASM engine: {}", e),
+            })
+        })?;
 
         Ok(Self {
             engine,
             allowed_capabilities: Vec::new(),
+            config: None,
         })
     }
 
@@ -32,14 +48,23 @@ impl PluginRuntime {
         self
     }
 
+    /// Set the configuration plugins with `read_config` can read.
+    pub fn with_config(mut self, config: serde_json::Value) -> Self {
+        self.config = Some(config);
+        self
+    }
+
     /// Execute a loaded plugin.
     pub fn execute(&self, plugin: &LoadedPlugin) -> Result<PluginInstance> {
+        let name = &plugin.info.name;
+        let permissions = &plugin.info.manifest.permissions;
+
         // Check capabilities
-        for cap in &plugin.info.manifest.permissions {
+        for cap in permissions {
             if !self.allowed_capabilities.contains(cap) {
                 return Err(CkError::Plugin(PluginError::PermissionDenied {
-                    name: plugin.info.name.clone(),
-                    permission: format!("{:?}", cap),
+                    name: name.clone(),
+                    permission: cap.as_str().to_string(),
                 }));
             }
         }
@@ -47,44 +72,106 @@ impl PluginRuntime {
         // Compile the module
         let module = Module::new(&self.engine, &plugin.wasm_bytes).map_err(|e| {
             CkError::Plugin(PluginError::LoadFailed {
-                name: plugin.info.name.clone(),
+                name: name.clone(),
                 message: format!("Failed to compile WASM: {}", e),
             })
         })?;
 
         // Create store and linker
-        let mut store = Store::new(&self.engine, PluginState::new());
+        let mut state = PluginState::new();
+        if permissions.contains(&PluginCapability::ReadConfig) {
+            state.config = self.config.clone();
+        }
+        let mut store = Store::new(&self.engine, state);
         let mut linker = Linker::new(&self.engine);
 
         // Add host functions based on capabilities
-        self.setup_host_functions(&mut linker)?;
+        self.setup_host_functions(&mut linker, permissions)
+            .map_err(|e| {
+                CkError::Plugin(PluginError::LoadFailed {
+                    name: name.clone(),
+                    message: format!("Failed to link host functions: {}", e),
+                })
+            })?;
 
         // Instantiate
-        let instance = linker.instantiate(&mut store, &module).map_err(|e| {
+        let load_failed = |message: String| {
             CkError::Plugin(PluginError::LoadFailed {
-                name: plugin.info.name.clone(),
-                message: format!("Failed to instantiate: {}", e),
+                name: name.clone(),
+                message,
             })
-        })?;
+        };
+        store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|e| load_failed(e.to_string()))?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(|e| load_failed(format!("Failed to instantiate: {}", e)))?;
+
+        if let Ok(init) = instance.get_typed_func::<(), i32>(&mut store, exports::INIT) {
+            match init.call(&mut store, ()) {
+                Ok(0) => {}
+                Ok(status) => {
+                    return Err(load_failed(format!(
+                        "{} returned {}",
+                        exports::INIT,
+                        status
+                    )))
+                }
+                Err(e) => return Err(load_failed(format!("{} failed: {}", exports::INIT, e))),
+            }
+        }
 
         Ok(PluginInstance {
-            name: plugin.info.name.clone(),
-            _store: store,
-            _instance: instance,
+            name: name.clone(),
+            store,
+            instance,
         })
     }
 
     /// Set up host functions for the linker.
-    fn setup_host_functions(&self, _linker: &mut Linker<PluginState>) -> Result<()> {
-        // Add host functions based on allowed capabilities
-        // This is where we'd expose ck functionality to plugins
+    ///
+    /// Functions behind a permission are only linked for plugins that
+    /// declare it, so an undeclared import fails at instantiation.
+    fn setup_host_functions(
+        &self,
+        linker: &mut Linker<PluginState>,
+        permissions: &[PluginCapability],
+    ) -> wasmtime::Result<()> {
+        linker.func_wrap(
+            imports::MODULE,
+            imports::LOG,
+            |mut caller: Caller<'_, PluginState>, ptr: i32, len: i32| {
+                let bytes = read_guest(&mut caller, ptr as u32, len as u32)?;
+                let line = String::from_utf8_lossy(&bytes).into_owned();
+                tracing::debug!("plugin: {}", line);
+                caller.data_mut().output.push(line);
+                Ok(())
+            },
+        )?;
 
-        // For now, just a placeholder
-        // In a real implementation, we'd add functions for:
-        // - Reading configuration
-        // - Accessing git data
-        // - Logging
-        // etc.
+        if permissions.contains(&PluginCapability::ReadConfig) {
+            linker.func_wrap(
+                imports::MODULE,
+                imports::CONFIG,
+                |mut caller: Caller<'_, PluginState>| {
+                    let config = serde_json::to_vec(&caller.data().config)?;
+                    write_guest(&mut caller, &config)
+                },
+            )?;
+        }
+
+        if permissions.contains(&PluginCapability::Env) {
+            linker.func_wrap(
+                imports::MODULE,
+                imports::ENV,
+                |mut caller: Caller<'_, PluginState>, ptr: i32, len: i32| {
+                    let name = read_guest(&mut caller, ptr as u32, len as u32)?;
+                    let value = std::env::var(String::from_utf8_lossy(&name).as_ref()).ok();
+                    write_guest(&mut caller, &serde_json::to_vec(&value)?)
+                },
+            )?;
+        }
 
         Ok(())
     }
@@ -99,12 +186,13 @@ impl Default for PluginRuntime {
 /// State passed to plugins.
 #[derive(Debug, Default)]
 pub struct PluginState {
-    /// Output buffer.
-    #[allow(dead_code)]
+    /// Lines the plugin logged.
     pub output: Vec<String>,
     /// Error buffer.
     #[allow(dead_code)]
     pub errors: Vec<String>,
+    /// Configuration, for plugins with `read_config`.
+    pub config: Option<serde_json::Value>,
 }
 
 impl PluginState {
@@ -119,31 +207,132 @@ pub struct PluginInstance {
     /// Plugin name.
     pub name: String,
     /// Wasmtime store.
-    _store: Store<PluginState>,
+    store: Store<PluginState>,
     /// Wasmtime instance.
-    _instance: Instance,
+    instance: Instance,
 }
 
 impl PluginInstance {
-    /// Call the plugin's validate function.
-    pub fn validate(&mut self, _message: &str) -> Result<ValidateResult> {
-        // Placeholder - would actually call the WASM function
-        Ok(ValidateResult {
-            valid: true,
-            errors: Vec::new(),
-            warnings: Vec::new(),
+    /// Call the plugin's validate function with the JSON form of a
+    /// [`RuleContext`](crate::rules::RuleContext).
+    ///
+    /// A plugin without a validate function accepts every message.
+    pub fn validate(&mut self, context: &serde_json::Value) -> Result<ValidateResult> {
+        match self.call(exports::VALIDATE, context)? {
+            Some(output) => serde_json::from_slice(&output)
+                .map_err(|e| self.failed(format!("Invalid validate result: {}", e))),
+            None => Ok(ValidateResult::default()),
+        }
+    }
+
+    /// Call the plugin's generate function with the JSON form of a
+    /// [`RepositoryContext`](crate::analysis::RepositoryContext).
+    pub fn generate(&mut self, context: &serde_json::Value) -> Result<Option<String>> {
+        match self.call(exports::GENERATE, context)? {
+            Some(output) => serde_json::from_slice(&output)
+                .map_err(|e| self.failed(format!("Invalid generate result: {}", e))),
+            None => Ok(None),
+        }
+    }
+
+    /// Lines the plugin logged through `ck.log`.
+    pub fn log(&self) -> &[String] {
+        &self.store.data().output
+    }
+
+    /// Pass `input` to the `export` function and read back its output.
+    ///
+    /// Returns `None` when the plugin doesn't export the function or
+    /// returns no output.
+    fn call(&mut self, export: &str, input: &serde_json::Value) -> Result<Option<Vec<u8>>> {
+        if self.instance.get_func(&mut self.store, export).is_none() {
+            return Ok(None);
+        }
+        let input = serde_json::to_vec(input).map_err(|e| self.failed(e.to_string()))?;
+        self.store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|e| self.failed(e.to_string()))?;
+
+        let output = self
+            .call_raw(export, &input)
+            .map_err(|e| self.failed(format!("{} failed: {}", export, e)))?;
+        Ok(output)
+    }
+
+    fn call_raw(&mut self, export: &str, input: &[u8]) -> wasmtime::Result<Option<Vec<u8>>> {
+        let store = &mut self.store;
+        let memory = self
+            .instance
+            .get_memory(&mut *store, exports::MEMORY)
+            .ok_or_else(|| wasmtime::Error::msg("the plugin exports no memory"))?;
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut *store, exports::ALLOC)?;
+        let ptr = alloc.call(&mut *store, input.len() as i32)?;
+        memory.write(&mut *store, ptr as u32 as usize, input)?;
+
+        let func = self
+            .instance
+            .get_typed_func::<(i32, i32), i64>(&mut *store, export)?;
+        let packed = func.call(&mut *store, (ptr, input.len() as i32))?;
+        if packed == 0 {
+            return Ok(None);
+        }
+
+        let (ptr, len) = unpack(packed);
+        let mut output = vec![0; len as usize];
+        memory.read(&*store, ptr as usize, &mut output)?;
+        if let Ok(dealloc) = self
+            .instance
+            .get_typed_func::<(i32, i32), ()>(&mut *store, exports::DEALLOC)
+        {
+            dealloc.call(&mut *store, (ptr as i32, len as i32))?;
+        }
+        Ok(Some(output))
+    }
+
+    fn failed(&self, message: String) -> CkError {
+        CkError::Plugin(PluginError::ExecutionFailed {
+            name: self.name.clone(),
+            message,
         })
     }
+}
 
-    /// Call the plugin's generate function.
-    pub fn generate(&mut self, _context: &str) -> Result<Option<String>> {
-        // Placeholder - would actually call the WASM function
-        Ok(None)
+/// The memory a plugin exports for host functions.
+fn guest_memory(caller: &mut Caller<'_, PluginState>) -> wasmtime::Result<Memory> {
+    match caller.get_export(exports::MEMORY) {
+        Some(Extern::Memory(memory)) => Ok(memory),
+        _ => Err(wasmtime::Error::msg("the plugin exports no memory")),
     }
 }
 
+/// Copy `len` bytes at `ptr` out of the plugin's memory.
+fn read_guest(
+    caller: &mut Caller<'_, PluginState>,
+    ptr: u32,
+    len: u32,
+) -> wasmtime::Result<Vec<u8>> {
+    let memory = guest_memory(caller)?;
+    let mut bytes = vec![0; len as usize];
+    memory.read(&*caller, ptr as usize, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Copy `bytes` into a buffer from the plugin's `ck_alloc`, returning it packed.
+fn write_guest(caller: &mut Caller<'_, PluginState>, bytes: &[u8]) -> wasmtime::Result<i64> {
+    let alloc = match caller.get_export(exports::ALLOC) {
+        Some(Extern::Func(alloc)) => alloc.typed::<i32, i32>(&*caller)?,
+        _ => return Err(wasmtime::Error::msg("the plugin exports no ck_alloc")),
+    };
+    let ptr = alloc.call(&mut *caller, bytes.len() as i32)?;
+    guest_memory(caller)?.write(&mut *caller, ptr as u32 as usize, bytes)?;
+    Ok(pack(ptr as u32, bytes.len() as u32))
+}
+
 /// Result from plugin validation.
-#[derive(Debug)]
+#[derive(Debug, Deserialize)]
+#[serde(default)]
 pub struct ValidateResult {
     /// Whether the message is valid.
     pub valid: bool,
@@ -153,9 +342,62 @@ pub struct ValidateResult {
     pub warnings: Vec<String>,
 }
 
+impl Default for ValidateResult {
+    fn default() -> Self {
+        Self {
+            valid: true,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::plugins::abi::PluginManifest;
+    use crate::plugins::loader::PluginInfo;
+
+    /// A plugin that logs its input, rejects every message and suggests
+    /// a fixed one.
+    const ECHO_PLUGIN: &str = r#"
+        (module
+          (import "ck" "log" (func $log (param i32 i32)))
+          (memory (export "memory") 1)
+          (global $next (mut i32) (i32.const 1024))
+          (data (i32.const 16) "{\"valid\":false,\"errors\":[\"no ticket\"]}")
+          (data (i32.const 128) "\"chore: tidy up\"")
+          (func (export "ck_alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get $len)))
+            (local.get $ptr))
+          (func (export "ck_plugin_validate") (param $ptr i32) (param $len i32) (result i64)
+            (call $log (local.get $ptr) (local.get $len))
+            (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 38)))
+          (func (export "ck_plugin_generate") (param i32 i32) (result i64)
+            (i64.or (i64.shl (i64.const 128) (i64.const 32)) (i64.const 16))))
+    "#;
+
+    fn plugin(wat: &str, permissions: Vec<PluginCapability>) -> LoadedPlugin {
+        let manifest = PluginManifest {
+            name: "echo".to_string(),
+            version: "1.0.0".to_string(),
+            ck_version: ">=0.1.0".to_string(),
+            description: None,
+            author: None,
+            permissions,
+        };
+        LoadedPlugin {
+            info: PluginInfo {
+                name: manifest.name.clone(),
+                path: "echo".into(),
+                manifest,
+                has_wasm: true,
+            },
+            wasm_bytes: wat.as_bytes().to_vec(),
+        }
+    }
 
     #[test]
     fn test_plugin_runtime_new() {
@@ -169,4 +411,52 @@ mod tests {
         assert!(state.output.is_empty());
         assert!(state.errors.is_empty());
     }
+
+    #[test]
+    fn test_validate_and_generate() {
+        let runtime = PluginRuntime::new().unwrap();
+        let mut instance = runtime.execute(&plugin(ECHO_PLUGIN, vec![])).unwrap();
+
+        let context = serde_json::json!({"message": "feat: add export"});
+        let result = instance.validate(&context).unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.errors, vec!["no ticket"]);
+        assert!(result.warnings.is_empty());
+        assert_eq!(instance.log(), [context.to_string()]);
+
+        let generated = instance.generate(&serde_json::json!({})).unwrap();
+        assert_eq!(generated.as_deref(), Some("chore: tidy up"));
+    }
+
+    #[test]
+    fn test_permissions() {
+        let runtime = PluginRuntime::new().unwrap();
+        let needs_env = plugin(ECHO_PLUGIN, vec![PluginCapability::Env]);
+        assert!(matches!(
+            runtime.execute(&needs_env),
+            Err(CkError::Plugin(PluginError::PermissionDenied { .. }))
+        ));
+
+        // Imports behind an undeclared permission are not linked
+        let reads_env = r#"(module (import "ck" "env" (func (param i32 i32) (result i64))))"#;
+        assert!(runtime.execute(&plugin(reads_env, vec![])).is_err());
+    }
+
+    #[test]
+    fn test_runaway_plugin_runs_out_of_fuel() {
+        let looping = r#"
+            (module
+              (memory (export "memory") 1)
+              (func (export "ck_alloc") (param i32) (result i32) (i32.const 0))
+              (func (export "ck_plugin_validate") (param i32 i32) (result i64)
+                (loop $forever (br $forever))
+                (i64.const 0)))
+        "#;
+        let runtime = PluginRuntime::new().unwrap();
+        let mut instance = runtime.execute(&plugin(looping, vec![])).unwrap();
+        assert!(matches!(
+            instance.validate(&serde_json::json!({})),
+            Err(CkError::Plugin(PluginError::ExecutionFailed { .. }))
+        ));
+    }
 }
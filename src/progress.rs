@@ -0,0 +1,115 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Progress bars and spinners for long operations.
+//!
+//! Progress is drawn on stderr, and only when it is a terminal. It is
+//! turned off for CI, non-interactive and machine-readable runs, so it
+//! never ends up in logs or JSON output.
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Delay between spinner frames.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Turn progress reporting on or off for the process.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Check if progress is drawn.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+        && console::Term::stderr().is_term()
+        && std::env::var_os("CI").is_none()
+}
+
+/// A spinner or bar that is cleared when finished or dropped.
+#[derive(Debug)]
+pub struct Progress {
+    bar: ProgressBar,
+}
+
+impl Progress {
+    /// A spinner for work of unknown length.
+    pub fn spinner(message: &str) -> Self {
+        let bar = Self::draw(None);
+        bar.set_style(
+            ProgressStyle::with_template("{spinner:.cyan} {msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        );
+        bar.set_message(message.to_string());
+        Self::start(bar)
+    }
+
+    /// A bar counting `len` steps.
+    pub fn bar(len: usize, message: &str) -> Self {
+        let bar = Self::draw(Some(len as u64));
+        bar.set_style(
+            ProgressStyle::with_template("{spinner:.cyan} {msg} [{bar:30.cyan/dim}] {pos}/{len}")
+                .unwrap_or_else(|_| ProgressStyle::default_bar())
+                .progress_chars("=> "),
+        );
+        bar.set_message(message.to_string());
+        Self::start(bar)
+    }
+
+    fn start(bar: ProgressBar) -> Self {
+        if !bar.is_hidden() {
+            bar.enable_steady_tick(TICK);
+        }
+        Self { bar }
+    }
+
+    fn draw(len: Option<u64>) -> ProgressBar {
+        let target = if is_enabled() {
+            ProgressDrawTarget::stderr()
+        } else {
+            ProgressDrawTarget::hidden()
+        };
+        ProgressBar::with_draw_target(len, target)
+    }
+
+    /// Advance by one step.
+    pub fn inc(&self) {
+        self.bar.inc(1);
+    }
+
+    /// Replace the message.
+    pub fn set_message(&self, message: &str) {
+        self.bar.set_message(message.to_string());
+    }
+
+    /// Clear the progress from the terminal.
+    pub fn finish(self) {}
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_progress_is_hidden() {
+        set_enabled(false);
+        assert!(!is_enabled());
+
+        let progress = Progress::bar(3, "Checking commits");
+        assert!(progress.bar.is_hidden());
+        progress.inc();
+        assert_eq!(progress.bar.position(), 1);
+        progress.finish();
+
+        assert!(Progress::spinner("Analyzing changes").bar.is_hidden());
+        set_enabled(true);
+    }
+}
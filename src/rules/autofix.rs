@@ -0,0 +1,151 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Automatic fixes for rule violations.
+
+use super::mood;
+
+/// Check if a rule has an automatic subject fix.
+pub fn is_fixable(code: &str) -> bool {
+    matches!(
+        code,
+        "subject-case" | "subject-trailing-period" | "subject-imperative"
+    )
+}
+
+/// Apply the automatic fix for a rule to a subject.
+///
+/// Returns `None` when the rule has no automatic fix or the subject
+/// is already compliant.
+pub fn fix_subject(code: &str, subject: &str) -> Option<String> {
+    let fixed = match code {
+        "subject-case" => {
+            let mut chars = subject.chars();
+            let first = chars.next()?;
+            format!("{}{}", first.to_lowercase(), chars.as_str())
+        }
+        "subject-trailing-period" => subject.trim_end_matches('.').to_string(),
+        "subject-imperative" => {
+            let (first_word, imperative) = mood::subject_imperative(subject)?;
+            if !reads_as_verb(subject) {
+                return None;
+            }
+            let rest = &subject[subject.find(first_word)? + first_word.len()..];
+            format!("{}{}", imperative, rest)
+        }
+        _ => return None,
+    };
+
+    if fixed == subject {
+        None
+    } else {
+        Some(fixed)
+    }
+}
+
+/// Whether the first word of `subject` is safe to rewrite as a verb.
+///
+/// The mood check still reports `cached values were stale` and `logs
+/// rotated daily`, but a first word followed by a predicate, or an `-s`
+/// form followed by a participle or adverb, may be a noun and is left for
+/// the author to reword.
+fn reads_as_verb(subject: &str) -> bool {
+    let mut words = subject.split_whitespace();
+    let plural = words
+        .next()
+        .is_some_and(|word| word.to_lowercase().ends_with('s'));
+    let next: Vec<String> = words.take(2).map(str::to_lowercase).collect();
+
+    let predicate = next.iter().any(|word| mood::starts_predicate(word));
+    let modifier = plural
+        && next
+            .first()
+            .is_some_and(|word| is_participle(word) || is_adverb(word));
+    !predicate && !modifier
+}
+
+/// Whether `word` is the past participle of a known verb: `rotated`,
+/// `built`.
+fn is_participle(word: &str) -> bool {
+    !word.ends_with('s') && !word.ends_with("ing") && mood::imperative(word).is_some()
+}
+
+/// Whether `word` looks like an adverb: `daily`, `correctly`.
+fn is_adverb(word: &str) -> bool {
+    word.len() > 3 && word.ends_with("ly") && !mood::is_imperative(word)
+}
+
+/// Apply every available subject fix for a set of rule codes.
+pub fn apply_subject_fixes<'a>(codes: impl IntoIterator<Item = &'a str>, subject: &str) -> String {
+    codes
+        .into_iter()
+        .fold(subject.to_string(), |current, code| {
+            fix_subject(code, &current).unwrap_or(current)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_subject_case() {
+        assert_eq!(
+            fix_subject("subject-case", "Add feature"),
+            Some("add feature".to_string())
+        );
+        assert_eq!(fix_subject("subject-case", "add feature"), None);
+    }
+
+    #[test]
+    fn test_fix_trailing_period() {
+        assert_eq!(
+            fix_subject("subject-trailing-period", "add feature."),
+            Some("add feature".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fix_imperative() {
+        assert_eq!(
+            fix_subject("subject-imperative", "added new parser"),
+            Some("add new parser".to_string())
+        );
+        assert_eq!(
+            fix_subject("subject-imperative", "migrated config loader"),
+            Some("migrate config loader".to_string())
+        );
+        assert_eq!(fix_subject("subject-imperative", "tweak parser"), None);
+        assert_eq!(fix_subject("subject-imperative", "tests are flaky"), None);
+        assert_eq!(
+            fix_subject("subject-imperative", "cached values were stale"),
+            None
+        );
+        assert_eq!(
+            fix_subject("subject-imperative", "logs rotated daily"),
+            None
+        );
+        assert_eq!(
+            fix_subject("subject-imperative", "builds reliably on windows"),
+            None
+        );
+        assert_eq!(
+            fix_subject("subject-imperative", "fixed broken link in readme"),
+            Some("fix broken link in readme".to_string())
+        );
+        assert_eq!(
+            fix_subject("subject-imperative", "updated generated files"),
+            Some("update generated files".to_string())
+        );
+    }
+
+    #[test]
+    fn test_apply_subject_fixes() {
+        let codes = [
+            "subject-imperative",
+            "subject-case",
+            "subject-trailing-period",
+        ];
+        assert_eq!(apply_subject_fixes(codes, "Added parser."), "add parser");
+    }
+}
@@ -0,0 +1,126 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Azure Pipelines logging commands for check results.
+//!
+//! Each issue becomes a `##vso[task.logissue]` line, which Azure shows in
+//! the run summary and on the pull request. Warnings without failures mark
+//! the task as succeeded with issues instead of plain success.
+
+use super::validator::{ValidationIssue, ValidationResult};
+
+/// Logging commands for check results.
+///
+/// `failed` is the overall outcome, which may count warnings as failures.
+/// `path_for` maps a commit SHA to the file its issues are reported on.
+pub fn logging_commands(
+    results: &[ValidationResult],
+    failed: bool,
+    path_for: impl Fn(&str) -> Option<String>,
+) -> Vec<String> {
+    let mut commands = Vec::new();
+    for result in results {
+        let sha = result.commit_sha.as_deref().unwrap_or("HEAD");
+        let path = path_for(sha);
+        let subject = result.message.lines().next().unwrap_or("");
+        for issue in result.errors.iter().chain(&result.warnings) {
+            commands.push(log_issue(path.as_deref(), short(sha), subject, issue));
+        }
+    }
+
+    let has_warnings = results.iter().any(|r| !r.warnings.is_empty());
+    if !failed && has_warnings {
+        commands.push("##vso[task.complete result=SucceededWithIssues;]".to_string());
+    }
+    commands
+}
+
+/// Print the logging commands to stdout, where the agent picks them up.
+pub fn print(
+    results: &[ValidationResult],
+    failed: bool,
+    path_for: impl Fn(&str) -> Option<String>,
+) {
+    for command in logging_commands(results, failed, path_for) {
+        println!("{}", command);
+    }
+}
+
+fn log_issue(path: Option<&str>, sha: &str, subject: &str, issue: &ValidationIssue) -> String {
+    let mut properties = vec![
+        format!("type={}", if issue.is_error { "error" } else { "warning" }),
+        format!("code={}", escape_property(&issue.code)),
+    ];
+    if let Some(path) = path {
+        properties.push(format!("sourcepath={}", escape_property(path)));
+        properties.push("linenumber=1".to_string());
+    }
+
+    let mut message = format!("{} {}: {}", sha, subject, issue.message);
+    if let Some(ref suggestion) = issue.suggestion {
+        message.push_str(&format!(" ({})", suggestion));
+    }
+    format!(
+        "##vso[task.logissue {};]{}",
+        properties.join(";"),
+        escape_message(&message)
+    )
+}
+
+/// Escape a logging command message, which ends at the line break.
+fn escape_message(text: &str) -> String {
+    text.replace('%', "%AZP25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a logging command property, which also ends at `;` or `]`.
+fn escape_property(text: &str) -> String {
+    escape_message(text).replace(';', "%3B").replace(']', "%5D")
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..7.min(sha.len())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(code: &str, is_error: bool) -> ValidationIssue {
+        ValidationIssue {
+            code: code.to_string(),
+            message: "Subject is 100% too long".to_string(),
+            suggestion: None,
+            is_error,
+            line: Some(1),
+            span: None,
+        }
+    }
+
+    #[test]
+    fn test_logging_commands() {
+        let mut failing = ValidationResult::new("feat: add export".to_string());
+        failing.commit_sha = Some("1234567890".to_string());
+        failing.errors.push(issue("subject-max-length", true));
+        let commands = logging_commands(&[failing], true, |_| Some("src/a;b.rs".to_string()));
+        assert_eq!(
+            commands,
+            vec![
+                "##vso[task.logissue type=error;code=subject-max-length;\
+                 sourcepath=src/a%3Bb.rs;linenumber=1;]\
+                 1234567 feat: add export: Subject is 100%AZP25 too long"
+            ]
+        );
+
+        let mut warning = ValidationResult::new("fix: handle input".to_string());
+        warning.warnings.push(issue("subject-imperative", false));
+        let commands = logging_commands(&[warning], false, |_| None);
+        assert!(commands[0]
+            .starts_with("##vso[task.logissue type=warning;code=subject-imperative;]HEAD"));
+        assert_eq!(
+            commands[1],
+            "##vso[task.complete result=SucceededWithIssues;]"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Baseline of accepted violations in existing history.
+//!
+//! `.ck-baseline.json` maps commit SHAs to the rule codes they violated
+//! when the baseline was taken. Range checks ignore those findings, so
+//! stricter rules apply to new commits without rewriting old ones.
+//!
+//! The baseline records the hash of the configuration it was taken
+//! under. Once the configuration changes it no longer applies, so old
+//! findings can't hide violations of new rules until it is re-recorded.
+
+use crate::error::{CkError, ConfigError, Result};
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use super::validator::ValidationResult;
+
+/// Name of the baseline file in the repository root.
+pub const BASELINE_FILE: &str = ".ck-baseline.json";
+
+/// Rule codes accepted per commit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Hash of the configuration the baseline was taken under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_hash: Option<String>,
+    /// Violated rule codes keyed by commit SHA.
+    #[serde(default)]
+    pub commits: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Baseline {
+    /// Load the baseline from `root`, or an empty one if there is none.
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join(BASELINE_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content).map_err(|e| {
+            CkError::Config(ConfigError::ParseError {
+                message: format!("{}: {}", path.display(), e),
+            })
+        })
+    }
+
+    /// Write the baseline to `root`.
+    pub fn save(&self, root: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).unwrap_or_default();
+        std::fs::write(root.join(BASELINE_FILE), content + "\n")?;
+        Ok(())
+    }
+
+    /// Record the findings of commits checked under the configuration with
+    /// hash `config_hash`.
+    pub fn from_results(results: &[ValidationResult], config_hash: &str) -> Self {
+        let mut commits = BTreeMap::new();
+        for result in results {
+            let sha = match &result.commit_sha {
+                Some(sha) => sha,
+                None => continue,
+            };
+            let codes: BTreeSet<String> = result
+                .errors
+                .iter()
+                .chain(&result.warnings)
+                .map(|issue| issue.code.clone())
+                .collect();
+            if !codes.is_empty() {
+                commits.insert(sha.clone(), codes);
+            }
+        }
+        Self {
+            config_hash: Some(config_hash.to_string()),
+            commits,
+        }
+    }
+
+    /// Whether the baseline was taken under another configuration.
+    ///
+    /// Baselines from before hashes were recorded always apply.
+    pub fn is_stale(&self, config_hash: &str) -> bool {
+        self.config_hash
+            .as_deref()
+            .is_some_and(|hash| hash != config_hash)
+    }
+
+    /// Number of baselined findings.
+    pub fn len(&self) -> usize {
+        self.commits.values().map(BTreeSet::len).sum()
+    }
+
+    /// Check if the baseline accepts no findings.
+    pub fn is_empty(&self) -> bool {
+        self.commits.is_empty()
+    }
+
+    /// Report a written baseline.
+    pub fn print_saved(&self) {
+        println!(
+            "{} Baselined {} finding(s) in {} commit(s) to {}",
+            style("✓").green(),
+            self.len(),
+            self.commits.len(),
+            BASELINE_FILE
+        );
+    }
+
+    /// Drop baselined findings from `results`, returning how many were dropped.
+    pub fn apply(&self, results: &mut [ValidationResult]) -> usize {
+        let mut ignored = 0;
+        for result in results {
+            let codes = match result
+                .commit_sha
+                .as_ref()
+                .and_then(|sha| self.commits.get(sha))
+            {
+                Some(codes) => codes,
+                None => continue,
+            };
+
+            let before = result.issue_count();
+            result.errors.retain(|issue| !codes.contains(&issue.code));
+            result.warnings.retain(|issue| !codes.contains(&issue.code));
+            ignored += before - result.issue_count();
+        }
+        ignored
+    }
+}
+
+/// Note on stderr that the baseline was ignored because the configuration
+/// changed.
+pub fn print_stale() {
+    eprintln!(
+        "{} Ignored {}: it was recorded under a different configuration; \
+         re-record it with --update-baseline",
+        style("⚠").yellow(),
+        BASELINE_FILE
+    );
+}
+
+/// Note on stderr how many findings the baseline hid.
+pub fn print_ignored(ignored: usize) {
+    if ignored > 0 {
+        eprintln!(
+            "{} Ignored {} baselined finding(s) from {}",
+            style("ℹ").blue(),
+            ignored,
+            BASELINE_FILE
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::ValidationIssue;
+    use crate::testing::FixtureRepo;
+
+    fn result(sha: &str, codes: &[&str]) -> ValidationResult {
+        let mut result = ValidationResult::new(String::new());
+        result.commit_sha = Some(sha.to_string());
+        for code in codes {
+            result.errors.push(ValidationIssue {
+                code: code.to_string(),
+                message: String::new(),
+                suggestion: None,
+                is_error: true,
+                line: None,
+                span: None,
+            });
+        }
+        result
+    }
+
+    #[test]
+    fn test_baseline_ignores_recorded_findings() {
+        let baseline = Baseline::from_results(
+            &[result("aaa", &["subject-max-length"]), result("bbb", &[])],
+            "sha256:a",
+        );
+        assert_eq!(baseline.len(), 1);
+
+        let mut results = vec![
+            result("aaa", &["subject-max-length", "scope-required"]),
+            result("ccc", &["subject-max-length"]),
+        ];
+        assert_eq!(baseline.apply(&mut results), 1);
+        assert_eq!(results[0].errors[0].code, "scope-required");
+        assert_eq!(results[1].errors.len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let fixture = FixtureRepo::new();
+        assert!(Baseline::load(fixture.path()).unwrap().is_empty());
+
+        let baseline = Baseline::from_results(&[result("aaa", &["body-required"])], "sha256:a");
+        baseline.save(fixture.path()).unwrap();
+        assert_eq!(Baseline::load(fixture.path()).unwrap(), baseline);
+    }
+
+    #[test]
+    fn test_stale_baseline() {
+        let baseline = Baseline::from_results(&[result("aaa", &["body-required"])], "sha256:a");
+        assert!(!baseline.is_stale("sha256:a"));
+        assert!(baseline.is_stale("sha256:b"));
+
+        let legacy: Baseline = serde_json::from_str(r#"{"commits": {}}"#).unwrap();
+        assert!(!legacy.is_stale("sha256:b"));
+    }
+}
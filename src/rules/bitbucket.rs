@@ -0,0 +1,220 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Bitbucket Code Insights reports for check results.
+//!
+//! A pipeline step posts `report` with `PUT .../commit/{commit}/reports/{report_id}`
+//! and `annotations` with `POST .../reports/{report_id}/annotations`.
+//! `commit` is filled in when a range holds a single commit; otherwise the
+//! step uses `$BITBUCKET_COMMIT`.
+
+use serde::Serialize;
+
+use super::validator::{ValidationIssue, ValidationResult};
+
+/// Report id, stable across runs so each run replaces the last report.
+pub const REPORT_ID: &str = "ck";
+
+/// Maximum annotations Bitbucket accepts per request.
+pub const MAX_ANNOTATIONS: usize = 100;
+
+/// A Code Insights report with its annotations.
+#[derive(Debug, Clone, Serialize)]
+pub struct BitbucketReport {
+    /// Id the report is stored under.
+    pub report_id: String,
+    /// Commit the report is for, when a single commit was checked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Report creation request.
+    pub report: Report,
+    /// Annotation creation request.
+    pub annotations: Vec<Annotation>,
+}
+
+/// The body of a report creation request.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    /// Report title.
+    pub title: String,
+    /// Summary shown under the title.
+    pub details: String,
+    /// Always `BUG`.
+    pub report_type: String,
+    /// Tool that produced the report.
+    pub reporter: String,
+    /// `PASSED` or `FAILED`.
+    pub result: String,
+    /// Counts shown in the report.
+    pub data: Vec<ReportData>,
+}
+
+/// A value shown in the report.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportData {
+    /// Label.
+    pub title: String,
+    /// Always `NUMBER`.
+    #[serde(rename = "type")]
+    pub data_type: String,
+    /// Value.
+    pub value: usize,
+}
+
+/// A report annotation.
+#[derive(Debug, Clone, Serialize)]
+pub struct Annotation {
+    /// Id unique within the report.
+    pub external_id: String,
+    /// Always `CODE_SMELL`.
+    pub annotation_type: String,
+    /// One-line summary.
+    pub summary: String,
+    /// Full message.
+    pub details: String,
+    /// `HIGH` for errors, `MEDIUM` for warnings.
+    pub severity: String,
+    /// File the annotation is shown on, when the commit changes one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Line in `path`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+impl BitbucketReport {
+    /// Build a report from check results.
+    ///
+    /// `failed` is the overall outcome, which may count warnings as
+    /// failures. `path_for` maps a commit SHA to the file annotations on
+    /// it are attached to.
+    pub fn from_results(
+        results: &[ValidationResult],
+        failed: bool,
+        path_for: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let failing = results.iter().filter(|r| !r.is_valid()).count();
+        let mut details = if failing == 0 {
+            format!("{} commit(s) pass", results.len())
+        } else {
+            format!("{} of {} commit(s) fail", failing, results.len())
+        };
+
+        let mut annotations = Vec::new();
+        for result in results {
+            let sha = result.commit_sha.as_deref().unwrap_or("HEAD");
+            let path = path_for(sha);
+            let subject = result.message.lines().next().unwrap_or("");
+            for issue in result.errors.iter().chain(&result.warnings) {
+                let id = format!("{}-{}", short(sha), annotations.len() + 1);
+                annotations.push(annotation(id, path.clone(), subject, issue));
+            }
+        }
+        if annotations.len() > MAX_ANNOTATIONS {
+            details.push_str(&format!(
+                "; showing the first {} of {} annotations",
+                MAX_ANNOTATIONS,
+                annotations.len()
+            ));
+            annotations.truncate(MAX_ANNOTATIONS);
+        }
+
+        let count = |pick: fn(&ValidationResult) -> usize| results.iter().map(pick).sum();
+        let data = vec![
+            data("Commits", results.len()),
+            data("Errors", count(|r| r.errors.len())),
+            data("Warnings", count(|r| r.warnings.len())),
+        ];
+
+        Self {
+            report_id: REPORT_ID.to_string(),
+            commit: match results {
+                [single] => single.commit_sha.clone(),
+                _ => None,
+            },
+            report: Report {
+                title: "Commit messages".to_string(),
+                details,
+                report_type: "BUG".to_string(),
+                reporter: "ck".to_string(),
+                result: if failed { "FAILED" } else { "PASSED" }.to_string(),
+                data,
+            },
+            annotations,
+        }
+    }
+
+    /// Print the report as JSON to stdout.
+    pub fn print(&self) {
+        println!("{}", serde_json::to_string_pretty(self).unwrap_or_default());
+    }
+}
+
+fn annotation(
+    external_id: String,
+    path: Option<String>,
+    subject: &str,
+    issue: &ValidationIssue,
+) -> Annotation {
+    let mut details = format!("{}\n{}", subject, issue.message);
+    if let Some(ref suggestion) = issue.suggestion {
+        details.push_str(&format!("\n{}", suggestion));
+    }
+
+    Annotation {
+        external_id,
+        annotation_type: "CODE_SMELL".to_string(),
+        summary: format!("{}: {}", issue.code, issue.message),
+        details,
+        severity: if issue.is_error { "HIGH" } else { "MEDIUM" }.to_string(),
+        line: path.as_ref().map(|_| 1),
+        path,
+    }
+}
+
+fn data(title: &str, value: usize) -> ReportData {
+    ReportData {
+        title: title.to_string(),
+        data_type: "NUMBER".to_string(),
+        value,
+    }
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..7.min(sha.len())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let mut result = ValidationResult::new("feat: added export".to_string());
+        result.commit_sha = Some("1234567890".to_string());
+        result.warnings.push(ValidationIssue {
+            code: "subject-imperative".to_string(),
+            message: "Subject should use imperative mood".to_string(),
+            suggestion: Some("Use 'add'".to_string()),
+            is_error: false,
+            line: Some(1),
+            span: None,
+        });
+
+        let report =
+            BitbucketReport::from_results(&[result], false, |_| Some("src/export.rs".to_string()));
+        assert_eq!(report.commit.as_deref(), Some("1234567890"));
+        assert_eq!(report.report.result, "PASSED");
+        assert_eq!(report.report.details, "1 commit(s) pass");
+        assert_eq!(report.report.data[2].value, 1);
+
+        let annotation = &report.annotations[0];
+        assert_eq!(annotation.external_id, "1234567-1");
+        assert_eq!(annotation.severity, "MEDIUM");
+        assert_eq!(annotation.path.as_deref(), Some("src/export.rs"));
+        assert_eq!(
+            annotation.details,
+            "feat: added export\nSubject should use imperative mood\nUse 'add'"
+        );
+    }
+}
@@ -6,12 +6,13 @@
 use crate::commit::CommitMessage;
 use crate::config::CkConfig;
 
-use super::validator::ValidationIssue;
+use super::context::RuleContext;
+use super::validator::{Span, ValidationIssue};
 
 /// Trait for custom rules.
 pub trait Rule: std::fmt::Debug + Send + Sync {
-    /// Check the commit message and return an issue if validation fails.
-    fn check(&self, message: &CommitMessage, config: &CkConfig) -> Option<ValidationIssue>;
+    /// Check a commit and return an issue if validation fails.
+    fn check(&self, context: &RuleContext) -> Option<ValidationIssue>;
 
     /// Get the rule name.
     fn name(&self) -> &str;
@@ -25,8 +26,9 @@ impl Clone for Box<dyn Rule> {
     }
 }
 
-/// Apply all built-in rules to a commit message.
-pub fn apply_builtin_rules(message: &CommitMessage, config: &CkConfig) -> Vec<ValidationIssue> {
+/// Apply all built-in rules to a commit.
+pub fn apply_builtin_rules(context: &RuleContext) -> Vec<ValidationIssue> {
+    let (message, config) = (context.message, context.config);
     let mut issues = Vec::new();
 
     // Subject length rules
@@ -41,7 +43,7 @@ pub fn apply_builtin_rules(message: &CommitMessage, config: &CkConfig) -> Vec<Va
     if let Some(issue) = check_allowed_types(message, config) {
         issues.push(issue);
     }
-    if let Some(issue) = check_forbidden_types(message, config) {
+    if let Some(issue) = check_forbidden_types(message, config, context.branch.as_deref()) {
         issues.push(issue);
     }
 
@@ -52,9 +54,29 @@ pub fn apply_builtin_rules(message: &CommitMessage, config: &CkConfig) -> Vec<Va
     if let Some(issue) = check_allowed_scopes(message, config) {
         issues.push(issue);
     }
+    if let Some(issue) = check_scope_format(message, config) {
+        issues.push(issue);
+    }
+    if let Some(issue) = check_scope_length(message, config) {
+        issues.push(issue);
+    }
 
     // Body rules
-    if let Some(issue) = check_require_body(message, config) {
+    if let Some(issue) = check_require_body(message, config, context.branch.as_deref()) {
+        issues.push(issue);
+    }
+    if let Some(issue) = check_body_sections(message, config) {
+        issues.push(issue);
+    }
+
+    // Footer rules
+    if let Some(issue) = check_deprecated_footer(message) {
+        issues.push(issue);
+    }
+    if let Some(issue) = check_required_trailers(message, config) {
+        issues.push(issue);
+    }
+    if let Some(issue) = super::ticket::check_ticket(message, &config.rules.ticket) {
         issues.push(issue);
     }
 
@@ -68,10 +90,30 @@ pub fn apply_builtin_rules(message: &CommitMessage, config: &CkConfig) -> Vec<Va
     if let Some(issue) = check_subject_trailing_period(message) {
         issues.push(issue);
     }
+    if let Some(issue) =
+        super::language::check_language(message, &config.rules.language, context.history_language)
+    {
+        issues.push(issue);
+    }
 
     issues
 }
 
+/// Apply the rules that only look at the subject. They are cheap enough
+/// to run on every keystroke while the subject is typed.
+pub fn check_subject(message: &CommitMessage, config: &CkConfig) -> Vec<ValidationIssue> {
+    [
+        check_max_subject_length(message, config),
+        check_min_subject_length(message, config),
+        check_subject_case(message),
+        check_subject_trailing_period(message),
+        check_imperative_mood(message),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
 /// Check maximum subject length.
 fn check_max_subject_length(message: &CommitMessage, config: &CkConfig) -> Option<ValidationIssue> {
     let max = config.rules.max_subject_length;
@@ -84,6 +126,7 @@ fn check_max_subject_length(message: &CommitMessage, config: &CkConfig) -> Optio
             suggestion: Some(format!("Shorten the subject to {} characters or less", max)),
             is_error: true,
             line: Some(1),
+            span: subject_span(message, max..len),
         })
     } else {
         None
@@ -102,6 +145,7 @@ fn check_min_subject_length(message: &CommitMessage, config: &CkConfig) -> Optio
             suggestion: Some("Add more detail to the subject".to_string()),
             is_error: true,
             line: Some(1),
+            span: subject_span(message, 0..len),
         })
     } else {
         None
@@ -111,36 +155,47 @@ fn check_min_subject_length(message: &CommitMessage, config: &CkConfig) -> Optio
 /// Check if the commit type is allowed.
 fn check_allowed_types(message: &CommitMessage, config: &CkConfig) -> Option<ValidationIssue> {
     let type_str = message.commit_type.as_str();
+    let allowed = config.allowed_type_names();
 
-    if !config.rules.allowed_types.is_empty()
-        && !config.rules.allowed_types.iter().any(|t| t == type_str)
-    {
+    if !config.rules.allowed_types.is_empty() && !allowed.iter().any(|t| t == type_str) {
         Some(ValidationIssue {
             code: "type-not-allowed".to_string(),
             message: format!("Commit type '{}' is not allowed", type_str),
-            suggestion: Some(format!(
-                "Use one of: {}",
-                config.rules.allowed_types.join(", ")
-            )),
+            suggestion: Some(format!("Use one of: {}", allowed.join(", "))),
             is_error: true,
             line: Some(1),
+            span: Some(type_span(message)),
         })
     } else {
         None
     }
 }
 
-/// Check if the commit type is forbidden.
-fn check_forbidden_types(message: &CommitMessage, config: &CkConfig) -> Option<ValidationIssue> {
+/// Check if the commit type is forbidden, globally or on `branch`.
+fn check_forbidden_types(
+    message: &CommitMessage,
+    config: &CkConfig,
+    branch: Option<&str>,
+) -> Option<ValidationIssue> {
     let type_str = message.commit_type.as_str();
 
     if config.rules.forbidden_types.iter().any(|t| t == type_str) {
+        let on_branch = branch.filter(|branch| {
+            config
+                .rules
+                .branch_rules(branch)
+                .is_some_and(|rules| rules.forbid.iter().any(|t| t == type_str))
+        });
         Some(ValidationIssue {
             code: "type-forbidden".to_string(),
-            message: format!("Commit type '{}' is forbidden", type_str),
+            message: match on_branch {
+                Some(branch) => format!("Commit type '{}' is forbidden on {}", type_str, branch),
+                None => format!("Commit type '{}' is forbidden", type_str),
+            },
             suggestion: Some("Use a different commit type".to_string()),
             is_error: true,
             line: Some(1),
+            span: Some(type_span(message)),
         })
     } else {
         None
@@ -156,6 +211,7 @@ fn check_require_scope(message: &CommitMessage, config: &CkConfig) -> Option<Val
             suggestion: Some("Add a scope in parentheses: type(scope): subject".to_string()),
             is_error: true,
             line: Some(1),
+            span: Some(type_span(message)),
         })
     } else {
         None
@@ -177,74 +233,199 @@ fn check_allowed_scopes(message: &CommitMessage, config: &CkConfig) -> Option<Va
                 )),
                 is_error: true,
                 line: Some(1),
+                span: scope_span(message),
             });
         }
     }
     None
 }
 
-/// Check if body is required.
-fn check_require_body(message: &CommitMessage, config: &CkConfig) -> Option<ValidationIssue> {
+/// Check that the scope follows the configured case and pattern.
+fn check_scope_format(message: &CommitMessage, config: &CkConfig) -> Option<ValidationIssue> {
+    let scope = message.scope.as_deref()?;
+    let rules = &config.rules.scope;
+    let (problem, suggestion) = match (rules.case, rules.pattern.as_deref()) {
+        (Some(case), _) if !case.matches(scope) => (
+            format!("Scope '{}' is not {}", scope, case.as_str()),
+            format!("Use '{}'", case.apply(scope)),
+        ),
+        // An invalid pattern can never be satisfied
+        (_, Some(pattern)) if !regex::Regex::new(pattern).is_ok_and(|re| re.is_match(scope)) => (
+            format!("Scope '{}' does not match {}", scope, pattern),
+            "Use a scope that matches rules.scope.pattern".to_string(),
+        ),
+        _ => return None,
+    };
+
+    Some(ValidationIssue {
+        code: "scope-format".to_string(),
+        message: problem,
+        suggestion: Some(suggestion),
+        is_error: true,
+        line: Some(1),
+        span: scope_span(message),
+    })
+}
+
+/// Check that the scope is within the maximum length.
+fn check_scope_length(message: &CommitMessage, config: &CkConfig) -> Option<ValidationIssue> {
+    let scope = message.scope.as_deref()?;
+    let max = config.rules.scope.max_length?;
+    let length = scope.chars().count();
+    if length <= max {
+        return None;
+    }
+
+    Some(ValidationIssue {
+        code: "scope-max-length".to_string(),
+        message: format!("Scope is {} characters (max: {})", length, max),
+        suggestion: Some("Use a shorter scope, or map it with rules.scope.aliases".to_string()),
+        is_error: true,
+        line: Some(1),
+        span: scope_span(message),
+    })
+}
+
+/// Check if body is required, globally or on `branch`.
+fn check_require_body(
+    message: &CommitMessage,
+    config: &CkConfig,
+    branch: Option<&str>,
+) -> Option<ValidationIssue> {
     if config.rules.require_body && message.body.is_none() {
+        let on_branch = branch.filter(|branch| {
+            config
+                .rules
+                .branch_rules(branch)
+                .is_some_and(|rules| rules.require_body == Some(true))
+        });
         Some(ValidationIssue {
             code: "body-required".to_string(),
-            message: "Body is required but not provided".to_string(),
+            message: match on_branch {
+                Some(branch) => format!("Body is required on {}", branch),
+                None => "Body is required but not provided".to_string(),
+            },
             suggestion: Some("Add a body with more details about the change".to_string()),
             is_error: true,
             line: Some(1),
+            span: None,
         })
     } else {
         None
     }
 }
 
-/// Check if subject starts with imperative mood.
-fn check_imperative_mood(message: &CommitMessage) -> Option<ValidationIssue> {
-    let first_word = message.subject.split_whitespace().next()?;
-    let lower = first_word.to_lowercase();
-
-    // Common non-imperative patterns
-    let non_imperative = [
-        "added",
-        "adding",
-        "adds",
-        "fixed",
-        "fixing",
-        "fixes",
-        "updated",
-        "updating",
-        "updates",
-        "removed",
-        "removing",
-        "removes",
-        "changed",
-        "changing",
-        "changes",
-        "implemented",
-        "implementing",
-        "implements",
-        "created",
-        "creating",
-        "creates",
-    ];
-
-    if non_imperative.contains(&lower.as_str()) {
-        Some(ValidationIssue {
-            code: "subject-imperative".to_string(),
-            message: format!(
-                "Subject should use imperative mood (found '{}')",
-                first_word
-            ),
-            suggestion: Some(format!(
-                "Use imperative form like 'add' instead of '{}'",
-                first_word
-            )),
-            is_error: false, // Warning, not error
-            line: Some(1),
+/// Check that the body contains the sections required for the commit type.
+fn check_body_sections(message: &CommitMessage, config: &CkConfig) -> Option<ValidationIssue> {
+    let sections = config.rules.body_sections_for(message.commit_type.as_str());
+    if sections.is_empty() {
+        return None;
+    }
+
+    // Sections separated by blank lines may be parsed into the footer
+    let body = [message.body.as_deref(), message.footer.as_deref()]
+        .iter()
+        .flatten()
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let missing: Vec<&str> = sections
+        .iter()
+        .filter(|section| {
+            let pattern = section
+                .pattern
+                .clone()
+                .unwrap_or_else(|| format!("(?m)^{}", regex::escape(&section.header)));
+            // An invalid pattern can never be satisfied
+            !regex::Regex::new(&pattern)
+                .map(|re| re.is_match(&body))
+                .unwrap_or(false)
         })
-    } else {
-        None
+        .map(|section| section.header.as_str())
+        .collect();
+
+    if missing.is_empty() {
+        return None;
+    }
+
+    Some(ValidationIssue {
+        code: "body-section-missing".to_string(),
+        message: format!(
+            "Body is missing required sections for '{}': {}",
+            message.commit_type.as_str(),
+            missing.join(", ")
+        ),
+        suggestion: Some(format!("Add the sections: {}", missing.join(", "))),
+        is_error: true,
+        line: Some(3),
+        span: None,
+    })
+}
+
+/// Check that `Deprecated:` footers are well-formed.
+fn check_deprecated_footer(message: &CommitMessage) -> Option<ValidationIssue> {
+    let problem = crate::commit::deprecation::check_footer(&message.trailer_text())
+        .into_iter()
+        .next()?;
+
+    Some(ValidationIssue {
+        code: "footer-deprecated".to_string(),
+        message: problem,
+        suggestion: Some("Write the footer as 'Deprecated: <item>, use <replacement>'".to_string()),
+        is_error: true,
+        line: None,
+        span: None,
+    })
+}
+
+/// Check that the footer has every required trailer.
+fn check_required_trailers(message: &CommitMessage, config: &CkConfig) -> Option<ValidationIssue> {
+    let trailers = message.trailers();
+    let missing: Vec<&str> = config
+        .rules
+        .required_trailers
+        .iter()
+        .filter(|token| !trailers.iter().any(|t| t.has_token(token)))
+        .map(String::as_str)
+        .collect();
+    if missing.is_empty() {
+        return None;
     }
+
+    Some(ValidationIssue {
+        code: "footer-trailer-missing".to_string(),
+        message: format!(
+            "Footer is missing required trailers: {}",
+            missing.join(", ")
+        ),
+        suggestion: Some(format!(
+            "Add them with --trailer, e.g. --trailer '{}=...'",
+            missing[0]
+        )),
+        is_error: true,
+        line: None,
+        span: None,
+    })
+}
+
+/// Check if subject starts with imperative mood.
+fn check_imperative_mood(message: &CommitMessage) -> Option<ValidationIssue> {
+    let (first_word, imperative) = super::mood::subject_imperative(&message.subject)?;
+
+    Some(ValidationIssue {
+        code: "subject-imperative".to_string(),
+        message: format!(
+            "Subject should use imperative mood (found '{}')",
+            first_word
+        ),
+        suggestion: Some(format!(
+            "Use imperative form like '{}' instead of '{}'",
+            imperative, first_word
+        )),
+        is_error: false, // Warning, not error
+        line: Some(1),
+        span: subject_span(message, 0..first_word.len()),
+    })
 }
 
 /// Check if subject starts with lowercase.
@@ -258,6 +439,7 @@ fn check_subject_case(message: &CommitMessage) -> Option<ValidationIssue> {
             suggestion: Some("Start the subject with a lowercase letter".to_string()),
             is_error: false, // Warning
             line: Some(1),
+            span: subject_span(message, 0..first_char.len_utf8()),
         })
     } else {
         None
@@ -273,12 +455,40 @@ fn check_subject_trailing_period(message: &CommitMessage) -> Option<ValidationIs
             suggestion: Some("Remove the trailing period".to_string()),
             is_error: false, // Warning
             line: Some(1),
+            span: subject_span(message, message.subject.len() - 1..message.subject.len()),
         })
     } else {
         None
     }
 }
 
+/// Span of the type in the header.
+fn type_span(message: &CommitMessage) -> Span {
+    Span::in_text(&message.header(), 0, message.commit_type.as_str().len())
+}
+
+/// Span of the scope in the header, without the parentheses.
+fn scope_span(message: &CommitMessage) -> Option<Span> {
+    let start = message.commit_type.as_str().len() + 1;
+    let scope = message.scope.as_ref()?;
+    Some(Span::in_text(&message.header(), start, start + scope.len()))
+}
+
+/// Span of the subject bytes in `range` within the header. A range
+/// starting inside a character is moved back to its start.
+fn subject_span(message: &CommitMessage, range: std::ops::Range<usize>) -> Option<Span> {
+    let subject = &message.subject;
+    let start = (0..=range.start.min(subject.len()))
+        .rev()
+        .find(|&i| subject.is_char_boundary(i))?;
+    let offset = message.header_len() - subject.len();
+    Some(Span::in_text(
+        &message.header(),
+        offset + start,
+        offset + range.end.min(subject.len()),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -288,6 +498,7 @@ mod tests {
         CommitMessage {
             commit_type: CommitType::Feat,
             scope: None,
+            emoji: None,
             subject: subject.to_string(),
             body: None,
             footer: None,
@@ -322,6 +533,17 @@ mod tests {
         let message = make_message("add new feature");
         let issue = check_imperative_mood(&message);
         assert!(issue.is_none());
+
+        let message = make_message("bumping serde to 1.0.200");
+        let issue = check_imperative_mood(&message).unwrap();
+        assert_eq!(
+            issue.suggestion.as_deref(),
+            Some("Use imperative form like 'bump' instead of 'bumping'")
+        );
+
+        for subject in ["fixed broken link in readme", "updated generated files"] {
+            assert!(check_imperative_mood(&make_message(subject)).is_some());
+        }
     }
 
     #[test]
@@ -335,10 +557,142 @@ mod tests {
         assert!(issue.is_none());
     }
 
+    #[test]
+    fn test_scope_format_and_length() {
+        let mut config = CkConfig::default();
+        let message = CommitMessage::new(CommitType::Feat, "add avatars").with_scope("UserProfile");
+        assert!(check_scope_format(&message, &config).is_none());
+
+        config.rules.scope.case = Some(crate::config::ScopeCase::Kebab);
+        let issue = check_scope_format(&message, &config).unwrap();
+        assert_eq!(issue.code, "scope-format");
+        assert_eq!(issue.suggestion.as_deref(), Some("Use 'user-profile'"));
+
+        config.rules.scope.case = None;
+        config.rules.scope.pattern = Some("^[a-z]+$".to_string());
+        assert!(check_scope_format(&message, &config).is_some());
+        let message = message.with_scope("profile");
+        assert!(check_scope_format(&message, &config).is_none());
+
+        config.rules.scope.max_length = Some(5);
+        assert_eq!(
+            check_scope_length(&message, &config).unwrap().code,
+            "scope-max-length"
+        );
+    }
+
+    #[test]
+    fn test_body_sections() {
+        let mut config = CkConfig::default();
+        config.rules.body_sections.insert(
+            "feat".to_string(),
+            vec![
+                crate::config::BodySectionConfig {
+                    header: "Testing:".to_string(),
+                    pattern: None,
+                },
+                crate::config::BodySectionConfig {
+                    header: "Before:".to_string(),
+                    pattern: Some(r"(?m)^Before:.*\d".to_string()),
+                },
+            ],
+        );
+
+        let mut message = make_message("add new feature");
+        let issue = check_body_sections(&message, &config).unwrap();
+        assert!(issue.message.contains("Testing:, Before:"));
+
+        message.body = Some("Testing: unit tests".to_string());
+        message.footer = Some("Before: 120ms".to_string());
+        assert!(check_body_sections(&message, &config).is_none());
+        message.footer = None;
+
+        message.body = Some("Testing: unit tests\nBefore: slow".to_string());
+        let issue = check_body_sections(&message, &config).unwrap();
+        assert!(issue.message.ends_with("Before:"));
+    }
+
     #[test]
     fn test_trailing_period() {
         let message = make_message("add new feature.");
         let issue = check_subject_trailing_period(&message);
         assert!(issue.is_some());
     }
+
+    #[test]
+    fn test_check_subject() {
+        let config = CkConfig::default();
+        let codes: Vec<String> = check_subject(&make_message("Add new feature."), &config)
+            .into_iter()
+            .map(|issue| issue.code)
+            .collect();
+        assert_eq!(codes, vec!["subject-case", "subject-trailing-period"]);
+        assert!(check_subject(&make_message("add new feature"), &config).is_empty());
+    }
+
+    #[test]
+    fn test_custom_type_allowed() {
+        let mut config = CkConfig::default();
+        config.types = vec![crate::config::TypeDefinition {
+            name: "deps".to_string(),
+            description: "Dependency updates".to_string(),
+            emoji: None,
+        }];
+
+        let message = CommitMessage::parse_with("deps: bump serde to 1.0.200", &config).unwrap();
+        assert_eq!(message.commit_type.as_str(), "deps");
+        assert!(check_allowed_types(&message, &config).is_none());
+
+        config.types.clear();
+        let issue = check_allowed_types(&message, &config).unwrap();
+        assert_eq!(issue.code, "type-not-allowed");
+        assert!(CommitMessage::parse_with("deps: bump serde", &config).is_err());
+    }
+
+    #[test]
+    fn test_required_trailers() {
+        let mut config = CkConfig::default();
+        config.rules.required_trailers = vec!["Signed-off-by".to_string(), "Refs".to_string()];
+
+        let message = make_message("add parsing").with_footer("signed-off-by: Ada <a@b.c>");
+        let issue = check_required_trailers(&message, &config).unwrap();
+        assert_eq!(issue.code, "footer-trailer-missing");
+        assert_eq!(issue.message, "Footer is missing required trailers: Refs");
+
+        let message = message.with_footer("Signed-off-by: Ada <a@b.c>\nRefs: #1");
+        assert!(check_required_trailers(&message, &config).is_none());
+    }
+
+    #[test]
+    fn test_issue_spans() {
+        let config = CkConfig::default();
+        let message = make_message("Added parsing.").with_scope("api");
+        assert_eq!(message.header(), "feat(api): Added parsing.");
+
+        let period = check_subject_trailing_period(&message)
+            .unwrap()
+            .span
+            .unwrap();
+        assert_eq!((period.line, period.column, period.end_column), (1, 25, 26));
+        assert_eq!(&message.format()[period.start..period.end], ".");
+
+        let mood = check_imperative_mood(&message).unwrap().span.unwrap();
+        assert_eq!(&message.format()[mood.start..mood.end], "Added");
+
+        let long = make_message(&format!("{}é tail", "a".repeat(71)));
+        let over = check_max_subject_length(&long, &config)
+            .unwrap()
+            .span
+            .unwrap();
+        assert_eq!(&long.format()[over.start..over.end], "é tail");
+        assert_eq!(over.column, "feat: ".len() + 72);
+
+        let mut restricted = config.clone();
+        restricted.rules.scope.allowed = vec!["cli".to_string()];
+        let scope = check_allowed_scopes(&message, &restricted)
+            .unwrap()
+            .span
+            .unwrap();
+        assert_eq!(&message.format()[scope.start..scope.end], "api");
+    }
 }
@@ -0,0 +1,139 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Range check verdicts cached in git notes.
+//!
+//! After a range check with `--cache`, each commit gets a note under
+//! `refs/notes/ck-checks` recording the ck version, the hash of the
+//! configuration it was checked under, the branch, the language of the
+//! history when `rules.language.require = "history"`, and the verdict:
+//!
+//! ```text
+//! ck: 1.0.0
+//! config: sha256:...
+//! branch: main
+//! verdict: pass
+//! ```
+//!
+//! Later checks skip commits that passed under the same version,
+//! configuration, branch and history language, so a change to any of them
+//! checks every commit again. Only passes are reused, since failures have
+//! to be reported with their issues.
+
+use git2::Oid;
+
+use crate::config::CkConfig;
+use crate::git::notes::{read_note, write_note};
+use crate::git::Repository;
+use crate::version;
+
+use super::validator::ValidationResult;
+
+/// Notes ref holding cached verdicts.
+pub const CACHE_REF: &str = "refs/notes/ck-checks";
+
+/// Verdicts of range checks under one configuration.
+#[derive(Debug, Clone)]
+pub struct CheckCache {
+    config_hash: String,
+    branch: Option<String>,
+    history_language: Option<String>,
+}
+
+impl CheckCache {
+    /// A cache for checks under `config`, with branch rules already
+    /// applied, on `branch` and with `history_language` as the language of
+    /// the history when rules need it.
+    pub fn new(config: &CkConfig, branch: Option<&str>, history_language: Option<&str>) -> Self {
+        Self {
+            config_hash: config.hash(),
+            branch: branch.map(str::to_string),
+            history_language: history_language.map(str::to_string),
+        }
+    }
+
+    /// Whether `oid` passed a check under the same version, configuration,
+    /// branch and history language.
+    pub fn passed(&self, repo: &Repository, oid: Oid) -> bool {
+        read_note(repo, CACHE_REF, oid).is_some_and(|note| {
+            let field = |name: &str| {
+                note.lines()
+                    .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+            };
+            field("ck") == Some(version::VERSION)
+                && field("config") == Some(self.config_hash.as_str())
+                && field("branch") == self.branch.as_deref()
+                && field("history-language") == self.history_language.as_deref()
+                && field("verdict") == Some("pass")
+        })
+    }
+
+    /// Record the verdict for `oid`.
+    ///
+    /// Only clean results are recorded as passes: with warnings a later
+    /// strict check could still fail, and suppressions must keep showing.
+    pub fn record(&self, repo: &Repository, oid: Oid, result: &ValidationResult) {
+        let clean =
+            result.errors.is_empty() && result.warnings.is_empty() && result.suppressed.is_empty();
+        let verdict = if clean { "pass" } else { "fail" };
+        let mut note = format!("ck: {}\nconfig: {}\n", version::VERSION, self.config_hash);
+        if let Some(ref branch) = self.branch {
+            note.push_str(&format!("branch: {}\n", branch));
+        }
+        if let Some(ref language) = self.history_language {
+            note.push_str(&format!("history-language: {}\n", language));
+        }
+        note.push_str(&format!("verdict: {}\n", verdict));
+        if let Err(e) = write_note(repo, CACHE_REF, oid, &note) {
+            tracing::debug!("Could not cache the verdict for {}: {}", oid, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixtureRepo;
+
+    fn repo() -> (FixtureRepo, Repository) {
+        let fixture = FixtureRepo::new();
+        fixture.commit("feat: initial commit");
+        let repo = Repository::open(fixture.path()).unwrap();
+        (fixture, repo)
+    }
+
+    #[test]
+    fn test_cache_is_keyed_by_config() {
+        let (_fixture, repo) = repo();
+        let oid = repo.head_commit().unwrap().id();
+        let config = CkConfig::default();
+        let cache = CheckCache::new(&config, Some("main"), None);
+        assert!(!cache.passed(&repo, oid));
+
+        cache.record(
+            &repo,
+            oid,
+            &ValidationResult::new("feat: initial commit".into()),
+        );
+        assert!(cache.passed(&repo, oid));
+
+        let mut stricter = config;
+        stricter.rules.require_body = true;
+        assert!(!CheckCache::new(&stricter, Some("main"), None).passed(&repo, oid));
+    }
+
+    #[test]
+    fn test_cache_is_keyed_by_branch_and_history_language() {
+        let (_fixture, repo) = repo();
+        let oid = repo.head_commit().unwrap().id();
+        let config = CkConfig::default();
+        let result = ValidationResult::new("feat: initial commit".into());
+        CheckCache::new(&config, Some("main"), Some("eng")).record(&repo, oid, &result);
+
+        assert!(CheckCache::new(&config, Some("main"), Some("eng")).passed(&repo, oid));
+        assert!(!CheckCache::new(&config, Some("release"), Some("eng")).passed(&repo, oid));
+        assert!(!CheckCache::new(&config, None, Some("eng")).passed(&repo, oid));
+        assert!(!CheckCache::new(&config, Some("main"), Some("deu")).passed(&repo, oid));
+        assert!(!CheckCache::new(&config, Some("main"), None).passed(&repo, oid));
+    }
+}
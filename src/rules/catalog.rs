@@ -0,0 +1,432 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Catalog of active rules and their effective settings.
+
+use crate::cli::args::OutputFormat;
+use crate::config::{CkConfig, ConfigSources};
+use console::style;
+use serde::Serialize;
+
+/// Static description of a built-in rule.
+struct BuiltinRule {
+    code: &'static str,
+    description: &'static str,
+    is_error: bool,
+    /// Configuration keys the rule reads, as dotted paths.
+    keys: &'static [&'static str],
+}
+
+/// All built-in rules, in evaluation order.
+const BUILTIN_RULES: &[BuiltinRule] = &[
+    BuiltinRule {
+        code: "parse-error",
+        description: "Message must follow the conventional commit format",
+        is_error: true,
+        keys: &[],
+    },
+    BuiltinRule {
+        code: "subject-max-length",
+        description: "Subject must not exceed the maximum length",
+        is_error: true,
+        keys: &["rules.max_subject_length"],
+    },
+    BuiltinRule {
+        code: "subject-min-length",
+        description: "Subject must reach the minimum length",
+        is_error: true,
+        keys: &["rules.min_subject_length"],
+    },
+    BuiltinRule {
+        code: "type-not-allowed",
+        description: "Commit type must be in the allowed list",
+        is_error: true,
+        keys: &["rules.allowed_types"],
+    },
+    BuiltinRule {
+        code: "type-forbidden",
+        description: "Commit type must not be in the forbidden list",
+        is_error: true,
+        keys: &["rules.forbidden_types"],
+    },
+    BuiltinRule {
+        code: "scope-required",
+        description: "A scope must be provided",
+        is_error: true,
+        keys: &["rules.require_scope"],
+    },
+    BuiltinRule {
+        code: "scope-not-allowed",
+        description: "Scope must be in the allowed list",
+        is_error: true,
+        keys: &["rules.scope.allowed"],
+    },
+    BuiltinRule {
+        code: "scope-format",
+        description: "Scope must follow the configured case and pattern",
+        is_error: true,
+        keys: &["rules.scope.case", "rules.scope.pattern"],
+    },
+    BuiltinRule {
+        code: "scope-max-length",
+        description: "Scope must not exceed the maximum length",
+        is_error: true,
+        keys: &["rules.scope.max_length"],
+    },
+    BuiltinRule {
+        code: "body-required",
+        description: "A body must be provided",
+        is_error: true,
+        keys: &["rules.require_body"],
+    },
+    BuiltinRule {
+        code: "body-section-missing",
+        description: "Body must contain the sections required for the type",
+        is_error: true,
+        keys: &["rules.body_sections"],
+    },
+    BuiltinRule {
+        code: "template-section-empty",
+        description: "Sections of an enforced template must be filled in",
+        is_error: true,
+        keys: &["templates"],
+    },
+    BuiltinRule {
+        code: "footer-deprecated",
+        description: "Deprecated footers must name what is deprecated",
+        is_error: true,
+        keys: &[],
+    },
+    BuiltinRule {
+        code: "footer-trailer-missing",
+        description: "Footer must have the required trailers",
+        is_error: true,
+        keys: &["rules.required_trailers"],
+    },
+    BuiltinRule {
+        code: "path-scope",
+        description: "Changes under a path must use its scope",
+        is_error: true,
+        keys: &["rules.paths"],
+    },
+    BuiltinRule {
+        code: "path-body-required",
+        description: "Changes under a path must have a body when it requires one",
+        is_error: true,
+        keys: &["rules.paths"],
+    },
+    BuiltinRule {
+        code: "suppression-invalid",
+        description: "Ck-Disable footers must be justified and name suppressible rules",
+        is_error: true,
+        keys: &["rules.suppressible"],
+    },
+    BuiltinRule {
+        code: "ticket-missing",
+        description: "Message must reference a ticket",
+        is_error: true,
+        keys: &["rules.ticket.required", "rules.ticket.pattern"],
+    },
+    BuiltinRule {
+        code: "subject-language",
+        description: "Subject must be written in the required language",
+        is_error: true,
+        keys: &["rules.language.require", "rules.language.min_words"],
+    },
+    BuiltinRule {
+        code: "merge-default-message",
+        description: "Merge commits must not use git's generated subject",
+        is_error: true,
+        keys: &["rules.merge.allow_default_message"],
+    },
+    BuiltinRule {
+        code: "merge-foxtrot",
+        description: "Merge commits must keep the previous branch tip as first parent",
+        is_error: true,
+        keys: &["rules.merge.forbid_foxtrot"],
+    },
+    BuiltinRule {
+        code: "path-type",
+        description: "Changes confined to a path should use its type",
+        is_error: false,
+        keys: &["rules.paths"],
+    },
+    BuiltinRule {
+        code: "subject-imperative",
+        description: "Subject should use imperative mood",
+        is_error: false,
+        keys: &[],
+    },
+    BuiltinRule {
+        code: "subject-case",
+        description: "Subject should start with lowercase",
+        is_error: false,
+        keys: &[],
+    },
+    BuiltinRule {
+        code: "subject-trailing-period",
+        description: "Subject should not end with a period",
+        is_error: false,
+        keys: &[],
+    },
+];
+
+/// Effective severity of a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Violations fail validation.
+    Error,
+    /// Violations are reported but do not fail validation.
+    Warning,
+    /// The rule is disabled by the configuration.
+    Off,
+}
+
+impl Severity {
+    /// Get the string representation of the severity.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Off => "off",
+        }
+    }
+}
+
+/// Where a rule is implemented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleKind {
+    /// Shipped with ck.
+    Builtin,
+    /// Registered with the rule engine at runtime.
+    Custom,
+    /// Written as a CEL expression in the configuration.
+    Expression,
+    /// Provided by a WASM plugin.
+    Plugin,
+}
+
+/// An entry in the rule listing.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleEntry {
+    /// Rule code as reported in validation issues.
+    pub code: String,
+    /// Where the rule is implemented.
+    pub kind: RuleKind,
+    /// What the rule checks.
+    pub description: String,
+    /// Effective severity under the current configuration.
+    pub severity: Severity,
+    /// Configuration values the rule reads, keyed by dotted path.
+    pub config: serde_json::Map<String, serde_json::Value>,
+    /// Configuration layer that set the rule's settings.
+    pub source: String,
+}
+
+/// List all rules with their effective settings, sorted by kind and code.
+pub fn list_rules(
+    config: &CkConfig,
+    sources: &ConfigSources,
+    custom_rules: &[&str],
+) -> Vec<RuleEntry> {
+    let config_json = serde_json::to_value(config).unwrap_or_default();
+    let mut entries = Vec::new();
+
+    for rule in BUILTIN_RULES {
+        let severity = if !builtin_enabled(rule.code, config) {
+            Severity::Off
+        } else if rule.is_error {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+
+        let settings = rule
+            .keys
+            .iter()
+            .map(|key| {
+                let value = key
+                    .split('.')
+                    .try_fold(&config_json, |v, part| v.get(part))
+                    .cloned()
+                    .unwrap_or_default();
+                (key.to_string(), value)
+            })
+            .collect();
+
+        let source = rule
+            .keys
+            .iter()
+            .map(|key| sources.source_of(key))
+            .find(|source| *source != "default")
+            .unwrap_or("default");
+
+        entries.push(RuleEntry {
+            code: rule.code.to_string(),
+            kind: RuleKind::Builtin,
+            description: rule.description.to_string(),
+            severity,
+            config: settings,
+            source: source.to_string(),
+        });
+    }
+
+    for name in custom_rules {
+        entries.push(RuleEntry {
+            code: name.to_string(),
+            kind: RuleKind::Custom,
+            description: String::new(),
+            severity: Severity::Error,
+            config: serde_json::Map::new(),
+            source: "engine".to_string(),
+        });
+    }
+
+    for (name, rule) in &config.rules.expressions {
+        let mut settings = serde_json::Map::new();
+        settings.insert(
+            format!("rules.expressions.{}.expr", name),
+            rule.expr.clone().into(),
+        );
+        entries.push(RuleEntry {
+            code: format!("{}{}", super::expression::CODE_PREFIX, name),
+            kind: RuleKind::Expression,
+            description: rule.message.clone().unwrap_or_default(),
+            severity: if rule.warning {
+                Severity::Warning
+            } else {
+                Severity::Error
+            },
+            config: settings,
+            source: sources.source_of("rules.expressions").to_string(),
+        });
+    }
+
+    if config.plugins.enabled {
+        for name in &config.plugins.enabled_plugins {
+            entries.push(RuleEntry {
+                code: format!("{}{}", crate::plugins::CODE_PREFIX, name),
+                kind: RuleKind::Plugin,
+                description: format!("Validation provided by the '{}' plugin", name),
+                severity: Severity::Error,
+                config: serde_json::Map::new(),
+                source: sources.source_of("plugins.enabled_plugins").to_string(),
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.code.cmp(&b.code)));
+    entries
+}
+
+/// Check whether a built-in rule can report anything under a configuration.
+fn builtin_enabled(code: &str, config: &CkConfig) -> bool {
+    match code {
+        "type-not-allowed" => !config.rules.allowed_types.is_empty(),
+        "type-forbidden" => !config.rules.forbidden_types.is_empty(),
+        "scope-required" => config.rules.require_scope,
+        "scope-not-allowed" => !config.rules.scope.allowed.is_empty(),
+        "scope-format" => config.rules.scope.case.is_some() || config.rules.scope.pattern.is_some(),
+        "scope-max-length" => config.rules.scope.max_length.is_some(),
+        "body-required" => config.rules.require_body,
+        "body-section-missing" => !config.rules.body_sections.is_empty(),
+        "template-section-empty" => config.templates.values().any(|t| t.enforce),
+        "footer-trailer-missing" => !config.rules.required_trailers.is_empty(),
+        "ticket-missing" => config.rules.ticket.required,
+        "subject-language" => config.rules.language.require.is_some(),
+        "path-type" | "path-scope" | "path-body-required" => !config.rules.paths.is_empty(),
+        _ => true,
+    }
+}
+
+/// Print a rule listing to stdout.
+pub fn print_rules(entries: &[RuleEntry], format: Option<OutputFormat>) {
+    match format {
+        Some(OutputFormat::Json) => println!(
+            "{}",
+            serde_json::to_string_pretty(entries).unwrap_or_default()
+        ),
+        _ => {
+            for entry in entries {
+                let severity = match entry.severity {
+                    Severity::Error => style(entry.severity.as_str()).red(),
+                    Severity::Warning => style(entry.severity.as_str()).yellow(),
+                    Severity::Off => style(entry.severity.as_str()).dim(),
+                };
+                println!(
+                    "{:<7} {:<24} {} {}",
+                    severity,
+                    entry.code,
+                    entry.description,
+                    style(format!("[{}]", entry.source)).dim()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_default_rules() {
+        let entries = list_rules(&CkConfig::default(), &ConfigSources::default(), &[]);
+        assert_eq!(entries.len(), BUILTIN_RULES.len());
+
+        let scope = entries.iter().find(|e| e.code == "scope-required").unwrap();
+        assert_eq!(scope.severity, Severity::Off);
+        assert_eq!(scope.source, "default");
+        assert_eq!(scope.config["rules.require_scope"], false);
+
+        let codes: Vec<&str> = entries.iter().map(|e| e.code.as_str()).collect();
+        let mut sorted = codes.clone();
+        sorted.sort();
+        assert_eq!(codes, sorted);
+    }
+
+    #[test]
+    fn test_emitted_codes_are_listed() {
+        // Issues are raised with a literal code, directly or through a helper
+        let pattern = regex::Regex::new(r#"(?:code: |issue\(\s*)"([a-z]+(?:-[a-z]+)+)""#).unwrap();
+        let sources = [
+            include_str!("builtin.rs"),
+            include_str!("engine.rs"),
+            include_str!("language.rs"),
+            include_str!("merge.rs"),
+            include_str!("paths.rs"),
+            include_str!("ticket.rs"),
+            include_str!("../commit/builder.rs"),
+        ];
+        let mut emitted: Vec<&str> = sources
+            .iter()
+            .filter_map(|source| source.split("#[cfg(test)]").next())
+            .flat_map(|source| pattern.captures_iter(source))
+            .filter_map(|captures| captures.get(1))
+            .map(|code| code.as_str())
+            .collect();
+        emitted.push(super::super::suppress::INVALID_CODE);
+        assert!(emitted.len() > 20);
+
+        for code in emitted {
+            assert!(
+                BUILTIN_RULES.iter().any(|rule| rule.code == code),
+                "{} is missing from the catalog",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_list_custom_and_plugin_rules() {
+        let mut config = CkConfig::default();
+        config.plugins.enabled = true;
+        config.plugins.enabled_plugins = vec!["jira".to_string()];
+
+        let entries = list_rules(&config, &ConfigSources::default(), &["no-todo"]);
+        assert_eq!(entries[entries.len() - 2].kind, RuleKind::Custom);
+        assert_eq!(entries.last().unwrap().code, "plugin:jira");
+    }
+}
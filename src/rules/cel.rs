@@ -0,0 +1,791 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! A small interpreter for a subset of CEL (Common Expression Language).
+//!
+//! Supported are literals (`null`, booleans, integers, doubles, strings,
+//! lists and maps), field access and indexing, the usual arithmetic,
+//! comparison and logical operators, `in`, the conditional `? :`, the
+//! functions `size`, `int`, `double`, `string`, `has`, `contains`,
+//! `startsWith`, `endsWith`, `matches`, `lowerAscii` and `upperAscii`, and
+//! the macros `all`, `exists`, `exists_one`, `filter` and `map`. `has()`
+//! treats a `null` field as unset.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A CEL value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// `null`.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A signed integer.
+    Int(i64),
+    /// A floating point number.
+    Double(f64),
+    /// A string.
+    String(String),
+    /// A list.
+    List(Vec<Value>),
+    /// A map with string keys.
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Double(_) => "double",
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+        }
+    }
+}
+
+impl From<&serde_json::Value> for Value {
+    fn from(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::Int(i),
+                None => Value::Double(n.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(s) => Value::String(s.clone()),
+            serde_json::Value::Array(items) => Value::List(items.iter().map(Value::from).collect()),
+            serde_json::Value::Object(fields) => Value::Map(
+                fields
+                    .iter()
+                    .map(|(k, v)| (k.clone(), Value::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// A parse or evaluation error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CelError(pub String);
+
+impl fmt::Display for CelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+type CelResult<T> = std::result::Result<T, CelError>;
+
+fn error<T>(message: impl Into<String>) -> CelResult<T> {
+    Err(CelError(message.into()))
+}
+
+/// A parsed expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// A literal value.
+    Literal(Value),
+    /// A variable.
+    Ident(String),
+    /// Field access, `a.b`.
+    Member(Box<Expr>, String),
+    /// Indexing, `a[b]`.
+    Index(Box<Expr>, Box<Expr>),
+    /// A function call, with a receiver for `a.f(...)`.
+    Call {
+        /// Receiver of a method-style call.
+        target: Option<Box<Expr>>,
+        /// Function name.
+        name: String,
+        /// Arguments.
+        args: Vec<Expr>,
+    },
+    /// A unary operator.
+    Unary(&'static str, Box<Expr>),
+    /// A binary operator.
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    /// `cond ? a : b`.
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// A list literal.
+    List(Vec<Expr>),
+    /// A map literal.
+    Map(Vec<(Expr, Expr)>),
+}
+
+/// Parse an expression.
+pub fn parse(source: &str) -> CelResult<Expr> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.expr()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => error(format!("unexpected {:?}", token)),
+    }
+}
+
+/// Parse and evaluate an expression with the given variables.
+pub fn eval(source: &str, vars: &BTreeMap<String, Value>) -> CelResult<Value> {
+    Evaluator {
+        scopes: vec![vars.clone()],
+    }
+    .eval(&parse(source)?)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i64),
+    Double(f64),
+    Str(String),
+    Op(&'static str),
+}
+
+const OPERATORS: &[&str] = &[
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "+", "-", "*", "/", "%", "?", ":", ".", ",",
+    "(", ")", "[", "]", "{", "}",
+];
+
+fn tokenize(source: &str) -> CelResult<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push(if text.contains('.') {
+                Token::Double(
+                    text.parse()
+                        .map_err(|_| CelError(format!("bad number '{}'", text)))?,
+                )
+            } else {
+                Token::Int(
+                    text.parse()
+                        .map_err(|_| CelError(format!("bad number '{}'", text)))?,
+                )
+            });
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '"' || c == '\'' {
+            let (text, end) = read_string(&chars, i)?;
+            tokens.push(Token::Str(text));
+            i = end;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            match OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+                Some(op) => {
+                    tokens.push(Token::Op(op));
+                    i += op.len();
+                }
+                None => return error(format!("unexpected character '{}'", c)),
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Read a quoted string starting at `start`, returning it and the index after it.
+fn read_string(chars: &[char], start: usize) -> CelResult<(String, usize)> {
+    let quote = chars[start];
+    let mut text = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            c if c == quote => return Ok((text, i + 1)),
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                text.push(match chars[i] {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    other => other,
+                });
+            }
+            c => text.push(c),
+        }
+        i += 1;
+    }
+    error("unterminated string")
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> CelResult<()> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            error(format!("expected '{}'", op))
+        }
+    }
+
+    fn expr(&mut self) -> CelResult<Expr> {
+        let cond = self.binary(0)?;
+        if self.eat("?") {
+            let then = self.expr()?;
+            self.expect(":")?;
+            let otherwise = self.expr()?;
+            return Ok(Expr::Conditional(
+                Box::new(cond),
+                Box::new(then),
+                Box::new(otherwise),
+            ));
+        }
+        Ok(cond)
+    }
+
+    /// Binary operators by precedence level, loosest first.
+    fn binary(&mut self, level: usize) -> CelResult<Expr> {
+        const LEVELS: &[&[&str]] = &[
+            &["||"],
+            &["&&"],
+            &["==", "!=", "<", "<=", ">", ">=", "in"],
+            &["+", "-"],
+            &["*", "/", "%"],
+        ];
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut left = self.binary(level + 1)?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op(op)) if LEVELS[level].contains(op) => *op,
+                Some(Token::Ident(word)) if word == "in" && LEVELS[level].contains(&"in") => "in",
+                _ => break,
+            };
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> CelResult<Expr> {
+        if self.eat("!") {
+            return Ok(Expr::Unary("!", Box::new(self.unary()?)));
+        }
+        if self.eat("-") {
+            return Ok(Expr::Unary("-", Box::new(self.unary()?)));
+        }
+        self.member()
+    }
+
+    fn member(&mut self) -> CelResult<Expr> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat(".") {
+                let name = match self.next() {
+                    Some(Token::Ident(name)) => name,
+                    _ => return error("expected a field name after '.'"),
+                };
+                if self.eat("(") {
+                    let args = self.args(")")?;
+                    expr = Expr::Call {
+                        target: Some(Box::new(expr)),
+                        name,
+                        args,
+                    };
+                } else {
+                    expr = Expr::Member(Box::new(expr), name);
+                }
+            } else if self.eat("[") {
+                let index = self.expr()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> CelResult<Expr> {
+        match self.next() {
+            Some(Token::Int(i)) => Ok(Expr::Literal(Value::Int(i))),
+            Some(Token::Double(d)) => Ok(Expr::Literal(Value::Double(d))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                _ if self.eat("(") => Ok(Expr::Call {
+                    target: None,
+                    name,
+                    args: self.args(")")?,
+                }),
+                _ => Ok(Expr::Ident(name)),
+            },
+            Some(Token::Op("(")) => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Op("[")) => Ok(Expr::List(self.args("]")?)),
+            Some(Token::Op("{")) => {
+                let mut entries = Vec::new();
+                while !self.eat("}") {
+                    let key = self.expr()?;
+                    self.expect(":")?;
+                    entries.push((key, self.expr()?));
+                    if !self.eat(",") {
+                        self.expect("}")?;
+                        break;
+                    }
+                }
+                Ok(Expr::Map(entries))
+            }
+            Some(token) => error(format!("unexpected {:?}", token)),
+            None => error("unexpected end of expression"),
+        }
+    }
+
+    /// Comma-separated expressions up to the closing token.
+    fn args(&mut self, close: &str) -> CelResult<Vec<Expr>> {
+        let mut args = Vec::new();
+        if self.eat(close) {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.expr()?);
+            if self.eat(close) {
+                return Ok(args);
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+struct Evaluator {
+    scopes: Vec<BTreeMap<String, Value>>,
+}
+
+impl Evaluator {
+    fn eval(&mut self, expr: &Expr) -> CelResult<Value> {
+        match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Ident(name) => match self.scopes.iter().rev().find_map(|s| s.get(name)) {
+                Some(value) => Ok(value.clone()),
+                None => error(format!("undeclared reference to '{}'", name)),
+            },
+            Expr::Member(target, field) => match self.eval(target)? {
+                Value::Map(fields) => match fields.get(field) {
+                    Some(value) => Ok(value.clone()),
+                    None => error(format!("no such key: {}", field)),
+                },
+                other => error(format!(
+                    "cannot select '{}' on {}",
+                    field,
+                    other.type_name()
+                )),
+            },
+            Expr::Index(target, index) => {
+                let target = self.eval(target)?;
+                let index = self.eval(index)?;
+                match (target, index) {
+                    (Value::List(items), Value::Int(i)) => {
+                        match usize::try_from(i).ok().and_then(|i| items.get(i)) {
+                            Some(value) => Ok(value.clone()),
+                            None => error(format!("index {} out of range", i)),
+                        }
+                    }
+                    (Value::Map(fields), Value::String(key)) => match fields.get(&key) {
+                        Some(value) => Ok(value.clone()),
+                        None => error(format!("no such key: {}", key)),
+                    },
+                    (target, index) => error(format!(
+                        "cannot index {} with {}",
+                        target.type_name(),
+                        index.type_name()
+                    )),
+                }
+            }
+            Expr::Call { target, name, args } => self.call(target.as_deref(), name, args),
+            Expr::Unary(op, operand) => match (*op, self.eval(operand)?) {
+                ("!", Value::Bool(b)) => Ok(Value::Bool(!b)),
+                ("-", Value::Int(i)) => Ok(Value::Int(-i)),
+                ("-", Value::Double(d)) => Ok(Value::Double(-d)),
+                (op, value) => error(format!("no such overload: {}{}", op, value.type_name())),
+            },
+            Expr::Binary(op, left, right) => self.binary(op, left, right),
+            Expr::Conditional(cond, then, otherwise) => match self.eval(cond)? {
+                Value::Bool(true) => self.eval(then),
+                Value::Bool(false) => self.eval(otherwise),
+                other => error(format!("condition must be bool, got {}", other.type_name())),
+            },
+            Expr::List(items) => Ok(Value::List(
+                items
+                    .iter()
+                    .map(|item| self.eval(item))
+                    .collect::<CelResult<_>>()?,
+            )),
+            Expr::Map(entries) => {
+                let mut map = BTreeMap::new();
+                for (key, value) in entries {
+                    let key = match self.eval(key)? {
+                        Value::String(key) => key,
+                        other => {
+                            return error(format!(
+                                "map keys must be strings, got {}",
+                                other.type_name()
+                            ))
+                        }
+                    };
+                    map.insert(key, self.eval(value)?);
+                }
+                Ok(Value::Map(map))
+            }
+        }
+    }
+
+    fn binary(&mut self, op: &str, left: &Expr, right: &Expr) -> CelResult<Value> {
+        // Logical operators short-circuit
+        if op == "&&" || op == "||" {
+            let short = op == "||";
+            return match self.eval(left)? {
+                Value::Bool(b) if b == short => Ok(Value::Bool(short)),
+                Value::Bool(_) => match self.eval(right)? {
+                    Value::Bool(b) => Ok(Value::Bool(b)),
+                    other => error(format!(
+                        "no such overload: bool {} {}",
+                        op,
+                        other.type_name()
+                    )),
+                },
+                other => error(format!(
+                    "no such overload: {} {} bool",
+                    other.type_name(),
+                    op
+                )),
+            };
+        }
+
+        let left = self.eval(left)?;
+        let right = self.eval(right)?;
+        let mismatch = |l: &Value, r: &Value| {
+            error(format!(
+                "no such overload: {} {} {}",
+                l.type_name(),
+                op,
+                r.type_name()
+            ))
+        };
+
+        match op {
+            "==" => Ok(Value::Bool(values_equal(&left, &right))),
+            "!=" => Ok(Value::Bool(!values_equal(&left, &right))),
+            "in" => match right {
+                Value::List(ref items) => Ok(Value::Bool(
+                    items.iter().any(|item| values_equal(item, &left)),
+                )),
+                Value::Map(ref fields) => match left {
+                    Value::String(ref key) => Ok(Value::Bool(fields.contains_key(key))),
+                    _ => mismatch(&left, &right),
+                },
+                _ => mismatch(&left, &right),
+            },
+            "<" | "<=" | ">" | ">=" => {
+                let ordering = match (&left, &right) {
+                    (Value::String(a), Value::String(b)) => a.partial_cmp(b),
+                    _ => match (as_number(&left), as_number(&right)) {
+                        (Some(a), Some(b)) => a.partial_cmp(&b),
+                        _ => return mismatch(&left, &right),
+                    },
+                };
+                let ordering = match ordering {
+                    Some(ordering) => ordering,
+                    None => return Ok(Value::Bool(false)),
+                };
+                Ok(Value::Bool(match op {
+                    "<" => ordering.is_lt(),
+                    "<=" => ordering.is_le(),
+                    ">" => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                }))
+            }
+            _ => match (left, right) {
+                (Value::Int(a), Value::Int(b)) => {
+                    let result = match op {
+                        "+" => a.checked_add(b),
+                        "-" => a.checked_sub(b),
+                        "*" => a.checked_mul(b),
+                        "/" => a.checked_div(b),
+                        _ => a.checked_rem(b),
+                    };
+                    match result {
+                        Some(value) => Ok(Value::Int(value)),
+                        None => error("integer overflow or division by zero"),
+                    }
+                }
+                (Value::String(a), Value::String(b)) if op == "+" => Ok(Value::String(a + &b)),
+                (Value::List(mut a), Value::List(b)) if op == "+" => {
+                    a.extend(b);
+                    Ok(Value::List(a))
+                }
+                (left, right) => match (as_number(&left), as_number(&right)) {
+                    (Some(a), Some(b)) if op != "%" => Ok(Value::Double(match op {
+                        "+" => a + b,
+                        "-" => a - b,
+                        "*" => a * b,
+                        _ => a / b,
+                    })),
+                    _ => mismatch(&left, &right),
+                },
+            },
+        }
+    }
+
+    fn call(&mut self, target: Option<&Expr>, name: &str, args: &[Expr]) -> CelResult<Value> {
+        // Macros bind a variable and evaluate their arguments lazily
+        if let (Some(target), "all" | "exists" | "exists_one" | "filter" | "map") = (target, name) {
+            return self.comprehension(target, name, args);
+        }
+        if name == "has" && target.is_none() {
+            return match args {
+                [Expr::Member(inner, field)] => match self.eval(inner)? {
+                    Value::Map(fields) => Ok(Value::Bool(
+                        fields.get(field).is_some_and(|v| *v != Value::Null),
+                    )),
+                    other => error(format!("has() needs a map, got {}", other.type_name())),
+                },
+                _ => error("has() needs a field selection, e.g. has(commit.scope)"),
+            };
+        }
+
+        // Method calls take their receiver as the first argument
+        let mut values = Vec::new();
+        if let Some(target) = target {
+            values.push(self.eval(target)?);
+        }
+        for arg in args {
+            values.push(self.eval(arg)?);
+        }
+
+        match (name, values.as_slice()) {
+            ("size", [Value::String(s)]) => Ok(Value::Int(s.chars().count() as i64)),
+            ("size", [Value::List(items)]) => Ok(Value::Int(items.len() as i64)),
+            ("size", [Value::Map(fields)]) => Ok(Value::Int(fields.len() as i64)),
+            ("contains", [Value::String(s), Value::String(t)]) => {
+                Ok(Value::Bool(s.contains(t.as_str())))
+            }
+            ("startsWith", [Value::String(s), Value::String(t)]) => {
+                Ok(Value::Bool(s.starts_with(t.as_str())))
+            }
+            ("endsWith", [Value::String(s), Value::String(t)]) => {
+                Ok(Value::Bool(s.ends_with(t.as_str())))
+            }
+            ("matches", [Value::String(s), Value::String(pattern)]) => {
+                match regex::Regex::new(pattern) {
+                    Ok(re) => Ok(Value::Bool(re.is_match(s))),
+                    Err(e) => error(format!("invalid regex '{}': {}", pattern, e)),
+                }
+            }
+            ("lowerAscii", [Value::String(s)]) => Ok(Value::String(s.to_ascii_lowercase())),
+            ("upperAscii", [Value::String(s)]) => Ok(Value::String(s.to_ascii_uppercase())),
+            ("int", [Value::Int(i)]) => Ok(Value::Int(*i)),
+            ("int", [Value::Double(d)]) => Ok(Value::Int(*d as i64)),
+            ("int", [Value::String(s)]) => match s.parse() {
+                Ok(i) => Ok(Value::Int(i)),
+                Err(_) => error(format!("cannot convert '{}' to int", s)),
+            },
+            ("double", [value]) => match as_number(value) {
+                Some(d) => Ok(Value::Double(d)),
+                None => error(format!("cannot convert {} to double", value.type_name())),
+            },
+            ("string", [Value::String(s)]) => Ok(Value::String(s.clone())),
+            ("string", [Value::Int(i)]) => Ok(Value::String(i.to_string())),
+            ("string", [Value::Double(d)]) => Ok(Value::String(d.to_string())),
+            ("string", [Value::Bool(b)]) => Ok(Value::String(b.to_string())),
+            _ => error(format!(
+                "no such function: {}({})",
+                name,
+                values
+                    .iter()
+                    .map(Value::type_name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Evaluate `target.macro(var, expr)` over a list or a map's keys.
+    fn comprehension(&mut self, target: &Expr, name: &str, args: &[Expr]) -> CelResult<Value> {
+        let (var, body) = match args {
+            [Expr::Ident(var), body] => (var.clone(), body),
+            _ => return error(format!("{}() needs a variable and an expression", name)),
+        };
+        let items = match self.eval(target)? {
+            Value::List(items) => items,
+            Value::Map(fields) => fields.into_keys().map(Value::String).collect(),
+            other => {
+                return error(format!(
+                    "{}() needs a list or map, got {}",
+                    name,
+                    other.type_name()
+                ))
+            }
+        };
+
+        let mut matched = Vec::new();
+        let mut mapped = Vec::new();
+        for item in items {
+            self.scopes
+                .push(BTreeMap::from([(var.clone(), item.clone())]));
+            let result = self.eval(body);
+            self.scopes.pop();
+
+            match (name, result?) {
+                ("map", value) => mapped.push(value),
+                (_, Value::Bool(true)) => matched.push(item),
+                (_, Value::Bool(false)) => {
+                    if name == "all" {
+                        return Ok(Value::Bool(false));
+                    }
+                }
+                (_, other) => {
+                    return error(format!(
+                        "{}() predicate must be bool, got {}",
+                        name,
+                        other.type_name()
+                    ))
+                }
+            }
+            if name == "exists" && !matched.is_empty() {
+                return Ok(Value::Bool(true));
+            }
+        }
+
+        Ok(match name {
+            "all" => Value::Bool(true),
+            "exists" => Value::Bool(false),
+            "exists_one" => Value::Bool(matched.len() == 1),
+            "filter" => Value::List(matched),
+            _ => Value::List(mapped),
+        })
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(i) => Some(*i as f64),
+        Value::Double(d) => Some(*d),
+        _ => None,
+    }
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (as_number(a), as_number(b)) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str) -> CelResult<Value> {
+        let doc = serde_json::json!({
+            "commit": {"type": "perf", "scope": "parser", "subject": "speed up lexing"},
+            "diff": {"files": [
+                {"path": "src/parser/lexer.rs", "additions": 40},
+                {"path": "benches/lexer.rs", "additions": 12}
+            ]}
+        });
+        let vars = match Value::from(&doc) {
+            Value::Map(vars) => vars,
+            _ => unreachable!(),
+        };
+        eval(source, &vars)
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(run("1 + 2 * 3 == 7 && !(2 > 3)"), Ok(Value::Bool(true)));
+        assert_eq!(run("'a' + \"b\""), Ok(Value::String("ab".into())));
+        assert_eq!(
+            run("commit.type in ['perf', 'fix'] ? 1 : 2"),
+            Ok(Value::Int(1))
+        );
+        assert_eq!(run("size(commit.subject) > 5.5"), Ok(Value::Bool(true)));
+        assert_eq!(run("{'a': 1}['a'] + [1, 2][1]"), Ok(Value::Int(3)));
+        // The right side is not evaluated once the result is known
+        assert_eq!(run("true || missing.field"), Ok(Value::Bool(true)));
+        assert!(run("1 / 0").is_err());
+    }
+
+    #[test]
+    fn test_macros_and_functions() {
+        assert_eq!(
+            run("commit.type != 'perf' || diff.files.exists(f, f.path.startsWith('benches/'))"),
+            Ok(Value::Bool(true))
+        );
+        assert_eq!(
+            run("diff.files.filter(f, f.additions > 20).map(f, f.path)"),
+            Ok(Value::List(vec![Value::String(
+                "src/parser/lexer.rs".into()
+            )]))
+        );
+        assert_eq!(
+            run("diff.files.all(f, f.path.matches('^(src|benches)/'))"),
+            Ok(Value::Bool(true))
+        );
+        assert_eq!(
+            run("has(commit.scope) && !has(commit.body)"),
+            Ok(Value::Bool(true))
+        );
+        assert_eq!(run("has({'a': null}.a)"), Ok(Value::Bool(false)));
+        assert_eq!(
+            run("commit.subject.upperAscii().contains('LEX')"),
+            Ok(Value::Bool(true))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse("commit.type ==").is_err());
+        assert!(parse("'unterminated").is_err());
+        assert_eq!(
+            run("nope"),
+            Err(CelError("undeclared reference to 'nope'".into()))
+        );
+        assert!(run("commit.type + 1").is_err());
+    }
+}
@@ -0,0 +1,257 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! GitHub Check Run payloads for check results.
+//!
+//! The payload is the request body of `POST /repos/{owner}/{repo}/check-runs`.
+//! `head_sha` is filled in when a range holds a single commit; otherwise
+//! the bot sets it to the pull request head. Annotations must point at a file in the repository; each is placed on
+//! line 1 of the first file the offending commit changes.
+
+use crate::git;
+use serde::Serialize;
+
+use super::validator::{ValidationIssue, ValidationResult};
+
+/// Name shown for the check in the pull request.
+pub const CHECK_NAME: &str = "ck";
+
+/// Maximum annotations GitHub accepts per request.
+pub const MAX_ANNOTATIONS: usize = 50;
+
+/// Annotation path used when a commit changes no files.
+const FALLBACK_PATH: &str = "ck.toml";
+
+/// A check run creation request.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckRun {
+    /// Check name.
+    pub name: String,
+    /// Commit the check is for, when a single commit was checked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_sha: Option<String>,
+    /// Always `completed`.
+    pub status: String,
+    /// `success` or `failure`.
+    pub conclusion: String,
+    /// Title, summary and annotations.
+    pub output: CheckRunOutput,
+}
+
+/// The output of a check run.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckRunOutput {
+    /// One-line title.
+    pub title: String,
+    /// Markdown summary.
+    pub summary: String,
+    /// Per-issue annotations.
+    pub annotations: Vec<Annotation>,
+}
+
+/// A check run annotation.
+#[derive(Debug, Clone, Serialize)]
+pub struct Annotation {
+    /// File the annotation is shown on.
+    pub path: String,
+    /// First line.
+    pub start_line: usize,
+    /// Last line.
+    pub end_line: usize,
+    /// `failure` or `warning`.
+    pub annotation_level: String,
+    /// Annotation title.
+    pub title: String,
+    /// Annotation message.
+    pub message: String,
+}
+
+impl CheckRun {
+    /// Build a check run from check results.
+    ///
+    /// `failed` is the overall outcome, which may count warnings as
+    /// failures. `path_for` maps a commit SHA to the file annotations on
+    /// it are attached to.
+    pub fn from_results(
+        results: &[ValidationResult],
+        failed: bool,
+        path_for: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let failing = results.iter().filter(|r| !r.is_valid()).count();
+        let title = if failing == 0 {
+            format!("{} commit(s) pass", results.len())
+        } else {
+            format!("{} of {} commit(s) fail", failing, results.len())
+        };
+
+        let mut annotations = Vec::new();
+        for result in results {
+            let sha = result.commit_sha.as_deref().unwrap_or("HEAD");
+            let path = path_for(sha).unwrap_or_else(|| FALLBACK_PATH.to_string());
+            let subject = result.message.lines().next().unwrap_or("");
+            for issue in result.errors.iter().chain(&result.warnings) {
+                annotations.push(annotation(&path, short(sha), subject, issue));
+            }
+        }
+
+        let mut summary = summary(results);
+        if annotations.len() > MAX_ANNOTATIONS {
+            summary.push_str(&format!(
+                "\nShowing the first {} of {} annotations.\n",
+                MAX_ANNOTATIONS,
+                annotations.len()
+            ));
+            annotations.truncate(MAX_ANNOTATIONS);
+        }
+
+        let head_sha = match results {
+            [single] => single.commit_sha.clone(),
+            _ => None,
+        };
+
+        Self {
+            name: CHECK_NAME.to_string(),
+            head_sha,
+            status: "completed".to_string(),
+            conclusion: if failed { "failure" } else { "success" }.to_string(),
+            output: CheckRunOutput {
+                title,
+                summary,
+                annotations,
+            },
+        }
+    }
+
+    /// Print the payload as JSON to stdout.
+    pub fn print(&self) {
+        println!("{}", serde_json::to_string_pretty(self).unwrap_or_default());
+    }
+}
+
+/// The first file a commit changes, relative to the repository root.
+pub fn first_changed_path(sha: &str) -> Option<String> {
+    let diff = git::get_diff(sha).ok()?;
+    let file = diff.files.first()?;
+    Some(file.path.to_string_lossy().to_string())
+}
+
+fn annotation(path: &str, sha: &str, subject: &str, issue: &ValidationIssue) -> Annotation {
+    let mut message = format!("{}\n{}", subject, issue.message);
+    if let Some(ref suggestion) = issue.suggestion {
+        message.push_str(&format!("\n{}", suggestion));
+    }
+
+    Annotation {
+        path: path.to_string(),
+        start_line: 1,
+        end_line: 1,
+        annotation_level: if issue.is_error { "failure" } else { "warning" }.to_string(),
+        title: format!("{}: {}", sha, issue.code),
+        message,
+    }
+}
+
+/// Markdown table of the checked commits.
+fn summary(results: &[ValidationResult]) -> String {
+    let mut summary = String::from("| | Commit | Subject | Issues |\n|---|---|---|---|\n");
+    for result in results {
+        let status = if !result.is_valid() {
+            "❌"
+        } else if !result.warnings.is_empty() {
+            "⚠️"
+        } else {
+            "✅"
+        };
+        let codes: Vec<_> = result
+            .errors
+            .iter()
+            .chain(&result.warnings)
+            .map(|issue| format!("`{}`", issue.code))
+            .collect();
+        summary.push_str(&format!(
+            "| {} | `{}` | {} | {} |\n",
+            status,
+            short(result.commit_sha.as_deref().unwrap_or("HEAD")),
+            result
+                .message
+                .lines()
+                .next()
+                .unwrap_or("")
+                .replace('|', "\\|"),
+            codes.join(", ")
+        ));
+    }
+    summary
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..7.min(sha.len())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(sha: &str, message: &str, code: Option<&str>) -> ValidationResult {
+        let mut result = ValidationResult::new(message.to_string());
+        result.commit_sha = Some(sha.to_string());
+        if let Some(code) = code {
+            result.warnings.push(ValidationIssue {
+                code: code.to_string(),
+                message: "Subject should use imperative mood".to_string(),
+                suggestion: Some("Use 'add'".to_string()),
+                is_error: false,
+                line: Some(1),
+                span: None,
+            });
+        }
+        result
+    }
+
+    #[test]
+    fn test_check_run_payload() {
+        let results = vec![
+            result(
+                "1234567890",
+                "feat: added export",
+                Some("subject-imperative"),
+            ),
+            result("abcdef0123", "fix: handle | in paths", None),
+        ];
+        let run = CheckRun::from_results(&results, false, |sha| {
+            (sha == "1234567890").then(|| "src/export.rs".to_string())
+        });
+
+        assert_eq!(run.conclusion, "success");
+        assert_eq!(run.head_sha, None);
+        assert_eq!(run.output.title, "2 commit(s) pass");
+        assert!(run
+            .output
+            .summary
+            .contains("| ⚠️ | `1234567` | feat: added export | `subject-imperative` |"));
+        assert!(run.output.summary.contains("handle \\| in paths"));
+
+        let annotation = &run.output.annotations[0];
+        assert_eq!(annotation.path, "src/export.rs");
+        assert_eq!(annotation.annotation_level, "warning");
+        assert_eq!(annotation.title, "1234567: subject-imperative");
+    }
+
+    #[test]
+    fn test_annotations_are_capped() {
+        let results: Vec<_> = (0..60)
+            .map(|i| {
+                result(
+                    &format!("{:07}", i),
+                    "feat: added x",
+                    Some("subject-imperative"),
+                )
+            })
+            .collect();
+        let run = CheckRun::from_results(&results, true, |_| None);
+        assert_eq!(run.conclusion, "failure");
+        assert_eq!(run.output.annotations.len(), MAX_ANNOTATIONS);
+        assert_eq!(run.output.annotations[0].path, FALLBACK_PATH);
+        assert!(run.output.summary.contains("first 50 of 60"));
+    }
+}
@@ -0,0 +1,268 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Everything a rule can look at when checking a commit.
+//!
+//! Expression rules and plugins see the context as JSON:
+//!
+//! ```text
+//! commit.type, commit.scope, commit.subject, commit.header,
+//! commit.body, commit.footer, commit.breaking
+//! diff.available, diff.additions, diff.deletions,
+//! diff.files[].path, .status, .additions, .deletions, .binary
+//! branch, packages
+//! ci.provider, ci.pull_request, ci.target_branch  (null outside CI)
+//! ```
+
+use crate::commit::CommitMessage;
+use crate::config::CkConfig;
+use crate::git::{ChangeType, DiffInfo};
+use crate::rules::language::HistoryLanguage;
+use serde::Serialize;
+use serde_json::json;
+
+/// Input to rule checks.
+#[derive(Debug, Clone)]
+pub struct RuleContext<'a> {
+    /// The commit message being checked.
+    pub message: &'a CommitMessage,
+    /// Effective configuration.
+    pub config: &'a CkConfig,
+    /// Changes in the commit, when known.
+    pub diff: Option<&'a DiffInfo>,
+    /// Branch the commit is on, when known.
+    pub branch: Option<String>,
+    /// CI environment, when running in CI.
+    pub ci: Option<CiEnvironment>,
+    /// Names of the packages the commit touches.
+    pub packages: Vec<String>,
+    /// Language of the repository's history, for `rules.language.require
+    /// = "history"`.
+    pub history_language: Option<&'a HistoryLanguage>,
+}
+
+impl<'a> RuleContext<'a> {
+    /// Create a context with only a message and configuration.
+    pub fn new(message: &'a CommitMessage, config: &'a CkConfig) -> Self {
+        Self {
+            message,
+            config,
+            diff: None,
+            branch: None,
+            ci: None,
+            packages: Vec::new(),
+            history_language: None,
+        }
+    }
+
+    /// Set the diff of the commit.
+    pub fn with_diff(mut self, diff: Option<&'a DiffInfo>) -> Self {
+        self.diff = diff;
+        self
+    }
+
+    /// Set the branch the commit is on.
+    pub fn with_branch(mut self, branch: Option<String>) -> Self {
+        self.branch = branch;
+        self
+    }
+
+    /// Set the CI environment.
+    pub fn with_ci(mut self, ci: Option<CiEnvironment>) -> Self {
+        self.ci = ci;
+        self
+    }
+
+    /// Set the packages the commit touches.
+    pub fn with_packages(mut self, packages: Vec<String>) -> Self {
+        self.packages = packages;
+        self
+    }
+
+    /// Set where the language of the history is read from.
+    pub fn with_history_language(mut self, history: &'a HistoryLanguage) -> Self {
+        self.history_language = Some(history);
+        self
+    }
+
+    /// The context as a JSON document, without the configuration.
+    pub fn to_json(&self) -> serde_json::Value {
+        let (message, diff) = (self.message, self.diff);
+        let files: Vec<_> = diff
+            .map(|diff| diff.files.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|file| {
+                json!({
+                    "path": file.path.to_string_lossy(),
+                    "status": status(file.change_type),
+                    "additions": file.lines_added,
+                    "deletions": file.lines_removed,
+                    "binary": file.is_binary,
+                })
+            })
+            .collect();
+
+        json!({
+            "commit": {
+                "type": message.commit_type.as_str(),
+                "scope": message.scope,
+                "subject": message.subject,
+                "header": message.header(),
+                "body": message.body,
+                "footer": message.footer,
+                "breaking": message.is_breaking,
+            },
+            "diff": {
+                "available": diff.is_some(),
+                "additions": diff.map_or(0, |d| d.stats.lines_added),
+                "deletions": diff.map_or(0, |d| d.stats.lines_removed),
+                "files": files,
+            },
+            "branch": self.branch.as_deref().unwrap_or(""),
+            "packages": self.packages,
+            "ci": self.ci,
+        })
+    }
+}
+
+fn status(change: ChangeType) -> &'static str {
+    match change {
+        ChangeType::Added => "added",
+        ChangeType::Deleted => "deleted",
+        ChangeType::Modified => "modified",
+        ChangeType::Renamed => "renamed",
+        ChangeType::Copied => "copied",
+        ChangeType::TypeChange => "typechange",
+    }
+}
+
+/// The CI system ck runs under.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CiEnvironment {
+    /// CI provider, e.g. `github`, `gitlab` or `generic`.
+    pub provider: String,
+    /// Whether the build is for a pull or merge request.
+    pub pull_request: bool,
+    /// Branch the pull request targets.
+    pub target_branch: Option<String>,
+}
+
+impl CiEnvironment {
+    /// Detect the CI environment from the process environment.
+    pub fn detect() -> Option<Self> {
+        Self::from_env(|key| std::env::var(key).ok().filter(|v| !v.is_empty()))
+    }
+
+    /// Detect the CI environment using `var` to read variables.
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        // Provider, marker variable, and the variable holding the PR target
+        const PROVIDERS: &[(&str, &str, &str)] = &[
+            ("github", "GITHUB_ACTIONS", "GITHUB_BASE_REF"),
+            ("gitlab", "GITLAB_CI", "CI_MERGE_REQUEST_TARGET_BRANCH_NAME"),
+            ("azure", "TF_BUILD", "SYSTEM_PULLREQUEST_TARGETBRANCH"),
+            (
+                "bitbucket",
+                "BITBUCKET_BUILD_NUMBER",
+                "BITBUCKET_PR_DESTINATION_BRANCH",
+            ),
+            ("jenkins", "JENKINS_URL", "CHANGE_TARGET"),
+            ("circleci", "CIRCLECI", "CIRCLE_PULL_REQUEST"),
+        ];
+
+        for (provider, marker, target) in PROVIDERS {
+            if var(marker).is_some() {
+                // Azure names the target as a full ref
+                let target_branch =
+                    var(target).map(|branch| branch.trim_start_matches("refs/heads/").to_string());
+                return Some(Self {
+                    provider: provider.to_string(),
+                    pull_request: target_branch.is_some(),
+                    // CircleCI only exposes the PR URL, not its base
+                    target_branch: target_branch.filter(|_| *provider != "circleci"),
+                });
+            }
+        }
+
+        var("CI").map(|_| Self {
+            provider: "generic".to_string(),
+            pull_request: false,
+            target_branch: None,
+        })
+    }
+}
+
+/// Environment variable holding a pull request title.
+pub const PR_TITLE_VAR: &str = "CK_PR_TITLE";
+
+/// Discover the title of the pull request being built.
+///
+/// Reads `CK_PR_TITLE`, then the `pull_request.title` of the GitHub
+/// Actions event payload at `GITHUB_EVENT_PATH`.
+pub fn pull_request_title() -> Option<String> {
+    if let Some(title) = std::env::var(PR_TITLE_VAR).ok().filter(|t| !t.is_empty()) {
+        return Some(title);
+    }
+
+    pull_request_event()?["title"].as_str().map(str::to_string)
+}
+
+/// The `pull_request` object of the GitHub Actions event payload at
+/// `GITHUB_EVENT_PATH`.
+pub fn pull_request_event() -> Option<serde_json::Value> {
+    let event_path = std::env::var("GITHUB_EVENT_PATH").ok()?;
+    let event = std::fs::read_to_string(event_path).ok()?;
+    pull_request_from_event(&event)
+}
+
+/// The pull request in a GitHub event payload.
+fn pull_request_from_event(event: &str) -> Option<serde_json::Value> {
+    let mut event: serde_json::Value = serde_json::from_str(event).ok()?;
+    Some(event["pull_request"].take()).filter(|pr| pr.is_object())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn detect(vars: &[(&str, &str)]) -> Option<CiEnvironment> {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        CiEnvironment::from_env(|key| vars.get(key).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn test_detect_ci_environment() {
+        assert_eq!(detect(&[]), None);
+
+        let github = detect(&[("GITHUB_ACTIONS", "true"), ("GITHUB_BASE_REF", "main")]).unwrap();
+        assert_eq!(github.provider, "github");
+        assert!(github.pull_request);
+        assert_eq!(github.target_branch.as_deref(), Some("main"));
+
+        let circle = detect(&[("CIRCLECI", "true"), ("CIRCLE_PULL_REQUEST", "https://x/1")]);
+        assert!(circle.as_ref().unwrap().pull_request);
+        assert_eq!(circle.unwrap().target_branch, None);
+
+        let azure = detect(&[
+            ("TF_BUILD", "True"),
+            ("SYSTEM_PULLREQUEST_TARGETBRANCH", "refs/heads/main"),
+        ]);
+        assert_eq!(azure.unwrap().target_branch.as_deref(), Some("main"));
+
+        assert_eq!(detect(&[("CI", "1")]).unwrap().provider, "generic");
+    }
+
+    #[test]
+    fn test_title_from_event() {
+        let event = r#"{"action": "opened", "pull_request": {"title": "feat: add export"}}"#;
+        assert_eq!(
+            pull_request_from_event(event).unwrap()["title"].as_str(),
+            Some("feat: add export")
+        );
+        assert_eq!(
+            pull_request_from_event(r#"{"ref": "refs/heads/main"}"#),
+            None
+        );
+    }
+}
@@ -0,0 +1,254 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Comparison of validation outcomes between two rule configurations.
+
+use crate::cli::args::OutputFormat;
+use crate::error::Result;
+use console::style;
+use std::collections::BTreeSet;
+
+use super::engine::RuleEngine;
+use super::validator::ValidationResult;
+
+/// Overall outcome of validating one commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    /// No issues.
+    Pass,
+    /// Warnings only.
+    Warn,
+    /// At least one error.
+    Fail,
+}
+
+impl Outcome {
+    /// Get the string representation of the outcome.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Pass => "pass",
+            Outcome::Warn => "warn",
+            Outcome::Fail => "fail",
+        }
+    }
+}
+
+/// Validation of a single commit under one configuration.
+#[derive(Debug, Clone)]
+struct Evaluation {
+    outcome: Outcome,
+    codes: BTreeSet<String>,
+}
+
+impl Evaluation {
+    fn of(result: &ValidationResult) -> Self {
+        let outcome = if !result.errors.is_empty() {
+            Outcome::Fail
+        } else if !result.warnings.is_empty() {
+            Outcome::Warn
+        } else {
+            Outcome::Pass
+        };
+        let codes = result
+            .errors
+            .iter()
+            .chain(&result.warnings)
+            .map(|i| i.code.clone())
+            .collect();
+        Self { outcome, codes }
+    }
+}
+
+/// A commit whose validation changed between the two configurations.
+#[derive(Debug, Clone)]
+pub struct CommitDiff {
+    /// Commit SHA.
+    pub sha: String,
+    /// First line of the commit message.
+    pub header: String,
+    /// Outcome under the base configuration.
+    pub before: Outcome,
+    /// Outcome under the other configuration.
+    pub after: Outcome,
+    /// Rule codes only reported under the other configuration.
+    pub added: Vec<String>,
+    /// Rule codes only reported under the base configuration.
+    pub removed: Vec<String>,
+}
+
+/// Differences in validation outcomes over a set of commits.
+#[derive(Debug, Clone, Default)]
+pub struct RulesDiff {
+    /// Number of commits compared.
+    pub total: usize,
+    /// Commits whose reported issues changed.
+    pub changed: Vec<CommitDiff>,
+}
+
+impl RulesDiff {
+    /// Compare two engines over the commits in `range`.
+    ///
+    /// Each engine checks the range as `ck check` does, so diffs, the
+    /// branch and the merge policy count under both configurations.
+    pub fn compute(base: &RuleEngine, other: &RuleEngine, range: &str) -> Result<Self> {
+        let before = base.check_range(range)?;
+        let after = other.check_range(range)?;
+        Ok(Self::from_results(&before, &after))
+    }
+
+    /// Compare results for the same commits, in the same order.
+    pub fn from_results(before: &[ValidationResult], after: &[ValidationResult]) -> Self {
+        let changed = before
+            .iter()
+            .zip(after)
+            .filter_map(|(base, other)| {
+                let before = Evaluation::of(base);
+                let after = Evaluation::of(other);
+                if before.codes == after.codes && before.outcome == after.outcome {
+                    return None;
+                }
+                Some(CommitDiff {
+                    sha: base.commit_sha.clone().unwrap_or_default(),
+                    header: base.message.lines().next().unwrap_or("").to_string(),
+                    before: before.outcome,
+                    after: after.outcome,
+                    added: after.codes.difference(&before.codes).cloned().collect(),
+                    removed: before.codes.difference(&after.codes).cloned().collect(),
+                })
+            })
+            .collect();
+
+        Self {
+            total: before.len(),
+            changed,
+        }
+    }
+
+    /// Number of commits that pass under the base but fail under the other.
+    pub fn newly_failing(&self) -> usize {
+        self.changed
+            .iter()
+            .filter(|c| c.before != Outcome::Fail && c.after == Outcome::Fail)
+            .count()
+    }
+
+    /// Number of commits that fail under the base but pass under the other.
+    pub fn newly_passing(&self) -> usize {
+        self.changed
+            .iter()
+            .filter(|c| c.before == Outcome::Fail && c.after != Outcome::Fail)
+            .count()
+    }
+
+    /// Print the diff to stdout.
+    pub fn print(&self, format: Option<OutputFormat>) {
+        match format {
+            Some(OutputFormat::Json) => self.print_json(),
+            _ => self.print_text(),
+        }
+    }
+
+    /// Print in text format.
+    fn print_text(&self) {
+        for commit in &self.changed {
+            let marker = match commit.after.cmp(&commit.before) {
+                std::cmp::Ordering::Greater => style("▲").red().bold(),
+                std::cmp::Ordering::Less => style("▼").green().bold(),
+                std::cmp::Ordering::Equal => style("•").yellow().bold(),
+            };
+            let short_sha = &commit.sha[..7.min(commit.sha.len())];
+            println!(
+                "{} {} {} ({} → {})",
+                marker,
+                style(short_sha).cyan(),
+                commit.header,
+                commit.before.as_str(),
+                commit.after.as_str()
+            );
+            for code in &commit.added {
+                println!("    {} {}", style("+").red(), code);
+            }
+            for code in &commit.removed {
+                println!("    {} {}", style("-").green(), code);
+            }
+        }
+
+        println!(
+            "{} commits compared: {} changed, {} newly failing, {} newly passing",
+            self.total,
+            self.changed.len(),
+            self.newly_failing(),
+            self.newly_passing()
+        );
+    }
+
+    /// Print in JSON format.
+    fn print_json(&self) {
+        let json = serde_json::json!({
+            "total": self.total,
+            "newly_failing": self.newly_failing(),
+            "newly_passing": self.newly_passing(),
+            "changed": self.changed.iter().map(|c| {
+                serde_json::json!({
+                    "commit": c.sha,
+                    "header": c.header,
+                    "before": c.before.as_str(),
+                    "after": c.after.as_str(),
+                    "added": c.added,
+                    "removed": c.removed,
+                })
+            }).collect::<Vec<_>>(),
+        });
+
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json).unwrap_or_default()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CkConfig;
+
+    fn check(engine: &RuleEngine) -> Vec<ValidationResult> {
+        [
+            ("aaa", "feat(core): add parser"),
+            ("bbb", "fix: handle empty input"),
+        ]
+        .into_iter()
+        .map(|(sha, message)| {
+            let mut result = engine.validate_string(message).unwrap();
+            result.commit_sha = Some(sha.to_string());
+            result
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_identical_configs() {
+        let engine = RuleEngine::new(CkConfig::default());
+        let diff = RulesDiff::from_results(&check(&engine), &check(&engine));
+        assert_eq!(diff.total, 2);
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn test_stricter_config() {
+        let base = RuleEngine::new(CkConfig::default());
+        let mut strict = CkConfig::default();
+        strict.rules.require_scope = true;
+        let other = RuleEngine::new(strict);
+
+        let diff = RulesDiff::from_results(&check(&base), &check(&other));
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].sha, "bbb");
+        assert_eq!(diff.changed[0].after, Outcome::Fail);
+        assert_eq!(diff.newly_failing(), 1);
+
+        let reverse = RulesDiff::from_results(&check(&other), &check(&base));
+        assert_eq!(reverse.newly_passing(), 1);
+        assert_eq!(reverse.changed[0].removed, diff.changed[0].added);
+    }
+}
@@ -3,41 +3,114 @@
 
 //! Rule engine for commit validation.
 
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::analysis::changed_packages;
+use crate::commit::editor::strip_comments;
 use crate::commit::CommitMessage;
-use crate::config::CkConfig;
-use crate::error::Result;
-use crate::git;
+use crate::config::{BranchRuleConfig, CkConfig, ConfigSources, QueueStrategy, RequiredLanguage};
+use crate::error::{CkError, Result};
+use crate::git::{self, DiffInfo};
+use crate::interrupt;
+use crate::plugins::EnabledPlugins;
+use crate::progress::Progress;
 
 use super::builtin::{apply_builtin_rules, Rule};
-use super::validator::ValidationResult;
+use super::cache::CheckCache;
+use super::catalog::{list_rules, RuleEntry};
+use super::context::{CiEnvironment, RuleContext};
+use super::expression::check_expressions;
+use super::language::HistoryLanguage;
+use super::merge::{self, MergeVerdict};
+use super::paths::check_path_rules;
+use super::queue::{self, PullRequest};
+use super::suppress;
+use super::validator::{ValidationIssue, ValidationResult};
 
 /// Rule engine for validating commit messages.
 #[derive(Debug, Clone)]
 pub struct RuleEngine {
     config: CkConfig,
     custom_rules: Vec<Box<dyn Rule>>,
+    plugins: Option<Arc<EnabledPlugins>>,
+    branch: Option<String>,
+    ci: Option<CiEnvironment>,
+    cache: bool,
+    config_hash: String,
+    history_language: Arc<HistoryLanguage>,
 }
 
 impl RuleEngine {
     /// Create a new rule engine with the given configuration.
+    ///
+    /// The current branch and CI environment are read once here, not for
+    /// every message validated.
     pub fn new(config: CkConfig) -> Self {
         Self {
+            config_hash: config.hash(),
+            plugins: EnabledPlugins::load(&config).map(Arc::new),
             config,
             custom_rules: Vec::new(),
+            branch: git::get_branch_name().ok(),
+            ci: CiEnvironment::detect(),
+            cache: false,
+            history_language: Arc::default(),
         }
     }
 
+    /// Skip commits in range checks that passed under the same rules
+    /// before, and record new verdicts in git notes.
+    pub fn with_cache(mut self) -> Self {
+        self.cache = true;
+        self
+    }
+
+    /// Apply the rules of `branch` instead of those of the current branch,
+    /// e.g. for a commit made with `--onto`.
+    pub fn with_branch(mut self, branch: &str) -> Self {
+        self.branch = Some(branch.to_string());
+        self
+    }
+
     /// Add a custom rule to the engine.
     pub fn add_rule(&mut self, rule: Box<dyn Rule>) {
         self.custom_rules.push(rule);
     }
 
+    /// List all active rules with their effective settings.
+    pub fn list_rules(&self, sources: &ConfigSources) -> Vec<RuleEntry> {
+        let custom: Vec<&str> = self.custom_rules.iter().map(|r| r.name()).collect();
+        list_rules(&self.config, sources, &custom)
+    }
+
     /// Validate a commit message.
     pub fn validate(&self, message: &CommitMessage) -> ValidationResult {
+        self.validate_with_diff(message, None)
+    }
+
+    /// Validate a commit message together with its diff.
+    ///
+    /// Expression rules see the diff; without one `diff.available` is false
+    /// and path rules don't apply. Rules configured under `rules.paths` for
+    /// the changed files and under `rules.branch` for the branch apply on
+    /// top of the global rules.
+    pub fn validate_with_diff(
+        &self,
+        message: &CommitMessage,
+        diff: Option<&DiffInfo>,
+    ) -> ValidationResult {
+        let files: Vec<_> = diff
+            .map(|diff| diff.files.iter().map(|f| f.path.clone()).collect())
+            .unwrap_or_default();
+        let config = self.effective_config(self.branch.as_deref(), &files);
+        let context = self.context(message, diff, &config, &files);
         let mut result = ValidationResult::new(message.format());
+        result.config_hash = Some(self.config_hash.clone());
 
         // Apply built-in rules
-        let builtin_issues = apply_builtin_rules(message, &self.config);
+        let builtin_issues = apply_builtin_rules(&context);
         for issue in builtin_issues {
             if issue.is_error {
                 result.errors.push(issue);
@@ -46,9 +119,18 @@ impl RuleEngine {
             }
         }
 
+        // Apply path rules
+        for issue in check_path_rules(&context) {
+            if issue.is_error {
+                result.errors.push(issue);
+            } else {
+                result.warnings.push(issue);
+            }
+        }
+
         // Apply custom rules
         for rule in &self.custom_rules {
-            if let Some(issue) = rule.check(message, &self.config) {
+            if let Some(issue) = rule.check(&context) {
                 if issue.is_error {
                     result.errors.push(issue);
                 } else {
@@ -57,58 +139,346 @@ impl RuleEngine {
             }
         }
 
+        // Apply plugin rules
+        if let Some(ref plugins) = self.plugins {
+            for issue in plugins.check(&context) {
+                if issue.is_error {
+                    result.errors.push(issue);
+                } else {
+                    result.warnings.push(issue);
+                }
+            }
+        }
+
+        // Apply expression rules
+        for issue in check_expressions(&context) {
+            if issue.is_error {
+                result.errors.push(issue);
+            } else {
+                result.warnings.push(issue);
+            }
+        }
+
+        suppress::apply(&mut result, &message.trailer_text(), &config);
         result
     }
 
+    /// Build the context rules check a message in.
+    fn context<'a>(
+        &'a self,
+        message: &'a CommitMessage,
+        diff: Option<&'a DiffInfo>,
+        config: &'a CkConfig,
+        files: &[PathBuf],
+    ) -> RuleContext<'a> {
+        RuleContext::new(message, config)
+            .with_diff(diff)
+            .with_branch(self.branch.clone())
+            .with_ci(self.ci.clone())
+            .with_packages(changed_packages(files, config))
+            .with_history_language(&self.history_language)
+    }
+
+    /// The configuration with the rules for the changed `files` and then
+    /// those of `branch` applied.
+    fn effective_config(&self, branch: Option<&str>, files: &[PathBuf]) -> Cow<'_, CkConfig> {
+        let rules = &self.config.rules;
+        let branch = branch.filter(|branch| rules.branch_rules(branch).is_some());
+        let by_path = !files.is_empty() && !rules.paths.is_empty();
+        if branch.is_none() && !by_path {
+            return Cow::Borrowed(&self.config);
+        }
+
+        let mut config = self.config.clone();
+        if by_path {
+            config.rules = config.rules.for_paths(files);
+        }
+        if let Some(branch) = branch {
+            config.rules = config.rules.for_branch(branch);
+        }
+        Cow::Owned(config)
+    }
+
+    /// Whether any rule needs the diff of the commit being validated.
+    ///
+    /// Custom rules and plugins may read the diff, so they always get it.
+    pub fn needs_diff(&self) -> bool {
+        !self.config.rules.expressions.is_empty()
+            || !self.config.rules.paths.is_empty()
+            || !self.custom_rules.is_empty()
+            || self.plugins.is_some()
+    }
+
+    /// Parse a commit message, accepting the types this engine's
+    /// configuration defines.
+    pub fn parse(&self, message: &str) -> Result<CommitMessage> {
+        CommitMessage::parse_with(message, &self.config)
+    }
+
     /// Validate a commit message string.
     pub fn validate_string(&self, message: &str) -> Result<ValidationResult> {
-        let parsed = CommitMessage::parse(message)?;
+        let parsed = self.parse(message)?;
         Ok(self.validate(&parsed))
     }
 
+    /// Validate only a header line, ignoring body requirements.
+    ///
+    /// Used where a subject is seen without its body, e.g. in rebase
+    /// todo lists.
+    pub fn validate_header(&self, header: &str) -> Result<ValidationResult> {
+        let mut result = self.validate_string(header)?;
+        result.errors.retain(|i| !i.code.starts_with("body-"));
+        result.warnings.retain(|i| !i.code.starts_with("body-"));
+        Ok(result)
+    }
+
+    /// Check a pull request title, which becomes the commit header when
+    /// the pull request is squash-merged.
+    pub fn check_title(&self, title: &str) -> ValidationResult {
+        let mut result = match self.validate_header(title) {
+            Ok(result) => result,
+            Err(e) => parse_error_result(title, &e.to_string()),
+        };
+        result.config_hash = Some(self.config_hash.clone());
+        result
+    }
+
+    /// Check a message that isn't a commit yet, e.g. in the commit-msg hook.
+    ///
+    /// Comment lines are dropped as git drops them. Rules that need a diff
+    /// see the staged changes, and while a merge is in progress the message
+    /// is checked against `rules.merge` first.
+    pub fn check_message(&self, text: &str) -> ValidationResult {
+        let message = strip_comments(text);
+        let repo = git::open_repo().ok();
+
+        let verdict = match repo {
+            Some(ref repo) if repo.is_merging() => {
+                merge::check_merge(&message, &self.config.rules.merge)
+            }
+            _ => MergeVerdict::Validate,
+        };
+        // Outside a repository there are no staged changes to see
+        self.check_uncommitted(message, verdict, || match repo {
+            Some(_) => git::get_staged_diff().ok(),
+            None => None,
+        })
+    }
+
+    /// Check what a merge queue lands when `head` is merged into `target`.
+    ///
+    /// A squash merge is checked as the message the platform composes,
+    /// against the combined diff. A merge commit is checked under
+    /// `rules.merge` before the commits it brings in, and rebased commits
+    /// are checked one by one.
+    pub fn check_queue(
+        &self,
+        target: &str,
+        head: &str,
+        pr: &PullRequest,
+        strategy: QueueStrategy,
+    ) -> Result<Vec<ValidationResult>> {
+        let repo = git::open_repo()?;
+        let range = format!("{}..{}", target, head);
+        let config = &self.config.rules.queue;
+
+        let (message, verdict, mut results) = match strategy {
+            QueueStrategy::Rebase => return self.check_range(&range),
+            QueueStrategy::Squash => {
+                let commits: Vec<String> = repo
+                    .get_commits_in_range(&range)?
+                    .into_iter()
+                    .map(|(_, message)| message)
+                    .collect();
+                let message = queue::squash_message(config, pr, &commits)?;
+                (message, MergeVerdict::Validate, Vec::new())
+            }
+            QueueStrategy::Merge => {
+                let message = queue::merge_message(config, pr)?;
+                let verdict = merge::check_merge(&message, &self.config.rules.merge);
+                (message, verdict, self.check_range(&range)?)
+            }
+        };
+
+        let mut diff = None;
+        if self.needs_diff() {
+            diff = Some(queue::landed_diff(&repo, target, head)?);
+        }
+        results.insert(0, self.check_uncommitted(message, verdict, || diff));
+        Ok(results)
+    }
+
+    /// Check a message under a merge verdict, loading the diff only when
+    /// the message is validated and a rule needs it.
+    fn check_uncommitted(
+        &self,
+        message: String,
+        verdict: MergeVerdict,
+        diff: impl FnOnce() -> Option<DiffInfo>,
+    ) -> ValidationResult {
+        let mut result = match verdict {
+            MergeVerdict::Exempt => ValidationResult::new(message),
+            MergeVerdict::Rejected(issue) => {
+                let mut result = ValidationResult::new(message);
+                result.errors.push(issue);
+                result
+            }
+            MergeVerdict::Validate => match self.parse(&message) {
+                Ok(parsed) => {
+                    let diff = if self.needs_diff() { diff() } else { None };
+                    self.validate_with_diff(&parsed, diff.as_ref())
+                }
+                Err(e) => parse_error_result(&message, &e.to_string()),
+            },
+        };
+        result.config_hash = Some(self.config_hash.clone());
+        result
+    }
+
     /// Check a specific commit by reference.
     pub fn check_commit(&self, reference: &str) -> Result<ValidationResult> {
         let message = git::get_commit_message(reference)?;
-        self.validate_string(&message)
+        self.validate_commit(reference, &message)
     }
 
     /// Check a range of commits.
+    ///
+    /// Every commit is checked against the `rules.branch` rules of the
+    /// engine's branch, the current one unless [`RuleEngine::with_branch`]
+    /// names the branch the range is headed for, not the branch each commit
+    /// was first made on.
+    ///
+    /// Merge commits are checked according to `rules.merge`. With
+    /// [`RuleEngine::with_cache`], commits that passed before under the same
+    /// rules aren't checked again.
     pub fn check_range(&self, range: &str) -> Result<Vec<ValidationResult>> {
-        let commits = git::get_commit_range(range)?;
+        let repo = git::open_repo()?;
+        let policy = &self.config.rules.merge;
+        let foxtrots = if policy.forbid_foxtrot {
+            merge::foxtrot_merges(&repo, range)?
+        } else {
+            Vec::new()
+        };
+        let commits = repo.get_commits_in_range(range)?;
+        let progress = Progress::bar(commits.len(), "Checking commits");
         let mut results = Vec::new();
 
+        // Custom rules and plugins can't be hashed, so their verdicts aren't cached
+        let cache = (self.cache && self.custom_rules.is_empty() && self.plugins.is_none())
+            .then(|| self.check_cache());
+        let mut cached = 0;
+
         for (oid, message) in commits {
-            let mut result = self.validate_string(&message)?;
-            result.commit_sha = Some(oid);
+            interrupt::checkpoint();
+            progress.inc();
+            let is_merge = repo
+                .inner()
+                .find_commit(oid)
+                .map(|c| c.parent_count() > 1)
+                .unwrap_or(false);
+            let verdict = if is_merge {
+                merge::check_merge(&message, policy)
+            } else {
+                MergeVerdict::Validate
+            };
+
+            let sha = oid.to_string();
+            let mut result = match verdict {
+                MergeVerdict::Exempt => ValidationResult::new(message),
+                MergeVerdict::Rejected(issue) => {
+                    let mut result = ValidationResult::new(message);
+                    result.errors.push(issue);
+                    result
+                }
+                MergeVerdict::Validate
+                    if cache.as_ref().is_some_and(|cache| cache.passed(&repo, oid)) =>
+                {
+                    cached += 1;
+                    ValidationResult::new(message)
+                }
+                // A message that does not parse fails on its own instead of
+                // aborting the whole range
+                MergeVerdict::Validate => match self.validate_commit(&sha, &message) {
+                    Ok(result) => {
+                        if let Some(ref cache) = cache {
+                            cache.record(&repo, oid, &result);
+                        }
+                        result
+                    }
+                    Err(CkError::Commit(e)) => parse_error_result(&message, &e.to_string()),
+                    Err(e) => return Err(e),
+                },
+            };
+            if foxtrots.contains(&oid) {
+                result.errors.push(merge::foxtrot_issue());
+            }
+            result.commit_sha = Some(sha);
+            result.config_hash = Some(self.config_hash.clone());
             results.push(result);
         }
 
+        if cached > 0 {
+            tracing::debug!(
+                "Skipped {} commit(s) that passed under the same rules",
+                cached
+            );
+        }
         Ok(results)
     }
 
-    /// Get the current branch rules.
-    pub fn get_branch_rules(&self) -> Option<&crate::config::BranchRuleConfig> {
-        let branch = git::get_branch_name().ok()?;
+    /// The verdict cache for range checks on the engine's branch.
+    fn check_cache(&self) -> CheckCache {
+        let config = self.effective_config(self.branch.as_deref(), &[]);
+        let language = &config.rules.language;
+        let history_language = match language.require {
+            Some(RequiredLanguage::History) => self.history_language.get(language.min_words),
+            _ => None,
+        };
+        CheckCache::new(
+            &config,
+            self.branch.as_deref(),
+            history_language.map(|lang| lang.code()),
+        )
+    }
 
-        // Check for exact match first
-        if let Some(rules) = self.config.rules.branch.get(&branch) {
-            return Some(rules);
-        }
+    /// Validate the message of a commit, loading its diff when needed.
+    fn validate_commit(&self, reference: &str, message: &str) -> Result<ValidationResult> {
+        let parsed = self.parse(message)?;
+        let diff = if self.needs_diff() {
+            Some(git::get_diff(reference)?)
+        } else {
+            None
+        };
+        Ok(self.validate_with_diff(&parsed, diff.as_ref()))
+    }
 
-        // Check for pattern match (e.g., "feature/*")
-        for (pattern, rules) in &self.config.rules.branch {
-            if pattern.contains('*') {
-                let glob = glob::Pattern::new(pattern).ok()?;
-                if glob.matches(&branch) {
-                    return Some(rules);
-                }
-            }
-        }
+    /// Get the current branch rules.
+    pub fn get_branch_rules(&self) -> Option<&BranchRuleConfig> {
+        self.config.rules.branch_rules(self.branch.as_deref()?)
+    }
 
-        None
+    /// Whether the branch requires signed commits.
+    pub fn requires_signing(&self) -> bool {
+        self.get_branch_rules()
+            .and_then(|rules| rules.require_signed)
+            .unwrap_or(false)
     }
 }
 
+/// A result for a message that does not parse.
+fn parse_error_result(message: &str, error: &str) -> ValidationResult {
+    let mut result = ValidationResult::new(message.to_string());
+    result.errors.push(ValidationIssue {
+        code: "parse-error".to_string(),
+        message: error.to_string(),
+        suggestion: Some("Use the format: type(scope): subject".to_string()),
+        is_error: true,
+        line: Some(1),
+        span: None,
+    });
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,6 +492,7 @@ mod tests {
         let message = CommitMessage {
             commit_type: CommitType::Feat,
             scope: Some("core".to_string()),
+            emoji: None,
             subject: "add new feature".to_string(),
             body: None,
             footer: None,
@@ -140,6 +511,7 @@ mod tests {
         let message = CommitMessage {
             commit_type: CommitType::Feat,
             scope: None,
+            emoji: None,
             subject: "a".repeat(100), // Way too long
             body: None,
             footer: None,
@@ -162,4 +534,133 @@ mod tests {
         let result = engine.validate_string("wip: work in progress").unwrap();
         assert!(!result.is_valid());
     }
+
+    #[test]
+    fn test_check_message_drops_comments() {
+        let engine = RuleEngine::new(CkConfig::default());
+        let text = "feat(cli): add export command\n\
+                    # Please enter the commit message for your changes.\n\
+                    # ------------------------ >8 ------------------------\n\
+                    diff --git a/src/cli.rs b/src/cli.rs\n";
+        let result = engine.check_message(text);
+        assert!(result.is_valid());
+        assert_eq!(result.message, "feat(cli): add export command");
+
+        let result = engine.check_message("Add export command\n# comment\n");
+        assert_eq!(result.errors[0].code, "parse-error");
+    }
+
+    #[test]
+    fn test_check_title() {
+        let mut config = CkConfig::default();
+        config.rules.require_body = true;
+        let engine = RuleEngine::new(config);
+
+        assert!(engine
+            .check_title("feat(cli): add export command")
+            .is_valid());
+        let result = engine.check_title("Add export command");
+        assert_eq!(result.errors[0].code, "parse-error");
+    }
+
+    #[test]
+    fn test_validate_header_ignores_body_rules() {
+        let mut config = CkConfig::default();
+        config.rules.require_body = true;
+        let engine = RuleEngine::new(config);
+
+        assert!(!engine
+            .validate_string("feat: add new parser")
+            .unwrap()
+            .is_valid());
+        assert!(engine
+            .validate_header("feat: add new parser")
+            .unwrap()
+            .is_valid());
+    }
+
+    #[test]
+    fn test_branch_rules() {
+        let mut config = CkConfig::default();
+        config.rules.branch.insert(
+            "main".to_string(),
+            BranchRuleConfig {
+                forbid: vec!["chore".to_string()],
+                require_body: Some(true),
+                require_signed: Some(true),
+                ..Default::default()
+            },
+        );
+        let main = RuleEngine::new(config.clone()).with_branch("main");
+        let feature = RuleEngine::new(config).with_branch("feature/x");
+
+        let result = main.validate_string("chore: bump dependencies").unwrap();
+        let codes: Vec<_> = result.errors.iter().map(|e| e.code.as_str()).collect();
+        assert_eq!(codes, ["type-forbidden", "body-required"]);
+        assert_eq!(
+            result.errors[0].message,
+            "Commit type 'chore' is forbidden on main"
+        );
+        assert!(main.requires_signing());
+
+        assert!(feature
+            .validate_string("chore: bump dependencies")
+            .unwrap()
+            .is_valid());
+        assert!(!feature.requires_signing());
+    }
+
+    /// Flags commits that change more lines than the message admits to.
+    #[derive(Debug)]
+    struct DiffSizeRule;
+
+    impl Rule for DiffSizeRule {
+        fn check(&self, context: &RuleContext) -> Option<ValidationIssue> {
+            let diff = context.diff?;
+            (diff.stats.total_lines_changed() > 100).then(|| ValidationIssue {
+                code: "diff-too-large".to_string(),
+                message: "Commit changes more than 100 lines".to_string(),
+                suggestion: None,
+                is_error: true,
+                line: None,
+                span: None,
+            })
+        }
+
+        fn name(&self) -> &str {
+            "diff-too-large"
+        }
+    }
+
+    #[test]
+    fn test_custom_types_stay_with_their_config() {
+        let mut config = CkConfig::default();
+        config.types.push(crate::config::TypeDefinition {
+            name: "deps".to_string(),
+            description: "Dependency updates".to_string(),
+            emoji: None,
+        });
+        let with_deps = RuleEngine::new(config);
+        let without = RuleEngine::new(CkConfig::default());
+
+        assert!(with_deps
+            .validate_string("deps: bump serde")
+            .unwrap()
+            .is_valid());
+        assert!(without.validate_string("deps: bump serde").is_err());
+    }
+
+    #[test]
+    fn test_custom_rule_sees_diff() {
+        let mut engine = RuleEngine::new(CkConfig::default());
+        assert!(!engine.needs_diff());
+        engine.add_rule(Box::new(DiffSizeRule));
+        assert!(engine.needs_diff());
+        let message = CommitMessage::parse("feat: add new parser").unwrap();
+
+        let mut diff = DiffInfo::empty();
+        diff.stats.lines_added = 150;
+        assert!(engine.validate(&message).is_valid());
+        assert!(!engine.validate_with_diff(&message, Some(&diff)).is_valid());
+    }
 }
@@ -0,0 +1,245 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Example commit messages that obey the configuration.
+//!
+//! Examples are built from canned subjects and kept only if they pass the
+//! configured rules, so they can be pasted into docs or used as fixtures
+//! by checks written against ck's format.
+
+use crate::commit::CommitMessage;
+use crate::config::{CkConfig, CommitType};
+use crate::error::{CkError, Result};
+
+use super::engine::RuleEngine;
+
+/// Subjects per type, tried in order.
+const VARIANTS: usize = 3;
+
+/// Scope used when the configuration doesn't suggest one.
+const FALLBACK_SCOPE: &str = "parser";
+
+/// What to generate examples for.
+#[derive(Debug, Clone, Default)]
+pub struct ExampleOptions {
+    /// Type of every example; all allowed types in turn when unset.
+    pub commit_type: Option<CommitType>,
+    /// Scope of every example; the first configured scope when unset.
+    pub scope: Option<String>,
+    /// Mark the examples as breaking changes.
+    pub breaking: bool,
+    /// Number of examples.
+    pub count: usize,
+}
+
+/// Generate up to `options.count` examples that pass the rules.
+///
+/// Fails with the rule findings when no candidate passes, e.g. because the
+/// requested type or scope is not allowed.
+pub fn generate(config: &CkConfig, options: &ExampleOptions) -> Result<Vec<CommitMessage>> {
+    let engine = RuleEngine::new(config.clone());
+    let mut examples = Vec::new();
+    let mut first_failure = None;
+
+    for candidate in candidates(config, options) {
+        if examples.len() == options.count {
+            break;
+        }
+        let result = engine.validate(&candidate);
+        if result.is_valid() {
+            examples.push(candidate);
+        } else if first_failure.is_none() {
+            first_failure = Some(result);
+        }
+    }
+
+    match first_failure {
+        Some(result) if examples.is_empty() => {
+            let findings: Vec<String> = result
+                .errors
+                .iter()
+                .map(|issue| format!("{}: {}", issue.code, issue.message))
+                .collect();
+            Err(CkError::WithContext {
+                code: "example-none-pass",
+                context: "example".to_string(),
+                message: format!(
+                    "No example passes the configured rules ({})",
+                    findings.join("; ")
+                ),
+            })
+        }
+        _ => Ok(examples),
+    }
+}
+
+/// Candidate messages: the first subject of every type, then the second,
+/// and so on.
+fn candidates(config: &CkConfig, options: &ExampleOptions) -> Vec<CommitMessage> {
+    let rules = &config.rules;
+    let types: Vec<CommitType> = match &options.commit_type {
+        Some(commit_type) => vec![commit_type.clone()],
+        None => config
+            .allowed_type_names()
+            .iter()
+            .filter(|name| !rules.forbidden_types.contains(name))
+            .filter_map(|name| config.commit_type(name))
+            .collect(),
+    };
+    let scope = options
+        .scope
+        .clone()
+        .or_else(|| rules.scope.allowed.first().cloned())
+        .or_else(|| config.monorepo.packages.first().map(|p| p.scope.clone()))
+        .unwrap_or_else(|| FALLBACK_SCOPE.to_string());
+
+    let mut candidates = Vec::new();
+    for variant in 0..VARIANTS {
+        for commit_type in &types {
+            let mut message =
+                CommitMessage::new(commit_type.clone(), subject(commit_type, variant))
+                    .with_scope(scope.as_str())
+                    .with_breaking(options.breaking);
+
+            let sections = rules.body_sections_for(commit_type.as_str());
+            if rules.require_body || !sections.is_empty() || options.breaking {
+                let mut body = vec!["Explain what changed and why.".to_string()];
+                body.extend(sections.iter().map(|s| format!("{} ...", s.header)));
+                message = message.with_body(body.join("\n\n"));
+            }
+            if options.breaking {
+                message = message.with_footer("BREAKING CHANGE: describe what callers must change");
+            }
+            candidates.push(message);
+        }
+    }
+    candidates
+}
+
+/// A plausible subject for an example of `commit_type`.
+fn subject(commit_type: &CommitType, variant: usize) -> &'static str {
+    let subjects: [&str; VARIANTS] = match commit_type {
+        CommitType::Feat => [
+            "add support for nested tables",
+            "allow exporting reports as csv",
+            "add a retry limit to uploads",
+        ],
+        CommitType::Fix => [
+            "handle empty input without panicking",
+            "keep trailing newlines when saving",
+            "retry requests after a timeout",
+        ],
+        CommitType::Docs => [
+            "describe the release process",
+            "document the config file format",
+            "fix broken links in the guide",
+        ],
+        CommitType::Style => [
+            "format imports consistently",
+            "remove trailing whitespace",
+            "align match arms",
+        ],
+        CommitType::Refactor => [
+            "split tokenizer into its own module",
+            "extract config loading from main",
+            "replace manual loops with iterators",
+        ],
+        CommitType::Perf => [
+            "cache compiled patterns between runs",
+            "avoid cloning large buffers",
+            "stream files instead of reading them whole",
+        ],
+        CommitType::Test => [
+            "cover unterminated string literals",
+            "add cases for empty archives",
+            "check error messages for bad input",
+        ],
+        CommitType::Revert => [
+            "undo the cache size change",
+            "restore the previous retry policy",
+            "back out the new file layout",
+        ],
+        CommitType::Ci => [
+            "run tests on every pull request",
+            "cache dependencies between jobs",
+            "add a nightly build",
+        ],
+        _ => [
+            "update the build configuration",
+            "bump dependencies to latest",
+            "clean up release scripts",
+        ],
+    };
+    subjects[variant % VARIANTS]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(commit_type: Option<CommitType>, count: usize) -> ExampleOptions {
+        ExampleOptions {
+            commit_type,
+            count,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_generate_for_type_and_scope() {
+        let config = CkConfig::default();
+        let examples = generate(
+            &config,
+            &ExampleOptions {
+                scope: Some("api".to_string()),
+                ..options(Some(CommitType::Fix), 2)
+            },
+        )
+        .unwrap();
+        let headers: Vec<String> = examples.iter().map(CommitMessage::header).collect();
+        assert_eq!(
+            headers,
+            vec![
+                "fix(api): handle empty input without panicking",
+                "fix(api): keep trailing newlines when saving",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_cycles_allowed_types() {
+        let mut config = CkConfig::default();
+        config.rules.require_body = true;
+        config.rules.scope.allowed = vec!["cli".to_string()];
+        let examples = generate(&config, &options(None, 3)).unwrap();
+        let types: Vec<&str> = examples.iter().map(|m| m.commit_type.as_str()).collect();
+        assert_eq!(types, vec!["feat", "fix", "docs"]);
+        assert!(examples.iter().all(|m| m.scope.as_deref() == Some("cli")));
+        assert!(examples.iter().all(|m| m.body.is_some()));
+    }
+
+    #[test]
+    fn test_generate_breaking() {
+        let config = CkConfig::default();
+        let examples = generate(
+            &config,
+            &ExampleOptions {
+                breaking: true,
+                ..options(Some(CommitType::Feat), 1)
+            },
+        )
+        .unwrap();
+        let formatted = examples[0].format();
+        assert!(formatted.starts_with("feat(parser)!: add support for nested tables"));
+        assert!(formatted.contains("BREAKING CHANGE: "));
+    }
+
+    #[test]
+    fn test_generate_rejects_forbidden_type() {
+        let config = CkConfig::default();
+        let err = generate(&config, &options(Some(CommitType::Wip), 1)).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No example passes the configured rules (type-"));
+    }
+}
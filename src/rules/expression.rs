@@ -0,0 +1,142 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Rules written as CEL expressions.
+//!
+//! Each rule in `[rules.expressions]` is evaluated against the JSON form
+//! of the [`RuleContext`]. A rule passes when its expression evaluates to
+//! `true`.
+
+use std::collections::BTreeMap;
+
+use super::cel::{self, Value};
+use super::context::RuleContext;
+use super::validator::ValidationIssue;
+
+/// Prefix of the issue codes reported by expression rules.
+pub const CODE_PREFIX: &str = "expr:";
+
+/// Evaluate every expression rule against a commit.
+pub fn check_expressions(context: &RuleContext) -> Vec<ValidationIssue> {
+    let rules = &context.config.rules.expressions;
+    if rules.is_empty() {
+        return Vec::new();
+    }
+
+    let vars = match Value::from(&context.to_json()) {
+        Value::Map(vars) => vars,
+        _ => BTreeMap::new(),
+    };
+
+    let mut issues = Vec::new();
+    for (name, rule) in rules {
+        let code = format!("{}{}", CODE_PREFIX, name);
+        let (message, is_error) = match cel::eval(&rule.expr, &vars) {
+            Ok(Value::Bool(true)) => continue,
+            Ok(Value::Bool(false)) => (
+                rule.message
+                    .clone()
+                    .unwrap_or_else(|| format!("Commit violates rule '{}'", name)),
+                !rule.warning,
+            ),
+            Ok(_) => ("Expression must evaluate to a boolean".to_string(), true),
+            Err(e) => (format!("Expression failed: {}", e), true),
+        };
+
+        issues.push(ValidationIssue {
+            code,
+            message,
+            suggestion: None,
+            is_error,
+            line: None,
+            span: None,
+        });
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commit::CommitMessage;
+    use crate::config::{CkConfig, ExpressionRuleConfig};
+    use crate::git::diff::{ChangeType, DiffInfo, FileChange};
+    use crate::rules::context::CiEnvironment;
+    use std::path::PathBuf;
+
+    fn config(expr: &str) -> CkConfig {
+        let mut config = CkConfig::default();
+        config.rules.expressions.insert(
+            "perf-bench".to_string(),
+            ExpressionRuleConfig {
+                expr: expr.to_string(),
+                message: Some("perf commits must update a benchmark".to_string()),
+                warning: false,
+            },
+        );
+        config
+    }
+
+    fn diff(path: &str) -> DiffInfo {
+        let mut diff = DiffInfo::empty();
+        diff.files.push(FileChange {
+            path: PathBuf::from(path),
+            change_type: ChangeType::Modified,
+            lines_added: 3,
+            lines_removed: 1,
+            is_binary: false,
+            old_path: None,
+        });
+        diff
+    }
+
+    #[test]
+    fn test_expression_rule() {
+        let config = config(
+            r#"commit.type != "perf" || diff.files.exists(f, f.path.startsWith("benches/"))"#,
+        );
+        let message = CommitMessage::parse("perf(lexer): skip whitespace faster").unwrap();
+
+        let diff_without_bench = diff("src/lexer.rs");
+        let context = RuleContext::new(&message, &config).with_diff(Some(&diff_without_bench));
+        let issues = check_expressions(&context);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "expr:perf-bench");
+        assert_eq!(issues[0].message, "perf commits must update a benchmark");
+        assert!(issues[0].is_error);
+
+        let diff_with_bench = diff("benches/lexer.rs");
+        let context = RuleContext::new(&message, &config).with_diff(Some(&diff_with_bench));
+        assert!(check_expressions(&context).is_empty());
+    }
+
+    #[test]
+    fn test_expression_errors_are_reported() {
+        let message = CommitMessage::parse("feat: add the export command").unwrap();
+
+        let failing = config("commit.nope == 1");
+        let issues = check_expressions(&RuleContext::new(&message, &failing));
+        assert!(issues[0].message.starts_with("Expression failed"));
+
+        let not_bool = config("size(commit.subject)");
+        let issues = check_expressions(&RuleContext::new(&message, &not_bool));
+        assert_eq!(issues[0].message, "Expression must evaluate to a boolean");
+    }
+
+    #[test]
+    fn test_expression_sees_branch_ci_and_packages() {
+        let message = CommitMessage::parse("feat: add the export command").unwrap();
+        let config =
+            config(r#"ci.pull_request && ci.target_branch == branch && "cli" in packages"#);
+        let ci = CiEnvironment::from_env(|key| match key {
+            "GITHUB_ACTIONS" => Some("true".to_string()),
+            "GITHUB_BASE_REF" => Some("main".to_string()),
+            _ => None,
+        });
+        let context = RuleContext::new(&message, &config)
+            .with_branch(Some("main".to_string()))
+            .with_ci(ci)
+            .with_packages(vec!["cli".to_string()]);
+        assert!(check_expressions(&context).is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Subject language, configured under `[rules.language]`.
+//!
+//! Detection is statistical, so only the prose words of a subject are
+//! looked at (identifiers, paths and `code` are dropped), subjects that are
+//! too short are not checked, and a subject is only flagged when it reads
+//! clearly as another language when weighed against the required one.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use whatlang::{Detector, Lang};
+
+use crate::commit::CommitMessage;
+use crate::config::{LanguageConfig, RequiredLanguage};
+use crate::git;
+
+use super::validator::ValidationIssue;
+
+/// Number of commits read to find the language of the history.
+const HISTORY_DEPTH: usize = 100;
+
+/// Subjects that must be detected before the history has a language.
+const HISTORY_MIN_SUBJECTS: usize = 5;
+
+/// Confidence a subject needs in another language, weighed against the
+/// required one alone, to be flagged.
+const MIN_CONFIDENCE: f64 = 0.5;
+
+/// The prose of `text`: words made only of letters, apostrophes and
+/// hyphens, outside backticks.
+fn prose(text: &str) -> Vec<&str> {
+    text.split('`')
+        .step_by(2)
+        .flat_map(str::split_whitespace)
+        .map(|word| word.trim_matches(|c: char| matches!(c, ',' | '.' | ':' | ';' | '!' | '?')))
+        .filter(|word| {
+            !word.is_empty()
+                && word
+                    .chars()
+                    .all(|c| c.is_alphabetic() || c == '\'' || c == '-')
+        })
+        .collect()
+}
+
+/// The prose of `text` as one string, when it has at least `min_words`
+/// words.
+fn prose_text(text: &str, min_words: usize) -> Option<String> {
+    let words = prose(text);
+    (words.len() >= min_words).then(|| words.join(" "))
+}
+
+/// The most likely language of `text`, when it has at least `min_words`
+/// words of prose.
+pub fn detect(text: &str, min_words: usize) -> Option<Lang> {
+    whatlang::detect_lang(&prose_text(text, min_words)?)
+}
+
+/// The language `text` is written in when it is clearly not `required`.
+pub fn other_language(text: &str, required: Lang, min_words: usize) -> Option<Lang> {
+    let prose = prose_text(text, min_words)?;
+    let detected = whatlang::detect_lang(&prose).filter(|lang| *lang != required)?;
+    Detector::with_allowlist(vec![required, detected])
+        .detect(&prose)
+        .filter(|info| info.lang() == detected && info.confidence() >= MIN_CONFIDENCE)
+        .map(|info| info.lang())
+}
+
+/// The language most of `subjects` are written in, when enough of them
+/// can be told.
+pub fn dominant<'a>(subjects: impl IntoIterator<Item = &'a str>, min_words: usize) -> Option<Lang> {
+    let mut counts: HashMap<Lang, usize> = HashMap::new();
+    for lang in subjects.into_iter().filter_map(|s| detect(s, min_words)) {
+        *counts.entry(lang).or_default() += 1;
+    }
+    let detected: usize = counts.values().sum();
+    if detected < HISTORY_MIN_SUBJECTS {
+        return None;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(lang, count)| (*count, std::cmp::Reverse(lang.code())))
+        .map(|(lang, _)| lang)
+}
+
+/// The language of a repository's recent subjects, kept by a rule engine
+/// so the history is read once rather than for every message. The answer
+/// is read again when the repository, its `HEAD` or `min_words` changes.
+#[derive(Debug, Default)]
+pub struct HistoryLanguage {
+    cached: Mutex<Option<(HistoryKey, Option<Lang>)>>,
+}
+
+/// What the history language was computed for: workdir, `HEAD`, `min_words`.
+type HistoryKey = (PathBuf, Option<git2::Oid>, usize);
+
+impl HistoryLanguage {
+    /// The language of the current repository's recent subjects.
+    pub fn get(&self, min_words: usize) -> Option<Lang> {
+        let repo = git::open_repo().ok()?;
+        let head = repo.head_commit().ok().map(|commit| commit.id());
+        let key = (repo.workdir().to_path_buf(), head, min_words);
+
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_key, lang)) = cached.as_ref() {
+            if *cached_key == key {
+                return *lang;
+            }
+        }
+        let messages = repo
+            .recent_commit_messages(HISTORY_DEPTH)
+            .unwrap_or_default();
+        let subjects: Vec<String> = messages
+            .iter()
+            .map(|m| match CommitMessage::parse(m) {
+                Ok(parsed) => parsed.subject,
+                Err(_) => m.lines().next().unwrap_or("").to_string(),
+            })
+            .collect();
+        let lang = dominant(subjects.iter().map(String::as_str), min_words);
+        *cached = Some((key, lang));
+        lang
+    }
+}
+
+/// The language `config` requires. `history` needs the engine's
+/// [`HistoryLanguage`] and is off without one.
+fn required(config: &LanguageConfig, history: Option<&HistoryLanguage>) -> Option<Lang> {
+    match config.require? {
+        RequiredLanguage::Code(lang) => Some(lang),
+        RequiredLanguage::History => history?.get(config.min_words),
+    }
+}
+
+/// Check that the subject is written in the required language.
+pub fn check_language(
+    message: &CommitMessage,
+    config: &LanguageConfig,
+    history: Option<&HistoryLanguage>,
+) -> Option<ValidationIssue> {
+    let required = required(config, history)?;
+    let detected = other_language(&message.subject, required, config.min_words)?;
+
+    Some(ValidationIssue {
+        code: "subject-language".to_string(),
+        message: format!(
+            "Subject looks like {} rather than {}",
+            detected.eng_name(),
+            required.eng_name()
+        ),
+        suggestion: Some(format!("Write the subject in {}", required.eng_name())),
+        is_error: true,
+        line: Some(1),
+        span: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CommitType;
+
+    fn config(require: &str) -> LanguageConfig {
+        LanguageConfig {
+            require: Some(require.parse().unwrap()),
+            ..LanguageConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_prose() {
+        assert_eq!(
+            prose("handle `Option::None` in parse_args, finally."),
+            ["handle", "in", "finally"]
+        );
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            detect("handle empty responses from the upload server", 4),
+            Some(Lang::Eng)
+        );
+        assert_eq!(
+            detect("corrige la gestion des réponses vides du serveur", 4),
+            Some(Lang::Fra)
+        );
+        assert_eq!(detect("bump serde", 4), None);
+    }
+
+    #[test]
+    fn test_other_language() {
+        assert_eq!(
+            other_language("behebe den Absturz beim Laden der Datei", Lang::Eng, 4),
+            Some(Lang::Deu)
+        );
+        for english in [
+            "rename config loader to settings",
+            "drop support for old node versions",
+            "fix crash when loading the file",
+        ] {
+            assert_eq!(other_language(english, Lang::Eng, 4), None, "{}", english);
+        }
+    }
+
+    #[test]
+    fn test_dominant() {
+        let subjects = [
+            "handle empty responses from the upload server",
+            "add retries to the export command",
+            "document the configuration file format",
+            "remove the deprecated login flow entirely",
+            "speed up the lookup of cached packages",
+            "corrige la gestion des réponses vides du serveur",
+            "bump serde",
+        ];
+        assert_eq!(dominant(subjects, 4), Some(Lang::Eng));
+        assert_eq!(dominant(subjects.into_iter().take(3), 4), None);
+    }
+
+    #[test]
+    fn test_check_language() {
+        let english = CommitMessage::new(CommitType::Fix, "handle empty responses from the server");
+        let french = CommitMessage::new(
+            CommitType::Fix,
+            "corrige la gestion des réponses vides du serveur",
+        );
+        let short = CommitMessage::new(CommitType::Fix, "corrige serde");
+
+        assert!(check_language(&english, &config("eng"), None).is_none());
+        assert!(check_language(&short, &config("eng"), None).is_none());
+        let issue = check_language(&french, &config("eng"), None).unwrap();
+        assert_eq!(issue.code, "subject-language");
+        assert_eq!(
+            issue.message,
+            "Subject looks like French rather than English"
+        );
+
+        assert!(check_language(&french, &config("history"), None).is_none());
+        assert!(check_language(&french, &LanguageConfig::default(), None).is_none());
+    }
+
+    #[test]
+    fn test_required_language_parse() {
+        assert_eq!(
+            "ENG".parse::<RequiredLanguage>(),
+            Ok(RequiredLanguage::Code(Lang::Eng))
+        );
+        assert_eq!(
+            "history".parse::<RequiredLanguage>(),
+            Ok(RequiredLanguage::History)
+        );
+        assert!("klingon".parse::<RequiredLanguage>().is_err());
+        assert!(toml::from_str::<LanguageConfig>("require = \"klingon\"").is_err());
+    }
+}
@@ -0,0 +1,198 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Policy for merge commits in range checks.
+//!
+//! Merge commits are identified by having more than one parent. Git's
+//! generated subjects (`Merge branch ...`, `Merge pull request ...`) can be
+//! allowed or rejected, and other merge subjects can be exempt from the
+//! rules or required to be conventional.
+//!
+//! A foxtrot merge is a merge whose first parent is not the previous tip
+//! of the target branch, which happens when `git pull` merges the remote
+//! into a local branch and the result is pushed. It swaps the mainline,
+//! so `git log --first-parent` no longer follows the branch's history.
+
+use crate::config::MergeRulesConfig;
+use crate::error::{CkError, GitError, Result};
+use crate::git::Repository;
+use git2::Oid;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::validator::ValidationIssue;
+
+lazy_static! {
+    static ref DEFAULT_MESSAGE: Regex =
+        Regex::new(r"^Merge (branch|branches|remote-tracking branch|pull request|tag|commit) ")
+            .unwrap();
+}
+
+/// How a merge commit is checked.
+#[derive(Debug, Clone)]
+pub enum MergeVerdict {
+    /// The merge is not checked against the rules.
+    Exempt,
+    /// The merge violates the merge policy.
+    Rejected(ValidationIssue),
+    /// The merge is checked like any other commit.
+    Validate,
+}
+
+/// Check if a subject is one git generates for merges.
+pub fn is_default_message(message: &str) -> bool {
+    DEFAULT_MESSAGE.is_match(message.lines().next().unwrap_or(""))
+}
+
+/// Decide how a merge commit with `message` is checked.
+pub fn check_merge(message: &str, config: &MergeRulesConfig) -> MergeVerdict {
+    if is_default_message(message) {
+        if config.allow_default_message {
+            return MergeVerdict::Exempt;
+        }
+        return MergeVerdict::Rejected(ValidationIssue {
+            code: "merge-default-message".to_string(),
+            message: "Merge commits must not use git's generated message".to_string(),
+            suggestion: Some(
+                "Describe the merge, e.g. 'chore: merge release/1.2 into main'".to_string(),
+            ),
+            is_error: true,
+            line: Some(1),
+            span: None,
+        });
+    }
+
+    if config.require_conventional {
+        MergeVerdict::Validate
+    } else {
+        MergeVerdict::Exempt
+    }
+}
+
+/// The issue reported for a foxtrot merge.
+pub fn foxtrot_issue() -> ValidationIssue {
+    ValidationIssue {
+        code: "merge-foxtrot".to_string(),
+        message: "Foxtrot merge: the first parent is not the previous branch tip".to_string(),
+        suggestion: Some(
+            "Rebase onto the target branch (git pull --rebase) instead of merging it in"
+                .to_string(),
+        ),
+        is_error: true,
+        line: None,
+        span: None,
+    }
+}
+
+/// Foxtrot merges on the first-parent history of a `from..to` range.
+///
+/// Walks the first parents of `to`. If `from` is an ancestor of `to` but
+/// is not reached that way, the merge where the walk leaves `from`'s
+/// history is a foxtrot merge.
+pub fn foxtrot_merges(repo: &Repository, range: &str) -> Result<Vec<Oid>> {
+    let (from, to) = match range.split_once("..") {
+        Some((from, to)) if !to.starts_with('.') => (from, to),
+        _ => return Ok(Vec::new()),
+    };
+    let from = match repo.get_commit(from) {
+        Ok(from) => from.id(),
+        // The base of a shallow clone may not be fetched
+        Err(_) if repo.inner().is_shallow() => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut commit = repo.get_commit(to)?;
+
+    let git = repo.inner();
+    let reaches = |descendant: Oid| -> Result<bool> {
+        Ok(descendant == from
+            || git.graph_descendant_of(descendant, from).map_err(|e| {
+                CkError::Git(GitError::CommandFailed {
+                    command: "graph_descendant_of".to_string(),
+                    message: e.message().to_string(),
+                })
+            })?)
+    };
+
+    let mut foxtrots = Vec::new();
+    while commit.id() != from && reaches(commit.id())? {
+        let first_parent = match commit.parent(0) {
+            Ok(parent) => parent,
+            Err(_) => break,
+        };
+        if commit.parent_count() > 1 && !reaches(first_parent.id())? {
+            foxtrots.push(commit.id());
+            break;
+        }
+        commit = first_parent;
+    }
+    Ok(foxtrots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixtureRepo;
+
+    #[test]
+    fn test_check_merge() {
+        let mut config = MergeRulesConfig::default();
+        assert!(is_default_message("Merge branch 'main' into feature"));
+        assert!(is_default_message(
+            "Merge pull request #12 from a/b\n\nfeat: x"
+        ));
+        assert!(!is_default_message("chore: merge release into main"));
+
+        assert!(matches!(
+            check_merge("Merge branch 'x'", &config),
+            MergeVerdict::Exempt
+        ));
+        assert!(matches!(
+            check_merge("merged stuff", &config),
+            MergeVerdict::Validate
+        ));
+
+        config.allow_default_message = false;
+        assert!(matches!(
+            check_merge("Merge branch 'x'", &config),
+            MergeVerdict::Rejected(ref issue) if issue.code == "merge-default-message"
+        ));
+        config.require_conventional = false;
+        assert!(matches!(
+            check_merge("merged stuff", &config),
+            MergeVerdict::Exempt
+        ));
+    }
+
+    #[test]
+    fn test_foxtrot_merges() {
+        let fixture = FixtureRepo::new();
+        let base = fixture.commit("chore: start").head();
+        fixture.git(&["checkout", "-q", "-b", "feature"]);
+        fixture.commit("feat: local work");
+        fixture.git(&["checkout", "-q", "main"]);
+        let main = fixture.commit("fix: remote work").head();
+
+        // Merging feature into main keeps main as the first parent
+        fixture.git(&[
+            "merge",
+            "--no-ff",
+            "-m",
+            "Merge branch 'feature'",
+            "feature",
+        ]);
+        let repo = Repository::open(fixture.path()).unwrap();
+        assert!(foxtrot_merges(&repo, &format!("{}..HEAD", main))
+            .unwrap()
+            .is_empty());
+
+        // Merging main into feature and pushing that swaps the mainline
+        fixture.git(&["checkout", "feature"]);
+        fixture.git(&["merge", "--no-ff", "-m", "Merge branch 'main'", &main]);
+        let foxtrot = fixture.head();
+        let found = foxtrot_merges(&repo, &format!("{}..HEAD", main)).unwrap();
+        assert_eq!(found, vec![Oid::from_str(&foxtrot).unwrap()]);
+        assert!(foxtrot_merges(&repo, &format!("{}..HEAD", base))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -6,10 +6,34 @@
 //! This module provides a configurable rule engine for validating
 //! commit messages against a set of rules.
 
+pub mod autofix;
+pub mod azure;
+pub mod baseline;
+pub mod bitbucket;
 mod builtin;
+mod cache;
+mod catalog;
+pub mod cel;
+pub mod checkrun;
+mod context;
+mod diff;
 mod engine;
+pub mod examples;
+pub mod expression;
+pub mod language;
+pub mod merge;
+pub mod mood;
+mod paths;
+pub mod queue;
+pub mod style_guide;
+pub mod suppress;
+pub mod ticket;
 mod validator;
+pub mod verify;
 
 pub use builtin::*;
+pub use catalog::{print_rules, RuleEntry, RuleKind, Severity};
+pub use context::{pull_request_event, pull_request_title, CiEnvironment, RuleContext};
+pub use diff::{CommitDiff, Outcome, RulesDiff};
 pub use engine::RuleEngine;
-pub use validator::{ValidationIssue, ValidationResult};
+pub use validator::{print_escalation, SuppressedIssue, ValidationIssue, ValidationResult};
@@ -0,0 +1,181 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Subject mood, checked against the verb lexicon in `verbs.txt`.
+//!
+//! The lexicon lists verbs in their imperative form, with irregular past
+//! forms after them. Regular `-s`, `-ed` and `-ing` forms are undone by
+//! rule (`migrated`, `bumping`, `stopped`, `applies`), so a word only counts
+//! as non-imperative when it conjugates back to a verb the lexicon knows.
+
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+const LEXICON: &str = include_str!("verbs.txt");
+
+lazy_static! {
+    /// Every known form, mapped to its imperative.
+    static ref VERBS: HashMap<&'static str, &'static str> = {
+        let mut verbs = HashMap::new();
+        for line in LEXICON.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut forms = line.split_whitespace();
+            let base = match forms.next() {
+                Some(base) => base,
+                None => continue,
+            };
+            verbs.insert(base, base);
+            for form in forms {
+                verbs.entry(form).or_insert(base);
+            }
+        }
+        verbs
+    };
+}
+
+/// Whether `word` is the imperative form of a known verb.
+pub fn is_imperative(word: &str) -> bool {
+    let lower = word.to_lowercase();
+    VERBS.get(lower.as_str()) == Some(&lower.as_str())
+}
+
+/// The imperative of `word` when it is another form of a known verb:
+/// `refactored` gives `refactor`, `add` and unknown words give `None`.
+pub fn imperative(word: &str) -> Option<&'static str> {
+    let lower = word.to_lowercase();
+    match VERBS.get(lower.as_str()) {
+        Some(base) if *base == lower => None,
+        Some(base) => Some(*base),
+        None => stems(&lower)
+            .into_iter()
+            .find_map(|stem| VERBS.get(stem.as_str()).filter(|base| **base == stem))
+            .copied(),
+    }
+}
+
+/// The first word of `subject` and its imperative, when the subject opens
+/// with another form of a known verb.
+///
+/// An `-s` form with a predicate right after it, or after the next word,
+/// reads as a plural noun rather than a verb (`tests are flaky`, `builds
+/// now pass`), so it is not reported. `-ed` and `-ing` forms always are.
+pub fn subject_imperative(subject: &str) -> Option<(&str, &'static str)> {
+    let mut words = subject.split_whitespace();
+    let first = words.next()?;
+    let imperative = imperative(first)?;
+    let plural_noun = first.to_lowercase().ends_with('s') && words.take(2).any(starts_predicate);
+    if plural_noun {
+        None
+    } else {
+        Some((first, imperative))
+    }
+}
+
+/// Whether `word` starts a predicate when it follows a noun phrase: `are`,
+/// `was`, `can`, `now`, `not`.
+pub fn starts_predicate(word: &str) -> bool {
+    PREDICATE_WORDS.contains(&word.to_lowercase().as_str())
+}
+
+/// Auxiliaries, negations and adverbs that follow the noun phrase a
+/// sentence opens with.
+const PREDICATE_WORDS: &[&str] = &[
+    "is", "are", "was", "were", "has", "have", "had", "can", "could", "will", "would", "should",
+    "must", "may", "might", "now", "no", "not", "don't", "doesn't", "didn't", "won't", "can't",
+];
+
+/// Candidate imperatives for a regular `-s`, `-ed` or `-ing` form, most
+/// likely first.
+fn stems(word: &str) -> Vec<String> {
+    let mut stems = Vec::new();
+    if let Some(stem) = word.strip_suffix("ies") {
+        stems.push(format!("{}y", stem));
+    }
+    if let Some(stem) = word.strip_suffix("ied") {
+        stems.push(format!("{}y", stem));
+    }
+    if let Some(stem) = word.strip_suffix("es") {
+        stems.push(stem.to_string());
+    }
+    if let Some(stem) = word.strip_suffix('s') {
+        stems.push(stem.to_string());
+    }
+    for suffix in ["ed", "ing"] {
+        if let Some(stem) = word.strip_suffix(suffix) {
+            stems.push(stem.to_string());
+            stems.push(format!("{}e", stem));
+            // stopped, mapping
+            let mut chars = stem.chars().rev();
+            if let (Some(last), Some(before)) = (chars.next(), chars.next()) {
+                if last == before {
+                    stems.push(stem[..stem.len() - last.len_utf8()].to_string());
+                }
+            }
+        }
+    }
+    stems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imperative() {
+        assert_eq!(imperative("refactored"), Some("refactor"));
+        assert_eq!(imperative("Bumping"), Some("bump"));
+        assert_eq!(imperative("migrated"), Some("migrate"));
+        assert_eq!(imperative("stopped"), Some("stop"));
+        assert_eq!(imperative("applies"), Some("apply"));
+        assert_eq!(imperative("fixes"), Some("fix"));
+        assert_eq!(imperative("uses"), Some("use"));
+        assert_eq!(imperative("wrote"), Some("write"));
+        assert_eq!(imperative("built"), Some("build"));
+    }
+
+    #[test]
+    fn test_subject_imperative() {
+        assert_eq!(
+            subject_imperative("Added retries to uploads"),
+            Some(("Added", "add"))
+        );
+        assert_eq!(
+            subject_imperative("logs rotated daily"),
+            Some(("logs", "log"))
+        );
+        assert_eq!(
+            subject_imperative("fixes flaky upload tests"),
+            Some(("fixes", "fix"))
+        );
+        assert!(subject_imperative("tests are flaky on windows").is_none());
+        assert!(subject_imperative("builds now pass on windows").is_none());
+        assert_eq!(
+            subject_imperative("cached values were stale"),
+            Some(("cached", "cache"))
+        );
+        assert_eq!(
+            subject_imperative("fixed broken link in readme"),
+            Some(("fixed", "fix"))
+        );
+        assert_eq!(
+            subject_imperative("updated generated files"),
+            Some(("updated", "update"))
+        );
+        assert!(subject_imperative("add retries").is_none());
+        assert!(subject_imperative("").is_none());
+    }
+
+    #[test]
+    fn test_imperative_leaves_other_words() {
+        for word in ["add", "Process", "address", "embed", "speed", "set"] {
+            assert!(imperative(word).is_none(), "{}", word);
+            assert!(is_imperative(word), "{}", word);
+        }
+        for word in ["need", "string", "things", "always", "readme", "is"] {
+            assert!(imperative(word).is_none(), "{}", word);
+            assert!(!is_imperative(word), "{}", word);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Rules for the paths a commit changes.
+//!
+//! Each entry in `[rules.paths]` applies to the files its glob matches.
+//! A `type` or `scope` is expected only when every changed file matches,
+//! since a commit spanning several areas can't follow all of them, while
+//! `require_scope` and `require_body` apply as soon as one file matches.
+//! Path rules need the commit's diff; without one they don't apply.
+
+use std::path::Path;
+
+use super::context::RuleContext;
+use super::validator::ValidationIssue;
+
+/// Check the changed files of a commit against `rules.paths`.
+pub fn check_path_rules(context: &RuleContext) -> Vec<ValidationIssue> {
+    let (message, rules) = (context.message, &context.config.rules);
+    let files: Vec<&Path> = match context.diff {
+        Some(diff) if !rules.paths.is_empty() => {
+            diff.files.iter().map(|file| file.path.as_path()).collect()
+        }
+        _ => return Vec::new(),
+    };
+    if files.is_empty() {
+        return Vec::new();
+    }
+
+    let commit_type = message.commit_type.as_str();
+    let header_with = |commit_type: &str, scope: Option<&str>| match scope {
+        Some(scope) => format!("{}({}): {}", commit_type, scope, message.subject),
+        None => format!("{}: {}", commit_type, message.subject),
+    };
+
+    let mut issues = Vec::new();
+    for (pattern, rule) in &rules.paths {
+        let glob = match glob::Pattern::new(pattern) {
+            Ok(glob) => glob,
+            Err(_) => continue,
+        };
+        let matched = files.iter().filter(|file| glob.matches_path(file)).count();
+        if matched == 0 {
+            continue;
+        }
+        let all_match = matched == files.len();
+
+        if let Some(ref expected) = rule.commit_type {
+            if all_match && commit_type != expected {
+                issues.push(issue(
+                    "path-type",
+                    format!(
+                        "Changes under {} should be type '{}', not '{}'",
+                        pattern, expected, commit_type
+                    ),
+                    Some(format!(
+                        "Use: {}",
+                        header_with(expected, message.scope.as_deref())
+                    )),
+                    false,
+                ));
+            }
+        }
+
+        let require_scope = rule.require_scope == Some(true);
+        match (&message.scope, &rule.scope) {
+            // A globally required scope is already reported
+            (None, expected) if require_scope && !rules.require_scope => {
+                issues.push(issue(
+                    "path-scope",
+                    format!("Changes under {} need a scope", pattern),
+                    Some(match expected {
+                        Some(expected) => {
+                            format!("Use: {}", header_with(commit_type, Some(expected)))
+                        }
+                        None => "Add a scope in parentheses: type(scope): subject".to_string(),
+                    }),
+                    true,
+                ));
+            }
+            (Some(actual), Some(expected)) if all_match && actual != expected => {
+                issues.push(issue(
+                    "path-scope",
+                    format!(
+                        "Changes under {} should use scope '{}', not '{}'",
+                        pattern, expected, actual
+                    ),
+                    Some(format!("Use: {}", header_with(commit_type, Some(expected)))),
+                    require_scope,
+                ));
+            }
+            _ => {}
+        }
+
+        if rule.require_body == Some(true) && message.body.is_none() && !rules.require_body {
+            issues.push(issue(
+                "path-body-required",
+                format!("Changes under {} need a body", pattern),
+                Some("Add a body explaining the change".to_string()),
+                true,
+            ));
+        }
+    }
+    issues
+}
+
+fn issue(
+    code: &str,
+    message: String,
+    suggestion: Option<String>,
+    is_error: bool,
+) -> ValidationIssue {
+    ValidationIssue {
+        code: code.to_string(),
+        message,
+        suggestion,
+        is_error,
+        line: Some(1),
+        span: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commit::CommitMessage;
+    use crate::config::{CkConfig, PathRuleConfig};
+    use crate::git::diff::{ChangeType, DiffInfo, FileChange};
+    use std::path::PathBuf;
+
+    fn config() -> CkConfig {
+        let mut config = CkConfig::default();
+        config.rules.paths.insert(
+            "docs/**".to_string(),
+            PathRuleConfig {
+                commit_type: Some("docs".to_string()),
+                ..Default::default()
+            },
+        );
+        config.rules.paths.insert(
+            "src/core/**".to_string(),
+            PathRuleConfig {
+                require_scope: Some(true),
+                scope: Some("core".to_string()),
+                ..Default::default()
+            },
+        );
+        config
+    }
+
+    fn diff(paths: &[&str]) -> DiffInfo {
+        let mut diff = DiffInfo::empty();
+        for path in paths {
+            diff.files.push(FileChange {
+                path: PathBuf::from(path),
+                change_type: ChangeType::Modified,
+                lines_added: 1,
+                lines_removed: 0,
+                is_binary: false,
+                old_path: None,
+            });
+        }
+        diff
+    }
+
+    fn check(header: &str, paths: &[&str]) -> Vec<ValidationIssue> {
+        let config = config();
+        let message = CommitMessage::parse(header).unwrap();
+        let diff = diff(paths);
+        check_path_rules(&RuleContext::new(&message, &config).with_diff(Some(&diff)))
+    }
+
+    #[test]
+    fn test_path_type() {
+        let issues = check("feat: describe the config", &["docs/config.md"]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "path-type");
+        assert!(!issues[0].is_error);
+        assert_eq!(
+            issues[0].suggestion.as_deref(),
+            Some("Use: docs: describe the config")
+        );
+
+        // Mixed commits aren't held to one area's type
+        assert!(check("feat: add config", &["docs/config.md", "src/config.rs"]).is_empty());
+    }
+
+    #[test]
+    fn test_path_scope() {
+        let issues = check(
+            "fix: handle empty input",
+            &["src/core/parse.rs", "README.md"],
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "path-scope");
+        assert!(issues[0].is_error);
+        assert_eq!(
+            issues[0].suggestion.as_deref(),
+            Some("Use: fix(core): handle empty input")
+        );
+
+        let issues = check("fix(cli): handle empty input", &["src/core/parse.rs"]);
+        assert_eq!(
+            issues[0].message,
+            "Changes under src/core/** should use scope 'core', not 'cli'"
+        );
+        assert!(check("fix(core): handle empty input", &["src/core/parse.rs"]).is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Messages a merge queue lands on the target branch.
+//!
+//! Merge queue bots check a candidate before it lands, and with squash
+//! merges the individual commits never reach the target branch. What
+//! matters is the message the platform composes from the pull request,
+//! as described by `[rules.queue]`. Merge commits get a subject from
+//! `merge_subject` with the pull request title as their body, as GitHub
+//! writes them.
+
+use crate::config::{QueueConfig, SquashMessage};
+use crate::error::{CkError, GitError, Result};
+use crate::git::{diff::diff_trees, DiffInfo, Repository};
+
+/// A pull request waiting in the merge queue.
+#[derive(Debug, Clone, Default)]
+pub struct PullRequest {
+    /// Title, which becomes the squashed subject.
+    pub title: Option<String>,
+    /// Description, which becomes the squashed body.
+    pub body: Option<String>,
+    /// Number, appended to squashed subjects.
+    pub number: Option<u64>,
+    /// Source branch, named in merge commit subjects.
+    pub branch: Option<String>,
+}
+
+impl PullRequest {
+    /// Fill in what is missing from the GitHub Actions event payload.
+    pub fn or_from_event(mut self) -> Self {
+        let event = match super::pull_request_event() {
+            Some(event) => event,
+            None => return self,
+        };
+        let text = |key: &str| event[key].as_str().map(str::to_string);
+        self.title = self.title.or_else(|| text("title"));
+        self.body = self.body.or_else(|| text("body"));
+        self.number = self.number.or_else(|| event["number"].as_u64());
+        self.branch = self
+            .branch
+            .or_else(|| event["head"]["ref"].as_str().map(str::to_string));
+        self
+    }
+
+    fn title(&self) -> Result<&str> {
+        self.title
+            .as_deref()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .ok_or_else(|| missing("a title (--title, CK_PR_TITLE or the GitHub event payload)"))
+    }
+}
+
+/// The message of a squash merge of `commits`, given newest first.
+pub fn squash_message(
+    config: &QueueConfig,
+    pr: &PullRequest,
+    commits: &[String],
+) -> Result<String> {
+    let message = match (config.squash_message, commits) {
+        (_, []) => return Err(missing("commits between the target and the head")),
+        (SquashMessage::Title, _) => with_body(pr.title()?, pr.body.as_deref()),
+        (SquashMessage::Commits, [single]) => single.trim().to_string(),
+        (SquashMessage::Commits, _) => {
+            let list = commits
+                .iter()
+                .rev()
+                .map(|message| format!("* {}", message.trim()))
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            with_body(pr.title()?, Some(&list))
+        }
+        (SquashMessage::FirstCommit, [.., first]) => first.trim().to_string(),
+    };
+
+    Ok(match pr.number.filter(|_| config.append_number) {
+        Some(number) => match message.split_once('\n') {
+            Some((header, rest)) => format!("{} (#{})\n{}", header, number, rest),
+            None => format!("{} (#{})", message, number),
+        },
+        None => message,
+    })
+}
+
+/// The message of the merge commit landing `pr`.
+pub fn merge_message(config: &QueueConfig, pr: &PullRequest) -> Result<String> {
+    let mut subject = config.merge_subject.clone();
+    if subject.contains("{number}") {
+        let number = pr.number.ok_or_else(|| missing("a number (--number)"))?;
+        subject = subject.replace("{number}", &number.to_string());
+    }
+    if subject.contains("{branch}") {
+        let branch = pr
+            .branch
+            .as_deref()
+            .ok_or_else(|| missing("a branch (--branch)"))?;
+        subject = subject.replace("{branch}", branch);
+    }
+    Ok(with_body(&subject, pr.title.as_deref()))
+}
+
+/// Changes `head` brings to `target`, from their merge base.
+pub fn landed_diff(repo: &Repository, target: &str, head: &str) -> Result<DiffInfo> {
+    let target = repo.get_commit(target)?;
+    let head = repo.get_commit(head)?;
+    let base = repo
+        .inner()
+        .merge_base(target.id(), head.id())
+        .and_then(|base| repo.inner().find_commit(base))
+        .map_err(|e| {
+            CkError::Git(GitError::CommandFailed {
+                command: "merge_base".to_string(),
+                message: e.message().to_string(),
+            })
+        })?;
+    diff_trees(repo, Some(base.tree_id()), head.tree_id())
+}
+
+/// `header` followed by `body` when there is one.
+fn with_body(header: &str, body: Option<&str>) -> String {
+    match body.map(str::trim).filter(|body| !body.is_empty()) {
+        Some(body) => format!("{}\n\n{}", header.trim(), body),
+        None => header.trim().to_string(),
+    }
+}
+
+fn missing(what: &str) -> CkError {
+    CkError::WithContext {
+        code: "merge-check-missing",
+        context: "merge-check".to_string(),
+        message: format!("The pull request needs {}", what),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pr() -> PullRequest {
+        PullRequest {
+            title: Some("feat(api): add export".to_string()),
+            body: Some("Adds CSV export.\n".to_string()),
+            number: Some(42),
+            branch: Some("ada/export".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_squash_message() {
+        let mut config = QueueConfig::default();
+        let commits = vec![
+            "fix typo".to_string(),
+            "feat: add export\n\nBody".to_string(),
+        ];
+        assert_eq!(
+            squash_message(&config, &pr(), &commits).unwrap(),
+            "feat(api): add export (#42)\n\nAdds CSV export."
+        );
+
+        config.squash_message = SquashMessage::Commits;
+        assert_eq!(
+            squash_message(&config, &pr(), &commits).unwrap(),
+            "feat(api): add export (#42)\n\n* feat: add export\n\nBody\n\n* fix typo"
+        );
+        assert_eq!(
+            squash_message(&config, &pr(), &commits[..1]).unwrap(),
+            "fix typo (#42)"
+        );
+
+        config.squash_message = SquashMessage::FirstCommit;
+        config.append_number = false;
+        assert_eq!(
+            squash_message(&config, &pr(), &commits).unwrap(),
+            "feat: add export\n\nBody"
+        );
+
+        config.squash_message = SquashMessage::Title;
+        let untitled = PullRequest::default();
+        assert!(squash_message(&config, &untitled, &commits).is_err());
+        assert!(squash_message(&config, &pr(), &[]).is_err());
+    }
+
+    #[test]
+    fn test_merge_message() {
+        let config = QueueConfig::default();
+        assert_eq!(
+            merge_message(&config, &pr()).unwrap(),
+            "Merge pull request #42 from ada/export\n\nfeat(api): add export"
+        );
+        let unnumbered = PullRequest {
+            number: None,
+            ..pr()
+        };
+        assert!(merge_message(&config, &unnumbered).is_err());
+    }
+}
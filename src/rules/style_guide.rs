@@ -0,0 +1,298 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Commit style guide rendered from the effective configuration.
+//!
+//! The guide is a markdown section for `CONTRIBUTING.md` describing the
+//! types, scopes, length limits and branch policies ck enforces. It sits
+//! between marker comments, so regenerating it replaces only that section
+//! and `--check` can fail CI when the docs drift from `ck.toml`.
+
+use crate::commit::CommitMessage;
+use crate::config::CkConfig;
+use std::fmt::Write;
+
+use super::examples::{generate, ExampleOptions};
+
+/// Marker opening the generated section.
+pub const START_MARKER: &str = "<!-- ck:style-guide:start -->";
+
+/// Marker closing the generated section.
+pub const END_MARKER: &str = "<!-- ck:style-guide:end -->";
+
+/// Number of example messages shown.
+const MAX_EXAMPLES: usize = 3;
+
+/// Render the style guide for `config`, wrapped in the section markers.
+pub fn render(config: &CkConfig) -> String {
+    let rules = &config.rules;
+    let mut out = String::new();
+
+    let _ = writeln!(out, "{}", START_MARKER);
+    out.push_str("## Commit Messages\n\n");
+    out.push_str(
+        "<!-- Generated from ck.toml by `ck style-guide`; edit the configuration instead. -->\n\n",
+    );
+    out.push_str(
+        "Commits follow [Conventional Commits](https://www.conventionalcommits.org/):\n\n",
+    );
+    out.push_str("```text\ntype(scope): subject\n\nbody\n\nfooters\n```\n\n");
+
+    let _ = writeln!(
+        out,
+        "- The subject is {} to {} characters long.",
+        rules.min_subject_length, rules.max_subject_length
+    );
+    if rules.require_scope || rules.scope.require {
+        out.push_str("- A scope is required.\n");
+    } else {
+        out.push_str("- A scope is optional.\n");
+    }
+    if rules.require_body {
+        out.push_str("- A body explaining the change is required.\n");
+    }
+    out.push_str("- Breaking changes add `!` after the type and a `BREAKING CHANGE:` footer.\n");
+    if !rules.suppressible.is_empty() {
+        let _ = writeln!(
+            out,
+            "- A `Ck-Disable:` footer with a justification may override {}.",
+            code_list(&rules.suppressible)
+        );
+    }
+
+    render_types(&mut out, config);
+    render_scopes(&mut out, config);
+    render_body_sections(&mut out, config);
+    render_branches(&mut out, config);
+
+    let examples = examples(config);
+    if !examples.is_empty() {
+        out.push_str("\n### Examples\n\n```text\n");
+        out.push_str(&examples.join("\n\n"));
+        out.push_str("\n```\n");
+    }
+
+    let _ = writeln!(out, "{}", END_MARKER);
+    out
+}
+
+/// Replace the marked section in `document`, or append it when absent.
+pub fn update_section(document: &str, section: &str) -> String {
+    let start = document.find(START_MARKER);
+    let end = document.find(END_MARKER).map(|end| end + END_MARKER.len());
+
+    match (start, end) {
+        (Some(start), Some(end)) if start < end => {
+            let rest = document[end..]
+                .strip_prefix('\n')
+                .unwrap_or(&document[end..]);
+            format!("{}{}{}", &document[..start], section, rest)
+        }
+        _ if document.trim().is_empty() => section.to_string(),
+        _ => format!("{}\n\n{}", document.trim_end(), section),
+    }
+}
+
+/// Allowed types with their descriptions, and forbidden types.
+fn render_types(out: &mut String, config: &CkConfig) {
+    let forbidden = &config.rules.forbidden_types;
+    let types: Vec<String> = config
+        .allowed_type_names()
+        .into_iter()
+        .filter(|name| !forbidden.contains(name))
+        .collect();
+
+    out.push_str("\n### Types\n\n| Type | Use for |\n| --- | --- |\n");
+    for name in &types {
+        let commit_type = config.commit_type(name);
+        let description = commit_type
+            .as_ref()
+            .map(|t| config.type_description(t))
+            .unwrap_or("");
+        let emoji = commit_type
+            .as_ref()
+            .and_then(|t| config.type_emoji(t))
+            .filter(|_| config.ui.emoji)
+            .map(|emoji| format!("{} ", emoji))
+            .unwrap_or_default();
+        let _ = writeln!(out, "| {}`{}` | {} |", emoji, name, description);
+    }
+    if !forbidden.is_empty() {
+        let _ = writeln!(out, "\nNot allowed: {}.", code_list(forbidden));
+    }
+}
+
+/// Allowed scopes, aliases and monorepo packages.
+fn render_scopes(out: &mut String, config: &CkConfig) {
+    let scope = &config.rules.scope;
+    let packages: Vec<String> = config
+        .monorepo
+        .packages
+        .iter()
+        .map(|p| p.scope.clone())
+        .collect();
+    if scope.allowed.is_empty()
+        && scope.forbidden.is_empty()
+        && scope.aliases.is_empty()
+        && packages.is_empty()
+    {
+        return;
+    }
+
+    out.push_str("\n### Scopes\n\n");
+    if scope.allowed.is_empty() {
+        out.push_str("Any scope naming the affected area may be used.\n");
+    } else {
+        let _ = writeln!(out, "Allowed scopes: {}.", code_list(&scope.allowed));
+    }
+    if !packages.is_empty() {
+        let _ = writeln!(out, "\nMonorepo packages: {}.", code_list(&packages));
+    }
+    if !scope.forbidden.is_empty() {
+        let _ = writeln!(out, "\nNot allowed: {}.", code_list(&scope.forbidden));
+    }
+    if !scope.aliases.is_empty() {
+        out.push_str("\nUse the preferred name for these scopes:\n\n");
+        for (alias, preferred) in &scope.aliases {
+            let _ = writeln!(out, "- `{}` → `{}`", alias, preferred);
+        }
+    }
+}
+
+/// Body sections required per type.
+fn render_body_sections(out: &mut String, config: &CkConfig) {
+    let sections = &config.rules.body_sections;
+    if sections.values().all(Vec::is_empty) {
+        return;
+    }
+
+    out.push_str("\n### Required body sections\n\n");
+    for (commit_type, sections) in sections.iter().filter(|(_, s)| !s.is_empty()) {
+        let headers: Vec<String> = sections.iter().map(|s| s.header.clone()).collect();
+        let _ = writeln!(out, "- `{}`: {}", commit_type, code_list(&headers));
+    }
+}
+
+/// Policies of branches with their own rules.
+fn render_branches(out: &mut String, config: &CkConfig) {
+    if config.rules.branch.is_empty() {
+        return;
+    }
+
+    out.push_str("\n### Branch policies\n\n| Branch | Policy |\n| --- | --- |\n");
+    for (branch, rules) in &config.rules.branch {
+        let mut policy = Vec::new();
+        if !rules.forbid.is_empty() {
+            policy.push(format!("forbids {}", code_list(&rules.forbid)));
+        }
+        if !rules.allow.is_empty() {
+            policy.push(format!("allows {}", code_list(&rules.allow)));
+        }
+        match rules.require_body {
+            Some(true) => policy.push("requires a body".to_string()),
+            Some(false) => policy.push("doesn't require a body".to_string()),
+            None => {}
+        }
+        if rules.require_signed == Some(true) {
+            policy.push("requires signed commits".to_string());
+        }
+        if policy.is_empty() {
+            policy.push("default rules".to_string());
+        }
+        let _ = writeln!(out, "| `{}` | {} |", branch, policy.join("; "));
+    }
+}
+
+/// Example messages that pass the configured rules.
+fn examples(config: &CkConfig) -> Vec<String> {
+    let options = ExampleOptions {
+        count: MAX_EXAMPLES,
+        ..Default::default()
+    };
+    generate(config, &options)
+        .unwrap_or_default()
+        .iter()
+        .map(CommitMessage::format)
+        .collect()
+}
+
+/// Items formatted as inline code, comma separated.
+fn code_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("`{}`", item))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BodySectionConfig, BranchRuleConfig};
+
+    #[test]
+    fn test_render_reflects_config() {
+        let mut config = CkConfig::default();
+        config.rules.require_scope = true;
+        config.rules.scope.allowed = vec!["cli".to_string(), "core".to_string()];
+        config.rules.body_sections.insert(
+            "fix".to_string(),
+            vec![BodySectionConfig {
+                header: "Root cause:".to_string(),
+                pattern: None,
+            }],
+        );
+        config.rules.branch.insert(
+            "main".to_string(),
+            BranchRuleConfig {
+                require_signed: Some(true),
+                ..Default::default()
+            },
+        );
+
+        let guide = render(&config);
+        assert!(guide.starts_with(START_MARKER));
+        assert!(guide.trim_end().ends_with(END_MARKER));
+        assert!(guide.contains("- The subject is 10 to 72 characters long."));
+        assert!(guide.contains("- A scope is required."));
+        assert!(guide.contains("| `feat` | A new feature |"));
+        assert!(guide.contains("Not allowed: `wip`."));
+        assert!(guide.contains("Allowed scopes: `cli`, `core`."));
+        assert!(guide.contains("- `fix`: `Root cause:`"));
+        assert!(guide.contains("| `main` | requires signed commits |"));
+        assert!(guide.contains("feat(cli): add support for nested tables"));
+        assert!(guide.contains("fix(cli): handle empty input without panicking\n\nExplain"));
+    }
+
+    #[test]
+    fn test_examples_pass_rules() {
+        use super::super::engine::RuleEngine;
+
+        let mut config = CkConfig::default();
+        config.rules.max_subject_length = 40;
+        let engine = RuleEngine::new(config.clone());
+        let examples = examples(&config);
+        assert_eq!(examples.len(), MAX_EXAMPLES);
+        for example in examples {
+            assert!(engine.validate_string(&example).unwrap().is_valid());
+        }
+    }
+
+    #[test]
+    fn test_update_section() {
+        let section = format!("{}\nnew\n{}\n", START_MARKER, END_MARKER);
+        let document = format!(
+            "# Contributing\n\n{}\nold\n{}\n\n## Testing\n",
+            START_MARKER, END_MARKER
+        );
+        assert_eq!(
+            update_section(&document, &section),
+            format!("# Contributing\n\n{}\n## Testing\n", section)
+        );
+        assert_eq!(
+            update_section("# Contributing\n", &section),
+            format!("# Contributing\n\n{}", section)
+        );
+        assert_eq!(update_section("", &section), section);
+    }
+}
@@ -0,0 +1,177 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Per-commit rule suppression with `Ck-Disable:` footers.
+//!
+//! A suppression names one or more rule codes and must be followed by an
+//! indented justification line, so the override is reviewable:
+//!
+//! ```text
+//! Ck-Disable: subject-max-length
+//!   The subject quotes the upstream advisory title verbatim
+//! ```
+//!
+//! Only rules listed in `rules.suppressible` can be suppressed.
+
+use crate::config::CkConfig;
+use serde::Serialize;
+
+use super::validator::{SuppressedIssue, ValidationIssue, ValidationResult};
+
+/// Footer token for suppressions.
+pub const SUPPRESS_TOKEN: &str = "Ck-Disable";
+
+/// Code reported for suppressions that are not allowed.
+pub const INVALID_CODE: &str = "suppression-invalid";
+
+/// A `Ck-Disable:` footer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Suppression {
+    /// Suppressed rule codes.
+    pub rules: Vec<String>,
+    /// Why the rules are suppressed.
+    pub justification: Option<String>,
+}
+
+/// Parse every `Ck-Disable:` footer in a message.
+pub fn parse(message: &str) -> Vec<Suppression> {
+    let prefix = format!("{}:", SUPPRESS_TOKEN);
+    let mut suppressions: Vec<Suppression> = Vec::new();
+    let mut in_footer = false;
+
+    for line in message.lines() {
+        if let Some(rules) = line.strip_prefix(&prefix) {
+            suppressions.push(Suppression {
+                rules: rules
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|rule| !rule.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                justification: None,
+            });
+            in_footer = true;
+        } else if in_footer && line.starts_with(char::is_whitespace) && !line.trim().is_empty() {
+            // Indented lines continue the footer's justification
+            let suppression = suppressions.last_mut().expect("footer was pushed");
+            let justification = suppression.justification.get_or_insert_with(String::new);
+            if !justification.is_empty() {
+                justification.push(' ');
+            }
+            justification.push_str(line.trim());
+        } else {
+            in_footer = false;
+        }
+    }
+
+    suppressions
+}
+
+/// Move issues suppressed by the message's footers out of `result`.
+///
+/// Suppressions without a justification, or naming rules that may not be
+/// suppressed, are reported as errors and suppress nothing.
+pub fn apply(result: &mut ValidationResult, trailers: &str, config: &CkConfig) {
+    for suppression in parse(trailers) {
+        let justification = match suppression.justification {
+            Some(justification) => justification,
+            None => {
+                result.errors.push(invalid(
+                    format!(
+                        "{}: {} has no justification",
+                        SUPPRESS_TOKEN,
+                        suppression.rules.join(", ")
+                    ),
+                    "Explain the override on an indented line below the footer",
+                ));
+                continue;
+            }
+        };
+
+        for rule in suppression.rules {
+            if !config.rules.suppressible.contains(&rule) {
+                result.errors.push(invalid(
+                    format!("Rule '{}' cannot be suppressed", rule),
+                    "Add the rule to rules.suppressible to allow overriding it",
+                ));
+                continue;
+            }
+
+            for issues in [&mut result.errors, &mut result.warnings] {
+                let (suppressed, kept) = issues.drain(..).partition(|i| i.code == rule);
+                *issues = kept;
+                result
+                    .suppressed
+                    .extend(suppressed.into_iter().map(|issue| SuppressedIssue {
+                        issue,
+                        justification: justification.clone(),
+                    }));
+            }
+        }
+    }
+}
+
+fn invalid(message: String, suggestion: &str) -> ValidationIssue {
+    ValidationIssue {
+        code: INVALID_CODE.to_string(),
+        message,
+        suggestion: Some(suggestion.to_string()),
+        is_error: true,
+        line: None,
+        span: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RuleEngine;
+
+    #[test]
+    fn test_parse() {
+        let message = "Refs: #4\nCk-Disable: subject-max-length, subject-case\n  quoted upstream\n  title\nCk-Disable: body-required";
+        assert_eq!(
+            parse(message),
+            vec![
+                Suppression {
+                    rules: vec!["subject-max-length".into(), "subject-case".into()],
+                    justification: Some("quoted upstream title".into()),
+                },
+                Suppression {
+                    rules: vec!["body-required".into()],
+                    justification: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_suppression_requires_configuration_and_justification() {
+        let subject = "a".repeat(80);
+        let justified = format!(
+            "feat: {}\n\nCk-Disable: subject-max-length\n  matches the upstream advisory title",
+            subject
+        );
+
+        let mut config = CkConfig::default();
+        let result = RuleEngine::new(config.clone())
+            .validate_string(&justified)
+            .unwrap();
+        assert!(result.errors.iter().any(|i| i.code == INVALID_CODE));
+        assert!(result.errors.iter().any(|i| i.code == "subject-max-length"));
+
+        config.rules.suppressible = vec!["subject-max-length".to_string()];
+        let engine = RuleEngine::new(config);
+        let result = engine.validate_string(&justified).unwrap();
+        assert!(result.is_valid());
+        assert_eq!(result.suppressed[0].issue.code, "subject-max-length");
+        assert_eq!(
+            result.suppressed[0].justification,
+            "matches the upstream advisory title"
+        );
+
+        let unjustified = format!("feat: {}\n\nCk-Disable: subject-max-length", subject);
+        let result = engine.validate_string(&unjustified).unwrap();
+        assert_eq!(result.errors.len(), 2);
+    }
+}
@@ -0,0 +1,232 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Ticket references, configured under `[rules.ticket]`.
+//!
+//! `branch_patterns` pull a ticket such as `JIRA-1234` out of the branch
+//! name, and messages that don't reference one get it in the footer or the
+//! subject. With `required`, a message without a reference is an error.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::commit::CommitMessage;
+use crate::config::{TicketConfig, TicketPlacement};
+
+use super::validator::ValidationIssue;
+
+lazy_static! {
+    /// Configured patterns, compiled once. Invalid ones map to `None`.
+    static ref COMPILED: Mutex<HashMap<String, Option<Regex>>> = Mutex::new(HashMap::new());
+}
+
+/// `pattern` compiled, or `None` when it is invalid.
+fn regex(pattern: &str) -> Option<Regex> {
+    let mut compiled = COMPILED.lock().unwrap_or_else(|e| e.into_inner());
+    compiled
+        .entry(pattern.to_string())
+        .or_insert_with(|| Regex::new(pattern).ok())
+        .clone()
+}
+
+/// Whether `ticket` belongs to one of the configured projects. `#123`
+/// references have no project and always count.
+fn in_project(config: &TicketConfig, ticket: &str) -> bool {
+    if config.prefixes.is_empty() || ticket.starts_with('#') {
+        return true;
+    }
+    match ticket.rsplit_once('-') {
+        Some((prefix, _)) => config.prefixes.iter().any(|p| p == prefix),
+        None => false,
+    }
+}
+
+/// The ticket in `branch`: the first group, or else the whole match, of
+/// the first branch pattern that matches. Invalid patterns are skipped.
+pub fn branch_ticket(config: &TicketConfig, branch: &str) -> Option<String> {
+    config.branch_patterns.iter().find_map(|pattern| {
+        let captures = regex(pattern)?.captures(branch)?;
+        let ticket = captures.get(1).or_else(|| captures.get(0))?;
+        Some(ticket.as_str().to_string()).filter(|t| !t.is_empty() && in_project(config, t))
+    })
+}
+
+/// Whether `message` references a ticket anywhere.
+pub fn has_ticket(message: &CommitMessage, config: &TicketConfig) -> bool {
+    // An invalid pattern can never be satisfied
+    regex(&config.pattern).is_some_and(|re| {
+        re.find_iter(&message.format())
+            .any(|m| in_project(config, m.as_str()))
+    })
+}
+
+/// `message` with `ticket` placed where the configuration says.
+pub fn add_ticket(message: CommitMessage, config: &TicketConfig, ticket: &str) -> CommitMessage {
+    match config.placement {
+        TicketPlacement::Footer => {
+            let trailer = format!("{}: {}", config.token, ticket);
+            let footer = match message.footer {
+                Some(ref footer) => format!("{}\n{}", footer, trailer),
+                None => trailer,
+            };
+            message.with_footer(footer)
+        }
+        TicketPlacement::Subject => CommitMessage {
+            subject: config
+                .subject_format
+                .replace("{ticket}", ticket)
+                .replace("{subject}", &message.subject),
+            ..message
+        },
+        TicketPlacement::None => message,
+    }
+}
+
+/// `message` with the ticket from `branch` added, unless it already
+/// references one.
+pub fn with_branch_ticket(
+    message: CommitMessage,
+    config: &TicketConfig,
+    branch: Option<&str>,
+) -> CommitMessage {
+    match branch.and_then(|branch| branch_ticket(config, branch)) {
+        Some(ticket) if !has_ticket(&message, config) => add_ticket(message, config, &ticket),
+        _ => message,
+    }
+}
+
+/// Check that the message references a ticket when one is required.
+pub fn check_ticket(message: &CommitMessage, config: &TicketConfig) -> Option<ValidationIssue> {
+    if !config.required || has_ticket(message, config) {
+        return None;
+    }
+
+    Some(ValidationIssue {
+        code: "ticket-missing".to_string(),
+        message: "Message does not reference a ticket".to_string(),
+        suggestion: Some(
+            "Add one with --issue, or commit on a branch matching rules.ticket.branch_patterns"
+                .to_string(),
+        ),
+        is_error: true,
+        line: None,
+        span: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CommitType;
+
+    fn config() -> TicketConfig {
+        TicketConfig {
+            branch_patterns: vec![
+                r"^(?:feature|fix)/([A-Z]+-[0-9]+)".to_string(),
+                r"^hotfix/[0-9]+".to_string(),
+            ],
+            ..TicketConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_branch_ticket() {
+        let config = config();
+        assert_eq!(
+            branch_ticket(&config, "feature/JIRA-1234-login").as_deref(),
+            Some("JIRA-1234")
+        );
+        assert_eq!(
+            branch_ticket(&config, "hotfix/77").as_deref(),
+            Some("hotfix/77")
+        );
+        assert!(branch_ticket(&config, "main").is_none());
+    }
+
+    #[test]
+    fn test_default_branch_ticket() {
+        let config = TicketConfig::default();
+        assert_eq!(
+            branch_ticket(&config, "feature/JIRA-1234-login").as_deref(),
+            Some("JIRA-1234")
+        );
+        assert_eq!(
+            branch_ticket(&config, "AUTH-9-timeouts").as_deref(),
+            Some("AUTH-9")
+        );
+        assert!(branch_ticket(&config, "fix/login-timeout").is_none());
+        assert!(branch_ticket(&config, "fix/UTF-8-paths").is_some());
+
+        let config = TicketConfig {
+            prefixes: vec!["JIRA".to_string()],
+            ..TicketConfig::default()
+        };
+        assert!(branch_ticket(&config, "fix/UTF-8-paths").is_none());
+        assert_eq!(
+            branch_ticket(&config, "fix/JIRA-7-paths").as_deref(),
+            Some("JIRA-7")
+        );
+    }
+
+    #[test]
+    fn test_has_ticket() {
+        let config = TicketConfig::default();
+        let has =
+            |subject: &str| has_ticket(&CommitMessage::new(CommitType::Fix, subject), &config);
+        assert!(has("handle JIRA-12 timeouts"));
+        assert!(has("handle timeouts (#42)"));
+        assert!(!has("handle XJIRA-12x timeouts"));
+        assert!(!has("handle ÄBC-12 timeouts"));
+        assert!(!has("handle issue#42 timeouts"));
+
+        let config = TicketConfig {
+            prefixes: vec!["JIRA".to_string()],
+            ..TicketConfig::default()
+        };
+        let message = CommitMessage::new(CommitType::Fix, "read UTF-8 paths");
+        assert!(!has_ticket(&message, &config));
+        assert!(has_ticket(&message.with_footer("Refs: JIRA-3"), &config));
+    }
+
+    #[test]
+    fn test_with_branch_ticket() {
+        let mut config = config();
+        let message = CommitMessage::new(CommitType::Fix, "handle login timeouts");
+        let branch = Some("fix/AUTH-9-timeouts");
+
+        let footer = with_branch_ticket(message.clone(), &config, branch);
+        assert_eq!(footer.footer.as_deref(), Some("Refs: AUTH-9"));
+        // Added once
+        let again = with_branch_ticket(footer.clone(), &config, branch);
+        assert_eq!(again.footer, footer.footer);
+
+        config.placement = TicketPlacement::Subject;
+        let subject = with_branch_ticket(message.clone(), &config, branch);
+        assert_eq!(subject.subject, "AUTH-9 handle login timeouts");
+        assert!(subject.footer.is_none());
+
+        config.placement = TicketPlacement::None;
+        assert!(!has_ticket(
+            &with_branch_ticket(message, &config, branch),
+            &config
+        ));
+    }
+
+    #[test]
+    fn test_check_ticket() {
+        let mut config = config();
+        let message = CommitMessage::new(CommitType::Fix, "handle login timeouts");
+        assert!(check_ticket(&message, &config).is_none());
+
+        config.required = true;
+        assert_eq!(
+            check_ticket(&message, &config).unwrap().code,
+            "ticket-missing"
+        );
+        let message = message.with_footer("Refs: #42");
+        assert!(check_ticket(&message, &config).is_none());
+    }
+}
@@ -5,9 +5,42 @@
 
 use crate::cli::args::OutputFormat;
 use console::{style, Style};
+use serde::Serialize;
+
+/// Where an issue is in the validated message, on a single line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    /// One-based line.
+    pub line: usize,
+    /// One-based column of the first character, counted in characters.
+    pub column: usize,
+    /// One-based column just past the last character.
+    pub end_column: usize,
+    /// Byte offset of the first character in the message.
+    pub start: usize,
+    /// Byte offset just past the last character.
+    pub end: usize,
+}
+
+impl Span {
+    /// The span of bytes `start..end` of `text`, which must not cross a
+    /// line break.
+    pub fn in_text(text: &str, start: usize, end: usize) -> Self {
+        let before = &text[..start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        Self {
+            line: before.matches('\n').count() + 1,
+            column,
+            end_column: column + text[start..end].chars().count(),
+            start,
+            end,
+        }
+    }
+}
 
 /// A single validation issue.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ValidationIssue {
     /// Error code for programmatic handling.
     pub code: String,
@@ -19,6 +52,9 @@ pub struct ValidationIssue {
     pub is_error: bool,
     /// Line number where the issue was found.
     pub line: Option<usize>,
+    /// The offending text in [`ValidationResult::message`], when the rule
+    /// can point at it.
+    pub span: Option<Span>,
 }
 
 impl ValidationIssue {
@@ -55,6 +91,15 @@ impl ValidationIssue {
     }
 }
 
+/// An issue suppressed by a `Ck-Disable:` footer.
+#[derive(Debug, Clone)]
+pub struct SuppressedIssue {
+    /// The suppressed issue.
+    pub issue: ValidationIssue,
+    /// Justification given in the footer.
+    pub justification: String,
+}
+
 /// Result of validating a commit message.
 #[derive(Debug, Clone)]
 pub struct ValidationResult {
@@ -66,6 +111,12 @@ pub struct ValidationResult {
     pub errors: Vec<ValidationIssue>,
     /// Validation warnings.
     pub warnings: Vec<ValidationIssue>,
+    /// Issues suppressed by the message's footers.
+    pub suppressed: Vec<SuppressedIssue>,
+    /// Codes of warnings promoted to errors.
+    pub promoted: Vec<String>,
+    /// Hash of the configuration the message was checked under.
+    pub config_hash: Option<String>,
 }
 
 impl ValidationResult {
@@ -76,6 +127,9 @@ impl ValidationResult {
             commit_sha: None,
             errors: Vec::new(),
             warnings: Vec::new(),
+            suppressed: Vec::new(),
+            promoted: Vec::new(),
+            config_hash: None,
         }
     }
 
@@ -84,6 +138,22 @@ impl ValidationResult {
         self.errors.is_empty()
     }
 
+    /// Turn warnings with the given codes into errors.
+    pub fn promote(&mut self, codes: &[String]) {
+        let (promoted, kept): (Vec<_>, Vec<_>) = self
+            .warnings
+            .drain(..)
+            .partition(|w| codes.contains(&w.code));
+        self.warnings = kept;
+        for mut issue in promoted {
+            issue.is_error = true;
+            if !self.promoted.contains(&issue.code) {
+                self.promoted.push(issue.code.clone());
+            }
+            self.errors.push(issue);
+        }
+    }
+
     /// Get the total number of issues.
     pub fn issue_count(&self) -> usize {
         self.errors.len() + self.warnings.len()
@@ -120,6 +190,16 @@ impl ValidationResult {
         for warning in &self.warnings {
             println!("  {}", warning.format());
         }
+
+        // Print suppressed issues so overrides stay visible
+        for suppressed in &self.suppressed {
+            println!(
+                "  {} {} {}",
+                style("⊘").dim(),
+                style(&suppressed.issue.code).dim(),
+                style(format!("suppressed: {}", suppressed.justification)).dim()
+            );
+        }
     }
 
     /// Print in JSON format.
@@ -128,20 +208,15 @@ impl ValidationResult {
             "valid": self.is_valid(),
             "commit": self.commit_sha,
             "message": self.message,
-            "errors": self.errors.iter().map(|e| {
+            "errors": self.errors,
+            "warnings": self.warnings,
+            "promoted": self.promoted,
+            "config_hash": self.config_hash,
+            "suppressed": self.suppressed.iter().map(|s| {
                 serde_json::json!({
-                    "code": e.code,
-                    "message": e.message,
-                    "suggestion": e.suggestion,
-                    "line": e.line,
-                })
-            }).collect::<Vec<_>>(),
-            "warnings": self.warnings.iter().map(|w| {
-                serde_json::json!({
-                    "code": w.code,
-                    "message": w.message,
-                    "suggestion": w.suggestion,
-                    "line": w.line,
+                    "code": s.issue.code,
+                    "message": s.issue.message,
+                    "justification": s.justification,
                 })
             }).collect::<Vec<_>>(),
         });
@@ -170,6 +245,35 @@ impl ValidationResult {
     }
 }
 
+/// Explain on stderr how severities were escalated for a check.
+///
+/// `warnings_fail` names the setting that made warnings fail, if any.
+pub fn print_escalation(results: &[ValidationResult], warnings_fail: Option<&str>) {
+    let mut promoted: Vec<&str> = results
+        .iter()
+        .flat_map(|r| r.promoted.iter().map(String::as_str))
+        .collect();
+    promoted.sort_unstable();
+    promoted.dedup();
+
+    if !promoted.is_empty() {
+        eprintln!(
+            "{} Promoted to errors in CI: {}",
+            style("ℹ").blue(),
+            promoted.join(", ")
+        );
+    }
+    if let Some(setting) = warnings_fail {
+        if results.iter().any(|r| !r.warnings.is_empty()) {
+            eprintln!(
+                "{} Warnings fail this check ({})",
+                style("ℹ").blue(),
+                setting
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,6 +294,7 @@ mod tests {
             suggestion: None,
             is_error: true,
             line: Some(1),
+            span: None,
         });
 
         assert!(!result.is_valid());
@@ -204,6 +309,7 @@ mod tests {
             suggestion: Some("Fix it".to_string()),
             is_error: true,
             line: Some(1),
+            span: None,
         };
 
         let formatted = issue.format();
@@ -211,6 +317,27 @@ mod tests {
         assert!(formatted.contains("Test message"));
     }
 
+    #[test]
+    fn test_promote() {
+        let mut result = ValidationResult::new("feat: Added parser".to_string());
+        for code in ["subject-imperative", "subject-case"] {
+            result.warnings.push(ValidationIssue {
+                code: code.to_string(),
+                message: String::new(),
+                suggestion: None,
+                is_error: false,
+                line: None,
+                span: None,
+            });
+        }
+
+        result.promote(&["subject-imperative".to_string()]);
+        assert!(!result.is_valid());
+        assert!(result.errors[0].is_error);
+        assert_eq!(result.promoted, vec!["subject-imperative"]);
+        assert_eq!(result.warnings[0].code, "subject-case");
+    }
+
     #[test]
     fn test_summary() {
         let mut result = ValidationResult::new("test".to_string());
@@ -222,6 +349,7 @@ mod tests {
             suggestion: None,
             is_error: false,
             line: None,
+            span: None,
         });
         assert!(result.summary().contains("1 warning"));
 
@@ -231,6 +359,7 @@ mod tests {
             suggestion: None,
             is_error: true,
             line: None,
+            span: None,
         });
         assert!(result.summary().contains("Invalid"));
     }
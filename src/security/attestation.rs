@@ -0,0 +1,351 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Signed envelopes for attestations.
+//!
+//! Envelopes follow DSSE: the payload is base64 encoded, and signatures are
+//! made over its pre-authentication encoding ([`pae`]), so a verifier checks
+//! them against the exact bytes before parsing the payload. Signatures use
+//! the key git signs commits with.
+//!
+//! Payloads may be in-toto statements for provenance chains; a written
+//! attestation can additionally be signed with cosign.
+
+use crate::error::{CkError, Result};
+use crate::git::{signing, Repository};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// SSH signature namespace for ck attestations.
+pub const SSH_NAMESPACE: &str = "ck-attestation";
+
+/// Statement type for in-toto v1 statements.
+pub const IN_TOTO_STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+
+/// Envelope payload type for in-toto statements.
+pub const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// An in-toto v1 statement.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Statement<P> {
+    /// Statement type, always [`IN_TOTO_STATEMENT_TYPE`].
+    #[serde(rename = "_type")]
+    pub statement_type: String,
+    /// Artifacts the statement is about.
+    pub subject: Vec<Subject>,
+    /// URI identifying the predicate schema.
+    #[serde(rename = "predicateType")]
+    pub predicate_type: String,
+    /// The predicate.
+    pub predicate: P,
+}
+
+/// An artifact identified by its digests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subject {
+    /// Artifact name.
+    pub name: String,
+    /// Digests keyed by algorithm, e.g. `gitCommit`.
+    pub digest: std::collections::BTreeMap<String, String>,
+}
+
+impl Subject {
+    /// A git commit subject.
+    pub fn git_commit(sha: &str) -> Self {
+        Self {
+            name: sha.to_string(),
+            digest: [("gitCommit".to_string(), sha.to_string())].into(),
+        }
+    }
+}
+
+impl<P> Statement<P> {
+    /// Create a statement about `subject`.
+    pub fn new(subject: Vec<Subject>, predicate_type: &str, predicate: P) -> Self {
+        Self {
+            statement_type: IN_TOTO_STATEMENT_TYPE.to_string(),
+            subject,
+            predicate_type: predicate_type.to_string(),
+            predicate,
+        }
+    }
+}
+
+/// A DSSE envelope: a payload with detached signatures over it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    /// Media type of the payload.
+    #[serde(rename = "payloadType")]
+    pub payload_type: String,
+    /// The signed payload, base64 encoded in JSON.
+    #[serde(with = "base64_bytes")]
+    pub payload: Vec<u8>,
+    /// Signatures over the PAE of `payload_type` and `payload`.
+    pub signatures: Vec<EnvelopeSignature>,
+}
+
+/// A detached signature in an envelope.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvelopeSignature {
+    /// Key the payload was signed with.
+    pub keyid: String,
+    /// Signature bytes, base64 encoded in JSON: an OpenPGP signature
+    /// packet or an SSH signature blob, as `gpg.format` says.
+    #[serde(with = "base64_bytes")]
+    pub sig: Vec<u8>,
+}
+
+/// Bytes as standard base64 strings, as DSSE encodes them.
+mod base64_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        bytes: &[u8],
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        BASE64.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+/// DSSE pre-authentication encoding of a payload: what signatures are made
+/// over, so the payload type is signed along with the payload.
+pub fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut encoded = format!(
+        "DSSEv1 {} {} {} ",
+        payload_type.len(),
+        payload_type,
+        payload.len()
+    )
+    .into_bytes();
+    encoded.extend_from_slice(payload);
+    encoded
+}
+
+impl Envelope {
+    /// Wrap a serializable payload.
+    pub fn new(payload_type: &str, payload: &impl Serialize) -> Result<Self> {
+        let payload = serde_json::to_string_pretty(payload).map_err(|e| CkError::WithContext {
+            code: "attestation-serialize",
+            context: "attestation".to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(Self {
+            payload_type: payload_type.to_string(),
+            payload: payload.into_bytes(),
+            signatures: Vec::new(),
+        })
+    }
+
+    /// The bytes signatures are made over.
+    pub fn signed_bytes(&self) -> Vec<u8> {
+        pae(&self.payload_type, &self.payload)
+    }
+
+    /// Sign the payload with git's configured signing key.
+    pub fn sign(&mut self, repo: &Repository) -> Result<()> {
+        signing::check_signing_setup()?;
+
+        let config = repo
+            .inner()
+            .config()
+            .map_err(|e| attestation_error(e.message()))?;
+        let format = config
+            .get_string("gpg.format")
+            .unwrap_or_else(|_| "openpgp".to_string());
+        let key = config
+            .get_string("user.signingkey")
+            .or_else(|_| config.get_string("user.email"))
+            .map_err(|_| attestation_error("No signing key configured"))?;
+
+        let signed = self.signed_bytes();
+        let signature = match format.as_str() {
+            "ssh" => {
+                let key_file = signing::expand_home(&key, repo.workdir());
+                let armored = run_signer(
+                    Command::new("ssh-keygen")
+                        .args(["-Y", "sign", "-n", SSH_NAMESPACE, "-f"])
+                        .arg(&key_file),
+                    &signed,
+                )?;
+                dearmor_ssh(&armored)?
+            }
+            "openpgp" => {
+                let program = config
+                    .get_string("gpg.openpgp.program")
+                    .or_else(|_| config.get_string("gpg.program"))
+                    .unwrap_or_else(|_| "gpg".to_string());
+                run_signer(
+                    Command::new(program).args(["--batch", "--detach-sign", "--local-user", &key]),
+                    &signed,
+                )?
+            }
+            other => {
+                return Err(attestation_error(&format!(
+                    "Signing attestations with gpg.format '{}' is not supported",
+                    other
+                )))
+            }
+        };
+
+        self.signatures.push(EnvelopeSignature {
+            keyid: key,
+            sig: signature,
+        });
+        Ok(())
+    }
+
+    /// Serialize the envelope as pretty JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// The signature blob inside an armored `ssh-keygen -Y sign` signature.
+fn dearmor_ssh(armored: &[u8]) -> Result<Vec<u8>> {
+    let text = String::from_utf8_lossy(armored);
+    let body: String = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("-----"))
+        .collect();
+    BASE64
+        .decode(body)
+        .map_err(|e| attestation_error(&format!("Unreadable SSH signature: {}", e)))
+}
+
+/// Pipe `payload` through a signing command and return its output.
+fn run_signer(command: &mut Command, payload: &[u8]) -> Result<Vec<u8>> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| attestation_error(&format!("Could not run signer: {}", e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(payload)?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(attestation_error(&format!(
+            "Signing failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Sign a file with `cosign sign-blob`, writing a bundle next to it.
+///
+/// Without a key cosign uses keyless signing.
+pub fn cosign_sign_blob(path: &Path, key: Option<&str>) -> Result<PathBuf> {
+    let mut bundle = path.as_os_str().to_owned();
+    bundle.push(".bundle");
+    let bundle = PathBuf::from(bundle);
+
+    let mut command = Command::new("cosign");
+    command
+        .args(["sign-blob", "--yes", "--bundle"])
+        .arg(&bundle);
+    if let Some(key) = key {
+        command.args(["--key", key]);
+    }
+    let output = command.arg(path).output().map_err(|e| {
+        attestation_error(&format!(
+            "Could not run cosign ({}). Install it from https://docs.sigstore.dev",
+            e
+        ))
+    })?;
+
+    if !output.status.success() {
+        return Err(attestation_error(&format!(
+            "cosign failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(bundle)
+}
+
+fn attestation_error(message: &str) -> CkError {
+    CkError::WithContext {
+        code: "attestation-sign",
+        context: "attestation".to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statement_shape() {
+        let statement = Statement::new(
+            vec![Subject::git_commit("abc123")],
+            "https://example.com/predicate/v1",
+            serde_json::json!({"passed": true}),
+        );
+        let json = serde_json::to_value(&statement).unwrap();
+        assert_eq!(json["_type"], IN_TOTO_STATEMENT_TYPE);
+        assert_eq!(json["subject"][0]["digest"]["gitCommit"], "abc123");
+        assert_eq!(json["predicateType"], "https://example.com/predicate/v1");
+    }
+
+    #[test]
+    fn test_envelope_keeps_signed_bytes() {
+        let envelope = Envelope::new("application/json", &serde_json::json!({"a": 1})).unwrap();
+        let parsed: Envelope = serde_json::from_str(&envelope.to_json()).unwrap();
+        assert_eq!(parsed.payload, envelope.payload);
+        assert_eq!(parsed.payload, b"{\n  \"a\": 1\n}");
+        assert!(parsed.signatures.is_empty());
+    }
+
+    #[test]
+    fn test_envelope_is_dsse() {
+        let mut envelope =
+            Envelope::new(IN_TOTO_PAYLOAD_TYPE, &serde_json::json!({"a": 1})).unwrap();
+        envelope.signatures.push(EnvelopeSignature {
+            keyid: "key".to_string(),
+            sig: vec![0, 1, 2],
+        });
+        let json: serde_json::Value = serde_json::from_str(&envelope.to_json()).unwrap();
+        assert_eq!(json["payloadType"], IN_TOTO_PAYLOAD_TYPE);
+        assert_eq!(json["signatures"][0]["sig"], "AAEC");
+
+        let payload = BASE64.decode(json["payload"].as_str().unwrap()).unwrap();
+        assert_eq!(payload, b"{\n  \"a\": 1\n}");
+        assert_eq!(
+            envelope.signed_bytes(),
+            b"DSSEv1 28 application/vnd.in-toto+json 12 {\n  \"a\": 1\n}"
+        );
+    }
+
+    #[test]
+    fn test_pae() {
+        // Example from the DSSE protocol description
+        assert_eq!(
+            pae("http://example.com/HelloWorld", b"hello world"),
+            b"DSSEv1 29 http://example.com/HelloWorld 11 hello world"
+        );
+        assert_eq!(pae("", b""), b"DSSEv1 0  0 ");
+    }
+
+    #[test]
+    fn test_dearmor_ssh() {
+        let armored =
+            b"-----BEGIN SSH SIGNATURE-----\nU1NIU0lH\nAAAA\n-----END SSH SIGNATURE-----\n";
+        assert_eq!(dearmor_ssh(armored).unwrap(), b"SSHSIG\0\0\0");
+    }
+}
@@ -3,8 +3,16 @@
 
 //! Security module for secret detection and signing.
 
+pub mod attestation;
+mod redact;
+pub mod sarif;
+pub mod scan;
 mod secrets;
 mod signing;
 
-pub use secrets::{detect_secrets, SecretMatch, SecretScanner};
+pub use redact::{display_path, redact_path};
+pub use secrets::{
+    detect_secrets, SecretMatch, SecretScan, SecretScanner, SuppressedSecret, Suppression,
+    IGNORE_MARKER,
+};
 pub use signing::{check_signing_status, SigningStatus};
@@ -0,0 +1,45 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Redaction of file paths for terminals where path names are sensitive.
+
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Replace a path with a short, stable hash.
+///
+/// The same path always redacts to the same value, so repeated mentions
+/// can still be correlated without revealing the name.
+pub fn redact_path(path: &Path) -> String {
+    let digest = Sha256::digest(path.to_string_lossy().as_bytes());
+    let hex: String = digest
+        .iter()
+        .take(4)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("<file:{}>", hex)
+}
+
+/// Format a path for display, redacting it when requested.
+pub fn display_path(path: &Path, redact: bool) -> String {
+    if redact {
+        redact_path(path)
+    } else {
+        path.display().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_path_is_stable_and_opaque() {
+        let path = Path::new("secrets/prod.env");
+        let redacted = redact_path(path);
+        assert_eq!(redacted, redact_path(path));
+        assert!(!redacted.contains("prod"));
+        assert_ne!(redacted, redact_path(Path::new("secrets/dev.env")));
+        assert_eq!(display_path(path, false), "secrets/prod.env");
+    }
+}
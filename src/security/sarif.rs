@@ -0,0 +1,144 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! SARIF 2.1.0 logs of secret scans, for code scanning uploads.
+//!
+//! Each secret pattern is a rule. Suppressed matches are kept as results
+//! with a suppression, `inSource` for inline comments and `external` for
+//! the allowlist, so dashboards show them as dismissed.
+
+use serde_json::{json, Value};
+
+use super::scan::{Finding, ScanReport};
+use super::secrets::Suppression;
+
+/// Schema of the logs.
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A SARIF log of `report`.
+pub fn to_sarif(report: &ScanReport) -> Value {
+    let mut rules: Vec<&str> = report
+        .findings
+        .iter()
+        .chain(&report.suppressed)
+        .map(|finding| finding.secret.pattern_name.as_str())
+        .collect();
+    rules.sort_unstable();
+    rules.dedup();
+
+    let results: Vec<Value> = report
+        .findings
+        .iter()
+        .chain(&report.suppressed)
+        .map(result)
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "ck",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/eshanized/CommitKit",
+                    "rules": rules.iter().map(|name| json!({
+                        "id": rule_id(name),
+                        "name": name,
+                        "shortDescription": { "text": format!("{} found", name) },
+                        "defaultConfiguration": { "level": "error" },
+                    })).collect::<Vec<_>>(),
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+fn result(finding: &Finding) -> Value {
+    let secret = &finding.secret;
+    let mut message = format!("Possible {} ({})", secret.pattern_name, secret.preview);
+    if let Some(ref commit) = finding.commit {
+        message.push_str(&format!(" added in {}", &commit[..7]));
+    }
+
+    let mut result = json!({
+        "ruleId": rule_id(&secret.pattern_name),
+        "level": "error",
+        "message": { "text": message },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": secret.file },
+                "region": { "startLine": secret.line.unwrap_or(1) },
+            }
+        }],
+        "partialFingerprints": { "secretHash/v1": secret.fingerprint },
+    });
+    if let Some(ref suppression) = finding.suppressed_by {
+        let (kind, justification) = match suppression {
+            Suppression::InlineComment => ("inSource", "ck:ignore-secret comment".to_string()),
+            Suppression::Path(glob) => ("external", format!("security.allowlist.paths: {}", glob)),
+            Suppression::Pattern(name) => {
+                ("external", format!("security.allowlist.patterns: {}", name))
+            }
+            Suppression::Fingerprint => ("external", "security.allowlist.fingerprints".to_string()),
+        };
+        result["suppressions"] = json!([{ "kind": kind, "justification": justification }]);
+    }
+    result
+}
+
+/// Rule id of a pattern: `secret/` and its name in kebab case.
+fn rule_id(pattern_name: &str) -> String {
+    let slug: Vec<String> = pattern_name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!("secret/{}", slug.join("-"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::SecretMatch;
+
+    fn finding(pattern_name: &str, suppressed_by: Option<Suppression>) -> Finding {
+        Finding {
+            commit: None,
+            secret: SecretMatch {
+                pattern_name: pattern_name.to_string(),
+                file: "deploy.sh".to_string(),
+                line: Some(3),
+                preview: "KEY=[REDACTED]".to_string(),
+                fingerprint: "sha256:abc".to_string(),
+            },
+            suppressed_by,
+        }
+    }
+
+    #[test]
+    fn test_to_sarif() {
+        let report = ScanReport {
+            target: "staged changes".to_string(),
+            scanned: 1,
+            findings: vec![finding("AWS Access Key", None)],
+            suppressed: vec![finding("JWT Token", Some(Suppression::InlineComment))],
+        };
+
+        let log = to_sarif(&report);
+        let run = &log["runs"][0];
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"][0]["id"],
+            "secret/aws-access-key"
+        );
+        assert_eq!(run["results"][0]["ruleId"], "secret/aws-access-key");
+        assert_eq!(
+            run["results"][0]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            3
+        );
+        assert!(run["results"][0].get("suppressions").is_none());
+        assert_eq!(run["results"][1]["suppressions"][0]["kind"], "inSource");
+    }
+}
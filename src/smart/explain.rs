@@ -0,0 +1,279 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Explanation of the factors behind a smart suggestion.
+
+use crate::analysis::diff::ChangeCategory;
+use crate::analysis::{
+    reconcile_scope, score_commit_types, CommitTypeScore, DiffAnalysis, RepositoryContext,
+};
+use crate::commit::CommitMessage;
+use crate::config::CkConfig;
+use crate::git::Repository;
+use console::style;
+use std::collections::BTreeMap;
+
+use super::semantic::{ChangeIntent, SemanticAnalyzer};
+
+/// Number of recent commits used for history priors.
+const HISTORY_DEPTH: usize = 50;
+
+/// Number of entries shown per history prior.
+const HISTORY_TOP: usize = 3;
+
+/// Factors behind a suggested type and scope.
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    /// Per-rule type scores, highest first.
+    pub type_scores: Vec<CommitTypeScore>,
+    /// Detected intent.
+    pub intent: ChangeIntent,
+    /// Intent detection signals and whether each fired.
+    pub signals: Vec<(&'static str, bool)>,
+    /// Number of changed files per category.
+    pub categories: Vec<(ChangeCategory, usize)>,
+    /// Inferred scope before reconciliation.
+    pub inferred_scope: Option<String>,
+    /// Note on how the scope was reconciled with the configuration.
+    pub scope_note: Option<String>,
+    /// Most frequent types in recent history.
+    pub history_types: Vec<(String, usize)>,
+    /// Most frequent scopes in recent history.
+    pub history_scopes: Vec<(String, usize)>,
+    /// Number of commits the history priors were taken from.
+    pub history_commits: usize,
+}
+
+impl Explanation {
+    /// Collect the factors for the staged changes in `context`.
+    pub fn build(context: &RepositoryContext, config: &CkConfig) -> Self {
+        let analysis = DiffAnalysis::from_diff(&context.diff_info);
+        let intent = SemanticAnalyzer::from_context(context).primary_intent();
+
+        let signals = vec![
+            ("documentation only", analysis.is_docs_change),
+            ("tests only", analysis.is_test_change),
+            ("configuration change", analysis.is_config_change),
+            ("refactoring pattern", analysis.is_refactoring),
+            ("fix pattern", analysis.is_fix),
+            ("adds functionality", analysis.adds_functionality),
+        ];
+        let categories = analysis
+            .categories
+            .iter()
+            .map(|(category, files)| (*category, files.len()))
+            .collect();
+
+        let scope_note = context
+            .suggested_scope
+            .as_deref()
+            .and_then(|scope| reconcile_scope(scope, &config.rules.scope).describe());
+
+        let history: Vec<CommitMessage> = Repository::open_current()
+            .and_then(|repo| repo.recent_commit_messages(HISTORY_DEPTH))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|message| CommitMessage::parse(message).ok())
+            .collect();
+
+        Self {
+            type_scores: score_commit_types(
+                &context.diff_info,
+                &context.staged_files,
+                config.ui.redact_paths,
+            ),
+            intent,
+            signals,
+            categories,
+            inferred_scope: context.suggested_scope.clone(),
+            scope_note,
+            history_types: top_counts(history.iter().map(|m| m.commit_type.to_string())),
+            history_scopes: top_counts(history.iter().filter_map(|m| m.scope.clone())),
+            history_commits: history.len(),
+        }
+    }
+
+    /// Render the explanation as a tree.
+    pub fn render(&self) -> String {
+        let mut lines = vec![style("Why this suggestion").bold().to_string()];
+
+        let scores = self
+            .type_scores
+            .iter()
+            .map(|s| format!("{:<8} {:.2}  {}", s.commit_type.as_str(), s.score, s.reason))
+            .collect();
+        push_branch(&mut lines, "Type scores".to_string(), scores, false);
+
+        let mut intent = Vec::new();
+        for (signal, fired) in &self.signals {
+            let mark = if *fired {
+                style("✓").green()
+            } else {
+                style("✗").dim()
+            };
+            intent.push(format!("{} {}", mark, signal));
+        }
+        for (category, count) in &self.categories {
+            intent.push(format!("{} file(s): {}", count, category_label(*category)));
+        }
+        push_branch(
+            &mut lines,
+            format!("Intent: {}", self.intent.description()),
+            intent,
+            false,
+        );
+
+        let scope = match self.inferred_scope {
+            Some(ref scope) => format!("Scope: {}", scope),
+            None => "Scope: none inferred".to_string(),
+        };
+        push_branch(
+            &mut lines,
+            scope,
+            self.scope_note.iter().cloned().collect(),
+            false,
+        );
+
+        let mut history: Vec<String> = self
+            .history_types
+            .iter()
+            .map(|(t, n)| format!("type {} ({}×)", t, n))
+            .collect();
+        history.extend(
+            self.history_scopes
+                .iter()
+                .map(|(s, n)| format!("scope {} ({}×)", s, n)),
+        );
+        push_branch(
+            &mut lines,
+            format!("History (last {} commits)", self.history_commits),
+            history,
+            true,
+        );
+
+        lines.join("\n")
+    }
+}
+
+/// Append a tree branch with its children.
+fn push_branch(lines: &mut Vec<String>, label: String, children: Vec<String>, last: bool) {
+    let (branch, indent) = if last {
+        ("└─", "   ")
+    } else {
+        ("├─", "│  ")
+    };
+    lines.push(format!("{} {}", branch, label));
+
+    let count = children.len();
+    for (i, child) in children.into_iter().enumerate() {
+        let twig = if i + 1 == count { "└─" } else { "├─" };
+        lines.push(format!("{}{} {}", indent, twig, child));
+    }
+}
+
+/// The most frequent values, most common first.
+fn top_counts(values: impl Iterator<Item = String>) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts.truncate(HISTORY_TOP);
+    counts
+}
+
+fn category_label(category: ChangeCategory) -> &'static str {
+    match category {
+        ChangeCategory::NewFiles => "new",
+        ChangeCategory::DeletedFiles => "deleted",
+        ChangeCategory::Tests => "tests",
+        ChangeCategory::Documentation => "documentation",
+        ChangeCategory::Configuration => "configuration",
+        ChangeCategory::Source => "source",
+        ChangeCategory::Build => "build",
+        ChangeCategory::Assets => "assets",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CommitType;
+
+    #[test]
+    fn test_top_counts() {
+        let values = ["feat", "fix", "feat", "docs", "feat", "fix", "ci"];
+        let top = top_counts(values.iter().map(|v| v.to_string()));
+        assert_eq!(
+            top,
+            vec![
+                ("feat".to_string(), 3),
+                ("fix".to_string(), 2),
+                ("ci".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_tree() {
+        console::set_colors_enabled(false);
+        let explanation = Explanation {
+            type_scores: vec![CommitTypeScore {
+                commit_type: CommitType::Docs,
+                score: 0.9,
+                reason: "Only documentation files changed".to_string(),
+            }],
+            intent: ChangeIntent::Documentation,
+            signals: vec![("documentation only", true)],
+            categories: vec![(ChangeCategory::Documentation, 2)],
+            inferred_scope: None,
+            scope_note: None,
+            history_types: vec![("docs".to_string(), 4)],
+            history_scopes: Vec::new(),
+            history_commits: 10,
+        };
+
+        let rendered = explanation.render();
+        assert!(rendered.contains("├─ Type scores\n│  └─ docs     0.90  Only documentation"));
+        assert!(rendered.contains("│  ├─ ✓ documentation only\n│  └─ 2 file(s): documentation"));
+        assert!(rendered.ends_with("└─ History (last 10 commits)\n   └─ type docs (4×)"));
+    }
+
+    #[test]
+    fn test_build_redacted() {
+        console::set_colors_enabled(false);
+        let path = std::path::PathBuf::from("benches/secret_parser.rs");
+        let mut diff_info = crate::git::DiffInfo::empty();
+        diff_info.files.push(crate::git::FileChange {
+            path: path.clone(),
+            change_type: crate::git::ChangeType::Modified,
+            lines_added: 10,
+            lines_removed: 2,
+            is_binary: false,
+            old_path: None,
+        });
+        let context = RepositoryContext {
+            staged_files: vec![path],
+            unstaged_files: Vec::new(),
+            diff_stats: diff_info.stats.clone(),
+            branch: "main".to_string(),
+            initial_commit: false,
+            packages: Vec::new(),
+            suggested_type: None,
+            suggested_scope: None,
+            warnings: crate::analysis::Warnings::new(),
+            diff_info,
+        };
+        let mut config = CkConfig::default();
+
+        let rendered = Explanation::build(&context, &config).render();
+        assert!(rendered.contains("Performance-related file: benches/secret_parser.rs"));
+
+        config.ui.redact_paths = true;
+        let rendered = Explanation::build(&context, &config).render();
+        assert!(rendered.contains("Performance-related file:"));
+        assert!(!rendered.contains("secret_parser"));
+    }
+}
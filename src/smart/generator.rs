@@ -3,15 +3,19 @@
 
 //! Smart commit message generator.
 
-use crate::analysis::RepositoryContext;
-use crate::commit::CommitMessage;
-use crate::config::{CkConfig, CommitType};
+use crate::analysis::{reconcile_scope, RepositoryContext, ScopeReconciliation, INITIAL_SUBJECT};
+use crate::commit::editor::{MessageEditor, MESSAGE_INSTRUCTIONS};
+use crate::commit::{gitmoji, CommitMessage};
+use crate::config::{CkConfig, CommitType, EmojiStyle, SmartTone};
 use crate::error::Result;
 use crate::git;
+use crate::plugins::EnabledPlugins;
+use crate::rules::ticket;
 
 use console::{style, Term};
-use dialoguer::{theme::ColorfulTheme, Confirm, Editor};
+use dialoguer::{theme::ColorfulTheme, Confirm};
 
+use super::provenance;
 use super::semantic::SemanticAnalyzer;
 
 /// Generated commit message.
@@ -21,55 +25,71 @@ pub struct GeneratedMessage {
     pub commit_type: CommitType,
     /// The scope.
     pub scope: Option<String>,
+    /// Gitmoji written before the subject.
+    pub emoji: Option<String>,
     /// The subject line.
     pub subject: String,
     /// Body with bullet points.
     pub body: Option<String>,
     /// Confidence score (0.0 - 1.0).
     pub confidence: f64,
+    /// Notes explaining adjustments behind the suggestion.
+    pub explanation: Vec<String>,
+    /// Trailers such as `Generated-by:`.
+    pub footer: Option<String>,
 }
 
 impl GeneratedMessage {
     /// Format as a complete commit message.
     pub fn format(&self) -> String {
-        let message = CommitMessage {
-            commit_type: self.commit_type,
-            scope: self.scope.clone(),
-            subject: self.subject.clone(),
-            body: self.body.clone(),
-            footer: None,
-            is_breaking: false,
-        };
-        message.format()
+        self.to_message().format()
     }
 
     /// Get the header line.
     pub fn header(&self) -> String {
-        let mut result = String::new();
-        result.push_str(self.commit_type.as_str());
+        self.to_message().header()
+    }
 
-        if let Some(ref scope) = self.scope {
-            result.push('(');
-            result.push_str(scope);
-            result.push(')');
+    fn to_message(&self) -> CommitMessage {
+        CommitMessage {
+            commit_type: self.commit_type.clone(),
+            scope: self.scope.clone(),
+            emoji: self.emoji.clone(),
+            subject: self.subject.clone(),
+            body: self.body.clone(),
+            footer: self.footer.clone(),
+            is_breaking: false,
         }
-
-        result.push_str(": ");
-        result.push_str(&self.subject);
-
-        result
     }
 }
 
+/// Subject layout used when `smart.subject_template` is not set.
+const DEFAULT_SUBJECT_TEMPLATE: &str = "{verb} {target}";
+
+/// Words dropped from the end of a shortened subject.
+const DANGLING_WORDS: &[&str] = &[
+    "a", "an", "and", "for", "in", "of", "or", "the", "to", "with",
+];
+
 /// Smart commit generator.
 pub struct SmartCommit {
     config: CkConfig,
+    show_confidence: bool,
 }
 
 impl SmartCommit {
     /// Create a new smart commit generator.
     pub fn new(config: CkConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            show_confidence: true,
+        }
+    }
+
+    /// Set whether the confidence score is shown.
+    pub fn with_confidence(mut self, show: bool) -> Self {
+        self.show_confidence = show;
+        self
     }
 
     /// Generate a commit message from the staged changes.
@@ -82,73 +102,223 @@ impl SmartCommit {
                 crate::error::GitError::NoStagedChanges,
             ));
         }
+        if let Some(message) = self.plugin_message(&context) {
+            return Ok(self.finish(message, &context.branch));
+        }
+        Ok(self.generate_for(&context, max_bullets, include_files))
+    }
+
+    /// Generate a commit message for the changes described by `context`,
+    /// with the ticket from its branch.
+    pub fn generate_for(
+        &self,
+        context: &RepositoryContext,
+        max_bullets: usize,
+        include_files: bool,
+    ) -> GeneratedMessage {
+        let message = self.analyze(context, max_bullets, include_files);
+        self.finish(message, &context.branch)
+    }
+
+    /// Generate a commit message from the analysis of `context`.
+    fn analyze(
+        &self,
+        context: &RepositoryContext,
+        max_bullets: usize,
+        include_files: bool,
+    ) -> GeneratedMessage {
+        // The first commit gets the conventional subject, not a file list
+        if context.initial_commit {
+            return GeneratedMessage {
+                commit_type: context.suggested_type.clone().unwrap_or(CommitType::Chore),
+                scope: context.suggested_scope.clone(),
+                emoji: None,
+                subject: INITIAL_SUBJECT.to_string(),
+                body: None,
+                confidence: 1.0,
+                explanation: vec!["First commit in the repository".to_string()],
+                footer: self.provenance_footer(provenance::HEURISTIC_MODEL),
+            };
+        }
 
         // Perform semantic analysis
-        let analyzer = SemanticAnalyzer::from_context(&context);
+        let analyzer = SemanticAnalyzer::from_context(context);
 
         // Get suggested type and scope
-        let commit_type = context.suggested_type.unwrap_or(analyzer.suggested_type());
-        let scope = context.suggested_scope.clone();
+        let commit_type = context
+            .suggested_type
+            .clone()
+            .unwrap_or(analyzer.suggested_type());
+
+        // Keep the scope within what validation accepts
+        let reconciliation = context
+            .suggested_scope
+            .as_deref()
+            .map(|scope| reconcile_scope(scope, &self.config.rules.scope));
+        let scope = reconciliation
+            .as_ref()
+            .and_then(|r| r.scope())
+            .map(str::to_string);
+        let explanation: Vec<String> = reconciliation
+            .iter()
+            .filter_map(ScopeReconciliation::describe)
+            .collect();
+        for note in &explanation {
+            tracing::debug!("{}", note);
+        }
 
         // Generate subject line
-        let subject = self.generate_subject(&analyzer, &context);
+        let subject = self.generate_subject(&analyzer, scope.as_deref());
 
         // Generate body
         let body = self.generate_body(&analyzer, max_bullets, include_files);
 
         // Calculate confidence
-        let confidence = self.calculate_confidence(&analyzer, &context);
+        let confidence = self.calculate_confidence(&analyzer, context, reconciliation.as_ref());
 
-        Ok(GeneratedMessage {
+        GeneratedMessage {
             commit_type,
             scope,
+            emoji: None,
             subject,
             body,
             confidence,
+            explanation,
+            footer: self.provenance_footer(provenance::HEURISTIC_MODEL),
+        }
+    }
+
+    /// `message` with the ticket from `branch` in the footer or subject,
+    /// as `[rules.ticket]` configures, and the type's gitmoji when
+    /// `ui.emoji_style` asks for one.
+    fn finish(&self, message: GeneratedMessage, branch: &str) -> GeneratedMessage {
+        let emoji = match self.config.ui.emoji_style {
+            EmojiStyle::Gitmoji => message.emoji.clone().or_else(|| {
+                gitmoji::for_type(&message.commit_type, &self.config).map(str::to_string)
+            }),
+            EmojiStyle::None => message.emoji.clone(),
+        };
+        let with_ticket = ticket::with_branch_ticket(
+            message.to_message(),
+            &self.config.rules.ticket,
+            Some(branch),
+        );
+        GeneratedMessage {
+            emoji,
+            subject: with_ticket.subject,
+            footer: with_ticket.footer,
+            ..message
+        }
+    }
+
+    /// The `Generated-by:` trailer, when provenance is enabled.
+    fn provenance_footer(&self, model: &str) -> Option<String> {
+        self.config.smart.provenance.then(|| {
+            format!(
+                "{}: {}",
+                provenance::GENERATED_BY_TRAILER,
+                provenance::generated_by(model)
+            )
         })
     }
 
-    /// Generate the subject line.
-    fn generate_subject(
-        &self,
-        analyzer: &SemanticAnalyzer,
-        _context: &RepositoryContext,
-    ) -> String {
-        let intent = analyzer.primary_intent();
-        let actions = analyzer.extract_actions();
+    /// The message an enabled plugin generates for `context`, if any.
+    ///
+    /// Plugin messages that aren't conventional are ignored in favor of
+    /// the built-in analysis.
+    fn plugin_message(&self, context: &RepositoryContext) -> Option<GeneratedMessage> {
+        let (plugin, message) = EnabledPlugins::load(&self.config)?.generate(context)?;
+        let parsed = match CommitMessage::parse_with(&message, &self.config) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                tracing::debug!("Ignoring message from plugin {}: {}", plugin, e);
+                return None;
+            }
+        };
+        let model = format!("{}{}", crate::plugins::CODE_PREFIX, plugin);
+        let footer = match (parsed.footer, self.provenance_footer(&model)) {
+            (Some(footer), Some(provenance)) => Some(format!("{}\n{}", footer, provenance)),
+            (footer, provenance) => footer.or(provenance),
+        };
+        Some(GeneratedMessage {
+            commit_type: parsed.commit_type,
+            scope: parsed.scope,
+            emoji: parsed.emoji,
+            subject: parsed.subject,
+            body: parsed.body,
+            confidence: 1.0,
+            explanation: vec![format!("Generated by the '{}' plugin", plugin)],
+            footer,
+        })
+    }
+
+    /// Generate the subject line in the configured style.
+    fn generate_subject(&self, analyzer: &SemanticAnalyzer, scope: Option<&str>) -> String {
+        let (verb, target) = self.subject_parts(analyzer);
+        let style = &self.config.smart;
+
+        let subject = render_subject(
+            style
+                .subject_template
+                .as_deref()
+                .unwrap_or(DEFAULT_SUBJECT_TEMPLATE),
+            &verb,
+            &target,
+            scope,
+        );
+        let subject = remove_forbidden(&subject, &style.forbid_words);
+        match style.max_subject_words {
+            Some(max) => limit_words(&subject, max),
+            None => subject,
+        }
+    }
+
+    /// Pick the verb and target the subject is built from.
+    fn subject_parts(&self, analyzer: &SemanticAnalyzer) -> (String, String) {
+        let actions = self.actions(analyzer);
 
         // Try to create a meaningful subject
         if !actions.is_empty() {
             // Use the first action as the base
             let first_action = &actions[0];
+            let verb = first_action.verb.imperative().to_string();
 
-            if actions.len() == 1 {
+            let target = match actions.len() {
                 // Single action: use it directly
-                format!("{} {}", first_action.verb.imperative(), first_action.target)
-            } else {
+                1 => first_action.target.clone(),
                 // Multiple actions: summarize
-                let verb = first_action.verb.imperative();
-                let target = if actions.len() == 2 {
-                    format!("{} and {}", first_action.target, actions[1].target)
-                } else {
-                    format!("{} and {} more", first_action.target, actions.len() - 1)
-                };
-                format!("{} {}", verb, target)
-            }
+                2 => format!("{} and {}", first_action.target, actions[1].target),
+                n => format!("{} and {} more", first_action.target, n - 1),
+            };
+            (verb, target)
         } else {
             // Fallback based on intent
             let areas = analyzer.affected_areas();
             let area = areas.first().map(|s| s.as_str()).unwrap_or("files");
 
-            match intent {
-                super::semantic::ChangeIntent::Feature => format!("add new {}", area),
-                super::semantic::ChangeIntent::BugFix => "fix issue".to_string(),
-                super::semantic::ChangeIntent::Documentation => "update documentation".to_string(),
-                super::semantic::ChangeIntent::Testing => "update tests".to_string(),
-                super::semantic::ChangeIntent::Refactoring => "refactor code".to_string(),
-                super::semantic::ChangeIntent::Configuration => "update configuration".to_string(),
-                super::semantic::ChangeIntent::Update => "update files".to_string(),
-            }
+            let (verb, target) = match analyzer.primary_intent() {
+                super::semantic::ChangeIntent::Feature => ("add", format!("new {}", area)),
+                super::semantic::ChangeIntent::BugFix => ("fix", "issue".to_string()),
+                super::semantic::ChangeIntent::Documentation => {
+                    ("update", "documentation".to_string())
+                }
+                super::semantic::ChangeIntent::Testing => ("update", "tests".to_string()),
+                super::semantic::ChangeIntent::Refactoring => ("refactor", "code".to_string()),
+                super::semantic::ChangeIntent::Configuration => {
+                    ("update", "configuration".to_string())
+                }
+                super::semantic::ChangeIntent::Update => ("update", "files".to_string()),
+            };
+            (verb.to_string(), target)
+        }
+    }
+
+    /// Extract actions, which name files, unless paths are redacted.
+    fn actions(&self, analyzer: &SemanticAnalyzer) -> Vec<super::semantic::ChangeAction> {
+        if self.config.ui.redact_paths {
+            Vec::new()
+        } else {
+            analyzer.extract_actions()
         }
     }
 
@@ -159,7 +329,11 @@ impl SmartCommit {
         max_bullets: usize,
         include_files: bool,
     ) -> Option<String> {
-        let actions = analyzer.extract_actions();
+        // Concise messages only carry the file list when asked for it
+        let actions = match self.config.smart.tone {
+            SmartTone::Concise => Vec::new(),
+            SmartTone::Detailed => self.actions(analyzer),
+        };
 
         if actions.is_empty() && !include_files {
             return None;
@@ -169,7 +343,10 @@ impl SmartCommit {
 
         // Add action bullet points
         for action in actions.iter().take(max_bullets) {
-            lines.push(action.as_bullet());
+            lines.push(remove_forbidden(
+                &action.as_bullet(),
+                &self.config.smart.forbid_words,
+            ));
         }
 
         // Add file list if requested
@@ -195,6 +372,7 @@ impl SmartCommit {
         &self,
         analyzer: &SemanticAnalyzer,
         context: &RepositoryContext,
+        reconciliation: Option<&ScopeReconciliation>,
     ) -> f64 {
         let mut score: f64 = 0.5; // Base score
 
@@ -213,9 +391,13 @@ impl SmartCommit {
             _ => {}
         }
 
-        // Higher confidence if scope is detected
-        if context.suggested_scope.is_some() {
-            score += 0.1;
+        // Higher confidence if scope is detected, less so if it had to be replaced
+        match reconciliation {
+            Some(ScopeReconciliation::Kept(_))
+            | Some(ScopeReconciliation::Normalized { .. })
+            | Some(ScopeReconciliation::Aliased { .. }) => score += 0.1,
+            Some(ScopeReconciliation::Nearest { .. }) => score += 0.05,
+            _ => {}
         }
 
         // Lower confidence for large diffs
@@ -244,19 +426,24 @@ impl SmartCommit {
         // Show header with styling
         term.write_line(&format!("  {}", style(&message.header()).green()))?;
 
-        if let Some(ref body) = message.body {
+        for section in message.body.iter().chain(&message.footer) {
             term.write_line("")?;
-            for line in body.lines() {
+            for line in section.lines() {
                 term.write_line(&format!("  {}", style(line).dim()))?;
             }
         }
 
         term.write_line("")?;
-        term.write_line(&format!(
-            "  {} Confidence: {:.0}%",
-            style("ℹ").blue(),
-            message.confidence * 100.0
-        ))?;
+        if self.show_confidence {
+            term.write_line(&format!(
+                "  {} Confidence: {:.0}%",
+                style("ℹ").blue(),
+                message.confidence * 100.0
+            ))?;
+            for note in &message.explanation {
+                term.write_line(&format!("    {}", style(note).dim()))?;
+            }
+        }
 
         // Allow editing
         let final_message = if allow_edit {
@@ -266,9 +453,9 @@ impl SmartCommit {
                 .interact()?;
 
             if wants_edit {
-                let edited = Editor::new()
-                    .edit(&message.format())
-                    .map_err(|e| crate::error::CkError::Ui(e.to_string()))?;
+                let edited = MessageEditor::from_config(&self.config)
+                    .with_instructions(MESSAGE_INSTRUCTIONS)
+                    .edit(&message.format())?;
 
                 edited.unwrap_or_else(|| message.format())
             } else {
@@ -296,7 +483,11 @@ impl SmartCommit {
                 final_message
             ))?;
         } else {
-            let sha = git::create_commit(&final_message, false)?;
+            let options = git::CommitOptions {
+                use_cli: self.config.git.use_cli,
+                ..Default::default()
+            };
+            let sha = git::create_commit_with_options(&final_message, false, &options)?;
             let short_sha = &sha[..7.min(sha.len())];
             let first_line = final_message.lines().next().unwrap_or("");
             term.write_line(&format!(
@@ -311,6 +502,69 @@ impl SmartCommit {
     }
 }
 
+/// Fill a subject template.
+///
+/// A placeholder with no value is dropped together with the literal word
+/// before it, so `"{target} in {scope}"` renders without a dangling `in`.
+fn render_subject(template: &str, verb: &str, target: &str, scope: Option<&str>) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous_literal = false;
+
+    for word in template.split_whitespace() {
+        if !word.contains('{') {
+            words.push(word.to_string());
+            previous_literal = true;
+            continue;
+        }
+
+        if word.contains("{scope}") && scope.is_none() {
+            if previous_literal {
+                words.pop();
+            }
+            previous_literal = false;
+            continue;
+        }
+
+        words.push(
+            word.replace("{verb}", verb)
+                .replace("{target}", target)
+                .replace("{scope}", scope.unwrap_or("")),
+        );
+        previous_literal = false;
+    }
+
+    words.join(" ")
+}
+
+/// Remove forbidden words, matching case-insensitively and ignoring
+/// surrounding punctuation.
+fn remove_forbidden(text: &str, forbid: &[String]) -> String {
+    if forbid.is_empty() {
+        return text.to_string();
+    }
+
+    text.split_whitespace()
+        .filter(|word| {
+            let bare = word.trim_matches(|c: char| !c.is_alphanumeric());
+            !forbid.iter().any(|f| f.eq_ignore_ascii_case(bare))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Keep at most `max` words, without ending on a connecting word.
+fn limit_words(text: &str, max: usize) -> String {
+    let mut words: Vec<&str> = text.split_whitespace().take(max.max(1)).collect();
+    while words.len() > 1
+        && words
+            .last()
+            .is_some_and(|w| DANGLING_WORDS.contains(&w.to_lowercase().as_str()))
+    {
+        words.pop();
+    }
+    words.join(" ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -320,9 +574,12 @@ mod tests {
         let msg = GeneratedMessage {
             commit_type: CommitType::Feat,
             scope: Some("core".to_string()),
+            emoji: None,
             subject: "add new feature".to_string(),
             body: Some("- add feature\n- update tests".to_string()),
             confidence: 0.8,
+            explanation: Vec::new(),
+            footer: None,
         };
 
         let formatted = msg.format();
@@ -335,11 +592,45 @@ mod tests {
         let msg = GeneratedMessage {
             commit_type: CommitType::Fix,
             scope: None,
+            emoji: None,
             subject: "fix bug".to_string(),
             body: None,
             confidence: 0.5,
+            explanation: Vec::new(),
+            footer: None,
         };
 
         assert_eq!(msg.header(), "fix: fix bug");
     }
+
+    #[test]
+    fn test_render_subject_template() {
+        let template = "{verb} {target} in {scope}";
+        assert_eq!(
+            render_subject(template, "add", "parser", Some("core")),
+            "add parser in core"
+        );
+        assert_eq!(
+            render_subject(template, "add", "parser", None),
+            "add parser"
+        );
+        assert_eq!(
+            render_subject(DEFAULT_SUBJECT_TEMPLATE, "fix", "issue", Some("core")),
+            "fix issue"
+        );
+    }
+
+    #[test]
+    fn test_forbid_and_limit_words() {
+        let forbid = vec!["misc".to_string(), "Stuff".to_string()];
+        assert_eq!(
+            remove_forbidden("update misc stuff, and docs", &forbid),
+            "update and docs"
+        );
+        assert_eq!(
+            limit_words("add parser and 3 more in core", 3),
+            "add parser"
+        );
+        assert_eq!(limit_words("add parser", 8), "add parser");
+    }
 }
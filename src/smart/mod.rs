@@ -3,8 +3,11 @@
 
 //! Smart commit generation module.
 
+mod explain;
 mod generator;
+pub mod provenance;
 mod semantic;
 
+pub use explain::Explanation;
 pub use generator::{GeneratedMessage, SmartCommit};
-pub use semantic::SemanticAnalyzer;
+pub use semantic::{ChangeIntent, SemanticAnalyzer};
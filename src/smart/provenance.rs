@@ -0,0 +1,53 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Provenance of generated messages.
+//!
+//! With `smart.provenance` enabled, generated messages carry a
+//! `Generated-by:` trailer naming ck and the model behind the message, so
+//! machine-authored commits can be counted and filtered later
+//! (`ck stats`, `git log --grep '^Generated-by:'`).
+
+/// Trailer token marking a generated message.
+pub const GENERATED_BY_TRAILER: &str = "Generated-by";
+
+/// Model name of the built-in diff analyzer.
+pub const HEURISTIC_MODEL: &str = "heuristic";
+
+/// Trailer value for a message generated by `model`.
+pub fn generated_by(model: &str) -> String {
+    format!("ck-smart v{} ({})", env!("CARGO_PKG_VERSION"), model)
+}
+
+/// The generator recorded in a message's `Generated-by:` trailer, if any.
+pub fn generator(message: &str) -> Option<String> {
+    let prefix = format!("{}:", GENERATED_BY_TRAILER.to_lowercase());
+    message.lines().rev().find_map(|line| {
+        let line = line.trim();
+        if line.to_lowercase().starts_with(&prefix) {
+            Some(line[prefix.len()..].trim().to_string()).filter(|value| !value.is_empty())
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_by_round_trip() {
+        let value = generated_by(HEURISTIC_MODEL);
+        assert!(value.starts_with("ck-smart v"));
+        assert!(value.ends_with("(heuristic)"));
+
+        let message = format!("feat: add export\n\n{}: {}", GENERATED_BY_TRAILER, value);
+        assert_eq!(generator(&message), Some(value));
+        assert_eq!(
+            generator("feat: add export\n\ngenerated-by: bot"),
+            Some("bot".to_string())
+        );
+        assert_eq!(generator("feat: add export"), None);
+    }
+}
@@ -0,0 +1,29 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `ck affected` over commits and staged changes.
+
+use super::{ck, project, transcript};
+
+#[test]
+fn affected_since_base() {
+    let repo = project();
+    repo.write("crates/core/Cargo.toml", "[package]\nname = \"core\"\n")
+        .write(
+            "crates/cli/Cargo.toml",
+            "[package]\nname = \"cli\"\n\n[dependencies]\ncore = { path = \"../core\" }\n",
+        )
+        .write("crates/docs/Cargo.toml", "[package]\nname = \"docs\"\n")
+        .commit("build: add the crates")
+        .write("crates/core/src/lib.rs", "pub fn core() {}\n")
+        .commit("feat(core): add core");
+
+    let assert = ck(&repo, &["affected", "--base", "HEAD~1"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+
+    repo.write("README.md", "# Parser\n\nNow in crates.\n")
+        .write("crates/docs/README.md", "# Docs\n")
+        .stage(&[]);
+    let assert = ck(&repo, &["--format", "json", "affected"]).success();
+    insta::assert_snapshot!("affected_staged_json", transcript(&repo, &assert));
+}
@@ -0,0 +1,147 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `ck check`.
+
+use super::{ck, project, transcript};
+
+#[test]
+fn check_valid_commit() {
+    let repo = project();
+    repo.write("src/lib.rs", "pub fn parse() -> bool { true }\n")
+        .commit("feat(parser): return whether parsing succeeded");
+
+    let assert = ck(&repo, &["check", "HEAD"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn check_range_with_violations() {
+    let repo = project();
+    repo.write("src/lib.rs", "pub fn parse() -> u8 { 0 }\n")
+        .commit("Added stuff.")
+        .write("src/lib.rs", "pub fn parse() -> u8 { 1 }\n")
+        .commit("fix: Handle empty input.");
+
+    let assert = ck(&repo, &["check", "HEAD~2..HEAD"]).failure();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn check_uses_repository_config() {
+    let repo = project();
+    repo.config("[rules]\nrequire_scope = true\n")
+        .commit("feat: add the config file");
+
+    let assert = ck(&repo, &["check", "HEAD"]).failure();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn check_range_caches_passes() {
+    let repo = project();
+    repo.write("src/lib.rs", "pub fn parse() -> u8 { 0 }\n")
+        .commit("feat(parser): return the parsed byte");
+
+    // Plain checks leave the notes alone
+    ck(&repo, &["check", "HEAD~1..HEAD"]).success();
+    assert!(repo.git(&["notes", "--ref=ck-checks", "list"]).is_empty());
+
+    ck(&repo, &["check", "HEAD~1..HEAD", "--cache"]).success();
+    assert!(repo
+        .git(&["notes", "--ref=ck-checks", "show", "HEAD"])
+        .contains("verdict: pass"));
+    ck(&repo, &["check", "HEAD~1..HEAD", "--cache"]).success();
+
+    // New rules invalidate the cached verdict
+    repo.config("[rules]\nrequire_body = true\n");
+    let assert = ck(&repo, &["check", "HEAD~1..HEAD", "--cache"]).failure();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn check_message_file() {
+    let repo = project();
+    repo.write(
+        "MSG",
+        "fix: Handle empty input.\n\n# Lines starting with '#' are ignored\n",
+    );
+
+    let assert = ck(&repo, &["check", "--message-file", "MSG"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn check_message_from_stdin() {
+    let repo = project();
+    let mut command = repo.command(env!("CARGO_BIN_EXE_ck"));
+    command.args(["--deterministic", "check", "--stdin"]);
+
+    let assert = assert_cmd::Command::from_std(command)
+        .write_stdin("Handle empty input\n")
+        .assert()
+        .failure();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn ci_validate_annotates_for_azure() {
+    let repo = project();
+    repo.git(&["update-ref", "refs/remotes/origin/main", "HEAD"]);
+    repo.write("src/lib.rs", "pub fn parse() -> u8 { 0 }\n")
+        .commit("fix: Handle empty input.");
+
+    // The pull request's commits are checked against origin/main
+    let mut command = repo.command(env!("CARGO_BIN_EXE_ck"));
+    command
+        .args(["--deterministic", "ci", "validate"])
+        .env("TF_BUILD", "True")
+        .env("SYSTEM_PULLREQUEST_TARGETBRANCH", "refs/heads/main");
+
+    let assert = assert_cmd::Command::from_std(command).assert().failure();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn check_with_config_sees_diff() {
+    let repo = project();
+    repo.write("src/lib.rs", "pub fn parse() -> u8 { 0 }\n")
+        .commit("perf(parser): skip whitespace faster")
+        .write(
+            "strict.toml",
+            "[rules.expressions.perf-bench]\n\
+             expr = 'commit.type != \"perf\" || diff.files.exists(f, f.path.startsWith(\"benches/\"))'\n\
+             message = \"perf commits must update a benchmark\"\n",
+        );
+
+    let assert = ck(
+        &repo,
+        &["check", "HEAD~1..HEAD", "--with-config", "strict.toml"],
+    )
+    .success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn check_with_config_in_shallow_clone() {
+    let origin = project();
+    for subject in [
+        "add the export command",
+        "add the import command",
+        "add the merge command",
+    ] {
+        origin.commit(&format!("feat: {}", subject));
+    }
+    let clone = super::FixtureRepo::new();
+    let url = format!("file://{}", origin.path().display());
+    clone.git(&["fetch", "--quiet", "--depth", "2", &url, "main"]);
+    clone.git(&["reset", "--quiet", "--hard", "FETCH_HEAD"]);
+    clone.write("strict.toml", "[rules]\nrequire_scope = true\n");
+
+    let assert = ck(
+        &clone,
+        &["check", "HEAD~3..HEAD", "--with-config", "strict.toml"],
+    )
+    .success();
+    insta::assert_snapshot!(transcript(&clone, &assert));
+}
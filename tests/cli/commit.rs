@@ -0,0 +1,170 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `ck commit` without prompts.
+
+use super::{ck, project, transcript};
+
+#[test]
+fn commit_dry_run() {
+    let repo = project();
+    repo.write("src/export.rs", "pub fn export() {}\n")
+        .stage(&[]);
+
+    let assert = ck(
+        &repo,
+        &[
+            "--dry-run",
+            "commit",
+            "-t",
+            "feat",
+            "-s",
+            "api",
+            "-m",
+            "add export",
+        ],
+    )
+    .success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+    assert_eq!(repo.git(&["log", "--format=%s"]), "chore: initial commit\n");
+}
+
+#[test]
+fn commit_creates_commit() {
+    let repo = project();
+    repo.write("src/lib.rs", "pub fn parse() -> Option<()> { None }\n")
+        .stage(&[]);
+
+    ck(&repo, &["commit", "-t", "fix", "-m", "handle timeouts"]).success();
+    assert_eq!(
+        repo.git(&["log", "-1", "--format=%s"]),
+        "fix: handle timeouts\n"
+    );
+}
+
+#[test]
+fn commit_gitmoji() {
+    let repo = project();
+    repo.config("[ui]\nemoji_style = \"gitmoji\"\n")
+        .write("src/lib.rs", "pub fn parse() -> Option<()> { None }\n")
+        .stage(&[]);
+
+    ck(&repo, &["commit", "-t", "fix", "-m", "handle timeouts"]).success();
+    assert_eq!(
+        repo.git(&["log", "-1", "--format=%s"]),
+        "fix: 🐛 handle timeouts\n"
+    );
+}
+
+#[test]
+fn commit_without_staged_changes() {
+    let repo = project();
+
+    let assert = ck(&repo, &["commit", "-t", "fix", "-m", "handle timeouts"]).failure();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn commit_requires_flags_without_prompts() {
+    let repo = project();
+    repo.write("src/lib.rs", "pub fn parse() -> u8 { 0 }\n")
+        .stage(&[]);
+
+    let assert = ck(&repo, &["commit", "-m", "handle timeouts"]).failure();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn commit_trailers() {
+    let repo = project();
+    repo.config("[rules]\nrequired_trailers = [\"Signed-off-by\"]\n")
+        .write("src/export.rs", "pub fn export() {}\n")
+        .stage(&[]);
+
+    let assert = ck(
+        &repo,
+        &[
+            "--dry-run",
+            "commit",
+            "-t",
+            "feat",
+            "-m",
+            "add export",
+            "--issue",
+            "42",
+            "--trailer",
+            "Co-authored-by=Ada <ada@example.com>",
+        ],
+    )
+    .success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn commit_with_template() {
+    let repo = project();
+    repo.config(
+        "[rules.ticket]\nbranch_patterns = ['^fix/([A-Z]+-[0-9]+)']\n\n\
+         [templates.change]\ntype = \"fix\"\nbody = \"Why:\\n\\nTesting:\\n\"\nfooter = \"Refs: {ticket}\"\nenforce = true\n",
+    )
+    .git(&["checkout", "-q", "-b", "fix/PARSE-12-timeouts"]);
+    repo.write("src/lib.rs", "pub fn parse() -> Option<()> { None }\n")
+        .stage(&[]);
+
+    let assert = ck(
+        &repo,
+        &[
+            "--dry-run",
+            "commit",
+            "--template",
+            "change",
+            "-m",
+            "handle timeouts",
+        ],
+    )
+    .failure();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+
+    let body = "Why: parsing hung on slow input\n\nTesting: added a timeout test";
+    let assert = ck(
+        &repo,
+        &[
+            "--dry-run",
+            "commit",
+            "--template",
+            "change",
+            "-m",
+            "handle timeouts",
+            "-b",
+            body,
+        ],
+    )
+    .success();
+    insta::assert_snapshot!("commit_with_template_filled", transcript(&repo, &assert));
+}
+
+#[test]
+fn commit_ticket_from_branch() {
+    let repo = project();
+    repo.config(
+        "[rules.ticket]\nbranch_patterns = ['^feature/([A-Z]+-[0-9]+)']\nrequired = true\n",
+    )
+    .git(&["checkout", "-q", "-b", "feature/PARSE-7-export"]);
+    repo.write("src/export.rs", "pub fn export() {}\n")
+        .stage(&[]);
+
+    let assert = ck(
+        &repo,
+        &["--dry-run", "commit", "-t", "feat", "-m", "add export"],
+    )
+    .success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+
+    repo.git(&["checkout", "-q", "-b", "export"]);
+    let assert = ck(
+        &repo,
+        &["--dry-run", "commit", "-t", "feat", "-m", "add export"],
+    )
+    .failure();
+    insta::assert_snapshot!("commit_ticket_missing", transcript(&repo, &assert));
+}
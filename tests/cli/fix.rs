@@ -0,0 +1,65 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `ck fix --auto` batch cleanups.
+
+use super::{ck, project, transcript};
+
+#[test]
+fn fix_auto_report() {
+    let repo = project();
+    repo.config("[rules]\ntype_aliases = { bug = \"fix\" }\n")
+        .commit("chore: add config");
+    let base = repo.head();
+    repo.write("src/a.rs", "pub fn a() {}\n")
+        .commit("feat: Added the a module.")
+        .write("src/b.rs", "pub fn b() {}\n")
+        .commit("bug: handle missing b")
+        .write("src/c.rs", "pub fn c() {}\n")
+        .commit("Update c");
+
+    let range = format!("{}..HEAD", base);
+    let assert = ck(
+        &repo,
+        &[
+            "fix",
+            "--range",
+            &range,
+            "--auto",
+            "--report",
+            "fix-report.json",
+        ],
+    )
+    .failure();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+    insta::assert_snapshot!(
+        "fix_auto_report_json",
+        repo.redact(&std::fs::read_to_string(repo.join("fix-report.json")).unwrap())
+    );
+    assert_eq!(
+        repo.git(&["log", "--format=%s", "-3"]),
+        "Update c\nfix: handle missing b\nfeat: add the a module\n"
+    );
+}
+
+#[test]
+fn fix_auto_refuses_published_commits() {
+    let repo = project();
+    repo.write("src/a.rs", "pub fn a() {}\n")
+        .commit("feat: Added the a module.");
+    let branch = repo.git(&["branch", "--show-current"]);
+    let remote = format!("refs/remotes/origin/{}", branch.trim());
+    repo.git(&["update-ref", &remote, "HEAD"]);
+    let head = repo.head();
+
+    let assert = ck(&repo, &["fix", "-n", "1", "--auto"]).failure();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+    assert_eq!(repo.head(), head);
+
+    let assert = ck(&repo, &["fix", "-n", "1", "--auto", "--force-rewrite"]).success();
+    insta::assert_snapshot!("fix_auto_force_rewrite", transcript(&repo, &assert));
+    assert_eq!(
+        repo.git(&["log", "--format=%s", "-1"]),
+        "feat: add the a module\n"
+    );
+}
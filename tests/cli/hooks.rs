@@ -0,0 +1,30 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `ck hooks`.
+
+use super::{ck, project, transcript};
+
+#[test]
+fn hooks_install_and_status() {
+    let repo = project();
+
+    let install = ck(&repo, &["hooks", "install"]).success();
+    insta::assert_snapshot!("install", transcript(&repo, &install));
+
+    let status = ck(&repo, &["hooks", "status"]).success();
+    insta::assert_snapshot!("status", transcript(&repo, &status));
+
+    let hook = std::fs::read_to_string(repo.join(".git/hooks/commit-msg")).unwrap();
+    insta::assert_snapshot!("commit_msg_hook", hook);
+}
+
+#[test]
+fn hooks_uninstall() {
+    let repo = project();
+    ck(&repo, &["hooks", "install"]).success();
+
+    let assert = ck(&repo, &["hooks", "uninstall"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+    assert!(!repo.join(".git/hooks/commit-msg").exists());
+}
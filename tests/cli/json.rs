@@ -0,0 +1,82 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `--format json` output.
+
+use super::{ck, project, transcript};
+
+#[test]
+fn json_check() {
+    let repo = project();
+    repo.commit("feat: Added export.");
+
+    let assert = ck(&repo, &["--format", "json", "check", "HEAD"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn json_review() {
+    let repo = project();
+    repo.write("tests/parse.rs", "#[test]\nfn parses() {}\n")
+        .commit("test: cover parsing");
+
+    let assert = ck(&repo, &["--format", "json", "review", "HEAD~1..HEAD"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn json_bump() {
+    let repo = project();
+    repo.git(&["tag", "v1.2.3"]);
+    repo.write("src/lib.rs", "pub fn parse() -> bool { true }\n")
+        .commit("feat(parser): report success");
+
+    let assert = ck(&repo, &["--format", "json", "bump"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn json_example() {
+    let repo = project();
+
+    let assert = ck(
+        &repo,
+        &["--format", "json", "example", "-t", "fix", "-n", "2"],
+    )
+    .success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn json_commit_dry_run() {
+    let repo = project();
+    repo.write("src/export.rs", "pub fn export() {}\n")
+        .stage(&[]);
+
+    let assert = ck(
+        &repo,
+        &[
+            "--format",
+            "json",
+            "--dry-run",
+            "commit",
+            "-t",
+            "feat",
+            "-s",
+            "api",
+            "-m",
+            "Add export",
+        ],
+    )
+    .success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn json_smart_dry_run() {
+    let repo = project();
+    repo.write("docs/guide.md", "# Guide\n").stage(&[]);
+
+    let assert = ck(&repo, &["--format", "json", "--dry-run", "smart"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
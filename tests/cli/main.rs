@@ -0,0 +1,47 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! End-to-end tests of the `ck` binary against fixture repositories.
+//!
+//! Output is compared with golden files in `snapshots/`. After an
+//! intended change, review and accept new output with `cargo insta review`.
+
+#[path = "../fixtures/mod.rs"]
+mod fixtures;
+
+mod affected;
+mod check;
+mod commit;
+mod fix;
+mod hooks;
+mod json;
+mod merge_check;
+mod plugin;
+mod scan;
+mod sign;
+mod smart;
+
+use assert_cmd::assert::{Assert, OutputAssertExt};
+use fixtures::FixtureRepo;
+
+/// Run ck in `repo` with reproducible output.
+fn ck(repo: &FixtureRepo, args: &[&str]) -> Assert {
+    repo.command(env!("CARGO_BIN_EXE_ck"))
+        .arg("--deterministic")
+        .args(args)
+        .assert()
+}
+
+/// Exit code, stdout and stderr of a run, with temporary paths redacted.
+fn transcript(repo: &FixtureRepo, assert: &Assert) -> String {
+    repo.redact(&fixtures::transcript(assert.get_output()))
+}
+
+/// A repository with one commit, ready for more changes.
+fn project() -> FixtureRepo {
+    let repo = FixtureRepo::new();
+    repo.write("README.md", "# Parser\n")
+        .write("src/lib.rs", "pub fn parse() {}\n")
+        .commit("chore: initial commit");
+    repo
+}
@@ -0,0 +1,43 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `ck merge-check`.
+
+use super::{ck, project, transcript};
+
+#[test]
+fn merge_check_squash_title() {
+    let repo = project();
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    repo.write("src/lib.rs", "pub fn parse() -> u8 { 0 }\n")
+        .commit("wip")
+        .write("src/lib.rs", "pub fn parse() -> u8 { 1 }\n")
+        .commit("fix typo");
+
+    // The commits never land, only the squashed pull request title does
+    let args = [
+        "merge-check",
+        "--target",
+        "main",
+        "--title",
+        "feat(parser): Return the parsed byte",
+        "--number",
+        "7",
+    ];
+    let assert = ck(&repo, &args).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn merge_check_squash_commits() {
+    let repo = project();
+    repo.config("[rules.queue]\nsquash_message = \"commits\"\nappend_number = false\n")
+        .commit("chore: configure the merge queue");
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    repo.write("src/lib.rs", "pub fn parse() -> u8 { 0 }\n")
+        .commit("Added stuff.");
+
+    // A single commit keeps its own message
+    let assert = ck(&repo, &["merge-check", "--target", "main"]).failure();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
@@ -0,0 +1,87 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `ck plugin`.
+
+use super::{ck, project, transcript};
+
+#[test]
+fn plugin_install_enable_and_list() {
+    let repo = project();
+    repo.write(
+        "dist/warn/plugin.toml",
+        "name = \"warn\"\nversion = \"1.0.0\"\nck_version = \">=0.1.0\"\npermissions = [\"env\"]\n",
+    )
+    .write("dist/warn/plugin.wasm", "(module)\n");
+
+    let assert = ck(&repo, &["plugin", "install", "dist/warn", "--enable"]).success();
+    let list = ck(&repo, &["plugin", "list"]).success();
+    insta::assert_snapshot!(format!(
+        "{}\n{}",
+        transcript(&repo, &assert),
+        transcript(&repo, &list)
+    ));
+}
+
+/// A plugin that warns unless its input contains `pattern`.
+fn expect_plugin(repo: &super::FixtureRepo, pattern: &str) {
+    let wat = format!(
+        r#"(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "{pattern}")
+  (data (i32.const 512) "{{\"warnings\":[\"pattern not found\"]}}")
+  (func (export "ck_alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "ck_plugin_validate") (param $ptr i32) (param $len i32) (result i64)
+    (local $i i32) (local $j i32)
+    (block $missing
+      (loop $scan
+        (br_if $missing
+          (i32.gt_u (i32.add (local.get $i) (i32.const {len})) (local.get $len)))
+        (local.set $j (i32.const 0))
+        (block $mismatch
+          (loop $compare
+            (br_if $mismatch
+              (i32.ne
+                (i32.load8_u (i32.add (local.get $ptr) (i32.add (local.get $i) (local.get $j))))
+                (i32.load8_u (local.get $j))))
+            (local.set $j (i32.add (local.get $j) (i32.const 1)))
+            (br_if $compare (i32.lt_u (local.get $j) (i32.const {len}))))
+          (return (i64.const 0)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $scan)))
+    (i64.or (i64.shl (i64.const 512) (i64.const 32)) (i64.const 34))))
+"#,
+        pattern = pattern.replace('"', "\\\""),
+        len = pattern.len(),
+    );
+    repo.write(
+        "dist/expect/plugin.toml",
+        "name = \"expect\"\nversion = \"1.0.0\"\nck_version = \">=0.1.0\"\n",
+    )
+    .write("dist/expect/plugin.wasm", &wat);
+    ck(repo, &["plugin", "install", "dist/expect", "--enable"]).success();
+}
+
+#[test]
+fn plugin_sees_commit_diff() {
+    let repo = project();
+    expect_plugin(&repo, r#""available":true"#);
+    repo.write("src/export.rs", "pub fn export() {}\n")
+        .commit("feat: add export");
+
+    let assert = ck(&repo, &["check", "HEAD"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn plugin_sees_changed_files() {
+    let repo = project();
+    expect_plugin(&repo, r#""path":"src/export.rs""#);
+    repo.write("src/export.rs", "pub fn export() {}\n")
+        .commit("feat: add export");
+    repo.write("src/lib.rs", "pub fn parse() -> bool { true }\n")
+        .commit("fix: report success");
+
+    let assert = ck(&repo, &["check", "HEAD~1"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
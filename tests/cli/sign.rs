@@ -0,0 +1,33 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `ck sign` for ranges that newly need signatures.
+
+use super::{ck, project, transcript};
+
+#[test]
+fn sign_dry_run_lists_own_unsigned_commits() {
+    let repo = project();
+    let base = repo.head();
+    repo.write("src/a.rs", "pub fn a() {}\n")
+        .commit("feat: add the a module")
+        .write("src/b.rs", "pub fn b() {}\n")
+        .stage(&[]);
+    repo.git(&[
+        "commit",
+        "-q",
+        "--author",
+        "Other Dev <other@example.com>",
+        "-m",
+        "fix: handle missing b",
+    ]);
+    let head = repo.head();
+
+    let range = format!("{}..HEAD", base);
+    let assert = ck(&repo, &["--dry-run", "sign", "--range", &range]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+    assert_eq!(repo.head(), head);
+
+    let assert = ck(&repo, &["sign", "--range", "HEAD~1"]).failure();
+    insta::assert_snapshot!("sign_needs_a_range", transcript(&repo, &assert));
+}
@@ -0,0 +1,43 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! `ck smart`.
+
+use super::{ck, project, transcript};
+
+#[test]
+fn smart_docs_change() {
+    let repo = project();
+    repo.write("docs/guide.md", "# Guide\n\nHow to parse.\n")
+        .stage(&[]);
+
+    let assert = ck(&repo, &["--dry-run", "smart"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn smart_scoped_source_change() {
+    let repo = project();
+    repo.write("src/parser/tokens.rs", "pub enum Token {}\n")
+        .write("src/parser/mod.rs", "pub mod tokens;\n")
+        .stage(&[]);
+
+    let assert = ck(&repo, &["--dry-run", "smart", "--include-files"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
+
+#[test]
+fn smart_scopes_bazel_target() {
+    let repo = project();
+    repo.config("[monorepo]\nbuild_system = \"bazel\"\n")
+        .write(
+            "svc/auth/BUILD.bazel",
+            "go_library(\n    name = \"tokens\",\n    srcs = [\"jwt.go\"],\n)\n",
+        )
+        .commit("build: add the auth package")
+        .write("svc/auth/jwt.go", "package auth\n")
+        .stage(&[]);
+
+    let assert = ck(&repo, &["--dry-run", "smart"]).success();
+    insta::assert_snapshot!(transcript(&repo, &assert));
+}
@@ -0,0 +1,182 @@
+// Author: Eshan Roy
+// SPDX-License-Identifier: MIT
+
+//! Fixture repositories for unit and end-to-end tests.
+//!
+//! A [`FixtureRepo`] is a throwaway git repository with fixed author and
+//! commit dates, so commit SHAs are the same on every run, and an isolated
+//! home directory, so the user's global git and ck configuration don't leak
+//! into the results.
+//!
+//! Plugin authors can use the same builder through the `testing` feature:
+//!
+//! ```no_run
+//! use ck::testing::FixtureRepo;
+//!
+//! let repo = FixtureRepo::new();
+//! repo.write("src/lib.rs", "pub fn answer() -> u32 { 42 }\n")
+//!     .commit("feat: add the answer");
+//! let output = repo.command("ck").args(["check", "HEAD"]).output().unwrap();
+//! assert!(output.status.success());
+//! ```
+
+// Each test binary uses a different part of the builder
+#![allow(dead_code)]
+
+use std::cell::Cell;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Timestamp of the first fixture commit: 2024-01-01T00:00:00Z.
+const EPOCH: u64 = 1_704_067_200;
+
+/// Name and email used for every fixture commit.
+const AUTHOR: (&str, &str) = ("Fixture Author", "fixture@example.com");
+
+/// A temporary git repository built up step by step.
+pub struct FixtureRepo {
+    dir: TempDir,
+    home: TempDir,
+    commits: Cell<u64>,
+}
+
+impl FixtureRepo {
+    /// An empty repository on branch `main`.
+    pub fn new() -> Self {
+        let repo = Self {
+            dir: TempDir::new().expect("create fixture directory"),
+            home: TempDir::new().expect("create fixture home"),
+            commits: Cell::new(0),
+        };
+        repo.git(&["init", "-q", "-b", "main"]);
+        repo.git(&["config", "user.name", AUTHOR.0]);
+        repo.git(&["config", "user.email", AUTHOR.1]);
+        repo.git(&["config", "commit.gpgsign", "false"]);
+        repo
+    }
+
+    /// Root of the working tree.
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Absolute path of `relative` in the working tree.
+    pub fn join(&self, relative: &str) -> PathBuf {
+        self.path().join(relative)
+    }
+
+    /// Write a file, creating parent directories.
+    pub fn write(&self, relative: &str, contents: &str) -> &Self {
+        let path = self.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("create fixture directories");
+        }
+        std::fs::write(&path, contents).expect("write fixture file");
+        self
+    }
+
+    /// Write `ck.toml` at the repository root.
+    pub fn config(&self, toml: &str) -> &Self {
+        self.write("ck.toml", toml)
+    }
+
+    /// Stage paths, or everything when `paths` is empty.
+    pub fn stage(&self, paths: &[&str]) -> &Self {
+        let mut args = vec!["add", "--"];
+        if paths.is_empty() {
+            args.push(".");
+        }
+        args.extend(paths);
+        self.git(&args);
+        self
+    }
+
+    /// Stage everything and commit it with `message`.
+    pub fn commit(&self, message: &str) -> &Self {
+        self.stage(&[]);
+        self.git(&["commit", "-q", "--allow-empty", "-m", message]);
+        self
+    }
+
+    /// Run git in the repository and return its stdout.
+    ///
+    /// Panics if git fails, with its stderr.
+    pub fn git(&self, args: &[&str]) -> String {
+        let output = self.command("git").args(args).output().expect("run git");
+        assert!(
+            output.status.success(),
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    /// SHA of HEAD.
+    pub fn head(&self) -> String {
+        self.git(&["rev-parse", "HEAD"]).trim().to_string()
+    }
+
+    /// A command run inside the repository with an isolated environment.
+    ///
+    /// Only `PATH` is inherited. Commits made by the command get the next
+    /// fixture date, so their SHAs are reproducible too.
+    pub fn command(&self, program: impl AsRef<OsStr>) -> Command {
+        let date = format!("{} +0000", EPOCH + 60 * self.commits.get());
+        self.commits.set(self.commits.get() + 1);
+
+        let mut command = Command::new(program);
+        command
+            .current_dir(self.path())
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", self.home.path())
+            .env("XDG_CONFIG_HOME", self.home.path().join(".config"))
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_AUTHOR_NAME", AUTHOR.0)
+            .env("GIT_AUTHOR_EMAIL", AUTHOR.1)
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_NAME", AUTHOR.0)
+            .env("GIT_COMMITTER_EMAIL", AUTHOR.1)
+            .env("GIT_COMMITTER_DATE", &date)
+            .env("TERM", "dumb");
+        command
+    }
+
+    /// Replace the repository and home paths in `output` with `[REPO]` and
+    /// `[HOME]`, and configuration hashes with `sha256:[HASH]`, so it can be
+    /// compared across runs and config changes.
+    pub fn redact(&self, output: &str) -> String {
+        let mut redacted = output.to_string();
+        for (dir, placeholder) in [(&self.dir, "[REPO]"), (&self.home, "[HOME]")] {
+            if let Ok(canonical) = dir.path().canonicalize() {
+                redacted = redacted.replace(&*canonical.to_string_lossy(), placeholder);
+            }
+            redacted = redacted.replace(&*dir.path().to_string_lossy(), placeholder);
+        }
+        let hash = regex::Regex::new("sha256:[0-9a-f]{64}").expect("valid hash pattern");
+        hash.replace_all(&redacted, "sha256:[HASH]").into_owned()
+    }
+}
+
+impl Default for FixtureRepo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stdout and stderr of a finished command, with its exit code, as one
+/// string for snapshots.
+pub fn transcript(output: &Output) -> String {
+    format!(
+        "exit: {}\n--- stdout\n{}--- stderr\n{}",
+        output
+            .status
+            .code()
+            .map_or("signal".to_string(), |code| code.to_string()),
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    )
+}